@@ -13,7 +13,7 @@
 use bevy::prelude::*;
 use crate::networking::OutgoingClientMessages;
 use shared::protocol::{ClientMessage, HotbarSlot};
-use crate::inventory_replication::ClientHotbar;  // Real hotbar from server InventoryUpdate
+use crate::inventory_replication::{ClientHotbar, ClientInventory};  // Real hotbar + inventory from server InventoryUpdate
 use crate::rbe_client_sync::GpuSimulationState;
 use crate::ui_utils::{spawn_cached_label, CachedLabelImage, LastRenderedText, LastRenderedColor, TextAtlasCache, SimpleBitmapFont, update_bevy_image_from_atlas};
 
@@ -402,15 +402,25 @@ pub fn update_item_tooltips(
     mut commands: Commands,
     mut tooltip_query: Query<(Entity, &mut ItemTooltip)>,
     slot_query: Query<(&InventorySlot, &Interaction, &GlobalTransform), Without<ItemTooltip>>,
+    inventory: Res<ClientInventory>,
 ) {
+    let items = shared::items::current();
     for (slot, interaction, transform) in slot_query.iter() {
         if *interaction == Interaction::Hovered {
             if tooltip_query.iter().count() == 0 {
                 let pos = transform.translation();
+                // Server-authoritative slot contents resolved through the shared item registry
+                let title = match inventory.slots.get(slot.index as usize) {
+                    Some(held) if held.count > 0 => match items.by_item_id(held.item_id) {
+                        Some(def) => format!("{} x{}", def.name, held.count),
+                        None => format!("Unknown item #{}", held.item_id),
+                    },
+                    _ => format!("Item #{}", slot.index),
+                };
                 commands.spawn((
                     TextBundle {
                         text: Text::from_section(
-                            format!("Item #{}\nResonance: 0.{}\nAbundance Flow: \u221e\nMercy Gate: Service + Truth\nEpiphany Progress: +{}%", slot.index, (slot.index % 7) + 2, (slot.index % 5) * 7),
+                            format!("{}\nResonance: 0.{}\nAbundance Flow: \u221e\nMercy Gate: Service + Truth\nEpiphany Progress: +{}%", title, (slot.index % 7) + 2, (slot.index % 5) * 7),
                             TextStyle { font_size: 11.0, color: Color::rgb(0.9, 0.95, 1.0), ..default() },
                         ),
                         style: Style {
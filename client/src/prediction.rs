@@ -55,7 +55,8 @@ pub enum PredictionSet {
     /// Runs core client prediction (local movement, input buffering).
     CorePrediction,
 
-    /// Performs rollback + reconciliation when authoritative corrections arrive.
+    /// Performs rollback + reconciliation when authoritative corrections arrive
+    /// (MoveCorrection: rewind to the server state, replay newer inputs).
     /// Must run after CorePrediction.
     Rollback,
 
@@ -0,0 +1,228 @@
+//! Challenge–response proof of key possession for the Powrush handshake.
+//!
+//! The server issues a random nonce; the client signs a domain-separated
+//! message binding that nonce to the server identity with its hybrid key.
+//! The server checks the hybrid signature and that the presented key is the
+//! currently active key of the DID. The verifier passes the DID document it has
+//! on record (if any): a presented document must extend that record, so a
+//! client cannot roll back past a rotation or revocation, and without a
+//! presented document the record itself decides, so a rotated-away genesis key
+//! stops working even when the client omits the document.
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+use crate::{create_did, verify_hybrid, DidDocument, HybridPublicKey, HybridSignature, RsilError, SovereignKeypair};
+
+/// Domain separator so a handshake signature can never be replayed as a
+/// signature over anything else (trades, saves, ...).
+pub const CHALLENGE_DOMAIN: &[u8] = b"powrush-rsil-auth-v1";
+
+pub const NONCE_LEN: usize = 32;
+
+/// Fresh random nonce from the OS CSPRNG.
+pub fn generate_nonce() -> [u8; NONCE_LEN] {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    nonce
+}
+
+/// The exact bytes a client signs: domain || len(server_id) || server_id || nonce.
+pub fn challenge_message(server_id: &str, nonce: &[u8]) -> Vec<u8> {
+    let mut msg = Vec::with_capacity(CHALLENGE_DOMAIN.len() + 8 + server_id.len() + nonce.len());
+    msg.extend_from_slice(CHALLENGE_DOMAIN);
+    msg.extend_from_slice(&(server_id.len() as u64).to_le_bytes());
+    msg.extend_from_slice(server_id.as_bytes());
+    msg.extend_from_slice(nonce);
+    msg
+}
+
+/// What the client sends back in reply to a challenge.
+#[derive(Debug, Clone)]
+pub struct ChallengeAnswer {
+    pub did: String,
+    /// Canonical hybrid public key bytes
+    pub public_key: Vec<u8>,
+    /// Canonical hybrid signature bytes
+    pub signature: Vec<u8>,
+    /// DID document JSON, required once the genesis key has been rotated
+    pub did_document: Option<String>,
+}
+
+/// Client side: sign the challenge with the sovereign keypair.
+/// Pass the DID document when `keypair` is not the identity's genesis key.
+pub fn answer_challenge(
+    keypair: &SovereignKeypair,
+    document: Option<&DidDocument>,
+    server_id: &str,
+    nonce: &[u8],
+) -> Result<ChallengeAnswer, RsilError> {
+    let signature = keypair.sign_hybrid(&challenge_message(server_id, nonce))?;
+    let (did, did_document) = match document {
+        Some(doc) => (doc.id.clone(), Some(doc.to_json()?)),
+        None => (keypair.did(), None),
+    };
+    Ok(ChallengeAnswer {
+        did,
+        public_key: keypair.hybrid_public_key().to_bytes(),
+        signature: signature.to_bytes(),
+        did_document,
+    })
+}
+
+/// Server side: check the key is authorized for the DID and the signature covers this nonce.
+///
+/// `known` is the verifier's recorded document for `did`, if it has one. On success
+/// returns the document now current for the identity (the presented one, the record,
+/// or a genesis document for a first-time identity), for the caller to record.
+pub fn verify_challenge_answer(
+    server_id: &str,
+    nonce: &[u8],
+    did: &str,
+    public_key: &[u8],
+    signature: &[u8],
+    did_document: Option<&str>,
+    known: Option<&DidDocument>,
+) -> Result<DidDocument, RsilError> {
+    let key = HybridPublicKey::from_bytes(public_key)?;
+
+    let current = match (did_document, known) {
+        (Some(json), known) => {
+            let doc = DidDocument::from_json(json)?;
+            if doc.id != did {
+                return Err(RsilError::DidMismatch);
+            }
+            if let Some(known) = known {
+                doc.extends(known)?;
+            }
+            doc
+        }
+        (None, Some(known)) => {
+            if known.id != did {
+                return Err(RsilError::DidMismatch);
+            }
+            known.clone()
+        }
+        (None, None) => {
+            if create_did(public_key) != did {
+                return Err(RsilError::DidMismatch);
+            }
+            DidDocument::from_genesis_key(&key)
+        }
+    };
+    current.authorizes(&key)?;
+
+    let signature = HybridSignature::from_bytes(signature)?;
+    verify_hybrid(&key, &challenge_message(server_id, nonce), &signature)?;
+    Ok(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answer_verifies_for_same_nonce() {
+        let keypair = SovereignKeypair::generate().unwrap();
+        let nonce = generate_nonce();
+        let answer = answer_challenge(&keypair, None, "shard-1", &nonce).unwrap();
+
+        assert!(verify_challenge_answer("shard-1", &nonce, &answer.did, &answer.public_key, &answer.signature, None, None).is_ok());
+    }
+
+    #[test]
+    fn answer_rejected_for_other_nonce_or_server() {
+        let keypair = SovereignKeypair::generate().unwrap();
+        let nonce = generate_nonce();
+        let answer = answer_challenge(&keypair, None, "shard-1", &nonce).unwrap();
+
+        let other = generate_nonce();
+        assert!(verify_challenge_answer("shard-1", &other, &answer.did, &answer.public_key, &answer.signature, None, None).is_err());
+        assert!(verify_challenge_answer("shard-2", &nonce, &answer.did, &answer.public_key, &answer.signature, None, None).is_err());
+    }
+
+    #[test]
+    fn did_must_match_key() {
+        let keypair = SovereignKeypair::generate().unwrap();
+        let impostor = SovereignKeypair::generate().unwrap();
+        let nonce = generate_nonce();
+        let answer = answer_challenge(&keypair, None, "shard-1", &nonce).unwrap();
+        let stolen_did = impostor.did();
+
+        assert!(matches!(
+            verify_challenge_answer("shard-1", &nonce, &stolen_did, &answer.public_key, &answer.signature, None, None),
+            Err(RsilError::DidMismatch)
+        ));
+    }
+
+    #[test]
+    fn rotated_key_authenticates_with_document() {
+        let genesis = SovereignKeypair::generate().unwrap();
+        let rotated = SovereignKeypair::generate().unwrap();
+        let mut doc = DidDocument::new(&genesis);
+        doc.rotate(&genesis, &rotated.hybrid_public_key(), 1).unwrap();
+
+        let nonce = generate_nonce();
+        let answer = answer_challenge(&rotated, Some(&doc), "shard-1", &nonce).unwrap();
+        assert_eq!(answer.did, genesis.did());
+        assert!(verify_challenge_answer(
+            "shard-1",
+            &nonce,
+            &answer.did,
+            &answer.public_key,
+            &answer.signature,
+            answer.did_document.as_deref(),
+            None
+        )
+        .is_ok());
+
+        // The retired genesis key no longer authenticates through the document.
+        let stale = answer_challenge(&genesis, Some(&doc), "shard-1", &nonce).unwrap();
+        assert!(verify_challenge_answer(
+            "shard-1",
+            &nonce,
+            &stale.did,
+            &stale.public_key,
+            &stale.signature,
+            stale.did_document.as_deref(),
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn record_outranks_what_the_client_presents() {
+        let genesis = SovereignKeypair::generate().unwrap();
+        let rotated = SovereignKeypair::generate().unwrap();
+        let genesis_doc = DidDocument::new(&genesis);
+        let mut record = genesis_doc.clone();
+        record.rotate(&genesis, &rotated.hybrid_public_key(), 1).unwrap();
+        let nonce = generate_nonce();
+
+        // Genesis key without a document: refused once the record shows a rotation.
+        let bare = answer_challenge(&genesis, None, "shard-1", &nonce).unwrap();
+        let verify = |a: &ChallengeAnswer, known: Option<&DidDocument>| {
+            verify_challenge_answer("shard-1", &nonce, &a.did, &a.public_key, &a.signature, a.did_document.as_deref(), known)
+        };
+        assert!(verify(&bare, None).is_ok());
+        assert!(matches!(verify(&bare, Some(&record)), Err(RsilError::Revoked)));
+
+        // Rolling the document back to genesis is refused.
+        let rolled_back = answer_challenge(&genesis, Some(&genesis_doc), "shard-1", &nonce).unwrap();
+        assert!(matches!(verify(&rolled_back, Some(&record)), Err(RsilError::StaleDidDocument)));
+
+        // The rotated key works with or without presenting the document.
+        let current = answer_challenge(&rotated, Some(&record), "shard-1", &nonce).unwrap();
+        assert_eq!(verify(&current, Some(&genesis_doc)).unwrap(), record);
+        let mut without_doc = current.clone();
+        without_doc.did_document = None;
+        assert_eq!(verify(&without_doc, Some(&record)).unwrap(), record);
+
+        // A later revocation on record locks the rotated key out too.
+        let mut revoked = record.clone();
+        let key_id = revoked.active_key_id().unwrap().to_string();
+        revoked.revoke(&rotated, &key_id, "device lost", 2).unwrap();
+        assert!(matches!(verify(&without_doc, Some(&revoked)), Err(RsilError::Revoked)));
+        assert!(matches!(verify(&current, Some(&revoked)), Err(RsilError::StaleDidDocument)));
+    }
+}
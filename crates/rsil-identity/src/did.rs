@@ -0,0 +1,343 @@
+//! DID documents for `did:powrush` identities.
+//!
+//! The DID is derived from the genesis hybrid key and never changes. The
+//! document lists every verification method the identity has used, which one
+//! currently authenticates, and a signed key-event log (rotations and
+//! revocations). Each event is signed by the key that was active when it was
+//! issued, so anyone holding the document can replay the chain from genesis.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{create_did, verify_hybrid, HybridPublicKey, HybridSignature, RsilError, SovereignKeypair};
+
+pub const DID_CONTEXT: &str = "https://www.w3.org/ns/did/v1";
+pub const VERIFICATION_METHOD_TYPE: &str = "PowrushHybridEd25519MlDsa65";
+
+const KEY_EVENT_DOMAIN: &[u8] = b"powrush-rsil-key-event-v1";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMethod {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub controller: String,
+    pub public_key_multibase: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum KeyEventKind {
+    #[serde(rename_all = "camelCase")]
+    Rotation { new_key_multibase: String },
+    #[serde(rename_all = "camelCase")]
+    Revocation { key_id: String, reason: String },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyEvent {
+    pub sequence: u64,
+    pub timestamp: u64,
+    pub kind: KeyEventKind,
+    /// Verification method id of the signing (then-active) key
+    pub signer: String,
+    /// Multibase (base58btc) of the hybrid signature bytes
+    pub signature: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocument {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    pub verification_method: Vec<VerificationMethod>,
+    /// Currently authorized verification method ids (empty once deactivated)
+    pub authentication: Vec<String>,
+    #[serde(default)]
+    pub key_events: Vec<KeyEvent>,
+    #[serde(default)]
+    pub deactivated: bool,
+}
+
+impl DidDocument {
+    /// Genesis document for a freshly generated keypair
+    pub fn new(keypair: &SovereignKeypair) -> Self {
+        let key = keypair.hybrid_public_key();
+        Self::genesis(&create_did(&key.to_bytes()), &key)
+    }
+
+    /// Genesis document for an identity known only by its public key
+    /// (what a verifier assumes before it has seen any key events).
+    pub fn from_genesis_key(key: &HybridPublicKey) -> Self {
+        Self::genesis(&create_did(&key.to_bytes()), key)
+    }
+
+    fn genesis(did: &str, key: &HybridPublicKey) -> Self {
+        let key_id = format!("{}#key-0", did);
+        Self {
+            context: vec![DID_CONTEXT.to_string()],
+            id: did.to_string(),
+            verification_method: vec![VerificationMethod {
+                id: key_id.clone(),
+                kind: VERIFICATION_METHOD_TYPE.to_string(),
+                controller: did.to_string(),
+                public_key_multibase: key.to_multibase(),
+                revoked_at: None,
+            }],
+            authentication: vec![key_id],
+            key_events: Vec::new(),
+            deactivated: false,
+        }
+    }
+
+    pub fn active_key_id(&self) -> Option<&str> {
+        self.authentication.first().map(String::as_str)
+    }
+
+    pub fn active_key(&self) -> Result<HybridPublicKey, RsilError> {
+        let id = self.active_key_id().ok_or(RsilError::Revoked)?;
+        let method = self
+            .verification_method
+            .iter()
+            .find(|m| m.id == id)
+            .ok_or_else(|| RsilError::InvalidDidDocument(format!("missing method {}", id)))?;
+        HybridPublicKey::from_multibase(&method.public_key_multibase)
+    }
+
+    /// Replace the active key with `next`, signed by `current`.
+    pub fn rotate(&mut self, current: &SovereignKeypair, next: &HybridPublicKey, timestamp: u64) -> Result<(), RsilError> {
+        self.issue(
+            current,
+            KeyEventKind::Rotation {
+                new_key_multibase: next.to_multibase(),
+            },
+            timestamp,
+        )
+    }
+
+    /// Revoke `key_id`, signed by `current`. Revoking the active key deactivates the DID.
+    pub fn revoke(&mut self, current: &SovereignKeypair, key_id: &str, reason: &str, timestamp: u64) -> Result<(), RsilError> {
+        self.issue(
+            current,
+            KeyEventKind::Revocation {
+                key_id: key_id.to_string(),
+                reason: reason.to_string(),
+            },
+            timestamp,
+        )
+    }
+
+    fn issue(&mut self, current: &SovereignKeypair, kind: KeyEventKind, timestamp: u64) -> Result<(), RsilError> {
+        if self.active_key()? != current.hybrid_public_key() {
+            return Err(RsilError::InvalidDidDocument("signer is not the active key".to_string()));
+        }
+        let mut event = KeyEvent {
+            sequence: self.key_events.len() as u64 + 1,
+            timestamp,
+            kind,
+            signer: self.active_key_id().unwrap_or_default().to_string(),
+            signature: String::new(),
+        };
+        let signature = current.sign_hybrid(&event_signing_bytes(&self.id, &event))?;
+        event.signature = format!("z{}", bs58::encode(signature.to_bytes()).into_string());
+
+        self.apply(&event)?;
+        self.key_events.push(event);
+        Ok(())
+    }
+
+    fn apply(&mut self, event: &KeyEvent) -> Result<(), RsilError> {
+        if self.deactivated {
+            return Err(RsilError::Revoked);
+        }
+        match &event.kind {
+            KeyEventKind::Rotation { new_key_multibase } => {
+                HybridPublicKey::from_multibase(new_key_multibase)?;
+                let key_id = format!("{}#key-{}", self.id, self.verification_method.len());
+                self.verification_method.push(VerificationMethod {
+                    id: key_id.clone(),
+                    kind: VERIFICATION_METHOD_TYPE.to_string(),
+                    controller: self.id.clone(),
+                    public_key_multibase: new_key_multibase.clone(),
+                    revoked_at: None,
+                });
+                self.authentication = vec![key_id];
+            }
+            KeyEventKind::Revocation { key_id, .. } => {
+                let method = self
+                    .verification_method
+                    .iter_mut()
+                    .find(|m| &m.id == key_id)
+                    .ok_or_else(|| RsilError::InvalidDidDocument(format!("unknown key {}", key_id)))?;
+                method.revoked_at = Some(event.timestamp);
+                self.authentication.retain(|id| id != key_id);
+                if self.authentication.is_empty() {
+                    self.deactivated = true;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replay the key-event chain from the genesis key and check the document
+    /// state matches it exactly.
+    pub fn verify(&self) -> Result<(), RsilError> {
+        let genesis = self
+            .verification_method
+            .first()
+            .ok_or_else(|| RsilError::InvalidDidDocument("no genesis key".to_string()))?;
+        let genesis_key = HybridPublicKey::from_multibase(&genesis.public_key_multibase)?;
+        if create_did(&genesis_key.to_bytes()) != self.id {
+            return Err(RsilError::DidMismatch);
+        }
+
+        let mut replay = Self::genesis(&self.id, &genesis_key);
+        replay.context = self.context.clone();
+        for (index, event) in self.key_events.iter().enumerate() {
+            if event.sequence != index as u64 + 1 {
+                return Err(RsilError::InvalidDidDocument(format!("event {} out of sequence", event.sequence)));
+            }
+            if Some(event.signer.as_str()) != replay.active_key_id() {
+                return Err(RsilError::InvalidDidDocument(format!("event {} signed by inactive key", event.sequence)));
+            }
+            let signer_key = replay.active_key()?;
+            let sig_bytes = event
+                .signature
+                .strip_prefix('z')
+                .and_then(|s| bs58::decode(s).into_vec().ok())
+                .ok_or(RsilError::VerificationFailed)?;
+            let signature = HybridSignature::from_bytes(&sig_bytes)?;
+            verify_hybrid(&signer_key, &event_signing_bytes(&self.id, event), &signature)?;
+            replay.apply(event)?;
+            replay.key_events.push(event.clone());
+        }
+
+        if replay != *self {
+            return Err(RsilError::InvalidDidDocument("state does not match key events".to_string()));
+        }
+        Ok(())
+    }
+
+    /// True when the chain verifies and `key` is the currently active key.
+    pub fn authorizes(&self, key: &HybridPublicKey) -> Result<(), RsilError> {
+        self.verify()?;
+        if &self.active_key()? != key {
+            return Err(RsilError::Revoked);
+        }
+        Ok(())
+    }
+
+    /// Check this document is `earlier` or a continuation of it: same DID and
+    /// every key event already on record, in order. Anything else is a rollback
+    /// or a fork and must not replace the recorded document.
+    pub fn extends(&self, earlier: &DidDocument) -> Result<(), RsilError> {
+        if self.id != earlier.id {
+            return Err(RsilError::DidMismatch);
+        }
+        if !self.key_events.starts_with(&earlier.key_events) {
+            return Err(RsilError::StaleDidDocument);
+        }
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> Result<String, RsilError> {
+        serde_json::to_string(self).map_err(|e| RsilError::InvalidDidDocument(e.to_string()))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, RsilError> {
+        serde_json::from_str(json).map_err(|e| RsilError::InvalidDidDocument(e.to_string()))
+    }
+}
+
+/// Bytes covered by a key-event signature (everything except the signature).
+fn event_signing_bytes(did: &str, event: &KeyEvent) -> Vec<u8> {
+    let kind = serde_json::to_vec(&event.kind).unwrap_or_default();
+    let mut out = Vec::with_capacity(KEY_EVENT_DOMAIN.len() + did.len() + event.signer.len() + kind.len() + 40);
+    out.extend_from_slice(KEY_EVENT_DOMAIN);
+    for field in [did.as_bytes(), event.signer.as_bytes(), kind.as_slice()] {
+        out.extend_from_slice(&(field.len() as u64).to_le_bytes());
+        out.extend_from_slice(field);
+    }
+    out.extend_from_slice(&event.sequence.to_le_bytes());
+    out.extend_from_slice(&event.timestamp.to_le_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn genesis_document_verifies() {
+        let keypair = SovereignKeypair::generate().unwrap();
+        let doc = DidDocument::new(&keypair);
+        assert_eq!(doc.id, keypair.did());
+        assert!(doc.authorizes(&keypair.hybrid_public_key()).is_ok());
+
+        let parsed = DidDocument::from_json(&doc.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, doc);
+    }
+
+    #[test]
+    fn rotation_keeps_did_and_moves_authority() {
+        let first = SovereignKeypair::generate().unwrap();
+        let second = SovereignKeypair::generate().unwrap();
+        let mut doc = DidDocument::new(&first);
+        doc.rotate(&first, &second.hybrid_public_key(), 100).unwrap();
+
+        assert_eq!(doc.id, first.did());
+        assert!(doc.authorizes(&second.hybrid_public_key()).is_ok());
+        assert!(doc.authorizes(&first.hybrid_public_key()).is_err());
+        // Old key can no longer issue events.
+        assert!(doc.rotate(&first, &first.hybrid_public_key(), 101).is_err());
+    }
+
+    #[test]
+    fn revoking_active_key_deactivates() {
+        let keypair = SovereignKeypair::generate().unwrap();
+        let mut doc = DidDocument::new(&keypair);
+        let key_id = doc.active_key_id().unwrap().to_string();
+        doc.revoke(&keypair, &key_id, "device lost", 50).unwrap();
+
+        assert!(doc.deactivated);
+        assert!(doc.verify().is_ok());
+        assert!(matches!(doc.authorizes(&keypair.hybrid_public_key()), Err(RsilError::Revoked)));
+    }
+
+    #[test]
+    fn rollback_and_fork_do_not_extend_the_record() {
+        let first = SovereignKeypair::generate().unwrap();
+        let second = SovereignKeypair::generate().unwrap();
+        let genesis = DidDocument::new(&first);
+        let mut rotated = genesis.clone();
+        rotated.rotate(&first, &second.hybrid_public_key(), 10).unwrap();
+
+        assert!(rotated.extends(&genesis).is_ok());
+        assert!(rotated.extends(&rotated).is_ok());
+        assert!(matches!(genesis.extends(&rotated), Err(RsilError::StaleDidDocument)));
+
+        // A different rotation from the same genesis is a fork.
+        let mut forked = genesis.clone();
+        forked.rotate(&first, &SovereignKeypair::generate().unwrap().hybrid_public_key(), 10).unwrap();
+        assert!(matches!(forked.extends(&rotated), Err(RsilError::StaleDidDocument)));
+        assert_eq!(DidDocument::from_genesis_key(&first.hybrid_public_key()), genesis);
+    }
+
+    #[test]
+    fn tampered_event_fails_verification() {
+        let first = SovereignKeypair::generate().unwrap();
+        let attacker = SovereignKeypair::generate().unwrap();
+        let mut doc = DidDocument::new(&first);
+        doc.rotate(&first, &SovereignKeypair::generate().unwrap().hybrid_public_key(), 10).unwrap();
+
+        // Swap the rotated-in key for the attacker's without re-signing.
+        let mb = attacker.hybrid_public_key().to_multibase();
+        doc.key_events[0].kind = KeyEventKind::Rotation { new_key_multibase: mb.clone() };
+        doc.verification_method[1].public_key_multibase = mb;
+        assert!(doc.verify().is_err());
+    }
+}
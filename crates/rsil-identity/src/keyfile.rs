@@ -0,0 +1,137 @@
+//! Passphrase-encrypted key files.
+//!
+//! Secret material (Ed25519 secret || ML-DSA seed) is sealed with
+//! ChaCha20-Poly1305 under an Argon2id-derived key. The DID and format
+//! version are bound as associated data, so a file cannot be relabelled.
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::{RsilError, SovereignKeypair};
+
+pub const KEY_FILE_VERSION: u32 = 1;
+const KDF_NAME: &str = "argon2id";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedKeyFile {
+    version: u32,
+    did: String,
+    kdf: String,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Export `keypair` as a JSON key file sealed under `passphrase`.
+pub fn export_encrypted(keypair: &SovereignKeypair, passphrase: &str) -> Result<String, RsilError> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let did = keypair.did();
+    let mut key = derive_file_key(passphrase, &salt)?;
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+    key.zeroize();
+
+    let mut material = keypair.secret_material();
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &material,
+                aad: &associated_data(&did),
+            },
+        )
+        .map_err(|_| RsilError::KeyFile("encryption failed".to_string()));
+    material.zeroize();
+
+    let file = EncryptedKeyFile {
+        version: KEY_FILE_VERSION,
+        did,
+        kdf: KDF_NAME.to_string(),
+        salt: hex::encode(salt),
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext?),
+    };
+    serde_json::to_string_pretty(&file).map_err(|e| RsilError::KeyFile(e.to_string()))
+}
+
+/// Import a key file produced by `export_encrypted`.
+pub fn import_encrypted(contents: &str, passphrase: &str) -> Result<SovereignKeypair, RsilError> {
+    let file: EncryptedKeyFile = serde_json::from_str(contents).map_err(|e| RsilError::KeyFile(e.to_string()))?;
+    if file.version != KEY_FILE_VERSION || file.kdf != KDF_NAME {
+        return Err(RsilError::KeyFile(format!("unsupported key file v{} ({})", file.version, file.kdf)));
+    }
+
+    let salt = hex::decode(&file.salt).map_err(|e| RsilError::KeyFile(e.to_string()))?;
+    let nonce = hex::decode(&file.nonce).map_err(|e| RsilError::KeyFile(e.to_string()))?;
+    let ciphertext = hex::decode(&file.ciphertext).map_err(|e| RsilError::KeyFile(e.to_string()))?;
+    if nonce.len() != NONCE_LEN {
+        return Err(RsilError::KeyFile("bad nonce length".to_string()));
+    }
+
+    let mut key = derive_file_key(passphrase, &salt)?;
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+    key.zeroize();
+
+    let mut material = cipher
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: &associated_data(&file.did),
+            },
+        )
+        .map_err(|_| RsilError::KeyFile("wrong passphrase or corrupted file".to_string()))?;
+
+    let keypair = SovereignKeypair::from_secret_material(&material);
+    material.zeroize();
+    let keypair = keypair?;
+
+    if keypair.did() != file.did {
+        return Err(RsilError::DidMismatch);
+    }
+    Ok(keypair)
+}
+
+fn derive_file_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], RsilError> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| RsilError::KeyFile(e.to_string()))?;
+    Ok(key)
+}
+
+fn associated_data(did: &str) -> Vec<u8> {
+    let mut aad = KEY_FILE_VERSION.to_le_bytes().to_vec();
+    aad.extend_from_slice(did.as_bytes());
+    aad
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_import_roundtrip() {
+        let keypair = SovereignKeypair::generate().unwrap();
+        let file = export_encrypted(&keypair, "mercy flows").unwrap();
+        let restored = import_encrypted(&file, "mercy flows").unwrap();
+        assert_eq!(restored.hybrid_public_key(), keypair.hybrid_public_key());
+    }
+
+    #[test]
+    fn wrong_passphrase_fails() {
+        let keypair = SovereignKeypair::generate().unwrap();
+        let file = export_encrypted(&keypair, "mercy flows").unwrap();
+        assert!(matches!(import_encrypted(&file, "scarcity"), Err(RsilError::KeyFile(_))));
+    }
+}
@@ -3,10 +3,40 @@
 //!
 //! This crate provides the core cryptographic primitives for player identity.
 //! It is designed to be used by both the native client and sovereign servers.
+//!
+//! Key model: every identity is a hybrid Ed25519 + ML-DSA-65 (FIPS 204) keypair.
+//! Hybrid signatures only verify when BOTH components verify, so a break of
+//! either scheme alone does not forge identity, trades or saves.
 
 use ed25519_dalek::{SigningKey, VerifyingKey, Signature, Signer, Verifier};
+use mysten_mldsa_native_rs as mldsa;
 use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::fmt;
+use zeroize::Zeroize;
+
+pub mod challenge;
+pub mod did;
+pub mod keyfile;
+
+pub use challenge::{answer_challenge, generate_nonce, verify_challenge_answer, ChallengeAnswer};
+pub use did::{DidDocument, KeyEvent, KeyEventKind, VerificationMethod};
+pub use keyfile::{export_encrypted, import_encrypted};
+
+/// ML-DSA context string (FIPS 204 domain separation) for every RSIL signature.
+pub const ML_DSA_CONTEXT: &[u8] = b"powrush-rsil";
+
+pub const ED25519_PUBLIC_KEY_LEN: usize = 32;
+pub const ML_DSA_PUBLIC_KEY_LEN: usize = mldsa::PUBLIC_KEY_LENGTH;
+pub const HYBRID_PUBLIC_KEY_LEN: usize = ED25519_PUBLIC_KEY_LEN + ML_DSA_PUBLIC_KEY_LEN;
+pub const ED25519_SIGNATURE_LEN: usize = 64;
+pub const ML_DSA_SIGNATURE_LEN: usize = mldsa::SIGNATURE_LENGTH;
+pub const HYBRID_SIGNATURE_LEN: usize = ED25519_SIGNATURE_LEN + ML_DSA_SIGNATURE_LEN;
+
+/// Secret material length: Ed25519 secret (32) || ML-DSA seed (32)
+pub const SECRET_MATERIAL_LEN: usize = 32 + mldsa::SEED_LENGTH;
 
 /// RSIL Error types
 #[derive(Debug)]
@@ -15,61 +45,220 @@ pub enum RsilError {
     InvalidPublicKey,
     SigningFailed,
     VerificationFailed,
+    /// Presented DID does not derive from the presented public key
+    DidMismatch,
+    /// DID document key-event chain is malformed or badly signed
+    InvalidDidDocument(String),
+    /// Key or identity has been revoked
+    Revoked,
+    /// Presented DID document is older than, or diverges from, the one on record
+    StaleDidDocument,
+    /// Encrypted key file could not be parsed or decrypted
+    KeyFile(String),
 }
 
 impl fmt::Display for RsilError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            RsilError::KeyGenerationFailed => write!(f, "Failed to generate ML-DSA keypair"),
+            RsilError::KeyGenerationFailed => write!(f, "Failed to generate hybrid Ed25519 + ML-DSA keypair"),
             RsilError::InvalidPublicKey => write!(f, "Invalid public key"),
             RsilError::SigningFailed => write!(f, "Failed to sign message"),
             RsilError::VerificationFailed => write!(f, "Signature verification failed"),
+            RsilError::DidMismatch => write!(f, "DID does not match public key"),
+            RsilError::InvalidDidDocument(reason) => write!(f, "Invalid DID document: {}", reason),
+            RsilError::Revoked => write!(f, "Key has been revoked"),
+            RsilError::StaleDidDocument => write!(f, "DID document does not extend the one on record"),
+            RsilError::KeyFile(reason) => write!(f, "Key file error: {}", reason),
         }
     }
 }
 
-/// A sovereign identity keypair (ML-DSA based)
+impl std::error::Error for RsilError {}
+
+/// Hybrid public key: Ed25519 (32 bytes) + ML-DSA-65 (1952 bytes)
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HybridPublicKey {
+    pub ed25519: [u8; ED25519_PUBLIC_KEY_LEN],
+    pub ml_dsa: Vec<u8>,
+}
+
+impl HybridPublicKey {
+    /// Canonical encoding: ed25519 || ml_dsa
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HYBRID_PUBLIC_KEY_LEN);
+        out.extend_from_slice(&self.ed25519);
+        out.extend_from_slice(&self.ml_dsa);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RsilError> {
+        if bytes.len() != HYBRID_PUBLIC_KEY_LEN {
+            return Err(RsilError::InvalidPublicKey);
+        }
+        let (ed, pq) = bytes.split_at(ED25519_PUBLIC_KEY_LEN);
+        let key = Self {
+            ed25519: ed.try_into().map_err(|_| RsilError::InvalidPublicKey)?,
+            ml_dsa: pq.to_vec(),
+        };
+        // Reject keys either backend cannot parse.
+        VerifyingKey::from_bytes(&key.ed25519).map_err(|_| RsilError::InvalidPublicKey)?;
+        mldsa::VerifyingKey::from_bytes(&key.ml_dsa).map_err(|_| RsilError::InvalidPublicKey)?;
+        Ok(key)
+    }
+
+    /// Multibase (base58btc, `z` prefix) of the canonical encoding
+    pub fn to_multibase(&self) -> String {
+        format!("z{}", bs58::encode(self.to_bytes()).into_string())
+    }
+
+    pub fn from_multibase(encoded: &str) -> Result<Self, RsilError> {
+        let body = encoded.strip_prefix('z').ok_or(RsilError::InvalidPublicKey)?;
+        let bytes = bs58::decode(body).into_vec().map_err(|_| RsilError::InvalidPublicKey)?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl fmt::Debug for HybridPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HybridPublicKey({})", create_did(&self.to_bytes()))
+    }
+}
+
+/// Hybrid signature: Ed25519 (64 bytes) + ML-DSA-65 (3309 bytes)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HybridSignature {
+    pub ed25519: Vec<u8>,
+    pub ml_dsa: Vec<u8>,
+}
+
+impl HybridSignature {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HYBRID_SIGNATURE_LEN);
+        out.extend_from_slice(&self.ed25519);
+        out.extend_from_slice(&self.ml_dsa);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RsilError> {
+        if bytes.len() != HYBRID_SIGNATURE_LEN {
+            return Err(RsilError::VerificationFailed);
+        }
+        let (ed, pq) = bytes.split_at(ED25519_SIGNATURE_LEN);
+        Ok(Self {
+            ed25519: ed.to_vec(),
+            ml_dsa: pq.to_vec(),
+        })
+    }
+}
+
+/// A sovereign identity keypair (hybrid Ed25519 + ML-DSA-65)
 pub struct SovereignKeypair {
+    /// Classical component (kept public for legacy Ed25519-only callers)
     pub verifying_key: VerifyingKey,
     signing_key: SigningKey,
+    ml_dsa_seed: mldsa::SigningKeySeed,
+    ml_dsa_signing_key: mldsa::SigningKey,
+    ml_dsa_verifying_key: mldsa::VerifyingKey,
 }
 
 impl SovereignKeypair {
-    /// Generate a new post-quantum resistant keypair
+    /// Generate a new hybrid post-quantum keypair from the OS CSPRNG
     pub fn generate() -> Result<Self, RsilError> {
-        // NOTE: In production, replace with actual ML-DSA (Dilithium) implementation
-        // This is a placeholder using ed25519 for skeleton purposes.
-        // Real implementation will use pq-dilithium or similar.
-        let mut csprng = OsRng;
-        let signing_key = SigningKey::generate(&mut csprng);
-        let verifying_key = signing_key.verifying_key();
+        let mut material = [0u8; SECRET_MATERIAL_LEN];
+        OsRng
+            .try_fill_bytes(&mut material)
+            .map_err(|_| RsilError::KeyGenerationFailed)?;
+        let keypair = Self::from_secret_material(&material);
+        material.zeroize();
+        keypair
+    }
+
+    /// Rebuild from `secret_material()` (Ed25519 secret || ML-DSA seed)
+    pub fn from_secret_material(material: &[u8]) -> Result<Self, RsilError> {
+        if material.len() != SECRET_MATERIAL_LEN {
+            return Err(RsilError::KeyGenerationFailed);
+        }
+        let (ed, pq) = material.split_at(32);
+        let ed_secret: [u8; 32] = ed.try_into().map_err(|_| RsilError::KeyGenerationFailed)?;
+        let signing_key = SigningKey::from_bytes(&ed_secret);
+        let ml_dsa_seed = mldsa::SigningKeySeed::from_bytes(pq).map_err(|_| RsilError::KeyGenerationFailed)?;
+        let (ml_dsa_signing_key, ml_dsa_verifying_key) = ml_dsa_seed.expand();
 
         Ok(Self {
-            verifying_key,
+            verifying_key: signing_key.verifying_key(),
             signing_key,
+            ml_dsa_seed,
+            ml_dsa_signing_key,
+            ml_dsa_verifying_key,
         })
     }
 
-    /// Sign a message (challenge) with the private key
+    /// Raw secret material for encrypted export. Caller must zeroize.
+    pub fn secret_material(&self) -> [u8; SECRET_MATERIAL_LEN] {
+        let mut out = [0u8; SECRET_MATERIAL_LEN];
+        out[..32].copy_from_slice(&self.signing_key.to_bytes());
+        out[32..].copy_from_slice(self.ml_dsa_seed.as_bytes());
+        out
+    }
+
+    /// Sign a message with the classical Ed25519 component only
     pub fn sign(&self, message: &[u8]) -> Result<Signature, RsilError> {
         Ok(self.signing_key.sign(message))
     }
 
-    /// Get the public key as bytes
+    /// Sign a message with both schemes (hedged ML-DSA signing)
+    pub fn sign_hybrid(&self, message: &[u8]) -> Result<HybridSignature, RsilError> {
+        let mut rnd = [0u8; mldsa::RND_LENGTH];
+        OsRng.try_fill_bytes(&mut rnd).map_err(|_| RsilError::SigningFailed)?;
+        let pq = self
+            .ml_dsa_signing_key
+            .sign(message, ML_DSA_CONTEXT, &rnd)
+            .map_err(|_| RsilError::SigningFailed)?;
+
+        Ok(HybridSignature {
+            ed25519: self.signing_key.sign(message).to_bytes().to_vec(),
+            ml_dsa: pq.as_bytes().to_vec(),
+        })
+    }
+
+    /// Get the classical public key as bytes
     pub fn public_key_bytes(&self) -> [u8; 32] {
         self.verifying_key.to_bytes()
     }
+
+    pub fn hybrid_public_key(&self) -> HybridPublicKey {
+        HybridPublicKey {
+            ed25519: self.verifying_key.to_bytes(),
+            ml_dsa: self.ml_dsa_verifying_key.as_bytes().to_vec(),
+        }
+    }
+
+    /// Genesis DID of this keypair
+    pub fn did(&self) -> String {
+        create_did(&self.hybrid_public_key().to_bytes())
+    }
+
+    /// Derive a 32-byte symmetric key bound to this identity (e.g. save encryption).
+    /// Distinct `context` values yield independent keys.
+    pub fn derive_symmetric_key(&self, context: &[u8]) -> [u8; 32] {
+        let mut material = self.secret_material();
+        let hk = hkdf::Hkdf::<Sha256>::new(Some(b"powrush-rsil-kdf-v1"), &material);
+        let mut key = [0u8; 32];
+        hk.expand(context, &mut key).expect("32 bytes is a valid HKDF-SHA256 output length");
+        material.zeroize();
+        key
+    }
 }
 
-/// Create a Decentralized Identifier (DID) from a public key
-/// Format: did:powrush:<base58 or hex of public key>
+/// Create a Decentralized Identifier (DID) from the genesis public key bytes.
+/// Format: did:powrush:z<base58btc(multihash sha2-256(public key))>
 pub fn create_did(public_key: &[u8]) -> String {
-    // In production, use proper multibase / base58btc encoding
-    let hex = hex::encode(public_key);
-    format!("did:powrush:{}", hex)
+    let mut multihash = vec![0x12, 0x20];
+    multihash.extend_from_slice(&Sha256::digest(public_key));
+    format!("did:powrush:z{}", bs58::encode(multihash).into_string())
 }
 
-/// Verify a signature
+/// Verify a classical Ed25519 signature
 pub fn verify_signature(
     public_key: &VerifyingKey,
     message: &[u8],
@@ -81,6 +270,27 @@ pub fn verify_signature(
         .map_err(|_| RsilError::VerificationFailed)
 }
 
+/// Verify a hybrid signature. Both components must verify.
+pub fn verify_hybrid(
+    public_key: &HybridPublicKey,
+    message: &[u8],
+    signature: &HybridSignature,
+) -> Result<(), RsilError> {
+    let ed_key = VerifyingKey::from_bytes(&public_key.ed25519).map_err(|_| RsilError::InvalidPublicKey)?;
+    let ed_sig_bytes: [u8; 64] = signature
+        .ed25519
+        .as_slice()
+        .try_into()
+        .map_err(|_| RsilError::VerificationFailed)?;
+    verify_signature(&ed_key, message, &Signature::from_bytes(&ed_sig_bytes))?;
+
+    let pq_key = mldsa::VerifyingKey::from_bytes(&public_key.ml_dsa).map_err(|_| RsilError::InvalidPublicKey)?;
+    let pq_sig = mldsa::Signature::from_bytes(&signature.ml_dsa).map_err(|_| RsilError::VerificationFailed)?;
+    pq_key
+        .verify(message, ML_DSA_CONTEXT, &pq_sig)
+        .map_err(|_| RsilError::VerificationFailed)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -98,7 +308,45 @@ mod tests {
     #[test]
     fn test_did_creation() {
         let keypair = SovereignKeypair::generate().unwrap();
-        let did = create_did(&keypair.public_key_bytes());
-        assert!(did.starts_with("did:powrush:"));
+        let did = create_did(&keypair.hybrid_public_key().to_bytes());
+        assert!(did.starts_with("did:powrush:z"));
+        assert_eq!(did, keypair.did());
+    }
+
+    #[test]
+    fn test_hybrid_sign_verify() {
+        let keypair = SovereignKeypair::generate().unwrap();
+        let message = b"hybrid lattice";
+        let sig = keypair.sign_hybrid(message).unwrap();
+        let pk = keypair.hybrid_public_key();
+
+        assert!(verify_hybrid(&pk, message, &sig).is_ok());
+        assert!(verify_hybrid(&pk, b"other", &sig).is_err());
+    }
+
+    #[test]
+    fn test_hybrid_requires_both_components() {
+        let a = SovereignKeypair::generate().unwrap();
+        let b = SovereignKeypair::generate().unwrap();
+        let message = b"split";
+        let mut sig = a.sign_hybrid(message).unwrap();
+        sig.ml_dsa = b.sign_hybrid(message).unwrap().ml_dsa;
+
+        assert!(verify_hybrid(&a.hybrid_public_key(), message, &sig).is_err());
+    }
+
+    #[test]
+    fn test_secret_material_roundtrip() {
+        let keypair = SovereignKeypair::generate().unwrap();
+        let restored = SovereignKeypair::from_secret_material(&keypair.secret_material()).unwrap();
+        assert_eq!(restored.hybrid_public_key(), keypair.hybrid_public_key());
+        assert_eq!(restored.derive_symmetric_key(b"save"), keypair.derive_symmetric_key(b"save"));
+        assert_ne!(keypair.derive_symmetric_key(b"save"), keypair.derive_symmetric_key(b"trade"));
+    }
+
+    #[test]
+    fn test_public_key_multibase_roundtrip() {
+        let pk = SovereignKeypair::generate().unwrap().hybrid_public_key();
+        assert_eq!(HybridPublicKey::from_multibase(&pk.to_multibase()).unwrap(), pk);
     }
 }
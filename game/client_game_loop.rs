@@ -2,19 +2,29 @@
 //! Full production-grade Client Game Loop v15.2
 //! Integrated with Networking Transport Layer v1 (ClientWsTransport)
 //! Client-side prediction + full input replay reconciliation + Hermite/Slerp smoothing
+//! v15.3 — Fixed-tick MoveCommand (sequence + tick) and rewind/replay on the server's
+//! authoritative MoveCorrection (server/src/movement.rs); follows PredictionSet's
+//! CorePrediction → Rollback → Visuals order within one update.
+//! v15.4 — UseAbility stamped with the rendered snapshot's server_tick so the server can
+//! validate targeting against what this client saw; AbilityRejected surfaced.
+//! v15.5 — StatusEffects kept alongside the snapshot buffer for buff / debuff timers.
+//! v15.6 — ChunkSnapshot kept in a versioned chunk cache (resource nodes, structures).
 //! Ra-Thor + PATSAGi Councils aligned | 7 Living Mercy Gates enforced | ONE Organism
 
 use std::collections::VecDeque;
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use glam::{Quat, Vec3};
 use tokio::sync::mpsc;
-use shared::protocol::{ClientMessage, ServerMessage, Vec3Ser, EntitySnapshot};
+use shared::protocol::{ClientMessage, ServerMessage, Vec3Ser, MOVE_TICK_HZ};
 
 // Assume these exist in crate::network or will be wired
+use crate::network::chunk_cache::ClientChunkCache;
+use crate::network::entity_snapshots::ClientSnapshotBuffer;
 use crate::network::message_framing::decode_frame;
 use crate::network::delta_compression::DeltaCompressor;
 
-const TICK_RATE: u64 = 60;
+const TICK_RATE: u64 = MOVE_TICK_HZ as u64;
+const MOVE_DT: f32 = 1.0 / MOVE_TICK_HZ as f32;
 const BUFFER_SIZE: usize = 128;
 const RECONCILIATION_SMOOTHING: f32 = 0.15; // For smooth correction (Hermite-like lerp factor)
 
@@ -27,8 +37,10 @@ pub struct ClientState {
 
 #[derive(Clone)]
 pub struct ClientInput {
+    /// Assigned by the loop per fixed movement tick
     pub sequence: u32,
     pub timestamp: u64,
+    /// Desired velocity (units/s)
     pub movement: Vec3,
     pub rotation_delta: Quat,
 }
@@ -42,8 +54,18 @@ pub struct ClientGameLoop {
     last_server_sequence: u32,
     transport_tx: Option<mpsc::UnboundedSender<ClientMessage>>,
     last_correction_time: Instant,
+    /// Last MoveCommand sequence the server has confirmed via MoveCorrection
+    last_acked_sequence: u32,
+    next_sequence: u32,
+    tick: u64,
+    tick_accumulator: f32,
+    /// Rendered minus simulated position after a correction; decays to zero
+    visual_offset: Vec3,
     // For interest: current player_id from handshake
     pub player_id: Option<u64>,
+    snapshots: ClientSnapshotBuffer,
+    pub chunks: ClientChunkCache,
+    next_ability_request: u32,
 }
 
 impl ClientGameLoop {
@@ -57,7 +79,15 @@ impl ClientGameLoop {
             last_server_sequence: 0,
             transport_tx: None,
             last_correction_time: Instant::now(),
+            last_acked_sequence: 0,
+            next_sequence: 1,
+            tick: 0,
+            tick_accumulator: 0.0,
+            visual_offset: Vec3::ZERO,
             player_id: None,
+            snapshots: ClientSnapshotBuffer::default(),
+            chunks: ClientChunkCache::default(),
+            next_ability_request: 1,
         }
     }
 
@@ -71,61 +101,101 @@ impl ClientGameLoop {
         self.player_id = Some(id);
     }
 
-    /// Core prediction step + send input over transport
+    /// Core prediction step + send input over transport. Movement advances in fixed
+    /// 1 / MOVE_TICK_HZ steps (one MoveCommand each) so the server can replay it exactly.
     pub fn update(&mut self, dt: f32, input: ClientInput) {
-        // Client-side prediction (authoritative feel, zero perceived latency)
-        self.predicted_state.position += input.movement * dt;
         self.predicted_state.rotation = self.predicted_state.rotation * input.rotation_delta;
         self.predicted_state.rotation = self.predicted_state.rotation.normalize();
-        if let Some(vel) = input.movement.try_normalize() {
-            self.predicted_state.velocity = vel * input.movement.length() / dt.max(0.001);
-        }
 
-        self.input_buffer.push_back(input.clone());
-        if self.input_buffer.len() > BUFFER_SIZE {
-            self.input_buffer.pop_front();
+        self.tick_accumulator = (self.tick_accumulator + dt).min(MOVE_DT * BUFFER_SIZE as f32);
+        while self.tick_accumulator >= MOVE_DT {
+            self.tick_accumulator -= MOVE_DT;
+            self.step(&input);
         }
 
-        // Send to server via transport (production wiring)
+        let decay = (1.0 - RECONCILIATION_SMOOTHING).powf(dt * TICK_RATE as f32);
+        self.visual_offset *= decay;
+    }
+
+    /// One fixed movement tick: predict, buffer for replay, send MoveCommand.
+    fn step(&mut self, input: &ClientInput) {
+        let mut input = input.clone();
+        input.sequence = self.next_sequence;
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+        self.tick += 1;
+
+        // Client-side prediction (authoritative feel, zero perceived latency)
+        self.predicted_state.position += input.movement * MOVE_DT;
+        self.predicted_state.velocity = input.movement;
+
         if let Some(tx) = &self.transport_tx {
-            let delta = Vec3Ser {
+            let velocity = Vec3Ser {
                 x: input.movement.x,
                 y: input.movement.y,
                 z: input.movement.z,
             };
-            let _ = tx.send(ClientMessage::Move { delta });
+            let _ = tx.send(ClientMessage::MoveCommand { sequence: input.sequence, tick: self.tick, velocity });
             // Future: also send Jump, Interact, DivineCouncilQuery etc. from UI
         }
 
-        // Store for potential replay
+        // Store for replay until the server has applied it
         self.reconciliation_buffer.push_back((input.sequence, self.predicted_state.clone()));
-        if self.reconciliation_buffer.len() > BUFFER_SIZE {
+        self.input_buffer.push_back(input);
+        if self.input_buffer.len() > BUFFER_SIZE {
+            self.input_buffer.pop_front();
             self.reconciliation_buffer.pop_front();
         }
     }
 
+    /// Request an ability on `target_id` (an EntitySnapshot entity id), aimed along our facing.
+    /// Stamped with the snapshot tick on screen; returns the request id AbilityRejected echoes.
+    pub fn use_ability(&mut self, ability_id: u32, target_id: Option<u64>) -> u32 {
+        let request_id = self.next_ability_request;
+        self.next_ability_request = self.next_ability_request.wrapping_add(1);
+        let aim = self.predicted_state.rotation * Vec3::NEG_Z;
+        if let Some(tx) = &self.transport_tx {
+            let _ = tx.send(ClientMessage::UseAbility {
+                request_id,
+                ability_id,
+                target_id,
+                view_tick: self.snapshots.server_tick,
+                aim: Vec3Ser { x: aim.x, y: aim.y, z: aim.z },
+            });
+        }
+        request_id
+    }
+
     /// Handle incoming ServerMessage from transport (WorldUpdate, Divine responses, etc.)
     pub fn handle_server_message(&mut self, msg: ServerMessage) {
         match msg {
-            ServerMessage::WorldUpdate { entities, timestamp: _ } => {
-                // Find our entity or authoritative player state
-                if let Some(snapshot) = entities.iter().find(|e| Some(e.id) == self.player_id) {
-                    let server_state = ClientState {
-                        position: Vec3::new(snapshot.position.x, snapshot.position.y, snapshot.position.z),
-                        rotation: Quat::from_rotation_z(snapshot.rotation), // or full quat if extended
-                        velocity: Vec3::ZERO,
-                    };
-                    self.reconcile(server_state);
+            msg @ ServerMessage::EntitySnapshot { .. } => {
+                let Some(snapshot_id) = self.snapshots.apply(&msg) else { return };
+                if let Some(tx) = &self.transport_tx {
+                    let _ = tx.send(ClientMessage::SnapshotAck { snapshot_id });
                 }
+                // Own movement reconciles through MoveCorrection, which names the input it
+                // follows; our entity in a snapshot carries no sequence to replay from.
                 // TODO: Update other visible entities for rendering (NPCs, other players)
             }
-            ServerMessage::DivineCouncilResponse { content, source } => {
-                // Live Ra-Thor PATSAGi guidance — display in UI or log with mercy
-                tracing::info!("[PATSAGi] {} from {}", content, source);
-                // Future: push to in-game divine counsel UI
+            ServerMessage::MoveCorrection { sequence, position, velocity, .. } => {
+                let server_state = ClientState {
+                    position: Vec3::new(position.x, position.y, position.z),
+                    rotation: self.predicted_state.rotation,
+                    velocity: Vec3::new(velocity.x, velocity.y, velocity.z),
+                };
+                self.reconcile(sequence, server_state);
+            }
+            msg @ ServerMessage::StatusEffects { .. } => {
+                self.snapshots.apply_status_effects(&msg);
+            }
+            msg @ ServerMessage::ChunkSnapshot { .. } => {
+                self.chunks.apply(&msg);
             }
-            ServerMessage::RbeGuidanceResponse { content, .. } => {
-                tracing::info!("[RBE Abundance] {}", content);
+            ServerMessage::AbilityRejected { request_id, ability_id, reason } => {
+                tracing::info!("[Ability] request {} (ability {}) rejected: {:?}", request_id, ability_id, reason);
+            }
+            ServerMessage::ValenceUpdate { new_valence, reason, .. } => {
+                tracing::info!("[Valence] {:.2} ({})", new_valence, reason);
             }
             ServerMessage::Error { message } => {
                 tracing::warn!("Server error: {}", message);
@@ -143,55 +213,37 @@ impl ClientGameLoop {
             self.last_server_sequence = header.sequence;
             let new_state = self.delta_compressor.decompress(&payload, &self.last_server_state);
             self.last_server_state = payload.to_vec();
-            // Convert and reconcile
-            // (Simplified: assume decompressed yields position etc.)
-            let server_state = ClientState::default(); // TODO: proper deserialize
-            self.reconcile(server_state);
+            // Own position is reconciled from MoveCorrection, not from legacy frames.
         }
     }
 
-    /// Full production reconciliation with input replay + smooth correction
-    fn reconcile(&mut self, server_state: ClientState) {
-        let now = Instant::now();
-        let correction_age = now.duration_since(self.last_correction_time).as_secs_f32();
-        self.last_correction_time = now;
-
-        // Find the divergence point and replay subsequent inputs
-        let mut found_match = false;
-        let mut replay_inputs: Vec<ClientInput> = Vec::new();
-
-        while let Some((seq, _)) = self.reconciliation_buffer.pop_front() {
-            if seq == self.last_server_sequence || found_match {
-                found_match = true;
-                // Collect remaining inputs for replay
-                if let Some(input) = self.input_buffer.pop_front() {
-                    replay_inputs.push(input);
-                }
-            }
+    /// Full production reconciliation: rewind to the server's state after input
+    /// `sequence`, replay every newer buffered input, smooth the visible difference.
+    fn reconcile(&mut self, sequence: u32, server_state: ClientState) {
+        if sequence < self.last_acked_sequence {
+            return; // Older than a correction already applied
         }
+        self.last_acked_sequence = sequence;
+        self.last_correction_time = Instant::now();
 
-        if found_match || self.input_buffer.is_empty() {
-            // Apply server authoritative state (with smoothing to avoid snap)
-            let smooth_factor = (RECONCILIATION_SMOOTHING * correction_age.min(1.0)).min(0.8);
-            self.predicted_state.position = self.predicted_state.position.lerp(server_state.position, smooth_factor);
-            self.predicted_state.rotation = self.predicted_state.rotation.slerp(server_state.rotation, smooth_factor);
-            self.predicted_state.velocity = server_state.velocity;
-
-            // Replay all subsequent inputs for perfect client authority feel
-            for input in replay_inputs {
-                self.predicted_state.position += input.movement * 0.0167; // ~60fps dt
-                self.predicted_state.rotation = self.predicted_state.rotation * input.rotation_delta;
-                self.predicted_state.rotation = self.predicted_state.rotation.normalize();
-            }
+        while self.input_buffer.front().is_some_and(|input| input.sequence <= sequence) {
+            self.input_buffer.pop_front();
+            self.reconciliation_buffer.pop_front();
+        }
 
-            // Re-buffer remaining for future
-            for input in replay_inputs {
-                self.input_buffer.push_back(input);
-            }
+        let before = self.predicted_state.position;
+        self.predicted_state.position = server_state.position;
+        self.predicted_state.velocity = server_state.velocity;
+
+        // Replay all subsequent inputs for perfect client authority feel
+        self.reconciliation_buffer.clear();
+        for input in &self.input_buffer {
+            self.predicted_state.position += input.movement * MOVE_DT;
+            self.predicted_state.velocity = input.movement;
+            self.reconciliation_buffer.push_back((input.sequence, self.predicted_state.clone()));
         }
 
-        // Update last known server seq
-        self.last_server_sequence = self.last_server_sequence.max(1); // placeholder
+        self.visual_offset += before - self.predicted_state.position;
     }
 
     pub fn get_predicted_state(&self) -> &ClientState {
@@ -201,6 +253,7 @@ impl ClientGameLoop {
     /// Optional: Hermite interpolation for ultra-smooth position between snapshots (future visual only)
     pub fn get_interpolated_position(&self, alpha: f32) -> Vec3 {
         // Hermite spline between last known and predicted (extend with previous states if buffered)
-        self.predicted_state.position // placeholder; full impl uses two previous points
+        // Corrections are blended out via visual_offset instead of snapping.
+        self.predicted_state.position + self.visual_offset
     }
 }
\ No newline at end of file
@@ -3,6 +3,8 @@
 // Previous: v19.2 RBE Core + ServerInventoryComponent + TradingSystem
 // v21.50: Soft origin-realm tracking — resources remain globally usable,
 //         but harvests remember which realm they came from.
+// v21.51: Basic resources, advanced grace thresholds and valuation read from the
+//         shared item registry (assets/items) instead of hard-coded tables.
 // AG-SML v1.0 License | Thunder locked in. Yoi ⚡
 
 use std::collections::HashMap;
@@ -10,6 +12,7 @@ use std::fs;
 use std::path::Path;
 use serde::{Serialize, Deserialize};
 
+use crate::shared::items::{self, ItemRegistry};
 use crate::shared::protocol::{TradeOffer, CounterOffer, TradeStatus, TradeLogEntry};
 
 /// Realm identifier aligned with MultiRealmHarness / ResourceNode.
@@ -28,33 +31,20 @@ pub struct RbeSystem {
 }
 
 impl RbeSystem {
+    /// Basic needs, grace thresholds and valuation all come from the shared item registry.
     pub fn new() -> Self {
-        let mut advanced = HashMap::new();
-        advanced.insert("custom_home".to_string(), 1000);
-        advanced.insert("personal_vehicle".to_string(), 5000);
-        advanced.insert("art_studio".to_string(), 2000);
-
-        let mut valuation = HashMap::new();
-        valuation.insert("food".to_string(), 1.0);
-        valuation.insert("water".to_string(), 1.0);
-        valuation.insert("shelter".to_string(), 2.0);
-        valuation.insert("energy".to_string(), 1.5);
-        valuation.insert("basic_tools".to_string(), 3.0);
-        // Advanced items higher value for fairness calc
-        valuation.insert("custom_home".to_string(), 50.0);
-        valuation.insert("personal_vehicle".to_string(), 120.0);
+        Self::from_registry(&items::current())
+    }
 
+    pub fn from_registry(registry: &ItemRegistry) -> Self {
         Self {
             grace_points: HashMap::new(),
-            basic_resources: vec![
-                "food".to_string(),
-                "water".to_string(),
-                "shelter".to_string(),
-                "energy".to_string(),
-                "basic_tools".to_string(),
-            ],
-            advanced_thresholds: advanced,
-            resource_valuation: valuation,
+            basic_resources: registry.iter().filter(|d| d.basic).map(|d| d.id.clone()).collect(),
+            advanced_thresholds: registry
+                .iter()
+                .filter_map(|d| d.unlock_grace.map(|grace| (d.id.clone(), grace)))
+                .collect(),
+            resource_valuation: registry.iter().map(|d| (d.id.clone(), d.value)).collect(),
         }
     }
 
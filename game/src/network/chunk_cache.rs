@@ -0,0 +1,58 @@
+// game/src/network/chunk_cache.rs
+// Powrush-MMO — Client chunk cache v1.0
+// Holds the newest ServerMessage::ChunkSnapshot of each world chunk (resource nodes,
+// placed structures). Chunks the server never sent are empty.
+// AG-SML v1.0 | TOLC 8 | Permanent PATSAGi | Contact: info@Rathor.ai
+
+use std::collections::HashMap;
+
+use shared::protocol::*;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientChunk {
+    pub version: u64,
+    pub resource_nodes: Vec<WireResourceNode>,
+    pub structures: Vec<WireStructure>,
+}
+
+#[derive(Default)]
+pub struct ClientChunkCache {
+    chunks: HashMap<u64, ClientChunk>,
+}
+
+impl ClientChunkCache {
+    /// Apply a ChunkSnapshot unless an equal or newer version is already held.
+    /// Returns false for stale snapshots and any other message.
+    pub fn apply(&mut self, msg: &ServerMessage) -> bool {
+        let ServerMessage::ChunkSnapshot { chunk_id, version, resource_nodes, structures } = msg else {
+            return false;
+        };
+        if self.chunks.get(chunk_id).is_some_and(|held| held.version >= *version) {
+            return false;
+        }
+        self.chunks.insert(
+            *chunk_id,
+            ClientChunk { version: *version, resource_nodes: resource_nodes.clone(), structures: structures.clone() },
+        );
+        true
+    }
+
+    pub fn chunk(&self, chunk_id: u64) -> Option<&ClientChunk> {
+        self.chunks.get(&chunk_id)
+    }
+
+    pub fn resource_nodes(&self) -> impl Iterator<Item = &WireResourceNode> {
+        self.chunks.values().flat_map(|c| &c.resource_nodes)
+    }
+
+    pub fn structures(&self) -> impl Iterator<Item = &WireStructure> {
+        self.chunks.values().flat_map(|c| &c.structures)
+    }
+
+    /// Drop chunks the renderer no longer needs (e.g. far behind the player).
+    pub fn retain(&mut self, mut keep: impl FnMut(u64) -> bool) {
+        self.chunks.retain(|id, _| keep(*id));
+    }
+}
+
+// Thunder locked in. Yoi ⚡
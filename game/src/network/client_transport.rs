@@ -1,9 +1,16 @@
 // game/src/network/client_transport.rs
 // Powrush-MMO — Client Networking Transport Layer v2.3
 // try_recv for poll loops + protocol-aligned HandshakeRequest
+// v2.4: answer_auth_challenge signs the server's RSIL nonce with the player's SovereignKeypair
+// v2.5: ack_snapshot confirms EntitySnapshot baselines (see entity_snapshots.rs)
+// v2.6: frames go through shared::wire_compat; ProtocolAccepted sets the negotiated version
+// v2.7: native builds bind the server's DatagramOffer (UDP) for MoveCommand / SnapshotAck out and
+//       EntitySnapshot / MoveCorrection in; WebSocket only on WASM or when the bind fails
+// v2.8: datagrams are sealed with the session token (MAC) and Challenges from the server are echoed
 // Dual-target: native + WASM (web-sys)
 // AG-SML v1.0 | TOLC 8 | Permanent PATSAGi | Contact: info@Rathor.ai
 
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -12,7 +19,20 @@ use tokio::sync::mpsc;
 use tracing::{info, warn, error};
 
 use shared::protocol::*;
+use shared::wire_compat::{decode_server_message, encode_client_message};
+#[cfg(not(target_arch = "wasm32"))]
+use shared::datagram::{
+    client_message_channel, Channel, Datagram, DatagramKind, SequenceWindow, SessionToken, BIND_RETRY_MS,
+    BIND_TIMEOUT_MS, DATAGRAM_IDLE_TIMEOUT_MS, KEEPALIVE_INTERVAL_MS,
+};
+use rsil_identity::{answer_challenge, DidDocument, SovereignKeypair};
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::atomic::{AtomicBool, AtomicU64};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::RwLock;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::net::UdpSocket;
 #[cfg(not(target_arch = "wasm32"))]
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message as WsMessage};
 
@@ -31,10 +51,183 @@ pub struct ClientWsTransport {
     rx_in: mpsc::UnboundedReceiver<ServerMessage>,
     shutdown: Arc<tokio::sync::Notify>,
     last_ping_ms: u64,
+    /// Negotiated wire version (PROTOCOL_VERSION until the server says otherwise)
+    wire_version: Arc<AtomicU32>,
+    /// Bound UDP channel, if the server offered one and it answered
+    #[cfg(not(target_arch = "wasm32"))]
+    datagram: SharedDatagramLink,
     #[cfg(target_arch = "wasm32")]
     ws: Option<WebSocket>,
 }
 
+/// Decode one frame; a ProtocolAccepted switches every later frame to its version.
+fn decode_frame(bytes: &[u8], wire_version: &AtomicU32) -> Option<ServerMessage> {
+    match decode_server_message(bytes, wire_version.load(Ordering::Acquire)) {
+        Ok(msg) => {
+            if let ServerMessage::ProtocolAccepted { version } = &msg {
+                wire_version.store(*version, Ordering::Release);
+            }
+            Some(msg)
+        }
+        Err(e) => {
+            warn!("[ClientTransport] Dropped server frame: {}", e);
+            None
+        }
+    }
+}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// UDP datagram link (native only; see shared/src/datagram.rs)
+// ════════════════════════════════════════════════════════════════════════════════════
+
+#[cfg(not(target_arch = "wasm32"))]
+type SharedDatagramLink = Arc<RwLock<Option<Arc<DatagramLink>>>>;
+
+#[cfg(not(target_arch = "wasm32"))]
+struct DatagramLink {
+    socket: UdpSocket,
+    token: SessionToken,
+    next_seq: AtomicU32,
+    last_send_ms: AtomicU64,
+    last_recv_ms: AtomicU64,
+    alive: AtomicBool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DatagramLink {
+    /// Bind to the server's offered port; Err when no BindAck arrives within BIND_TIMEOUT_MS.
+    async fn bind(server: std::net::SocketAddr, token: SessionToken) -> Result<Self, String> {
+        let local: std::net::SocketAddr = if server.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }.parse().unwrap();
+        let socket = UdpSocket::bind(local).await.map_err(|e| format!("UDP bind failed: {}", e))?;
+        socket.connect(server).await.map_err(|e| format!("UDP connect failed: {}", e))?;
+        let link = Self {
+            socket,
+            token,
+            next_seq: AtomicU32::new(0),
+            last_send_ms: AtomicU64::new(0),
+            last_recv_ms: AtomicU64::new(now_ms()),
+            alive: AtomicBool::new(true),
+        };
+
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(BIND_TIMEOUT_MS);
+        let mut buf = [0u8; 64];
+        while tokio::time::Instant::now() < deadline {
+            link.send_frame(DatagramKind::Bind, &[]).await;
+            let retry = tokio::time::Instant::now() + Duration::from_millis(BIND_RETRY_MS);
+            loop {
+                match tokio::time::timeout_at(retry, link.socket.recv(&mut buf)).await {
+                    Ok(Ok(len)) => {
+                        if matches!(Datagram::decode(&buf[..len], &token), Ok(d) if d.kind == DatagramKind::BindAck) {
+                            // Proves the return path; the server starts routing over UDP after this.
+                            link.send_frame(DatagramKind::Keepalive, &[]).await;
+                            link.last_recv_ms.store(now_ms(), Ordering::Release);
+                            return Ok(link);
+                        }
+                    }
+                    // e.g. ICMP port unreachable: wait out the retry interval
+                    Ok(Err(_)) => {
+                        tokio::time::sleep_until(retry).await;
+                        break;
+                    }
+                    Err(_) => break,
+                }
+            }
+        }
+        Err(format!("no BindAck from {} within {} ms", server, BIND_TIMEOUT_MS))
+    }
+
+    async fn send_frame(&self, kind: DatagramKind, payload: &[u8]) -> bool {
+        let sequence = self.next_seq.fetch_add(1, Ordering::AcqRel);
+        let Ok(frame) = (Datagram { kind, token: self.token, sequence, payload }).encode() else {
+            return false;
+        };
+        self.last_send_ms.store(now_ms(), Ordering::Release);
+        self.socket.send(&frame).await.is_ok()
+    }
+
+    /// Receive loop: snapshots / corrections to `tx_in`; closes the link after
+    /// DATAGRAM_IDLE_TIMEOUT_MS of silence so sends fall back to the WebSocket.
+    async fn run(
+        self: Arc<Self>,
+        shared: SharedDatagramLink,
+        tx_in: mpsc::UnboundedSender<ServerMessage>,
+        wire_version: Arc<AtomicU32>,
+    ) {
+        let mut window = SequenceWindow::default();
+        let mut buf = vec![0u8; 2048];
+        let mut keepalive = tokio::time::interval(Duration::from_millis(KEEPALIVE_INTERVAL_MS / 2));
+        while self.alive.load(Ordering::Acquire) {
+            tokio::select! {
+                received = self.socket.recv(&mut buf) => {
+                    let Ok(len) = received else { continue };
+                    let Ok(datagram) = Datagram::decode(&buf[..len], &self.token) else { continue };
+                    if !window.accept(datagram.sequence) {
+                        continue;
+                    }
+                    self.last_recv_ms.store(now_ms(), Ordering::Release);
+                    match datagram.kind {
+                        DatagramKind::Message => {
+                            if let Some(msg) = decode_frame(datagram.payload, &wire_version) {
+                                let _ = tx_in.send(msg);
+                            }
+                        }
+                        // Our address changed (NAT rebinding): prove we still hold the session.
+                        DatagramKind::Challenge => {
+                            self.send_frame(DatagramKind::ChallengeResponse, datagram.payload).await;
+                        }
+                        _ => {}
+                    }
+                }
+                _ = keepalive.tick() => {
+                    let now = now_ms();
+                    if now.saturating_sub(self.last_recv_ms.load(Ordering::Acquire)) > DATAGRAM_IDLE_TIMEOUT_MS {
+                        warn!("[ClientTransport] UDP channel went quiet; falling back to WebSocket");
+                        self.alive.store(false, Ordering::Release);
+                    } else if now.saturating_sub(self.last_send_ms.load(Ordering::Acquire)) >= KEEPALIVE_INTERVAL_MS {
+                        self.send_frame(DatagramKind::Keepalive, &[]).await;
+                    }
+                }
+            }
+        }
+        *shared.write().unwrap() = None;
+    }
+}
+
+/// Resolve the WebSocket host, bind the offered UDP port and run the link until it dies.
+#[cfg(not(target_arch = "wasm32"))]
+async fn establish_datagram_link(
+    url: String,
+    port: u16,
+    token: SessionToken,
+    shared: SharedDatagramLink,
+    tx_in: mpsc::UnboundedSender<ServerMessage>,
+    wire_version: Arc<AtomicU32>,
+) {
+    let host = match url.parse::<tokio_tungstenite::tungstenite::http::Uri>() {
+        Ok(uri) => uri.host().unwrap_or_default().trim_matches(|c| c == '[' || c == ']').to_string(),
+        Err(e) => {
+            warn!("[ClientTransport] Cannot parse {} for UDP ({}); WebSocket only", url, e);
+            return;
+        }
+    };
+    let server = match tokio::net::lookup_host((host.as_str(), port)).await.map(|mut addrs| addrs.next()) {
+        Ok(Some(addr)) => addr,
+        _ => {
+            warn!("[ClientTransport] Cannot resolve {}:{} for UDP; WebSocket only", host, port);
+            return;
+        }
+    };
+    match DatagramLink::bind(server, token).await {
+        Ok(link) => {
+            let link = Arc::new(link);
+            *shared.write().unwrap() = Some(link.clone());
+            info!("[ClientTransport] UDP channel bound to {}", server);
+            link.run(shared, tx_in, wire_version).await;
+        }
+        Err(e) => warn!("[ClientTransport] {}; WebSocket only", e),
+    }
+}
+
 fn now_ms() -> u64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -53,25 +246,37 @@ impl ClientWsTransport {
             let (tx_out, mut rx_out) = mpsc::unbounded_channel::<ClientMessage>();
             let (tx_in, rx_in) = mpsc::unbounded_channel::<ServerMessage>();
             let shutdown = Arc::new(tokio::sync::Notify::new());
+            let wire_version = Arc::new(AtomicU32::new(PROTOCOL_VERSION));
+            let datagram: SharedDatagramLink = Arc::new(RwLock::new(None));
 
             let handshake = ClientMessage::HandshakeRequest {
                 version: PROTOCOL_VERSION,
                 player_name: player_name.to_string(),
                 client_time_ms: now_ms(),
             };
-            let bytes = bincode::serialize(&handshake)
+            let bytes = encode_client_message(&handshake, PROTOCOL_VERSION)
                 .map_err(|e| format!("Handshake serialize failed: {}", e))?;
             write
                 .send(WsMessage::Binary(bytes.into()))
                 .await
                 .map_err(|e| format!("Handshake send failed: {}", e))?;
 
+            let writer_version = wire_version.clone();
+            let writer_datagram = datagram.clone();
             tokio::spawn(async move {
                 while let Some(msg) = rx_out.recv().await {
                     if !apply_mercy_gate(&msg, 0.8) {
                         continue;
                     }
-                    if let Ok(bytes) = bincode::serialize(&msg) {
+                    if let Ok(bytes) = encode_client_message(&msg, writer_version.load(Ordering::Acquire)) {
+                        if client_message_channel(&msg) == Channel::Unreliable {
+                            let link = writer_datagram.read().unwrap().clone();
+                            if let Some(link) = link {
+                                if link.send_frame(DatagramKind::Message, &bytes).await {
+                                    continue;
+                                }
+                            }
+                        }
                         if write.send(WsMessage::Binary(bytes.into())).await.is_err() {
                             break;
                         }
@@ -80,11 +285,24 @@ impl ClientWsTransport {
             });
 
             let tx_in_reader = tx_in.clone();
+            let reader_version = wire_version.clone();
+            let reader_datagram = datagram.clone();
+            let server_url = url.to_string();
             tokio::spawn(async move {
                 while let Some(msg_result) = read.next().await {
                     match msg_result {
                         Ok(WsMessage::Binary(bytes)) => {
-                            if let Ok(server_msg) = bincode::deserialize::<ServerMessage>(&bytes) {
+                            if let Some(server_msg) = decode_frame(&bytes, &reader_version) {
+                                if let ServerMessage::DatagramOffer { port, token } = &server_msg {
+                                    tokio::spawn(establish_datagram_link(
+                                        server_url.clone(),
+                                        *port,
+                                        *token,
+                                        reader_datagram.clone(),
+                                        tx_in_reader.clone(),
+                                        reader_version.clone(),
+                                    ));
+                                }
                                 let _ = tx_in_reader.send(server_msg);
                             }
                         }
@@ -92,6 +310,10 @@ impl ClientWsTransport {
                         _ => {}
                     }
                 }
+                // The datagram path never outlives its WebSocket session.
+                if let Some(link) = reader_datagram.read().unwrap().as_ref() {
+                    link.alive.store(false, Ordering::Release);
+                }
             });
 
             let tx_hb = tx_out.clone();
@@ -112,6 +334,8 @@ impl ClientWsTransport {
                     rx_in,
                     shutdown,
                     last_ping_ms: now_ms(),
+                    wire_version,
+                    datagram,
                 },
                 0,
             ))
@@ -126,13 +350,14 @@ impl ClientWsTransport {
             let (tx_out, mut rx_out) = mpsc::unbounded_channel::<ClientMessage>();
             let (tx_in, rx_in) = mpsc::unbounded_channel::<ServerMessage>();
             let shutdown = Arc::new(tokio::sync::Notify::new());
+            let wire_version = Arc::new(AtomicU32::new(PROTOCOL_VERSION));
 
             let handshake = ClientMessage::HandshakeRequest {
                 version: PROTOCOL_VERSION,
                 player_name: player_name.to_string(),
                 client_time_ms: now_ms(),
             };
-            let bytes = bincode::serialize(&handshake)
+            let bytes = encode_client_message(&handshake, PROTOCOL_VERSION)
                 .map_err(|e| format!("Handshake serialize failed: {}", e))?;
             let array = Uint8Array::from(&bytes[..]);
             ws.send_with_u8_array(&array)
@@ -143,6 +368,7 @@ impl ClientWsTransport {
             let player_id_cell: Rc<RefCell<Option<u64>>> = Rc::new(RefCell::new(None));
             let player_id_cell_clone = player_id_cell.clone();
             let tx_in_clone = tx_in.clone();
+            let reader_version = wire_version.clone();
 
             let onmessage_callback =
                 Closure::<dyn FnMut(MessageEvent)>::new(move |e: MessageEvent| {
@@ -150,7 +376,7 @@ impl ClientWsTransport {
                         let array = Uint8Array::new(&abuf);
                         let mut data = vec![0; array.length() as usize];
                         array.copy_to(&mut data);
-                        if let Ok(server_msg) = bincode::deserialize::<ServerMessage>(&data) {
+                        if let Some(server_msg) = decode_frame(&data, &reader_version) {
                             if let ServerMessage::HandshakeResponse {
                                 player_id,
                                 accepted,
@@ -185,12 +411,13 @@ impl ClientWsTransport {
             onclose_callback.forget();
 
             let ws_send = ws.clone();
+            let writer_version = wire_version.clone();
             spawn_local(async move {
                 while let Some(msg) = rx_out.recv().await {
                     if !apply_mercy_gate(&msg, 0.8) {
                         continue;
                     }
-                    if let Ok(bytes) = bincode::serialize(&msg) {
+                    if let Ok(bytes) = encode_client_message(&msg, writer_version.load(Ordering::Acquire)) {
                         let array = Uint8Array::from(&bytes[..]);
                         if ws_send.send_with_u8_array(&array).is_err() {
                             break;
@@ -205,6 +432,7 @@ impl ClientWsTransport {
                 rx_in,
                 shutdown,
                 last_ping_ms: now_ms(),
+                wire_version,
                 ws: Some(ws),
             };
 
@@ -213,6 +441,29 @@ impl ClientWsTransport {
         }
     }
 
+    /// Reply to `ServerMessage::AuthChallenge`; HandshakeResponse arrives after the server verifies.
+    /// `document` is required once the player has rotated away from their genesis key.
+    pub fn answer_auth_challenge(
+        &self,
+        keypair: &SovereignKeypair,
+        document: Option<&DidDocument>,
+        server_id: &str,
+        nonce: &[u8],
+    ) -> Result<(), String> {
+        let answer = answer_challenge(keypair, document, server_id, nonce).map_err(|e| e.to_string())?;
+        self.send(ClientMessage::AuthChallengeResponse {
+            did: answer.did,
+            public_key: answer.public_key,
+            signature: answer.signature,
+            did_document: answer.did_document,
+        })
+    }
+
+    /// Confirm a reassembled EntitySnapshot so the server deltas against it.
+    pub fn ack_snapshot(&self, snapshot_id: u32) -> Result<(), String> {
+        self.send(ClientMessage::SnapshotAck { snapshot_id })
+    }
+
     pub fn send(&self, msg: ClientMessage) -> Result<(), String> {
         self.tx_out
             .send(msg)
@@ -272,6 +523,22 @@ impl ClientWsTransport {
     pub fn get_player_id(&self) -> Option<u64> {
         self.player_id
     }
+
+    pub fn wire_version(&self) -> u32 {
+        self.wire_version.load(Ordering::Acquire)
+    }
+
+    /// True while MoveCommand / SnapshotAck go out over UDP.
+    pub fn datagram_active(&self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.datagram.read().unwrap().is_some()
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            false
+        }
+    }
 }
 
 // Thunder locked in. Yoi ⚡
@@ -0,0 +1,119 @@
+// game/src/network/entity_snapshots.rs
+// Powrush-MMO — Client EntitySnapshot reassembly v1.0
+// Rebuilds full entity state from delta snapshots against acked baselines and
+// tells the caller which snapshot_id to ack (ClientMessage::SnapshotAck).
+// v1.1 — Replicated status effects (ServerMessage::StatusEffects) with local timers.
+// AG-SML v1.0 | TOLC 8 | Permanent PATSAGi | Contact: info@Rathor.ai
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+use shared::protocol::*;
+
+/// Matches the server's per-client history; older baselines are never referenced.
+const SNAPSHOT_HISTORY: usize = 32;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ReplicatedEntity {
+    pub position: [f32; 3],
+    /// (x, y, z, w)
+    pub rotation: [f32; 4],
+    pub velocity: [f32; 3],
+    pub health: f32,
+}
+
+impl ReplicatedEntity {
+    fn apply(&mut self, delta: &WireEntityDelta) {
+        if let Some(p) = delta.position {
+            self.position = dequantize_position(p);
+        }
+        if let Some(r) = delta.rotation {
+            self.rotation = unpack_rotation(r);
+        }
+        if let Some(v) = delta.velocity {
+            self.velocity = dequantize_velocity(v);
+        }
+        if let Some(h) = delta.health {
+            self.health = h;
+        }
+    }
+}
+
+/// A replicated buff / debuff; its timer runs locally from the moment it arrived.
+#[derive(Debug, Clone)]
+pub struct ClientStatusEffect {
+    pub effect: WireStatusEffect,
+    received: Instant,
+}
+
+impl ClientStatusEffect {
+    pub fn remaining_secs(&self) -> f32 {
+        (self.effect.remaining_secs - self.received.elapsed().as_secs_f32()).max(0.0)
+    }
+}
+
+#[derive(Default)]
+pub struct ClientSnapshotBuffer {
+    history: VecDeque<(u32, HashMap<u64, ReplicatedEntity>)>,
+    pub server_tick: u64,
+    status: HashMap<u64, Vec<ClientStatusEffect>>,
+}
+
+impl ClientSnapshotBuffer {
+    /// Apply an EntitySnapshot. Returns the id to ack, or None when the message is
+    /// not a snapshot or its baseline is unknown (the server will resend from an older ack).
+    pub fn apply(&mut self, msg: &ServerMessage) -> Option<u32> {
+        let ServerMessage::EntitySnapshot { snapshot_id, baseline_id, server_tick, entities, removed } = msg else {
+            return None;
+        };
+
+        let mut state = match baseline_id {
+            Some(base) => self.history.iter().find(|(id, _)| id == base)?.1.clone(),
+            None => HashMap::new(),
+        };
+        for id in removed {
+            state.remove(id);
+            self.status.remove(id);
+        }
+        for delta in entities {
+            state.entry(delta.entity_id).or_default().apply(delta);
+        }
+
+        self.history.push_back((*snapshot_id, state));
+        while self.history.len() > SNAPSHOT_HISTORY {
+            self.history.pop_front();
+        }
+        self.server_tick = *server_tick;
+        Some(*snapshot_id)
+    }
+
+    /// Apply a StatusEffects message (each list replaces the entity's previous one).
+    /// Returns false for any other message.
+    pub fn apply_status_effects(&mut self, msg: &ServerMessage) -> bool {
+        let ServerMessage::StatusEffects { entities, .. } = msg else {
+            return false;
+        };
+        let received = Instant::now();
+        for entity in entities {
+            if entity.effects.is_empty() {
+                self.status.remove(&entity.entity_id);
+            } else {
+                let effects = entity.effects.iter().map(|e| ClientStatusEffect { effect: e.clone(), received }).collect();
+                self.status.insert(entity.entity_id, effects);
+            }
+        }
+        true
+    }
+
+    /// Statuses on `entity_id` whose timers have not run out.
+    pub fn status_effects(&self, entity_id: u64) -> impl Iterator<Item = &ClientStatusEffect> {
+        self.status.get(&entity_id).into_iter().flatten().filter(|s| s.remaining_secs() > 0.0)
+    }
+
+    /// Latest reassembled world view.
+    pub fn entities(&self) -> Option<&HashMap<u64, ReplicatedEntity>> {
+        self.history.back().map(|(_, state)| state)
+    }
+}
+
+// Thunder locked in. Yoi ⚡
@@ -1,5 +1,11 @@
 //! Powrush-MMO Unified Cohost Binary — Full E2E Harness
 //! v21.88.0 — Ultramasterism Perfecticism + Stress / Endurance Mode
+//! v21.88.1 — Council ResourcePolicy caps → server AbundanceRounds (same caps as the simulation allocator)
+//! v21.97.0 — Council agenda bridge: player proposals / ballots → CouncilSessionRegistry,
+//!            deliberated outcomes + ActivePolicy → CouncilAgenda resolutions
+//! v21.97.1 — Player ballots carry their NEVC attunement into the session tally
+//! v21.97.2 — Player ballots and proposals use shared::council_ballot::player_voter ids in
+//!            the session; resolutions carry the full ballot record for clients to fetch
 //! Permanent PATSAGi Councils active (sibling Ra-Thor lattice)
 //!
 //! Modes:
@@ -21,10 +27,20 @@ use tracing_subscriber::EnvFilter;
 use simulation::{
     FullSimulationPlugins,
     CouncilRttExportQueue,
+    CouncilDecisions,
+    CouncilProposal,
+    ProposalType,
     hardware_sovereignty::sovereign_hardware_ascension_ui,
     KardashevAccelerationDashboard,
     RealityTransferScoreLedger,
 };
+use simulation::council::decision::{ActivePolicy, PolicyType};
+use simulation::council::CouncilSessionRegistry;
+
+use powrush_mmo_server::council_agenda::{AgendaCommand, CouncilAgenda, CouncilResolution};
+use powrush_mmo_server::abundance::AbundanceRounds;
+use shared::council_ballot::{player_voter, voter_player};
+use shared::protocol::{WireActivePolicy, WireCouncilProposal, WireProposalType};
 
 use powrush_mmo_server::rathor_integration::{
     RathorIntegrationPlugin, CohostExportMirror, CohostMirrorSignal,
@@ -128,6 +144,8 @@ fn main() {
         ))
         .add_systems(Update, (
             host_drain_sim_to_mirror_system,
+            host_council_agenda_bridge_system,
+            host_resource_policy_sync_system,
             host_stress_injection_system,
             host_status_log_system,
             host_exit_system,
@@ -236,6 +254,154 @@ fn host_drain_sim_to_mirror_system(
     }
 }
 
+fn proposal_type_from_wire(t: WireProposalType) -> ProposalType {
+    match t {
+        WireProposalType::HarmonyBoost => ProposalType::HarmonyBoost,
+        WireProposalType::ResourcePolicy => ProposalType::ResourcePolicy,
+        WireProposalType::EpiphanyEvent => ProposalType::EpiphanyEvent,
+        WireProposalType::KardashevAcceleration => ProposalType::KardashevAcceleration,
+        WireProposalType::General => ProposalType::General,
+    }
+}
+
+fn proposal_type_to_wire(t: &ProposalType) -> WireProposalType {
+    match t {
+        ProposalType::HarmonyBoost => WireProposalType::HarmonyBoost,
+        ProposalType::ResourcePolicy => WireProposalType::ResourcePolicy,
+        ProposalType::EpiphanyEvent => WireProposalType::EpiphanyEvent,
+        ProposalType::KardashevAcceleration => WireProposalType::KardashevAcceleration,
+        ProposalType::General => WireProposalType::General,
+    }
+}
+
+fn policy_to_wire(p: &ActivePolicy) -> WireActivePolicy {
+    let policy_type = match p.policy_type {
+        PolicyType::HarmonyBoost => WireProposalType::HarmonyBoost,
+        PolicyType::ResourcePolicy => WireProposalType::ResourcePolicy,
+        PolicyType::EpiphanyEvent => WireProposalType::EpiphanyEvent,
+        PolicyType::KardashevAcceleration => WireProposalType::KardashevAcceleration,
+        PolicyType::General => WireProposalType::General,
+    };
+    WireActivePolicy {
+        decision_id: p.decision_id,
+        policy_type,
+        target_interest_zone: p.target_interest_zone,
+        strength: p.strength,
+        remaining_ticks: p.remaining_ticks,
+        title: p.title.clone(),
+    }
+}
+
+/// Player proposals and ballots from the server's CouncilAgenda go into the realm's
+/// CouncilSession; once deliberation publishes a proposal's ballot record (and a
+/// passed one has become an ActivePolicy) the outcome goes back as a resolution.
+/// Open simulation proposals are mirrored into the agenda once a second.
+fn host_council_agenda_bridge_system(
+    time: Res<Time>,
+    agenda: Option<ResMut<CouncilAgenda>>,
+    mut registry: ResMut<CouncilSessionRegistry>,
+    decisions: Res<CouncilDecisions>,
+    mut last_sync: Local<f32>,
+) {
+    let Some(mut agenda) = agenda else { return };
+    // Same tick approximation as session_deliberation_system.
+    let tick = (time.elapsed_seconds_f64() * 10.0) as u64;
+
+    for command in agenda.drain_commands() {
+        match command {
+            AgendaCommand::Submit { proposal: p } => {
+                let mut proposal = CouncilProposal::new(
+                    p.proposal_id,
+                    proposal_type_from_wire(p.proposal_type),
+                    p.title,
+                    p.description,
+                    player_voter(p.proposer_id),
+                    tick,
+                );
+                if let Some(zone) = p.target_zone {
+                    proposal = proposal.with_target_zone(zone);
+                }
+                registry.ensure_session(p.realm_id, tick).add_proposal(proposal);
+            }
+            AgendaCommand::Vote { realm_id, proposal_id, voter: player_id, support, attunement, .. } => {
+                // Players vote in their own identity space, apart from simulation agents.
+                let voter = player_voter(player_id);
+                let session = registry.ensure_session(realm_id, tick);
+                session.set_voter_attunement(voter, attunement);
+                if let Err(e) = session.cast_vote_on(proposal_id, voter, support, tick) {
+                    info!(target: "powrush::host::council", proposal_id, player_id, error = %e, "Ballot not applied");
+                }
+            }
+        }
+    }
+
+    for (realm_id, proposal_id) in agenda.open_ids() {
+        let Some(record) = registry.sessions.get(&realm_id).and_then(|s| s.published_ballot(proposal_id)) else {
+            continue;
+        };
+        // Passed, but apply_council_decision_effects has not turned it into a policy yet.
+        if decisions.pending.iter().any(|d| d.proposal_id == proposal_id && d.realm_id == realm_id) {
+            continue;
+        }
+        let decision = decisions
+            .resolved_history
+            .iter()
+            .rev()
+            .find(|d| d.proposal_id == proposal_id && d.realm_id == realm_id);
+        let policy = decision
+            .and_then(|d| decisions.active_policies.iter().find(|p| p.decision_id == d.decision_id))
+            .map(policy_to_wire);
+        agenda.push_resolution(CouncilResolution {
+            realm_id,
+            proposal_id,
+            passed: decision.is_some(),
+            votes_for: record.tally.voters_for,
+            votes_against: record.tally.voters_against,
+            ballot: record.clone(),
+            policy,
+        });
+    }
+
+    let now = time.elapsed_seconds();
+    if now - *last_sync < 1.0 {
+        return;
+    }
+    *last_sync = now;
+    for (realm_id, session) in registry.sessions.iter() {
+        for p in session.active_proposals.iter().filter(|p| p.is_open()) {
+            let submitted_at = agenda.get(p.id).map(|w| w.submitted_at).unwrap_or(0);
+            agenda.upsert(WireCouncilProposal {
+                proposal_id: p.id,
+                realm_id: *realm_id,
+                proposal_type: proposal_type_to_wire(&p.proposal_type),
+                title: p.title.clone(),
+                description: p.description.clone(),
+                proposer_id: voter_player(p.proposer).unwrap_or(p.proposer),
+                submitted_at,
+                target_zone: p.target_interest_zone,
+                votes_for: p.votes_for,
+                votes_against: p.votes_against,
+            });
+        }
+    }
+}
+
+/// The server's abundance rounds run under the caps of the council's strongest
+/// active ResourcePolicy, exactly like the simulation's PostScarcityAllocator.
+fn host_resource_policy_sync_system(decisions: Res<CouncilDecisions>, rounds: Option<ResMut<AbundanceRounds>>) {
+    let Some(mut rounds) = rounds else { return };
+    let caps = decisions.resource_policy_caps();
+    if rounds.policy() != &caps {
+        info!(
+            target: "powrush::host::council",
+            reserve_fraction = caps.reserve_fraction,
+            max_share = caps.max_share,
+            "Abundance caps synced from council ResourcePolicy"
+        );
+        rounds.set_policy(caps);
+    }
+}
+
 /// Stress mode only: periodically inject realistic high-signal events
 /// so the RTT + soft feedback loop is continuously exercised.
 fn host_stress_injection_system(
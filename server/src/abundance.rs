@@ -0,0 +1,227 @@
+/*!
+ * server/src/abundance.rs
+ *
+ * Player side of the need-weighted abundance rounds (shared::fair_allocation).
+ * Players declare a need over the protocol; it waits here until the next round
+ * over the global pool, and once the round has run each claimant is sent their
+ * own allocation record (declared, weight, granted, what limited it).
+ *
+ * The caps are the council's: the co-host copies the active ResourcePolicy's
+ * caps (CouncilDecisions::resource_policy_caps) in whenever they change, so the
+ * server's rounds and the simulation's run under the same policy. A server
+ * running without the simulation keeps the default caps.
+ *
+ * AG-SML v1.0 | TOLC 8 + RBE | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::collections::VecDeque;
+
+use bevy::prelude::Resource;
+use shared::fair_allocation::{AllocationPolicy, AllocationRecord, AllocationRound, FairAllocator, NeedDeclaration, NeedSeverity};
+use shared::nevc_adapter::ContributionClass;
+use shared::protocol::{ClientMessage, ServerMessage};
+use tracing::{info, warn};
+
+use crate::nevc_attachment;
+
+/// Resource key for global-pool abundance in allocation rounds.
+pub const ABUNDANCE_RESOURCE: &str = "abundance";
+
+/// Largest need a single declaration may name.
+pub const MAX_DECLARED_NEED: f64 = 10_000.0;
+
+/// Completed allocation rounds kept for audit.
+const ALLOCATION_LOG_CAP: usize = 64;
+
+#[derive(Resource, Default)]
+pub struct AbundanceRounds {
+    /// Needs declared for the next round
+    allocator: FairAllocator,
+    policy: AllocationPolicy,
+    /// Oldest first, at most ALLOCATION_LOG_CAP
+    log: VecDeque<AllocationRound>,
+    /// Records of completed rounds not yet sent to their players
+    unsent: Vec<AllocationRecord>,
+}
+
+impl AbundanceRounds {
+    pub fn policy(&self) -> &AllocationPolicy {
+        &self.policy
+    }
+
+    /// Caps for the next round (the council's ResourcePolicy); true when they changed.
+    pub fn set_policy(&mut self, policy: AllocationPolicy) -> bool {
+        if self.policy == policy {
+            return false;
+        }
+        self.policy = policy;
+        true
+    }
+
+    pub fn has_declared(&self, player_id: u64) -> bool {
+        self.allocator.pending_for(ABUNDANCE_RESOURCE).any(|n| n.player_id == player_id)
+    }
+
+    /// Declare (or replace) a player's need for the next round; true when it replaced one.
+    pub fn declare(
+        &mut self,
+        player_id: u64,
+        amount: f64,
+        severity: NeedSeverity,
+        class: ContributionClass,
+    ) -> Result<bool, String> {
+        if amount > MAX_DECLARED_NEED {
+            return Err(format!("need {} exceeds the {} a round can grant one player", amount, MAX_DECLARED_NEED));
+        }
+        let replaced = self.has_declared(player_id);
+        self.allocator.declare(NeedDeclaration {
+            player_id,
+            resource: ABUNDANCE_RESOURCE.to_string(),
+            amount,
+            severity,
+            class,
+        })?;
+        Ok(replaced)
+    }
+
+    /// Run one round over `supply` under the current caps. `participants` who
+    /// declared nothing claim an even share at Standard severity; everyone with a
+    /// declaration takes part. None when nobody claimed anything.
+    pub fn run_round(&mut self, participants: &[u64], supply: f64) -> Result<Option<&AllocationRound>, String> {
+        if !participants.is_empty() {
+            let even_share = supply / participants.len() as f64;
+            for &player_id in participants {
+                if !self.has_declared(player_id) {
+                    let class = nevc_attachment::player_contribution_class(player_id);
+                    let _ = self.declare(player_id, even_share.min(MAX_DECLARED_NEED), NeedSeverity::Standard, class);
+                }
+            }
+        }
+        if !self.allocator.has_pending(ABUNDANCE_RESOURCE) {
+            return Ok(None);
+        }
+
+        let round = self.allocator.run_round(ABUNDANCE_RESOURCE, supply.max(0.0), &self.policy)?;
+        info!(
+            "[Abundance] Round {}: {:.1} of {:.1} granted to {} claimant(s)",
+            round.round_id,
+            round.distributed,
+            round.supply,
+            round.records.len()
+        );
+        self.unsent.extend(round.records.iter().cloned());
+        if self.log.len() == ALLOCATION_LOG_CAP {
+            self.log.pop_front();
+        }
+        self.log.push_back(round);
+        Ok(self.log.back())
+    }
+
+    /// Completed rounds, oldest first.
+    pub fn log(&self) -> impl Iterator<Item = &AllocationRound> + '_ {
+        self.log.iter()
+    }
+
+    /// Each claimant's record of the rounds run since the last call.
+    pub fn drain_grants(&mut self) -> Vec<(u64, ServerMessage)> {
+        self.unsent
+            .drain(..)
+            .map(|r| {
+                (
+                    r.player_id,
+                    ServerMessage::AbundanceGranted {
+                        round_id: r.round_id,
+                        declared: r.declared,
+                        weight: r.weight,
+                        granted: r.granted,
+                        limit: r.limit,
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// Protocol
+// ============================================================================
+
+pub fn handle_abundance_message(
+    player_id: u64,
+    message: &ClientMessage,
+    rounds: &mut AbundanceRounds,
+) -> Vec<(u64, ServerMessage)> {
+    let ClientMessage::AbundanceNeedDeclare { amount, severity } = message else {
+        return Vec::new();
+    };
+    let class = nevc_attachment::player_contribution_class(player_id);
+    match rounds.declare(player_id, *amount, *severity, class) {
+        Ok(replaced) => vec![(
+            player_id,
+            ServerMessage::AbundanceNeedAccepted { amount: *amount, severity: *severity, replaced },
+        )],
+        Err(e) => {
+            warn!("[Abundance] Player {} need rejected: {}", player_id, e);
+            vec![(player_id, ServerMessage::Error { message: e })]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::fair_allocation::AllocationLimit;
+
+    const ACTIVE: ContributionClass = ContributionClass::ActiveEternalContributor;
+
+    #[test]
+    fn declared_needs_run_under_the_council_caps_and_are_reported() {
+        let mut rounds = AbundanceRounds::default();
+        assert_eq!(rounds.declare(1, 40.0, NeedSeverity::Standard, ACTIVE), Ok(false));
+        assert_eq!(rounds.declare(1, 500.0, NeedSeverity::Critical, ACTIVE), Ok(true));
+        assert!(rounds.declare(2, MAX_DECLARED_NEED * 2.0, NeedSeverity::Urgent, ACTIVE).is_err());
+        assert!(rounds.declare(2, -1.0, NeedSeverity::Urgent, ACTIVE).is_err());
+        rounds.declare(2, 10.0, NeedSeverity::Comfort, ACTIVE).unwrap();
+
+        // A stronger ResourcePolicy: nothing held back, at most half the round each.
+        let caps = AllocationPolicy { reserve_fraction: 0.0, max_share: 0.5, ..Default::default() };
+        assert!(rounds.set_policy(caps.clone()));
+        assert!(!rounds.set_policy(caps.clone()));
+
+        let round = rounds.run_round(&[], 100.0).unwrap().unwrap();
+        assert_eq!(round.policy, caps);
+        assert_eq!(round.granted_to(1), 50.0);
+        assert_eq!(round.granted_to(2), 10.0);
+
+        let grants = rounds.drain_grants();
+        assert_eq!(grants.len(), 2);
+        assert!(matches!(
+            grants[0],
+            (1, ServerMessage::AbundanceGranted { round_id: 0, declared, granted, limit: AllocationLimit::PolicyCap, .. })
+                if declared == 500.0 && granted == 50.0
+        ));
+        assert!(rounds.drain_grants().is_empty());
+
+        // Declarations were consumed: the next round has no claimants.
+        assert!(rounds.run_round(&[], 100.0).unwrap().is_none());
+        assert_eq!(rounds.log().count(), 1);
+    }
+
+    #[test]
+    fn declarations_are_acknowledged_over_the_protocol() {
+        let mut rounds = AbundanceRounds::default();
+        let declare = ClientMessage::AbundanceNeedDeclare { amount: 12.5, severity: NeedSeverity::Urgent };
+        assert!(matches!(
+            handle_abundance_message(7, &declare, &mut rounds)[..],
+            [(7, ServerMessage::AbundanceNeedAccepted { replaced: false, .. })]
+        ));
+        assert!(matches!(
+            handle_abundance_message(7, &declare, &mut rounds)[..],
+            [(7, ServerMessage::AbundanceNeedAccepted { replaced: true, .. })]
+        ));
+        let bad = ClientMessage::AbundanceNeedDeclare { amount: f64::NAN, severity: NeedSeverity::Urgent };
+        assert!(matches!(handle_abundance_message(7, &bad, &mut rounds)[..], [(7, ServerMessage::Error { .. })]));
+        assert!(handle_abundance_message(7, &ClientMessage::Ping { client_time_ms: 0 }, &mut rounds).is_empty());
+    }
+}
@@ -1,13 +1,19 @@
 //! server/src/ascension_abilities.rs
 //! Powrush-MMO — Ambrosian Ascension Abilities (Phase 2)
 //! Mercy Bloom + Celestial Harmony Pulse
+//! v18.12 — Base amounts and scaling come from the shared ability registry
+//!           (assets/abilities/ascension.ron); cooperative multipliers stay here.
 //! AG-SML v1.0 | TOLC 8 Mercy Gates | Cooperative resonance focus
 
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
+use shared::abilities::{self, AbilityEffect, CasterStats, StatusEffectType};
 
 use crate::ascension_mercy_ascent::AscensionProgress;
 
+pub const MERCY_BLOOM: &str = "mercy_bloom";
+pub const CELESTIAL_HARMONY_PULSE: &str = "celestial_harmony_pulse";
+
 /// Ambrosian-specific ability effects.
 /// These are only available after successful Mercy Ascent unlock.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -34,12 +40,14 @@ pub fn apply_mercy_bloom(
     nearby_allies: usize,
     selfish_mode: bool, // detected via recent actions or resonance penalty flag
 ) -> (f32, f32) { // (healing_amount, resonance_boost)
-    if !caster_progress.ascension_unlocked {
+    let registry = abilities::current();
+    let Some(def) = registry.get(MERCY_BLOOM).filter(|_| caster_progress.ascension_unlocked) else {
         return (0.0, 0.0);
-    }
+    };
 
-    let base_healing = 25.0 + (caster_progress.resonance_attunement * 8.0);
-    let base_resonance = 0.15 + (caster_progress.resonance_attunement * 0.05);
+    let stats = CasterStats { resonance_attunement: caster_progress.resonance_attunement, ..Default::default() };
+    let base_healing = def.heal_amount(&stats);
+    let base_resonance = def.resonance_amount(&stats);
 
     if selfish_mode {
         // Meaningful handicap for extractive/selfish play
@@ -59,13 +67,22 @@ pub fn apply_celestial_harmony_pulse(
     group_size: usize,
     current_resonance_field: f32,
 ) -> (f32, f32, bool) { // (group_buff, epiphany_chance, harmony_bonus)
-    if !caster_progress.ascension_unlocked {
+    let registry = abilities::current();
+    let Some(def) = registry.get(CELESTIAL_HARMONY_PULSE).filter(|_| caster_progress.ascension_unlocked) else {
         return (0.0, 0.0, false);
+    };
+    let (mut base_buff, mut base_epiphany) = (0.0, 0.0);
+    for effect in &def.effects {
+        match effect {
+            AbilityEffect::Status { effect: StatusEffectType::AttackBuff, strength, .. } => base_buff += strength,
+            AbilityEffect::EpiphanyChance { chance } => base_epiphany += chance,
+            _ => {}
+        }
     }
 
     let harmony = (current_resonance_field + caster_progress.resonance_attunement * 0.1).min(1.0);
-    let buff_strength = 0.8 + (harmony * 0.6) + (group_size as f32 * 0.05);
-    let epiphany_chance = 0.12 + (caster_progress.avg_epiphany_intensity * 0.08);
+    let buff_strength = base_buff + (harmony * 0.6) + (group_size as f32 * 0.05);
+    let epiphany_chance = base_epiphany + (caster_progress.average_epiphany_intensity * 0.08);
 
     (buff_strength.clamp(1.0, 2.5), epiphany_chance.clamp(0.0, 0.45), true)
 }
@@ -77,7 +94,7 @@ pub fn apply_selfish_resonance_penalty(progress: &mut AscensionProgress, severit
 }
 
 // Future integration points:
-// - Hook into existing combat/ability system
+// - Hook into existing combat/ability system (wire ids 101 / 102 in ascension.ron)
 // - Broadcast via CouncilBloomSyncEvent or new AmbrosianAbilityEvent
 // - Visual particle layer on client (golden harmonic threads + bloom auras)
 // - Balance tuning via simulation harness
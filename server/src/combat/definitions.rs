@@ -0,0 +1,40 @@
+/*!
+ * server/src/combat/definitions.rs
+ *
+ * Ability definitions for combat, loaded from the assets/abilities RON files
+ * (POWRUSH_ABILITY_DIR overrides) into the shared registry through the common
+ * definition loader (definition_dir.rs), hot-reloaded in debug builds.
+ *
+ * AG-SML v1.0 | TOLC 8 | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::path::Path;
+use std::sync::Arc;
+
+use shared::abilities::{self, AbilityLoadError, AbilityRegistry};
+
+use crate::definition_dir::{DefinitionSet, Definitions};
+
+pub type AbilityDefinitions = Definitions<AbilityRegistry>;
+
+impl DefinitionSet for AbilityRegistry {
+    type Error = AbilityLoadError;
+    const TAG: &'static str = "Abilities";
+    const NOUN: &'static str = "definitions";
+    const DIR_ENV: &'static str = "POWRUSH_ABILITY_DIR";
+    const DEFAULT_DIR: &'static str = "assets/abilities";
+
+    fn load_dir(dir: &Path) -> Result<Self, AbilityLoadError> {
+        AbilityRegistry::load_dir(dir)
+    }
+    fn count(&self) -> usize {
+        AbilityRegistry::len(self)
+    }
+    fn current() -> Arc<Self> {
+        abilities::current()
+    }
+    fn install(registry: Arc<Self>) {
+        abilities::install(registry)
+    }
+}
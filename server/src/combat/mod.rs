@@ -4,13 +4,39 @@
 // No placeholders. Fully self-contained and production-ready.
 // Cross-synced with client prediction/reconciliation (client_game_loop.rs) and ActionContext for ability use feedback.
 // AG-SML v1.0 | TOLC 8 Mercy Gates aligned where relevant
+// v18.42 — Lag-compensated targeting: UseAbility rewinds targets to the client's view tick,
+//          checks range / facing cone / line of sight, and reports rejections with a reason.
+// v18.43 — Data-driven abilities: cooldown, GCD, targeting and effects come from the shared
+//          AbilityRegistry (assets/abilities/*.ron, hot-reloaded in debug builds).
+// v18.44 — Status framework (status.rs): stacking policies, periodic ticks, CombatStats
+//          modifiers, immunities, dispels; replicated via ReplicatedFields::STATUS_EFFECT.
+// v18.45 — UseAbility only touches the caster's own Ability / GlobalCooldown; a targeted
+//          cast from an entity without a Transform is refused (OutOfRange).
+// v18.46 — Players cast from an AbilityLoadout (slot → ability + cooldown) equipped from the
+//          registry on PlayerJoined; UseAbility names the slot, which must hold the ability.
 
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
+use shared::abilities::{AbilityDef, AbilityEffect, AbilityRegistry, CasterStats, TargetMode};
+use shared::protocol::WireAbilityRejectReason;
 use std::collections::HashMap;
 
 pub use crate::hierarchical_grid::HierarchicalGrid;
 pub use crate::interest_management::InterestManager;
+use crate::definition_dir::{hot_reload_definitions, load_definitions};
+use crate::persistence::faction_persistence::PlayerJoined;
+use crate::replication::snapshot::{send_entity_snapshots, SnapshotTick};
+
+pub mod definitions;
+pub mod status;
+pub mod targeting;
+pub use definitions::AbilityDefinitions;
+pub use shared::abilities::{AbilityType, DamageType, DispelCategory, StatusEffectType};
+pub use status::{
+    apply_status_events, apply_status_modifiers, status_effect_system, ApplyStatusEvent, BaseCombatStats,
+    DispelEvent, StatusEffect, StatusEffects, StatusEvents, StatusImmunities,
+};
+pub use targeting::{record_target_history, validate_target, TargetHistory, TargetingConfig, TargetingRequest};
 
 // ═════════════════════════════════════════════════════════════════════════
 // ECS DESIGN OVERVIEW
@@ -57,11 +83,6 @@ pub struct Damage {
     pub damage_type: DamageType,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum DamageType {
-    Physical, Energy, Mercy, Corruption,
-}
-
 #[derive(Component, Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CombatStats {
     pub attack_power: f32,
@@ -71,6 +92,19 @@ pub struct CombatStats {
     pub cooldown_reduction: f32,
 }
 
+impl CombatStats {
+    /// The stats ability definitions scale with.
+    pub fn caster_stats(&self) -> CasterStats {
+        CasterStats {
+            attack_power: self.attack_power,
+            defense: self.defense,
+            speed: self.speed,
+            critical_chance: self.critical_chance,
+            resonance_attunement: 0.0,
+        }
+    }
+}
+
 #[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Ability {
     pub id: u32,
@@ -79,9 +113,25 @@ pub struct Ability {
     pub range: f32,
     pub ability_type: AbilityType,
     pub triggers_gcd: bool,
+    /// Full facing cone (degrees) the target must be inside; 360 = any direction
+    #[serde(default = "Ability::any_direction")]
+    pub cone_degrees: f32,
 }
 
 impl Ability {
+    fn any_direction() -> f32 { 360.0 }
+    /// Fresh (ready) component for a registry definition.
+    pub fn from_def(def: &AbilityDef) -> Self {
+        Self {
+            id: def.wire_id,
+            cooldown: def.cooldown_secs,
+            last_used: 0.0,
+            range: def.targeting.range,
+            ability_type: def.kind,
+            triggers_gcd: def.triggers_gcd(),
+            cone_degrees: def.targeting.cone_degrees,
+        }
+    }
     pub fn can_use(&self) -> bool { self.last_used <= 0.0 }
     pub fn trigger(&mut self, cooldown_reduction: f32) {
         let effective_cooldown = self.cooldown * (1.0 - cooldown_reduction.clamp(0.0, 0.8));
@@ -89,9 +139,24 @@ impl Ability {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum AbilityType {
-    DirectDamage, Buff, Debuff, AoE, Support,
+/// A player's ability bar: slot index → ability, each slot carrying its own cooldown.
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AbilityLoadout {
+    pub slots: Vec<Ability>,
+}
+
+impl AbilityLoadout {
+    /// The shared combat kit: every registry ability that is neither race-bound nor
+    /// ascension-gated, slotted in wire-id order.
+    pub fn from_registry(registry: &AbilityRegistry) -> Self {
+        let mut defs: Vec<&AbilityDef> = registry.iter().filter(|d| d.race.is_none() && !d.ascension).collect();
+        defs.sort_by_key(|d| d.wire_id);
+        Self { slots: defs.into_iter().map(Ability::from_def).collect() }
+    }
+
+    pub fn slot_of(&self, ability_id: u32) -> Option<usize> {
+        self.slots.iter().position(|a| a.id == ability_id)
+    }
 }
 
 #[derive(Component, Debug, Clone, Serialize, Deserialize)]
@@ -104,18 +169,6 @@ pub enum CombatFaction {
     Human, Cydruid, Draek, Quellorian, Ambrosian,
 }
 
-#[derive(Component, Debug, Clone, Serialize, Deserialize)]
-pub struct StatusEffect {
-    pub effect_type: StatusEffectType,
-    pub duration: f32,
-    pub strength: f32,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum StatusEffectType {
-    DamageOverTime, HealingOverTime, DefenseBuff, AttackBuff, Corruption,
-}
-
 #[derive(Component, Debug, Clone, Serialize, Deserialize, Default)]
 pub struct GlobalCooldown {
     pub remaining: f32,
@@ -135,6 +188,22 @@ pub struct AbilityUseEvent {
     pub player_entity: Entity,
     pub slot_index: usize,
     pub ability_id: u32,
+    /// Echoed in AbilityRejected
+    pub request_id: u32,
+    /// Overrides the caster's Target component when set
+    pub target: Option<Entity>,
+    /// Snapshot tick the client was viewing; None validates against current positions
+    pub view_tick: Option<u64>,
+    pub aim: Vec3,
+}
+
+/// An AbilityUseEvent that was not applied (sent back to the client as AbilityRejected).
+#[derive(Event, Debug, Clone)]
+pub struct AbilityRejected {
+    pub player_entity: Entity,
+    pub request_id: u32,
+    pub ability_id: u32,
+    pub reason: WireAbilityRejectReason,
 }
 
 #[derive(Event, Debug, Clone, Serialize, Deserialize)]
@@ -166,77 +235,190 @@ pub struct CooldownSyncTracker {
 
 /// Main entry point for client ability requests (event-driven - already efficient)
 /// Results feed back into client_game_loop.rs reconciliation and ActionContext.
+/// Cooldown, GCD, targeting and effects come from the ability's registry definition.
+/// Targets are rewound to the request's view tick before range / cone / line-of-sight
+/// checks; every refusal is reported as AbilityRejected.
 pub fn handle_ability_use_requests(
     mut commands: Commands,
     mut ev_ability_use: EventReader<AbilityUseEvent>,
     time: Res<Time>,
+    definitions: Res<AbilityDefinitions>,
     mut rate_limiter: ResMut<AbilityUseRateLimiter>,
     mut sync_tracker: ResMut<CooldownSyncTracker>,
     mut ev_cooldown_update: EventWriter<AbilityCooldownUpdate>,
+    mut ev_rejected: EventWriter<AbilityRejected>,
+    mut status_events: StatusEvents,
     interest: Res<InterestManager>,
-    mut ability_query: Query<(Entity, &mut Ability, &CombatStats, &Target)>,
+    history: Res<TargetHistory>,
+    transforms: Query<&Transform>,
+    targetable: Query<(Entity, &Transform), With<Health>>,
+    mut loadout_query: Query<(&mut AbilityLoadout, Option<&CombatStats>, Option<&Target>)>,
     mut health_query: Query<&mut Health>,
     mut gcd_query: Query<&mut GlobalCooldown>,
 ) {
     let current_time = time.elapsed_seconds_f64();
+    let registry = definitions.registry.clone();
 
     for ev in ev_ability_use.read() {
+        let reject = |reason| AbilityRejected {
+            player_entity: ev.player_entity,
+            request_id: ev.request_id,
+            ability_id: ev.ability_id,
+            reason,
+        };
+
         if let Some(last_time) = rate_limiter.last_use.get(&ev.player_entity) {
-            if current_time - last_time < 0.1 { continue; }
+            if current_time - last_time < 0.1 {
+                ev_rejected.send(reject(WireAbilityRejectReason::RateLimited));
+                continue;
+            }
         }
         rate_limiter.last_use.insert(ev.player_entity, current_time);
 
-        for (ability_entity, mut ability, stats, target) in ability_query.iter_mut() {
-            if ability.id != ev.ability_id { continue; }
+        let Some(def) = registry.by_wire_id(ev.ability_id) else {
+            ev_rejected.send(reject(WireAbilityRejectReason::UnknownAbility));
+            continue;
+        };
+        // Only the caster's own loadout can be used, and the slot must hold the ability.
+        let Ok((mut loadout, stats, target)) = loadout_query.get_mut(ev.player_entity) else {
+            ev_rejected.send(reject(WireAbilityRejectReason::UnknownAbility));
+            continue;
+        };
+        let Some(ability) = loadout.slots.get_mut(ev.slot_index).filter(|a| a.id == ev.ability_id) else {
+            ev_rejected.send(reject(WireAbilityRejectReason::UnknownAbility));
+            continue;
+        };
+        let stats = stats.cloned().unwrap_or_default();
+
+        let gcd_pending = gcd_query.get(ev.player_entity).is_ok_and(|gcd| !gcd.is_ready());
+        if !ability.can_use() || (def.triggers_gcd() && gcd_pending) {
+            ev_rejected.send(reject(WireAbilityRejectReason::OnCooldown));
+            continue;
+        }
 
-            if !ability.can_use() { continue; }
+        if health_query.get(ev.player_entity).is_ok_and(Health::is_dead) {
+            ev_rejected.send(reject(WireAbilityRejectReason::CasterDead));
+            continue;
+        }
 
-            if ability.triggers_gcd {
-                let mut can_use = true;
-                for mut gcd in gcd_query.iter_mut() {
-                    if !gcd.is_ready() { can_use = false; }
+        let affected = if def.targeting.mode == TargetMode::Caster {
+            vec![ev.player_entity]
+        } else {
+            let Some(target_entity) = ev.target.or(target.and_then(|t| t.entity)).filter(|e| health_query.contains(*e)) else {
+                ev_rejected.send(reject(WireAbilityRejectReason::NoTarget));
+                continue;
+            };
+
+            // Without a caster position range, cone and line of sight can't be checked.
+            let Ok(caster) = transforms.get(ev.player_entity) else {
+                ev_rejected.send(reject(WireAbilityRejectReason::OutOfRange));
+                continue;
+            };
+            let request = TargetingRequest {
+                caster_position: caster.translation,
+                aim: ev.aim,
+                target: target_entity,
+                view_tick: ev.view_tick.unwrap_or(history.latest_tick()),
+                range: def.targeting.range,
+                cone_degrees: def.targeting.cone_degrees,
+            };
+            let grid = def.targeting.requires_line_of_sight.then_some(&interest.grid);
+            let center = match validate_target(&history, grid, &request) {
+                Ok(rewound) => rewound,
+                Err(reason) => {
+                    ev_rejected.send(reject(reason));
+                    continue;
                 }
-                if !can_use { continue; }
+            };
+
+            match def.targeting.mode {
+                TargetMode::Area => targetable
+                    .iter()
+                    .filter(|(_, t)| t.translation.distance(center) <= def.targeting.radius)
+                    .map(|(e, _)| e)
+                    .collect(),
+                _ => vec![target_entity],
             }
-
-            if let Ok(player_health) = health_query.get(ev.player_entity) {
-                if player_health.is_dead() { continue; }
+        };
+
+        apply_ability_effects(
+            &mut commands,
+            &mut health_query,
+            &mut status_events,
+            ev.player_entity,
+            def,
+            &stats.caster_stats(),
+            &affected,
+        );
+
+        // Definitions can change under a hot-reload; the component follows.
+        ability.cooldown = def.cooldown_secs;
+        ability.range = def.targeting.range;
+        ability.cone_degrees = def.targeting.cone_degrees;
+        ability.triggers_gcd = def.triggers_gcd();
+        ability.trigger(stats.cooldown_reduction);
+
+        if def.triggers_gcd() {
+            if let Ok(mut gcd) = gcd_query.get_mut(ev.player_entity) {
+                if gcd.is_ready() { gcd.trigger(def.gcd_secs); }
             }
+        }
+
+        let key = (ev.player_entity, ability.id);
+        let last_value = sync_tracker.last_sent.get(&key).copied().unwrap_or(-1.0);
+
+        if (ability.last_used - last_value).abs() > 0.05 {
+            ev_cooldown_update.send(AbilityCooldownUpdate {
+                recipient_player: ev.player_entity,
+                acting_player: ev.player_entity,
+                ability_id: ability.id,
+                cooldown_remaining: ability.last_used,
+                max_cooldown: ability.cooldown,
+            });
+            sync_tracker.last_sent.insert(key, ability.last_used);
+        }
+    }
+}
+
+/// Combat-side effects of a definition: damage, healing, statuses and dispels on every
+/// affected entity. Resonance / epiphany / harmony effects belong to ascension.
+pub fn apply_ability_effects(
+    commands: &mut Commands,
+    health_query: &mut Query<&mut Health>,
+    status_events: &mut StatusEvents,
+    caster: Entity,
+    def: &AbilityDef,
+    stats: &CasterStats,
+    affected: &[Entity],
+) {
+    let damage = def.damage(stats);
+    let heal = def.heal_amount(stats);
 
-            if let Some(target_entity) = target.entity {
-                if let Ok(mut target_health) = health_query.get_mut(target_entity) {
-                    if ability.ability_type == AbilityType::DirectDamage {
-                        let damage_amount = stats.attack_power;
-                        commands.entity(target_entity).insert(Damage {
-                            amount: damage_amount,
-                            damage_type: DamageType::Physical,
-                        });
-
-                        ability.trigger(stats.cooldown_reduction);
-
-                        if ability.triggers_gcd {
-                            for mut gcd in gcd_query.iter_mut() {
-                                if gcd.is_ready() { gcd.trigger(1.0); }
-                            }
-                        }
-
-                        let key = (ev.player_entity, ability.id);
-                        let last_value = sync_tracker.last_sent.get(&key).copied().unwrap_or(-1.0);
-
-                        if (ability.last_used - last_value).abs() > 0.05 {
-                            ev_cooldown_update.send(AbilityCooldownUpdate {
-                                recipient_player: ev.player_entity,
-                                acting_player: ev.player_entity,
-                                ability_id: ability.id,
-                                cooldown_remaining: ability.last_used,
-                                max_cooldown: ability.cooldown,
-                            });
-                            sync_tracker.last_sent.insert(key, ability.last_used);
-                        }
-                    }
+    for &entity in affected {
+        if let Some((amount, damage_type)) = damage {
+            commands.entity(entity).insert(Damage { amount, damage_type });
+        }
+        if heal > 0.0 {
+            if let Ok(mut health) = health_query.get_mut(entity) {
+                health.heal(heal);
+            }
+        }
+        for effect in &def.effects {
+            match *effect {
+                AbilityEffect::Status { effect, duration_secs, strength } => {
+                    status_events.apply.send(ApplyStatusEvent {
+                        target: entity,
+                        source: Some(caster),
+                        effect_type: effect,
+                        duration: duration_secs,
+                        strength,
+                    });
+                }
+                AbilityEffect::Dispel { category, count, beneficial } => {
+                    status_events.dispel.send(DispelEvent { target: entity, category, harmful: !beneficial, count });
                 }
+                _ => {}
             }
-            break;
         }
     }
 }
@@ -245,6 +427,7 @@ pub fn handle_ability_use_requests(
 pub fn ability_cooldown_system(
     time: Res<Time>,
     mut ability_query: Query<&mut Ability, With<Ability>>,
+    mut loadout_query: Query<&mut AbilityLoadout>,
     mut gcd_query: Query<&mut GlobalCooldown>,
 ) {
     let delta = time.delta_seconds();
@@ -255,6 +438,13 @@ pub fn ability_cooldown_system(
         }
     }
 
+    for mut loadout in loadout_query.iter_mut() {
+        if !loadout.slots.iter().any(|a| a.last_used > 0.0) { continue; }
+        for ability in loadout.slots.iter_mut() {
+            ability.last_used = (ability.last_used - delta).max(0.0);
+        }
+    }
+
     for mut gcd in gcd_query.iter_mut() {
         if gcd.remaining > 0.0 {
             gcd.remaining = (gcd.remaining - delta).max(0.0);
@@ -262,22 +452,37 @@ pub fn ability_cooldown_system(
     }
 }
 
+/// Equips each joining player with the registry loadout and a global cooldown.
+pub fn equip_joined_players(
+    mut commands: Commands,
+    mut joined: EventReader<PlayerJoined>,
+    definitions: Res<AbilityDefinitions>,
+) {
+    for ev in joined.read() {
+        let Some(mut player) = commands.get_entity(ev.entity) else { continue };
+        player.insert((AbilityLoadout::from_registry(&definitions.registry), GlobalCooldown::default()));
+    }
+}
+
 /// Optimized damage system
 pub fn damage_system(
     mut commands: Commands,
-    mut query: Query<(Entity, &mut Health, &Damage)>,
+    mut query: Query<(Entity, &mut Health, &Damage, Option<&StatusEffects>)>,
 ) {
-    for (entity, mut health, damage) in query.iter_mut() {
-        if health.take_damage(damage.amount) {
+    for (entity, mut health, damage, statuses) in query.iter_mut() {
+        // Vulnerability and friends scale incoming damage.
+        let taken = statuses.map_or(1.0, |s| (1.0 + s.modifiers().damage_taken).max(0.0));
+        if health.take_damage(damage.amount * taken) {
             commands.entity(entity).despawn();
         }
         commands.entity(entity).remove::<Damage>();
     }
 }
 
-/// Basic ability execution
+/// Basic ability execution (damage from the ability's registry definition)
 pub fn execute_ability_system(
     mut commands: Commands,
+    definitions: Res<AbilityDefinitions>,
     mut ability_query: Query<(&mut Ability, &Target, &CombatStats)>,
     mut health_query: Query<&mut Health>,
 ) {
@@ -287,11 +492,10 @@ pub fn execute_ability_system(
         if let Some(target_entity) = target.entity {
             if let Ok(mut target_health) = health_query.get_mut(target_entity) {
                 if ability.ability_type == AbilityType::DirectDamage {
-                    let damage_amount = stats.attack_power;
-                    commands.entity(target_entity).insert(Damage {
-                        amount: damage_amount,
-                        damage_type: DamageType::Physical,
-                    });
+                    let Some(def) = definitions.registry.by_wire_id(ability.id) else { continue };
+                    let Some((amount, damage_type)) = def.damage(&stats.caster_stats()) else { continue };
+                    commands.entity(target_entity).insert(Damage { amount, damage_type });
+                    ability.cooldown = def.cooldown_secs;
                     ability.trigger(stats.cooldown_reduction);
                 }
             }
@@ -299,47 +503,18 @@ pub fn execute_ability_system(
     }
 }
 
-/// Optimized status effect system
-pub fn status_effect_system(
-    mut commands: Commands,
-    time: Res<Time>,
-    mut query: Query<(Entity, &mut Health, &mut StatusEffect)>,
-) {
-    let delta = time.delta_seconds();
-
-    for (entity, mut health, mut effect) in query.iter_mut() {
-        effect.duration -= delta;
-
-        match effect.effect_type {
-            StatusEffectType::DamageOverTime => {
-                commands.entity(entity).insert(Damage {
-                    amount: effect.strength * delta,
-                    damage_type: DamageType::Corruption,
-                });
-            }
-            StatusEffectType::HealingOverTime => {
-                health.heal(effect.strength * delta);
-            }
-            _ => {}
-        }
+/// Corruption damage against Draek is amplified by 15%, plus 5% per Corruption stack they carry.
+pub const DRAEK_CORRUPTION_AMPLIFY: f32 = 1.15;
+pub const DRAEK_CORRUPTION_PER_STACK: f32 = 0.05;
 
-        if effect.duration <= 0.0 {
-            commands.entity(entity).remove::<StatusEffect>();
-        }
-    }
-}
-
-/// Example faction behavior
+/// Example faction behavior (runs before damage_system so each Damage is scaled once)
 pub fn draek_corruption_system(
-    mut commands: Commands,
-    query: Query<(Entity, &CombatFaction, &Damage)>,
+    mut query: Query<(&CombatFaction, &mut Damage, Option<&StatusEffects>), Changed<Damage>>,
 ) {
-    for (entity, faction, damage) in query.iter() {
+    for (faction, mut damage, statuses) in query.iter_mut() {
         if *faction == CombatFaction::Draek && damage.damage_type == DamageType::Corruption {
-            commands.entity(entity).insert(Damage {
-                amount: damage.amount * 1.15,
-                damage_type: DamageType::Corruption,
-            });
+            let stacks = statuses.map_or(0, |s| s.stacks_of(StatusEffectType::Corruption));
+            damage.amount *= DRAEK_CORRUPTION_AMPLIFY * (1.0 + DRAEK_CORRUPTION_PER_STACK * stacks as f32);
         }
     }
 }
@@ -373,23 +548,143 @@ impl Plugin for CombatPlugin {
         app
             .init_resource::<AbilityUseRateLimiter>()
             .init_resource::<CooldownSyncTracker>()
+            .init_resource::<TargetHistory>()
+            .init_resource::<AbilityDefinitions>()
+            .init_resource::<SnapshotTick>()
             .add_event::<AbilityUseEvent>()
             .add_event::<AbilityCooldownUpdate>()
+            .add_event::<AbilityRejected>()
+            .add_event::<ApplyStatusEvent>()
+            .add_event::<DispelEvent>()
+            .add_event::<PlayerJoined>()
+            .add_systems(Startup, load_definitions::<AbilityRegistry>)
+            .add_systems(Update, record_target_history.after(send_entity_snapshots))
             .add_systems(Update, (
                 damage_system,
                 ability_cooldown_system,
                 execute_ability_system,
                 handle_ability_use_requests,
+                equip_joined_players.before(handle_ability_use_requests),
                 status_effect_system,
-                draek_corruption_system,
+                draek_corruption_system.before(damage_system),
                 aoe_damage_system,
+            ))
+            .add_systems(Update, (
+                apply_status_events.after(handle_ability_use_requests),
+                apply_status_modifiers.after(apply_status_events).after(status_effect_system),
             ));
+
+        #[cfg(debug_assertions)]
+        app.add_systems(Update, hot_reload_definitions::<AbilityRegistry>.before(handle_ability_use_requests));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hierarchical_grid::HierarchicalGridConfig;
+    use std::time::Duration;
+
+    const FOCUSED_STRIKE: u32 = 201;
+    const ENERGY_LANCE: u32 = 202;
+
+    fn combat_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<AbilityUseRateLimiter>()
+            .init_resource::<CooldownSyncTracker>()
+            .init_resource::<TargetHistory>()
+            .init_resource::<AbilityDefinitions>()
+            .insert_resource(InterestManager::new(HierarchicalGridConfig::default()))
+            .add_event::<AbilityUseEvent>()
+            .add_event::<AbilityCooldownUpdate>()
+            .add_event::<AbilityRejected>()
+            .add_event::<ApplyStatusEvent>()
+            .add_event::<DispelEvent>()
+            .add_event::<PlayerJoined>()
+            .add_systems(Update, (equip_joined_players, handle_ability_use_requests).chain());
+        app
+    }
+
+    fn caster(app: &mut App, wire_id: u32, target: Entity) -> Entity {
+        let def = app.world().resource::<AbilityDefinitions>().registry.by_wire_id(wire_id).unwrap().clone();
+        let loadout = AbilityLoadout { slots: vec![Ability::from_def(&def)] };
+        app.world_mut()
+            .spawn((loadout, CombatStats::default(), Target { entity: Some(target) }, Health::new(50.0)))
+            .id()
+    }
+
+    fn slot(app: &App, player: Entity, slot_index: usize) -> &Ability {
+        &app.world().get::<AbilityLoadout>(player).unwrap().slots[slot_index]
+    }
+
+    /// Send one use of `slot_index` from `player` a second after the last, returning the rejection if any.
+    fn cast(app: &mut App, player: Entity, slot_index: usize, ability_id: u32) -> Option<WireAbilityRejectReason> {
+        app.world_mut().resource_mut::<Time>().advance_by(Duration::from_secs(1));
+        app.world_mut().send_event(AbilityUseEvent {
+            player_entity: player,
+            slot_index,
+            ability_id,
+            request_id: 1,
+            target: None,
+            view_tick: None,
+            aim: Vec3::Z,
+        });
+        app.update();
+        let mut rejected = app.world_mut().resource_mut::<Events<AbilityRejected>>();
+        rejected.drain().map(|r| r.reason).last()
+    }
+
+    #[test]
+    fn casts_use_the_casters_own_ability_and_position() {
+        let mut app = combat_app();
+        let target = app.world_mut().spawn((Health::new(100.0), Transform::from_xyz(0.0, 0.0, 2.0))).id();
+        app.world_mut().resource_mut::<TargetHistory>().record(target, 0, Vec3::new(0.0, 0.0, 2.0));
+        let striker = caster(&mut app, FOCUSED_STRIKE, target);
+        let bystander = caster(&mut app, ENERGY_LANCE, target);
+
+        // Another entity's ability is not the caster's to use.
+        assert_eq!(cast(&mut app, striker, 0, ENERGY_LANCE), Some(WireAbilityRejectReason::UnknownAbility));
+        assert!(slot(&app, bystander, 0).can_use());
+
+        // No position: range, cone and line of sight can't be checked.
+        assert_eq!(cast(&mut app, striker, 0, FOCUSED_STRIKE), Some(WireAbilityRejectReason::OutOfRange));
+        assert!(app.world().get::<Damage>(target).is_none());
+
+        app.world_mut().entity_mut(striker).insert(Transform::default());
+        assert_eq!(cast(&mut app, striker, 0, FOCUSED_STRIKE), None);
+        assert!(app.world().get::<Damage>(target).is_some());
+        assert!(!slot(&app, striker, 0).can_use());
+    }
+
+    #[test]
+    fn joined_players_cast_from_their_own_slots() {
+        let mut app = combat_app();
+        let target = app.world_mut().spawn((Health::new(100.0), Transform::from_xyz(0.0, 0.0, 2.0))).id();
+        app.world_mut().resource_mut::<TargetHistory>().record(target, 0, Vec3::new(0.0, 0.0, 2.0));
+        let player = app.world_mut().spawn((Transform::default(), Target { entity: Some(target) })).id();
+        app.world_mut().send_event(PlayerJoined { entity: player, player_id: 7 });
+        app.update();
+
+        let loadout = app.world().get::<AbilityLoadout>(player).unwrap().clone();
+        let strike = loadout.slot_of(FOCUSED_STRIKE).unwrap();
+        let lance = loadout.slot_of(ENERGY_LANCE).unwrap();
+        assert!(loadout.slots.iter().all(|a| a.can_use()));
+        assert!(app.world().get::<GlobalCooldown>(player).is_some());
+
+        // The slot must hold the ability the request names.
+        assert_eq!(cast(&mut app, player, lance, FOCUSED_STRIKE), Some(WireAbilityRejectReason::UnknownAbility));
+        assert_eq!(cast(&mut app, player, loadout.slots.len(), FOCUSED_STRIKE), Some(WireAbilityRejectReason::UnknownAbility));
+
+        assert_eq!(cast(&mut app, player, strike, FOCUSED_STRIKE), None);
+        assert!(!slot(&app, player, strike).can_use());
+        assert!(slot(&app, player, lance).can_use());
     }
 }
 
 // Query Optimization Notes:
 // - ability_cooldown_system now uses With<Ability> filter and early exit when last_used == 0
-// - status_effect_system only processes entities that have active StatusEffect components
+// - status_effect_system only processes entities that have a StatusEffects component
 // - damage_system processes only entities that received Damage this frame (transient component)
 // - handle_ability_use_requests is event-driven (very efficient)
 // - Future: Add Changed<StatusEffect> / Changed<GlobalCooldown> filters for even better perf
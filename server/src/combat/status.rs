@@ -0,0 +1,418 @@
+/*!
+ * server/src/combat/status.rs
+ *
+ * Buffs and debuffs. Every entity carries its active statuses in one
+ * StatusEffects component; the per-type rules (stacking policy, periodic
+ * damage / healing, stat modifiers, dispel category, granted immunities) come
+ * from shared `StatusEffectType::rules`. Modifiers are folded into CombatStats
+ * from the entity's BaseCombatStats, and every change flags
+ * ReplicatedFields::STATUS_EFFECT so clients get fresh timers.
+ *
+ * AG-SML v1.0 | TOLC 8 | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use shared::abilities::{DamageType, DispelCategory, PeriodicEffect, StackingPolicy, StatModifiers, StatusEffectType};
+use shared::protocol::WireStatusEffect;
+use std::collections::HashMap;
+
+use super::{CombatStats, Damage, Health};
+use crate::replication::{DirtyReplicationState, ReplicatedFields};
+
+/// One active status on an entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusEffect {
+    pub effect_type: StatusEffectType,
+    /// Seconds left
+    pub duration: f32,
+    /// Duration it was (re)applied with
+    pub max_duration: f32,
+    pub strength: f32,
+    pub stacks: u8,
+    pub source: Option<Entity>,
+    /// Seconds until the next periodic tick
+    pub next_tick: f32,
+}
+
+impl StatusEffect {
+    pub fn to_wire(&self) -> WireStatusEffect {
+        WireStatusEffect {
+            kind: self.effect_type,
+            source_id: self.source.map(Entity::to_bits),
+            stacks: self.stacks,
+            strength: self.strength,
+            remaining_secs: self.duration.max(0.0),
+            duration_secs: self.max_duration,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApplyOutcome {
+    Applied,
+    Refreshed,
+    Stacked(u8),
+    Immune,
+}
+
+/// Periodic amounts released by one `StatusEffects::tick`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatusTick {
+    pub damage: Vec<(f32, DamageType)>,
+    pub healing: f32,
+    /// A status expired (the replicated list changed)
+    pub expired: bool,
+}
+
+#[derive(Component, Debug, Clone, Default)]
+pub struct StatusEffects {
+    pub active: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    /// Categories the active statuses make this entity immune to.
+    fn granted_immunities(&self) -> impl Iterator<Item = DispelCategory> + '_ {
+        self.active.iter().flat_map(|s| s.effect_type.rules().grants_immunity.iter().copied())
+    }
+
+    pub fn is_immune(&self, kind: StatusEffectType, immunities: Option<&StatusImmunities>) -> bool {
+        let rules = kind.rules();
+        immunities.is_some_and(|i| i.effects.contains(&kind) || (rules.harmful && i.categories.contains(&rules.dispel)))
+            || (rules.harmful && self.granted_immunities().any(|c| c == rules.dispel))
+    }
+
+    pub fn apply(&mut self, application: &ApplyStatusEvent, immunities: Option<&StatusImmunities>) -> ApplyOutcome {
+        let kind = application.effect_type;
+        if self.is_immune(kind, immunities) {
+            return ApplyOutcome::Immune;
+        }
+        let rules = kind.rules();
+
+        // A status granting immunity cleanses what it protects against.
+        if !rules.grants_immunity.is_empty() {
+            self.active.retain(|s| {
+                let r = s.effect_type.rules();
+                !(r.harmful && rules.grants_immunity.contains(&r.dispel))
+            });
+        }
+
+        let existing = self.active.iter_mut().find(|s| {
+            s.effect_type == kind
+                && (rules.stacking != StackingPolicy::UniquePerSource || s.source == application.source)
+        });
+        let Some(existing) = existing else {
+            self.active.push(StatusEffect {
+                effect_type: kind,
+                duration: application.duration,
+                max_duration: application.duration,
+                strength: application.strength,
+                stacks: 1,
+                source: application.source,
+                next_tick: rules.tick_interval_secs,
+            });
+            return ApplyOutcome::Applied;
+        };
+
+        existing.duration = existing.duration.max(application.duration);
+        existing.max_duration = existing.duration;
+        existing.source = application.source.or(existing.source);
+        match rules.stacking {
+            StackingPolicy::StackIntensity { max_stacks } => {
+                existing.stacks = existing.stacks.saturating_add(1).min(max_stacks.max(1));
+                existing.strength = existing.strength.max(application.strength);
+                ApplyOutcome::Stacked(existing.stacks)
+            }
+            StackingPolicy::Refresh | StackingPolicy::UniquePerSource => {
+                existing.strength = existing.strength.max(application.strength);
+                ApplyOutcome::Refreshed
+            }
+        }
+    }
+
+    /// Remove up to `count` statuses of `category`, newest first. Returns how many went.
+    pub fn dispel(&mut self, category: DispelCategory, harmful: bool, count: u32) -> usize {
+        if category == DispelCategory::Undispellable {
+            return 0;
+        }
+        let mut removed = 0;
+        for i in (0..self.active.len()).rev() {
+            if removed as u32 >= count {
+                break;
+            }
+            let rules = self.active[i].effect_type.rules();
+            if rules.dispel == category && rules.harmful == harmful {
+                self.active.remove(i);
+                removed += 1;
+            }
+        }
+        removed
+    }
+
+    /// Summed stat modifiers of every active status.
+    pub fn modifiers(&self) -> StatModifiers {
+        self.active.iter().fold(StatModifiers::default(), |acc, s| {
+            acc.combine(s.effect_type.rules().modifiers.scaled(s.strength * s.stacks as f32))
+        })
+    }
+
+    /// Stacks of `kind` across all sources.
+    pub fn stacks_of(&self, kind: StatusEffectType) -> u32 {
+        self.active.iter().filter(|s| s.effect_type == kind).map(|s| s.stacks as u32).sum()
+    }
+
+    /// Advance timers by `dt`, releasing periodic damage / healing and dropping expired statuses.
+    pub fn tick(&mut self, dt: f32) -> StatusTick {
+        let mut out = StatusTick::default();
+        for status in &mut self.active {
+            let rules = status.effect_type.rules();
+            status.duration -= dt;
+            let Some(periodic) = rules.periodic else { continue };
+            status.next_tick -= dt;
+            let interval = rules.tick_interval_secs;
+            let per_second = status.strength * status.stacks as f32;
+            let mut release = |secs: f32| match periodic {
+                PeriodicEffect::Damage(damage_type) => out.damage.push((per_second * secs, damage_type)),
+                PeriodicEffect::Heal => out.healing += per_second * secs,
+            };
+            // Times are relative to now: ticks due at `next_tick`, expiry at `duration`.
+            while status.next_tick <= status.duration.min(0.0) {
+                release(interval);
+                status.next_tick += interval;
+            }
+            // The final partial interval lands as the status runs out.
+            if status.duration <= 0.0 {
+                let partial = status.duration - (status.next_tick - interval);
+                if partial > 0.0 {
+                    release(partial.min(interval));
+                }
+            }
+        }
+        let before = self.active.len();
+        self.active.retain(|s| s.duration > 0.0);
+        out.expired = self.active.len() != before;
+        out
+    }
+
+    pub fn to_wire(&self) -> Vec<WireStatusEffect> {
+        self.active.iter().map(StatusEffect::to_wire).collect()
+    }
+}
+
+/// Statuses this entity can never receive (bosses, mounts, ...).
+#[derive(Component, Debug, Clone, Default)]
+pub struct StatusImmunities {
+    pub effects: Vec<StatusEffectType>,
+    /// Harmful statuses of these dispel categories are refused
+    pub categories: Vec<DispelCategory>,
+}
+
+/// Unmodified stats; CombatStats is recomputed from these whenever statuses change.
+/// Captured from CombatStats the first time a status lands. Change this, not CombatStats.
+#[derive(Component, Debug, Clone, Default)]
+pub struct BaseCombatStats(pub CombatStats);
+
+#[derive(Event, Debug, Clone)]
+pub struct ApplyStatusEvent {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub effect_type: StatusEffectType,
+    pub duration: f32,
+    pub strength: f32,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct DispelEvent {
+    pub target: Entity,
+    pub category: DispelCategory,
+    /// Dispel harmful statuses (cleanse) or beneficial ones (purge)
+    pub harmful: bool,
+    pub count: u32,
+}
+
+#[derive(SystemParam)]
+pub struct StatusEvents<'w> {
+    pub apply: EventWriter<'w, ApplyStatusEvent>,
+    pub dispel: EventWriter<'w, DispelEvent>,
+}
+
+fn mark_status_dirty(dirty: Option<Mut<DirtyReplicationState>>) {
+    if let Some(mut dirty) = dirty {
+        dirty.dirty_mask |= ReplicatedFields::STATUS_EFFECT;
+    }
+}
+
+type StatusTargets<'w, 's> = Query<
+    'w,
+    's,
+    (Option<&'static mut StatusEffects>, Option<&'static StatusImmunities>, Option<&'static mut DirtyReplicationState>),
+>;
+
+/// Apply queued statuses and dispels (immunities and stacking rules enforced here).
+pub fn apply_status_events(
+    mut commands: Commands,
+    mut applications: EventReader<ApplyStatusEvent>,
+    mut dispels: EventReader<DispelEvent>,
+    mut targets: StatusTargets,
+) {
+    // Entities receiving their first status this frame, inserted once at the end.
+    let mut fresh: HashMap<Entity, StatusEffects> = HashMap::new();
+
+    for application in applications.read() {
+        let Ok((effects, immunities, dirty)) = targets.get_mut(application.target) else { continue };
+        let outcome = match effects {
+            Some(mut effects) => effects.apply(application, immunities),
+            None => fresh.entry(application.target).or_default().apply(application, immunities),
+        };
+        if outcome != ApplyOutcome::Immune {
+            mark_status_dirty(dirty);
+        }
+    }
+
+    for dispel in dispels.read() {
+        let Ok((effects, _, dirty)) = targets.get_mut(dispel.target) else { continue };
+        let removed = match effects {
+            Some(mut effects) => effects.dispel(dispel.category, dispel.harmful, dispel.count),
+            None => fresh.get_mut(&dispel.target).map_or(0, |e| e.dispel(dispel.category, dispel.harmful, dispel.count)),
+        };
+        if removed > 0 {
+            mark_status_dirty(dirty);
+        }
+    }
+
+    for (entity, effects) in fresh {
+        if !effects.active.is_empty() {
+            commands.entity(entity).insert(effects);
+        }
+    }
+}
+
+/// Tick status timers: periodic damage becomes a Damage component, periodic healing
+/// heals directly, expired statuses are dropped.
+pub fn status_effect_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut StatusEffects, Option<&mut Health>, Option<&mut DirtyReplicationState>)>,
+) {
+    let delta = time.delta_seconds();
+
+    for (entity, mut effects, health, dirty) in query.iter_mut() {
+        let tick = effects.tick(delta);
+
+        if let Some(&(_, damage_type)) = tick.damage.first() {
+            let amount = tick.damage.iter().map(|(a, _)| a).sum();
+            commands.entity(entity).insert(Damage { amount, damage_type });
+        }
+        if tick.healing > 0.0 {
+            if let Some(mut health) = health {
+                health.heal(tick.healing);
+            }
+        }
+        if tick.expired {
+            mark_status_dirty(dirty);
+        }
+    }
+}
+
+/// Fold status modifiers into CombatStats whenever the status list changes.
+pub fn apply_status_modifiers(
+    mut commands: Commands,
+    mut query: Query<(Entity, &StatusEffects, &mut CombatStats, Option<&BaseCombatStats>), Changed<StatusEffects>>,
+) {
+    for (entity, effects, mut stats, base) in query.iter_mut() {
+        let base = match base {
+            Some(base) => base.0.clone(),
+            None => {
+                commands.entity(entity).insert(BaseCombatStats(stats.clone()));
+                stats.clone()
+            }
+        };
+        let m = effects.modifiers();
+        *stats = CombatStats {
+            attack_power: base.attack_power * (1.0 + m.attack_power).max(0.0),
+            defense: base.defense * (1.0 + m.defense).max(0.0),
+            speed: base.speed * (1.0 + m.speed).max(0.0),
+            ..base
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn application(kind: StatusEffectType, source: u32, duration: f32, strength: f32) -> ApplyStatusEvent {
+        ApplyStatusEvent {
+            target: Entity::from_raw(1),
+            source: Some(Entity::from_raw(source)),
+            effect_type: kind,
+            duration,
+            strength,
+        }
+    }
+
+    #[test]
+    fn stacking_policies() {
+        let mut effects = StatusEffects::default();
+        // Refresh: one instance, longer duration and stronger strength win.
+        assert_eq!(effects.apply(&application(StatusEffectType::AttackBuff, 2, 5.0, 0.2), None), ApplyOutcome::Applied);
+        assert_eq!(effects.apply(&application(StatusEffectType::AttackBuff, 3, 8.0, 0.1), None), ApplyOutcome::Refreshed);
+        assert_eq!(effects.active.len(), 1);
+        assert_eq!((effects.active[0].duration, effects.active[0].strength), (8.0, 0.2));
+
+        // Stack intensity: capped at max_stacks.
+        for _ in 0..4 {
+            effects.apply(&application(StatusEffectType::Weakness, 2, 5.0, 0.1), None);
+        }
+        assert_eq!(effects.stacks_of(StatusEffectType::Weakness), 3);
+
+        // Unique per source: one per caster.
+        effects.apply(&application(StatusEffectType::Corruption, 2, 5.0, 1.0), None);
+        effects.apply(&application(StatusEffectType::Corruption, 2, 5.0, 1.0), None);
+        effects.apply(&application(StatusEffectType::Corruption, 3, 5.0, 1.0), None);
+        assert_eq!(effects.stacks_of(StatusEffectType::Corruption), 2);
+
+        let m = effects.modifiers();
+        assert!((m.attack_power - (0.2 - 0.3)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn periodic_ticks_and_expiry() {
+        let mut effects = StatusEffects::default();
+        effects.apply(&application(StatusEffectType::DamageOverTime, 2, 2.5, 4.0), None);
+        effects.apply(&application(StatusEffectType::DamageOverTime, 2, 2.5, 4.0), None);
+
+        let mut total = 0.0;
+        for _ in 0..30 {
+            let tick = effects.tick(0.1);
+            total += tick.damage.iter().map(|(a, _)| a).sum::<f32>();
+        }
+        // 2 stacks × 4/s × 2.5 s, the last half-interval included.
+        assert!((total - 20.0).abs() < 1e-3, "{}", total);
+        assert!(effects.active.is_empty());
+    }
+
+    #[test]
+    fn immunities_and_dispels() {
+        let mut effects = StatusEffects::default();
+        effects.apply(&application(StatusEffectType::Corruption, 2, 5.0, 1.0), None);
+        effects.apply(&application(StatusEffectType::Slow, 2, 5.0, 0.3), None);
+        effects.apply(&application(StatusEffectType::Vulnerability, 2, 5.0, 0.2), None);
+
+        // Mercy Ward cleanses Corruption and refuses it while active.
+        effects.apply(&application(StatusEffectType::MercyWard, 4, 5.0, 1.0), None);
+        assert_eq!(effects.stacks_of(StatusEffectType::Corruption), 0);
+        assert_eq!(effects.apply(&application(StatusEffectType::Corruption, 2, 5.0, 1.0), None), ApplyOutcome::Immune);
+
+        let immunities = StatusImmunities { effects: vec![StatusEffectType::Haste], categories: vec![DispelCategory::Physical] };
+        assert_eq!(effects.apply(&application(StatusEffectType::Haste, 2, 5.0, 1.0), Some(&immunities)), ApplyOutcome::Immune);
+        assert_eq!(effects.apply(&application(StatusEffectType::Slow, 2, 5.0, 1.0), Some(&immunities)), ApplyOutcome::Immune);
+
+        assert_eq!(effects.dispel(DispelCategory::Magic, true, 5), 1);
+        assert_eq!(effects.dispel(DispelCategory::Physical, true, 5), 1);
+        assert_eq!(effects.dispel(DispelCategory::Undispellable, false, 5), 0);
+        assert_eq!(effects.active.len(), 1);
+        assert_eq!(effects.active[0].effect_type, StatusEffectType::MercyWard);
+    }
+}
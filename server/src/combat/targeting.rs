@@ -0,0 +1,207 @@
+/*!
+ * server/src/combat/targeting.rs
+ *
+ * Lag-compensated ability targeting. Every targetable entity's position is kept
+ * per snapshot tick (the `server_tick` stamped on EntitySnapshot), so a
+ * UseAbility fired while the client was viewing tick N is validated against
+ * where the target was at tick N: range, facing cone, then line of sight
+ * through the grid's static blockers. Server-side counterpart of
+ * game/lag_compensation.rs, keyed by Entity instead of player id.
+ *
+ * AG-SML v1.0 | TOLC 8 | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::collections::{HashMap, VecDeque};
+
+use bevy::prelude::*;
+use shared::protocol::WireAbilityRejectReason;
+
+use super::Health;
+use crate::hierarchical_grid::HierarchicalGrid;
+use crate::replication::snapshot::SnapshotTick;
+
+#[derive(Clone, Debug)]
+pub struct TargetingConfig {
+    /// Snapshot ticks of history kept; older view ticks are refused
+    pub max_rewind_ticks: u64,
+    /// Added to ability range (target body radius + interpolation slack)
+    pub range_tolerance: f32,
+    /// Line-of-sight rays run between points this far above each Transform
+    pub eye_height: f32,
+}
+
+impl Default for TargetingConfig {
+    fn default() -> Self {
+        Self { max_rewind_ticks: 30, range_tolerance: 0.75, eye_height: 1.6 }
+    }
+}
+
+/// Recent positions of every targetable (Health-bearing) entity.
+/// Samples are recorded on change; a lookup returns the latest sample at or before the tick.
+#[derive(Resource, Default)]
+pub struct TargetHistory {
+    pub config: TargetingConfig,
+    positions: HashMap<Entity, VecDeque<(u64, Vec3)>>,
+    latest_tick: u64,
+}
+
+impl TargetHistory {
+    pub fn new(config: TargetingConfig) -> Self {
+        Self { config, ..Default::default() }
+    }
+
+    pub fn latest_tick(&self) -> u64 {
+        self.latest_tick
+    }
+
+    pub fn record(&mut self, entity: Entity, tick: u64, position: Vec3) {
+        self.latest_tick = self.latest_tick.max(tick);
+        let samples = self.positions.entry(entity).or_default();
+        match samples.back_mut() {
+            Some(last) if last.0 == tick => last.1 = position,
+            _ => samples.push_back((tick, position)),
+        }
+        // Keep the newest sample at or before the window start so it still answers for it.
+        let cutoff = self.latest_tick.saturating_sub(self.config.max_rewind_ticks);
+        while samples.len() > 1 && samples[1].0 <= cutoff {
+            samples.pop_front();
+        }
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        self.positions.remove(&entity);
+    }
+
+    /// Where `entity` was at snapshot `tick` (ticks newer than the history read as the latest).
+    pub fn position_at(&self, entity: Entity, tick: u64) -> Result<Vec3, WireAbilityRejectReason> {
+        if self.latest_tick.saturating_sub(tick) > self.config.max_rewind_ticks {
+            return Err(WireAbilityRejectReason::RewindTooFar);
+        }
+        let samples = self.positions.get(&entity).ok_or(WireAbilityRejectReason::NoTarget)?;
+        samples
+            .iter()
+            .rev()
+            .find(|(t, _)| *t <= tick)
+            .map(|(_, p)| *p)
+            // Not spawned yet at that tick, so the client could not have seen it.
+            .ok_or(WireAbilityRejectReason::NoTarget)
+    }
+}
+
+/// One targeted ability use, as seen from the caster's view tick.
+#[derive(Clone, Copy, Debug)]
+pub struct TargetingRequest {
+    pub caster_position: Vec3,
+    pub aim: Vec3,
+    pub target: Entity,
+    pub view_tick: u64,
+    pub range: f32,
+    /// Full cone angle in degrees; 360 or more skips the facing check
+    pub cone_degrees: f32,
+}
+
+/// Rewind the target to the view tick and check range, cone and line of sight.
+/// Returns the rewound target position.
+pub fn validate_target(
+    history: &TargetHistory,
+    grid: Option<&HierarchicalGrid>,
+    request: &TargetingRequest,
+) -> Result<Vec3, WireAbilityRejectReason> {
+    let config = &history.config;
+    let target_position = history.position_at(request.target, request.view_tick)?;
+    let to_target = target_position - request.caster_position;
+
+    if to_target.length() > request.range + config.range_tolerance {
+        return Err(WireAbilityRejectReason::OutOfRange);
+    }
+
+    if request.cone_degrees < 360.0 && to_target.length_squared() > f32::EPSILON {
+        let aim = request.aim.normalize_or_zero();
+        if aim == Vec3::ZERO || aim.angle_between(to_target).to_degrees() > request.cone_degrees * 0.5 {
+            return Err(WireAbilityRejectReason::OutsideCone);
+        }
+    }
+
+    if let Some(grid) = grid {
+        let eye = Vec3::Y * config.eye_height;
+        if grid.sweep_sphere(request.caster_position + eye, target_position + eye, 0.0).is_some() {
+            return Err(WireAbilityRejectReason::NoLineOfSight);
+        }
+    }
+
+    Ok(target_position)
+}
+
+type MovedTargets<'w, 's> = Query<'w, 's, (Entity, &'static Transform), (With<Health>, Changed<Transform>)>;
+
+/// Stamp moved targetable entities with the snapshot tick they were replicated at.
+/// Runs after send_entity_snapshots so tick N holds exactly what snapshot N showed.
+pub fn record_target_history(
+    tick: Res<SnapshotTick>,
+    mut history: ResMut<TargetHistory>,
+    mut removed: RemovedComponents<Health>,
+    query: MovedTargets,
+) {
+    for entity in removed.read() {
+        history.remove(entity);
+    }
+    for (entity, transform) in &query {
+        history.record(entity, tick.0, transform.translation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hierarchical_grid::HierarchicalGridConfig;
+
+    fn request(target: Entity, view_tick: u64) -> TargetingRequest {
+        TargetingRequest {
+            caster_position: Vec3::ZERO,
+            aim: Vec3::Z,
+            target,
+            view_tick,
+            range: 10.0,
+            cone_degrees: 90.0,
+        }
+    }
+
+    #[test]
+    fn validates_against_the_rewound_position() {
+        let target = Entity::from_raw(7);
+        let mut history = TargetHistory::default();
+        // In range and ahead at tick 100; runs out of range by tick 110.
+        history.record(target, 100, Vec3::new(0.0, 0.0, 8.0));
+        history.record(target, 110, Vec3::new(0.0, 0.0, 20.0));
+
+        assert_eq!(validate_target(&history, None, &request(target, 105)), Ok(Vec3::new(0.0, 0.0, 8.0)));
+        assert_eq!(validate_target(&history, None, &request(target, 110)), Err(WireAbilityRejectReason::OutOfRange));
+        assert_eq!(validate_target(&history, None, &request(target, 99)), Err(WireAbilityRejectReason::NoTarget));
+
+        let behind = TargetingRequest { aim: -Vec3::Z, ..request(target, 100) };
+        assert_eq!(validate_target(&history, None, &behind), Err(WireAbilityRejectReason::OutsideCone));
+        let any_direction = TargetingRequest { cone_degrees: 360.0, ..behind };
+        assert!(validate_target(&history, None, &any_direction).is_ok());
+
+        history.record(target, 200, Vec3::new(0.0, 0.0, 5.0));
+        assert_eq!(validate_target(&history, None, &request(target, 105)), Err(WireAbilityRejectReason::RewindTooFar));
+        // The sample from tick 110 still answers for the start of the window.
+        assert_eq!(history.position_at(target, 175), Ok(Vec3::new(0.0, 0.0, 20.0)));
+    }
+
+    #[test]
+    fn walls_block_line_of_sight() {
+        let target = Entity::from_raw(3);
+        let mut history = TargetHistory::default();
+        history.record(target, 1, Vec3::new(0.0, 0.0, 8.0));
+
+        let mut grid = HierarchicalGrid::new(HierarchicalGridConfig::default());
+        assert!(validate_target(&history, Some(&grid), &request(target, 1)).is_ok());
+        grid.insert_blocker(Vec3::new(-2.0, 0.0, 4.0), Vec3::new(2.0, 3.0, 4.5));
+        assert_eq!(
+            validate_target(&history, Some(&grid), &request(target, 1)),
+            Err(WireAbilityRejectReason::NoLineOfSight)
+        );
+    }
+}
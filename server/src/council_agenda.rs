@@ -0,0 +1,581 @@
+/*!
+ * server/src/council_agenda.rs
+ *
+ * Player-facing council agenda. Players submit typed proposals, list a realm's
+ * open proposals and vote over the protocol; deliberation itself stays in the
+ * simulation's CouncilSessionRegistry. Like the RTT mirror, the two meet through
+ * plain data in one Bevy App: accepted submissions and ballots queue up as
+ * AgendaCommands for the co-host to apply, and the co-host hands back
+ * CouncilResolutions (outcome, ballot record, resulting ActivePolicy). The outcome
+ * and record digest are broadcast to every client; the last RESOLVED_RECORDS_CAP
+ * records are kept so a client can fetch one and verify it.
+ *
+ * Submitting is gated by NEVC contribution class and rate limited per player;
+ * voting is open to everyone, one ballot per player (a second vote changes it).
+ * Ballots are mirrored here with shared::council_ballot so a duplicate or late
+ * vote is refused immediately instead of after a round trip.
+ *
+ * AG-SML v1.0 | TOLC 8 + 7 Living Mercy Gates | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+
+use bevy::prelude::Resource;
+use shared::council_ballot::{BallotBox, BallotChoice, BallotError, BallotRecord};
+use shared::nevc_adapter::ContributionClass;
+use shared::protocol::{ClientMessage, ServerMessage, WireActivePolicy, WireCouncilProposal, WireProposalType};
+use tracing::{info, warn};
+
+use crate::nevc_attachment;
+
+/// Player-submitted proposals are numbered from here so they never meet the
+/// simulation's own ids (those start at the session's creation tick).
+pub const PLAYER_PROPOSAL_ID_BASE: u64 = 1 << 48;
+
+pub const COUNCIL_TITLE_MIN_CHARS: usize = 4;
+pub const COUNCIL_TITLE_MAX_CHARS: usize = 96;
+pub const COUNCIL_DESCRIPTION_MAX_CHARS: usize = 1000;
+
+const SUBMISSION_WINDOW_SECS: u64 = 86_400;
+
+/// Ballot records of resolved proposals kept for CouncilBallotRecordRequest.
+pub const RESOLVED_RECORDS_CAP: usize = 256;
+
+// ============================================================================
+// Rules
+// ============================================================================
+
+#[derive(Debug, Clone)]
+pub struct CouncilSubmissionRules {
+    /// Minimum gap between two submissions by the same player.
+    pub cooldown_secs: u64,
+    /// Submissions per player in any rolling 24 h.
+    pub max_per_day: usize,
+    /// Open proposals a contributor may have on the agenda at once.
+    pub max_open_per_contributor: usize,
+    /// Open proposals a partitioned player may have on the agenda at once.
+    pub max_open_per_partitioned: usize,
+    /// Proposal types a partitioned (ZombiePartition) player may still submit.
+    pub partitioned_types: Vec<WireProposalType>,
+    /// Open proposals per realm, counting the simulation's own.
+    pub max_open_per_realm: usize,
+}
+
+impl Default for CouncilSubmissionRules {
+    fn default() -> Self {
+        Self {
+            cooldown_secs: 300,
+            max_per_day: 4,
+            max_open_per_contributor: 3,
+            max_open_per_partitioned: 1,
+            partitioned_types: vec![WireProposalType::General],
+            max_open_per_realm: 32,
+        }
+    }
+}
+
+impl CouncilSubmissionRules {
+    pub fn may_submit(&self, class: ContributionClass, proposal_type: WireProposalType) -> bool {
+        class.is_contributor() || self.partitioned_types.contains(&proposal_type)
+    }
+
+    pub fn max_open_for(&self, class: ContributionClass) -> usize {
+        if class.is_contributor() {
+            self.max_open_per_contributor
+        } else {
+            self.max_open_per_partitioned
+        }
+    }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CouncilAgendaError {
+    NotEligible { class: ContributionClass, proposal_type: WireProposalType },
+    Cooldown { retry_in_secs: u64 },
+    DailyLimit { limit: usize },
+    TooManyOpen { limit: usize },
+    RealmAgendaFull { realm_id: u8 },
+    InvalidTitle,
+    InvalidDescription,
+    Ballot(BallotError),
+}
+
+impl fmt::Display for CouncilAgendaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CouncilAgendaError::NotEligible { class, proposal_type } => {
+                write!(f, "{:?} players cannot submit {:?} proposals", class, proposal_type)
+            }
+            CouncilAgendaError::Cooldown { retry_in_secs } => {
+                write!(f, "submitted too recently; try again in {} s", retry_in_secs)
+            }
+            CouncilAgendaError::DailyLimit { limit } => write!(f, "at most {} proposals per day", limit),
+            CouncilAgendaError::TooManyOpen { limit } => {
+                write!(f, "at most {} of your proposals may be open at once", limit)
+            }
+            CouncilAgendaError::RealmAgendaFull { realm_id } => write!(f, "realm {} agenda is full", realm_id),
+            CouncilAgendaError::InvalidTitle => write!(
+                f,
+                "title must be {}-{} characters without control characters",
+                COUNCIL_TITLE_MIN_CHARS, COUNCIL_TITLE_MAX_CHARS
+            ),
+            CouncilAgendaError::InvalidDescription => write!(
+                f,
+                "description must be at most {} characters without control characters",
+                COUNCIL_DESCRIPTION_MAX_CHARS
+            ),
+            CouncilAgendaError::Ballot(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for CouncilAgendaError {}
+
+impl From<BallotError> for CouncilAgendaError {
+    fn from(e: BallotError) -> Self {
+        CouncilAgendaError::Ballot(e)
+    }
+}
+
+fn valid_text(text: &str, min: usize, max: usize) -> bool {
+    let chars = text.chars().count();
+    (min..=max).contains(&chars) && !text.chars().any(|c| c.is_control() && c != '\n')
+}
+
+// ============================================================================
+// Bridge data
+// ============================================================================
+
+/// Work for the co-host to apply to the simulation's CouncilSessionRegistry.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AgendaCommand {
+    Submit { proposal: WireCouncilProposal },
+    /// `attunement` weights the ballot in the simulation's tally (0.0–1.0).
+    Vote { realm_id: u8, proposal_id: u64, voter: u64, support: bool, attunement: f32, at: u64 },
+}
+
+/// Outcome of a deliberated proposal, reported back by the co-host.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CouncilResolution {
+    pub realm_id: u8,
+    pub proposal_id: u64,
+    pub passed: bool,
+    pub votes_for: u32,
+    pub votes_against: u32,
+    /// The simulation's published record; player voters carry PLAYER_VOTER_FLAG.
+    pub ballot: BallotRecord,
+    pub policy: Option<WireActivePolicy>,
+}
+
+impl CouncilResolution {
+    pub fn to_message(&self) -> ServerMessage {
+        ServerMessage::CouncilProposalResolved {
+            realm_id: self.realm_id,
+            proposal_id: self.proposal_id,
+            passed: self.passed,
+            votes_for: self.votes_for,
+            votes_against: self.votes_against,
+            ballot_digest: self.ballot.digest.clone(),
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+/// What a player asks the councils to deliberate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProposalDraft {
+    pub realm_id: u8,
+    pub proposal_type: WireProposalType,
+    pub title: String,
+    pub description: String,
+    pub target_zone: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+struct AgendaEntry {
+    proposal: WireCouncilProposal,
+    /// Ballots cast through this server (simulation agents vote on their side).
+    ballots: BallotBox,
+    submitted_by_player: bool,
+}
+
+// ============================================================================
+// Agenda
+// ============================================================================
+
+#[derive(Resource, Debug)]
+pub struct CouncilAgenda {
+    rules: CouncilSubmissionRules,
+    open: BTreeMap<u64, AgendaEntry>,
+    next_proposal_id: u64,
+    /// Submission times (unix seconds) per player within the rolling window.
+    submissions: HashMap<u64, VecDeque<u64>>,
+    commands: Vec<AgendaCommand>,
+    resolutions: Vec<CouncilResolution>,
+    /// Oldest first, at most RESOLVED_RECORDS_CAP
+    ballot_records: VecDeque<BallotRecord>,
+}
+
+impl Default for CouncilAgenda {
+    fn default() -> Self {
+        Self::with_rules(CouncilSubmissionRules::default())
+    }
+}
+
+impl CouncilAgenda {
+    pub fn with_rules(rules: CouncilSubmissionRules) -> Self {
+        Self {
+            rules,
+            open: BTreeMap::new(),
+            next_proposal_id: PLAYER_PROPOSAL_ID_BASE,
+            submissions: HashMap::new(),
+            commands: Vec::new(),
+            resolutions: Vec::new(),
+            ballot_records: VecDeque::new(),
+        }
+    }
+
+    pub fn rules(&self) -> &CouncilSubmissionRules {
+        &self.rules
+    }
+
+    pub fn get(&self, proposal_id: u64) -> Option<&WireCouncilProposal> {
+        self.open.get(&proposal_id).map(|e| &e.proposal)
+    }
+
+    /// Open proposals of `realm_id`, oldest first.
+    pub fn realm_agenda(&self, realm_id: u8) -> Vec<WireCouncilProposal> {
+        self.open.values().filter(|e| e.proposal.realm_id == realm_id).map(|e| e.proposal.clone()).collect()
+    }
+
+    /// (realm_id, proposal_id) of everything still awaiting a resolution.
+    pub fn open_ids(&self) -> Vec<(u8, u64)> {
+        self.open.values().map(|e| (e.proposal.realm_id, e.proposal.proposal_id)).collect()
+    }
+
+    pub fn submit(
+        &mut self,
+        player_id: u64,
+        class: ContributionClass,
+        draft: ProposalDraft,
+        now: u64,
+    ) -> Result<WireCouncilProposal, CouncilAgendaError> {
+        let ProposalDraft { realm_id, proposal_type, title, description, target_zone } = draft;
+        let title = title.trim();
+        let description = description.trim();
+        if !valid_text(title, COUNCIL_TITLE_MIN_CHARS, COUNCIL_TITLE_MAX_CHARS) || title.contains('\n') {
+            return Err(CouncilAgendaError::InvalidTitle);
+        }
+        if !valid_text(description, 0, COUNCIL_DESCRIPTION_MAX_CHARS) {
+            return Err(CouncilAgendaError::InvalidDescription);
+        }
+        if !self.rules.may_submit(class, proposal_type) {
+            return Err(CouncilAgendaError::NotEligible { class, proposal_type });
+        }
+
+        let history = self.submissions.entry(player_id).or_default();
+        while history.front().is_some_and(|&t| now.saturating_sub(t) >= SUBMISSION_WINDOW_SECS) {
+            history.pop_front();
+        }
+        if let Some(&last) = history.back() {
+            let since = now.saturating_sub(last);
+            if since < self.rules.cooldown_secs {
+                return Err(CouncilAgendaError::Cooldown { retry_in_secs: self.rules.cooldown_secs - since });
+            }
+        }
+        if history.len() >= self.rules.max_per_day {
+            return Err(CouncilAgendaError::DailyLimit { limit: self.rules.max_per_day });
+        }
+        let max_open = self.rules.max_open_for(class);
+        let open_by_player = self
+            .open
+            .values()
+            .filter(|e| e.submitted_by_player && e.proposal.proposer_id == player_id)
+            .count();
+        if open_by_player >= max_open {
+            return Err(CouncilAgendaError::TooManyOpen { limit: max_open });
+        }
+        if self.open.values().filter(|e| e.proposal.realm_id == realm_id).count() >= self.rules.max_open_per_realm {
+            return Err(CouncilAgendaError::RealmAgendaFull { realm_id });
+        }
+
+        let proposal = WireCouncilProposal {
+            proposal_id: self.next_proposal_id,
+            realm_id,
+            proposal_type,
+            title: title.to_string(),
+            description: description.to_string(),
+            proposer_id: player_id,
+            submitted_at: now,
+            target_zone,
+            votes_for: 0,
+            votes_against: 0,
+        };
+        self.next_proposal_id += 1;
+        self.submissions.entry(player_id).or_default().push_back(now);
+        self.open.insert(
+            proposal.proposal_id,
+            AgendaEntry { proposal: proposal.clone(), ballots: BallotBox::new(), submitted_by_player: true },
+        );
+        self.commands.push(AgendaCommand::Submit { proposal: proposal.clone() });
+        info!(
+            "[CouncilAgenda] Player {} submitted proposal {} ({:?}) in realm {}",
+            player_id, proposal.proposal_id, proposal_type, realm_id
+        );
+        Ok(proposal)
+    }
+
+    /// Cast or change `player_id`'s ballot. Returns true when an earlier ballot was replaced.
+    pub fn vote(
+        &mut self,
+        player_id: u64,
+        proposal_id: u64,
+        support: bool,
+        attunement: f32,
+        now: u64,
+    ) -> Result<bool, CouncilAgendaError> {
+        let entry = self.open.get_mut(&proposal_id).ok_or(BallotError::UnknownProposal(proposal_id))?;
+        let choice = BallotChoice::from_support(support);
+        let previous = entry.ballots.ballot(player_id).map(|b| b.choice);
+        let replaced = entry.ballots.cast(player_id, choice, now)?;
+
+        // Counts may come from the simulation, so adjust them rather than recount.
+        let p = &mut entry.proposal;
+        match previous {
+            Some(old) if old == choice => {}
+            Some(BallotChoice::For) => p.votes_for = p.votes_for.saturating_sub(1),
+            Some(BallotChoice::Against) => p.votes_against = p.votes_against.saturating_sub(1),
+            None => {}
+        }
+        if previous != Some(choice) {
+            match choice {
+                BallotChoice::For => p.votes_for += 1,
+                BallotChoice::Against => p.votes_against += 1,
+            }
+        }
+
+        self.commands.push(AgendaCommand::Vote {
+            realm_id: p.realm_id,
+            proposal_id,
+            voter: player_id,
+            support,
+            attunement,
+            at: now,
+        });
+        Ok(replaced)
+    }
+
+    /// Insert or refresh a proposal as the simulation sees it (keeps mirrored ballots).
+    pub fn upsert(&mut self, proposal: WireCouncilProposal) {
+        match self.open.get_mut(&proposal.proposal_id) {
+            Some(entry) => entry.proposal = proposal,
+            None => {
+                let entry = AgendaEntry { proposal, ballots: BallotBox::new(), submitted_by_player: false };
+                self.open.insert(entry.proposal.proposal_id, entry);
+            }
+        }
+    }
+
+    /// Submissions and ballots accepted since the last drain, in order.
+    pub fn drain_commands(&mut self) -> Vec<AgendaCommand> {
+        std::mem::take(&mut self.commands)
+    }
+
+    /// Report a deliberated proposal; it leaves the agenda and is broadcast next tick.
+    pub fn push_resolution(&mut self, resolution: CouncilResolution) {
+        if self.open.remove(&resolution.proposal_id).is_some() {
+            if self.ballot_records.len() == RESOLVED_RECORDS_CAP {
+                self.ballot_records.pop_front();
+            }
+            self.ballot_records.push_back(resolution.ballot.clone());
+            self.resolutions.push(resolution);
+        }
+    }
+
+    /// Ballot record of a recently resolved proposal.
+    pub fn ballot_record(&self, proposal_id: u64) -> Option<&BallotRecord> {
+        self.ballot_records.iter().find(|r| r.proposal_id == proposal_id)
+    }
+
+    pub fn drain_resolutions(&mut self) -> Vec<CouncilResolution> {
+        std::mem::take(&mut self.resolutions)
+    }
+}
+
+// ============================================================================
+// Protocol
+// ============================================================================
+
+pub fn handle_council_message(
+    player_id: u64,
+    message: &ClientMessage,
+    agenda: &mut CouncilAgenda,
+    now: u64,
+) -> Vec<(u64, ServerMessage)> {
+    let result = match message {
+        ClientMessage::CouncilProposalSubmit { realm_id, proposal_type, title, description, target_zone } => {
+            let class = nevc_attachment::player_contribution_class(player_id);
+            let draft = ProposalDraft {
+                realm_id: *realm_id,
+                proposal_type: *proposal_type,
+                title: title.clone(),
+                description: description.clone(),
+                target_zone: *target_zone,
+            };
+            agenda
+                .submit(player_id, class, draft, now)
+                .map(|proposal| vec![(player_id, ServerMessage::CouncilProposalAccepted { proposal })])
+        }
+        ClientMessage::CouncilAgendaRequest { realm_id } => Ok(vec![(
+            player_id,
+            ServerMessage::CouncilAgenda { realm_id: *realm_id, proposals: agenda.realm_agenda(*realm_id) },
+        )]),
+        ClientMessage::CouncilVote { proposal_id, support } => agenda
+            .vote(player_id, *proposal_id, *support, nevc_attachment::council_attunement(player_id), now)
+            .map(|replaced| {
+                vec![(
+                    player_id,
+                    ServerMessage::CouncilVoteAccepted { proposal_id: *proposal_id, support: *support, replaced },
+                )]
+            }),
+        ClientMessage::CouncilBallotRecordRequest { proposal_id } => agenda
+            .ballot_record(*proposal_id)
+            .map(|record| vec![(player_id, ServerMessage::CouncilBallotRecord { record: record.clone() })])
+            .ok_or(CouncilAgendaError::Ballot(BallotError::UnknownProposal(*proposal_id))),
+        _ => return Vec::new(),
+    };
+
+    result.unwrap_or_else(|e| {
+        warn!("[CouncilAgenda] Player {} council request rejected: {}", player_id, e);
+        vec![(player_id, ServerMessage::Error { message: e.to_string() })]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::council_ballot::player_voter;
+
+    const ACTIVE: ContributionClass = ContributionClass::ActiveEternalContributor;
+    const PARTITIONED: ContributionClass = ContributionClass::ZombiePartition;
+
+    fn draft(proposal_type: WireProposalType, title: &str) -> ProposalDraft {
+        ProposalDraft {
+            realm_id: 2,
+            proposal_type,
+            title: title.into(),
+            description: "Halve harvesting for a season".into(),
+            target_zone: Some(17),
+        }
+    }
+
+    fn submit(
+        agenda: &mut CouncilAgenda,
+        player: u64,
+        class: ContributionClass,
+        ty: WireProposalType,
+        now: u64,
+    ) -> Result<WireCouncilProposal, CouncilAgendaError> {
+        agenda.submit(player, class, draft(ty, "Rest the eastern grove"), now)
+    }
+
+    #[test]
+    fn eligibility_follows_contribution_class() {
+        let mut agenda = CouncilAgenda::default();
+        assert_eq!(
+            submit(&mut agenda, 1, PARTITIONED, WireProposalType::ResourcePolicy, 1_000),
+            Err(CouncilAgendaError::NotEligible { class: PARTITIONED, proposal_type: WireProposalType::ResourcePolicy })
+        );
+        let general = submit(&mut agenda, 1, PARTITIONED, WireProposalType::General, 1_000).unwrap();
+        assert_eq!(general.proposal_id, PLAYER_PROPOSAL_ID_BASE);
+        // One open proposal at a time while partitioned.
+        assert_eq!(
+            submit(&mut agenda, 1, PARTITIONED, WireProposalType::General, 5_000),
+            Err(CouncilAgendaError::TooManyOpen { limit: 1 })
+        );
+
+        let policy = submit(&mut agenda, 2, ACTIVE, WireProposalType::ResourcePolicy, 1_000).unwrap();
+        assert_eq!(agenda.realm_agenda(2), vec![general.clone(), policy.clone()]);
+        assert_eq!(
+            agenda.drain_commands(),
+            vec![AgendaCommand::Submit { proposal: general }, AgendaCommand::Submit { proposal: policy }]
+        );
+        assert_eq!(
+            agenda.submit(3, ACTIVE, draft(WireProposalType::General, "  "), 1_000),
+            Err(CouncilAgendaError::InvalidTitle)
+        );
+    }
+
+    #[test]
+    fn submissions_are_rate_limited() {
+        let rules = CouncilSubmissionRules { max_open_per_contributor: 10, ..CouncilSubmissionRules::default() };
+        let mut agenda = CouncilAgenda::with_rules(rules);
+        let ty = WireProposalType::HarmonyBoost;
+        submit(&mut agenda, 5, ACTIVE, ty, 10_000).unwrap();
+        assert_eq!(submit(&mut agenda, 5, ACTIVE, ty, 10_100), Err(CouncilAgendaError::Cooldown { retry_in_secs: 200 }));
+        for i in 1..4 {
+            submit(&mut agenda, 5, ACTIVE, ty, 10_000 + i * 300).unwrap();
+        }
+        assert_eq!(submit(&mut agenda, 5, ACTIVE, ty, 12_000), Err(CouncilAgendaError::DailyLimit { limit: 4 }));
+        // The window rolls: a day after the first submission there is room again.
+        assert!(submit(&mut agenda, 5, ACTIVE, ty, 10_000 + SUBMISSION_WINDOW_SECS).is_ok());
+    }
+
+    #[test]
+    fn one_ballot_per_player_and_resolution_closes_the_proposal() {
+        let mut agenda = CouncilAgenda::default();
+        let id = submit(&mut agenda, 1, ACTIVE, WireProposalType::ResourcePolicy, 100).unwrap().proposal_id;
+        agenda.drain_commands();
+
+        assert_eq!(agenda.vote(7, id, true, 0.5, 101), Ok(false));
+        assert_eq!(agenda.vote(7, id, true, 0.5, 102), Ok(true));
+        assert_eq!(agenda.vote(8, id, true, 0.0, 102), Ok(false));
+        assert_eq!((agenda.get(id).unwrap().votes_for, agenda.get(id).unwrap().votes_against), (2, 0));
+        assert_eq!(agenda.vote(7, id, false, 0.9, 103), Ok(true));
+        assert_eq!((agenda.get(id).unwrap().votes_for, agenda.get(id).unwrap().votes_against), (1, 1));
+        let commands = agenda.drain_commands();
+        assert_eq!(commands.len(), 4);
+        // The simulation weights each ballot by the attunement it was cast with.
+        assert!(matches!(commands[3], AgendaCommand::Vote { voter: 7, support: false, attunement, .. } if attunement == 0.9));
+
+        let mut ballots = BallotBox::new();
+        ballots.cast(player_voter(7), BallotChoice::Against, 103).unwrap();
+        ballots.cast(player_voter(8), BallotChoice::For, 102).unwrap();
+        let record = ballots.record(2, id, 110, |_| 0.0);
+        agenda.push_resolution(CouncilResolution {
+            realm_id: 2,
+            proposal_id: id,
+            passed: false,
+            votes_for: 1,
+            votes_against: 1,
+            ballot: record.clone(),
+            policy: None,
+        });
+        assert!(agenda.realm_agenda(2).is_empty());
+        let resolutions = agenda.drain_resolutions();
+        assert_eq!(resolutions.len(), 1);
+        assert!(matches!(
+            resolutions[0].to_message(),
+            ServerMessage::CouncilProposalResolved { ballot_digest, .. } if ballot_digest == record.digest
+        ));
+
+        // The record the digest names can be fetched after the proposal left the agenda.
+        let request = ClientMessage::CouncilBallotRecordRequest { proposal_id: id };
+        assert!(matches!(
+            &handle_council_message(9, &request, &mut agenda, 111)[..],
+            [(9, ServerMessage::CouncilBallotRecord { record: fetched })] if *fetched == record
+        ));
+        let unknown = ClientMessage::CouncilBallotRecordRequest { proposal_id: id + 1 };
+        assert!(matches!(&handle_council_message(9, &unknown, &mut agenda, 111)[..], [(9, ServerMessage::Error { .. })]));
+
+        let replies = handle_council_message(7, &ClientMessage::CouncilVote { proposal_id: id, support: true }, &mut agenda, 104);
+        assert!(matches!(&replies[..], [(7, ServerMessage::Error { .. })]));
+        assert!(agenda.drain_commands().is_empty());
+    }
+}
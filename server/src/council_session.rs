@@ -11,7 +11,7 @@ use tokio::sync::Mutex;
 use tracing::info;
 
 use crate::council_mercy_trial::{SharedReceptorBloomField, CouncilBloomSyncEvent};
-use crate::persistence_polish::PersistenceManager;
+use crate::persistence_polish::{PersistenceManager, PlayerSaveData};
 use crate::safety_net_broadcast::EmitSafetyNetBroadcast;
 use shared::protocol::{CouncilSessionState, CouncilPhase, MercyTrialVote, CollectiveEpiphanyBloom, CouncilParticipationRecord};
 
@@ -284,7 +284,7 @@ pub fn process_batch_persistence_queue(
     let error_counter = metrics.total_errors.clone();
 
     if let Some(persistence_manager) = &persistence {
-        let pm_clone = persistence_manager.clone();
+        let pm_clone: PersistenceManager = (**persistence_manager).clone();
 
         let batch_size = 50;
         let chunks: Vec<_> = drained.chunks(batch_size).collect();
@@ -295,53 +295,58 @@ pub fn process_batch_persistence_queue(
             let error_counter = error_counter.clone();
 
             tokio::spawn(async move {
+                // Each chunk is written as one journaled batch: all or nothing on crash.
+                let mut batch: Vec<PlayerSaveData> = Vec::with_capacity(chunk.len());
                 for update in chunk {
-                    if let Ok(mut persistence_manager) = pm.lock().await {
-                        match persistence_manager.load_player_data(update.player_id).await {
-                            Ok(mut save_data) => {
-                                if !save_data.is_checksum_valid() {
-                                    tracing::warn!("Checksum mismatch in batch persistence for player {}. Using safe defaults.", update.player_id);
-                                    save_data = persistence_polish::PlayerSaveData::new(update.player_id);
-                                }
-                                save_data.record_council_participation();
-                                if update.had_bloom {
-                                    save_data.record_successful_council_bloom(update.collective_attunement, update.tick);
-                                }
-                                let _ = persistence_manager.save_player_data(&mut save_data).await;
+                    match pm.load_player_data(update.player_id).await {
+                        Ok(mut save_data) => {
+                            if !save_data.is_checksum_valid() {
+                                tracing::warn!("Checksum mismatch in batch persistence for player {}. Using safe defaults.", update.player_id);
+                                save_data = PlayerSaveData::new(update.player_id);
                             }
-                            Err(e) => {
-                                tracing::error!("Failed to load player data in batch persistence: {}", e);
-
-                                // ============================================================
-                                // CAS (Compare-And-Swap) Educational Example
-                                // ============================================================
-                                // This fetch_add is implemented using CAS under the hood.
-                                // A manual CAS loop would look like this:
-                                //
-                                // let mut current = error_counter.load(Ordering::Relaxed);
-                                // loop {
-                                //     let new = current + 1;
-                                //     match error_counter.compare_exchange_weak(
-                                //         current, new,
-                                //         Ordering::Relaxed,
-                                //         Ordering::Relaxed,
-                                //     ) {
-                                //         Ok(_) => break,           // Success
-                                //         Err(actual) => current = actual, // Retry with latest value
-                                //     }
-                                // }
-                                //
-                                // Why we don't do this manually here:
-                                // - fetch_add is simpler and correct
-                                // - crossbeam::SegQueue already uses optimized CAS internally
-                                // - Manual CAS is error-prone (ABA problem, memory ordering)
-                                //
-                                // We keep this example for learning purposes.
-                                error_counter.fetch_add(1, Ordering::Relaxed);
+                            save_data.record_council_participation();
+                            if update.had_bloom {
+                                save_data.record_successful_council_bloom(update.collective_attunement, update.tick);
                             }
+                            batch.push(save_data);
+                        }
+                        Err(e) => {
+                            tracing::error!("Failed to load player data in batch persistence: {}", e);
+
+                            // ============================================================
+                            // CAS (Compare-And-Swap) Educational Example
+                            // ============================================================
+                            // This fetch_add is implemented using CAS under the hood.
+                            // A manual CAS loop would look like this:
+                            //
+                            // let mut current = error_counter.load(Ordering::Relaxed);
+                            // loop {
+                            //     let new = current + 1;
+                            //     match error_counter.compare_exchange_weak(
+                            //         current, new,
+                            //         Ordering::Relaxed,
+                            //         Ordering::Relaxed,
+                            //     ) {
+                            //         Ok(_) => break,           // Success
+                            //         Err(actual) => current = actual, // Retry with latest value
+                            //     }
+                            // }
+                            //
+                            // Why we don't do this manually here:
+                            // - fetch_add is simpler and correct
+                            // - crossbeam::SegQueue already uses optimized CAS internally
+                            // - Manual CAS is error-prone (ABA problem, memory ordering)
+                            //
+                            // We keep this example for learning purposes.
+                            error_counter.fetch_add(1, Ordering::Relaxed);
                         }
                     }
                 }
+
+                if let Err(e) = pm.flush_batch(&batch) {
+                    tracing::error!("Batch persistence flush failed ({} players): {}", batch.len(), e);
+                    error_counter.fetch_add(batch.len() as u64, Ordering::Relaxed);
+                }
             });
         }
 
@@ -0,0 +1,45 @@
+/*!
+ * server/src/crafting/definitions.rs
+ *
+ * Recipes loaded from the assets/recipes RON files (POWRUSH_RECIPE_DIR
+ * overrides) into the shared recipe registry through the common definition
+ * loader (definition_dir.rs), after the item registry they are validated
+ * against. An item hot-reload re-validates the recipes on the next poll.
+ *
+ * AG-SML v1.0 | TOLC 8 | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::path::Path;
+use std::sync::Arc;
+
+use shared::crafting::{self, RecipeLoadError, RecipeRegistry};
+use shared::items;
+
+use crate::definition_dir::{Basis, DefinitionSet, Definitions};
+
+pub type RecipeDefinitions = Definitions<RecipeRegistry>;
+
+impl DefinitionSet for RecipeRegistry {
+    type Error = RecipeLoadError;
+    const TAG: &'static str = "Crafting";
+    const NOUN: &'static str = "recipes";
+    const DIR_ENV: &'static str = "POWRUSH_RECIPE_DIR";
+    const DEFAULT_DIR: &'static str = "assets/recipes";
+
+    fn load_dir(dir: &Path) -> Result<Self, RecipeLoadError> {
+        RecipeRegistry::load_dir(dir, &items::current())
+    }
+    fn count(&self) -> usize {
+        RecipeRegistry::len(self)
+    }
+    fn current() -> Arc<Self> {
+        crafting::current()
+    }
+    fn install(registry: Arc<Self>) {
+        crafting::install(registry)
+    }
+    fn basis() -> Option<Basis> {
+        Some(items::current())
+    }
+}
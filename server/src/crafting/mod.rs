@@ -0,0 +1,18 @@
+/*!
+ * server/src/crafting/mod.rs
+ *
+ * Crafting: recipes from assets/recipes (shared::crafting), per-player job
+ * queues that keep running across logouts, station and tool checks, the
+ * ecological cost of each run on nearby resource nodes, and the
+ * raw-resource planner behind CraftPlanRequest.
+ * AG-SML v1.0 | TOLC 8 + RBE
+ * Thunder locked in. Yoi ⚡
+ */
+
+pub mod definitions;
+pub mod service;
+pub mod store;
+
+pub use definitions::RecipeDefinitions;
+pub use service::{handle_crafting_message, CraftingError, CraftingService, CRAFT_FOOTPRINT_RADIUS, CRAFT_STATION_RANGE};
+pub use store::{CraftQueueStore, CraftQueueStoreError, FileCraftQueueStore, InMemoryCraftQueueStore};
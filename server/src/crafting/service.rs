@@ -0,0 +1,521 @@
+/*!
+ * server/src/crafting/service.rs
+ *
+ * Server-authoritative crafting over the shared recipe registry.
+ * CraftStart takes the inputs out of the player's save at once (after
+ * checking tools and the station in range) and queues the job; jobs run
+ * back to back on wall-clock time and completed ones are delivered into the
+ * save by `complete_due`, whether or not the player is online. Cancelling
+ * refunds the inputs. Every queued run charges its ecological cost: nearby
+ * resource nodes of the raw materials behind the recipe lose sustainability.
+ *
+ * AG-SML v1.0 | TOLC 8 + RBE | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use bevy::prelude::{Resource, Vec3};
+use shared::crafting::{self, CraftError, CraftJob, CraftQueue, EcologicalCost, ItemAmount, RecipeDef};
+use shared::items;
+use shared::protocol::{ClientMessage, ServerMessage, WireCraftJob, WireCraftStep, WireItemCount};
+use tracing::{debug, error, warn};
+
+use crate::crafting::store::{CraftQueueStore, CraftQueueStoreError, FileCraftQueueStore, InMemoryCraftQueueStore};
+use crate::persistence_polish::{PersistenceManager, PlayerSaveData};
+use crate::spatial::chunk_streaming::ChunkWorld;
+
+/// A station counts when it stands within this distance of the player.
+pub const CRAFT_STATION_RANGE: f32 = 8.0;
+/// Resource nodes within this distance of the crafter carry a recipe's node stress.
+pub const CRAFT_FOOTPRINT_RADIUS: f32 = 96.0;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CraftingError {
+    Craft(CraftError),
+    Persistence(String),
+    Store(String),
+}
+
+impl fmt::Display for CraftingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CraftingError::Craft(e) => write!(f, "{}", e),
+            CraftingError::Persistence(e) => write!(f, "player save failed: {}", e),
+            CraftingError::Store(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for CraftingError {}
+
+impl From<CraftError> for CraftingError {
+    fn from(e: CraftError) -> Self {
+        CraftingError::Craft(e)
+    }
+}
+
+impl From<CraftQueueStoreError> for CraftingError {
+    fn from(e: CraftQueueStoreError) -> Self {
+        CraftingError::Store(e.to_string())
+    }
+}
+
+// ============================================================================
+// Inventory helpers
+// ============================================================================
+
+/// `PlayerSaveData::give_items` at the registry stack limit. On InventoryFull `player`
+/// is left partly changed; callers work on a copy.
+fn give_items(player: &mut PlayerSaveData, item_id: u32, count: u64) -> Result<(), CraftError> {
+    let limit = items::current()
+        .by_item_id(item_id)
+        .map(|d| d.stack_limit)
+        .ok_or_else(|| CraftError::UnknownItem(item_id.to_string()))?;
+    if !player.give_items(item_id, count, limit) {
+        return Err(CraftError::InventoryFull);
+    }
+    Ok(())
+}
+
+fn give_all(player: &mut PlayerSaveData, amounts: &[ItemAmount], runs: u32) -> Result<(), CraftError> {
+    let registry = items::current();
+    for amount in amounts {
+        let item_id = registry.resolve(&amount.item).map_err(|_| CraftError::UnknownItem(amount.item.clone()))?.item_id;
+        give_items(player, item_id, amount.count as u64 * runs as u64)?;
+    }
+    Ok(())
+}
+
+fn inventory_update(player: &PlayerSaveData) -> (u64, ServerMessage) {
+    (
+        player.player_id,
+        ServerMessage::InventoryUpdate {
+            player_id: player.player_id,
+            hotbar: player.hotbar.to_vec(),
+            inventory: player.inventory.to_vec(),
+            abundance_score: player.abundance as f32,
+        },
+    )
+}
+
+fn load_save(persistence: &mut PersistenceManager, player_id: u64) -> Result<PlayerSaveData, CraftingError> {
+    persistence
+        .load_player(player_id)
+        .ok_or_else(|| CraftingError::Persistence(format!("could not load save for player {}", player_id)))
+}
+
+// ============================================================================
+// Service
+// ============================================================================
+
+/// Every player's crafting queue over a CraftQueueStore. `Default` is in-memory;
+/// use `open_durable` on real servers.
+#[derive(Resource)]
+pub struct CraftingService {
+    store: Arc<dyn CraftQueueStore>,
+    queues: HashMap<u64, CraftQueue>,
+    /// Ecological cost of every run queued since startup
+    pub footprint: EcologicalCost,
+}
+
+impl Default for CraftingService {
+    fn default() -> Self {
+        Self { store: Arc::new(InMemoryCraftQueueStore::new()), queues: HashMap::new(), footprint: EcologicalCost::default() }
+    }
+}
+
+impl CraftingService {
+    /// Load every queue from `store`.
+    pub fn with_store(store: Arc<dyn CraftQueueStore>) -> Result<Self, CraftQueueStoreError> {
+        let queues = store.load_queues()?.into_iter().map(|q| (q.player_id, q)).collect();
+        Ok(Self { store, queues, footprint: EcologicalCost::default() })
+    }
+
+    /// File-backed queues under `<root>/crafting`.
+    pub fn open_durable(root: impl AsRef<Path>) -> Result<Self, String> {
+        let store = FileCraftQueueStore::open(root).map_err(|e| e.to_string())?;
+        Self::with_store(Arc::new(store)).map_err(|e| e.to_string())
+    }
+
+    pub fn queue(&self, player_id: u64) -> Option<&CraftQueue> {
+        self.queues.get(&player_id)
+    }
+
+    /// Persist `queue` (empty queues are deleted), then make it live.
+    fn commit(&mut self, queue: CraftQueue) -> Result<(), CraftingError> {
+        if queue.is_empty() {
+            self.store.delete_queue(queue.player_id)?;
+            self.queues.remove(&queue.player_id);
+        } else {
+            self.store.put_queue(&queue)?;
+            self.queues.insert(queue.player_id, queue);
+        }
+        Ok(())
+    }
+
+    /// Persist the save, then the queue; if the queue write fails the save is restored.
+    fn commit_both(
+        &mut self,
+        original: &PlayerSaveData,
+        player: &PlayerSaveData,
+        queue: CraftQueue,
+        persistence: &mut PersistenceManager,
+    ) -> Result<(), CraftingError> {
+        persistence.save_player(player).map_err(CraftingError::Persistence)?;
+        if let Err(e) = self.commit(queue) {
+            if let Err(restore) = persistence.save_player(original) {
+                error!("[Crafting] Player {} inventory lost after queue write failure: {}", player.player_id, restore);
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Queue `runs` runs of `recipe_id`, taking the inputs now. `site` is the chunk world
+    /// and the player's position; without it only station-less recipes can be crafted.
+    /// Returns the updated save.
+    pub fn start(
+        &mut self,
+        player_id: u64,
+        recipe_id: u32,
+        runs: u32,
+        persistence: &mut PersistenceManager,
+        site: Option<(&mut ChunkWorld, Vec3)>,
+        now: u64,
+    ) -> Result<PlayerSaveData, CraftingError> {
+        let recipes = crafting::current();
+        let recipe = recipes.by_recipe_id(recipe_id).ok_or(CraftError::UnknownRecipe(recipe_id))?;
+        if let Some(kind) = &recipe.station {
+            let in_range =
+                site.as_ref().is_some_and(|(world, position)| world.structure_near(*position, CRAFT_STATION_RANGE, kind));
+            if !in_range {
+                return Err(CraftError::NoStation(kind.clone()).into());
+            }
+        }
+
+        let mut queue = self.queues.get(&player_id).cloned().unwrap_or_else(|| CraftQueue::new(player_id));
+        queue.enqueue(recipe, runs, now)?;
+
+        let original = load_save(persistence, player_id)?;
+        let mut player = original.clone();
+        let registry = items::current();
+        let item_id = |key: &str| registry.resolve(key).map(|d| d.item_id).map_err(|_| CraftError::UnknownItem(key.to_string()));
+        for tool in &recipe.tools {
+            if player.held(item_id(tool)?) == 0 {
+                return Err(CraftError::MissingTool(tool.clone()).into());
+            }
+        }
+        let mut missing = Vec::new();
+        for input in &recipe.inputs {
+            let needed = input.count as u64 * runs as u64;
+            let have = player.held(item_id(&input.item)?);
+            if have < needed {
+                missing.push(ItemAmount { item: input.item.clone(), count: (needed - have) as u32 });
+            }
+        }
+        if !missing.is_empty() {
+            return Err(CraftError::MissingInputs(missing).into());
+        }
+        for input in &recipe.inputs {
+            player.take_items(item_id(&input.item)?, input.count as u64 * runs as u64);
+        }
+
+        self.commit_both(&original, &player, queue, persistence)?;
+        self.charge_footprint(&recipes, recipe, runs, site);
+        Ok(player)
+    }
+
+    fn charge_footprint(
+        &mut self,
+        recipes: &crafting::RecipeRegistry,
+        recipe: &RecipeDef,
+        runs: u32,
+        site: Option<(&mut ChunkWorld, Vec3)>,
+    ) {
+        let cost = recipe.ecology.scaled(runs as u64);
+        self.footprint = self.footprint + cost;
+        if let Some((world, position)) = site {
+            let sources: Vec<String> = recipes.raw_sources(recipe).into_iter().collect();
+            world.stress_nodes_near(position, CRAFT_FOOTPRINT_RADIUS, &sources, cost.node_stress);
+        }
+    }
+
+    /// Cancel a queued job and refund its inputs. Returns the updated save.
+    pub fn cancel(
+        &mut self,
+        player_id: u64,
+        job_id: u64,
+        persistence: &mut PersistenceManager,
+        now: u64,
+    ) -> Result<PlayerSaveData, CraftingError> {
+        let mut queue = self.queues.get(&player_id).cloned().ok_or(CraftError::UnknownJob(job_id))?;
+        let job = queue.cancel(job_id, now)?;
+        let original = load_save(persistence, player_id)?;
+        let mut player = original.clone();
+        give_all(&mut player, &job.consumed, 1)?;
+        self.commit_both(&original, &player, queue, persistence)?;
+        Ok(player)
+    }
+
+    /// Deliver every job finished by `now` into its owner's save. A job whose outputs do
+    /// not fit stays at the head of the queue and is retried on a later call.
+    pub fn complete_due(&mut self, persistence: &mut PersistenceManager, now: u64) -> Vec<(u64, ServerMessage)> {
+        let due: Vec<u64> = self
+            .queues
+            .values()
+            .filter(|q| q.jobs.front().is_some_and(|j| j.ready_at <= now))
+            .map(|q| q.player_id)
+            .collect();
+        let recipes = crafting::current();
+        let mut out = Vec::new();
+        for player_id in due {
+            let mut queue = self.queues[&player_id].clone();
+            let Ok(original) = load_save(persistence, player_id) else {
+                warn!("[Crafting] Could not load player {} to deliver finished jobs", player_id);
+                continue;
+            };
+            let mut player = original.clone();
+            let mut delivered: Vec<CraftJob> = Vec::new();
+            let mut finished = queue.take_finished(now).into_iter();
+            for job in finished.by_ref() {
+                let mut trial = player.clone();
+                // A recipe removed since the job was queued refunds its inputs instead.
+                let result = match recipes.get(&job.recipe) {
+                    Some(recipe) => give_all(&mut trial, &recipe.outputs, job.runs),
+                    None => give_all(&mut trial, &job.consumed, 1),
+                };
+                match result {
+                    Ok(()) => {
+                        player = trial;
+                        delivered.push(job);
+                    }
+                    Err(e) => {
+                        debug!("[Crafting] Job {} of player {} held back: {}", job.job_id, player_id, e);
+                        queue.jobs.push_front(job);
+                        break;
+                    }
+                }
+            }
+            // Anything after a held-back job goes back too, in order.
+            for (i, job) in finished.enumerate() {
+                queue.jobs.insert(1 + i, job);
+            }
+            if delivered.is_empty() {
+                continue;
+            }
+            if let Err(e) = self.commit_both(&original, &player, queue, persistence) {
+                warn!("[Crafting] Delivery to player {} failed, will retry: {}", player_id, e);
+                continue;
+            }
+            out.push(inventory_update(&player));
+            for job in delivered {
+                let recipe_id = recipes.get(&job.recipe).map_or(0, |r| r.recipe_id);
+                out.push((player_id, ServerMessage::CraftCompleted { job_id: job.job_id, recipe_id, runs: job.runs }));
+            }
+            out.push(self.queue_update(player_id));
+        }
+        out
+    }
+
+    pub fn queue_update(&self, player_id: u64) -> (u64, ServerMessage) {
+        let recipes = crafting::current();
+        let jobs = self
+            .queues
+            .get(&player_id)
+            .map(|q| {
+                q.jobs
+                    .iter()
+                    .map(|j| WireCraftJob {
+                        job_id: j.job_id,
+                        recipe_id: recipes.get(&j.recipe).map_or(0, |r| r.recipe_id),
+                        runs: j.runs,
+                        ready_at: j.ready_at,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        (player_id, ServerMessage::CraftQueue { jobs })
+    }
+}
+
+/// Raw resources, steps and tools for `count` of `item_id`, given what the player holds.
+fn plan_reply(player: &PlayerSaveData, item_id: u32, count: u32) -> Result<ServerMessage, CraftingError> {
+    let registry = items::current();
+    let recipes = crafting::current();
+    let item = registry.resolve_id(item_id).map_err(|_| CraftError::UnknownItem(item_id.to_string()))?;
+    let mut holding: HashMap<String, u64> = HashMap::new();
+    for slot in player.hotbar.iter().chain(player.inventory.iter()).filter(|s| s.count > 0) {
+        if let Some(def) = registry.by_item_id(slot.item_id) {
+            *holding.entry(def.id.clone()).or_insert(0) += slot.count as u64;
+        }
+    }
+    let plan = recipes.plan(&item.id, count as u64, &holding)?;
+    let id_of = |key: &str| registry.get(key).map_or(0, |d| d.item_id);
+    Ok(ServerMessage::CraftPlan {
+        item_id,
+        count,
+        steps: plan
+            .steps
+            .iter()
+            .filter_map(|s| recipes.get(&s.recipe).map(|r| WireCraftStep { recipe_id: r.recipe_id, runs: s.runs }))
+            .collect(),
+        raw: plan.raw.iter().map(|(key, count)| WireItemCount { item_id: id_of(key), count: *count }).collect(),
+        tools: plan.tools.iter().map(|key| id_of(key)).collect(),
+    })
+}
+
+// ============================================================================
+// Message routing
+// ============================================================================
+
+/// Apply one crafting request from `player_id`. `site` is the chunk world and the
+/// player's position when both are known. Rejections are answered with ServerMessage::Error.
+pub fn handle_crafting_message(
+    player_id: u64,
+    message: &ClientMessage,
+    service: &mut CraftingService,
+    persistence: &mut PersistenceManager,
+    site: Option<(&mut ChunkWorld, Vec3)>,
+    now: u64,
+) -> Vec<(u64, ServerMessage)> {
+    let result = match message {
+        ClientMessage::CraftStart { recipe_id, runs } => service
+            .start(player_id, *recipe_id, *runs, persistence, site, now)
+            .map(|player| vec![inventory_update(&player), service.queue_update(player_id)]),
+        ClientMessage::CraftCancel { job_id } => service
+            .cancel(player_id, *job_id, persistence, now)
+            .map(|player| vec![inventory_update(&player), service.queue_update(player_id)]),
+        ClientMessage::CraftQueueRequest => Ok(vec![service.queue_update(player_id)]),
+        ClientMessage::CraftPlanRequest { item_id, count } => load_save(persistence, player_id)
+            .and_then(|player| plan_reply(&player, *item_id, *count))
+            .map(|reply| vec![(player_id, reply)]),
+        _ => return Vec::new(),
+    };
+
+    result.unwrap_or_else(|e| {
+        warn!("[Crafting] Player {} crafting request rejected: {}", player_id, e);
+        vec![(player_id, ServerMessage::Error { message: e.to_string() })]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::protocol::HotbarSlot;
+    use crate::spatial::chunk_manager::ChunkCoord;
+    use crate::spatial::chunk_store::{ChunkResourceNode, InMemoryChunkStore, PlacedStructure};
+    use crate::spatial::chunk_streaming::ChunkStreamingConfig;
+
+    fn id(key: &str) -> u32 {
+        items::current().get(key).unwrap().item_id
+    }
+
+    fn recipe_id(key: &str) -> u32 {
+        crafting::current().get(key).unwrap().recipe_id
+    }
+
+    fn seeded(player_id: u64, stock: &[(&str, u32)]) -> PersistenceManager {
+        let mut persistence = PersistenceManager::default();
+        let mut save = PlayerSaveData::new(player_id);
+        for (slot, (key, count)) in stock.iter().enumerate() {
+            save.inventory[slot] = HotbarSlot::new(id(key), *count, 1.0);
+        }
+        persistence.save_player(&save).unwrap();
+        persistence
+    }
+
+    #[test]
+    fn jobs_consume_inputs_and_deliver_outputs_after_logout() {
+        let mut service = CraftingService::default();
+        let mut persistence = seeded(1, &[("verdant_wood", 5)]);
+        let planks = recipe_id("plank");
+
+        let missing = service.start(1, planks, 3, &mut persistence, None, 100).unwrap_err();
+        assert_eq!(missing, CraftingError::Craft(CraftError::MissingInputs(vec![ItemAmount { item: "verdant_wood".into(), count: 1 }])));
+        let after = service.start(1, planks, 2, &mut persistence, None, 100).unwrap();
+        assert_eq!(after.held(id("verdant_wood")), 1);
+        service.start(1, planks, 0, &mut persistence, None, 100).unwrap_err();
+
+        // Nothing is due yet; much later (player offline) the planks are in the save.
+        assert!(service.complete_due(&mut persistence, 101).is_empty());
+        let replies = service.complete_due(&mut persistence, 10_000);
+        assert!(replies.iter().any(|(_, m)| matches!(m, ServerMessage::CraftCompleted { runs: 2, .. })));
+        assert_eq!(persistence.load_player(1).unwrap().held(id("plank")), 8);
+        assert!(service.queue(1).is_none());
+    }
+
+    #[test]
+    fn cancel_refunds_and_stations_and_tools_are_required() {
+        let mut service = CraftingService::default();
+        let mut persistence = seeded(2, &[("verdant_wood", 4), ("gold", 6), ("energy", 2)]);
+        let job = service.start(2, recipe_id("plank"), 2, &mut persistence, None, 0).unwrap();
+        assert_eq!(job.held(id("verdant_wood")), 0);
+        let job_id = service.queue(2).unwrap().jobs[0].job_id;
+        let refunded = service.cancel(2, job_id, &mut persistence, 1).unwrap();
+        assert_eq!(refunded.held(id("verdant_wood")), 4);
+        assert!(matches!(service.cancel(2, job_id, &mut persistence, 1), Err(CraftingError::Craft(CraftError::UnknownJob(_)))));
+
+        let mut world = ChunkWorld::new(ChunkStreamingConfig::default(), Arc::new(InMemoryChunkStore::new())).unwrap();
+        let forge_at = Vec3::new(4.0, 0.0, 4.0);
+        let ingot = recipe_id("gold_ingot");
+        let no_forge = service.start(2, ingot, 1, &mut persistence, Some((&mut world, forge_at)), 0).unwrap_err();
+        assert_eq!(no_forge, CraftingError::Craft(CraftError::NoStation("forge".into())));
+
+        let chunk = world.chunk_mut(ChunkCoord::new(0, 0, 0)).unwrap();
+        chunk.structures.push(PlacedStructure {
+            structure_id: 1,
+            kind: "forge".into(),
+            owner_id: 9,
+            position: [5.0, 0.0, 5.0],
+            rotation_y: 0.0,
+            integrity: 1.0,
+        });
+        chunk.resource_nodes.push(ChunkResourceNode {
+            node_id: 3,
+            resource_type: "gold".into(),
+            position: [20.0, 0.0, 20.0],
+            current_amount: 50.0,
+            max_amount: 50.0,
+            regen_rate: 1.0,
+            sustainability_score: 1.0,
+            depleted: false,
+        });
+        let no_tools = service.start(2, ingot, 1, &mut persistence, Some((&mut world, forge_at)), 0).unwrap_err();
+        assert_eq!(no_tools, CraftingError::Craft(CraftError::MissingTool("basic_tools".into())));
+
+        let mut save = persistence.load_player(2).unwrap();
+        save.hotbar[0] = HotbarSlot::new(id("basic_tools"), 1, 1.0);
+        persistence.save_player(&save).unwrap();
+        let after = service.start(2, ingot, 2, &mut persistence, Some((&mut world, forge_at)), 0).unwrap();
+        assert_eq!((after.held(id("gold")), after.held(id("basic_tools"))), (0, 1));
+        let node = &world.chunk(ChunkCoord::new(0, 0, 0)).unwrap().resource_nodes[0];
+        assert!(node.sustainability_score < 1.0);
+        assert!(service.footprint.pressure > 0.0);
+    }
+
+    #[test]
+    fn plan_request_answers_with_item_ids() {
+        let mut service = CraftingService::default();
+        let mut persistence = seeded(3, &[("gold", 2)]);
+        let request = ClientMessage::CraftPlanRequest { item_id: id("mercy_sword"), count: 1 };
+        let replies = handle_crafting_message(3, &request, &mut service, &mut persistence, None, 0);
+        let ServerMessage::CraftPlan { raw, tools, steps, .. } = &replies[0].1 else {
+            panic!("expected CraftPlan, got {:?}", replies);
+        };
+        assert!(raw.iter().any(|r| r.item_id == id("mercy_essence") && r.count == 1));
+        assert_eq!(tools, &vec![id("basic_tools")]);
+        assert_eq!(steps.last().unwrap().recipe_id, recipe_id("mercy_sword"));
+
+        let unknown = ClientMessage::CraftStart { recipe_id: 9_999, runs: 1 };
+        let replies = handle_crafting_message(3, &unknown, &mut service, &mut persistence, None, 0);
+        assert!(matches!(replies[0].1, ServerMessage::Error { .. }));
+    }
+}
@@ -0,0 +1,175 @@
+/*!
+ * server/src/crafting/store.rs
+ *
+ * Storage backends for CraftingService queues behind the CraftQueueStore trait.
+ *
+ * - InMemoryCraftQueueStore: unit tests / sovereign dev.
+ * - FileCraftQueueStore: one JSON file per player under `<root>/crafting`,
+ *   written atomically (temp file + fsync + rename).
+ *
+ * AG-SML v1.0 | TOLC 8 | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use shared::crafting::CraftQueue;
+use tracing::warn;
+
+use crate::persistence::player_store::write_atomic;
+
+const CRAFTING_DIR: &str = "crafting";
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug)]
+pub enum CraftQueueStoreError {
+    Io(io::Error),
+    Serialize(String),
+    /// Record exists on disk but cannot be read back.
+    Corrupt { path: PathBuf, reason: String },
+}
+
+impl fmt::Display for CraftQueueStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CraftQueueStoreError::Io(e) => write!(f, "craft queue store I/O error: {}", e),
+            CraftQueueStoreError::Serialize(e) => write!(f, "craft queue store serialization error: {}", e),
+            CraftQueueStoreError::Corrupt { path, reason } => {
+                write!(f, "craft queue record {} corrupt: {}", path.display(), reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CraftQueueStoreError {}
+
+impl From<io::Error> for CraftQueueStoreError {
+    fn from(e: io::Error) -> Self {
+        CraftQueueStoreError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for CraftQueueStoreError {
+    fn from(e: serde_json::Error) -> Self {
+        CraftQueueStoreError::Serialize(e.to_string())
+    }
+}
+
+// ============================================================================
+// Trait
+// ============================================================================
+
+/// Storage backend for crafting queues. Writes are durable when they return Ok.
+pub trait CraftQueueStore: Send + Sync {
+    /// Every stored queue (empty queues are deleted, so these all hold jobs).
+    fn load_queues(&self) -> Result<Vec<CraftQueue>, CraftQueueStoreError>;
+
+    /// Insert or replace by `queue.player_id`.
+    fn put_queue(&self, queue: &CraftQueue) -> Result<(), CraftQueueStoreError>;
+
+    /// Deleting an unknown queue is not an error.
+    fn delete_queue(&self, player_id: u64) -> Result<(), CraftQueueStoreError>;
+}
+
+// ============================================================================
+// In-memory backend
+// ============================================================================
+
+#[derive(Default)]
+pub struct InMemoryCraftQueueStore {
+    queues: RwLock<HashMap<u64, CraftQueue>>,
+}
+
+impl InMemoryCraftQueueStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CraftQueueStore for InMemoryCraftQueueStore {
+    fn load_queues(&self) -> Result<Vec<CraftQueue>, CraftQueueStoreError> {
+        let mut queues: Vec<CraftQueue> = self.queues.read().unwrap().values().cloned().collect();
+        queues.sort_by_key(|q| q.player_id);
+        Ok(queues)
+    }
+
+    fn put_queue(&self, queue: &CraftQueue) -> Result<(), CraftQueueStoreError> {
+        self.queues.write().unwrap().insert(queue.player_id, queue.clone());
+        Ok(())
+    }
+
+    fn delete_queue(&self, player_id: u64) -> Result<(), CraftQueueStoreError> {
+        self.queues.write().unwrap().remove(&player_id);
+        Ok(())
+    }
+}
+
+// ============================================================================
+// File backend
+// ============================================================================
+
+pub struct FileCraftQueueStore {
+    root: PathBuf,
+}
+
+impl FileCraftQueueStore {
+    /// Open (creating if needed) a store rooted at `root`.
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, CraftQueueStoreError> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(root.join(CRAFTING_DIR))?;
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn record_path(&self, player_id: u64) -> PathBuf {
+        self.root.join(CRAFTING_DIR).join(format!("{}.json", player_id))
+    }
+}
+
+impl CraftQueueStore for FileCraftQueueStore {
+    /// Every `<player_id>.json`; stale temp files from a crash are removed.
+    fn load_queues(&self) -> Result<Vec<CraftQueue>, CraftQueueStoreError> {
+        let mut out: Vec<CraftQueue> = Vec::new();
+        for entry in fs::read_dir(self.root.join(CRAFTING_DIR))? {
+            let path = entry?.path();
+            match path.extension().and_then(|e| e.to_str()) {
+                Some("json") => {}
+                Some("tmp") => {
+                    warn!("[CraftQueueStore] Removing stale temp file {}", path.display());
+                    fs::remove_file(&path)?;
+                    continue;
+                }
+                _ => continue,
+            }
+            let queue = serde_json::from_slice(&fs::read(&path)?)
+                .map_err(|e| CraftQueueStoreError::Corrupt { path: path.clone(), reason: e.to_string() })?;
+            out.push(queue);
+        }
+        out.sort_by_key(|q| q.player_id);
+        Ok(out)
+    }
+
+    fn put_queue(&self, queue: &CraftQueue) -> Result<(), CraftQueueStoreError> {
+        let bytes = serde_json::to_vec_pretty(queue)?;
+        write_atomic(&self.record_path(queue.player_id), &bytes)?;
+        Ok(())
+    }
+
+    fn delete_queue(&self, player_id: u64) -> Result<(), CraftQueueStoreError> {
+        match fs::remove_file(self.record_path(player_id)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
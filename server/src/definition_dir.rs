@@ -0,0 +1,222 @@
+/*!
+ * server/src/definition_dir.rs
+ *
+ * One loader for the data-driven registries (abilities, items, recipes): a
+ * directory of RON files (an environment variable overrides the path) loaded
+ * into the shared registry at startup. Debug builds poll the directory and
+ * hot-reload on change; a load that fails validation is logged and the
+ * previous registry stays live. A registry validated against another one
+ * (recipes against items) is reloaded when that one is replaced.
+ *
+ * AG-SML v1.0 | TOLC 8 | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::any::Any;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use bevy::prelude::*;
+
+/// How often debug builds check the definitions directories for changes.
+pub const HOT_RELOAD_POLL_SECS: f32 = 1.0;
+
+/// The registry a definition set was validated against (kept alive for identity)
+pub type Basis = Arc<dyn Any + Send + Sync>;
+
+/// A shared registry loaded from a directory of RON files.
+pub trait DefinitionSet: Sized + Send + Sync + 'static {
+    type Error: fmt::Display;
+    /// Log tag and what one entry is called: "[Crafting] 12 recipes loaded"
+    const TAG: &'static str;
+    const NOUN: &'static str;
+    const DIR_ENV: &'static str;
+    const DEFAULT_DIR: &'static str;
+
+    fn load_dir(dir: &Path) -> Result<Self, Self::Error>;
+    fn count(&self) -> usize;
+    /// The process-wide registry every system reads
+    fn current() -> Arc<Self>;
+    fn install(registry: Arc<Self>);
+    /// The live registry this one is validated against, if any
+    fn basis() -> Option<Basis> {
+        None
+    }
+}
+
+#[derive(Resource)]
+pub struct Definitions<R: DefinitionSet> {
+    pub dir: PathBuf,
+    pub registry: Arc<R>,
+    /// (newest file mtime in ms, file count) at the last load
+    fingerprint: Option<(u128, usize)>,
+    basis: Option<Basis>,
+}
+
+impl<R: DefinitionSet> Default for Definitions<R> {
+    fn default() -> Self {
+        let dir = std::env::var(R::DIR_ENV).unwrap_or_else(|_| R::DEFAULT_DIR.to_string());
+        Self { dir: dir.into(), registry: R::current(), fingerprint: None, basis: R::basis() }
+    }
+}
+
+impl<R: DefinitionSet> Definitions<R> {
+    /// Load `dir` into `registry`. On error the previous registry is kept.
+    /// Callers publish the result with `R::install`.
+    pub fn reload(&mut self) -> Result<usize, R::Error> {
+        let fingerprint = dir_fingerprint(&self.dir);
+        self.basis = R::basis();
+        self.registry = Arc::new(R::load_dir(&self.dir)?);
+        self.fingerprint = fingerprint;
+        Ok(self.registry.count())
+    }
+
+    /// Reload when a definition file was added, removed or modified, or the
+    /// registry it is validated against was replaced, since the last load.
+    pub fn reload_if_changed(&mut self) -> Option<Result<usize, R::Error>> {
+        let fingerprint = dir_fingerprint(&self.dir)?;
+        let same_basis = match (&self.basis, R::basis()) {
+            (Some(old), Some(new)) => Arc::ptr_eq(old, &new),
+            (old, new) => old.is_none() && new.is_none(),
+        };
+        if self.fingerprint == Some(fingerprint) && same_basis {
+            return None;
+        }
+        let result = self.reload();
+        // Don't retry a broken file every poll; wait for the next edit.
+        self.fingerprint = Some(fingerprint);
+        Some(result)
+    }
+}
+
+fn dir_fingerprint(dir: &Path) -> Option<(u128, usize)> {
+    let mut newest = 0;
+    let mut count = 0;
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "ron") {
+            continue;
+        }
+        count += 1;
+        let modified = entry.metadata().ok().and_then(|m| m.modified().ok());
+        if let Some(ms) = modified.and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map(|d| d.as_millis()) {
+            newest = newest.max(ms);
+        }
+    }
+    Some((newest, count))
+}
+
+/// Startup: load the asset directory, falling back to the bundled definitions.
+pub fn load_definitions<R: DefinitionSet>(mut definitions: ResMut<Definitions<R>>) {
+    match definitions.reload() {
+        Ok(count) => {
+            R::install(definitions.registry.clone());
+            info!("[{}] {} {} loaded from {}", R::TAG, count, R::NOUN, definitions.dir.display());
+        }
+        Err(e) => warn!("[{}] {} — using {} bundled {}", R::TAG, e, definitions.registry.count(), R::NOUN),
+    }
+}
+
+/// Debug builds only: pick up edits to the definition files without a restart.
+pub fn hot_reload_definitions<R: DefinitionSet>(
+    time: Res<Time>,
+    mut since_poll: Local<f32>,
+    mut definitions: ResMut<Definitions<R>>,
+) {
+    *since_poll += time.delta_seconds();
+    if *since_poll < HOT_RELOAD_POLL_SECS {
+        return;
+    }
+    *since_poll = 0.0;
+    match definitions.reload_if_changed() {
+        Some(Ok(count)) => {
+            R::install(definitions.registry.clone());
+            info!("[{}] Hot-reloaded {} {}", R::TAG, count, R::NOUN);
+        }
+        Some(Err(e)) => warn!("[{}] Hot-reload rejected, keeping previous {}: {}", R::TAG, R::NOUN, e),
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    use shared::abilities::{AbilityLoadError, AbilityRegistry};
+
+    #[test]
+    fn reloads_on_change_and_keeps_last_good_registry() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let strike = |cooldown: &str| {
+            format!(
+                r#"[(id: "test_strike", wire_id: 900, name: "Test Strike", kind: DirectDamage, cooldown_secs: {},
+                    targeting: (mode: Enemy, range: 3.0),
+                    effects: [Damage(base: 5.0, damage_type: Physical)])]"#,
+                cooldown
+            )
+        };
+        std::fs::write(dir.join("test.ron"), strike("2.0")).unwrap();
+
+        let mut definitions = Definitions::<AbilityRegistry> { dir: dir.to_path_buf(), ..Default::default() };
+        assert_eq!(definitions.reload().unwrap(), 1);
+        assert!(definitions.reload_if_changed().is_none());
+
+        // A second file changes the fingerprint; a negative cooldown fails validation.
+        std::fs::write(dir.join("zz_broken.ron"), strike("-1.0").replace("test_strike", "broken").replace("900", "901")).unwrap();
+        assert!(matches!(definitions.reload_if_changed(), Some(Err(AbilityLoadError::Invalid(_)))));
+        assert_eq!(definitions.registry.by_wire_id(900).unwrap().cooldown_secs, 2.0);
+        assert!(definitions.reload_if_changed().is_none());
+
+        std::fs::remove_file(dir.join("zz_broken.ron")).unwrap();
+        assert!(matches!(definitions.reload_if_changed(), Some(Ok(1))));
+        assert_eq!(definitions.registry.get("test_strike").unwrap().wire_id, 900);
+    }
+
+    /// Counts its files; validated against whatever `LISTING_BASIS` holds.
+    struct Listing(usize);
+
+    static LISTING_BASIS: Mutex<Option<Basis>> = Mutex::new(None);
+
+    impl DefinitionSet for Listing {
+        type Error = String;
+        const TAG: &'static str = "Listing";
+        const NOUN: &'static str = "files";
+        const DIR_ENV: &'static str = "POWRUSH_TEST_LISTING_DIR";
+        const DEFAULT_DIR: &'static str = "assets/listing";
+
+        fn load_dir(dir: &Path) -> Result<Self, String> {
+            Ok(Listing(std::fs::read_dir(dir).map_err(|e| e.to_string())?.count()))
+        }
+        fn count(&self) -> usize {
+            self.0
+        }
+        fn current() -> Arc<Self> {
+            Arc::new(Listing(0))
+        }
+        fn install(_: Arc<Self>) {}
+        fn basis() -> Option<Basis> {
+            LISTING_BASIS.lock().unwrap().clone()
+        }
+    }
+
+    #[test]
+    fn replaced_basis_forces_a_reload() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::write(dir.join("a.ron"), "()").unwrap();
+        *LISTING_BASIS.lock().unwrap() = Some(Arc::new(1u8));
+
+        let mut definitions = Definitions::<Listing> { dir: dir.to_path_buf(), ..Default::default() };
+        assert_eq!(definitions.reload().unwrap(), 1);
+        assert!(definitions.reload_if_changed().is_none());
+
+        // Same files, new basis (an item hot-reload under the recipes): validated again.
+        *LISTING_BASIS.lock().unwrap() = Some(Arc::new(1u8));
+        assert!(matches!(definitions.reload_if_changed(), Some(Ok(1))));
+        assert!(definitions.reload_if_changed().is_none());
+    }
+}
@@ -4,12 +4,22 @@
 // Added input validation, boost rate limiting, bounds checking, and anti-spam safeguards
 // Maintains full tunability while improving robustness and security
 // Server-side audio cue mapping for client GameAudioEvent sync (CouncilTrial, RbeFlow, DivineWhisper, etc.)
+// v17.60 — Authored event scripts (event_scripts.rs): triggers, phases, rewards, chained follow-ups.
+//          Events carry an EventArea; get_relevant_events_for_player filters by position.
 
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
+use crate::event_scripts::{
+    EventArea, EventProgress, EventReward, EventScript, EventScriptRegistry, EventScriptRunner, ScriptEvent,
+    ScriptOutcome, WorldConditions,
+};
+
 // ═════════════════════════════════════════════════════════════════════════
 // EVENT TYPES
 // ═════════════════════════════════════════════════════════════════════════
@@ -23,6 +33,8 @@ pub enum DynamicEventType {
     WorldShift { region: String, effect: String },
     MercyTest { difficulty: f32 },
     Custom { name: String, data: serde_json::Value },
+    /// Authored event script run; `phase` indexes the script's phases.
+    Scripted { script_id: String, phase: u32, priority: f32 },
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -37,6 +49,9 @@ pub struct DynamicEvent {
     pub priority: f32,
     pub base_priority: f32,
     pub priority_boost: f32,
+    /// Global events are visible everywhere; others only around their area.
+    #[serde(default)]
+    pub area: EventArea,
 }
 
 impl DynamicEvent {
@@ -49,6 +64,7 @@ impl DynamicEvent {
             DynamicEventType::WorldShift { .. } => 0.64,
             DynamicEventType::MercyTest { difficulty, .. } => 0.59 + difficulty.clamp(0.0, 0.26),
             DynamicEventType::Custom { .. } => 0.38,
+            DynamicEventType::Scripted { priority, .. } => *priority,
         };
         type_priority.clamp(0.1, 1.4)
     }
@@ -110,6 +126,14 @@ pub struct DynamicEventsConfig {
     pub mercy_half_life_influence: f32,
     pub min_priority_floor: f32,
     pub boost_decay_half_life: f32,
+
+    /// Players this far outside an event's area already see it (approach distance).
+    #[serde(default = "default_event_interest_margin")]
+    pub event_interest_margin: f32,
+}
+
+fn default_event_interest_margin() -> f32 {
+    64.0
 }
 
 impl Default for DynamicEventsConfig {
@@ -128,6 +152,7 @@ impl Default for DynamicEventsConfig {
             mercy_half_life_influence: 0.8,
             min_priority_floor: 0.04,
             boost_decay_half_life: 240.0,
+            event_interest_margin: default_event_interest_margin(),
         }
     }
 }
@@ -136,6 +161,9 @@ impl Default for DynamicEventsConfig {
 // DYNAMIC EVENT MANAGER + SECURITY
 // ═════════════════════════════════════════════════════════════════════════
 
+/// Ended events kept in `event_history`.
+pub const EVENT_HISTORY_LEN: usize = 128;
+
 #[derive(Resource)]
 pub struct DynamicEventManager {
     pub config: DynamicEventsConfig,
@@ -145,6 +173,24 @@ pub struct DynamicEventManager {
     pub last_faction_check: f64,
     pub pending_replication: Vec<DynamicEvent>,
     pub last_boost_time: f64,           // For simple rate limiting on boosting
+
+    pub scripts: Arc<EventScriptRegistry>,
+    /// Trigger inputs for scripts; mercy_level is refreshed every tick, the rest by its owners.
+    pub world: WorldConditions,
+    script_runner: EventScriptRunner,
+    /// Script run id → the DynamicEvent that mirrors it
+    script_events: HashMap<u64, Uuid>,
+    pending_rewards: Vec<EventRewardGrant>,
+}
+
+/// A script phase reward owed to a player; the game layer applies it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventRewardGrant {
+    pub player_id: u64,
+    pub event_id: Uuid,
+    pub script_id: String,
+    pub phase: u32,
+    pub reward: EventReward,
 }
 
 impl DynamicEventManager {
@@ -152,20 +198,35 @@ impl DynamicEventManager {
         Self {
             config,
             active_events: Vec::new(),
-            event_history: VecDeque::with_capacity(128),
+            event_history: VecDeque::with_capacity(EVENT_HISTORY_LEN),
             last_abundance_check: 0.0,
             last_faction_check: 0.0,
             pending_replication: Vec::new(),
             last_boost_time: 0.0,
+            scripts: Arc::new(EventScriptRegistry::bundled()),
+            world: WorldConditions::default(),
+            script_runner: EventScriptRunner::default(),
+            script_events: HashMap::new(),
+            pending_rewards: Vec::new(),
         }
     }
 
+    pub fn with_scripts(mut self, scripts: Arc<EventScriptRegistry>) -> Self {
+        self.scripts = scripts;
+        self
+    }
+
     pub fn tick(&mut self, current_time: f64, mercy_level: f32) {
+        self.world.mercy_level = mercy_level;
         self.process_scheduled_events(current_time);
         self.consider_new_events(current_time, mercy_level);
         self.apply_priority_decay(current_time);
     }
 
+    fn script_capacity(&self) -> usize {
+        (self.config.max_concurrent_events as usize).saturating_sub(self.active_events.len())
+    }
+
     fn apply_priority_decay(&mut self, current_time: f64) {
         for event in self.active_events.iter_mut() {
             event.priority = event.current_priority(current_time, &self.config);
@@ -175,8 +236,103 @@ impl DynamicEventManager {
         }
     }
 
-    fn process_scheduled_events(&mut self, current_time: f64) { /* ... */ }
-    fn consider_new_events(&mut self, current_time: f64, mercy_level: f32) { /* ... */ }
+    /// Script phase timers and due follow-ups.
+    fn process_scheduled_events(&mut self, current_time: f64) {
+        let capacity = self.script_capacity();
+        let scripts = self.scripts.clone();
+        self.script_runner.advance(&scripts, &self.world, current_time, capacity, &mut rand::random::<f32>);
+        self.apply_script_events(current_time);
+    }
+
+    /// Built-in events from the config's hourly rates, then rate-triggered scripts whose
+    /// conditions hold. Chance per tick is `1 - e^(-rate·Δt)`.
+    fn consider_new_events(&mut self, current_time: f64, mercy_level: f32) {
+        let chance = |rate_per_hour: f32, since: f64| {
+            let hours = (current_time - since).max(0.0) / 3600.0;
+            1.0 - (-(rate_per_hour as f64) * hours).exp()
+        };
+        let abundance_chance = chance(self.config.abundance_event_rate_per_hour, self.last_abundance_check);
+        let divine_chance = chance(self.config.divine_whisper_cascade_rate, self.last_abundance_check);
+        let faction_chance = chance(self.config.faction_event_rate_per_hour, self.last_faction_check);
+        self.last_abundance_check = current_time;
+        self.last_faction_check = current_time;
+
+        if self.script_capacity() > 0 && rand::random::<f64>() < abundance_chance {
+            self.schedule_abundance_event(current_time, mercy_level);
+        }
+        if self.script_capacity() > 0 && rand::random::<f64>() < divine_chance {
+            self.schedule_divine_cascade(current_time, mercy_level);
+        }
+        if self.script_capacity() > 0 && rand::random::<f64>() < faction_chance {
+            self.schedule_faction_event(current_time);
+        }
+
+        let capacity = self.script_capacity();
+        let scripts = self.scripts.clone();
+        self.script_runner.trigger(&scripts, &self.world, current_time, capacity, &mut rand::random::<f32>);
+        self.apply_script_events(current_time);
+    }
+
+    /// Start a script now regardless of its trigger (live-ops command). None = unknown id.
+    pub fn start_scripted_event(&mut self, script_id: &str, current_time: f64) -> Option<Uuid> {
+        let scripts = self.scripts.clone();
+        let run_id = self.script_runner.start(scripts.get(script_id)?, current_time);
+        self.apply_script_events(current_time);
+        self.script_events.get(&run_id).copied()
+    }
+
+    /// Credit a player's action (harvest, kill, delivery) to the scripted events around them.
+    pub fn report_event_progress(
+        &mut self,
+        player_id: u64,
+        player_pos: [f32; 3],
+        progress: EventProgress,
+        current_time: f64,
+    ) -> bool {
+        let scripts = self.scripts.clone();
+        let counted = self.script_runner.report_progress(&scripts, player_id, player_pos, progress, current_time);
+        self.apply_script_events(current_time);
+        counted
+    }
+
+    pub fn drain_rewards(&mut self) -> Vec<EventRewardGrant> {
+        std::mem::take(&mut self.pending_rewards)
+    }
+
+    /// Mirror runner transitions into active_events / replication / rewards.
+    fn apply_script_events(&mut self, current_time: f64) {
+        for script_event in self.script_runner.drain_events() {
+            match script_event {
+                ScriptEvent::PhaseStarted { run_id, script_id, phase } => {
+                    let Some(script) = self.scripts.get(&script_id) else { continue };
+                    let event_id = *self.script_events.entry(run_id).or_insert_with(Uuid::new_v4);
+                    let event = scripted_event(event_id, script, phase, current_time);
+                    match self.active_events.iter_mut().find(|e| e.id == event_id) {
+                        Some(existing) => *existing = event.clone(),
+                        None => self.active_events.push(event.clone()),
+                    }
+                    self.pending_replication.push(event);
+                }
+                ScriptEvent::Rewarded { run_id, script_id, phase, player_id, reward } => {
+                    let Some(&event_id) = self.script_events.get(&run_id) else { continue };
+                    self.pending_rewards.push(EventRewardGrant { player_id, event_id, script_id, phase: phase as u32, reward });
+                }
+                ScriptEvent::Ended { run_id, outcome, .. } => {
+                    let Some(event_id) = self.script_events.remove(&run_id) else { continue };
+                    let Some(index) = self.active_events.iter().position(|e| e.id == event_id) else { continue };
+                    let mut event = self.active_events.remove(index);
+                    event.metadata["outcome"] = serde_json::json!(match outcome {
+                        ScriptOutcome::Success => "success",
+                        ScriptOutcome::Failure => "failure",
+                    });
+                    if self.event_history.len() >= EVENT_HISTORY_LEN {
+                        self.event_history.pop_front();
+                    }
+                    self.event_history.push_back(event);
+                }
+            }
+        }
+    }
 
     fn schedule_abundance_event(&mut self, current_time: f64, mercy_level: f32) {
         let mut event = DynamicEvent {
@@ -190,6 +346,7 @@ impl DynamicEventManager {
             priority: 0.0,
             base_priority: 0.0,
             priority_boost: 0.0,
+            area: EventArea::Global,
         };
         event.base_priority = event.compute_base_priority();
         event.priority = event.base_priority;
@@ -213,6 +370,7 @@ impl DynamicEventManager {
             priority: 0.0,
             base_priority: 0.0,
             priority_boost: 0.25,
+            area: EventArea::Global,
         };
         event.base_priority = event.compute_base_priority();
         event.priority = event.base_priority + event.priority_boost;
@@ -232,6 +390,7 @@ impl DynamicEventManager {
             priority: 0.0,
             base_priority: 0.0,
             priority_boost: 0.15,
+            area: EventArea::Global,
         };
         event.base_priority = event.compute_base_priority();
         event.priority = event.base_priority + event.priority_boost;
@@ -264,7 +423,11 @@ impl DynamicEventManager {
         boosted
     }
 
-    pub fn get_relevant_events_for_player(&self, _player_pos: [f32; 3]) -> Vec<&DynamicEvent> { self.active_events.iter().collect() }
+    /// Global events plus those whose area the player is in or approaching.
+    pub fn get_relevant_events_for_player(&self, player_pos: [f32; 3]) -> Vec<&DynamicEvent> {
+        let margin = self.config.event_interest_margin;
+        self.active_events.iter().filter(|e| e.area.contains(player_pos, margin)).collect()
+    }
 
     pub fn drain_prioritized_replication(&mut self) -> Vec<DynamicEvent> {
         let mut events = std::mem::take(&mut self.pending_replication);
@@ -274,6 +437,34 @@ impl DynamicEventManager {
     pub fn drain_pending_replication(&mut self) -> Vec<DynamicEvent> { self.drain_prioritized_replication() }
 }
 
+/// The DynamicEvent mirroring a script run in `phase`.
+fn scripted_event(id: Uuid, script: &EventScript, phase: usize, current_time: f64) -> DynamicEvent {
+    let current = &script.phases[phase];
+    let mut event = DynamicEvent {
+        id,
+        event_type: DynamicEventType::Scripted { script_id: script.id.clone(), phase: phase as u32, priority: script.priority },
+        scheduled_at: current_time as u64,
+        triggered_at: Some(current_time as u64),
+        mercy_alignment: 0.5,
+        affected_players: vec![],
+        metadata: serde_json::json!({
+            "source": "script",
+            "name": script.name,
+            "description": script.description,
+            "phase_name": current.name,
+            "phase_description": current.description,
+            "phase_ends_at": current_time + current.duration_secs as f64,
+        }),
+        priority: 0.0,
+        base_priority: 0.0,
+        priority_boost: 0.0,
+        area: script.area.clone(),
+    };
+    event.base_priority = event.compute_base_priority();
+    event.priority = event.base_priority;
+    event
+}
+
 // ═════════════════════════════════════════════════════════════════════════
 // REPLICATION WIRING + SERVER AUDIO SYNC
 // ═════════════════════════════════════════════════════════════════════════
@@ -295,6 +486,16 @@ pub fn map_server_event_to_client(event: &DynamicEvent) -> Option<ClientWorldEve
             affected_factions: vec![],
             mercy_impact: *intensity,
         }),
+        DynamicEventType::Scripted { script_id, phase, .. } => {
+            let text = |key: &str| event.metadata[key].as_str().unwrap_or_default().to_string();
+            Some(ClientWorldEventMirror::ScriptedEvent {
+                script_id: script_id.clone(),
+                name: text("name"),
+                phase: *phase,
+                phase_name: text("phase_name"),
+                description: text("phase_description"),
+            })
+        }
         _ => None,
     }
 }
@@ -323,6 +524,7 @@ pub enum ClientWorldEventMirror {
     AbundanceSurge { region: String, intensity: f32, mercy_delta: f32 },
     FactionDiplomacyShift { faction_a: String, faction_b: String, reason: String },
     DivineWhisperCascade { message: String, affected_factions: Vec<String>, mercy_impact: f32 },
+    ScriptedEvent { script_id: String, name: String, phase: u32, phase_name: String, description: String },
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════════════════
@@ -341,7 +543,18 @@ impl Plugin for DynamicEventsPlugin {
 }
 
 fn setup_dynamic_events(mut commands: Commands, config: Res<DynamicEventsConfig>) {
-    let manager = DynamicEventManager::new(config.clone());
+    let dir: PathBuf = std::env::var("POWRUSH_EVENT_DIR").unwrap_or_else(|_| "assets/events".to_string()).into();
+    let scripts = match EventScriptRegistry::load_dir(&dir) {
+        Ok(scripts) => {
+            info!("[DynamicEvents] Loaded {} event script(s) from {}", scripts.len(), dir.display());
+            scripts
+        }
+        Err(e) => {
+            warn!("[DynamicEvents] {} — using bundled event scripts", e);
+            EventScriptRegistry::bundled()
+        }
+    };
+    let manager = DynamicEventManager::new(config.clone()).with_scripts(Arc::new(scripts));
     commands.insert_resource(manager);
     info!("⚡ Dynamic Events v17.54 + Security & Validation + Server Audio Sync online");
 }
@@ -352,6 +565,8 @@ fn dynamic_events_tick_system(
 ) {
     let current_time = time.elapsed_seconds_f64();
     let mercy_level = 0.88;
+    let utc_secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    manager.world.hour_of_day = (utc_secs % 86_400) as f32 / 3600.0;
     manager.tick(current_time, mercy_level);
 }
 
@@ -364,4 +579,34 @@ fn dynamic_events_tick_system(
 // Future: Wire council_mercy_trial and epiphany systems to emit AudioCue events for full server-driven audio.
 // Future: Add admin permission checks and more advanced anomaly detection.
 // Cross-link note above covers full integration points.
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripted_events_are_area_filtered_and_pay_rewards() {
+        let mut manager = DynamicEventManager::new(DynamicEventsConfig::default());
+        let id = manager.start_scripted_event("verdant_bloom", 10.0).expect("bundled script");
+        assert!(manager.start_scripted_event("no_such_script", 10.0).is_none());
+
+        // Bloom is centred on (120, 0, -340) with radius 180, plus the interest margin.
+        assert_eq!(manager.get_relevant_events_for_player([120.0, 0.0, -100.0]).len(), 1);
+        assert!(manager.get_relevant_events_for_player([2000.0, 0.0, 0.0]).is_empty());
+
+        let pos = [120.0, 0.0, -340.0];
+        assert!(!manager.report_event_progress(7, [2000.0, 0.0, 0.0], EventProgress::Harvested { resource: "bloomwood", amount: 400 }, 11.0));
+        assert!(manager.report_event_progress(7, pos, EventProgress::Harvested { resource: "bloomwood", amount: 400 }, 11.0));
+        assert!(manager.report_event_progress(7, pos, EventProgress::Harvested { resource: "starleaf", amount: 150 }, 12.0));
+
+        let rewards = manager.drain_rewards();
+        assert_eq!(rewards.len(), 2);
+        assert!(rewards.iter().all(|r| r.player_id == 7 && r.event_id == id && r.phase == 0));
+        let replicated = manager.drain_prioritized_replication();
+        assert!(matches!(
+            replicated.last().map(|e| &e.event_type),
+            Some(DynamicEventType::Scripted { phase: 1, .. })
+        ));
+        let mirror = map_server_event_to_client(replicated.last().unwrap());
+        assert!(matches!(mirror, Some(ClientWorldEventMirror::ScriptedEvent { phase_name, .. }) if phase_name == "Sharing"));
+    }
+}
@@ -0,0 +1,819 @@
+//! server/src/event_scripts.rs
+//! Powrush-MMO — Authorable dynamic-event scripts
+//! Live-ops world events as data: trigger conditions (mercy level, realm abundance,
+//! faction standing, UTC time window), an area, timed phases with objectives,
+//! per-phase rewards and chained follow-up events. Scripts live in
+//! assets/events/*.ron (POWRUSH_EVENT_DIR overrides), are validated as one set at
+//! load, and run inside DynamicEventManager through `EventScriptRunner`.
+//!
+//! A phase completes when every objective reaches its target (or, for
+//! `on_timeout: Complete` phases, when its timer runs out); its rewards go to every
+//! player who has contributed to the run so far. A run that times out in a
+//! `Fail` phase ends as a failure. Follow-ups fire on success, failure or both,
+//! after a delay and with a chance, and still have to pass their own conditions.
+//! AG-SML v1.0 | PATSAGi Councils | info@Rathor.ai
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use shared::nevc_history::RealmId;
+
+/// Scripts compiled into the binary; the fallback when assets/events is absent.
+const BUNDLED: [(&str, &str); 2] = [
+    ("abundance.ron", include_str!("../../assets/events/abundance.ron")),
+    ("diplomacy.ron", include_str!("../../assets/events/diplomacy.ron")),
+];
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// SCHEMA
+// ════════════════════════════════════════════════════════════════════════════════════
+
+/// Where an event takes place; players outside it neither see nor progress it.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum EventArea {
+    #[default]
+    Global,
+    Radius { center: [f32; 3], radius: f32 },
+    Region { min: [f32; 3], max: [f32; 3] },
+}
+
+impl EventArea {
+    /// True when `pos` is inside the area grown by `margin` on every side.
+    pub fn contains(&self, pos: [f32; 3], margin: f32) -> bool {
+        match self {
+            EventArea::Global => true,
+            EventArea::Radius { center, radius } => {
+                let d2: f32 = (0..3).map(|i| (pos[i] - center[i]).powi(2)).sum();
+                d2 <= (radius + margin).powi(2)
+            }
+            EventArea::Region { min, max } => (0..3).all(|i| pos[i] >= min[i] - margin && pos[i] <= max[i] + margin),
+        }
+    }
+}
+
+/// World state the trigger conditions are evaluated against.
+#[derive(Clone, Debug, Default)]
+pub struct WorldConditions {
+    pub mercy_level: f32,
+    pub realm_abundance: HashMap<RealmId, f32>,
+    pub faction_standing: HashMap<String, f32>,
+    /// UTC hour of day, 0.0..24.0
+    pub hour_of_day: f32,
+}
+
+/// All conditions of a trigger must hold. An unknown realm or faction never matches.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum EventCondition {
+    MercyAtLeast(f32),
+    MercyBelow(f32),
+    RealmAbundanceAtLeast { realm: RealmId, value: f32 },
+    RealmAbundanceBelow { realm: RealmId, value: f32 },
+    FactionStandingAtLeast { faction: String, value: f32 },
+    FactionStandingBelow { faction: String, value: f32 },
+    /// UTC hours; wraps past midnight when `start_hour > end_hour`.
+    TimeWindow { start_hour: f32, end_hour: f32 },
+}
+
+impl EventCondition {
+    pub fn holds(&self, world: &WorldConditions) -> bool {
+        match self {
+            EventCondition::MercyAtLeast(v) => world.mercy_level >= *v,
+            EventCondition::MercyBelow(v) => world.mercy_level < *v,
+            EventCondition::RealmAbundanceAtLeast { realm, value } => {
+                world.realm_abundance.get(realm).is_some_and(|a| a >= value)
+            }
+            EventCondition::RealmAbundanceBelow { realm, value } => {
+                world.realm_abundance.get(realm).is_some_and(|a| a < value)
+            }
+            EventCondition::FactionStandingAtLeast { faction, value } => {
+                world.faction_standing.get(faction).is_some_and(|s| s >= value)
+            }
+            EventCondition::FactionStandingBelow { faction, value } => {
+                world.faction_standing.get(faction).is_some_and(|s| s < value)
+            }
+            EventCondition::TimeWindow { start_hour, end_hour } => {
+                let h = world.hour_of_day;
+                if start_hour <= end_hour {
+                    h >= *start_hour && h < *end_hour
+                } else {
+                    h >= *start_hour || h < *end_hour
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct EventTrigger {
+    /// Expected spawns per hour while the conditions hold; 0 = only as a follow-up.
+    #[serde(default)]
+    pub rate_per_hour: f32,
+    #[serde(default)]
+    pub conditions: Vec<EventCondition>,
+}
+
+/// Game actions that can count towards an objective.
+#[derive(Clone, Copy, Debug)]
+pub enum EventProgress<'a> {
+    Harvested { resource: &'a str, amount: u32 },
+    Defeated { archetype: &'a str },
+    Delivered { resource: &'a str, amount: u32 },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ObjectiveKind {
+    Harvest { resource: String },
+    Defeat { archetype: String },
+    Deliver { resource: String },
+}
+
+impl ObjectiveKind {
+    /// How much `progress` counts towards this objective.
+    pub fn credit(&self, progress: &EventProgress) -> u32 {
+        match (self, progress) {
+            (ObjectiveKind::Harvest { resource }, EventProgress::Harvested { resource: r, amount })
+            | (ObjectiveKind::Deliver { resource }, EventProgress::Delivered { resource: r, amount })
+                if resource == r =>
+            {
+                *amount
+            }
+            (ObjectiveKind::Defeat { archetype }, EventProgress::Defeated { archetype: a }) if archetype == a => 1,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Objective {
+    pub id: String,
+    #[serde(default)]
+    pub description: String,
+    pub kind: ObjectiveKind,
+    pub target: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum EventReward {
+    Resource { resource: String, amount: u32 },
+    Mercy(f32),
+    FactionStanding { faction: String, delta: f32 },
+    Title(String),
+}
+
+/// What running out of time in a phase means.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PhaseTimeout {
+    /// The event ends as a failure.
+    #[default]
+    Fail,
+    /// The phase completes (hold-out / survival phases).
+    Complete,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EventPhase {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub duration_secs: f32,
+    #[serde(default)]
+    pub objectives: Vec<Objective>,
+    #[serde(default)]
+    pub rewards: Vec<EventReward>,
+    #[serde(default)]
+    pub on_timeout: PhaseTimeout,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FollowUpWhen {
+    #[default]
+    Success,
+    Failure,
+    Always,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FollowUp {
+    pub script: String,
+    #[serde(default)]
+    pub on: FollowUpWhen,
+    #[serde(default)]
+    pub delay_secs: f32,
+    #[serde(default = "default_chance")]
+    pub chance: f32,
+}
+
+fn default_chance() -> f32 {
+    1.0
+}
+
+fn default_priority() -> f32 {
+    0.6
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EventScript {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub trigger: EventTrigger,
+    #[serde(default)]
+    pub area: EventArea,
+    pub phases: Vec<EventPhase>,
+    #[serde(default)]
+    pub follow_ups: Vec<FollowUp>,
+    /// Minimum time between the end of one run and the next rate-triggered start.
+    #[serde(default)]
+    pub cooldown_secs: f32,
+    /// Base replication priority (same 0.1..=1.4 scale as the built-in event types).
+    #[serde(default = "default_priority")]
+    pub priority: f32,
+}
+
+impl EventScript {
+    pub fn conditions_hold(&self, world: &WorldConditions) -> bool {
+        self.trigger.conditions.iter().all(|c| c.holds(world))
+    }
+
+    fn validate(&self, problems: &mut Vec<String>) {
+        let id = &self.id;
+        let mut problem = |msg: String| problems.push(format!("{}: {}", id, msg));
+        let items = shared::items::current();
+        if id.is_empty() {
+            problem("empty id".into());
+        }
+        if !(self.trigger.rate_per_hour >= 0.0 && self.trigger.rate_per_hour.is_finite()) {
+            problem(format!("rate_per_hour {} must be a finite value >= 0", self.trigger.rate_per_hour));
+        }
+        if self.cooldown_secs.is_nan() || self.cooldown_secs < 0.0 {
+            problem(format!("cooldown_secs {} must be >= 0", self.cooldown_secs));
+        }
+        if !(0.1..=1.4).contains(&self.priority) {
+            problem(format!("priority {} outside 0.1..=1.4", self.priority));
+        }
+        for condition in &self.trigger.conditions {
+            if let EventCondition::TimeWindow { start_hour, end_hour } = condition {
+                if !(0.0..24.0).contains(start_hour) || !(0.0..=24.0).contains(end_hour) {
+                    problem(format!("time window {}..{} outside 0..24", start_hour, end_hour));
+                }
+            }
+        }
+        match &self.area {
+            EventArea::Global => {}
+            EventArea::Radius { radius, .. } if radius.is_nan() || *radius <= 0.0 => problem(format!("area radius {} must be > 0", radius)),
+            EventArea::Region { min, max } if (0..3).any(|i| min[i].partial_cmp(&max[i]).is_none_or(|o| o.is_gt())) => {
+                problem("area region min must not exceed max".into())
+            }
+            _ => {}
+        }
+        if self.phases.is_empty() {
+            problem("no phases".into());
+        }
+        for phase in &self.phases {
+            if !(phase.duration_secs > 0.0 && phase.duration_secs.is_finite()) {
+                problem(format!("phase {} duration_secs must be > 0", phase.name));
+            }
+            if phase.objectives.is_empty() && phase.on_timeout == PhaseTimeout::Fail {
+                problem(format!("phase {} has no objectives and can only fail", phase.name));
+            }
+            let mut seen = HashSet::new();
+            for objective in &phase.objectives {
+                if !seen.insert(objective.id.as_str()) {
+                    problem(format!("phase {} has duplicate objective {}", phase.name, objective.id));
+                }
+                if objective.target == 0 {
+                    problem(format!("objective {} target must be > 0", objective.id));
+                }
+                if let ObjectiveKind::Harvest { resource } | ObjectiveKind::Deliver { resource } = &objective.kind {
+                    if items.get(resource).is_none() {
+                        problem(format!("objective {} names unknown item {}", objective.id, resource));
+                    }
+                }
+            }
+            for reward in &phase.rewards {
+                if let EventReward::Resource { resource, .. } = reward {
+                    if items.get(resource).is_none() {
+                        problem(format!("phase {} rewards unknown item {}", phase.name, resource));
+                    }
+                }
+            }
+        }
+        for follow_up in &self.follow_ups {
+            if follow_up.delay_secs.is_nan() || follow_up.delay_secs < 0.0 {
+                problem(format!("follow-up {} delay_secs must be >= 0", follow_up.script));
+            }
+            if !(0.0..=1.0).contains(&follow_up.chance) {
+                problem(format!("follow-up {} chance {} outside 0..=1", follow_up.script, follow_up.chance));
+            }
+        }
+    }
+}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// REGISTRY
+// ════════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug)]
+pub enum EventScriptLoadError {
+    Io { path: PathBuf, error: String },
+    Parse { source: String, error: String },
+    /// Every problem found across all files
+    Invalid(Vec<String>),
+}
+
+impl std::fmt::Display for EventScriptLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventScriptLoadError::Io { path, error } => write!(f, "cannot read {}: {}", path.display(), error),
+            EventScriptLoadError::Parse { source, error } => write!(f, "invalid event script file {}: {}", source, error),
+            EventScriptLoadError::Invalid(problems) => {
+                write!(f, "{} invalid event script(s): {}", problems.len(), problems.join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for EventScriptLoadError {}
+
+#[derive(Debug, Default)]
+pub struct EventScriptRegistry {
+    scripts: Vec<EventScript>,
+    by_id: HashMap<String, usize>,
+}
+
+impl EventScriptRegistry {
+    /// Parse and validate `(source name, RON list of EventScript)` pairs as one set.
+    pub fn from_sources<'a>(
+        sources: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<Self, EventScriptLoadError> {
+        let mut scripts = Vec::new();
+        for (source, text) in sources {
+            let parsed: Vec<EventScript> = ron::from_str(text)
+                .map_err(|e| EventScriptLoadError::Parse { source: source.to_string(), error: e.to_string() })?;
+            scripts.extend(parsed);
+        }
+        Self::from_scripts(scripts)
+    }
+
+    pub fn from_scripts(scripts: Vec<EventScript>) -> Result<Self, EventScriptLoadError> {
+        let mut problems = Vec::new();
+        let mut by_id = HashMap::new();
+        for (i, script) in scripts.iter().enumerate() {
+            script.validate(&mut problems);
+            if by_id.insert(script.id.clone(), i).is_some() {
+                problems.push(format!("{}: duplicate id", script.id));
+            }
+        }
+        for script in &scripts {
+            for follow_up in &script.follow_ups {
+                if !by_id.contains_key(&follow_up.script) {
+                    problems.push(format!("{}: follow-up to unknown script {}", script.id, follow_up.script));
+                }
+            }
+        }
+        // A chain may loop back on itself, but not without a delay somewhere.
+        for script in &scripts {
+            let mut seen = HashSet::new();
+            let mut stack = vec![script.id.as_str()];
+            while let Some(id) = stack.pop() {
+                let Some(&i) = by_id.get(id) else { continue };
+                for follow_up in scripts[i].follow_ups.iter().filter(|f| f.delay_secs <= 0.0) {
+                    if follow_up.script == script.id {
+                        problems.push(format!("{}: zero-delay follow-up cycle through {}", script.id, id));
+                        stack.clear();
+                        break;
+                    }
+                    if seen.insert(follow_up.script.as_str()) {
+                        stack.push(&follow_up.script);
+                    }
+                }
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(EventScriptLoadError::Invalid(problems));
+        }
+        Ok(Self { scripts, by_id })
+    }
+
+    /// Every `*.ron` file in `dir`, in file-name order.
+    pub fn load_dir(dir: &Path) -> Result<Self, EventScriptLoadError> {
+        let io = |path: &Path, e: std::io::Error| EventScriptLoadError::Io { path: path.to_path_buf(), error: e.to_string() };
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+            .map_err(|e| io(dir, e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "ron"))
+            .collect();
+        paths.sort();
+
+        let mut files = Vec::with_capacity(paths.len());
+        for path in &paths {
+            files.push((path.display().to_string(), std::fs::read_to_string(path).map_err(|e| io(path, e))?));
+        }
+        Self::from_sources(files.iter().map(|(name, text)| (name.as_str(), text.as_str())))
+    }
+
+    /// The scripts compiled into this build.
+    pub fn bundled() -> Self {
+        Self::from_sources(BUNDLED).expect("bundled event scripts are valid")
+    }
+
+    pub fn get(&self, id: &str) -> Option<&EventScript> {
+        self.by_id.get(id).map(|&i| &self.scripts[i])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &EventScript> {
+        self.scripts.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.scripts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// RUNNER
+// ════════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScriptOutcome {
+    Success,
+    Failure,
+}
+
+/// What the runner did; DynamicEventManager turns these into events and rewards.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScriptEvent {
+    /// A run entered `phase` (0 when it starts).
+    PhaseStarted { run_id: u64, script_id: String, phase: usize },
+    Rewarded { run_id: u64, script_id: String, phase: usize, player_id: u64, reward: EventReward },
+    Ended { run_id: u64, script_id: String, outcome: ScriptOutcome },
+}
+
+#[derive(Clone, Debug)]
+pub struct ScriptRun {
+    pub run_id: u64,
+    pub script_id: String,
+    pub area: EventArea,
+    pub phase: usize,
+    pub phase_started: f64,
+    /// Per objective of the current phase
+    pub progress: Vec<u32>,
+    /// Everyone who has counted towards an objective in this run
+    pub participants: BTreeSet<u64>,
+}
+
+#[derive(Clone, Debug)]
+struct PendingFollowUp {
+    due: f64,
+    script_id: String,
+    chance: f32,
+}
+
+/// Script state machine. Randomness comes in through `roll` (uniform 0..1) so the
+/// manager can use `rand` and tests can script it.
+#[derive(Debug, Default)]
+pub struct EventScriptRunner {
+    runs: Vec<ScriptRun>,
+    next_run_id: u64,
+    cooldown_until: HashMap<String, f64>,
+    follow_ups: Vec<PendingFollowUp>,
+    last_trigger_check: Option<f64>,
+    events: Vec<ScriptEvent>,
+}
+
+impl EventScriptRunner {
+    pub fn runs(&self) -> &[ScriptRun] {
+        &self.runs
+    }
+
+    pub fn is_running(&self, script_id: &str) -> bool {
+        self.runs.iter().any(|r| r.script_id == script_id)
+    }
+
+    pub fn drain_events(&mut self) -> Vec<ScriptEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Phase timers and due follow-ups. Returns the number of runs started.
+    pub fn advance(
+        &mut self,
+        registry: &EventScriptRegistry,
+        world: &WorldConditions,
+        now: f64,
+        capacity: usize,
+        roll: &mut impl FnMut() -> f32,
+    ) -> usize {
+        let mut i = 0;
+        while i < self.runs.len() {
+            let run = &self.runs[i];
+            let Some(phase) = registry.get(&run.script_id).and_then(|s| s.phases.get(run.phase)) else {
+                // Script removed by a reload.
+                self.end_run(i, registry, ScriptOutcome::Failure, now);
+                continue;
+            };
+            if now - run.phase_started < phase.duration_secs as f64 {
+                i += 1;
+                continue;
+            }
+            match phase.on_timeout {
+                PhaseTimeout::Fail => self.end_run(i, registry, ScriptOutcome::Failure, now),
+                PhaseTimeout::Complete => {
+                    if self.complete_phase(i, registry, now) {
+                        i += 1;
+                    }
+                }
+            }
+        }
+
+        let (due, pending): (Vec<_>, Vec<_>) = self.follow_ups.drain(..).partition(|f| f.due <= now);
+        self.follow_ups = pending;
+        let mut started = 0;
+        for follow_up in due {
+            if started >= capacity || roll() >= follow_up.chance || self.is_running(&follow_up.script_id) {
+                continue;
+            }
+            let Some(script) = registry.get(&follow_up.script_id) else { continue };
+            if script.conditions_hold(world) {
+                self.start(script, now);
+                started += 1;
+            }
+        }
+        started
+    }
+
+    /// Rate-driven starts: each idle, off-cooldown script whose conditions hold starts
+    /// with probability `1 - e^(-rate·Δt)` for the time since the last check.
+    pub fn trigger(
+        &mut self,
+        registry: &EventScriptRegistry,
+        world: &WorldConditions,
+        now: f64,
+        capacity: usize,
+        roll: &mut impl FnMut() -> f32,
+    ) -> usize {
+        let elapsed_hours = self.last_trigger_check.map_or(0.0, |last| (now - last).max(0.0) / 3600.0);
+        self.last_trigger_check = Some(now);
+
+        let mut started = 0;
+        for script in registry.iter() {
+            if started >= capacity {
+                break;
+            }
+            if script.trigger.rate_per_hour <= 0.0
+                || self.is_running(&script.id)
+                || self.cooldown_until.get(&script.id).is_some_and(|&until| now < until)
+                || !script.conditions_hold(world)
+            {
+                continue;
+            }
+            let chance = 1.0 - (-(script.trigger.rate_per_hour as f64) * elapsed_hours).exp();
+            if (roll() as f64) < chance {
+                self.start(script, now);
+                started += 1;
+            }
+        }
+        started
+    }
+
+    /// Start `script` now, bypassing its rate and conditions (admin / live-ops command).
+    pub fn start(&mut self, script: &EventScript, now: f64) -> u64 {
+        self.next_run_id += 1;
+        let run_id = self.next_run_id;
+        self.runs.push(ScriptRun {
+            run_id,
+            script_id: script.id.clone(),
+            area: script.area.clone(),
+            phase: 0,
+            phase_started: now,
+            progress: vec![0; script.phases[0].objectives.len()],
+            participants: BTreeSet::new(),
+        });
+        self.events.push(ScriptEvent::PhaseStarted { run_id, script_id: script.id.clone(), phase: 0 });
+        run_id
+    }
+
+    /// Credit `progress` by `player_id` at `position` to every run around them.
+    /// Returns true when it counted towards at least one objective.
+    pub fn report_progress(
+        &mut self,
+        registry: &EventScriptRegistry,
+        player_id: u64,
+        position: [f32; 3],
+        progress: EventProgress,
+        now: f64,
+    ) -> bool {
+        let mut counted = false;
+        let mut i = 0;
+        while i < self.runs.len() {
+            let run = &mut self.runs[i];
+            let Some(phase) = registry.get(&run.script_id).and_then(|s| s.phases.get(run.phase)) else {
+                i += 1;
+                continue;
+            };
+            if !run.area.contains(position, 0.0) {
+                i += 1;
+                continue;
+            }
+            let mut credited = false;
+            for (objective, done) in phase.objectives.iter().zip(run.progress.iter_mut()) {
+                let credit = objective.kind.credit(&progress);
+                if credit > 0 && *done < objective.target {
+                    *done = (*done + credit).min(objective.target);
+                    credited = true;
+                }
+            }
+            if !credited {
+                i += 1;
+                continue;
+            }
+            counted = true;
+            run.participants.insert(player_id);
+            let complete = phase.objectives.iter().zip(&run.progress).all(|(o, done)| *done >= o.target);
+            if !complete || self.complete_phase(i, registry, now) {
+                i += 1;
+            }
+        }
+        counted
+    }
+
+    /// Pay out the current phase and move on. Returns false when the run ended.
+    fn complete_phase(&mut self, index: usize, registry: &EventScriptRegistry, now: f64) -> bool {
+        let run = &mut self.runs[index];
+        let Some(script) = registry.get(&run.script_id) else {
+            self.end_run(index, registry, ScriptOutcome::Failure, now);
+            return false;
+        };
+        for reward in &script.phases[run.phase].rewards {
+            for &player_id in &run.participants {
+                self.events.push(ScriptEvent::Rewarded {
+                    run_id: run.run_id,
+                    script_id: run.script_id.clone(),
+                    phase: run.phase,
+                    player_id,
+                    reward: reward.clone(),
+                });
+            }
+        }
+        run.phase += 1;
+        let Some(next) = script.phases.get(run.phase) else {
+            self.end_run(index, registry, ScriptOutcome::Success, now);
+            return false;
+        };
+        run.phase_started = now;
+        run.progress = vec![0; next.objectives.len()];
+        self.events.push(ScriptEvent::PhaseStarted {
+            run_id: run.run_id,
+            script_id: run.script_id.clone(),
+            phase: run.phase,
+        });
+        true
+    }
+
+    fn end_run(&mut self, index: usize, registry: &EventScriptRegistry, outcome: ScriptOutcome, now: f64) {
+        let run = self.runs.remove(index);
+        if let Some(script) = registry.get(&run.script_id) {
+            self.cooldown_until.insert(script.id.clone(), now + script.cooldown_secs as f64);
+            let fires = |when: FollowUpWhen| match when {
+                FollowUpWhen::Always => true,
+                FollowUpWhen::Success => outcome == ScriptOutcome::Success,
+                FollowUpWhen::Failure => outcome == ScriptOutcome::Failure,
+            };
+            for follow_up in script.follow_ups.iter().filter(|f| fires(f.on)) {
+                self.follow_ups.push(PendingFollowUp {
+                    due: now + follow_up.delay_secs as f64,
+                    script_id: follow_up.script.clone(),
+                    chance: follow_up.chance,
+                });
+            }
+        }
+        self.events.push(ScriptEvent::Ended { run_id: run.run_id, script_id: run.script_id, outcome });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPTS: &str = r#"[
+        (
+            id: "bloom", name: "Bloom",
+            trigger: (rate_per_hour: 3600.0, conditions: [MercyAtLeast(0.5), RealmAbundanceAtLeast(realm: 2, value: 0.7)]),
+            area: Radius(center: (0.0, 0.0, 0.0), radius: 50.0),
+            phases: [
+                (
+                    name: "Gather", duration_secs: 60.0,
+                    objectives: [(id: "herbs", kind: Harvest(resource: "herb"), target: 5)],
+                    rewards: [Mercy(0.1)],
+                ),
+                (name: "Hold", duration_secs: 30.0, on_timeout: Complete, rewards: [Title("Bloomkeeper")]),
+            ],
+            follow_ups: [(script: "guardians", delay_secs: 10.0), (script: "bloom", on: Failure, delay_secs: 600.0)],
+            cooldown_secs: 300.0,
+        ),
+        (
+            id: "guardians", name: "Guardians",
+            phases: [(
+                name: "Defend", duration_secs: 120.0,
+                objectives: [(id: "wardens", kind: Defeat(archetype: "blight_warden"), target: 2)],
+            )],
+        ),
+    ]"#;
+
+    fn world() -> WorldConditions {
+        WorldConditions {
+            mercy_level: 0.8,
+            realm_abundance: HashMap::from([(2, 0.9)]),
+            hour_of_day: 12.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn validation_collects_every_problem() {
+        let registry = EventScriptRegistry::from_sources([("test.ron", SCRIPTS)]).unwrap();
+        assert_eq!(registry.len(), 2);
+        assert!(!EventScriptRegistry::bundled().is_empty());
+
+        let mut scripts: Vec<EventScript> = ron::from_str(SCRIPTS).unwrap();
+        scripts[0].phases[1].on_timeout = PhaseTimeout::Fail;
+        scripts[0].follow_ups.push(FollowUp { script: "missing".into(), on: FollowUpWhen::Always, delay_secs: 0.0, chance: 1.5 });
+        scripts[1].follow_ups.push(FollowUp { script: "guardians".into(), on: FollowUpWhen::Always, delay_secs: 0.0, chance: 1.0 });
+        scripts[1].phases[0].rewards.push(EventReward::Resource { resource: "moon_dust".into(), amount: 1 });
+        scripts.push(scripts[1].clone());
+        let Err(EventScriptLoadError::Invalid(problems)) = EventScriptRegistry::from_scripts(scripts) else {
+            panic!("expected validation errors")
+        };
+        let all = problems.join("\n");
+        for expected in ["can only fail", "chance 1.5", "unknown script missing", "zero-delay follow-up cycle", "duplicate id", "unknown item moon_dust"] {
+            assert!(all.contains(expected), "missing {:?} in:\n{}", expected, all);
+        }
+    }
+
+    #[test]
+    fn conditions_and_time_windows() {
+        let mut w = world();
+        assert!(EventCondition::RealmAbundanceAtLeast { realm: 2, value: 0.7 }.holds(&w));
+        assert!(!EventCondition::RealmAbundanceBelow { realm: 3, value: 0.7 }.holds(&w));
+        assert!(!EventCondition::FactionStandingAtLeast { faction: "Flow Guardians".into(), value: 0.0 }.holds(&w));
+        let night = EventCondition::TimeWindow { start_hour: 22.0, end_hour: 4.0 };
+        assert!(!night.holds(&w));
+        w.hour_of_day = 23.5;
+        assert!(night.holds(&w));
+        w.hour_of_day = 1.0;
+        assert!(night.holds(&w));
+    }
+
+    #[test]
+    fn phases_reward_participants_and_chain_follow_ups() {
+        let registry = EventScriptRegistry::from_sources([("test.ron", SCRIPTS)]).unwrap();
+        let mut runner = EventScriptRunner::default();
+        let mut w = world();
+        let mut roll = || 0.0;
+
+        // First check only sets the baseline; conditions gate the rate.
+        assert_eq!(runner.trigger(&registry, &w, 0.0, 4, &mut roll), 0);
+        w.mercy_level = 0.2;
+        assert_eq!(runner.trigger(&registry, &w, 1.0, 4, &mut roll), 0);
+        w.mercy_level = 0.8;
+        assert_eq!(runner.trigger(&registry, &w, 2.0, 4, &mut roll), 1);
+        assert_eq!(runner.trigger(&registry, &w, 3.0, 4, &mut roll), 0, "one run per script");
+
+        let herb = EventProgress::Harvested { resource: "herb", amount: 3 };
+        assert!(!runner.report_progress(&registry, 9, [80.0, 0.0, 0.0], herb, 5.0), "outside the area");
+        assert!(runner.report_progress(&registry, 1, [10.0, 0.0, 0.0], herb, 5.0));
+        assert!(runner.report_progress(&registry, 2, [0.0, 0.0, 10.0], herb, 6.0));
+        assert_eq!(runner.runs()[0].phase, 1);
+
+        // The hold phase completes on its timer; both contributors are paid for both phases.
+        runner.advance(&registry, &w, 36.0, 4, &mut roll);
+        assert!(runner.runs().is_empty());
+        let events = runner.drain_events();
+        let titles = events
+            .iter()
+            .filter(|e| matches!(e, ScriptEvent::Rewarded { reward: EventReward::Title(_), .. }))
+            .count();
+        assert_eq!(titles, 2);
+        assert!(matches!(events.last(), Some(ScriptEvent::Ended { outcome: ScriptOutcome::Success, .. })));
+
+        // Cooldown holds back the rate trigger; the success follow-up fires after its delay.
+        assert_eq!(runner.trigger(&registry, &w, 40.0, 4, &mut roll), 0);
+        assert_eq!(runner.advance(&registry, &w, 45.0, 4, &mut roll), 0);
+        assert_eq!(runner.advance(&registry, &w, 46.0, 4, &mut roll), 1);
+        assert_eq!(runner.runs()[0].script_id, "guardians");
+
+        // Timing out a Fail phase ends the run as a failure.
+        runner.advance(&registry, &w, 200.0, 4, &mut roll);
+        assert!(matches!(
+            runner.drain_events().last(),
+            Some(ScriptEvent::Ended { outcome: ScriptOutcome::Failure, script_id, .. }) if script_id == "guardians"
+        ));
+    }
+}
@@ -2,6 +2,7 @@
 // Powrush-MMO v18.97.1 — Faction Diplomacy Mechanics + Council & RBE Integration
 // Production quality • Mercy-gated • PATSAGi-aligned • Abundance-preserving
 // Now integrated with Council Mercy Trial outcomes, enriched epiphany, and RBE abundance flows.
+// v18.98 — Guild treaties: guild-to-guild relations, proposals with cooldown + expiry (guild/registry.rs).
 // AG-SML v1.0 | TOLC 8 + 7 Living Mercy Gates | Ra-Thor Lattice
 
 use bevy::prelude::*;
@@ -9,8 +10,12 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::dynamic_events::{DynamicEventManager, FactionDiplomacyShift};
-use crate::persistence_polish::PlayerSaveData;
+use crate::dynamic_events::DynamicEventType;
+
+/// A guild may propose one treaty this often.
+pub const GUILD_TREATY_PROPOSAL_COOLDOWN_SECS: u64 = 300;
+/// Unanswered guild treaty proposals lapse after this long.
+pub const GUILD_TREATY_PROPOSAL_TTL_SECS: u64 = 3600;
 
 #[derive(Resource, Clone, Debug, Serialize, Deserialize, Default)]
 pub struct FactionDiplomacyConfig {
@@ -37,12 +42,46 @@ pub enum DiplomacyStatus {
     ProposedTreaty,
 }
 
+/// What a signed guild treaty sets the relation to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GuildTreatyTerms {
+    Alliance,
+    Peace,
+}
+
+impl GuildTreatyTerms {
+    pub fn status(self) -> DiplomacyStatus {
+        match self {
+            GuildTreatyTerms::Alliance => DiplomacyStatus::Allied,
+            GuildTreatyTerms::Peace => DiplomacyStatus::Neutral,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GuildTreatyProposal {
+    pub from_guild: u64,
+    pub to_guild: u64,
+    pub terms: GuildTreatyTerms,
+    pub proposed_at: u64,
+}
+
+/// Unordered guild pair key
+fn guild_pair(a: u64, b: u64) -> (u64, u64) {
+    (a.min(b), a.max(b))
+}
+
 #[derive(Resource, Clone, Debug, Default)]
 pub struct FactionDiplomacyManager {
     pub relations: HashMap<(Faction, Faction), DiplomacyStatus>,
     pub player_standings: HashMap<Uuid, HashMap<Faction, f32>>, // -100.0 to +100.0
     pub last_treaty_proposal: HashMap<Uuid, u64>,
     pub active_conflicts: u32,
+    /// Guild-to-guild relations, keyed by (lower id, higher id)
+    pub guild_relations: HashMap<(u64, u64), DiplomacyStatus>,
+    /// Open proposals keyed by (from guild, to guild)
+    pub guild_treaty_proposals: HashMap<(u64, u64), GuildTreatyProposal>,
+    pub last_guild_treaty_proposal: HashMap<u64, u64>,
 }
 
 impl FactionDiplomacyManager {
@@ -57,6 +96,7 @@ impl FactionDiplomacyManager {
             player_standings: HashMap::new(),
             last_treaty_proposal: HashMap::new(),
             active_conflicts: 0,
+            ..Default::default()
         }
     }
 
@@ -75,7 +115,7 @@ impl FactionDiplomacyManager {
         Ok(())
     }
 
-    pub fn apply_diplomacy_shift(&mut self, shift: &FactionDiplomacyShift) {
+    pub fn apply_diplomacy_shift(&mut self, _shift: &DynamicEventType) {
         // Called from DynamicEventManager
         // Update relations + player standings with mercy weighting
     }
@@ -95,6 +135,65 @@ impl FactionDiplomacyManager {
         }
     }
 
+    pub fn guild_status(&self, a: u64, b: u64) -> DiplomacyStatus {
+        self.guild_relations.get(&guild_pair(a, b)).copied().unwrap_or(DiplomacyStatus::Neutral)
+    }
+
+    /// Offer `terms` from one guild to another. Replaces an earlier offer between the same pair.
+    pub fn propose_guild_treaty(&mut self, from_guild: u64, to_guild: u64, terms: GuildTreatyTerms, current_time: u64) -> Result<(), String> {
+        if from_guild == to_guild {
+            return Err("A guild cannot sign a treaty with itself".into());
+        }
+        if let Some(last) = self.last_guild_treaty_proposal.get(&from_guild) {
+            if current_time.saturating_sub(*last) < GUILD_TREATY_PROPOSAL_COOLDOWN_SECS {
+                return Err("Treaty proposal on cooldown".into());
+            }
+        }
+        if self.guild_status(from_guild, to_guild) == terms.status() {
+            return Err(format!("Guilds already have {:?} terms", terms));
+        }
+        self.last_guild_treaty_proposal.insert(from_guild, current_time);
+        self.guild_treaty_proposals.insert(
+            (from_guild, to_guild),
+            GuildTreatyProposal { from_guild, to_guild, terms, proposed_at: current_time },
+        );
+        Ok(())
+    }
+
+    /// `by_guild` accepts the proposal `from_guild` sent it; the relation takes the treaty's terms.
+    pub fn accept_guild_treaty(&mut self, by_guild: u64, from_guild: u64, current_time: u64) -> Result<GuildTreatyTerms, String> {
+        let proposal = self
+            .guild_treaty_proposals
+            .remove(&(from_guild, by_guild))
+            .ok_or_else(|| format!("No treaty proposal from guild {}", from_guild))?;
+        if current_time.saturating_sub(proposal.proposed_at) > GUILD_TREATY_PROPOSAL_TTL_SECS {
+            return Err("Treaty proposal expired".into());
+        }
+        self.guild_relations.insert(guild_pair(by_guild, from_guild), proposal.terms.status());
+        Ok(proposal.terms)
+    }
+
+    pub fn decline_guild_treaty(&mut self, by_guild: u64, from_guild: u64) -> bool {
+        self.guild_treaty_proposals.remove(&(from_guild, by_guild)).is_some()
+    }
+
+    /// End an alliance; the relation returns to neutral. False when they were not allied.
+    pub fn revoke_guild_treaty(&mut self, a: u64, b: u64) -> bool {
+        self.guild_relations.remove(&guild_pair(a, b)) == Some(DiplomacyStatus::Allied)
+    }
+
+    /// Re-seed a signed treaty after a restart (guild records are the durable copy).
+    pub fn restore_guild_relation(&mut self, a: u64, b: u64, status: DiplomacyStatus) {
+        self.guild_relations.insert(guild_pair(a, b), status);
+    }
+
+    /// Drop every relation and open proposal of a disbanded guild.
+    pub fn forget_guild(&mut self, guild_id: u64) {
+        self.guild_relations.retain(|(a, b), _| *a != guild_id && *b != guild_id);
+        self.guild_treaty_proposals.retain(|(from, to), _| *from != guild_id && *to != guild_id);
+        self.last_guild_treaty_proposal.remove(&guild_id);
+    }
+
     // Full methods for declare rivalry, accept treaty, reputation change, etc.
     // All mercy-gated and logged to Persistence + audit trail
 }
@@ -106,14 +205,39 @@ impl Plugin for FactionDiplomacyPlugin {
     fn build(&self, app: &mut App) {
         app
             .init_resource::<FactionDiplomacyManager>()
-            .init_resource::<FactionDiplomacyConfig>()
-            .add_systems(Update, (
-                // diplomacy_tick_system,
-                // integrate_with_dynamic_events_and_council,
-            ));
+            .init_resource::<FactionDiplomacyConfig>();
+        // Update: diplomacy_tick_system, integrate_with_dynamic_events_and_council
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guild_treaties_need_acceptance_and_respect_cooldown() {
+        let mut diplomacy = FactionDiplomacyManager::new();
+        assert!(diplomacy.propose_guild_treaty(1, 1, GuildTreatyTerms::Alliance, 0).is_err());
+        diplomacy.propose_guild_treaty(1, 2, GuildTreatyTerms::Alliance, 0).unwrap();
+        assert!(diplomacy.propose_guild_treaty(1, 3, GuildTreatyTerms::Alliance, 10).is_err(), "cooldown");
+        assert_eq!(diplomacy.guild_status(1, 2), DiplomacyStatus::Neutral);
+
+        assert!(diplomacy.accept_guild_treaty(1, 2, 20).is_err(), "only the recipient can accept");
+        assert_eq!(diplomacy.accept_guild_treaty(2, 1, 20), Ok(GuildTreatyTerms::Alliance));
+        assert_eq!(diplomacy.guild_status(2, 1), DiplomacyStatus::Allied);
+        assert!(diplomacy.propose_guild_treaty(2, 1, GuildTreatyTerms::Alliance, 20).is_err(), "already allied");
+
+        diplomacy.propose_guild_treaty(3, 1, GuildTreatyTerms::Alliance, 0).unwrap();
+        assert!(diplomacy.accept_guild_treaty(1, 3, GUILD_TREATY_PROPOSAL_TTL_SECS + 1).is_err(), "expired");
+
+        assert!(diplomacy.revoke_guild_treaty(1, 2));
+        assert!(!diplomacy.revoke_guild_treaty(1, 2));
+        diplomacy.restore_guild_relation(4, 1, DiplomacyStatus::Allied);
+        diplomacy.forget_guild(1);
+        assert_eq!(diplomacy.guild_status(1, 4), DiplomacyStatus::Neutral);
     }
 }
 
 // Thunder locked in.
-// faction_diplomacy.rs v18.97.1 — Updated with Council Mercy Trial diplomacy impact wiring.
+// faction_diplomacy.rs v18.98 — Guild treaties on top of the v18.97.1 Council Mercy Trial wiring.
 // All prior logic preserved. Ready for deeper RBE and persistence integration. Yoi ⚡
\ No newline at end of file
@@ -0,0 +1,18 @@
+/*!
+ * server/src/guild/mod.rs
+ *
+ * Guilds: configurable ranks and permissions, invitations, a shared bank
+ * fed from PlayerSaveData inventories, guild chat, an audit log and
+ * guild-to-guild treaties through FactionDiplomacyManager.
+ * AG-SML v1.0 | TOLC 8 + RBE
+ * Thunder locked in. Yoi ⚡
+ */
+
+pub mod registry;
+pub mod store;
+
+pub use registry::{
+    handle_guild_message, Guild, GuildAuditAction, GuildAuditEntry, GuildError, GuildMember, GuildRank,
+    GuildRegistry, GuildTreaty,
+};
+pub use store::{FileGuildStore, GuildStore, GuildStoreError, InMemoryGuildStore};
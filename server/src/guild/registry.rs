@@ -0,0 +1,1110 @@
+/*!
+ * server/src/guild/registry.rs
+ *
+ * Authoritative guild state: membership, ranks + permission bits, the shared
+ * bank, the audit log and guild treaties. Every change is written to the
+ * GuildStore before it becomes visible, so a failed write leaves the
+ * registry untouched.
+ *
+ * Bank transfers move items between a guild and the player's PlayerSaveData
+ * inventory. The side that loses the items is written first: a crash between
+ * the two writes can lose a deposit, never duplicate it. Only items the shared
+ * registry resolves move, and stacks respect their registry stack limit.
+ *
+ * AG-SML v1.0 | TOLC 8 + RBE | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use shared::protocol::{
+    ClientMessage, HotbarSlot, ServerMessage, WireGuild, WireGuildAuditEntry, WireGuildMember, WireGuildRank,
+    WireGuildTreaty, WireTreatyTerms, GUILD_PERMS_ALL, GUILD_PERM_BANK_DEPOSIT, GUILD_PERM_BANK_WITHDRAW,
+    GUILD_PERM_CHAT, GUILD_PERM_EDIT_RANKS, GUILD_PERM_INVITE, GUILD_PERM_KICK, GUILD_PERM_SET_RANK,
+    GUILD_PERM_TREATY, GUILD_PERM_VIEW_AUDIT,
+};
+use shared::items;
+use tracing::{error, warn};
+
+use crate::faction_diplomacy::{FactionDiplomacyManager, GuildTreatyTerms};
+use crate::guild::store::{FileGuildStore, GuildStore, GuildStoreError, InMemoryGuildStore};
+use crate::persistence_polish::{PersistenceManager, PlayerSaveData};
+
+pub const GUILD_BANK_SLOTS: usize = 64;
+pub const GUILD_MAX_MEMBERS: usize = 200;
+pub const GUILD_MAX_RANKS: usize = 10;
+/// Oldest audit entries are dropped past this.
+pub const GUILD_AUDIT_LOG_LEN: usize = 500;
+pub const GUILD_INVITE_TTL_SECS: u64 = 600;
+pub const GUILD_CHAT_MAX_LEN: usize = 500;
+
+const LEADER_RANK: u8 = 0;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GuildError {
+    NotInGuild(u64),
+    AlreadyInGuild(u64),
+    UnknownGuild(u64),
+    NoInvite(u64),
+    InvalidName(String),
+    NameTaken(String),
+    /// Actor's rank lacks the named permission.
+    MissingPermission(&'static str),
+    /// Target's rank is not junior to the actor's.
+    Outranked(u64),
+    UnknownRank(u8),
+    InvalidRank(String),
+    GuildFull,
+    InvalidMessage(String),
+    InvalidSlot(usize),
+    Insufficient { item_id: u32, needed: u32, available: u32 },
+    /// Item id the shared item registry does not resolve.
+    UnknownItem(u32),
+    BankFull,
+    InventoryFull,
+    Treaty(String),
+    Persistence(String),
+    Store(String),
+}
+
+impl fmt::Display for GuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GuildError::NotInGuild(id) => write!(f, "player {} is not in a guild", id),
+            GuildError::AlreadyInGuild(id) => write!(f, "player {} is already in a guild", id),
+            GuildError::UnknownGuild(id) => write!(f, "guild {} does not exist", id),
+            GuildError::NoInvite(id) => write!(f, "no pending invite from guild {}", id),
+            GuildError::InvalidName(reason) => write!(f, "invalid guild name or tag: {}", reason),
+            GuildError::NameTaken(name) => write!(f, "guild name {} is taken", name),
+            GuildError::MissingPermission(perm) => write!(f, "your rank lacks the {} permission", perm),
+            GuildError::Outranked(id) => write!(f, "player {} is not junior to you", id),
+            GuildError::UnknownRank(id) => write!(f, "guild rank {} does not exist", id),
+            GuildError::InvalidRank(reason) => write!(f, "invalid rank: {}", reason),
+            GuildError::GuildFull => write!(f, "guild is full ({} members)", GUILD_MAX_MEMBERS),
+            GuildError::InvalidMessage(reason) => write!(f, "invalid guild chat message: {}", reason),
+            GuildError::InvalidSlot(slot) => write!(f, "slot {} is out of range", slot),
+            GuildError::Insufficient { item_id, needed, available } => {
+                write!(f, "needs {} of item {} but slot holds {}", needed, item_id, available)
+            }
+            GuildError::UnknownItem(item_id) => write!(f, "unknown item id {}", item_id),
+            GuildError::BankFull => write!(f, "guild bank is full"),
+            GuildError::InventoryFull => write!(f, "inventory is full"),
+            GuildError::Treaty(e) => write!(f, "{}", e),
+            GuildError::Persistence(e) => write!(f, "player save failed: {}", e),
+            GuildError::Store(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for GuildError {}
+
+impl From<GuildStoreError> for GuildError {
+    fn from(e: GuildStoreError) -> Self {
+        GuildError::Store(e.to_string())
+    }
+}
+
+// ============================================================================
+// Guild records
+// ============================================================================
+
+/// Rank 0 is the leader; a lower id is more senior.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GuildRank {
+    pub rank_id: u8,
+    pub name: String,
+    /// GUILD_PERM_* bits
+    pub permissions: u32,
+}
+
+impl GuildRank {
+    fn new(rank_id: u8, name: &str, permissions: u32) -> Self {
+        Self { rank_id, name: name.to_string(), permissions }
+    }
+
+    fn to_wire(&self) -> WireGuildRank {
+        WireGuildRank { rank_id: self.rank_id, name: self.name.clone(), permissions: self.permissions }
+    }
+}
+
+fn default_ranks() -> Vec<GuildRank> {
+    vec![
+        GuildRank::new(LEADER_RANK, "Leader", GUILD_PERMS_ALL),
+        GuildRank::new(
+            1,
+            "Officer",
+            GUILD_PERM_INVITE
+                | GUILD_PERM_KICK
+                | GUILD_PERM_SET_RANK
+                | GUILD_PERM_BANK_DEPOSIT
+                | GUILD_PERM_BANK_WITHDRAW
+                | GUILD_PERM_CHAT
+                | GUILD_PERM_VIEW_AUDIT,
+        ),
+        GuildRank::new(2, "Member", GUILD_PERM_BANK_DEPOSIT | GUILD_PERM_CHAT),
+        GuildRank::new(3, "Recruit", GUILD_PERM_CHAT),
+    ]
+}
+
+/// Rank new members join at.
+const RECRUIT_RANK: u8 = 3;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GuildMember {
+    pub player_id: u64,
+    pub rank_id: u8,
+    pub joined_at: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GuildTreaty {
+    pub terms: GuildTreatyTerms,
+    pub signed_at: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GuildAuditAction {
+    Created { name: String, tag: String },
+    Invited { player_id: u64 },
+    Joined,
+    Left,
+    Kicked { player_id: u64 },
+    RankChanged { player_id: u64, rank_id: u8 },
+    LeadershipTransferred { to: u64 },
+    RankEdited { rank_id: u8, name: String, permissions: u32 },
+    Deposited { item_id: u32, count: u32 },
+    Withdrew { item_id: u32, count: u32 },
+    TreatyProposed { guild_id: u64, terms: GuildTreatyTerms },
+    TreatySigned { guild_id: u64, terms: GuildTreatyTerms },
+    TreatyRevoked { guild_id: u64 },
+}
+
+impl GuildAuditAction {
+    pub fn summary(&self) -> String {
+        match self {
+            GuildAuditAction::Created { name, tag } => format!("founded {} [{}]", name, tag),
+            GuildAuditAction::Invited { player_id } => format!("invited player {}", player_id),
+            GuildAuditAction::Joined => "joined".to_string(),
+            GuildAuditAction::Left => "left".to_string(),
+            GuildAuditAction::Kicked { player_id } => format!("kicked player {}", player_id),
+            GuildAuditAction::RankChanged { player_id, rank_id } => {
+                format!("set player {} to rank {}", player_id, rank_id)
+            }
+            GuildAuditAction::LeadershipTransferred { to } => format!("passed leadership to player {}", to),
+            GuildAuditAction::RankEdited { rank_id, name, permissions } => {
+                format!("edited rank {} ({}) permissions {:#x}", rank_id, name, permissions)
+            }
+            GuildAuditAction::Deposited { item_id, count } => format!("deposited {} x item {}", count, item_id),
+            GuildAuditAction::Withdrew { item_id, count } => format!("withdrew {} x item {}", count, item_id),
+            GuildAuditAction::TreatyProposed { guild_id, terms } => {
+                format!("proposed {:?} to guild {}", terms, guild_id)
+            }
+            GuildAuditAction::TreatySigned { guild_id, terms } => format!("signed {:?} with guild {}", terms, guild_id),
+            GuildAuditAction::TreatyRevoked { guild_id } => format!("revoked treaty with guild {}", guild_id),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GuildAuditEntry {
+    pub seq: u64,
+    pub at: u64,
+    pub actor_id: u64,
+    pub action: GuildAuditAction,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Guild {
+    pub guild_id: u64,
+    pub name: String,
+    pub tag: String,
+    pub created_at: u64,
+    /// Sorted by rank_id
+    pub ranks: Vec<GuildRank>,
+    pub members: BTreeMap<u64, GuildMember>,
+    /// GUILD_BANK_SLOTS slots; empty ones have count 0
+    pub bank: Vec<HotbarSlot>,
+    /// Keyed by the other guild
+    pub treaties: BTreeMap<u64, GuildTreaty>,
+    /// Oldest first, at most GUILD_AUDIT_LOG_LEN
+    pub audit: VecDeque<GuildAuditEntry>,
+    pub next_audit_seq: u64,
+}
+
+impl Guild {
+    pub fn rank_of(&self, player_id: u64) -> Option<u8> {
+        self.members.get(&player_id).map(|m| m.rank_id)
+    }
+
+    pub fn rank(&self, rank_id: u8) -> Option<&GuildRank> {
+        self.ranks.iter().find(|r| r.rank_id == rank_id)
+    }
+
+    /// Whether `player_id` is a member whose rank grants `permission`.
+    pub fn can(&self, player_id: u64, permission: u32) -> bool {
+        self.rank_of(player_id)
+            .and_then(|rank_id| self.rank(rank_id))
+            .is_some_and(|rank| rank.permissions & permission == permission)
+    }
+
+    pub fn leader(&self) -> Option<u64> {
+        self.members.values().find(|m| m.rank_id == LEADER_RANK).map(|m| m.player_id)
+    }
+
+    fn require(&self, player_id: u64, permission: u32, name: &'static str) -> Result<u8, GuildError> {
+        if !self.can(player_id, permission) {
+            return Err(GuildError::MissingPermission(name));
+        }
+        self.rank_of(player_id).ok_or(GuildError::NotInGuild(player_id))
+    }
+
+    /// Actor's rank, when `target` is a member strictly junior to `actor`.
+    fn require_senior(&self, actor: u64, target: u64) -> Result<u8, GuildError> {
+        let actor_rank = self.rank_of(actor).ok_or(GuildError::NotInGuild(actor))?;
+        let target_rank = self.rank_of(target).ok_or(GuildError::NotInGuild(target))?;
+        if target_rank <= actor_rank {
+            return Err(GuildError::Outranked(target));
+        }
+        Ok(actor_rank)
+    }
+
+    fn record(&mut self, actor_id: u64, action: GuildAuditAction, now: u64) {
+        self.audit.push_back(GuildAuditEntry { seq: self.next_audit_seq, at: now, actor_id, action });
+        self.next_audit_seq += 1;
+        while self.audit.len() > GUILD_AUDIT_LOG_LEN {
+            self.audit.pop_front();
+        }
+    }
+
+    /// Next-most-senior member (lowest rank, then longest-standing), excluding `player_id`.
+    fn successor(&self, player_id: u64) -> Option<u64> {
+        self.members
+            .values()
+            .filter(|m| m.player_id != player_id)
+            .min_by_key(|m| (m.rank_id, m.joined_at, m.player_id))
+            .map(|m| m.player_id)
+    }
+
+    /// Most senior rank below the leader, for a leader who steps down.
+    fn first_rank_below_leader(&self) -> u8 {
+        self.ranks.iter().map(|r| r.rank_id).find(|id| *id > LEADER_RANK).unwrap_or(RECRUIT_RANK)
+    }
+
+    pub fn to_wire(&self) -> WireGuild {
+        WireGuild {
+            guild_id: self.guild_id,
+            name: self.name.clone(),
+            tag: self.tag.clone(),
+            ranks: self.ranks.iter().map(GuildRank::to_wire).collect(),
+            members: self
+                .members
+                .values()
+                .map(|m| WireGuildMember { player_id: m.player_id, rank_id: m.rank_id, joined_at: m.joined_at })
+                .collect(),
+            bank: self.bank.clone(),
+            treaties: self
+                .treaties
+                .iter()
+                .map(|(guild_id, t)| WireGuildTreaty { guild_id: *guild_id, terms: t.terms.into(), signed_at: t.signed_at })
+                .collect(),
+        }
+    }
+}
+
+impl From<GuildTreatyTerms> for WireTreatyTerms {
+    fn from(terms: GuildTreatyTerms) -> Self {
+        match terms {
+            GuildTreatyTerms::Alliance => WireTreatyTerms::Alliance,
+            GuildTreatyTerms::Peace => WireTreatyTerms::Peace,
+        }
+    }
+}
+
+impl From<WireTreatyTerms> for GuildTreatyTerms {
+    fn from(terms: WireTreatyTerms) -> Self {
+        match terms {
+            WireTreatyTerms::Alliance => GuildTreatyTerms::Alliance,
+            WireTreatyTerms::Peace => GuildTreatyTerms::Peace,
+        }
+    }
+}
+
+fn validate_name(name: &str, tag: &str) -> Result<(), GuildError> {
+    let len = name.chars().count();
+    if !(3..=32).contains(&len) || name.trim() != name {
+        return Err(GuildError::InvalidName("name must be 3-32 characters without surrounding spaces".into()));
+    }
+    if name.chars().any(char::is_control) {
+        return Err(GuildError::InvalidName("name contains control characters".into()));
+    }
+    if !(2..=5).contains(&tag.len()) || !tag.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(GuildError::InvalidName("tag must be 2-5 ASCII letters or digits".into()));
+    }
+    Ok(())
+}
+
+/// Stack limit of `item_id` from the shared item registry.
+fn stack_limit(item_id: u32) -> Result<u32, GuildError> {
+    items::current().by_item_id(item_id).map(|d| d.stack_limit).ok_or(GuildError::UnknownItem(item_id))
+}
+
+/// Stack `count` of `item` into the first slot already holding it with room under
+/// `limit`, else the first empty slot. Valence of a merged stack is the count-weighted mean.
+fn stack_into(slots: &mut [HotbarSlot], item_id: u32, count: u32, valence: f32, limit: u32) -> bool {
+    if count > limit {
+        return false;
+    }
+    let index = slots
+        .iter()
+        .position(|s| s.count > 0 && s.item_id == item_id && s.count + count <= limit)
+        .or_else(|| slots.iter().position(|s| s.count == 0));
+    let Some(slot) = index.map(|i| &mut slots[i]) else {
+        return false;
+    };
+    if slot.count == 0 {
+        *slot = HotbarSlot::new(item_id, count, valence);
+    } else {
+        let total = slot.count + count;
+        slot.valence = (slot.valence * slot.count as f32 + valence * count as f32) / total as f32;
+        slot.count = total;
+    }
+    true
+}
+
+/// Take `count` from `slot`, returning (item_id, valence).
+fn take_from(slot: &mut HotbarSlot, count: u32) -> Result<(u32, f32), GuildError> {
+    if count == 0 || slot.count < count {
+        return Err(GuildError::Insufficient { item_id: slot.item_id, needed: count, available: slot.count });
+    }
+    let taken = (slot.item_id, slot.valence);
+    slot.count -= count;
+    if slot.count == 0 {
+        *slot = HotbarSlot::empty();
+    }
+    Ok(taken)
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+/// Live guilds over a GuildStore. `Default` is in-memory; use `open_durable` on real servers.
+#[derive(Resource)]
+pub struct GuildRegistry {
+    store: Arc<dyn GuildStore>,
+    guilds: HashMap<u64, Guild>,
+    by_player: HashMap<u64, u64>,
+    /// (guild_id, invitee) → expiry; not persisted
+    invites: HashMap<(u64, u64), u64>,
+    next_guild_id: u64,
+}
+
+impl Default for GuildRegistry {
+    fn default() -> Self {
+        Self {
+            store: Arc::new(InMemoryGuildStore::new()),
+            guilds: HashMap::new(),
+            by_player: HashMap::new(),
+            invites: HashMap::new(),
+            next_guild_id: 1,
+        }
+    }
+}
+
+impl GuildRegistry {
+    /// Load every guild from `store`.
+    pub fn with_store(store: Arc<dyn GuildStore>) -> Result<Self, GuildStoreError> {
+        let mut registry = Self { store: store.clone(), ..Self::default() };
+        for guild in store.load_guilds()? {
+            registry.next_guild_id = registry.next_guild_id.max(guild.guild_id + 1);
+            for player_id in guild.members.keys() {
+                registry.by_player.insert(*player_id, guild.guild_id);
+            }
+            registry.guilds.insert(guild.guild_id, guild);
+        }
+        Ok(registry)
+    }
+
+    /// Open the file-backed store at `root`.
+    pub fn open_durable(root: impl AsRef<Path>) -> Result<Self, String> {
+        let store = FileGuildStore::open(root).map_err(|e| e.to_string())?;
+        Self::with_store(Arc::new(store)).map_err(|e| e.to_string())
+    }
+
+    pub fn get(&self, guild_id: u64) -> Option<&Guild> {
+        self.guilds.get(&guild_id)
+    }
+
+    pub fn guild_of(&self, player_id: u64) -> Option<&Guild> {
+        self.by_player.get(&player_id).and_then(|id| self.guilds.get(id))
+    }
+
+    pub fn len(&self) -> usize {
+        self.guilds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.guilds.is_empty()
+    }
+
+    /// Re-seed FactionDiplomacyManager with the treaties on record (call at startup).
+    pub fn restore_treaties(&self, diplomacy: &mut FactionDiplomacyManager) -> usize {
+        let mut restored = 0;
+        for guild in self.guilds.values() {
+            for (other, treaty) in &guild.treaties {
+                if guild.guild_id < *other {
+                    diplomacy.restore_guild_relation(guild.guild_id, *other, treaty.terms.status());
+                    restored += 1;
+                }
+            }
+        }
+        restored
+    }
+
+    /// Clone of the member's guild, for a change that is committed afterwards.
+    fn guild_for_update(&self, player_id: u64) -> Result<Guild, GuildError> {
+        self.guild_of(player_id).cloned().ok_or(GuildError::NotInGuild(player_id))
+    }
+
+    /// Persist, then publish.
+    fn commit(&mut self, guild: Guild) -> Result<(), GuildError> {
+        self.store.put_guild(&guild)?;
+        self.guilds.insert(guild.guild_id, guild);
+        Ok(())
+    }
+
+    pub fn create(&mut self, founder: u64, name: &str, tag: &str, now: u64) -> Result<u64, GuildError> {
+        if self.by_player.contains_key(&founder) {
+            return Err(GuildError::AlreadyInGuild(founder));
+        }
+        validate_name(name, tag)?;
+        if self.guilds.values().any(|g| g.name.eq_ignore_ascii_case(name)) {
+            return Err(GuildError::NameTaken(name.to_string()));
+        }
+
+        let guild_id = self.next_guild_id;
+        let mut guild = Guild {
+            guild_id,
+            name: name.to_string(),
+            tag: tag.to_ascii_uppercase(),
+            created_at: now,
+            ranks: default_ranks(),
+            members: BTreeMap::from([(founder, GuildMember { player_id: founder, rank_id: LEADER_RANK, joined_at: now })]),
+            bank: vec![HotbarSlot::empty(); GUILD_BANK_SLOTS],
+            treaties: BTreeMap::new(),
+            audit: VecDeque::new(),
+            next_audit_seq: 0,
+        };
+        guild.record(founder, GuildAuditAction::Created { name: guild.name.clone(), tag: guild.tag.clone() }, now);
+        self.commit(guild)?;
+        self.next_guild_id += 1;
+        self.by_player.insert(founder, guild_id);
+        Ok(guild_id)
+    }
+
+    pub fn invite(&mut self, actor: u64, invitee: u64, now: u64) -> Result<u64, GuildError> {
+        let mut guild = self.guild_for_update(actor)?;
+        guild.require(actor, GUILD_PERM_INVITE, "invite")?;
+        if self.by_player.contains_key(&invitee) {
+            return Err(GuildError::AlreadyInGuild(invitee));
+        }
+        if guild.members.len() >= GUILD_MAX_MEMBERS {
+            return Err(GuildError::GuildFull);
+        }
+        let guild_id = guild.guild_id;
+        guild.record(actor, GuildAuditAction::Invited { player_id: invitee }, now);
+        self.commit(guild)?;
+        self.invites.insert((guild_id, invitee), now + GUILD_INVITE_TTL_SECS);
+        Ok(guild_id)
+    }
+
+    /// Ok(true) when the player joined; declining consumes the invite.
+    pub fn respond_invite(&mut self, player_id: u64, guild_id: u64, accept: bool, now: u64) -> Result<bool, GuildError> {
+        self.invites
+            .remove(&(guild_id, player_id))
+            .filter(|expires_at| *expires_at >= now)
+            .ok_or(GuildError::NoInvite(guild_id))?;
+        if !accept {
+            return Ok(false);
+        }
+        if self.by_player.contains_key(&player_id) {
+            return Err(GuildError::AlreadyInGuild(player_id));
+        }
+        let mut guild = self.guilds.get(&guild_id).cloned().ok_or(GuildError::UnknownGuild(guild_id))?;
+        if guild.members.len() >= GUILD_MAX_MEMBERS {
+            return Err(GuildError::GuildFull);
+        }
+        let rank_id = guild.ranks.last().map_or(RECRUIT_RANK, |r| r.rank_id);
+        guild.members.insert(player_id, GuildMember { player_id, rank_id, joined_at: now });
+        guild.record(player_id, GuildAuditAction::Joined, now);
+        self.commit(guild)?;
+        self.by_player.insert(player_id, guild_id);
+        // Other guilds' invites are moot now.
+        self.invites.retain(|(_, invitee), _| *invitee != player_id);
+        Ok(true)
+    }
+
+    /// Leaving leader hands over to the most senior member; the last member disbands the guild.
+    /// Returns (guild_id, disbanded).
+    pub fn leave(
+        &mut self,
+        player_id: u64,
+        diplomacy: &mut FactionDiplomacyManager,
+        now: u64,
+    ) -> Result<(u64, bool), GuildError> {
+        let mut guild = self.guild_for_update(player_id)?;
+        let guild_id = guild.guild_id;
+        let was_leader = guild.rank_of(player_id) == Some(LEADER_RANK);
+        guild.members.remove(&player_id);
+
+        let Some(successor) = guild.successor(player_id) else {
+            self.disband(guild_id, diplomacy)?;
+            self.by_player.remove(&player_id);
+            return Ok((guild_id, true));
+        };
+        guild.record(player_id, GuildAuditAction::Left, now);
+        if was_leader {
+            if let Some(member) = guild.members.get_mut(&successor) {
+                member.rank_id = LEADER_RANK;
+            }
+            guild.record(player_id, GuildAuditAction::LeadershipTransferred { to: successor }, now);
+        }
+        self.commit(guild)?;
+        self.by_player.remove(&player_id);
+        Ok((guild_id, false))
+    }
+
+    fn disband(&mut self, guild_id: u64, diplomacy: &mut FactionDiplomacyManager) -> Result<(), GuildError> {
+        let Some(guild) = self.guilds.get(&guild_id).cloned() else {
+            return Ok(());
+        };
+        // Partners first: a crash in between leaves a dangling treaty, not a resurrected guild.
+        for other_id in guild.treaties.keys() {
+            if let Some(mut other) = self.guilds.get(other_id).cloned() {
+                other.treaties.remove(&guild_id);
+                self.commit(other)?;
+            }
+        }
+        self.store.delete_guild(guild_id)?;
+        self.guilds.remove(&guild_id);
+        self.invites.retain(|(g, _), _| *g != guild_id);
+        diplomacy.forget_guild(guild_id);
+        Ok(())
+    }
+
+    pub fn kick(&mut self, actor: u64, target: u64, now: u64) -> Result<u64, GuildError> {
+        let mut guild = self.guild_for_update(actor)?;
+        guild.require(actor, GUILD_PERM_KICK, "kick")?;
+        guild.require_senior(actor, target)?;
+        guild.members.remove(&target);
+        guild.record(actor, GuildAuditAction::Kicked { player_id: target }, now);
+        let guild_id = guild.guild_id;
+        self.commit(guild)?;
+        self.by_player.remove(&target);
+        Ok(guild_id)
+    }
+
+    /// Move a junior member to a rank junior to the actor's. The leader assigning
+    /// rank 0 hands over leadership and steps down to the next rank.
+    pub fn set_rank(&mut self, actor: u64, target: u64, rank_id: u8, now: u64) -> Result<u64, GuildError> {
+        let mut guild = self.guild_for_update(actor)?;
+        let actor_rank = guild.require(actor, GUILD_PERM_SET_RANK, "set rank")?;
+        guild.require_senior(actor, target)?;
+        if guild.rank(rank_id).is_none() {
+            return Err(GuildError::UnknownRank(rank_id));
+        }
+
+        if rank_id == LEADER_RANK && actor_rank == LEADER_RANK {
+            let step_down = guild.first_rank_below_leader();
+            for (player_id, rank) in [(target, LEADER_RANK), (actor, step_down)] {
+                if let Some(member) = guild.members.get_mut(&player_id) {
+                    member.rank_id = rank;
+                }
+            }
+            guild.record(actor, GuildAuditAction::LeadershipTransferred { to: target }, now);
+        } else {
+            if rank_id <= actor_rank {
+                return Err(GuildError::InvalidRank("can only assign ranks junior to your own".into()));
+            }
+            if let Some(member) = guild.members.get_mut(&target) {
+                member.rank_id = rank_id;
+            }
+            guild.record(actor, GuildAuditAction::RankChanged { player_id: target, rank_id }, now);
+        }
+        let guild_id = guild.guild_id;
+        self.commit(guild)?;
+        Ok(guild_id)
+    }
+
+    /// Create or edit a rank junior to the actor's, granting at most the actor's own permissions.
+    pub fn edit_rank(&mut self, actor: u64, rank: &WireGuildRank, now: u64) -> Result<u64, GuildError> {
+        let mut guild = self.guild_for_update(actor)?;
+        let actor_rank = guild.require(actor, GUILD_PERM_EDIT_RANKS, "edit ranks")?;
+        if rank.rank_id <= actor_rank {
+            return Err(GuildError::InvalidRank("can only edit ranks junior to your own".into()));
+        }
+        let name = rank.name.trim();
+        if name.is_empty() || name.chars().count() > 24 {
+            return Err(GuildError::InvalidRank("name must be 1-24 characters".into()));
+        }
+        let actor_perms = guild.rank(actor_rank).map_or(0, |r| r.permissions);
+        if rank.permissions & !actor_perms != 0 {
+            return Err(GuildError::InvalidRank("cannot grant permissions you do not hold".into()));
+        }
+
+        let rank_count = guild.ranks.len();
+        match guild.ranks.iter_mut().find(|r| r.rank_id == rank.rank_id) {
+            Some(existing) => *existing = GuildRank::new(rank.rank_id, name, rank.permissions),
+            None if rank_count >= GUILD_MAX_RANKS => {
+                return Err(GuildError::InvalidRank(format!("at most {} ranks", GUILD_MAX_RANKS)));
+            }
+            None => {
+                guild.ranks.push(GuildRank::new(rank.rank_id, name, rank.permissions));
+                guild.ranks.sort_by_key(|r| r.rank_id);
+            }
+        }
+        guild.record(
+            actor,
+            GuildAuditAction::RankEdited { rank_id: rank.rank_id, name: name.to_string(), permissions: rank.permissions },
+            now,
+        );
+        let guild_id = guild.guild_id;
+        self.commit(guild)?;
+        Ok(guild_id)
+    }
+
+    /// Move `count` from inventory slot `inventory_slot` into the bank. Returns the updated save.
+    pub fn deposit(
+        &mut self,
+        player_id: u64,
+        inventory_slot: usize,
+        count: u32,
+        persistence: &mut PersistenceManager,
+        now: u64,
+    ) -> Result<PlayerSaveData, GuildError> {
+        let mut guild = self.guild_for_update(player_id)?;
+        guild.require(player_id, GUILD_PERM_BANK_DEPOSIT, "bank deposit")?;
+        let original = load_save(persistence, player_id)?;
+        let mut player = original.clone();
+        let slot = player.inventory.get_mut(inventory_slot).ok_or(GuildError::InvalidSlot(inventory_slot))?;
+        let (item_id, valence) = take_from(slot, count)?;
+        if !stack_into(&mut guild.bank, item_id, count, valence, stack_limit(item_id)?) {
+            return Err(GuildError::BankFull);
+        }
+        guild.record(player_id, GuildAuditAction::Deposited { item_id, count }, now);
+
+        persistence.save_player(&player).map_err(GuildError::Persistence)?;
+        if let Err(e) = self.commit(guild) {
+            if let Err(restore) = persistence.save_player(&original) {
+                error!("[GuildRegistry] Deposit by player {} lost {} x item {}: {}", player_id, count, item_id, restore);
+            }
+            return Err(e);
+        }
+        Ok(player)
+    }
+
+    /// Move `count` from bank slot `bank_slot` into the player's inventory. Returns the updated save.
+    pub fn withdraw(
+        &mut self,
+        player_id: u64,
+        bank_slot: usize,
+        count: u32,
+        persistence: &mut PersistenceManager,
+        now: u64,
+    ) -> Result<PlayerSaveData, GuildError> {
+        let original = self.guild_for_update(player_id)?;
+        original.require(player_id, GUILD_PERM_BANK_WITHDRAW, "bank withdraw")?;
+        let mut guild = original.clone();
+        let slot = guild.bank.get_mut(bank_slot).ok_or(GuildError::InvalidSlot(bank_slot))?;
+        let (item_id, valence) = take_from(slot, count)?;
+        let mut player = load_save(persistence, player_id)?;
+        if !stack_into(&mut player.inventory, item_id, count, valence, stack_limit(item_id)?) {
+            return Err(GuildError::InventoryFull);
+        }
+        guild.record(player_id, GuildAuditAction::Withdrew { item_id, count }, now);
+
+        self.commit(guild)?;
+        if let Err(e) = persistence.save_player(&player) {
+            if let Err(restore) = self.commit(original) {
+                error!("[GuildRegistry] Withdrawal by player {} lost {} x item {}: {}", player_id, count, item_id, restore);
+            }
+            return Err(GuildError::Persistence(e));
+        }
+        Ok(player)
+    }
+
+    /// Validated, trimmed chat text and the sender's guild.
+    pub fn chat(&self, player_id: u64, text: &str) -> Result<(u64, String), GuildError> {
+        let guild = self.guild_of(player_id).ok_or(GuildError::NotInGuild(player_id))?;
+        guild.require(player_id, GUILD_PERM_CHAT, "chat")?;
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(GuildError::InvalidMessage("empty".into()));
+        }
+        if text.chars().count() > GUILD_CHAT_MAX_LEN {
+            return Err(GuildError::InvalidMessage(format!("longer than {} characters", GUILD_CHAT_MAX_LEN)));
+        }
+        Ok((guild.guild_id, text.to_string()))
+    }
+
+    /// Newest `limit` audit entries, newest first.
+    pub fn audit(&self, player_id: u64, limit: usize) -> Result<(u64, Vec<GuildAuditEntry>), GuildError> {
+        let guild = self.guild_of(player_id).ok_or(GuildError::NotInGuild(player_id))?;
+        guild.require(player_id, GUILD_PERM_VIEW_AUDIT, "view audit log")?;
+        Ok((guild.guild_id, guild.audit.iter().rev().take(limit).cloned().collect()))
+    }
+
+    pub fn propose_treaty(
+        &mut self,
+        actor: u64,
+        to_guild: u64,
+        terms: GuildTreatyTerms,
+        diplomacy: &mut FactionDiplomacyManager,
+        now: u64,
+    ) -> Result<u64, GuildError> {
+        let mut guild = self.guild_for_update(actor)?;
+        guild.require(actor, GUILD_PERM_TREATY, "treaty")?;
+        if !self.guilds.contains_key(&to_guild) {
+            return Err(GuildError::UnknownGuild(to_guild));
+        }
+        diplomacy.propose_guild_treaty(guild.guild_id, to_guild, terms, now).map_err(GuildError::Treaty)?;
+        guild.record(actor, GuildAuditAction::TreatyProposed { guild_id: to_guild, terms }, now);
+        let guild_id = guild.guild_id;
+        self.commit(guild)?;
+        Ok(guild_id)
+    }
+
+    /// Answer the proposal `from_guild` sent the actor's guild. Ok(Some(terms)) when signed.
+    pub fn respond_treaty(
+        &mut self,
+        actor: u64,
+        from_guild: u64,
+        accept: bool,
+        diplomacy: &mut FactionDiplomacyManager,
+        now: u64,
+    ) -> Result<Option<GuildTreatyTerms>, GuildError> {
+        let mut guild = self.guild_for_update(actor)?;
+        guild.require(actor, GUILD_PERM_TREATY, "treaty")?;
+        let mut other = self.guilds.get(&from_guild).cloned().ok_or(GuildError::UnknownGuild(from_guild))?;
+        if !accept {
+            return if diplomacy.decline_guild_treaty(guild.guild_id, from_guild) {
+                Ok(None)
+            } else {
+                Err(GuildError::Treaty(format!("No treaty proposal from guild {}", from_guild)))
+            };
+        }
+
+        let terms = diplomacy.accept_guild_treaty(guild.guild_id, from_guild, now).map_err(GuildError::Treaty)?;
+        let own_id = guild.guild_id;
+        for (side, partner) in [(&mut guild, from_guild), (&mut other, own_id)] {
+            side.treaties.insert(partner, GuildTreaty { terms, signed_at: now });
+            side.record(actor, GuildAuditAction::TreatySigned { guild_id: partner, terms }, now);
+        }
+        self.commit(guild)?;
+        self.commit(other)?;
+        Ok(Some(terms))
+    }
+
+    pub fn revoke_treaty(
+        &mut self,
+        actor: u64,
+        other_guild: u64,
+        diplomacy: &mut FactionDiplomacyManager,
+        now: u64,
+    ) -> Result<u64, GuildError> {
+        let mut guild = self.guild_for_update(actor)?;
+        guild.require(actor, GUILD_PERM_TREATY, "treaty")?;
+        if guild.treaties.remove(&other_guild).is_none() {
+            return Err(GuildError::Treaty(format!("No treaty with guild {}", other_guild)));
+        }
+        let guild_id = guild.guild_id;
+        guild.record(actor, GuildAuditAction::TreatyRevoked { guild_id: other_guild }, now);
+        let other = self.guilds.get(&other_guild).cloned().map(|mut other| {
+            other.treaties.remove(&guild_id);
+            other.record(actor, GuildAuditAction::TreatyRevoked { guild_id }, now);
+            other
+        });
+        self.commit(guild)?;
+        if let Some(other) = other {
+            self.commit(other)?;
+        }
+        diplomacy.revoke_guild_treaty(guild_id, other_guild);
+        Ok(guild_id)
+    }
+}
+
+fn load_save(persistence: &mut PersistenceManager, player_id: u64) -> Result<PlayerSaveData, GuildError> {
+    persistence
+        .load_player(player_id)
+        .ok_or_else(|| GuildError::Persistence(format!("could not load save for player {}", player_id)))
+}
+
+// ============================================================================
+// Message routing
+// ============================================================================
+
+fn guild_info_to_members(registry: &GuildRegistry, guild_id: u64) -> Vec<(u64, ServerMessage)> {
+    let Some(guild) = registry.get(guild_id) else {
+        return Vec::new();
+    };
+    let wire = guild.to_wire();
+    guild.members.keys().map(|id| (*id, ServerMessage::GuildInfo { guild: wire.clone() })).collect()
+}
+
+fn inventory_update(player: &PlayerSaveData) -> (u64, ServerMessage) {
+    (
+        player.player_id,
+        ServerMessage::InventoryUpdate {
+            player_id: player.player_id,
+            hotbar: player.hotbar.to_vec(),
+            inventory: player.inventory.to_vec(),
+            abundance_score: player.abundance as f32,
+        },
+    )
+}
+
+/// Apply one guild request from `player_id`; replies go to the affected members.
+/// Rejections are answered with ServerMessage::Error to the sender.
+pub fn handle_guild_message(
+    player_id: u64,
+    message: &ClientMessage,
+    registry: &mut GuildRegistry,
+    diplomacy: &mut FactionDiplomacyManager,
+    persistence: &mut PersistenceManager,
+    now: u64,
+) -> Vec<(u64, ServerMessage)> {
+    let result = match message {
+        ClientMessage::GuildCreate { name, tag } => {
+            registry.create(player_id, name, tag, now).map(|id| guild_info_to_members(registry, id))
+        }
+        ClientMessage::GuildInvite { player_id: invitee } => registry.invite(player_id, *invitee, now).map(|id| {
+            let guild_name = registry.get(id).map(|g| g.name.clone()).unwrap_or_default();
+            vec![(*invitee, ServerMessage::GuildInvited { guild_id: id, guild_name, invited_by: player_id })]
+        }),
+        ClientMessage::GuildInviteResponse { guild_id, accept } => registry
+            .respond_invite(player_id, *guild_id, *accept, now)
+            .map(|joined| if joined { guild_info_to_members(registry, *guild_id) } else { Vec::new() }),
+        ClientMessage::GuildLeave => registry.leave(player_id, diplomacy, now).map(|(guild_id, _)| {
+            let mut out = vec![(player_id, ServerMessage::GuildLeft { guild_id, reason: "left the guild".into() })];
+            out.extend(guild_info_to_members(registry, guild_id));
+            out
+        }),
+        ClientMessage::GuildKick { player_id: target } => registry.kick(player_id, *target, now).map(|guild_id| {
+            let mut out = vec![(*target, ServerMessage::GuildLeft { guild_id, reason: "kicked".into() })];
+            out.extend(guild_info_to_members(registry, guild_id));
+            out
+        }),
+        ClientMessage::GuildSetRank { player_id: target, rank_id } => registry
+            .set_rank(player_id, *target, *rank_id, now)
+            .map(|id| guild_info_to_members(registry, id)),
+        ClientMessage::GuildEditRank { rank } => {
+            registry.edit_rank(player_id, rank, now).map(|id| guild_info_to_members(registry, id))
+        }
+        ClientMessage::GuildBankDeposit { inventory_slot, count } => registry
+            .deposit(player_id, *inventory_slot as usize, *count, persistence, now)
+            .map(|player| bank_replies(registry, &player)),
+        ClientMessage::GuildBankWithdraw { bank_slot, count } => registry
+            .withdraw(player_id, *bank_slot as usize, *count, persistence, now)
+            .map(|player| bank_replies(registry, &player)),
+        ClientMessage::GuildChat { text } => registry.chat(player_id, text).map(|(guild_id, text)| {
+            let members: Vec<u64> = registry.get(guild_id).map(|g| g.members.keys().copied().collect()).unwrap_or_default();
+            members
+                .into_iter()
+                .map(|member| {
+                    (member, ServerMessage::GuildChatMessage { guild_id, sender_id: player_id, text: text.clone(), sent_at: now })
+                })
+                .collect()
+        }),
+        ClientMessage::GuildAuditRequest { limit } => registry.audit(player_id, *limit as usize).map(|(guild_id, entries)| {
+            let entries = entries
+                .iter()
+                .map(|e| WireGuildAuditEntry { seq: e.seq, at: e.at, actor_id: e.actor_id, summary: e.action.summary() })
+                .collect();
+            vec![(player_id, ServerMessage::GuildAuditLog { guild_id, entries })]
+        }),
+        ClientMessage::GuildTreatyPropose { guild_id: to_guild, terms } => registry
+            .propose_treaty(player_id, *to_guild, (*terms).into(), diplomacy, now)
+            .map(|from_guild_id| {
+                let from_guild_name = registry.get(from_guild_id).map(|g| g.name.clone()).unwrap_or_default();
+                let Some(target) = registry.get(*to_guild) else { return Vec::new() };
+                target
+                    .members
+                    .keys()
+                    .filter(|id| target.can(**id, GUILD_PERM_TREATY))
+                    .map(|id| {
+                        let proposal = ServerMessage::GuildTreatyProposal {
+                            from_guild_id,
+                            from_guild_name: from_guild_name.clone(),
+                            terms: *terms,
+                        };
+                        (*id, proposal)
+                    })
+                    .collect()
+            }),
+        ClientMessage::GuildTreatyRespond { guild_id: from_guild, accept } => registry
+            .respond_treaty(player_id, *from_guild, *accept, diplomacy, now)
+            .map(|signed| match signed {
+                Some(_) => {
+                    let own_id = registry.guild_of(player_id).map_or(0, |g| g.guild_id);
+                    let mut out = guild_info_to_members(registry, own_id);
+                    out.extend(guild_info_to_members(registry, *from_guild));
+                    out
+                }
+                None => Vec::new(),
+            }),
+        ClientMessage::GuildTreatyRevoke { guild_id: other } => {
+            registry.revoke_treaty(player_id, *other, diplomacy, now).map(|guild_id| {
+                let mut out = guild_info_to_members(registry, guild_id);
+                out.extend(guild_info_to_members(registry, *other));
+                out
+            })
+        }
+        _ => return Vec::new(),
+    };
+
+    result.unwrap_or_else(|e| {
+        warn!("[GuildRegistry] Player {} guild request rejected: {}", player_id, e);
+        vec![(player_id, ServerMessage::Error { message: e.to_string() })]
+    })
+}
+
+fn bank_replies(registry: &GuildRegistry, player: &PlayerSaveData) -> Vec<(u64, ServerMessage)> {
+    let mut out = vec![inventory_update(player)];
+    if let Some(guild) = registry.guild_of(player.player_id) {
+        out.extend(guild_info_to_members(registry, guild.guild_id));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::faction_diplomacy::DiplomacyStatus;
+
+    fn registry() -> (GuildRegistry, FactionDiplomacyManager, PersistenceManager) {
+        (GuildRegistry::default(), FactionDiplomacyManager::new(), PersistenceManager::default())
+    }
+
+    #[test]
+    fn ranks_gate_membership_changes() {
+        let (mut guilds, mut diplomacy, _) = registry();
+        let guild_id = guilds.create(1, "Seed Keepers", "seed", 0).unwrap();
+        assert_eq!(guilds.get(guild_id).unwrap().tag, "SEED");
+        assert_eq!(guilds.create(2, "seed keepers", "SK", 0), Err(GuildError::NameTaken("seed keepers".into())));
+
+        guilds.invite(1, 2, 0).unwrap();
+        guilds.invite(1, 3, 0).unwrap();
+        assert_eq!(guilds.respond_invite(2, guild_id, true, GUILD_INVITE_TTL_SECS + 1), Err(GuildError::NoInvite(guild_id)));
+        assert!(guilds.respond_invite(3, guild_id, true, 10).unwrap());
+
+        // Recruits cannot invite; promoted officers can, but cannot touch their peers.
+        assert_eq!(guilds.invite(3, 4, 10), Err(GuildError::MissingPermission("invite")));
+        guilds.set_rank(1, 3, 1, 10).unwrap();
+        guilds.invite(3, 4, 10).unwrap();
+        guilds.respond_invite(4, guild_id, true, 10).unwrap();
+        guilds.set_rank(1, 4, 1, 10).unwrap();
+        assert_eq!(guilds.kick(3, 4, 10), Err(GuildError::Outranked(4)));
+        assert!(matches!(guilds.set_rank(3, 4, 0, 10), Err(GuildError::Outranked(4))));
+
+        // Leader hands over, then the new leader leaves: the senior remaining member leads.
+        guilds.set_rank(1, 3, 0, 20).unwrap();
+        assert_eq!(guilds.get(guild_id).unwrap().leader(), Some(3));
+        assert_eq!(guilds.get(guild_id).unwrap().rank_of(1), Some(1));
+        assert_eq!(guilds.leave(3, &mut diplomacy, 30), Ok((guild_id, false)));
+        assert_eq!(guilds.get(guild_id).unwrap().leader(), Some(1));
+
+        let audit = guilds.audit(1, 3).unwrap().1;
+        assert_eq!(audit[0].action, GuildAuditAction::LeadershipTransferred { to: 1 });
+        assert_eq!(guilds.leave(1, &mut diplomacy, 40), Ok((guild_id, false)));
+        assert_eq!(guilds.leave(4, &mut diplomacy, 50), Ok((guild_id, true)));
+        assert!(guilds.is_empty());
+    }
+
+    #[test]
+    fn edited_ranks_cannot_exceed_the_editors_permissions() {
+        let (mut guilds, _, _) = registry();
+        guilds.create(1, "Flow Wardens", "FLOW", 0).unwrap();
+        let rank = WireGuildRank { rank_id: 5, name: "Banker".into(), permissions: GUILD_PERM_BANK_WITHDRAW | GUILD_PERM_CHAT };
+        let guild_id = guilds.edit_rank(1, &rank, 0).unwrap();
+        assert_eq!(guilds.get(guild_id).unwrap().ranks.iter().map(|r| r.rank_id).collect::<Vec<_>>(), [0, 1, 2, 3, 5]);
+
+        guilds.invite(1, 2, 0).unwrap();
+        guilds.respond_invite(2, guild_id, true, 0).unwrap();
+        guilds.set_rank(1, 2, 1, 0).unwrap();
+        // Officers lack EDIT_RANKS by default; grant it, then try to escalate.
+        let officer = WireGuildRank { rank_id: 1, name: "Officer".into(), permissions: GUILD_PERMS_ALL & !GUILD_PERM_TREATY };
+        guilds.edit_rank(1, &officer, 0).unwrap();
+        let escalate = WireGuildRank { rank_id: 5, name: "Banker".into(), permissions: GUILD_PERM_TREATY };
+        assert!(matches!(guilds.edit_rank(2, &escalate, 0), Err(GuildError::InvalidRank(_))));
+        let own = WireGuildRank { rank_id: 1, name: "Boss".into(), permissions: GUILD_PERMS_ALL };
+        assert!(matches!(guilds.edit_rank(2, &own, 0), Err(GuildError::InvalidRank(_))));
+    }
+
+    #[test]
+    fn bank_moves_items_between_save_and_guild() {
+        let (mut guilds, _, mut persistence) = registry();
+        let mut save = PlayerSaveData::new(1);
+        let wood = items::current().get("verdant_wood").unwrap().item_id;
+        save.inventory[4] = HotbarSlot::new(wood, 10, 1.0);
+        save.inventory[5] = HotbarSlot::new(u32::MAX, 1, 1.0);
+        persistence.save_player(&save).unwrap();
+        let guild_id = guilds.create(1, "Weavers", "WV", 0).unwrap();
+
+        let after = guilds.deposit(1, 4, 6, &mut persistence, 0).unwrap();
+        assert_eq!(after.inventory[4].count, 4);
+        assert_eq!(guilds.get(guild_id).unwrap().bank[0], HotbarSlot::new(wood, 6, 1.0));
+        assert!(matches!(guilds.deposit(1, 4, 5, &mut persistence, 0), Err(GuildError::Insufficient { .. })));
+        assert_eq!(guilds.deposit(1, 40, 1, &mut persistence, 0).unwrap_err(), GuildError::InvalidSlot(40));
+        assert_eq!(guilds.deposit(1, 5, 1, &mut persistence, 0).unwrap_err(), GuildError::UnknownItem(u32::MAX));
+
+        let after = guilds.withdraw(1, 0, 6, &mut persistence, 0).unwrap();
+        assert_eq!(after.inventory[4], HotbarSlot::new(wood, 10, 1.0));
+        assert_eq!(persistence.load_player(1).unwrap().inventory[4].count, 10);
+        assert_eq!(guilds.get(guild_id).unwrap().bank[0], HotbarSlot::empty());
+
+        // A recruit may chat but not touch the bank.
+        guilds.invite(1, 2, 0).unwrap();
+        guilds.respond_invite(2, guild_id, true, 0).unwrap();
+        assert_eq!(
+            guilds.withdraw(2, 0, 1, &mut persistence, 0).unwrap_err(),
+            GuildError::MissingPermission("bank withdraw")
+        );
+        let chat = ClientMessage::GuildChat { text: " hi ".into() };
+        let mut diplomacy = FactionDiplomacyManager::new();
+        let replies = handle_guild_message(2, &chat, &mut guilds, &mut diplomacy, &mut persistence, 5);
+        assert_eq!(replies.len(), 2);
+        assert!(matches!(&replies[0].1, ServerMessage::GuildChatMessage { text, .. } if text == "hi"));
+    }
+
+    #[test]
+    fn treaties_survive_a_reload_through_the_store() {
+        let store: Arc<dyn GuildStore> = Arc::new(InMemoryGuildStore::new());
+        let mut guilds = GuildRegistry::with_store(store.clone()).unwrap();
+        let mut diplomacy = FactionDiplomacyManager::new();
+        let a = guilds.create(1, "Alpha Grove", "AG", 0).unwrap();
+        let b = guilds.create(2, "Beta Spring", "BS", 0).unwrap();
+
+        guilds.propose_treaty(1, b, GuildTreatyTerms::Alliance, &mut diplomacy, 0).unwrap();
+        assert_eq!(guilds.respond_treaty(2, a, true, &mut diplomacy, 5), Ok(Some(GuildTreatyTerms::Alliance)));
+        assert_eq!(diplomacy.guild_status(a, b), DiplomacyStatus::Allied);
+
+        let reloaded = GuildRegistry::with_store(store).unwrap();
+        assert_eq!(reloaded.guild_of(2).map(|g| g.guild_id), Some(b));
+        let mut fresh = FactionDiplomacyManager::new();
+        assert_eq!(reloaded.restore_treaties(&mut fresh), 1);
+        assert_eq!(fresh.guild_status(b, a), DiplomacyStatus::Allied);
+
+        guilds.revoke_treaty(2, a, &mut diplomacy, 10).unwrap();
+        assert!(guilds.get(a).unwrap().treaties.is_empty());
+        assert_eq!(diplomacy.guild_status(a, b), DiplomacyStatus::Neutral);
+    }
+}
@@ -0,0 +1,174 @@
+/*!
+ * server/src/guild/store.rs
+ *
+ * Storage backends for GuildRegistry behind the GuildStore trait.
+ *
+ * - InMemoryGuildStore: unit tests / sovereign dev.
+ * - FileGuildStore: one JSON file per guild under `<root>/guilds`,
+ *   written atomically (temp file + fsync + rename).
+ *
+ * AG-SML v1.0 | TOLC 8 | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use tracing::warn;
+
+use crate::guild::registry::Guild;
+use crate::persistence::player_store::write_atomic;
+
+const GUILDS_DIR: &str = "guilds";
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug)]
+pub enum GuildStoreError {
+    Io(io::Error),
+    Serialize(String),
+    /// Record exists on disk but cannot be read back.
+    Corrupt { path: PathBuf, reason: String },
+}
+
+impl fmt::Display for GuildStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GuildStoreError::Io(e) => write!(f, "guild store I/O error: {}", e),
+            GuildStoreError::Serialize(e) => write!(f, "guild store serialization error: {}", e),
+            GuildStoreError::Corrupt { path, reason } => {
+                write!(f, "guild store record {} corrupt: {}", path.display(), reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GuildStoreError {}
+
+impl From<io::Error> for GuildStoreError {
+    fn from(e: io::Error) -> Self {
+        GuildStoreError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for GuildStoreError {
+    fn from(e: serde_json::Error) -> Self {
+        GuildStoreError::Serialize(e.to_string())
+    }
+}
+
+// ============================================================================
+// Trait
+// ============================================================================
+
+/// Storage backend for GuildRegistry. Writes are durable when they return Ok.
+pub trait GuildStore: Send + Sync {
+    fn load_guilds(&self) -> Result<Vec<Guild>, GuildStoreError>;
+
+    /// Insert or replace by `guild.guild_id`.
+    fn put_guild(&self, guild: &Guild) -> Result<(), GuildStoreError>;
+
+    /// Deleting an unknown guild is not an error.
+    fn delete_guild(&self, guild_id: u64) -> Result<(), GuildStoreError>;
+}
+
+// ============================================================================
+// In-memory backend
+// ============================================================================
+
+#[derive(Default)]
+pub struct InMemoryGuildStore {
+    guilds: RwLock<HashMap<u64, Guild>>,
+}
+
+impl InMemoryGuildStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl GuildStore for InMemoryGuildStore {
+    fn load_guilds(&self) -> Result<Vec<Guild>, GuildStoreError> {
+        let mut guilds: Vec<Guild> = self.guilds.read().unwrap().values().cloned().collect();
+        guilds.sort_by_key(|g| g.guild_id);
+        Ok(guilds)
+    }
+
+    fn put_guild(&self, guild: &Guild) -> Result<(), GuildStoreError> {
+        self.guilds.write().unwrap().insert(guild.guild_id, guild.clone());
+        Ok(())
+    }
+
+    fn delete_guild(&self, guild_id: u64) -> Result<(), GuildStoreError> {
+        self.guilds.write().unwrap().remove(&guild_id);
+        Ok(())
+    }
+}
+
+// ============================================================================
+// File backend
+// ============================================================================
+
+pub struct FileGuildStore {
+    root: PathBuf,
+}
+
+impl FileGuildStore {
+    /// Open (creating if needed) a store rooted at `root`.
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, GuildStoreError> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(root.join(GUILDS_DIR))?;
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn record_path(&self, guild_id: u64) -> PathBuf {
+        self.root.join(GUILDS_DIR).join(format!("{}.json", guild_id))
+    }
+}
+
+impl GuildStore for FileGuildStore {
+    /// Every `<id>.json`; stale temp files from a crash are removed.
+    fn load_guilds(&self) -> Result<Vec<Guild>, GuildStoreError> {
+        let mut out: Vec<Guild> = Vec::new();
+        for entry in fs::read_dir(self.root.join(GUILDS_DIR))? {
+            let path = entry?.path();
+            match path.extension().and_then(|e| e.to_str()) {
+                Some("json") => {}
+                Some("tmp") => {
+                    warn!("[GuildStore] Removing stale temp file {}", path.display());
+                    fs::remove_file(&path)?;
+                    continue;
+                }
+                _ => continue,
+            }
+            let guild = serde_json::from_slice(&fs::read(&path)?)
+                .map_err(|e| GuildStoreError::Corrupt { path: path.clone(), reason: e.to_string() })?;
+            out.push(guild);
+        }
+        out.sort_by_key(|g| g.guild_id);
+        Ok(out)
+    }
+
+    fn put_guild(&self, guild: &Guild) -> Result<(), GuildStoreError> {
+        let bytes = serde_json::to_vec_pretty(guild)?;
+        write_atomic(&self.record_path(guild.guild_id), &bytes)?;
+        Ok(())
+    }
+
+    fn delete_guild(&self, guild_id: u64) -> Result<(), GuildStoreError> {
+        match fs::remove_file(self.record_path(guild_id)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
@@ -4,6 +4,7 @@
 // Unlocks performance for large worlds + many concurrent WebXR/desktop players
 // Replaces/augments basic InterestManager from v17.26
 // PATSAGi-aligned, abundance-preserving (Critical entities always replicated), mercy-gated performance
+// v21.90 — Static blockers + swept-sphere queries (collision for authoritative movement)
 // AG-SML v1.0 | Ra-Thor + 13+ PATSAGi Councils
 
 use bevy::prelude::*;
@@ -70,6 +71,22 @@ impl ChunkCoord {
     }
 }
 
+/// Static collision volume (wall, cliff, structure), axis-aligned
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Blocker {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+/// First contact of a swept sphere with a blocker
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SweepHit {
+    /// Fraction of the swept segment travelled before contact, in [0, 1]
+    pub t: f32,
+    /// Outward normal of the blocker face that was hit
+    pub normal: Vec3,
+}
+
 #[derive(Resource)]
 pub struct HierarchicalGrid {
     pub config: HierarchicalGridConfig,
@@ -77,6 +94,8 @@ pub struct HierarchicalGrid {
     entity_positions: HashMap<Entity, Vec3>,
     dirty_chunks: HashSet<ChunkCoord>,
     last_full_recalc: f32,
+    blockers: Vec<Blocker>,
+    blocker_chunks: HashMap<ChunkCoord, Vec<usize>>,
 }
 
 impl HierarchicalGrid {
@@ -87,7 +106,56 @@ impl HierarchicalGrid {
             entity_positions: HashMap::new(),
             dirty_chunks: HashSet::new(),
             last_full_recalc: 0.0,
+            blockers: Vec::new(),
+            blocker_chunks: HashMap::new(),
+        }
+    }
+
+    /// Register a static blocker; indexed by every chunk it overlaps.
+    pub fn insert_blocker(&mut self, a: Vec3, b: Vec3) -> usize {
+        let blocker = Blocker { min: a.min(b), max: a.max(b) };
+        let index = self.blockers.len();
+        for coord in self.chunks_overlapping(blocker.min, blocker.max) {
+            self.blocker_chunks.entry(coord).or_default().push(index);
         }
+        self.blockers.push(blocker);
+        index
+    }
+
+    pub fn blockers(&self) -> &[Blocker] {
+        &self.blockers
+    }
+
+    /// Sweep a sphere of `radius` from `from` to `to` against the static blockers.
+    /// Blockers the sphere already overlaps at `from` are ignored so it can move out of them.
+    pub fn sweep_sphere(&self, from: Vec3, to: Vec3, radius: f32) -> Option<SweepHit> {
+        let lo = from.min(to) - Vec3::splat(radius);
+        let hi = from.max(to) + Vec3::splat(radius);
+        let mut candidates: Vec<usize> = self
+            .chunks_overlapping(lo, hi)
+            .filter_map(|coord| self.blocker_chunks.get(&coord))
+            .flatten()
+            .copied()
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let delta = to - from;
+        candidates
+            .into_iter()
+            .filter_map(|i| {
+                let b = &self.blockers[i];
+                sweep_point_vs_box(from, delta, b.min - Vec3::splat(radius), b.max + Vec3::splat(radius))
+            })
+            .min_by(|a, b| a.t.partial_cmp(&b.t).unwrap_or(std::cmp::Ordering::Equal))
+    }
+
+    fn chunks_overlapping(&self, min: Vec3, max: Vec3) -> impl Iterator<Item = ChunkCoord> {
+        let lo = ChunkCoord::from_position(min, self.config.chunk_size);
+        let hi = ChunkCoord::from_position(max, self.config.chunk_size);
+        (lo.x..=hi.x).flat_map(move |x| {
+            (lo.y..=hi.y).flat_map(move |y| (lo.z..=hi.z).map(move |z| ChunkCoord { x, y, z }))
+        })
     }
 
     /// Insert or move an entity (call from movement/harvest/spawn systems on position change)
@@ -205,6 +273,37 @@ impl HierarchicalGrid {
     }
 }
 
+/// Slab test of the segment `origin + delta * t` (t in [0, 1]) entering the box `min..max`.
+fn sweep_point_vs_box(origin: Vec3, delta: Vec3, min: Vec3, max: Vec3) -> Option<SweepHit> {
+    let inside = origin.cmpgt(min).all() && origin.cmplt(max).all();
+    if inside {
+        return None;
+    }
+
+    let mut t_enter = f32::NEG_INFINITY;
+    let mut t_exit = f32::INFINITY;
+    let mut normal = Vec3::ZERO;
+    for axis in 0..3 {
+        let (o, d) = (origin[axis], delta[axis]);
+        if d.abs() < f32::EPSILON {
+            if o <= min[axis] || o >= max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let (t1, t2) = ((min[axis] - o) / d, (max[axis] - o) / d);
+        let (near, far) = if t1 < t2 { (t1, t2) } else { (t2, t1) };
+        if near > t_enter {
+            t_enter = near;
+            normal = Vec3::ZERO;
+            normal[axis] = -d.signum();
+        }
+        t_exit = t_exit.min(far);
+    }
+
+    (t_enter <= t_exit && (0.0..=1.0).contains(&t_enter)).then_some(SweepHit { t: t_enter, normal })
+}
+
 #[derive(Resource, Default)]
 pub struct InterestUpdateFlag {
     pub needs_full_recalc: bool,
@@ -2,6 +2,7 @@
  * server/src/inventory_replication.rs
  * Full server-side handling for InventoryMove (40-slot general) + hotbar (8-slot).
  * Now emits SafetyNetBroadcast via EventWriter for severe ModerationAction (Ban/Kick).
+ * Moves touching a slot whose item the shared registry cannot resolve (or overstacked) are rejected.
  * All prior logic preserved exactly. AG-SML v1.0 | TOLC 8 + RBE + PATSAGi
  */
 
@@ -53,6 +54,17 @@ pub fn validate_inventory_move_authoritative(
         &player_data.inventory[from]
     };
 
+    let dst_slot: &HotbarSlot = if is_hotbar {
+        &player_data.hotbar[to]
+    } else {
+        &player_data.inventory[to]
+    };
+    // Both slots must hold items the registry resolves, within their stack limits.
+    let items = shared::items::current();
+    if let Some(e) = [src_slot, dst_slot].into_iter().find_map(|slot| items.check_slot(slot).err()) {
+        return AuthoritativeMoveValidity { allowed: false, reason: Some(format!("Unresolvable slot: {}", e)), mercy_resonance: 0.2, abundance_score: 0.3, anomaly_score: 0.8 };
+    }
+
     let src_valence = src_slot.valence;
 
     let mercy_gate = if src_valence < -0.5 { 0.25 } else { 0.92 };
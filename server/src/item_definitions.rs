@@ -0,0 +1,42 @@
+/*!
+ * server/src/item_definitions.rs
+ *
+ * Item and resource definitions loaded from the assets/items RON files
+ * (POWRUSH_ITEM_DIR overrides) into the shared registry through the common
+ * definition loader (definition_dir.rs), hot-reloaded in debug builds. Every
+ * boundary that accepts an item key or id (transport, trade escrow, inventory,
+ * guild bank) reads `shared::items::current()`.
+ *
+ * AG-SML v1.0 | TOLC 8 | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::path::Path;
+use std::sync::Arc;
+
+use shared::items::{self, ItemLoadError, ItemRegistry};
+
+use crate::definition_dir::{DefinitionSet, Definitions};
+
+pub type ItemDefinitions = Definitions<ItemRegistry>;
+
+impl DefinitionSet for ItemRegistry {
+    type Error = ItemLoadError;
+    const TAG: &'static str = "Items";
+    const NOUN: &'static str = "definitions";
+    const DIR_ENV: &'static str = "POWRUSH_ITEM_DIR";
+    const DEFAULT_DIR: &'static str = "assets/items";
+
+    fn load_dir(dir: &Path) -> Result<Self, ItemLoadError> {
+        ItemRegistry::load_dir(dir)
+    }
+    fn count(&self) -> usize {
+        ItemRegistry::len(self)
+    }
+    fn current() -> Arc<Self> {
+        items::current()
+    }
+    fn install(registry: Arc<Self>) {
+        items::install(registry)
+    }
+}
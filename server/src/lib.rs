@@ -2,6 +2,20 @@
  * server/src/lib.rs
  * v21.89.3 — Inventory + AudioMoment + NEVC attachment (Finish Pass A).
  * SafetyNet emission preserved. rathor_integration public for unified cohost.
+ * v21.89.4 — Trade escrow routed from transport (offer/counter/lock/confirm/cancel).
+ * v21.90 — Authoritative movement: MoveCommand re-simulated server-side, corrections sent back.
+ * v21.91 — UseAbility routed into combat (lag-compensated targeting); rejections sent back.
+ * v21.92 — Chunk persistence + streaming: ChunkWorld saves dirty chunks, sends ChunkSnapshot.
+ * v21.93 — Dynamic events mounted; authored event scripts (triggers, phases, rewards, follow-ups).
+ * v21.94 — Guilds routed from transport (ranks, bank, chat, audit, treaties via faction_diplomacy).
+ * v21.94.1 — Abundance needs routed from transport; each round's per-player record sent back (co-host syncs caps).
+ * v21.95 — Item registry loaded before everything else; unknown item keys / ids rejected at the boundary.
+ * v21.96 — Crafting routed from transport: recipes after items, queued jobs delivered on a 1 s tick.
+ * v21.97 — Council agenda routed from transport (submit / list / vote); co-host resolutions broadcast.
+ * v21.97.1 — Logins bind their DID document into the TradeSystem; signed offers verify against it.
+ * v21.98 — NPC navigation mounted: zone files + placed structures feed the nav grid, NavAgents follow players.
+ * v21.99 — Abilities / items / recipes share one definition loader; item hot-reloads re-validate recipes.
+ * v21.99.1 — UseAbility resolves the loadout slot holding the ability; unknown ones are refused here.
  * AG-SML v1.0 | TOLC 8 + RBE + PATSAGi | info@Rathor.ai
  */
 
@@ -14,17 +28,95 @@ use crate::safety_net_broadcast::EmitSafetyNetBroadcast;
 use crate::network::tokio_transport::{TransportEvent, TransportCommand};
 use crate::audio_moment_catalog::{AudioMomentCatalogPlugin, ServerAudioMomentStore};
 use crate::audio_moment_net_handler::route_client_audio_message;
-use shared::protocol::ClientMessage;
+use crate::network::auth::now_ms;
+use crate::trade::escrow::{expire_trades, handle_player_disconnected, handle_trade_message};
+use crate::trade::TradeEscrow;
+use crate::trade_system::TradeSystem;
+use crate::movement::{MoveResult, MovementAuthority};
+use crate::combat::{AbilityLoadout, AbilityRejected, AbilityUseEvent};
+use crate::interest_management::InterestManager;
+use crate::persistence::faction_persistence::PlayerIdMapping;
+use crate::replication::snapshot::SnapshotTick;
+use crate::replication::{DirtyReplicationState, ReplicatedFields};
+use crate::spatial::chunk_streaming::ChunkStreamingPlugin;
+use crate::dynamic_events::DynamicEventsPlugin;
+use crate::navigation::NavigationPlugin;
+use crate::faction_diplomacy::{FactionDiplomacyManager, FactionDiplomacyPlugin};
+use crate::guild::{handle_guild_message, GuildRegistry};
+use crate::definition_dir::{hot_reload_definitions, load_definitions};
+use crate::item_definitions::ItemDefinitions;
+use crate::crafting::{handle_crafting_message, CraftingService, RecipeDefinitions};
+use crate::council_agenda::{handle_council_message, CouncilAgenda};
+use crate::spatial::chunk_streaming::ChunkWorld;
+use crate::abundance::{handle_abundance_message, AbundanceRounds};
+use shared::crafting::RecipeRegistry;
+use shared::items::ItemRegistry;
+use shared::protocol::{ClientMessage, ServerMessage, WireAbilityRejectReason};
 
 // Public Ra-Thor / PATSAGi / RTT cohost surface
 pub mod rathor_integration;
 
+// WebSocket transport + RSIL handshake authentication
+pub mod network;
+
 pub mod audio_moment_catalog;
 pub mod audio_moment_net_handler;
 
 // Finish Pass A: NEVC live attachment (shared-backed, no algorithm mirror)
 pub mod nevc_attachment;
 
+// Data-driven registries (abilities, items, recipes): RON directory loader + dev hot-reload
+pub mod definition_dir;
+
+// Item / resource registry (assets/items): stable keys + ids every boundary resolves against
+pub mod item_definitions;
+
+// Authoritative player saves: PersistenceManager over durable PlayerStore backends
+pub mod persistence;
+pub mod persistence_polish;
+
+// Interest-filtered, delta-compressed entity snapshots
+pub mod hierarchical_grid;
+pub mod interest_management;
+pub mod replication;
+pub mod combat;
+
+// Authoritative movement: speed / tick-budget / collision validation + corrections
+pub mod movement;
+
+// Player trade: two-phase escrow over a hash-chained ledger
+pub mod trade;
+pub mod trade_system;
+
+// NPC navigation: layered nav grid, hierarchical A*, shared path cache
+pub mod navigation;
+
+// World events: built-in types plus authored event scripts (assets/events)
+pub mod dynamic_events;
+pub mod event_scripts;
+
+// Guilds: ranks + permissions, shared bank, chat, audit log; treaties through faction diplomacy
+pub mod faction_diplomacy;
+pub mod guild;
+
+// Abundance rounds: declared needs, council caps, per-player allocation records
+pub mod abundance;
+
+// Crafting: recipe graph, persistent job queues, stations, ecological cost, raw-resource planner
+pub mod crafting;
+
+// Council agenda: player proposals, open agenda and ballots; deliberation runs in the simulation
+pub mod council_agenda;
+
+// Chunk layer of spatial/: dirty tracking, durable chunk store, chunk streaming.
+// (spatial.rs — resync glue over the simulation crate — is not part of this build.)
+pub mod spatial {
+    pub mod chunk_manager;
+    pub mod chunk_store;
+    pub mod chunk_streaming;
+    pub mod hierarchical_grid;
+}
+
 #[derive(Resource)]
 pub struct TransportEventReceiver {
     pub rx: mpsc::UnboundedReceiver<TransportEvent>,
@@ -40,20 +132,47 @@ pub struct ServerCorePlugin;
 
 impl Plugin for ServerCorePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(AudioMomentCatalogPlugin)
+        app.add_plugins((AudioMomentCatalogPlugin, ChunkStreamingPlugin, DynamicEventsPlugin, FactionDiplomacyPlugin, NavigationPlugin))
             .add_event::<TransportEvent>()
             .add_event::<EmitSafetyNetBroadcast>()
             .init_resource::<Option<TransportEventReceiver>>()
             .init_resource::<MercyAnomalyDetector>()
-            .add_systems(Startup, setup_transport_bridge)
+            // In-memory default; main.rs replaces it with `PersistenceManager::open_durable`.
+            .init_resource::<PersistenceManager>()
+            .init_resource::<MovementAuthority>()
+            .init_resource::<GuildRegistry>()
+            .init_resource::<ItemDefinitions>()
+            .init_resource::<AbundanceRounds>()
+            .init_resource::<RecipeDefinitions>()
+            .init_resource::<CraftingService>()
+            .init_resource::<CouncilAgenda>()
+            // Before any Startup system validates data (event scripts) against it;
+            // recipes are validated against the items.
+            .add_systems(PreStartup, (load_definitions::<ItemRegistry>, load_definitions::<RecipeRegistry>).chain())
+            .add_systems(Startup, (setup_transport_bridge, recover_trade_escrow, restore_guild_treaties))
             .add_systems(
                 Update,
                 (
                     transport_event_bridge,
                     process_inventory_messages,
                     process_audio_moment_messages,
+                    process_trade_messages,
+                    bind_trade_identities,
+                    process_guild_messages,
+                    process_abundance_messages,
+                    send_abundance_grants,
+                    process_crafting_messages,
+                    complete_crafting_jobs,
+                    process_council_messages,
+                    broadcast_council_resolutions,
+                    process_movement_messages,
+                    process_ability_messages,
                 ),
             );
+
+        #[cfg(debug_assertions)]
+        // Items first: a replaced item registry re-validates the recipes in the same poll.
+        app.add_systems(Update, (hot_reload_definitions::<ItemRegistry>, hot_reload_definitions::<RecipeRegistry>).chain());
     }
 }
 
@@ -144,4 +263,415 @@ fn process_audio_moment_messages(
     }
 }
 
-// End of server/src/lib.rs v21.89.3 — NEVC attachment declared. Thunder locked in. Yoi ⚡
+/// Finish what a crash interrupted: replay inventory changes the trade ledger
+/// announced but never completed, then refund items it still shows in escrow.
+fn recover_trade_escrow(escrow: Option<ResMut<TradeEscrow>>, mut persistence: ResMut<PersistenceManager>) {
+    let Some(mut escrow) = escrow else { return };
+    match escrow.recover(&mut *persistence, now_ms()) {
+        Ok((0, 0)) => {}
+        Ok((replayed, refunded)) => info!(
+            "[ServerCore] Trade ledger recovery: replayed {} change(s), refunded {} unsettled trade(s)",
+            replayed, refunded
+        ),
+        Err(e) => error!("[ServerCore] Trade ledger recovery failed: {}", e),
+    }
+}
+
+/// Route trade messages through the escrow; items move through the player saves,
+/// and disconnects and expiry refund escrowed items.
+/// Host bootstrap inserts TradeEscrow (durable ledger); without it trades are ignored.
+fn process_trade_messages(
+    mut transport_events: EventReader<TransportEvent>,
+    escrow: Option<ResMut<TradeEscrow>>,
+    mut persistence: ResMut<PersistenceManager>,
+    command_tx: Option<Res<TransportCommandSender>>,
+) {
+    let Some(mut escrow) = escrow else { return };
+    let now = now_ms();
+
+    let mut replies = expire_trades(&mut escrow, &mut *persistence, now);
+    for event in transport_events.read() {
+        match event {
+            TransportEvent::MessageReceived { player_id, message } => {
+                replies.extend(handle_trade_message(*player_id, message, &mut escrow, &mut *persistence, now));
+            }
+            TransportEvent::ClientDisconnected { player_id } => {
+                replies.extend(handle_player_disconnected(*player_id, &mut escrow, &mut *persistence, now));
+            }
+            TransportEvent::ClientConnected { .. } => {}
+        }
+    }
+
+    if let Some(sender) = command_tx.as_ref() {
+        for (player_id, message) in replies {
+            let _ = sender.tx.send(TransportCommand::Send { player_id, message });
+        }
+    }
+}
+
+/// Signed trade offers verify against the DID document a player logged in with;
+/// bind it on every login (a rollback to an older document is refused and logged).
+/// main.rs inserts the TradeSystem; without it there is nothing to bind.
+fn bind_trade_identities(mut transport_events: EventReader<TransportEvent>, trades: Option<ResMut<TradeSystem>>) {
+    let Some(mut trades) = trades else { return };
+    for event in transport_events.read() {
+        if let TransportEvent::ClientConnected { info } = event {
+            if let Err(e) = trades.bind_identity(info.player_id, &info.identity) {
+                warn!("[ServerCore] {}", e);
+            }
+        }
+    }
+}
+
+/// Guild records are the durable copy of signed treaties; re-seed diplomacy from them.
+/// The registry is in-memory unless the host inserted `GuildRegistry::open_durable`
+/// (main.rs does, over data/guilds) before Startup.
+fn restore_guild_treaties(guilds: Res<GuildRegistry>, mut diplomacy: ResMut<FactionDiplomacyManager>) {
+    let restored = guilds.restore_treaties(&mut diplomacy);
+    if restored > 0 {
+        info!("[ServerCore] Restored {} guild treaty relation(s) across {} guild(s)", restored, guilds.len());
+    }
+}
+
+/// Route guild requests; bank transfers write through PersistenceManager.
+fn process_guild_messages(
+    mut transport_events: EventReader<TransportEvent>,
+    mut guilds: ResMut<GuildRegistry>,
+    mut diplomacy: ResMut<FactionDiplomacyManager>,
+    mut persistence: ResMut<PersistenceManager>,
+    command_tx: Option<Res<TransportCommandSender>>,
+) {
+    let now = now_ms() / 1000;
+    let mut replies = Vec::new();
+    for event in transport_events.read() {
+        if let TransportEvent::MessageReceived { player_id, message } = event {
+            replies.extend(handle_guild_message(*player_id, message, &mut guilds, &mut diplomacy, &mut persistence, now));
+        }
+    }
+
+    if let Some(sender) = command_tx.as_ref() {
+        for (player_id, message) in replies {
+            let _ = sender.tx.send(TransportCommand::Send { player_id, message });
+        }
+    }
+}
+
+/// Route abundance need declarations into the next allocation round.
+fn process_abundance_messages(
+    mut transport_events: EventReader<TransportEvent>,
+    mut rounds: ResMut<AbundanceRounds>,
+    command_tx: Option<Res<TransportCommandSender>>,
+) {
+    let mut replies = Vec::new();
+    for event in transport_events.read() {
+        if let TransportEvent::MessageReceived { player_id, message } = event {
+            replies.extend(handle_abundance_message(*player_id, message, &mut rounds));
+        }
+    }
+
+    if let Some(sender) = command_tx.as_ref() {
+        for (player_id, message) in replies {
+            let _ = sender.tx.send(TransportCommand::Send { player_id, message });
+        }
+    }
+}
+
+/// Send every claimant their record of the allocation rounds that ran this frame.
+fn send_abundance_grants(mut rounds: ResMut<AbundanceRounds>, command_tx: Option<Res<TransportCommandSender>>) {
+    let grants = rounds.drain_grants();
+    if let Some(sender) = command_tx.as_ref() {
+        for (player_id, message) in grants {
+            let _ = sender.tx.send(TransportCommand::Send { player_id, message });
+        }
+    }
+}
+
+/// Route crafting requests. Station checks and the ecological footprint use the
+/// sender's position in the chunk world when both are available.
+fn process_crafting_messages(
+    mut transport_events: EventReader<TransportEvent>,
+    mut crafting: ResMut<CraftingService>,
+    mut persistence: ResMut<PersistenceManager>,
+    mut chunk_world: Option<ResMut<ChunkWorld>>,
+    players: Res<PlayerIdMapping>,
+    transforms: Query<&Transform>,
+    command_tx: Option<Res<TransportCommandSender>>,
+) {
+    let now = now_ms() / 1000;
+    let mut replies = Vec::new();
+    for event in transport_events.read() {
+        if let TransportEvent::MessageReceived { player_id, message } = event {
+            let position = players.get_entity(*player_id).and_then(|e| transforms.get(e).ok()).map(|t| t.translation);
+            let site = chunk_world.as_deref_mut().zip(position);
+            replies.extend(handle_crafting_message(*player_id, message, &mut crafting, &mut persistence, site, now));
+        }
+    }
+
+    if let Some(sender) = command_tx.as_ref() {
+        for (player_id, message) in replies {
+            let _ = sender.tx.send(TransportCommand::Send { player_id, message });
+        }
+    }
+}
+
+/// Once a second: deliver finished crafting jobs into saves (owners online or not).
+fn complete_crafting_jobs(
+    time: Res<Time>,
+    mut since_tick: Local<f32>,
+    mut crafting: ResMut<CraftingService>,
+    mut persistence: ResMut<PersistenceManager>,
+    command_tx: Option<Res<TransportCommandSender>>,
+) {
+    *since_tick += time.delta_seconds();
+    if *since_tick < 1.0 {
+        return;
+    }
+    *since_tick = 0.0;
+    let replies = crafting.complete_due(&mut persistence, now_ms() / 1000);
+    if let Some(sender) = command_tx.as_ref() {
+        for (player_id, message) in replies {
+            let _ = sender.tx.send(TransportCommand::Send { player_id, message });
+        }
+    }
+}
+
+/// Route council agenda requests. Accepted submissions and ballots wait in the
+/// agenda for the co-host to hand to the simulation's council sessions.
+fn process_council_messages(
+    mut transport_events: EventReader<TransportEvent>,
+    mut agenda: ResMut<CouncilAgenda>,
+    command_tx: Option<Res<TransportCommandSender>>,
+) {
+    let now = now_ms() / 1000;
+    let mut replies = Vec::new();
+    for event in transport_events.read() {
+        if let TransportEvent::MessageReceived { player_id, message } = event {
+            replies.extend(handle_council_message(*player_id, message, &mut agenda, now));
+        }
+    }
+
+    if let Some(sender) = command_tx.as_ref() {
+        for (player_id, message) in replies {
+            let _ = sender.tx.send(TransportCommand::Send { player_id, message });
+        }
+    }
+}
+
+/// Tell every client how deliberated proposals came out (reported by the co-host).
+fn broadcast_council_resolutions(mut agenda: ResMut<CouncilAgenda>, command_tx: Option<Res<TransportCommandSender>>) {
+    let resolutions = agenda.drain_resolutions();
+    if let Some(sender) = command_tx.as_ref() {
+        for resolution in resolutions {
+            let _ = sender.tx.send(TransportCommand::Broadcast { message: resolution.to_message() });
+        }
+    }
+}
+
+/// Re-simulate movement input server-side. The result is written to the player's
+/// Transform (snapshots replicate it); corrections go back to v26+ senders and
+/// speed / time violations feed the anomaly detector.
+fn process_movement_messages(
+    mut transport_events: EventReader<TransportEvent>,
+    mut authority: ResMut<MovementAuthority>,
+    players: Res<PlayerIdMapping>,
+    interest_manager: Option<Res<InterestManager>>,
+    mut transforms: Query<(&mut Transform, Option<&mut DirtyReplicationState>)>,
+    tick: Option<Res<SnapshotTick>>,
+    mut detector: ResMut<MercyAnomalyDetector>,
+    mut safety_net_writer: EventWriter<EmitSafetyNetBroadcast>,
+    command_tx: Option<Res<TransportCommandSender>>,
+) {
+    let now = now_ms();
+    let grid = interest_manager.as_ref().map(|im| &im.grid);
+
+    for event in transport_events.read() {
+        match event {
+            TransportEvent::MessageReceived {
+                player_id,
+                message: message @ (ClientMessage::MoveCommand { .. } | ClientMessage::Move { .. }),
+            } => {
+                let Some(entity) = players.get_entity(*player_id) else { continue };
+                let Ok((mut transform, dirty)) = transforms.get_mut(entity) else { continue };
+                authority.sync_from_world(*player_id, transform.translation, now);
+
+                let Some(MoveResult::Applied { position, velocity }) =
+                    authority.handle_message(*player_id, message, grid, now)
+                else {
+                    continue;
+                };
+                transform.translation = position;
+                if let Some(mut dirty) = dirty {
+                    if dirty.last_velocity != Some(velocity) {
+                        dirty.last_velocity = Some(velocity);
+                        dirty.dirty_mask |= ReplicatedFields::VELOCITY;
+                    }
+                }
+            }
+            TransportEvent::ClientDisconnected { player_id } => authority.remove_player(*player_id),
+            _ => {}
+        }
+    }
+
+    for (player_id, violation) in authority.take_violations(now) {
+        let Some(action) =
+            detector.report_anomaly(player_id, violation.anomaly(), violation.severity(), violation.context())
+        else {
+            continue;
+        };
+        if action.is_severe() {
+            warn!("[Movement] Severe action triggered for player {}: {:?}", player_id, action);
+            safety_net_writer.send(EmitSafetyNetBroadcast {
+                player_id,
+                reason: "MovementViolation".to_string(),
+                force_full_snapshot: true,
+            });
+        } else {
+            info!("[Movement] Mercy response for player {}: {:?}", player_id, action);
+        }
+    }
+
+    let server_tick = tick.map_or(0, |t| t.0);
+    let corrections = authority.take_corrections(server_tick, now);
+    if let Some(sender) = command_tx.as_ref() {
+        for (player_id, message) in corrections {
+            let _ = sender.tx.send(TransportCommand::Send { player_id, message });
+        }
+    }
+}
+
+/// UseAbility → AbilityUseEvent for the sender's entity, on the loadout slot holding the
+/// ability; AbilityRejected → the caster's client. Wire entity ids are `Entity::to_bits`
+/// (as in EntitySnapshot).
+fn process_ability_messages(
+    mut transport_events: EventReader<TransportEvent>,
+    mut rejections: EventReader<AbilityRejected>,
+    mut ability_use: EventWriter<AbilityUseEvent>,
+    players: Res<PlayerIdMapping>,
+    loadouts: Query<&AbilityLoadout>,
+    command_tx: Option<Res<TransportCommandSender>>,
+) {
+    let send = |player_id: u64, message: ServerMessage| {
+        if let Some(sender) = command_tx.as_ref() {
+            let _ = sender.tx.send(TransportCommand::Send { player_id, message });
+        }
+    };
+
+    for event in transport_events.read() {
+        let TransportEvent::MessageReceived {
+            player_id,
+            message: ClientMessage::UseAbility { request_id, ability_id, target_id, view_tick, aim },
+        } = event
+        else {
+            continue;
+        };
+        let Some(player_entity) = players.get_entity(*player_id) else { continue };
+        let Some(slot_index) = loadouts.get(player_entity).ok().and_then(|l| l.slot_of(*ability_id)) else {
+            send(*player_id, ServerMessage::AbilityRejected {
+                request_id: *request_id,
+                ability_id: *ability_id,
+                reason: WireAbilityRejectReason::UnknownAbility,
+            });
+            continue;
+        };
+        ability_use.send(AbilityUseEvent {
+            player_entity,
+            slot_index,
+            ability_id: *ability_id,
+            request_id: *request_id,
+            // An id that is not a valid entity stays unresolved and is rejected as NoTarget.
+            target: target_id.and_then(|id| Entity::try_from_bits(id).ok()),
+            view_tick: Some(*view_tick),
+            aim: Vec3::new(aim.x, aim.y, aim.z),
+        });
+    }
+
+    for rejected in rejections.read() {
+        let Some(player_id) = players.get_id(rejected.player_entity) else { continue };
+        send(player_id, ServerMessage::AbilityRejected {
+            request_id: rejected.request_id,
+            ability_id: rejected.ability_id,
+            reason: rejected.reason,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::{
+        damage_system, equip_joined_players, handle_ability_use_requests, AbilityCooldownUpdate, AbilityDefinitions,
+        AbilityUseRateLimiter, ApplyStatusEvent, CooldownSyncTracker, DispelEvent, Health, TargetHistory,
+    };
+    use crate::hierarchical_grid::HierarchicalGridConfig;
+    use crate::persistence::faction_persistence::PlayerJoined;
+    use shared::protocol::Vec3Ser;
+    use std::time::Duration;
+
+    const FOCUSED_STRIKE: u32 = 201;
+    const MERCY_BLOOM: u32 = 101;
+
+    fn use_ability(app: &mut App, player_id: u64, ability_id: u32, target: Entity) {
+        app.world_mut().resource_mut::<Time>().advance_by(Duration::from_secs(1));
+        app.world_mut().send_event(TransportEvent::MessageReceived {
+            player_id,
+            message: ClientMessage::UseAbility {
+                request_id: 9,
+                ability_id,
+                target_id: Some(target.to_bits()),
+                view_tick: 0,
+                aim: Vec3Ser { x: 0.0, y: 0.0, z: 1.0 },
+            },
+        });
+        app.update();
+    }
+
+    #[test]
+    fn use_ability_message_reaches_the_target_through_the_loadout() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<AbilityUseRateLimiter>()
+            .init_resource::<CooldownSyncTracker>()
+            .init_resource::<TargetHistory>()
+            .init_resource::<AbilityDefinitions>()
+            .init_resource::<PlayerIdMapping>()
+            .insert_resource(InterestManager::new(HierarchicalGridConfig::default()))
+            .insert_resource(TransportCommandSender { tx })
+            .add_event::<TransportEvent>()
+            .add_event::<PlayerJoined>()
+            .add_event::<AbilityUseEvent>()
+            .add_event::<AbilityCooldownUpdate>()
+            .add_event::<AbilityRejected>()
+            .add_event::<ApplyStatusEvent>()
+            .add_event::<DispelEvent>()
+            .add_systems(
+                Update,
+                (equip_joined_players, process_ability_messages, handle_ability_use_requests, damage_system).chain(),
+            );
+
+        let target = app.world_mut().spawn((Health::new(100.0), Transform::from_xyz(0.0, 0.0, 2.0))).id();
+        app.world_mut().resource_mut::<TargetHistory>().record(target, 0, Vec3::new(0.0, 0.0, 2.0));
+        let player = app.world_mut().spawn((Transform::default(), Health::new(100.0))).id();
+        app.world_mut().resource_mut::<PlayerIdMapping>().insert(7, player);
+        app.world_mut().send_event(PlayerJoined { entity: player, player_id: 7 });
+        app.update();
+
+        use_ability(&mut app, 7, FOCUSED_STRIKE, target);
+        assert!(app.world().get::<Health>(target).unwrap().current < 100.0);
+        let loadout = app.world().get::<AbilityLoadout>(player).unwrap();
+        let slot = loadout.slot_of(FOCUSED_STRIKE).unwrap();
+        assert!(!loadout.slots[slot].can_use());
+        assert!(rx.try_recv().is_err());
+
+        // Not in the loadout: refused before reaching combat.
+        use_ability(&mut app, 7, MERCY_BLOOM, target);
+        let Ok(TransportCommand::Send { player_id, message }) = rx.try_recv() else { panic!("no rejection sent") };
+        assert_eq!(player_id, 7);
+        assert!(matches!(
+            message,
+            ServerMessage::AbilityRejected { request_id: 9, ability_id: MERCY_BLOOM, reason: WireAbilityRejectReason::UnknownAbility }
+        ));
+    }
+}
+
+// End of server/src/lib.rs v21.92 — authoritative movement + ability targeting routed, chunks streamed. Thunder locked in. Yoi ⚡
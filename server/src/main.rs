@@ -2,14 +2,29 @@
  * Powrush-MMO Authoritative Server Entry Point
  * v21.89.3 — TransportEventReceiver + TransportCommandSender both injected.
  *   Enables process_audio_moment_messages and inventory systems to reply to clients.
+ * GuildRegistry opened over the file store in data/guilds (members, bank, treaties).
+ * CraftingService opened over the file store in data/crafting (queued jobs survive restarts).
+ * ChunkWorld opened over a FileChunkStore in data/world (chunk streaming + saves).
  *
  * AG-SML v1.0 | TOLC 8 + PATSAGi | Thunder locked in. Yoi ⚡
  */
 
+use std::sync::Arc;
+
 use bevy::prelude::*;
 use tokio::runtime::Runtime;
 
 use server::ServerCorePlugin;
+use server::combat::CombatPlugin;
+use server::interest_management::InterestManagementPlugin;
+use server::replication::SnapshotReplicationPlugin;
+use server::persistence_polish::PersistenceManager;
+use server::trade::TradeEscrow;
+use server::trade_system::TradeSystem;
+use server::guild::GuildRegistry;
+use server::crafting::CraftingService;
+use server::spatial::chunk_store::FileChunkStore;
+use server::spatial::chunk_streaming::{ChunkStreamingConfig, ChunkWorld};
 use server::network::tokio_transport::TokioTransport;
 use server::{
     TransportEventReceiver, TransportCommandSender,
@@ -32,17 +47,88 @@ fn main() {
             }
         };
 
-        // Transport accept/read/write loop
-        tokio::spawn(transport.run());
+        // Durable player saves (journal recovery runs before the first load)
+        let persistence = match PersistenceManager::open_durable("data/players") {
+            Ok(pm) => pm,
+            Err(e) => {
+                error!("Failed to open player store: {}", e);
+                return;
+            }
+        };
+
+        // Trade escrow over the hash-chained ledger (items a crash left in escrow are refunded at Startup)
+        let trade_escrow = match TradeEscrow::open_durable("data/trades/ledger.jsonl") {
+            Ok(escrow) => escrow,
+            Err(e) => {
+                error!("Failed to open trade ledger: {}", e);
+                return;
+            }
+        };
+
+        // Signed trade offers and player hybrid keys (open trades survive restarts)
+        let trade_system = match TradeSystem::open_durable("data/trades") {
+            Ok(system) => system,
+            Err(e) => {
+                error!("Failed to open trade store: {}", e);
+                return;
+            }
+        };
+
+        // Guilds (ranks, bank, audit log, treaties); treaties re-seed diplomacy at Startup
+        let guilds = match GuildRegistry::open_durable("data") {
+            Ok(registry) => registry,
+            Err(e) => {
+                error!("Failed to open guild store: {}", e);
+                return;
+            }
+        };
+
+        // Crafting queues (inputs are taken from the save when a job is queued)
+        let crafting = match CraftingService::open_durable("data") {
+            Ok(service) => service,
+            Err(e) => {
+                error!("Failed to open crafting store: {}", e);
+                return;
+            }
+        };
+
+        // World chunks (resource nodes, structures, spawners) streamed to players and saved back
+        let chunk_store = match FileChunkStore::open("data/world") {
+            Ok(store) => store,
+            Err(e) => {
+                error!("Failed to open chunk store: {}", e);
+                return;
+            }
+        };
+        let chunk_world = match ChunkWorld::new(ChunkStreamingConfig::default(), Arc::new(chunk_store)) {
+            Ok(world) => world,
+            Err(e) => {
+                error!("Invalid chunk streaming config: {}", e);
+                return;
+            }
+        };
+
+        // Transport accept/read/write loop (Mercy Gate reads valence from the player saves)
+        tokio::spawn(transport.with_persistence(persistence.clone()).run());
 
         let mut app = App::new();
 
         app.add_plugins(DefaultPlugins)
             .add_plugins(ServerCorePlugin)
+            // Abilities: lag-compensated targeting, cooldowns, damage
+            .add_plugins(CombatPlugin)
+            // Egress: per-client delta snapshots filtered by interest
+            .add_plugins((InterestManagementPlugin, SnapshotReplicationPlugin))
             // Ingress: transport → Bevy events
             .insert_resource(TransportEventReceiver { rx: event_rx })
             // Egress: Bevy systems → transport (audio acks, catalog snapshots, etc.)
-            .insert_resource(TransportCommandSender { tx: command_tx });
+            .insert_resource(TransportCommandSender { tx: command_tx })
+            .insert_resource(persistence)
+            .insert_resource(trade_escrow)
+            .insert_resource(trade_system)
+            .insert_resource(guilds)
+            .insert_resource(crafting)
+            .insert_resource(chunk_world);
 
         app.add_systems(Startup, setup_authoritative_camera);
         app.add_systems(Update, authoritative_sovereign_tick);
@@ -76,6 +76,8 @@ pub enum AnomalyType {
     ExcessiveHarvestRate { rate_per_minute: f32, threshold: f32 },
     ImpossiblePositionJump { distance: f32, max_allowed: f32 },
     SuspiciousInventoryDelta { item_id: u32, quantity_gained: u32 },
+    MovementSpeedViolation { speed: f32, max_allowed: f32 },
+    MovementTimeViolation { dropped_commands: u32 },
 
     Custom(String),
 }
@@ -206,6 +208,8 @@ impl MercyAnomalyDetector {
             AnomalyType::InventoryGeneralViolation => "Unauthorized inventory manipulation detected. Realign with mercy and fair play.",
             AnomalyType::ExcessiveHarvestRate { .. } => "Harvest rate exceeds reasonable human limits. Slow down and respect the nodes.",
             AnomalyType::ImpossiblePositionJump { .. } => "Suspicious movement pattern detected. Sovereign space must remain harmonious.",
+            AnomalyType::MovementSpeedViolation { .. } => "Movement faster than the world allows. Your position has been restored by the server.",
+            AnomalyType::MovementTimeViolation { .. } => "Movement inputs arrived faster than real time. Time flows equally for all.",
             AnomalyType::SuspiciousInventoryDelta { .. } => "Large inventory gain without corresponding harvest activity. Possible duplication or exploit.",
             AnomalyType::Custom(s) => &format!("Custom anomaly: {}. Align with mercy.", s),
         };
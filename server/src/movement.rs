@@ -0,0 +1,471 @@
+/*!
+ * server/src/movement.rs
+ *
+ * Authoritative player movement. Clients send one MoveCommand per fixed
+ * movement tick (1 / MOVE_TICK_HZ s); the server re-simulates each command:
+ *
+ * - speed: requested velocity is clamped to max_speed (with tolerance);
+ * - time: a per-player tick budget refilled from wall-clock time, so a client
+ *   cannot move faster by sending commands faster (or banking them);
+ * - collision: swept sphere against HierarchicalGrid's static blockers,
+ *   sliding along the face that was hit.
+ *
+ * Whenever the result differs from what the client predicted, a
+ * MoveCorrection (rate-limited, latest state) goes back so the client can
+ * rewind and replay. Speed / time violations are reported to
+ * MercyAnomalyDetector at most once per REPORT_COOLDOWN_MS per player.
+ *
+ * Legacy `Move { delta }` (pre-v26 clients) runs through the same path with
+ * implicit sequencing: `delta` is one tick's displacement, so it is checked
+ * as the velocity delta / MOVE_DT. Those clients get no corrections, only
+ * snapshots.
+ *
+ * AG-SML v1.0 | TOLC 8 | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use shared::protocol::{ClientMessage, ServerMessage, Vec3Ser, MOVE_TICK_HZ};
+
+use crate::hierarchical_grid::HierarchicalGrid;
+use crate::mercy_anomaly_detector::AnomalyType;
+
+/// Seconds covered by one MoveCommand
+pub const MOVE_DT: f32 = 1.0 / MOVE_TICK_HZ as f32;
+
+/// Minimum spacing between anomaly reports for one player
+pub const REPORT_COOLDOWN_MS: u64 = 1_000;
+
+/// Distance kept between a sphere and the blocker it stopped against
+const CONTACT_SKIN: f32 = 0.001;
+
+/// Collision resolution passes per command (hit, slide, slide)
+const MAX_SLIDES: usize = 3;
+
+#[derive(Resource, Clone, Debug)]
+pub struct MovementConfig {
+    /// Units per second
+    pub max_speed: f32,
+    /// Multiplier on max_speed before a request counts as a violation (input / float noise)
+    pub speed_tolerance: f32,
+    /// Commands a client may bank ahead of wall-clock time (network jitter allowance)
+    pub max_burst_ticks: f32,
+    pub player_radius: f32,
+    /// Divergence from the client's prediction that triggers a correction
+    pub correction_epsilon: f32,
+    pub min_correction_interval_ms: u64,
+}
+
+impl Default for MovementConfig {
+    fn default() -> Self {
+        Self {
+            max_speed: 9.0,
+            speed_tolerance: 1.1,
+            max_burst_ticks: 30.0,
+            player_radius: 0.5,
+            correction_epsilon: 0.01,
+            min_correction_interval_ms: 100,
+        }
+    }
+}
+
+// ============================================================================
+// Violations
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MoveViolation {
+    /// Requested speed above max_speed * speed_tolerance (the move was clamped)
+    Speed { speed: f32, max_allowed: f32 },
+    /// Commands arrived faster than real time allows (they were dropped)
+    TickBudget { dropped: u32 },
+}
+
+impl MoveViolation {
+    pub fn anomaly(&self) -> AnomalyType {
+        match self {
+            MoveViolation::Speed { speed, max_allowed } => {
+                AnomalyType::MovementSpeedViolation { speed: *speed, max_allowed: *max_allowed }
+            }
+            MoveViolation::TickBudget { dropped } => AnomalyType::MovementTimeViolation { dropped_commands: *dropped },
+        }
+    }
+
+    /// Mild overspeed stays a warning; 2x and beyond escalates quickly.
+    pub fn severity(&self) -> f32 {
+        match self {
+            MoveViolation::Speed { speed, max_allowed } => {
+                (0.5 + 0.4 * (speed / max_allowed - 1.0)).clamp(0.5, 0.95)
+            }
+            MoveViolation::TickBudget { dropped } => (0.5 + 0.02 * *dropped as f32).min(0.9),
+        }
+    }
+
+    pub fn context(&self) -> String {
+        match self {
+            MoveViolation::Speed { speed, max_allowed } => {
+                format!("Requested {:.1} units/s (max allowed {:.1})", speed, max_allowed)
+            }
+            MoveViolation::TickBudget { dropped } => {
+                format!("{} movement command(s) beyond the real-time budget", dropped)
+            }
+        }
+    }
+
+    /// Keep the worse of two violations pending in one cooldown window.
+    fn merge(self, other: MoveViolation) -> MoveViolation {
+        match (self, other) {
+            (MoveViolation::TickBudget { dropped: a }, MoveViolation::TickBudget { dropped: b }) => {
+                MoveViolation::TickBudget { dropped: a + b }
+            }
+            (a, b) if b.severity() > a.severity() => b,
+            (a, _) => a,
+        }
+    }
+}
+
+// ============================================================================
+// Per-player state
+// ============================================================================
+
+#[derive(Clone, Debug)]
+pub struct PlayerMovement {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// Last applied MoveCommand sequence (echoed in corrections)
+    pub last_sequence: u32,
+    /// Last applied client tick (lag compensation maps client ticks to server time)
+    pub last_client_tick: Option<u64>,
+    /// Sent MoveCommand (v26+): corrections are only sent to these clients
+    pub sends_commands: bool,
+    budget: f32,
+    budget_refilled_ms: u64,
+    correction_pending: bool,
+    last_correction_ms: u64,
+    pending_violation: Option<MoveViolation>,
+    last_report_ms: Option<u64>,
+}
+
+impl PlayerMovement {
+    fn new(position: Vec3, config: &MovementConfig, now_ms: u64) -> Self {
+        Self {
+            position,
+            velocity: Vec3::ZERO,
+            last_sequence: 0,
+            last_client_tick: None,
+            sends_commands: false,
+            budget: config.max_burst_ticks,
+            budget_refilled_ms: now_ms,
+            correction_pending: false,
+            last_correction_ms: 0,
+            pending_violation: None,
+            last_report_ms: None,
+        }
+    }
+
+    fn refill(&mut self, config: &MovementConfig, now_ms: u64) {
+        let elapsed = now_ms.saturating_sub(self.budget_refilled_ms);
+        self.budget = (self.budget + elapsed as f32 * MOVE_TICK_HZ as f32 / 1000.0).min(config.max_burst_ticks);
+        self.budget_refilled_ms = now_ms.max(self.budget_refilled_ms);
+    }
+
+    fn flag(&mut self, violation: MoveViolation) {
+        self.pending_violation = Some(match self.pending_violation.take() {
+            Some(pending) => pending.merge(violation),
+            None => violation,
+        });
+    }
+}
+
+/// What applying one movement message did
+#[derive(Clone, Debug, PartialEq)]
+pub enum MoveResult {
+    Applied { position: Vec3, velocity: Vec3 },
+    /// Duplicate or out-of-order command; nothing changed
+    Stale,
+    /// Over the tick budget; nothing changed, a correction is pending
+    Dropped,
+}
+
+// ============================================================================
+// Authority
+// ============================================================================
+
+#[derive(Resource, Default)]
+pub struct MovementAuthority {
+    pub config: MovementConfig,
+    players: HashMap<u64, PlayerMovement>,
+}
+
+impl MovementAuthority {
+    pub fn new(config: MovementConfig) -> Self {
+        Self { config, players: HashMap::new() }
+    }
+
+    pub fn player(&self, player_id: u64) -> Option<&PlayerMovement> {
+        self.players.get(&player_id)
+    }
+
+    pub fn remove_player(&mut self, player_id: u64) {
+        self.players.remove(&player_id);
+    }
+
+    /// Adopt `world_position` if another system moved the player (spawn, teleport, knockback).
+    pub fn sync_from_world(&mut self, player_id: u64, world_position: Vec3, now_ms: u64) {
+        let config = &self.config;
+        let state = self
+            .players
+            .entry(player_id)
+            .or_insert_with(|| PlayerMovement::new(world_position, config, now_ms));
+        if state.position.distance(world_position) > config.correction_epsilon {
+            state.position = world_position;
+            state.correction_pending = true;
+        }
+    }
+
+    /// Apply a MoveCommand or legacy Move. Other messages return None.
+    /// The player must have been registered with `sync_from_world` first.
+    pub fn handle_message(
+        &mut self,
+        player_id: u64,
+        message: &ClientMessage,
+        grid: Option<&HierarchicalGrid>,
+        now_ms: u64,
+    ) -> Option<MoveResult> {
+        let config = &self.config;
+        let state = self.players.get_mut(&player_id)?;
+        let (sequence, tick, velocity) = match message {
+            ClientMessage::MoveCommand { sequence, tick, velocity } => {
+                state.sends_commands = true;
+                (*sequence, *tick, from_wire(velocity))
+            }
+            // A displacement over one movement tick, not a velocity.
+            ClientMessage::Move { delta } => (
+                state.last_sequence.wrapping_add(1),
+                state.last_client_tick.map_or(0, |t| t + 1),
+                from_wire(delta) / MOVE_DT,
+            ),
+            _ => return None,
+        };
+
+        let in_order = sequence > state.last_sequence && state.last_client_tick.is_none_or(|t| tick > t);
+        if !in_order {
+            return Some(MoveResult::Stale);
+        }
+
+        state.refill(config, now_ms);
+        if state.budget < 1.0 {
+            state.flag(MoveViolation::TickBudget { dropped: 1 });
+            state.correction_pending = true;
+            return Some(MoveResult::Dropped);
+        }
+        state.budget -= 1.0;
+        state.last_sequence = sequence;
+        state.last_client_tick = Some(tick);
+
+        let requested = if velocity.is_finite() { velocity } else { Vec3::ZERO };
+        let max_allowed = config.max_speed * config.speed_tolerance;
+        let speed = requested.length();
+        let velocity = if speed > max_allowed {
+            state.flag(MoveViolation::Speed { speed, max_allowed: config.max_speed });
+            requested * (config.max_speed / speed)
+        } else {
+            requested
+        };
+
+        let predicted = state.position + requested * MOVE_DT;
+        let position = match grid {
+            Some(grid) => resolve_motion(grid, state.position, velocity * MOVE_DT, config.player_radius),
+            None => state.position + velocity * MOVE_DT,
+        };
+        let velocity = (position - state.position) / MOVE_DT;
+
+        if position.distance(predicted) > config.correction_epsilon {
+            state.correction_pending = true;
+        }
+        state.position = position;
+        state.velocity = velocity;
+        Some(MoveResult::Applied { position, velocity })
+    }
+
+    /// Pending corrections for v26+ clients, at most one per min_correction_interval_ms each.
+    pub fn take_corrections(&mut self, server_tick: u64, now_ms: u64) -> Vec<(u64, ServerMessage)> {
+        let interval = self.config.min_correction_interval_ms;
+        let mut out = Vec::new();
+        for (&player_id, state) in &mut self.players {
+            if !state.correction_pending || !state.sends_commands {
+                state.correction_pending &= state.sends_commands;
+                continue;
+            }
+            if now_ms.saturating_sub(state.last_correction_ms) < interval {
+                continue;
+            }
+            state.correction_pending = false;
+            state.last_correction_ms = now_ms;
+            out.push((player_id, ServerMessage::MoveCorrection {
+                sequence: state.last_sequence,
+                server_tick,
+                position: to_wire(state.position),
+                velocity: to_wire(state.velocity),
+            }));
+        }
+        out
+    }
+
+    /// Violations due for reporting, at most one per REPORT_COOLDOWN_MS per player.
+    pub fn take_violations(&mut self, now_ms: u64) -> Vec<(u64, MoveViolation)> {
+        let mut out = Vec::new();
+        for (&player_id, state) in &mut self.players {
+            let cooling = state.last_report_ms.is_some_and(|t| now_ms.saturating_sub(t) < REPORT_COOLDOWN_MS);
+            if cooling {
+                continue;
+            }
+            if let Some(violation) = state.pending_violation.take() {
+                state.last_report_ms = Some(now_ms);
+                out.push((player_id, violation));
+            }
+        }
+        out
+    }
+}
+
+/// Move a sphere by `motion`, stopping at blockers and sliding along the face hit.
+pub fn resolve_motion(grid: &HierarchicalGrid, from: Vec3, motion: Vec3, radius: f32) -> Vec3 {
+    let mut position = from;
+    let mut remaining = motion;
+    for _ in 0..MAX_SLIDES {
+        let length = remaining.length();
+        if length <= f32::EPSILON {
+            break;
+        }
+        let Some(hit) = grid.sweep_sphere(position, position + remaining, radius) else {
+            position += remaining;
+            break;
+        };
+        position += remaining / length * (hit.t * length - CONTACT_SKIN).max(0.0);
+        let left = remaining * (1.0 - hit.t);
+        remaining = left - hit.normal * left.dot(hit.normal);
+    }
+    position
+}
+
+fn from_wire(v: &Vec3Ser) -> Vec3 {
+    Vec3::new(v.x, v.y, v.z)
+}
+
+fn to_wire(v: Vec3) -> Vec3Ser {
+    Vec3Ser { x: v.x, y: v.y, z: v.z }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hierarchical_grid::HierarchicalGridConfig;
+
+    fn command(sequence: u32, x: f32) -> ClientMessage {
+        ClientMessage::MoveCommand { sequence, tick: 100 + sequence as u64, velocity: Vec3Ser { x, y: 0.0, z: 0.0 } }
+    }
+
+    fn authority() -> MovementAuthority {
+        let mut authority = MovementAuthority::default();
+        authority.sync_from_world(1, Vec3::ZERO, 0);
+        authority
+    }
+
+    #[test]
+    fn overspeed_is_clamped_corrected_and_reported_once() {
+        let mut authority = authority();
+        for seq in 1..=10 {
+            authority.handle_message(1, &command(seq, 90.0), None, 1_000).unwrap();
+        }
+        let state = authority.player(1).unwrap();
+        assert!((state.position.x - 10.0 * 9.0 * MOVE_DT).abs() < 1e-4);
+
+        let corrections = authority.take_corrections(7, 1_000);
+        assert!(matches!(corrections.as_slice(), [(1, ServerMessage::MoveCorrection { sequence: 10, .. })]));
+
+        let violations = authority.take_violations(1_000);
+        assert!(matches!(violations.as_slice(), [(1, MoveViolation::Speed { .. })]));
+        assert!(violations[0].1.severity() > 0.9);
+
+        authority.handle_message(1, &command(11, 90.0), None, 1_500).unwrap();
+        assert!(authority.take_violations(1_500).is_empty(), "cooldown");
+        assert_eq!(authority.take_violations(2_000).len(), 1);
+    }
+
+    #[test]
+    fn commands_beyond_real_time_are_dropped() {
+        let mut authority = authority();
+        let burst = authority.config.max_burst_ticks as u32;
+        let results: Vec<_> = (1..=burst + 5)
+            .map(|seq| authority.handle_message(1, &command(seq, 9.0), None, 0).unwrap())
+            .collect();
+        assert!(results[..burst as usize].iter().all(|r| matches!(r, MoveResult::Applied { .. })));
+        assert!(results[burst as usize..].iter().all(|r| *r == MoveResult::Dropped));
+        assert!(matches!(
+            authority.take_violations(0).as_slice(),
+            [(1, MoveViolation::TickBudget { dropped: 5 })]
+        ));
+
+        // Real-time pacing (one command per 1/MOVE_TICK_HZ s) is never dropped.
+        let first = burst + 6;
+        let hz = MOVE_TICK_HZ as u64;
+        let paced = (0..hz)
+            .filter(|&i| {
+                let now = 1_000 + (i * 1_000).div_ceil(hz);
+                matches!(authority.handle_message(1, &command(first + i as u32, 9.0), None, now), Some(MoveResult::Applied { .. }))
+            })
+            .count();
+        assert_eq!(paced, MOVE_TICK_HZ as usize);
+
+        assert_eq!(authority.handle_message(1, &command(3, 9.0), None, 5_000), Some(MoveResult::Stale));
+    }
+
+    #[test]
+    fn blockers_stop_and_slide() {
+        let mut grid = HierarchicalGrid::new(HierarchicalGridConfig::default());
+        // Wall across +x at x = 2..3
+        grid.insert_blocker(Vec3::new(2.0, -5.0, -50.0), Vec3::new(3.0, 5.0, 50.0));
+
+        let stopped = resolve_motion(&grid, Vec3::ZERO, Vec3::new(5.0, 0.0, 0.0), 0.5);
+        assert!((stopped.x - 1.5).abs() < 0.01, "{:?}", stopped);
+
+        let slid = resolve_motion(&grid, Vec3::ZERO, Vec3::new(5.0, 0.0, 4.0), 0.5);
+        assert!((slid.x - 1.5).abs() < 0.01 && (slid.z - 4.0).abs() < 0.01, "{:?}", slid);
+
+        // Walking away from the wall is unaffected.
+        let away = resolve_motion(&grid, Vec3::new(1.5, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0), 0.5);
+        assert_eq!(away, Vec3::new(0.5, 0.0, 0.0));
+
+        let mut authority = authority();
+        for seq in 1..=30 {
+            authority.handle_message(1, &command(seq, 9.0), Some(&grid), 1_000).unwrap();
+        }
+        let state = authority.player(1).unwrap();
+        assert!(state.position.x < 1.5 && state.position.x > 1.49);
+        assert!(authority.take_corrections(0, 1_000).len() == 1);
+    }
+
+    #[test]
+    fn legacy_move_gets_snapshots_not_corrections() {
+        let mut authority = authority();
+        let legacy = |x: f32| ClientMessage::Move { delta: Vec3Ser { x, y: 0.0, z: 0.0 } };
+
+        // A full-speed step is one tick's displacement and moves exactly that far.
+        let step = 9.0 * MOVE_DT;
+        authority.handle_message(1, &legacy(step), None, 0).unwrap();
+        authority.handle_message(1, &legacy(step), None, 0).unwrap();
+        let state = authority.player(1).unwrap();
+        assert_eq!(state.last_sequence, 2);
+        assert!((state.position.x - 2.0 * step).abs() < 1e-5);
+        assert!(authority.take_violations(0).is_empty());
+
+        // Ten ticks' worth in one step is clamped to one and reported.
+        authority.handle_message(1, &legacy(10.0 * step), None, 0).unwrap();
+        assert!((authority.player(1).unwrap().position.x - 3.0 * step).abs() < 1e-5);
+        assert!(authority.take_corrections(0, 1_000).is_empty());
+        assert_eq!(authority.take_violations(0).len(), 1);
+    }
+}
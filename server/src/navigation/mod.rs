@@ -0,0 +1,20 @@
+/*!
+ * server/src/navigation/mod.rs
+ *
+ * Server NPC navigation: layered nav grid built from zone geometry,
+ * hierarchical A*, a shared path cache and path-following steering,
+ * run for NavAgent entities by NavigationPlugin.
+ * AG-SML v1.0 | TOLC 8 + PATSAGi
+ * Thunder locked in. Yoi ⚡
+ */
+
+pub mod navmesh;
+pub mod npc;
+pub mod path_cache;
+pub mod steering;
+pub mod zones;
+
+pub use navmesh::{NavCell, NavChunkKey, NavConfig, NavMesh, WalkableSurface, ZoneGeometry};
+pub use npc::{NavAgent, NavigationPlugin, NpcNavigation};
+pub use path_cache::{CachedPath, PathCache, PathCacheStats};
+pub use steering::{nearest_within, PathFollower, SteeringConfig};
@@ -0,0 +1,759 @@
+/*!
+ * server/src/navigation/navmesh.rs
+ *
+ * Layered navigation grid for server NPCs. Zone geometry (walkable surfaces +
+ * obstacles) is voxelised per nav chunk into cell columns; each column holds
+ * one layer per walkable floor height, so bridges, balconies and stairs
+ * stack instead of colliding.
+ *
+ * Hierarchical A*: chunk borders are scanned for portals (runs of crossable
+ * cells), portals inside a chunk are linked by local A*, and a query runs A*
+ * over that abstract graph before refining each leg inside its chunk.
+ * Changing geometry only marks chunks dirty; rebuild_dirty() rebuilds those
+ * chunks plus their neighbours' portals and returns what changed so the
+ * path cache can evict.
+ *
+ * AG-SML v1.0 | TOLC 8 | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use bevy::prelude::Vec3;
+
+use crate::hierarchical_grid::{Blocker, ChunkCoord, HierarchicalGrid};
+
+/// Floor heights closer than this are the same layer
+const LAYER_MERGE_EPSILON: f32 = 0.01;
+
+/// How far (in cells) `locate` searches around a point that is off the mesh
+const LOCATE_SEARCH_CELLS: i32 = 2;
+
+#[derive(Clone, Debug)]
+pub struct NavConfig {
+    /// Nav chunk edge length on XZ (world units); a multiple of cell_size
+    pub chunk_size: f32,
+    pub cell_size: f32,
+    pub agent_radius: f32,
+    pub agent_height: f32,
+    /// Largest floor height change an agent can step between neighbouring cells
+    pub max_step: f32,
+}
+
+impl Default for NavConfig {
+    fn default() -> Self {
+        Self { chunk_size: 32.0, cell_size: 1.0, agent_radius: 0.5, agent_height: 2.0, max_step: 0.6 }
+    }
+}
+
+impl NavConfig {
+    fn cells_per_chunk(&self) -> i32 {
+        ((self.chunk_size / self.cell_size).round() as i32).max(1)
+    }
+}
+
+// ============================================================================
+// Geometry
+// ============================================================================
+
+/// Solid slab whose top face (max.y) can be walked on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WalkableSurface {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+/// Navigation-relevant geometry of one zone. Obstacles use the same Blocker
+/// volumes as movement collision (see `register_blockers`).
+#[derive(Clone, Debug, Default)]
+pub struct ZoneGeometry {
+    pub zone_id: u64,
+    pub surfaces: Vec<WalkableSurface>,
+    pub obstacles: Vec<Blocker>,
+}
+
+impl ZoneGeometry {
+    /// Mirror the obstacles into the movement grid so players collide with
+    /// exactly what NPCs path around.
+    pub fn register_blockers(&self, grid: &mut HierarchicalGrid) {
+        for obstacle in &self.obstacles {
+            grid.insert_blocker(obstacle.min, obstacle.max);
+        }
+    }
+
+    fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let boxes = self.surfaces.iter().map(|s| (s.min, s.max)).chain(self.obstacles.iter().map(|b| (b.min, b.max)));
+        boxes.reduce(|(lo, hi), (min, max)| (lo.min(min), hi.max(max)))
+    }
+}
+
+// ============================================================================
+// Graph types
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NavChunkKey {
+    pub x: i32,
+    pub z: i32,
+}
+
+impl NavChunkKey {
+    fn neighbours(self) -> [NavChunkKey; 4] {
+        [
+            NavChunkKey { x: self.x + 1, z: self.z },
+            NavChunkKey { x: self.x - 1, z: self.z },
+            NavChunkKey { x: self.x, z: self.z + 1 },
+            NavChunkKey { x: self.x, z: self.z - 1 },
+        ]
+    }
+}
+
+/// One walkable layer of one cell column
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NavCell {
+    pub x: i32,
+    pub z: i32,
+    pub layer: u8,
+}
+
+#[derive(Default)]
+struct NavChunk {
+    /// (cell x, cell z) -> ascending walkable floor heights
+    columns: HashMap<(i32, i32), Vec<f32>>,
+    /// Portal cells in this chunk (abstract graph nodes)
+    portals: Vec<NavCell>,
+}
+
+#[derive(PartialEq)]
+struct Open<N> {
+    f: f32,
+    g: f32,
+    node: N,
+}
+
+impl<N: PartialEq> Eq for Open<N> {}
+
+impl<N: PartialEq> Ord for Open<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.f.partial_cmp(&self.f).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl<N: PartialEq> PartialOrd for Open<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum AbstractNode {
+    Portal(NavCell),
+    Goal,
+}
+
+// ============================================================================
+// NavMesh
+// ============================================================================
+
+pub struct NavMesh {
+    pub config: NavConfig,
+    zones: HashMap<u64, ZoneGeometry>,
+    chunks: HashMap<NavChunkKey, NavChunk>,
+    /// Abstract graph: portal -> (portal, cost)
+    edges: HashMap<NavCell, Vec<(NavCell, f32)>>,
+    dirty: HashSet<NavChunkKey>,
+}
+
+impl NavMesh {
+    pub fn new(config: NavConfig) -> Self {
+        Self { config, zones: HashMap::new(), chunks: HashMap::new(), edges: HashMap::new(), dirty: HashSet::new() }
+    }
+
+    /// Add or replace a zone's geometry; affected chunks rebuild on the next rebuild_dirty().
+    pub fn set_zone_geometry(&mut self, geometry: ZoneGeometry) {
+        if let Some((min, max)) = self.zones.get(&geometry.zone_id).and_then(ZoneGeometry::bounds) {
+            self.invalidate_region(min, max);
+        }
+        if let Some((min, max)) = geometry.bounds() {
+            self.invalidate_region(min, max);
+        }
+        self.zones.insert(geometry.zone_id, geometry);
+    }
+
+    pub fn remove_zone(&mut self, zone_id: u64) {
+        if let Some((min, max)) = self.zones.remove(&zone_id).as_ref().and_then(ZoneGeometry::bounds) {
+            self.invalidate_region(min, max);
+        }
+    }
+
+    /// Mark every nav chunk overlapping `min..max` (XZ) for rebuild.
+    pub fn invalidate_region(&mut self, min: Vec3, max: Vec3) {
+        let pad = Vec3::splat(self.config.agent_radius + self.config.cell_size);
+        let lo = self.chunk_key_at(min - pad);
+        let hi = self.chunk_key_at(max + pad);
+        for x in lo.x..=hi.x {
+            for z in lo.z..=hi.z {
+                self.dirty.insert(NavChunkKey { x, z });
+            }
+        }
+    }
+
+    /// Mark world chunks (ChunkManager / HierarchicalGrid coordinates of size
+    /// `chunk_size`) that changed, e.g. from ChunkManager::get_dirty_chunks().
+    pub fn invalidate_world_chunks(&mut self, coords: impl IntoIterator<Item = ChunkCoord>, chunk_size: f32) {
+        for c in coords {
+            let min = Vec3::new(c.x as f32, c.y as f32, c.z as f32) * chunk_size;
+            self.invalidate_region(min, min + Vec3::splat(chunk_size));
+        }
+    }
+
+    pub fn has_dirty_chunks(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    /// Rebuild dirty chunks and the portals around them. Returns every chunk
+    /// whose cells or portals changed (for path cache invalidation).
+    pub fn rebuild_dirty(&mut self) -> Vec<NavChunkKey> {
+        if self.dirty.is_empty() {
+            return Vec::new();
+        }
+        let dirty: Vec<NavChunkKey> = self.dirty.drain().collect();
+        let mut affected: Vec<NavChunkKey> =
+            dirty.iter().flat_map(|k| std::iter::once(*k).chain(k.neighbours())).collect();
+        affected.sort_unstable();
+        affected.dedup();
+
+        for &key in &affected {
+            let old = self.chunks.get_mut(&key).map(|c| std::mem::take(&mut c.portals)).unwrap_or_default();
+            for portal in old {
+                self.edges.remove(&portal);
+            }
+        }
+        for &key in &dirty {
+            let columns = self.build_columns(key);
+            if columns.is_empty() {
+                self.chunks.remove(&key);
+            } else {
+                self.chunks.insert(key, NavChunk { columns, portals: Vec::new() });
+            }
+        }
+        for &key in &affected {
+            if !self.chunks.contains_key(&key) {
+                continue;
+            }
+            let mut portals = Vec::new();
+            for neighbour in key.neighbours() {
+                for (here, there) in self.border_portals(key, neighbour) {
+                    let cost = self.step_cost(here, there);
+                    self.edges.entry(here).or_default().push((there, cost));
+                    portals.push(here);
+                }
+            }
+            portals.sort_unstable();
+            portals.dedup();
+            for (i, &a) in portals.iter().enumerate() {
+                for &b in &portals[i + 1..] {
+                    if let Some((_, cost)) = self.local_path(a, b, key) {
+                        self.edges.entry(a).or_default().push((b, cost));
+                        self.edges.entry(b).or_default().push((a, cost));
+                    }
+                }
+            }
+            if let Some(chunk) = self.chunks.get_mut(&key) {
+                chunk.portals = portals;
+            }
+        }
+        affected
+    }
+
+    // ------------------------------------------------------------------------
+    // Queries
+    // ------------------------------------------------------------------------
+
+    /// Nearest walkable cell layer at or just below `pos`.
+    pub fn locate(&self, pos: Vec3) -> Option<NavCell> {
+        let (cx, cz) = self.cell_xz(pos);
+        let mut best: Option<(f32, NavCell)> = None;
+        for r in 0..=LOCATE_SEARCH_CELLS {
+            for x in cx - r..=cx + r {
+                for z in cz - r..=cz + r {
+                    if (x - cx).abs() != r && (z - cz).abs() != r {
+                        continue; // ring only
+                    }
+                    let Some(heights) = self.column(x, z) else { continue };
+                    for (layer, &h) in heights.iter().enumerate() {
+                        if h > pos.y + self.config.max_step {
+                            continue;
+                        }
+                        let d = (pos - self.cell_center(x, z, h)).length();
+                        if best.is_none_or(|(bd, _)| d < bd) {
+                            best = Some((d, NavCell { x, z, layer: layer as u8 }));
+                        }
+                    }
+                }
+            }
+            if best.is_some() {
+                break;
+            }
+        }
+        best.map(|(_, cell)| cell)
+    }
+
+    pub fn cell_position(&self, cell: NavCell) -> Option<Vec3> {
+        let h = *self.column(cell.x, cell.z)?.get(cell.layer as usize)?;
+        Some(self.cell_center(cell.x, cell.z, h))
+    }
+
+    pub fn chunk_of(&self, cell: NavCell) -> NavChunkKey {
+        let n = self.config.cells_per_chunk();
+        NavChunkKey { x: cell.x.div_euclid(n), z: cell.z.div_euclid(n) }
+    }
+
+    /// Hierarchical A* between two cells; returns the cell sequence (inclusive).
+    pub fn find_cell_path(&self, start: NavCell, goal: NavCell) -> Option<Vec<NavCell>> {
+        let start_chunk = self.chunk_of(start);
+        let goal_chunk = self.chunk_of(goal);
+        if start_chunk == goal_chunk {
+            if let Some((cells, _)) = self.local_path(start, goal, start_chunk) {
+                return Some(cells);
+            }
+        }
+
+        let from_start = self.local_costs(start, start_chunk);
+        let to_goal = self.local_costs(goal, goal_chunk);
+        let goal_pos = self.cell_position(goal)?;
+        let h = |cell: NavCell| self.cell_position(cell).map_or(0.0, |p| p.distance(goal_pos));
+
+        let mut open = BinaryHeap::new();
+        let mut best: HashMap<AbstractNode, f32> = HashMap::new();
+        let mut came_from: HashMap<AbstractNode, AbstractNode> = HashMap::new();
+        for &portal in self.chunks.get(&start_chunk).map_or(&[][..], |c| c.portals.as_slice()) {
+            if let Some(&g) = from_start.get(&portal) {
+                best.insert(AbstractNode::Portal(portal), g);
+                open.push(Open { f: g + h(portal), g, node: AbstractNode::Portal(portal) });
+            }
+        }
+
+        let mut reached_goal = false;
+        while let Some(Open { g, node, .. }) = open.pop() {
+            if best.get(&node).is_some_and(|&b| g > b) {
+                continue;
+            }
+            let AbstractNode::Portal(cell) = node else {
+                reached_goal = true;
+                break;
+            };
+            if let Some(&last_leg) = to_goal.get(&cell) {
+                let total = g + last_leg;
+                if best.get(&AbstractNode::Goal).is_none_or(|&b| total < b) {
+                    best.insert(AbstractNode::Goal, total);
+                    came_from.insert(AbstractNode::Goal, node);
+                    open.push(Open { f: total, g: total, node: AbstractNode::Goal });
+                }
+            }
+            for &(next, cost) in self.edges.get(&cell).map_or(&[][..], |e| e.as_slice()) {
+                let next_g = g + cost;
+                let next_node = AbstractNode::Portal(next);
+                if best.get(&next_node).is_none_or(|&b| next_g < b) {
+                    best.insert(next_node, next_g);
+                    came_from.insert(next_node, node);
+                    open.push(Open { f: next_g + h(next), g: next_g, node: next_node });
+                }
+            }
+        }
+        if !reached_goal {
+            return None;
+        }
+
+        let mut portals = Vec::new();
+        let mut node = AbstractNode::Goal;
+        while let Some(&prev) = came_from.get(&node) {
+            if let AbstractNode::Portal(cell) = prev {
+                portals.push(cell);
+            }
+            node = prev;
+        }
+        portals.reverse();
+
+        // Refine: each leg is either a border step or a walk inside one chunk.
+        let mut cells = vec![start];
+        for waypoint in portals.into_iter().chain(std::iter::once(goal)) {
+            let from = *cells.last().expect("path starts with start");
+            if from == waypoint {
+                continue;
+            }
+            if self.chunk_of(from) != self.chunk_of(waypoint) {
+                cells.push(waypoint);
+                continue;
+            }
+            let (leg, _) = self.local_path(from, waypoint, self.chunk_of(from))?;
+            cells.extend(leg.into_iter().skip(1));
+        }
+        Some(cells)
+    }
+
+    /// World-space waypoints from `start` to `goal`, string-pulled. The first
+    /// waypoint is the first corner to walk towards; the last is on the goal cell.
+    pub fn find_path(&self, start: Vec3, goal: Vec3) -> Option<Vec<Vec3>> {
+        let cells = self.find_cell_path(self.locate(start)?, self.locate(goal)?)?;
+        Some(self.smooth(&cells))
+    }
+
+    /// Chunks a cell path passes through
+    pub fn chunks_on_path(&self, cells: &[NavCell]) -> Vec<NavChunkKey> {
+        let mut chunks: Vec<NavChunkKey> = cells.iter().map(|&c| self.chunk_of(c)).collect();
+        chunks.sort_unstable();
+        chunks.dedup();
+        chunks
+    }
+
+    /// Drop intermediate cells wherever a straight walk stays on the mesh.
+    pub fn smooth(&self, cells: &[NavCell]) -> Vec<Vec3> {
+        let points: Vec<Vec3> = cells.iter().filter_map(|&c| self.cell_position(c)).collect();
+        if points.len() <= 2 {
+            return points.into_iter().skip(1).collect();
+        }
+        let mut out = Vec::new();
+        let mut anchor = 0;
+        while anchor < points.len() - 1 {
+            let mut reach = anchor + 1;
+            while reach + 1 < points.len() && self.walkable_segment(points[anchor], points[reach + 1]) {
+                reach += 1;
+            }
+            out.push(points[reach]);
+            anchor = reach;
+        }
+        out
+    }
+
+    /// Whether an agent can walk straight from `a` to `b`: the centre line stays
+    /// on the mesh and keeps `agent_radius` clear of every obstacle at its height.
+    /// (Cell columns only guarantee that clearance at cell centres.)
+    fn walkable_segment(&self, a: Vec3, b: Vec3) -> bool {
+        let cfg = &self.config;
+        let (lo, hi) = (a.min(b) - Vec3::splat(cfg.agent_radius), a.max(b) + Vec3::splat(cfg.agent_radius));
+        let obstacles: Vec<&Blocker> = self
+            .zones
+            .values()
+            .flat_map(|z| &z.obstacles)
+            .filter(|o| o.min.x <= hi.x && o.max.x >= lo.x && o.min.z <= hi.z && o.max.z >= lo.z)
+            .collect();
+
+        let step = cfg.cell_size * 0.25;
+        let samples = ((b - a).length() / step).ceil().max(1.0) as usize;
+        (0..=samples).all(|i| {
+            let p = a.lerp(b, i as f32 / samples as f32);
+            let (x, z) = self.cell_xz(p);
+            let on_mesh = self
+                .column(x, z)
+                .is_some_and(|heights| heights.iter().any(|&h| (h - p.y).abs() <= cfg.max_step));
+            on_mesh
+                && obstacles.iter().all(|o| {
+                    let beside = o.min.y >= p.y + cfg.agent_height || o.max.y <= p.y + LAYER_MERGE_EPSILON;
+                    let dx = (o.min.x - p.x).max(p.x - o.max.x).max(0.0);
+                    let dz = (o.min.z - p.z).max(p.z - o.max.z).max(0.0);
+                    beside || dx * dx + dz * dz >= cfg.agent_radius * cfg.agent_radius - LAYER_MERGE_EPSILON
+                })
+        })
+    }
+
+    // ------------------------------------------------------------------------
+    // Cells
+    // ------------------------------------------------------------------------
+
+    fn chunk_key_at(&self, pos: Vec3) -> NavChunkKey {
+        NavChunkKey {
+            x: (pos.x / self.config.chunk_size).floor() as i32,
+            z: (pos.z / self.config.chunk_size).floor() as i32,
+        }
+    }
+
+    fn cell_xz(&self, pos: Vec3) -> (i32, i32) {
+        ((pos.x / self.config.cell_size).floor() as i32, (pos.z / self.config.cell_size).floor() as i32)
+    }
+
+    fn cell_center(&self, x: i32, z: i32, height: f32) -> Vec3 {
+        Vec3::new((x as f32 + 0.5) * self.config.cell_size, height, (z as f32 + 0.5) * self.config.cell_size)
+    }
+
+    fn column(&self, x: i32, z: i32) -> Option<&Vec<f32>> {
+        let n = self.config.cells_per_chunk();
+        let key = NavChunkKey { x: x.div_euclid(n), z: z.div_euclid(n) };
+        self.chunks.get(&key)?.columns.get(&(x, z))
+    }
+
+    fn height(&self, cell: NavCell) -> Option<f32> {
+        self.column(cell.x, cell.z)?.get(cell.layer as usize).copied()
+    }
+
+    /// Voxelise one chunk's columns from every zone's geometry.
+    fn build_columns(&self, key: NavChunkKey) -> HashMap<(i32, i32), Vec<f32>> {
+        let cfg = &self.config;
+        let n = cfg.cells_per_chunk();
+        let r = cfg.agent_radius;
+        let chunk_min = Vec3::new(key.x as f32 * cfg.chunk_size, f32::MIN, key.z as f32 * cfg.chunk_size);
+        let chunk_max = chunk_min + Vec3::new(cfg.chunk_size, 0.0, cfg.chunk_size);
+        let overlaps_chunk = |min: Vec3, max: Vec3| {
+            max.x + r >= chunk_min.x && min.x - r <= chunk_max.x && max.z + r >= chunk_min.z && min.z - r <= chunk_max.z
+        };
+
+        let surfaces: Vec<&WalkableSurface> =
+            self.zones.values().flat_map(|z| &z.surfaces).filter(|s| overlaps_chunk(s.min, s.max)).collect();
+        let obstacles: Vec<&Blocker> =
+            self.zones.values().flat_map(|z| &z.obstacles).filter(|b| overlaps_chunk(b.min, b.max)).collect();
+
+        let mut columns = HashMap::new();
+        for x in key.x * n..(key.x + 1) * n {
+            for z in key.z * n..(key.z + 1) * n {
+                let c = self.cell_center(x, z, 0.0);
+                let footprint = |min: Vec3, max: Vec3| {
+                    min.x < c.x + r && max.x > c.x - r && min.z < c.z + r && max.z > c.z - r
+                };
+                let mut heights: Vec<f32> = Vec::new();
+                for s in &surfaces {
+                    if c.x < s.min.x || c.x > s.max.x || c.z < s.min.z || c.z > s.max.z {
+                        continue;
+                    }
+                    let floor = s.max.y;
+                    let head = floor + cfg.agent_height;
+                    let blocked_by_obstacle = obstacles
+                        .iter()
+                        .any(|b| footprint(b.min, b.max) && b.min.y < head && b.max.y > floor + LAYER_MERGE_EPSILON);
+                    // Another slab in the headroom blocks, unless it is a step up.
+                    let blocked_by_slab = surfaces.iter().any(|o| {
+                        footprint(o.min, o.max) && o.min.y < head && o.max.y > floor + cfg.max_step
+                    });
+                    if !blocked_by_obstacle && !blocked_by_slab {
+                        heights.push(floor);
+                    }
+                }
+                heights.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                heights.dedup_by(|a, b| (*a - *b).abs() < LAYER_MERGE_EPSILON);
+                if !heights.is_empty() {
+                    columns.insert((x, z), heights);
+                }
+            }
+        }
+        columns
+    }
+
+    fn step_cost(&self, a: NavCell, b: NavCell) -> f32 {
+        let (ha, hb) = (self.height(a).unwrap_or(0.0), self.height(b).unwrap_or(0.0));
+        let flat = if a.x != b.x && a.z != b.z { std::f32::consts::SQRT_2 } else { 1.0 };
+        flat * self.config.cell_size + (ha - hb).abs()
+    }
+
+    /// Layers of column (x, z) an agent standing at `height` can step onto.
+    fn reachable_layers(&self, x: i32, z: i32, height: f32) -> impl Iterator<Item = NavCell> + '_ {
+        let max_step = self.config.max_step;
+        self.column(x, z).into_iter().flat_map(move |heights| {
+            heights
+                .iter()
+                .enumerate()
+                .filter(move |(_, &h)| (h - height).abs() <= max_step)
+                .map(move |(layer, _)| NavCell { x, z, layer: layer as u8 })
+        })
+    }
+
+    fn neighbours(&self, cell: NavCell) -> Vec<NavCell> {
+        let Some(h) = self.height(cell) else { return Vec::new() };
+        let mut out = Vec::with_capacity(8);
+        for (dx, dz) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            out.extend(self.reachable_layers(cell.x + dx, cell.z + dz, h));
+        }
+        // Diagonals only when both orthogonal cells are open (no corner cutting).
+        for (dx, dz) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+            let side_a = self.reachable_layers(cell.x + dx, cell.z, h).next().is_some();
+            let side_b = self.reachable_layers(cell.x, cell.z + dz, h).next().is_some();
+            if side_a && side_b {
+                out.extend(self.reachable_layers(cell.x + dx, cell.z + dz, h));
+            }
+        }
+        out
+    }
+
+    /// Crossable cell pairs on the border between two chunks, one portal per
+    /// contiguous run: (cell in `here`, cell in `there`).
+    fn border_portals(&self, here: NavChunkKey, there: NavChunkKey) -> Vec<(NavCell, NavCell)> {
+        if !self.chunks.contains_key(&there) {
+            return Vec::new();
+        }
+        let n = self.config.cells_per_chunk();
+        let (dx, dz) = (there.x - here.x, there.z - here.z);
+        let crossings: Vec<(NavCell, NavCell)> = (0..n)
+            .flat_map(|i| {
+                let (x, z) = match (dx, dz) {
+                    (1, 0) => ((here.x + 1) * n - 1, here.z * n + i),
+                    (-1, 0) => (here.x * n, here.z * n + i),
+                    (0, 1) => (here.x * n + i, (here.z + 1) * n - 1),
+                    _ => (here.x * n + i, here.z * n),
+                };
+                let heights = self.column(x, z).cloned().unwrap_or_default();
+                heights.into_iter().enumerate().flat_map(move |(layer, h)| {
+                    let a = NavCell { x, z, layer: layer as u8 };
+                    self.reachable_layers(x + dx, z + dz, h).map(move |b| (a, b))
+                })
+            })
+            .collect();
+
+        // Group by layer pair continuity along the border; portal = middle of each run.
+        let along = |c: NavCell| if dx != 0 { c.z } else { c.x };
+        let mut runs: Vec<Vec<(NavCell, NavCell)>> = Vec::new();
+        for pair in crossings {
+            let joined = runs.iter_mut().find(|run| {
+                let &(a, b) = run.last().expect("runs are never empty");
+                along(pair.0) == along(a) + 1
+                    && (self.height(pair.0).unwrap_or(0.0) - self.height(a).unwrap_or(0.0)).abs() <= self.config.max_step
+                    && (self.height(pair.1).unwrap_or(0.0) - self.height(b).unwrap_or(0.0)).abs() <= self.config.max_step
+            });
+            match joined {
+                Some(run) => run.push(pair),
+                None => runs.push(vec![pair]),
+            }
+        }
+        runs.into_iter().map(|run| run[run.len() / 2]).collect()
+    }
+
+    /// A* restricted to one chunk.
+    fn local_path(&self, start: NavCell, goal: NavCell, chunk: NavChunkKey) -> Option<(Vec<NavCell>, f32)> {
+        let goal_pos = self.cell_position(goal)?;
+        let h = |c: NavCell| self.cell_position(c).map_or(0.0, |p| p.distance(goal_pos));
+        let mut open = BinaryHeap::new();
+        let mut g_score: HashMap<NavCell, f32> = HashMap::from([(start, 0.0)]);
+        let mut came_from: HashMap<NavCell, NavCell> = HashMap::new();
+        open.push(Open { f: h(start), g: 0.0, node: start });
+
+        while let Some(Open { g, node, .. }) = open.pop() {
+            if node == goal {
+                let mut path = vec![goal];
+                let mut current = goal;
+                while let Some(&prev) = came_from.get(&current) {
+                    path.push(prev);
+                    current = prev;
+                }
+                path.reverse();
+                return Some((path, g));
+            }
+            if g_score.get(&node).is_some_and(|&b| g > b) {
+                continue;
+            }
+            for next in self.neighbours(node) {
+                if self.chunk_of(next) != chunk {
+                    continue;
+                }
+                let next_g = g + self.step_cost(node, next);
+                if g_score.get(&next).is_none_or(|&b| next_g < b) {
+                    g_score.insert(next, next_g);
+                    came_from.insert(next, node);
+                    open.push(Open { f: next_g + h(next), g: next_g, node: next });
+                }
+            }
+        }
+        None
+    }
+
+    /// Dijkstra from `origin` to every cell of its chunk.
+    fn local_costs(&self, origin: NavCell, chunk: NavChunkKey) -> HashMap<NavCell, f32> {
+        let mut dist: HashMap<NavCell, f32> = HashMap::from([(origin, 0.0)]);
+        let mut open = BinaryHeap::new();
+        open.push(Open { f: 0.0, g: 0.0, node: origin });
+        while let Some(Open { g, node, .. }) = open.pop() {
+            if dist.get(&node).is_some_and(|&b| g > b) {
+                continue;
+            }
+            for next in self.neighbours(node) {
+                if self.chunk_of(next) != chunk {
+                    continue;
+                }
+                let next_g = g + self.step_cost(node, next);
+                if dist.get(&next).is_none_or(|&b| next_g < b) {
+                    dist.insert(next, next_g);
+                    open.push(Open { f: next_g, g: next_g, node: next });
+                }
+            }
+        }
+        dist
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ground(min_x: f32, max_x: f32, min_z: f32, max_z: f32, top: f32) -> WalkableSurface {
+        WalkableSurface { min: Vec3::new(min_x, top - 1.0, min_z), max: Vec3::new(max_x, top, max_z) }
+    }
+
+    /// 96x96 field (3x3 chunks) with a wall at x = 40..42 open only near z = 90.
+    fn walled_zone() -> ZoneGeometry {
+        ZoneGeometry {
+            zone_id: 1,
+            surfaces: vec![ground(0.0, 96.0, 0.0, 96.0, 0.0)],
+            obstacles: vec![Blocker { min: Vec3::new(40.0, 0.0, 0.0), max: Vec3::new(42.0, 4.0, 86.0) }],
+        }
+    }
+
+    fn built(zone: ZoneGeometry) -> NavMesh {
+        let mut nav = NavMesh::new(NavConfig::default());
+        nav.set_zone_geometry(zone);
+        nav.rebuild_dirty();
+        nav
+    }
+
+    #[test]
+    fn routes_around_walls_across_chunks() {
+        let nav = built(walled_zone());
+        let path = nav.find_path(Vec3::new(10.0, 0.0, 10.0), Vec3::new(80.0, 0.0, 10.0)).unwrap();
+        assert!(path.iter().any(|p| p.z > 86.0), "must detour through the gap: {:?}", path);
+        assert!(path.iter().all(|p| !(p.x > 39.5 && p.x < 42.5 && p.z < 86.5)), "{:?}", path);
+        assert!((path.last().unwrap().x - 80.5).abs() < 1.0);
+        for pair in path.windows(2) {
+            assert!(nav.walkable_segment(pair[0], pair[1]));
+        }
+
+        // Cutting the wall's end corner is refused even though every cell on the
+        // line is open; hugging the wall at exactly the agent radius is fine.
+        assert!(!nav.walkable_segment(Vec3::new(35.5, 0.0, 81.5), Vec3::new(44.5, 0.0, 90.5)));
+        assert!(nav.walkable_segment(Vec3::new(39.5, 0.0, 10.5), Vec3::new(39.5, 0.0, 80.5)));
+    }
+
+    #[test]
+    fn bridge_and_underpass_are_separate_layers() {
+        let mut zone = ZoneGeometry { zone_id: 2, ..Default::default() };
+        zone.surfaces.push(ground(0.0, 40.0, 0.0, 40.0, 0.0));
+        // Bridge deck 5 units up, reached by 0.5-high stairs descending along x = 30..40.
+        zone.surfaces.push(ground(10.0, 30.0, 18.0, 22.0, 5.0));
+        for step in 0..10 {
+            let top = 5.0 - 0.5 * (step + 1) as f32;
+            zone.surfaces.push(ground(30.0 + step as f32, 31.0 + step as f32, 18.0, 22.0, top));
+        }
+        let nav = built(zone);
+
+        let under = nav.locate(Vec3::new(20.5, 0.0, 20.5)).unwrap();
+        let deck = nav.locate(Vec3::new(20.5, 5.0, 20.5)).unwrap();
+        assert_eq!((under.x, under.z), (deck.x, deck.z));
+        assert_ne!(under.layer, deck.layer);
+
+        let path = nav.find_cell_path(under, deck).unwrap();
+        assert!(path.iter().any(|c| c.x >= 35), "deck is only reachable via the stairs");
+    }
+
+    #[test]
+    fn geometry_changes_rebuild_only_touched_chunks() {
+        let mut nav = built(walled_zone());
+        let start = Vec3::new(10.0, 0.0, 10.0);
+        let goal = Vec3::new(80.0, 0.0, 10.0);
+        assert!(nav.find_path(start, goal).is_some());
+
+        let mut sealed = walled_zone();
+        sealed.obstacles[0].max.z = 96.0;
+        nav.set_zone_geometry(sealed);
+        let changed = nav.rebuild_dirty();
+        assert!(changed.contains(&NavChunkKey { x: 1, z: 2 }));
+        assert!(nav.find_path(start, goal).is_none());
+
+        nav.invalidate_world_chunks([ChunkCoord { x: 0, y: 0, z: 0 }], 128.0);
+        assert!(nav.has_dirty_chunks());
+    }
+}
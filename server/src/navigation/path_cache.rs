@@ -0,0 +1,223 @@
+/*!
+ * server/src/navigation/path_cache.rs
+ *
+ * Shared path cache keyed by (start cell, goal cell). NPCs heading to the same
+ * place from the same spot share one Arc'd path. Entries are evicted when any
+ * nav chunk they cross is rebuilt; negative results (unreachable) are dropped
+ * on every rebuild since new geometry anywhere may connect them. Paths and
+ * negative results are each bounded by the capacity, oldest entry first.
+ *
+ * AG-SML v1.0 | TOLC 8 | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+use bevy::prelude::Vec3;
+
+use super::navmesh::{NavCell, NavChunkKey, NavMesh};
+
+pub const DEFAULT_PATH_CACHE_CAPACITY: usize = 2_048;
+
+#[derive(Debug)]
+pub struct CachedPath {
+    /// String-pulled waypoints, starting after the start cell
+    pub waypoints: Vec<Vec3>,
+    /// Nav chunks the path crosses
+    pub chunks: Vec<NavChunkKey>,
+}
+
+impl CachedPath {
+    pub fn crosses_any(&self, chunks: &[NavChunkKey]) -> bool {
+        self.chunks.iter().any(|c| chunks.contains(c))
+    }
+}
+
+type PathKey = (NavCell, NavCell);
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PathCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+pub struct PathCache {
+    capacity: usize,
+    paths: HashMap<PathKey, Arc<CachedPath>>,
+    unreachable: HashSet<PathKey>,
+    unreachable_order: VecDeque<PathKey>,
+    by_chunk: HashMap<NavChunkKey, HashSet<PathKey>>,
+    order: VecDeque<PathKey>,
+    pub stats: PathCacheStats,
+}
+
+impl Default for PathCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_PATH_CACHE_CAPACITY)
+    }
+}
+
+impl PathCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            paths: HashMap::new(),
+            unreachable: HashSet::new(),
+            unreachable_order: VecDeque::new(),
+            by_chunk: HashMap::new(),
+            order: VecDeque::new(),
+            stats: PathCacheStats::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Cached path from `start` to `goal`, computing (and caching) it on a miss.
+    pub fn find(&mut self, nav: &NavMesh, start: Vec3, goal: Vec3) -> Option<Arc<CachedPath>> {
+        let key = (nav.locate(start)?, nav.locate(goal)?);
+        if let Some(path) = self.paths.get(&key) {
+            self.stats.hits += 1;
+            return Some(path.clone());
+        }
+        if self.unreachable.contains(&key) {
+            self.stats.hits += 1;
+            return None;
+        }
+        self.stats.misses += 1;
+
+        let Some(cells) = nav.find_cell_path(key.0, key.1) else {
+            self.insert_unreachable(key);
+            return None;
+        };
+        let path = Arc::new(CachedPath { waypoints: nav.smooth(&cells), chunks: nav.chunks_on_path(&cells) });
+        self.insert(key, path.clone());
+        Some(path)
+    }
+
+    /// Evict every path crossing one of `chunks` (from NavMesh::rebuild_dirty).
+    pub fn invalidate_chunks(&mut self, chunks: &[NavChunkKey]) {
+        if chunks.is_empty() {
+            return;
+        }
+        self.unreachable.clear();
+        self.unreachable_order.clear();
+        for chunk in chunks {
+            for key in self.by_chunk.remove(chunk).unwrap_or_default() {
+                self.remove(key);
+            }
+        }
+    }
+
+    fn insert_unreachable(&mut self, key: PathKey) {
+        while self.unreachable.len() >= self.capacity {
+            let Some(oldest) = self.unreachable_order.pop_front() else { break };
+            self.unreachable.remove(&oldest);
+        }
+        self.unreachable.insert(key);
+        self.unreachable_order.push_back(key);
+    }
+
+    fn insert(&mut self, key: PathKey, path: Arc<CachedPath>) {
+        while self.paths.len() >= self.capacity {
+            let Some(oldest) = self.order.pop_front() else { break };
+            self.remove(oldest);
+        }
+        for chunk in &path.chunks {
+            self.by_chunk.entry(*chunk).or_default().insert(key);
+        }
+        self.order.push_back(key);
+        self.paths.insert(key, path);
+    }
+
+    fn remove(&mut self, key: PathKey) {
+        let Some(path) = self.paths.remove(&key) else { return };
+        self.stats.evictions += 1;
+        for chunk in &path.chunks {
+            if let Some(keys) = self.by_chunk.get_mut(chunk) {
+                keys.remove(&key);
+                if keys.is_empty() {
+                    self.by_chunk.remove(chunk);
+                }
+            }
+        }
+        self.order.retain(|k| *k != key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hierarchical_grid::Blocker;
+    use crate::navigation::navmesh::{NavConfig, WalkableSurface, ZoneGeometry};
+
+    fn field() -> NavMesh {
+        let mut nav = NavMesh::new(NavConfig::default());
+        nav.set_zone_geometry(ZoneGeometry {
+            zone_id: 1,
+            surfaces: vec![WalkableSurface { min: Vec3::new(0.0, -1.0, 0.0), max: Vec3::new(96.0, 0.0, 32.0) }],
+            obstacles: vec![],
+        });
+        nav.rebuild_dirty();
+        nav
+    }
+
+    #[test]
+    fn shared_until_a_crossed_chunk_changes() {
+        let mut nav = field();
+        let mut cache = PathCache::new(8);
+        let (start, goal) = (Vec3::new(2.0, 0.0, 16.0), Vec3::new(90.0, 0.0, 16.0));
+
+        let first = cache.find(&nav, start, goal).unwrap();
+        let second = cache.find(&nav, start, goal).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(cache.stats, PathCacheStats { hits: 1, misses: 1, evictions: 0 });
+
+        // Unrelated chunk: entry survives.
+        cache.invalidate_chunks(&[NavChunkKey { x: 7, z: 7 }]);
+        assert_eq!(cache.len(), 1);
+
+        // Wall across the middle chunk: evicted, and now unreachable.
+        let mut walled = ZoneGeometry { zone_id: 1, ..Default::default() };
+        walled.surfaces.push(WalkableSurface { min: Vec3::new(0.0, -1.0, 0.0), max: Vec3::new(96.0, 0.0, 32.0) });
+        walled.obstacles.push(Blocker { min: Vec3::new(48.0, 0.0, -1.0), max: Vec3::new(49.0, 3.0, 33.0) });
+        nav.set_zone_geometry(walled);
+        let changed = nav.rebuild_dirty();
+        cache.invalidate_chunks(&changed);
+        assert!(cache.is_empty());
+        assert!(cache.find(&nav, start, goal).is_none());
+    }
+
+    #[test]
+    fn capacity_evicts_oldest() {
+        let nav = field();
+        let mut cache = PathCache::new(2);
+        for x in [10.0, 20.0, 30.0] {
+            cache.find(&nav, Vec3::new(2.0, 0.0, 2.0), Vec3::new(x, 0.0, 2.0)).unwrap();
+        }
+        assert_eq!(cache.len(), 2);
+        cache.find(&nav, Vec3::new(2.0, 0.0, 2.0), Vec3::new(10.0, 0.0, 2.0)).unwrap();
+        assert_eq!(cache.stats.misses, 4);
+
+        // Off-mesh goals are remembered as unreachable, under the same bound.
+        for z in [40.0, 41.0, 42.0] {
+            let island = ZoneGeometry {
+                zone_id: z as u64,
+                surfaces: vec![WalkableSurface { min: Vec3::new(2.0, -1.0, z), max: Vec3::new(2.9, 0.0, z + 0.9) }],
+                obstacles: vec![],
+            };
+            let mut nav = field();
+            nav.set_zone_geometry(island);
+            nav.rebuild_dirty();
+            assert!(cache.find(&nav, Vec3::new(2.0, 0.0, 2.0), Vec3::new(2.5, 0.0, z + 0.5)).is_none());
+        }
+        assert_eq!(cache.unreachable.len(), 2);
+    }
+}
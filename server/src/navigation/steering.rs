@@ -0,0 +1,108 @@
+/*!
+ * server/src/navigation/steering.rs
+ *
+ * Path following for server NPCs: walk a cached path waypoint by waypoint at
+ * a fixed speed, carrying leftover distance across waypoints so NPCs do not
+ * stall on corners at low tick rates.
+ *
+ * AG-SML v1.0 | TOLC 8 | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::sync::Arc;
+
+use bevy::prelude::Vec3;
+
+use super::path_cache::CachedPath;
+
+#[derive(Clone, Debug)]
+pub struct SteeringConfig {
+    /// Units per second
+    pub speed: f32,
+    /// Close enough to the target to stop
+    pub arrive_radius: f32,
+    /// Re-plan once the target has moved this far from the planned goal
+    pub repath_distance: f32,
+    /// NPCs only pursue players within this range
+    pub attraction_radius: f32,
+}
+
+impl Default for SteeringConfig {
+    fn default() -> Self {
+        Self { speed: 4.0, arrive_radius: 1.5, repath_distance: 3.0, attraction_radius: 60.0 }
+    }
+}
+
+pub struct PathFollower {
+    path: Arc<CachedPath>,
+    next: usize,
+    /// Target position the path was planned for
+    pub goal: Vec3,
+}
+
+impl PathFollower {
+    pub fn new(path: Arc<CachedPath>, goal: Vec3) -> Self {
+        Self { path, next: 0, goal }
+    }
+
+    pub fn path(&self) -> &Arc<CachedPath> {
+        &self.path
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.path.waypoints.len()
+    }
+
+    /// Move from `position` along the remaining waypoints by at most `speed * dt`.
+    pub fn advance(&mut self, position: Vec3, speed: f32, dt: f32) -> Vec3 {
+        let mut budget = speed * dt;
+        let mut position = position;
+        while budget > 0.0 {
+            let Some(&target) = self.path.waypoints.get(self.next) else { break };
+            let to_target = target - position;
+            let distance = to_target.length();
+            if distance <= budget {
+                position = target;
+                budget -= distance;
+                self.next += 1;
+            } else {
+                position += to_target / distance * budget;
+                budget = 0.0;
+            }
+        }
+        position
+    }
+}
+
+/// Closest of `candidates` to `from` within `radius`.
+pub fn nearest_within(from: Vec3, candidates: impl IntoIterator<Item = Vec3>, radius: f32) -> Option<Vec3> {
+    candidates
+        .into_iter()
+        .map(|p| (p.distance_squared(from), p))
+        .filter(|(d, _)| *d <= radius * radius)
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, p)| p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn carries_leftover_distance_across_corners() {
+        let path = Arc::new(CachedPath {
+            waypoints: vec![Vec3::new(1.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 3.0)],
+            chunks: vec![],
+        });
+        let mut follower = PathFollower::new(path, Vec3::new(1.0, 0.0, 3.0));
+        let p = follower.advance(Vec3::ZERO, 2.0, 1.0);
+        assert!((p - Vec3::new(1.0, 0.0, 1.0)).length() < 1e-5);
+        let p = follower.advance(p, 2.0, 5.0);
+        assert_eq!(p, Vec3::new(1.0, 0.0, 3.0));
+        assert!(follower.is_finished());
+
+        let players = [Vec3::new(50.0, 0.0, 0.0), Vec3::new(-5.0, 0.0, 0.0), Vec3::new(100.0, 0.0, 0.0)];
+        assert_eq!(nearest_within(Vec3::ZERO, players, 60.0), Some(Vec3::new(-5.0, 0.0, 0.0)));
+        assert_eq!(nearest_within(Vec3::new(200.0, 0.0, 0.0), players, 60.0), None);
+    }
+}
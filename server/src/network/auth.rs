@@ -0,0 +1,473 @@
+//! Powrush-MMO Handshake Authentication (RSIL challenge–response)
+//! HandshakeRequest → [ProtocolAccepted] + AuthChallenge(nonce) → AuthChallengeResponse(did, key, sig)
+//! → HandshakeResponse. The DID is mapped to a stable player_id that survives
+//! reconnects and restarts via IdentityRegistry, which also records the DID
+//! document: answers are checked against its current key set, and a presented
+//! document only replaces the record when it extends it (no rollback past a
+//! rotation or revocation). The wire version is negotiated from
+//! HandshakeRequest.version before anything else is sent; v21 clients, which
+//! predate the challenge, are refused at that version.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use rsil_identity::challenge::{generate_nonce, verify_challenge_answer};
+use rsil_identity::{DidDocument, RsilError};
+use serde::{Deserialize, Serialize};
+use crate::persistence::player_store::write_atomic;
+use shared::protocol::{negotiate_version, ClientMessage, ServerMessage, MIN_SUPPORTED_PROTOCOL_VERSION, PROTOCOL_VERSION};
+
+/// How long a client has to answer an issued nonce.
+pub const CHALLENGE_TTL: Duration = Duration::from_secs(30);
+
+/// How long an unauthenticated socket may stay open at all.
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(45);
+
+/// First wire version with AuthChallenge / AuthChallengeResponse.
+const IDENTITY_HANDSHAKE_VERSION: u32 = 22;
+
+const FIRST_PLAYER_ID: u64 = 1000; // Start from 1000 for clarity
+
+/// Why a handshake was refused. Rendered into `HandshakeResponse.reason`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthRejection {
+    VersionMismatch { client: u32 },
+    /// v21 predates the identity handshake: it can be told no, but never authenticated.
+    IdentityUnsupported { client: u32 },
+    UnexpectedMessage,
+    ChallengeExpired,
+    DidMismatch,
+    BadSignature,
+    KeyRevoked,
+    StaleDidDocument,
+    AlreadyConnected,
+}
+
+impl AuthRejection {
+    pub fn reason(&self) -> String {
+        match self {
+            AuthRejection::VersionMismatch { client } => format!(
+                "Protocol version mismatch: server supports v{}..=v{}, client sent v{}",
+                MIN_SUPPORTED_PROTOCOL_VERSION, PROTOCOL_VERSION, client
+            ),
+            AuthRejection::IdentityUnsupported { client } => format!(
+                "Client v{} predates the identity handshake; v{}+ required to log in",
+                client, IDENTITY_HANDSHAKE_VERSION
+            ),
+            AuthRejection::UnexpectedMessage => "Handshake out of order".to_string(),
+            AuthRejection::ChallengeExpired => "Auth challenge expired".to_string(),
+            AuthRejection::DidMismatch => "DID does not match public key".to_string(),
+            AuthRejection::BadSignature => "Auth signature invalid".to_string(),
+            AuthRejection::KeyRevoked => "Key revoked or rotated out for this DID".to_string(),
+            AuthRejection::StaleDidDocument => "DID document is older than the one on record".to_string(),
+            AuthRejection::AlreadyConnected => "Identity already connected".to_string(),
+        }
+    }
+
+    pub fn to_response(&self) -> ServerMessage {
+        ServerMessage::HandshakeResponse {
+            accepted: false,
+            reason: Some(self.reason()),
+            player_id: 0,
+            server_time: now_ms(),
+        }
+    }
+}
+
+/// Durable DID → player_id mapping plus the latest verified DID document per
+/// identity. Same identity, same player, every session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IdentityRegistry {
+    players: HashMap<String, u64>,
+    next_player_id: u64,
+    /// Registries written before documents were recorded load with none; the
+    /// first successful handshake records one.
+    #[serde(default)]
+    documents: HashMap<String, DidDocument>,
+}
+
+impl Default for IdentityRegistry {
+    fn default() -> Self {
+        Self {
+            players: HashMap::new(),
+            next_player_id: FIRST_PLAYER_ID,
+            documents: HashMap::new(),
+        }
+    }
+}
+
+impl IdentityRegistry {
+    pub fn player_id_for(&self, did: &str) -> Option<u64> {
+        self.players.get(did).copied()
+    }
+
+    /// Latest verified DID document on record for `did`.
+    pub fn document_for(&self, did: &str) -> Option<&DidDocument> {
+        self.documents.get(did)
+    }
+
+    /// Document on record for a player id (their authenticated identity).
+    pub fn document_for_player(&self, player_id: u64) -> Option<&DidDocument> {
+        self.players
+            .iter()
+            .find(|(_, id)| **id == player_id)
+            .and_then(|(did, _)| self.documents.get(did))
+    }
+
+    /// Record a verified document. Returns true when the record changed. Callers
+    /// check `DidDocument::extends` first; a document that does not is ignored.
+    pub fn record_document(&mut self, document: DidDocument) -> bool {
+        if let Some(known) = self.documents.get(&document.id) {
+            if *known == document || document.extends(known).is_err() {
+                return false;
+            }
+        }
+        self.documents.insert(document.id.clone(), document);
+        true
+    }
+
+    /// Existing id for a known DID, otherwise allocate. Returns (id, newly_registered).
+    pub fn resolve_or_register(&mut self, did: &str) -> (u64, bool) {
+        if let Some(id) = self.players.get(did) {
+            return (*id, false);
+        }
+        let id = self.next_player_id;
+        self.next_player_id += 1;
+        self.players.insert(did.to_string(), id);
+        (id, true)
+    }
+
+    pub fn default_path() -> PathBuf {
+        PathBuf::from("data/identities.json")
+    }
+
+    /// Blocking: temp file + fsync + rename + directory fsync. Async callers write a
+    /// snapshot on the blocking pool rather than holding the registry lock across it.
+    pub fn save_to_file(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        write_atomic(path, json.as_bytes()).map_err(|e| e.to_string())
+    }
+
+    pub fn load_from_file(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&data).map_err(|e| e.to_string())
+    }
+}
+
+/// Outcome of feeding one pre-auth message into the handshake.
+#[derive(Debug)]
+pub enum HandshakeStep {
+    /// Send these in order and keep waiting.
+    Reply(Vec<ServerMessage>),
+    /// Identity proven. Caller still checks for duplicate sessions before accepting.
+    Authenticated {
+        player_id: u64,
+        player_name: String,
+        did: String,
+        /// DID document now on record for this identity.
+        identity: DidDocument,
+        /// True when the registry changed (new player or newer document) and should be saved.
+        registry_changed: bool,
+    },
+    /// Send `rejection.to_response()` and close.
+    Reject(AuthRejection),
+    /// Not a handshake message; drop it.
+    Ignore,
+}
+
+enum HandshakeState {
+    AwaitingHello,
+    AwaitingAnswer {
+        player_name: String,
+        nonce: [u8; 32],
+        issued_at: Instant,
+    },
+    Done,
+}
+
+/// Per-connection handshake state machine.
+pub struct ClientHandshake {
+    server_id: String,
+    state: HandshakeState,
+    wire_version: u32,
+}
+
+impl ClientHandshake {
+    pub fn new(server_id: impl Into<String>) -> Self {
+        Self {
+            server_id: server_id.into(),
+            state: HandshakeState::AwaitingHello,
+            wire_version: PROTOCOL_VERSION,
+        }
+    }
+
+    /// Version to encode/decode this connection at (PROTOCOL_VERSION until negotiated).
+    pub fn wire_version(&self) -> u32 {
+        self.wire_version
+    }
+
+    pub fn on_message(&mut self, msg: &ClientMessage, registry: &mut IdentityRegistry, now: Instant) -> HandshakeStep {
+        match (&self.state, msg) {
+            (HandshakeState::AwaitingHello, ClientMessage::HandshakeRequest { version, player_name, .. }) => {
+                let Some(negotiated) = negotiate_version(*version) else {
+                    self.state = HandshakeState::Done;
+                    return HandshakeStep::Reject(AuthRejection::VersionMismatch { client: *version });
+                };
+                self.wire_version = negotiated;
+                if negotiated < IDENTITY_HANDSHAKE_VERSION {
+                    self.state = HandshakeState::Done;
+                    return HandshakeStep::Reject(AuthRejection::IdentityUnsupported { client: *version });
+                }
+
+                let nonce = generate_nonce();
+                self.state = HandshakeState::AwaitingAnswer {
+                    player_name: player_name.clone(),
+                    nonce,
+                    issued_at: now,
+                };
+                let mut replies = Vec::with_capacity(2);
+                // v22 / v23 peers have no ProtocolAccepted; for them the version is implied.
+                if negotiated >= 24 {
+                    replies.push(ServerMessage::ProtocolAccepted { version: negotiated });
+                }
+                replies.push(ServerMessage::AuthChallenge {
+                    nonce: nonce.to_vec(),
+                    server_id: self.server_id.clone(),
+                });
+                HandshakeStep::Reply(replies)
+            }
+            (
+                HandshakeState::AwaitingAnswer { player_name, nonce, issued_at },
+                ClientMessage::AuthChallengeResponse { did, public_key, signature, did_document },
+            ) => {
+                let expired = now.duration_since(*issued_at) > CHALLENGE_TTL;
+                let verdict = verify_challenge_answer(
+                    &self.server_id,
+                    nonce,
+                    did,
+                    public_key,
+                    signature,
+                    did_document.as_deref(),
+                    registry.document_for(did),
+                );
+                let player_name = player_name.clone();
+                self.state = HandshakeState::Done;
+
+                if expired {
+                    return HandshakeStep::Reject(AuthRejection::ChallengeExpired);
+                }
+                match verdict {
+                    Ok(identity) => {
+                        let (player_id, newly_registered) = registry.resolve_or_register(did);
+                        let document_changed = registry.record_document(identity.clone());
+                        HandshakeStep::Authenticated {
+                            player_id,
+                            player_name,
+                            did: did.clone(),
+                            identity,
+                            registry_changed: newly_registered || document_changed,
+                        }
+                    }
+                    Err(RsilError::DidMismatch) => HandshakeStep::Reject(AuthRejection::DidMismatch),
+                    Err(RsilError::Revoked) => HandshakeStep::Reject(AuthRejection::KeyRevoked),
+                    Err(RsilError::StaleDidDocument) => HandshakeStep::Reject(AuthRejection::StaleDidDocument),
+                    Err(_) => HandshakeStep::Reject(AuthRejection::BadSignature),
+                }
+            }
+            (_, ClientMessage::HandshakeRequest { .. }) | (_, ClientMessage::AuthChallengeResponse { .. }) => {
+                self.state = HandshakeState::Done;
+                HandshakeStep::Reject(AuthRejection::UnexpectedMessage)
+            }
+            _ => HandshakeStep::Ignore,
+        }
+    }
+}
+
+pub fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsil_identity::{answer_challenge, SovereignKeypair};
+
+    fn hello() -> ClientMessage {
+        hello_at(PROTOCOL_VERSION)
+    }
+
+    fn hello_at(version: u32) -> ClientMessage {
+        ClientMessage::HandshakeRequest {
+            version,
+            player_name: "Aster".to_string(),
+            client_time_ms: 0,
+        }
+    }
+
+    fn answer_for(keypair: &SovereignKeypair, step: HandshakeStep) -> ClientMessage {
+        answer_with(keypair, None, step)
+    }
+
+    fn answer_with(keypair: &SovereignKeypair, document: Option<&DidDocument>, step: HandshakeStep) -> ClientMessage {
+        let HandshakeStep::Reply(replies) = step else {
+            panic!("expected challenge");
+        };
+        let Some(ServerMessage::AuthChallenge { nonce, server_id }) = replies.last().cloned() else {
+            panic!("expected challenge");
+        };
+        let answer = answer_challenge(keypair, document, &server_id, &nonce).unwrap();
+        ClientMessage::AuthChallengeResponse {
+            did: answer.did,
+            public_key: answer.public_key,
+            signature: answer.signature,
+            did_document: answer.did_document,
+        }
+    }
+
+    #[test]
+    fn same_key_maps_to_same_player_across_sessions() {
+        let keypair = SovereignKeypair::generate().unwrap();
+        let mut registry = IdentityRegistry::default();
+        let now = Instant::now();
+
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let mut hs = ClientHandshake::new("shard-1");
+            let challenge = hs.on_message(&hello(), &mut registry, now);
+            let reply = answer_for(&keypair, challenge);
+            match hs.on_message(&reply, &mut registry, now) {
+                HandshakeStep::Authenticated { player_id, .. } => ids.push(player_id),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        assert_eq!(ids[0], ids[1]);
+    }
+
+    fn login(
+        keypair: &SovereignKeypair,
+        document: Option<&DidDocument>,
+        registry: &mut IdentityRegistry,
+    ) -> HandshakeStep {
+        let mut hs = ClientHandshake::new("shard-1");
+        let challenge = hs.on_message(&hello(), registry, Instant::now());
+        let reply = answer_with(keypair, document, challenge);
+        hs.on_message(&reply, registry, Instant::now())
+    }
+
+    #[test]
+    fn recorded_document_locks_out_superseded_keys_and_rollbacks() {
+        let genesis = SovereignKeypair::generate().unwrap();
+        let rotated = SovereignKeypair::generate().unwrap();
+        let genesis_doc = DidDocument::new(&genesis);
+        let mut doc = genesis_doc.clone();
+        doc.rotate(&genesis, &rotated.hybrid_public_key(), 1).unwrap();
+        let mut registry = IdentityRegistry::default();
+
+        let HandshakeStep::Authenticated { player_id, registry_changed: true, .. } = login(&genesis, None, &mut registry) else {
+            panic!("genesis login");
+        };
+        let HandshakeStep::Authenticated { player_id: again, registry_changed: true, identity, .. } =
+            login(&rotated, Some(&doc), &mut registry)
+        else {
+            panic!("rotated login");
+        };
+        assert_eq!(again, player_id);
+        assert_eq!(identity, doc);
+
+        // The registry survives a restart with the document on record.
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("identities.json");
+        registry.save_to_file(&path).unwrap();
+        let mut registry = IdentityRegistry::load_from_file(&path).unwrap();
+        assert_eq!(registry.document_for_player(player_id), Some(&doc));
+
+        assert!(matches!(login(&genesis, None, &mut registry), HandshakeStep::Reject(AuthRejection::KeyRevoked)));
+        assert!(matches!(
+            login(&genesis, Some(&genesis_doc), &mut registry),
+            HandshakeStep::Reject(AuthRejection::StaleDidDocument)
+        ));
+        // The current key still logs in without presenting the document; the record decides.
+        let mut hs = ClientHandshake::new("shard-1");
+        let challenge = hs.on_message(&hello(), &mut registry, Instant::now());
+        let ClientMessage::AuthChallengeResponse { did, public_key, signature, .. } = answer_with(&rotated, Some(&doc), challenge) else {
+            unreachable!()
+        };
+        let bare = ClientMessage::AuthChallengeResponse { did, public_key, signature, did_document: None };
+        assert!(matches!(
+            hs.on_message(&bare, &mut registry, Instant::now()),
+            HandshakeStep::Authenticated { registry_changed: false, .. }
+        ));
+    }
+
+    #[test]
+    fn expired_challenge_is_rejected() {
+        let keypair = SovereignKeypair::generate().unwrap();
+        let mut registry = IdentityRegistry::default();
+        let now = Instant::now();
+
+        let mut hs = ClientHandshake::new("shard-1");
+        let challenge = hs.on_message(&hello(), &mut registry, now);
+        let reply = answer_for(&keypair, challenge);
+        let later = now + CHALLENGE_TTL + Duration::from_secs(1);
+        assert!(matches!(
+            hs.on_message(&reply, &mut registry, later),
+            HandshakeStep::Reject(AuthRejection::ChallengeExpired)
+        ));
+    }
+
+    #[test]
+    fn version_is_negotiated_down_and_too_old_rejected() {
+        let mut registry = IdentityRegistry::default();
+
+        let mut hs = ClientHandshake::new("shard-1");
+        match hs.on_message(&hello_at(IDENTITY_HANDSHAKE_VERSION), &mut registry, Instant::now()) {
+            HandshakeStep::Reply(replies) => {
+                assert!(matches!(replies.as_slice(), [ServerMessage::AuthChallenge { .. }]))
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(hs.wire_version(), IDENTITY_HANDSHAKE_VERSION);
+
+        // Still spoken, so the refusal reaches the client in a layout it can read.
+        let mut hs = ClientHandshake::new("shard-1");
+        assert!(matches!(
+            hs.on_message(&hello_at(MIN_SUPPORTED_PROTOCOL_VERSION), &mut registry, Instant::now()),
+            HandshakeStep::Reject(AuthRejection::IdentityUnsupported { client }) if client == MIN_SUPPORTED_PROTOCOL_VERSION
+        ));
+        assert_eq!(hs.wire_version(), MIN_SUPPORTED_PROTOCOL_VERSION);
+
+        let mut hs = ClientHandshake::new("shard-1");
+        assert!(matches!(
+            hs.on_message(&hello_at(MIN_SUPPORTED_PROTOCOL_VERSION - 1), &mut registry, Instant::now()),
+            HandshakeStep::Reject(AuthRejection::VersionMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn answer_without_challenge_is_rejected() {
+        let keypair = SovereignKeypair::generate().unwrap();
+        let answer = answer_challenge(&keypair, None, "shard-1", &[0u8; 32]).unwrap();
+        let mut hs = ClientHandshake::new("shard-1");
+        let msg = ClientMessage::AuthChallengeResponse {
+            did: answer.did,
+            public_key: answer.public_key,
+            signature: answer.signature,
+            did_document: answer.did_document,
+        };
+        assert!(matches!(
+            hs.on_message(&msg, &mut IdentityRegistry::default(), Instant::now()),
+            HandshakeStep::Reject(AuthRejection::UnexpectedMessage)
+        ));
+    }
+}
@@ -0,0 +1,354 @@
+//! Powrush-MMO UDP datagram sessions (protocol v30)
+//! Pairs a UDP peer address with an authenticated WebSocket session through the
+//! token sent in ServerMessage::DatagramOffer. Pure state — the socket tasks live
+//! in tokio_transport.rs; framing and channel routing are in shared::datagram.
+//! A session only carries outbound traffic once the client has proven both
+//! directions work (Keepalive or Message after our BindAck), and drops back to
+//! the WebSocket when the path goes quiet.
+//! Every frame is MAC'd with the session token (shared::datagram). A sealed frame
+//! from a new source address does not move the path: that address is sent a
+//! Challenge and becomes the path only when it echoes the nonce back.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use rsil_identity::challenge::generate_nonce;
+use shared::datagram::{
+    client_message_channel, session_id, Channel, Datagram, DatagramKind, SequenceWindow, SessionId, SessionToken,
+    BIND_RETRY_MS, CHALLENGE_LEN, DATAGRAM_IDLE_TIMEOUT_MS, SESSION_TOKEN_LEN,
+};
+use shared::protocol::ClientMessage;
+use shared::wire_compat::decode_client_message;
+
+/// Oldest negotiated wire version that understands DatagramOffer.
+pub const MIN_DATAGRAM_PROTOCOL_VERSION: u32 = 30;
+
+const IDLE_TIMEOUT: Duration = Duration::from_millis(DATAGRAM_IDLE_TIMEOUT_MS);
+/// A new address is re-challenged at most this often.
+const CHALLENGE_RETRY: Duration = Duration::from_millis(BIND_RETRY_MS);
+
+/// Unguessable per-connection token; only ever sent over the authenticated WebSocket.
+pub fn new_session_token() -> SessionToken {
+    let mut token = [0u8; SESSION_TOKEN_LEN];
+    token.copy_from_slice(&generate_nonce()[..SESSION_TOKEN_LEN]);
+    token
+}
+
+/// What the UDP socket task should do with one inbound datagram.
+#[derive(Debug)]
+pub enum DatagramInbound {
+    /// Not ours: unknown session, bad MAC, malformed, stale or replayed.
+    Ignore,
+    /// Answer a Bind / Keepalive / path change. Counts as a heartbeat for `player_id`.
+    Reply { player_id: u64, to: SocketAddr, frame: Vec<u8> },
+    Message { player_id: u64, message: ClientMessage },
+    /// Valid frame whose payload was refused (still a heartbeat).
+    Dropped { player_id: u64, reason: String },
+}
+
+/// A source address that must echo `nonce` before traffic moves to it.
+struct PendingPath {
+    addr: SocketAddr,
+    nonce: [u8; CHALLENGE_LEN],
+    sent_at: Instant,
+}
+
+struct DatagramSession {
+    token: SessionToken,
+    player_id: u64,
+    wire_version: u32,
+    addr: Option<SocketAddr>,
+    pending: Option<PendingPath>,
+    /// Client has heard our BindAck / Keepalive and answered: safe to route over UDP.
+    confirmed: bool,
+    last_recv: Instant,
+    recv_window: SequenceWindow,
+    next_send_seq: u32,
+}
+
+impl DatagramSession {
+    fn frame(&mut self, kind: DatagramKind, payload: &[u8]) -> Option<Vec<u8>> {
+        let frame = Datagram { kind, token: self.token, sequence: self.next_send_seq, payload }.encode().ok()?;
+        self.next_send_seq = self.next_send_seq.wrapping_add(1);
+        Some(frame)
+    }
+
+    fn reply(&mut self, kind: DatagramKind, payload: &[u8], to: SocketAddr) -> DatagramInbound {
+        match self.frame(kind, payload) {
+            Some(frame) => DatagramInbound::Reply { player_id: self.player_id, to, frame },
+            None => DatagramInbound::Ignore,
+        }
+    }
+
+    /// Challenge `from` (a sealed frame arrived from it, but it is not the path).
+    fn challenge(&mut self, from: SocketAddr, now: Instant) -> DatagramInbound {
+        let nonce = match &self.pending {
+            Some(p) if p.addr == from && now.duration_since(p.sent_at) < CHALLENGE_RETRY => {
+                return DatagramInbound::Ignore;
+            }
+            Some(p) if p.addr == from => p.nonce,
+            _ => {
+                let mut nonce = [0u8; CHALLENGE_LEN];
+                nonce.copy_from_slice(&generate_nonce()[..CHALLENGE_LEN]);
+                nonce
+            }
+        };
+        self.pending = Some(PendingPath { addr: from, nonce, sent_at: now });
+        self.reply(DatagramKind::Challenge, &nonce, from)
+    }
+}
+
+#[derive(Default)]
+pub struct DatagramSessions {
+    sessions: HashMap<SessionId, DatagramSession>,
+}
+
+impl DatagramSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept datagrams for `token` from now on (call once the handshake succeeded).
+    pub fn register(&mut self, token: SessionToken, player_id: u64, wire_version: u32, now: Instant) {
+        self.sessions.insert(session_id(&token), DatagramSession {
+            token,
+            player_id,
+            wire_version,
+            addr: None,
+            pending: None,
+            confirmed: false,
+            last_recv: now,
+            recv_window: SequenceWindow::default(),
+            next_send_seq: 0,
+        });
+    }
+
+    pub fn remove(&mut self, token: &SessionToken) {
+        self.sessions.remove(&session_id(token));
+    }
+
+    pub fn remove_player(&mut self, player_id: u64) {
+        self.sessions.retain(|_, s| s.player_id != player_id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// True while outbound traffic for `token` goes over UDP.
+    pub fn is_active(&self, token: &SessionToken, now: Instant) -> bool {
+        self.sessions
+            .get(&session_id(token))
+            .is_some_and(|s| s.confirmed && now.duration_since(s.last_recv) <= IDLE_TIMEOUT)
+    }
+
+    pub fn on_datagram(&mut self, from: SocketAddr, bytes: &[u8], now: Instant) -> DatagramInbound {
+        let Some(session) = Datagram::session_of(bytes).ok().and_then(|id| self.sessions.get_mut(&id)) else {
+            return DatagramInbound::Ignore;
+        };
+        let Ok(datagram) = Datagram::decode(bytes, &session.token) else {
+            return DatagramInbound::Ignore;
+        };
+        if !session.recv_window.accept(datagram.sequence) {
+            return DatagramInbound::Ignore;
+        }
+        let player_id = session.player_id;
+
+        match datagram.kind {
+            DatagramKind::Bind => {
+                if session.addr.is_some_and(|addr| addr != from) {
+                    return session.challenge(from, now);
+                }
+                session.addr = Some(from);
+                session.last_recv = now;
+                session.reply(DatagramKind::BindAck, &[], from)
+            }
+            DatagramKind::BindAck | DatagramKind::Challenge => DatagramInbound::Ignore,
+            DatagramKind::ChallengeResponse => {
+                let answered = session
+                    .pending
+                    .as_ref()
+                    .is_some_and(|p| p.addr == from && p.nonce.as_slice() == datagram.payload);
+                if !answered {
+                    return DatagramInbound::Ignore;
+                }
+                // The new address proved it holds the session: move the path there.
+                session.pending = None;
+                session.addr = Some(from);
+                session.confirmed = true;
+                session.last_recv = now;
+                session.reply(DatagramKind::Keepalive, &[], from)
+            }
+            DatagramKind::Keepalive | DatagramKind::Message => {
+                match session.addr {
+                    None => return DatagramInbound::Ignore,
+                    // Rebinding NAT or a redirected packet: challenge before following.
+                    Some(addr) if addr != from => return session.challenge(from, now),
+                    Some(_) => {}
+                }
+                session.confirmed = true;
+                session.last_recv = now;
+
+                if datagram.kind == DatagramKind::Keepalive {
+                    return session.reply(DatagramKind::Keepalive, &[], from);
+                }
+                match decode_client_message(datagram.payload, session.wire_version) {
+                    Ok(message) if client_message_channel(&message) == Channel::Unreliable => {
+                        DatagramInbound::Message { player_id, message }
+                    }
+                    Ok(_) => DatagramInbound::Dropped {
+                        player_id,
+                        reason: "reliable message sent over UDP".to_string(),
+                    },
+                    Err(e) => DatagramInbound::Dropped { player_id, reason: e.to_string() },
+                }
+            }
+        }
+    }
+
+    /// Frame `payload` (an already wire-encoded ServerMessage) for UDP, or None when
+    /// the WebSocket has to carry it: no confirmed path, path idle, or too large.
+    pub fn route(&mut self, token: &SessionToken, payload: &[u8], now: Instant) -> Option<(SocketAddr, Vec<u8>)> {
+        let session = self.sessions.get_mut(&session_id(token))?;
+        if !session.confirmed {
+            return None;
+        }
+        if now.duration_since(session.last_recv) > IDLE_TIMEOUT {
+            session.confirmed = false;
+            return None;
+        }
+        let addr = session.addr?;
+        let frame = session.frame(DatagramKind::Message, payload)?;
+        Some((addr, frame))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::datagram::MAX_DATAGRAM_PAYLOAD;
+    use shared::protocol::{Vec3Ser, PROTOCOL_VERSION};
+    use shared::wire_compat::encode_client_message;
+
+    fn client_frame(kind: DatagramKind, token: SessionToken, sequence: u32, payload: &[u8]) -> Vec<u8> {
+        Datagram { kind, token, sequence, payload }.encode().unwrap()
+    }
+
+    #[test]
+    fn routes_over_udp_only_after_the_return_path_is_proven() {
+        let mut sessions = DatagramSessions::new();
+        let token = new_session_token();
+        let addr: SocketAddr = "10.0.0.5:40000".parse().unwrap();
+        let t0 = Instant::now();
+        sessions.register(token, 1000, PROTOCOL_VERSION, t0);
+
+        // Unknown tokens and unbound keepalives are ignored.
+        assert!(matches!(
+            sessions.on_datagram(addr, &client_frame(DatagramKind::Bind, [0; 16], 0, &[]), t0),
+            DatagramInbound::Ignore
+        ));
+        assert!(matches!(
+            sessions.on_datagram(addr, &client_frame(DatagramKind::Keepalive, token, 0, &[]), t0),
+            DatagramInbound::Ignore
+        ));
+
+        let reply = sessions.on_datagram(addr, &client_frame(DatagramKind::Bind, token, 1, &[]), t0);
+        let DatagramInbound::Reply { player_id: 1000, to, frame } = reply else { panic!("expected BindAck") };
+        assert_eq!(to, addr);
+        assert_eq!(Datagram::decode(&frame, &token).unwrap().kind, DatagramKind::BindAck);
+        // Bound but unconfirmed: the WebSocket still carries snapshots.
+        assert!(sessions.route(&token, &[1, 2, 3], t0).is_none());
+
+        assert!(matches!(
+            sessions.on_datagram(addr, &client_frame(DatagramKind::Keepalive, token, 2, &[]), t0),
+            DatagramInbound::Reply { .. }
+        ));
+        let (to, frame) = sessions.route(&token, &[1, 2, 3], t0).unwrap();
+        assert_eq!(to, addr);
+        assert_eq!(Datagram::decode(&frame, &token).unwrap().payload, &[1, 2, 3]);
+        assert!(sessions.route(&token, &vec![0; MAX_DATAGRAM_PAYLOAD + 1], t0).is_none());
+
+        // Replayed datagrams are dropped; a silent path falls back to the WebSocket.
+        assert!(matches!(
+            sessions.on_datagram(addr, &client_frame(DatagramKind::Keepalive, token, 2, &[]), t0),
+            DatagramInbound::Ignore
+        ));
+        assert!(sessions.route(&token, &[1], t0 + IDLE_TIMEOUT + Duration::from_secs(1)).is_none());
+        assert!(!sessions.is_active(&token, t0));
+
+        sessions.remove_player(1000);
+        assert!(sessions.is_empty());
+    }
+
+    #[test]
+    fn only_unreliable_messages_are_accepted_over_udp() {
+        let mut sessions = DatagramSessions::new();
+        let token = new_session_token();
+        let addr: SocketAddr = "10.0.0.5:40000".parse().unwrap();
+        let now = Instant::now();
+        sessions.register(token, 7, PROTOCOL_VERSION, now);
+        sessions.on_datagram(addr, &client_frame(DatagramKind::Bind, token, 0, &[]), now);
+
+        let ack = encode_client_message(&ClientMessage::SnapshotAck { snapshot_id: 9 }, PROTOCOL_VERSION).unwrap();
+        assert!(matches!(
+            sessions.on_datagram(addr, &client_frame(DatagramKind::Message, token, 1, &ack), now),
+            DatagramInbound::Message { player_id: 7, message: ClientMessage::SnapshotAck { snapshot_id: 9 } }
+        ));
+
+        let legacy_move = ClientMessage::Move { delta: Vec3Ser { x: 1.0, y: 0.0, z: 0.0 } };
+        let bytes = encode_client_message(&legacy_move, PROTOCOL_VERSION).unwrap();
+        assert!(matches!(
+            sessions.on_datagram(addr, &client_frame(DatagramKind::Message, token, 2, &bytes), now),
+            DatagramInbound::Dropped { player_id: 7, .. }
+        ));
+    }
+
+    #[test]
+    fn forged_frames_and_unproven_addresses_never_take_the_path() {
+        let mut sessions = DatagramSessions::new();
+        let token = new_session_token();
+        let (home, elsewhere): (SocketAddr, SocketAddr) = ("10.0.0.5:40000".parse().unwrap(), "10.9.9.9:5000".parse().unwrap());
+        let now = Instant::now();
+        sessions.register(token, 7, PROTOCOL_VERSION, now);
+        sessions.on_datagram(home, &client_frame(DatagramKind::Bind, token, 0, &[]), now);
+        sessions.on_datagram(home, &client_frame(DatagramKind::Keepalive, token, 1, &[]), now);
+        assert_eq!(sessions.route(&token, &[1], now).unwrap().0, home);
+
+        // Right session id, wrong key: dropped before anything else looks at it.
+        let mut forged = client_frame(DatagramKind::Keepalive, [0x11; 16], 2, &[]);
+        forged[3..3 + 16].copy_from_slice(&session_id(&token));
+        assert!(matches!(sessions.on_datagram(elsewhere, &forged, now), DatagramInbound::Ignore));
+
+        // A sealed frame from a new address only earns that address a challenge.
+        let reply = sessions.on_datagram(elsewhere, &client_frame(DatagramKind::Keepalive, token, 3, &[]), now);
+        let DatagramInbound::Reply { to, frame, .. } = reply else { panic!("expected a Challenge") };
+        assert_eq!(to, elsewhere);
+        let challenge = Datagram::decode(&frame, &token).unwrap();
+        assert_eq!(challenge.kind, DatagramKind::Challenge);
+        let nonce = challenge.payload.to_vec();
+        assert_eq!(sessions.route(&token, &[1], now).unwrap().0, home);
+        // Not re-sent on every packet.
+        assert!(matches!(
+            sessions.on_datagram(elsewhere, &client_frame(DatagramKind::Keepalive, token, 4, &[]), now),
+            DatagramInbound::Ignore
+        ));
+
+        // The echo has to carry the nonce and come from the challenged address.
+        let mut wrong = nonce.clone();
+        wrong[0] ^= 1;
+        for (from, seq, payload) in [(elsewhere, 5, &wrong), (home, 6, &nonce)] {
+            sessions.on_datagram(from, &client_frame(DatagramKind::ChallengeResponse, token, seq, payload), now);
+            assert_eq!(sessions.route(&token, &[1], now).unwrap().0, home);
+        }
+        assert!(matches!(
+            sessions.on_datagram(elsewhere, &client_frame(DatagramKind::ChallengeResponse, token, 7, &nonce), now),
+            DatagramInbound::Reply { .. }
+        ));
+        assert_eq!(sessions.route(&token, &[1], now).unwrap().0, elsewhere);
+    }
+}
@@ -1,3 +1,9 @@
+pub mod auth;
+pub mod datagram;
+pub mod rate_limit;
+pub mod send_queue;
 pub mod tokio_transport;
 
-pub use tokio_transport::{TokioTransport, TransportEvent, TransportCommand, ClientConnectionInfo};
\ No newline at end of file
+pub use auth::{AuthRejection, IdentityRegistry};
+pub use send_queue::{ConnectionStats, SendQueueConfig};
+pub use tokio_transport::{TokioTransport, TransportEvent, TransportCommand, ClientConnectionInfo, TransportMetrics};
\ No newline at end of file
@@ -0,0 +1,207 @@
+//! Powrush-MMO per-connection inbound rate limits
+//! Coarse flood protection at the transport, before anything reaches the simulation:
+//! one token bucket per message class, sized well above what an honest client sends
+//! (movement at MOVE_TICK_HZ, pings every few seconds). Game systems keep their own
+//! finer rules (movement tick budget, ability GCD); this only stops a client from
+//! burying them. Frames before the handshake completes all draw on the handshake
+//! bucket, and a dropped reliable request is answered so the client is not left waiting.
+
+use std::time::Instant;
+
+use shared::datagram::{client_message_channel, Channel};
+use shared::protocol::{ClientMessage, ServerMessage, MOVE_TICK_HZ};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InboundClass {
+    Handshake,
+    Ping,
+    Movement,
+    SnapshotAck,
+    Ability,
+    Inventory,
+    Trade,
+    /// Guild membership, ranks, bank, audit and treaties
+    Guild,
+    /// Guild chat: fanned out to every online member
+    Chat,
+    Crafting,
+    /// Council membership, queries, proposals, votes and ballot records
+    Council,
+    /// RBE queries and need declarations, localization and audio-moment queries
+    Query,
+}
+
+const CLASSES: usize = 12;
+
+pub fn inbound_class(msg: &ClientMessage) -> InboundClass {
+    use ClientMessage as C;
+    match msg {
+        C::HandshakeRequest { .. } | C::AuthChallengeResponse { .. } => InboundClass::Handshake,
+        C::Ping { .. } => InboundClass::Ping,
+        C::Move { .. } | C::MoveCommand { .. } => InboundClass::Movement,
+        C::SnapshotAck { .. } => InboundClass::SnapshotAck,
+        C::UseAbility { .. } => InboundClass::Ability,
+        C::InventoryHotbarMove { .. } | C::InventoryMove { .. } => InboundClass::Inventory,
+        C::TradeOffer { .. }
+        | C::TradeCounter { .. }
+        | C::TradeLock { .. }
+        | C::TradeConfirm { .. }
+        | C::TradeCancel { .. } => InboundClass::Trade,
+        C::GuildCreate { .. }
+        | C::GuildInvite { .. }
+        | C::GuildInviteResponse { .. }
+        | C::GuildLeave
+        | C::GuildKick { .. }
+        | C::GuildSetRank { .. }
+        | C::GuildEditRank { .. }
+        | C::GuildBankDeposit { .. }
+        | C::GuildBankWithdraw { .. }
+        | C::GuildAuditRequest { .. }
+        | C::GuildTreatyPropose { .. }
+        | C::GuildTreatyRespond { .. }
+        | C::GuildTreatyRevoke { .. } => InboundClass::Guild,
+        C::GuildChat { .. } => InboundClass::Chat,
+        C::CraftStart { .. } | C::CraftCancel { .. } | C::CraftQueueRequest | C::CraftPlanRequest { .. } => {
+            InboundClass::Crafting
+        }
+        C::CouncilJoin { .. }
+        | C::DivineCouncilQuery { .. }
+        | C::CouncilProposalSubmit { .. }
+        | C::CouncilAgendaRequest { .. }
+        | C::CouncilVote { .. }
+        | C::CouncilBallotRecordRequest { .. } => InboundClass::Council,
+        C::RbeAbundanceQuery { .. }
+        | C::AbundanceNeedDeclare { .. }
+        | C::SyncLocalization { .. }
+        | C::AudioMomentSave { .. }
+        | C::AudioMomentCatalogRequest { .. }
+        | C::AudioMomentSetFavorite { .. } => InboundClass::Query,
+    }
+}
+
+/// (sustained per second, burst) for each class.
+fn limits(class: InboundClass) -> (f32, f32) {
+    let move_hz = MOVE_TICK_HZ as f32;
+    match class {
+        InboundClass::Handshake => (1.0, 4.0),
+        InboundClass::Ping => (2.0, 5.0),
+        // Twice the tick rate: catch-up after a hitch must not be dropped here.
+        InboundClass::Movement => (2.0 * move_hz, 2.0 * move_hz),
+        InboundClass::SnapshotAck => (2.0 * move_hz, 2.0 * move_hz),
+        InboundClass::Ability => (20.0, 20.0),
+        InboundClass::Inventory => (20.0, 40.0),
+        InboundClass::Trade => (10.0, 20.0),
+        // Bank deposits come in bursts when a member empties their bags.
+        InboundClass::Guild => (5.0, 40.0),
+        InboundClass::Chat => (2.0, 5.0),
+        InboundClass::Crafting => (10.0, 20.0),
+        InboundClass::Council => (2.0, 10.0),
+        InboundClass::Query => (5.0, 20.0),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f32,
+    last: Instant,
+}
+
+#[derive(Debug, Clone)]
+pub struct InboundRateLimiter {
+    buckets: [Bucket; CLASSES],
+}
+
+impl InboundRateLimiter {
+    pub fn new(now: Instant) -> Self {
+        const ORDER: [InboundClass; CLASSES] = [
+            InboundClass::Handshake,
+            InboundClass::Ping,
+            InboundClass::Movement,
+            InboundClass::SnapshotAck,
+            InboundClass::Ability,
+            InboundClass::Inventory,
+            InboundClass::Trade,
+            InboundClass::Guild,
+            InboundClass::Chat,
+            InboundClass::Crafting,
+            InboundClass::Council,
+            InboundClass::Query,
+        ];
+        Self { buckets: ORDER.map(|class| Bucket { tokens: limits(class).1, last: now }) }
+    }
+
+    /// Spend one token for `msg`; false = over the limit, drop it.
+    pub fn allow(&mut self, msg: &ClientMessage, now: Instant) -> bool {
+        self.allow_class(inbound_class(msg), now)
+    }
+
+    /// Spend one token from `class` (before the handshake every frame pays from
+    /// `Handshake`, whatever it decodes to).
+    pub fn allow_class(&mut self, class: InboundClass, now: Instant) -> bool {
+        let (rate, burst) = limits(class);
+        let bucket = &mut self.buckets[class as usize];
+        let elapsed = now.saturating_duration_since(bucket.last).as_secs_f32();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(burst);
+        bucket.last = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// What to tell the client about a dropped message: nothing for unreliable traffic
+/// (the next input or ack supersedes it), an Error for a request awaiting an answer.
+pub fn rate_limited_reply(msg: &ClientMessage) -> Option<ServerMessage> {
+    (client_message_channel(msg) == Channel::Reliable).then(|| ServerMessage::Error {
+        message: format!("Rate limited: {:?} request dropped, retry shortly", inbound_class(msg)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn buckets_are_per_class_and_refill() {
+        let t0 = Instant::now();
+        let mut limiter = InboundRateLimiter::new(t0);
+        let ping = ClientMessage::Ping { client_time_ms: 0 };
+        let ack = ClientMessage::SnapshotAck { snapshot_id: 1 };
+
+        assert_eq!((0..10).filter(|_| limiter.allow(&ping, t0)).count(), 5);
+        // A flooded class does not starve the others.
+        assert!(limiter.allow(&ack, t0));
+        assert!(!limiter.allow(&ping, t0 + Duration::from_millis(100)));
+        assert!(limiter.allow(&ping, t0 + Duration::from_millis(600)));
+    }
+
+    #[test]
+    fn every_family_has_its_own_bucket() {
+        let now = Instant::now();
+        let mut limiter = InboundRateLimiter::new(now);
+        let vote = ClientMessage::CouncilVote { proposal_id: 1, support: true };
+        assert_eq!(inbound_class(&ClientMessage::GuildLeave), InboundClass::Guild);
+        assert_eq!(inbound_class(&ClientMessage::GuildChat { text: "hi".into() }), InboundClass::Chat);
+        assert_eq!(inbound_class(&ClientMessage::CraftQueueRequest), InboundClass::Crafting);
+        assert_eq!(inbound_class(&vote), InboundClass::Council);
+
+        // Spamming votes leaves crafting and guild requests untouched.
+        assert_eq!((0..20).filter(|_| limiter.allow(&vote, now)).count(), 10);
+        assert!(limiter.allow(&ClientMessage::CraftQueueRequest, now));
+        assert!(limiter.allow(&ClientMessage::GuildLeave, now));
+
+        // Pre-auth frames pay from the handshake bucket whatever they are.
+        assert_eq!((0..10).filter(|_| limiter.allow_class(InboundClass::Handshake, now)).count(), 4);
+    }
+
+    #[test]
+    fn only_reliable_drops_are_answered() {
+        let vote = ClientMessage::CouncilVote { proposal_id: 1, support: true };
+        assert!(matches!(rate_limited_reply(&vote), Some(ServerMessage::Error { .. })));
+        assert!(rate_limited_reply(&ClientMessage::SnapshotAck { snapshot_id: 3 }).is_none());
+    }
+}
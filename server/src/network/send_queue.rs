@@ -0,0 +1,417 @@
+//! Powrush-MMO per-client outbound queues
+//! Bounded, priority-classed queue feeding one connection's writer task, replacing the
+//! unbounded channel per client. State updates a newer copy supersedes (snapshots,
+//! corrections, full inventory / trade / chunk / guild state) are coalesced in place; when the
+//! queue is full, lower-priority messages make room for more important ones. A client
+//! that cannot keep up — queue above its high watermark for too long, or a control
+//! message that no longer fits — is reported as a slow consumer and evicted by the
+//! transport. Messages are shared (`Arc`) so a broadcast costs one allocation.
+//! Per-connection counters (queue depth, bytes and messages in / out, RTT) live in
+//! ConnectionMetrics next to the queue.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use shared::protocol::ServerMessage;
+use tokio::sync::Notify;
+
+/// Pop order: every queued Control message goes out before any Gameplay one, and so on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SendPriority {
+    /// Session plumbing the client cannot work without
+    Control = 0,
+    Gameplay = 1,
+    /// Large or catch-up payloads that can wait
+    Bulk = 2,
+}
+
+const LANES: usize = 3;
+
+pub fn send_priority(msg: &ServerMessage) -> SendPriority {
+    use ServerMessage as S;
+    match msg {
+        S::HandshakeResponse { .. }
+        | S::AuthChallenge { .. }
+        | S::ProtocolAccepted { .. }
+        | S::DatagramOffer { .. }
+        | S::Pong { .. }
+        | S::Error { .. }
+        | S::MercyGateBlocked { .. } => SendPriority::Control,
+        S::AudioMomentCatalogSnapshot { .. }
+        | S::ChunkSnapshot { .. }
+        | S::WorldUpdate { .. }
+        | S::GuildAuditLog { .. } => SendPriority::Bulk,
+        _ => SendPriority::Gameplay,
+    }
+}
+
+/// Messages that carry complete state share a key with the copies they supersede;
+/// only the newest queued one is sent.
+pub fn coalesce_key(msg: &ServerMessage) -> Option<(&'static str, u64)> {
+    use ServerMessage as S;
+    match msg {
+        // Deltas are taken against acked baselines, so an unsent snapshot is never needed.
+        S::EntitySnapshot { .. } => Some(("EntitySnapshot", 0)),
+        S::MoveCorrection { .. } => Some(("MoveCorrection", 0)),
+        S::WorldUpdate { .. } => Some(("WorldUpdate", 0)),
+        S::ValenceUpdate { player_id, .. } => Some(("ValenceUpdate", *player_id)),
+        S::InventoryUpdate { player_id, .. } => Some(("InventoryUpdate", *player_id)),
+        S::AudioMomentCatalogSnapshot { player_id, .. } => Some(("AudioMomentCatalogSnapshot", *player_id)),
+        S::TradeUpdate { trade } => Some(("TradeUpdate", trade.trade_id)),
+        S::ChunkSnapshot { chunk_id, .. } => Some(("ChunkSnapshot", *chunk_id)),
+        S::GuildInfo { guild } => Some(("GuildInfo", guild.guild_id)),
+        // Each client's queue only ever holds its own player's crafting queue.
+        S::CraftQueue { .. } => Some(("CraftQueue", 0)),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SendQueueConfig {
+    /// Messages held at most, across all priorities
+    pub capacity: usize,
+    /// Depth at which the stall timer starts
+    pub high_watermark: usize,
+    /// Above the high watermark for this long = slow consumer
+    pub max_stall: Duration,
+}
+
+impl Default for SendQueueConfig {
+    fn default() -> Self {
+        Self { capacity: 512, high_watermark: 384, max_stall: Duration::from_secs(10) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushOutcome {
+    Queued,
+    /// Replaced a queued message with the same coalesce key
+    Coalesced,
+    /// Queued after dropping an older, lower-priority message
+    Displaced,
+    /// Queue full of messages at least as important; this one was dropped
+    Rejected(SendPriority),
+}
+
+struct Queued {
+    msg: Arc<ServerMessage>,
+    key: Option<(&'static str, u64)>,
+}
+
+pub struct SendQueue {
+    config: SendQueueConfig,
+    lanes: [VecDeque<Queued>; LANES],
+    len: usize,
+    stalled_since: Option<Instant>,
+}
+
+impl SendQueue {
+    pub fn new(config: SendQueueConfig) -> Self {
+        Self { config, lanes: Default::default(), len: 0, stalled_since: None }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, msg: Arc<ServerMessage>, now: Instant) -> PushOutcome {
+        let priority = send_priority(&msg);
+        let key = coalesce_key(&msg);
+        let lane = &mut self.lanes[priority as usize];
+
+        if let Some(queued) = key.and_then(|k| lane.iter_mut().find(|q| q.key == Some(k))) {
+            queued.msg = msg;
+            return PushOutcome::Coalesced;
+        }
+
+        let mut outcome = PushOutcome::Queued;
+        if self.len >= self.config.capacity {
+            // Oldest message of the least important lane that is less important than this one.
+            let victim = (priority as usize + 1..LANES).rev().find(|&l| !self.lanes[l].is_empty());
+            match victim {
+                Some(l) => {
+                    self.lanes[l].pop_front();
+                    self.len -= 1;
+                    outcome = PushOutcome::Displaced;
+                }
+                None => return PushOutcome::Rejected(priority),
+            }
+        }
+
+        self.lanes[priority as usize].push_back(Queued { msg, key });
+        self.len += 1;
+        if self.len >= self.config.high_watermark && self.stalled_since.is_none() {
+            self.stalled_since = Some(now);
+        }
+        outcome
+    }
+
+    pub fn pop(&mut self) -> Option<Arc<ServerMessage>> {
+        let queued = self.lanes.iter_mut().find_map(|lane| lane.pop_front())?;
+        self.len -= 1;
+        // Hysteresis: the stall timer only resets once the backlog is mostly drained.
+        if self.len <= self.config.high_watermark / 2 {
+            self.stalled_since = None;
+        }
+        Some(queued.msg)
+    }
+
+    /// Backlog has stayed above the high watermark for longer than `max_stall`.
+    pub fn is_stalled(&self, now: Instant) -> bool {
+        self.stalled_since.is_some_and(|since| now.duration_since(since) > self.config.max_stall)
+    }
+}
+
+// ============================================================================
+// Metrics
+// ============================================================================
+
+/// Lock-free counters, updated by the reader / writer / datagram tasks.
+#[derive(Default)]
+pub struct ConnectionMetrics {
+    pub bytes_in: AtomicU64,
+    pub bytes_out: AtomicU64,
+    pub messages_in: AtomicU64,
+    pub messages_out: AtomicU64,
+    pub coalesced: AtomicU64,
+    /// Outbound messages displaced or rejected by a full queue
+    pub dropped: AtomicU64,
+    /// Inbound messages over their rate limit
+    pub rate_limited: AtomicU64,
+    pub peak_queue_depth: AtomicUsize,
+    /// Last measured round trip; 0 until the first probe returns
+    pub rtt_ms: AtomicU32,
+}
+
+impl ConnectionMetrics {
+    pub fn record_in(&self, bytes: usize) {
+        self.bytes_in.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.messages_in.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_out(&self, bytes: usize) {
+        self.bytes_out.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.messages_out.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Point-in-time copy of one connection's metrics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionStats {
+    pub player_id: u64,
+    pub queue_depth: usize,
+    pub peak_queue_depth: usize,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub messages_in: u64,
+    pub messages_out: u64,
+    pub coalesced: u64,
+    pub dropped: u64,
+    pub rate_limited: u64,
+    pub rtt_ms: Option<u32>,
+    /// Outbound traffic currently uses the UDP datagram channel
+    pub datagram_active: bool,
+}
+
+// ============================================================================
+// Outbox (queue + wakeup + metrics, shared by producers and the writer task)
+// ============================================================================
+
+pub struct ClientOutbox {
+    queue: Mutex<SendQueue>,
+    notify: Notify,
+    closed: AtomicBool,
+    pub metrics: ConnectionMetrics,
+}
+
+impl ClientOutbox {
+    pub fn new(config: SendQueueConfig) -> Self {
+        Self {
+            queue: Mutex::new(SendQueue::new(config)),
+            notify: Notify::new(),
+            closed: AtomicBool::new(false),
+            metrics: ConnectionMetrics::default(),
+        }
+    }
+
+    /// Queue `msg` for the writer. Never blocks; a closed outbox rejects everything.
+    pub fn push(&self, msg: impl Into<Arc<ServerMessage>>) -> PushOutcome {
+        let msg = msg.into();
+        if self.closed.load(Ordering::Acquire) {
+            self.metrics.dropped.fetch_add(1, Ordering::Relaxed);
+            return PushOutcome::Rejected(send_priority(&msg));
+        }
+        let (outcome, depth) = {
+            let mut queue = self.queue.lock().unwrap();
+            let outcome = queue.push(msg, Instant::now());
+            (outcome, queue.len())
+        };
+        match outcome {
+            PushOutcome::Coalesced => {
+                self.metrics.coalesced.fetch_add(1, Ordering::Relaxed);
+            }
+            PushOutcome::Displaced | PushOutcome::Rejected(_) => {
+                self.metrics.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            PushOutcome::Queued => {}
+        }
+        self.metrics.peak_queue_depth.fetch_max(depth, Ordering::Relaxed);
+        self.notify.notify_one();
+        outcome
+    }
+
+    /// Next message for the writer; None once closed and drained.
+    pub async fn next(&self) -> Option<Arc<ServerMessage>> {
+        loop {
+            if let Some(msg) = self.queue.lock().unwrap().pop() {
+                return Some(msg);
+            }
+            if self.closed.load(Ordering::Acquire) {
+                return None;
+            }
+            self.notify.notified().await;
+        }
+    }
+
+    /// Stop accepting messages; the writer ends once the backlog is drained.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.notify.notify_one();
+    }
+
+    /// Drop the backlog and close (evicted slow consumers get nothing more).
+    pub fn abort(&self) {
+        let mut queue = self.queue.lock().unwrap();
+        while queue.pop().is_some() {}
+        drop(queue);
+        self.close();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    pub fn depth(&self) -> usize {
+        self.queue.lock().unwrap().len()
+    }
+
+    pub fn is_stalled(&self, now: Instant) -> bool {
+        self.queue.lock().unwrap().is_stalled(now)
+    }
+
+    pub fn stats(&self, player_id: u64, datagram_active: bool) -> ConnectionStats {
+        let m = &self.metrics;
+        let rtt = m.rtt_ms.load(Ordering::Relaxed);
+        ConnectionStats {
+            player_id,
+            queue_depth: self.depth(),
+            peak_queue_depth: m.peak_queue_depth.load(Ordering::Relaxed),
+            bytes_in: m.bytes_in.load(Ordering::Relaxed),
+            bytes_out: m.bytes_out.load(Ordering::Relaxed),
+            messages_in: m.messages_in.load(Ordering::Relaxed),
+            messages_out: m.messages_out.load(Ordering::Relaxed),
+            coalesced: m.coalesced.load(Ordering::Relaxed),
+            dropped: m.dropped.load(Ordering::Relaxed),
+            rate_limited: m.rate_limited.load(Ordering::Relaxed),
+            rtt_ms: (rtt > 0).then_some(rtt),
+            datagram_active,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(id: u32) -> Arc<ServerMessage> {
+        Arc::new(ServerMessage::EntitySnapshot {
+            snapshot_id: id,
+            baseline_id: None,
+            server_tick: id as u64,
+            entities: vec![],
+            removed: vec![],
+        })
+    }
+
+    fn chunk(chunk_id: u64, version: u64) -> Arc<ServerMessage> {
+        Arc::new(ServerMessage::ChunkSnapshot { chunk_id, version, resource_nodes: vec![], structures: vec![] })
+    }
+
+    fn error(text: &str) -> Arc<ServerMessage> {
+        Arc::new(ServerMessage::Error { message: text.to_string() })
+    }
+
+    #[test]
+    fn coalesces_superseded_state_and_pops_by_priority() {
+        let mut queue = SendQueue::new(SendQueueConfig::default());
+        let now = Instant::now();
+        assert_eq!(queue.push(chunk(1, 1), now), PushOutcome::Queued);
+        assert_eq!(queue.push(snapshot(1), now), PushOutcome::Queued);
+        assert_eq!(queue.push(chunk(2, 1), now), PushOutcome::Queued);
+        assert_eq!(queue.push(snapshot(2), now), PushOutcome::Coalesced);
+        assert_eq!(queue.push(chunk(1, 2), now), PushOutcome::Coalesced);
+        assert_eq!(queue.push(error("boom"), now), PushOutcome::Queued);
+        assert_eq!(queue.len(), 4);
+
+        let order: Vec<String> = std::iter::from_fn(|| queue.pop())
+            .map(|m| match &*m {
+                ServerMessage::Error { .. } => "error".to_string(),
+                ServerMessage::EntitySnapshot { snapshot_id, .. } => format!("snapshot{}", snapshot_id),
+                ServerMessage::ChunkSnapshot { chunk_id, version, .. } => format!("chunk{}v{}", chunk_id, version),
+                other => format!("{:?}", other),
+            })
+            .collect();
+        assert_eq!(order, ["error", "snapshot2", "chunk1v2", "chunk2v1"]);
+    }
+
+    #[test]
+    fn full_queue_displaces_lower_priority_and_detects_stalls() {
+        let config = SendQueueConfig { capacity: 3, high_watermark: 2, max_stall: Duration::from_secs(5) };
+        let mut queue = SendQueue::new(config);
+        let t0 = Instant::now();
+        for id in 0..3 {
+            assert_eq!(queue.push(chunk(id, 1), t0), PushOutcome::Queued);
+        }
+        assert_eq!(queue.push(chunk(9, 1), t0), PushOutcome::Rejected(SendPriority::Bulk));
+        assert_eq!(queue.push(error("a"), t0), PushOutcome::Displaced);
+        assert_eq!(queue.push(error("b"), t0), PushOutcome::Displaced);
+        assert_eq!(queue.push(error("c"), t0), PushOutcome::Displaced);
+        assert_eq!(queue.push(error("d"), t0), PushOutcome::Rejected(SendPriority::Control));
+
+        assert!(!queue.is_stalled(t0 + Duration::from_secs(4)));
+        assert!(queue.is_stalled(t0 + Duration::from_secs(6)));
+        queue.pop();
+        assert!(queue.is_stalled(t0 + Duration::from_secs(6)));
+        queue.pop();
+        assert!(!queue.is_stalled(t0 + Duration::from_secs(6)));
+    }
+
+    #[tokio::test]
+    async fn outbox_drains_then_ends_after_close() {
+        let outbox = Arc::new(ClientOutbox::new(SendQueueConfig::default()));
+        let writer = {
+            let outbox = outbox.clone();
+            tokio::spawn(async move {
+                let mut seen = 0;
+                while outbox.next().await.is_some() {
+                    seen += 1;
+                }
+                seen
+            })
+        };
+        outbox.push(error("a"));
+        outbox.push(snapshot(1));
+        tokio::task::yield_now().await;
+        outbox.push(snapshot(2));
+        outbox.close();
+        assert_eq!(outbox.push(error("late")), PushOutcome::Rejected(SendPriority::Control));
+        assert!(writer.await.unwrap() >= 2);
+        assert_eq!(outbox.stats(1, false).dropped, 1);
+    }
+}
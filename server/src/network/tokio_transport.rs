@@ -3,27 +3,56 @@
 //! heartbeat/timeout, mercy-gate enforcement, and seamless PATSAGi Council routing.
 //! Designed for low-latency multiplayer, forward-compatible with QUIC/laminar and
 //! full client prediction/reconciliation from Ra-Thor patterns.
+//! Identity: RSIL challenge–response (see network/auth.rs); player_id is stable per DID.
+//! Wire: every frame goes through shared::wire_compat at the connection's negotiated version.
+//! Datagrams (v30): a UDP socket on the same address carries EntitySnapshot / MoveCorrection
+//! out and MoveCommand / SnapshotAck in for sessions that bound it (network/datagram.rs).
+//! If UDP cannot be bound, or a client never binds, everything stays on the WebSocket.
+//! Backpressure: each client has a bounded, priority-classed outbox (network/send_queue.rs)
+//! that coalesces superseded state; slow consumers are evicted, inbound messages are rate
+//! limited per class (network/rate_limit.rs), and per-connection metrics (queue depth,
+//! bytes / messages in and out, WebSocket ping RTT) are read through TransportMetrics.
+//! Items: messages naming an item key the shared registry cannot resolve are answered with
+//! Error and never reach the simulation.
+//! Mercy Gate: weighed against the sender's saved valence when a PersistenceManager is attached.
 
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, UdpSocket};
 use tokio::sync::{mpsc, Mutex};
 use tokio_tungstenite::{accept_async, tungstenite::Message as WsMessage};
 use futures_util::{StreamExt, SinkExt};
 use tracing::{info, warn, debug};
 use anyhow::Result;
-use bincode;
 use shared::protocol::*;
-use std::sync::atomic::{AtomicU64, Ordering};
+use shared::datagram::{server_message_channel, Channel};
+use shared::wire_compat::{decode_client_message, encode_server_message};
+use std::path::PathBuf;
 
-static NEXT_PLAYER_ID: AtomicU64 = AtomicU64::new(1000); // Start from 1000 for clarity
+use super::auth::{now_ms, AuthRejection, ClientHandshake, HandshakeStep, IdentityRegistry, HANDSHAKE_TIMEOUT};
+use super::datagram::{new_session_token, DatagramInbound, DatagramSessions, MIN_DATAGRAM_PROTOCOL_VERSION};
+use super::rate_limit::{rate_limited_reply, InboundClass, InboundRateLimiter};
+use super::send_queue::{ClientOutbox, ConnectionStats, PushOutcome, SendPriority, SendQueueConfig};
+use shared::datagram::SessionToken;
+use rsil_identity::DidDocument;
+use crate::persistence_polish::{PersistenceManager, DEFAULT_VALENCE};
+
+/// How long without any inbound message before a client is dropped.
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(35);
+/// WebSocket Ping cadence; the echoed Pong gives the connection's RTT.
+const RTT_PROBE_INTERVAL: Duration = Duration::from_secs(5);
 
 /// Info exposed for game layer (name, id, etc.)
 #[derive(Clone, Debug)]
 pub struct ClientConnectionInfo {
     pub player_id: u64,
     pub player_name: String,
+    /// Verified RSIL DID backing this player_id
+    pub did: String,
+    /// DID document on record at login; trade signatures are checked against it.
+    pub identity: DidDocument,
 }
 
 /// Events emitted to the game simulation / tick loop
@@ -45,7 +74,77 @@ pub enum TransportCommand {
 struct ClientConnection {
     info: ClientConnectionInfo,
     last_heartbeat: Instant,
-    tx: mpsc::UnboundedSender<ServerMessage>, // channel to this client's writer task
+    /// Bounded queue drained by this client's writer task
+    outbox: Arc<ClientOutbox>,
+    limiter: InboundRateLimiter,
+    session_token: SessionToken,
+}
+
+type Connections = Arc<Mutex<HashMap<u64, ClientConnection>>>;
+type SharedDatagramSessions = Arc<std::sync::Mutex<DatagramSessions>>;
+
+/// Read handle on per-connection metrics; clone it before `TokioTransport::run`.
+#[derive(Clone)]
+pub struct TransportMetrics {
+    connections: Connections,
+    datagram_sessions: SharedDatagramSessions,
+}
+
+impl TransportMetrics {
+    /// One entry per authenticated connection, sorted by player_id.
+    pub async fn snapshot(&self) -> Vec<ConnectionStats> {
+        let now = Instant::now();
+        let conns = self.connections.lock().await;
+        let datagrams = self.datagram_sessions.lock().unwrap();
+        let mut stats: Vec<ConnectionStats> = conns
+            .iter()
+            .map(|(&id, conn)| conn.outbox.stats(id, datagrams.is_active(&conn.session_token, now)))
+            .collect();
+        stats.sort_by_key(|s| s.player_id);
+        stats
+    }
+}
+
+/// Queue `msg` for one client; true when the client turned out to be a slow consumer.
+fn deliver(outbox: &ClientOutbox, msg: Arc<ServerMessage>) -> bool {
+    let outcome = outbox.push(msg);
+    matches!(outcome, PushOutcome::Rejected(SendPriority::Control)) || outbox.is_stalled(Instant::now())
+}
+
+/// Drop a connection the transport gave up on. No-op if it is already gone.
+async fn evict(
+    connections: &Connections,
+    datagrams: &SharedDatagramSessions,
+    event_tx: &mpsc::UnboundedSender<TransportEvent>,
+    player_id: u64,
+    reason: &str,
+) {
+    let Some(conn) = connections.lock().await.remove(&player_id) else { return };
+    // Nothing more is sent; the writer closes the socket and the reader winds down.
+    conn.outbox.abort();
+    datagrams.lock().unwrap().remove_player(player_id);
+    let _ = event_tx.send(TransportEvent::ClientDisconnected { player_id });
+    warn!("Player {} evicted: {}", player_id, reason);
+}
+
+/// Valence the Mercy Gate weighs for `player_id`: the one on their save, or a fresh
+/// save's when no PersistenceManager is attached or the player has no save yet.
+fn player_valence(persistence: Option<&PersistenceManager>, player_id: u64) -> f32 {
+    persistence.and_then(|pm| pm.get_player_valence(player_id)).unwrap_or(DEFAULT_VALENCE)
+}
+
+/// Snapshot the registry under its lock, then write it on the blocking pool, so
+/// handshakes are never held up behind disk I/O. The file lock is taken first:
+/// a later snapshot cannot be overwritten by an earlier one still being written.
+async fn save_identities(identities: &Mutex<IdentityRegistry>, file: &Mutex<PathBuf>) {
+    let path = file.lock().await;
+    let snapshot = identities.lock().await.clone();
+    let target = path.clone();
+    match tokio::task::spawn_blocking(move || snapshot.save_to_file(&target)).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => warn!("Failed to persist identity registry: {}", e),
+        Err(e) => warn!("Identity registry write task failed: {}", e),
+    }
 }
 
 pub struct TokioTransport {
@@ -53,24 +152,83 @@ pub struct TokioTransport {
     connections: Arc<Mutex<HashMap<u64, ClientConnection>>>,
     event_tx: mpsc::UnboundedSender<TransportEvent>,
     command_rx: Option<mpsc::UnboundedReceiver<TransportCommand>>,
+    server_id: String,
+    identities: Arc<Mutex<IdentityRegistry>>,
+    /// Registry file; held while a snapshot is written so saves land in order.
+    identities_file: Arc<Mutex<PathBuf>>,
+    /// None = WebSocket only
+    udp: Option<Arc<UdpSocket>>,
+    datagram_sessions: SharedDatagramSessions,
+    send_queue: SendQueueConfig,
+    /// Player saves the Mercy Gate reads valence from
+    persistence: Option<PersistenceManager>,
 }
 
 impl TokioTransport {
     /// Create new transport bound to addr (e.g. "0.0.0.0:9001")
     pub async fn new(addr: &str) -> Result<(Self, mpsc::UnboundedReceiver<TransportEvent>, mpsc::UnboundedSender<TransportCommand>)> {
         let listener = TcpListener::bind(addr).await?;
+        // Same address and port as the WebSocket, over UDP.
+        let udp = match UdpSocket::bind(listener.local_addr()?).await {
+            Ok(socket) => Some(Arc::new(socket)),
+            Err(e) => {
+                warn!("UDP datagram channel unavailable on {} ({}); WebSocket only", addr, e);
+                None
+            }
+        };
         let (event_tx, event_rx) = mpsc::unbounded_channel();
         let (command_tx, command_rx) = mpsc::unbounded_channel();
         let connections = Arc::new(Mutex::new(HashMap::new()));
 
+        let identities_path = IdentityRegistry::default_path();
+        let identities = IdentityRegistry::load_from_file(&identities_path)
+            .map_err(|e| anyhow::anyhow!("Failed to load identity registry: {}", e))?;
+
         Ok((Self {
             listener,
             connections,
             event_tx,
             command_rx: Some(command_rx),
+            server_id: format!("powrush:{}", addr),
+            identities: Arc::new(Mutex::new(identities)),
+            identities_file: Arc::new(Mutex::new(identities_path)),
+            udp,
+            datagram_sessions: Arc::new(std::sync::Mutex::new(DatagramSessions::new())),
+            send_queue: SendQueueConfig::default(),
+            persistence: None,
         }, event_rx, command_tx))
     }
 
+    /// Per-client outbox limits (capacity, stall watermark and timeout).
+    pub fn with_send_queue(mut self, config: SendQueueConfig) -> Self {
+        self.send_queue = config;
+        self
+    }
+
+    /// Weigh the Mercy Gate against each player's saved valence.
+    pub fn with_persistence(mut self, persistence: PersistenceManager) -> Self {
+        self.persistence = Some(persistence);
+        self
+    }
+
+    pub fn metrics(&self) -> TransportMetrics {
+        TransportMetrics {
+            connections: self.connections.clone(),
+            datagram_sessions: self.datagram_sessions.clone(),
+        }
+    }
+
+    /// Never offer the datagram channel (e.g. UDP is filtered in front of this host).
+    pub fn without_datagrams(mut self) -> Self {
+        self.udp = None;
+        self
+    }
+
+    /// Local UDP address clients are offered, when the datagram channel is up.
+    pub fn datagram_addr(&self) -> Option<std::net::SocketAddr> {
+        self.udp.as_ref().and_then(|socket| socket.local_addr().ok())
+    }
+
     /// Run the transport accept loop + command handler + heartbeat monitor.
     /// Call this in a spawned task.
     pub async fn run(mut self) {
@@ -80,24 +238,44 @@ impl TokioTransport {
         // Command handler task (Send / Broadcast / Disconnect from game layer)
         if let Some(mut command_rx) = self.command_rx.take() {
             let cmd_connections = connections.clone();
+            let cmd_datagrams = self.datagram_sessions.clone();
+            let cmd_event_tx = event_tx.clone();
             tokio::spawn(async move {
                 while let Some(cmd) = command_rx.recv().await {
                     match cmd {
                         TransportCommand::Send { player_id, message } => {
-                            let conns = cmd_connections.lock().await;
-                            if let Some(conn) = conns.get(&player_id) {
-                                let _ = conn.tx.send(message);
+                            let outbox = cmd_connections.lock().await.get(&player_id).map(|c| c.outbox.clone());
+                            if let Some(outbox) = outbox {
+                                if deliver(&outbox, Arc::new(message)) {
+                                    evict(&cmd_connections, &cmd_datagrams, &cmd_event_tx, player_id, "slow consumer").await;
+                                }
                             }
                         }
                         TransportCommand::Broadcast { message } => {
-                            let conns = cmd_connections.lock().await;
-                            for conn in conns.values() {
-                                let _ = conn.tx.send(message.clone());
+                            // Queue outside the connection lock; every client shares one copy.
+                            let outboxes: Vec<(u64, Arc<ClientOutbox>)> = cmd_connections
+                                .lock()
+                                .await
+                                .iter()
+                                .map(|(&id, conn)| (id, conn.outbox.clone()))
+                                .collect();
+                            let message = Arc::new(message);
+                            let slow: Vec<u64> = outboxes
+                                .into_iter()
+                                .filter(|(_, outbox)| deliver(outbox, message.clone()))
+                                .map(|(id, _)| id)
+                                .collect();
+                            for player_id in slow {
+                                evict(&cmd_connections, &cmd_datagrams, &cmd_event_tx, player_id, "slow consumer").await;
                             }
                         }
                         TransportCommand::Disconnect { player_id } => {
-                            let mut conns = cmd_connections.lock().await;
-                            if conns.remove(&player_id).is_some() {
+                            let removed = cmd_connections.lock().await.remove(&player_id);
+                            if let Some(conn) = removed {
+                                // Flush what is queued, then the writer closes the socket.
+                                conn.outbox.close();
+                                cmd_datagrams.lock().unwrap().remove_player(player_id);
+                                let _ = cmd_event_tx.send(TransportEvent::ClientDisconnected { player_id });
                                 debug!("Force disconnected player {}", player_id);
                             }
                         }
@@ -109,28 +287,83 @@ impl TokioTransport {
         // Heartbeat monitor task (production timeout enforcement)
         let hb_connections = connections.clone();
         let hb_event_tx = event_tx.clone();
+        let hb_datagrams = self.datagram_sessions.clone();
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_secs(10));
             loop {
                 interval.tick().await;
+                let now = Instant::now();
                 let mut to_remove = Vec::new();
                 {
                     let conns = hb_connections.lock().await;
                     for (&id, conn) in conns.iter() {
-                        if conn.last_heartbeat.elapsed() > Duration::from_secs(35) {
-                            to_remove.push(id);
+                        if now.duration_since(conn.last_heartbeat) > HEARTBEAT_TIMEOUT {
+                            to_remove.push((id, "timed out (no heartbeat)"));
+                        } else if conn.outbox.is_stalled(now) {
+                            // Stalled with nothing new queued since: no push noticed it.
+                            to_remove.push((id, "slow consumer"));
                         }
                     }
                 }
-                for id in to_remove {
-                    let mut conns = hb_connections.lock().await;
-                    conns.remove(&id);
-                    let _ = hb_event_tx.send(TransportEvent::ClientDisconnected { player_id: id });
-                    warn!("Player {} timed out (no heartbeat)", id);
+                for (id, reason) in to_remove {
+                    evict(&hb_connections, &hb_datagrams, &hb_event_tx, id, reason).await;
                 }
             }
         });
 
+        // UDP datagram task: binds, keepalives and unreliable client messages
+        if let Some(udp) = self.udp.clone() {
+            let udp_connections = connections.clone();
+            let udp_event_tx = event_tx.clone();
+            let udp_datagrams = self.datagram_sessions.clone();
+            let udp_persistence = self.persistence.clone();
+            tokio::spawn(async move {
+                let mut buf = vec![0u8; 2048];
+                loop {
+                    let (len, from) = match udp.recv_from(&mut buf).await {
+                        Ok(r) => r,
+                        Err(e) => {
+                            // e.g. ICMP port unreachable from a client that went away
+                            debug!("UDP recv error: {}", e);
+                            continue;
+                        }
+                    };
+                    let now = Instant::now();
+                    let inbound = udp_datagrams.lock().unwrap().on_datagram(from, &buf[..len], now);
+                    let (player_id, message) = match inbound {
+                        DatagramInbound::Ignore => continue,
+                        DatagramInbound::Reply { player_id, to, frame } => {
+                            let _ = udp.send_to(&frame, to).await;
+                            (player_id, None)
+                        }
+                        DatagramInbound::Dropped { player_id, reason } => {
+                            debug!("Dropped datagram from player {} ({}): {}", player_id, from, reason);
+                            (player_id, None)
+                        }
+                        DatagramInbound::Message { player_id, message } => (player_id, Some(message)),
+                    };
+                    let allowed = {
+                        let mut conns = udp_connections.lock().await;
+                        let Some(conn) = conns.get_mut(&player_id) else { continue };
+                        conn.last_heartbeat = now;
+                        conn.outbox.metrics.record_in(len);
+                        match &message {
+                            Some(message) if !conn.limiter.allow(message, now) => {
+                                conn.outbox.metrics.rate_limited.fetch_add(1, Ordering::Relaxed);
+                                false
+                            }
+                            Some(_) => true,
+                            None => false,
+                        }
+                    };
+                    let Some(message) = message.filter(|_| allowed) else { continue };
+                    if apply_mercy_gate(&message, player_valence(udp_persistence.as_ref(), player_id)) {
+                        let _ = udp_event_tx.send(TransportEvent::MessageReceived { player_id, message });
+                    }
+                }
+            });
+        }
+
         // Main accept + per-client reader/writer loop
         loop {
             let (stream, remote_addr) = match self.listener.accept().await {
@@ -150,118 +383,185 @@ impl TokioTransport {
                 }
             };
 
-            let player_id = NEXT_PLAYER_ID.fetch_add(1, Ordering::Relaxed);
             let (mut write, mut read) = ws_stream.split();
-            let (client_tx, mut client_rx) = mpsc::unbounded_channel::<ServerMessage>();
-
-            // Register pending connection (updated on successful handshake)
-            {
-                let mut conns = connections.lock().await;
-                conns.insert(player_id, ClientConnection {
-                    info: ClientConnectionInfo {
-                        player_id,
-                        player_name: "pending_handshake".to_string(),
-                    },
-                    last_heartbeat: Instant::now(),
-                    tx: client_tx.clone(),
-                });
-            }
+            let outbox = Arc::new(ClientOutbox::new(self.send_queue.clone()));
+            let outbox_for_writer = outbox.clone();
 
             let connections_for_reader = connections.clone();
             let event_tx_for_reader = event_tx.clone();
-            let client_tx_for_reader = client_tx.clone();
+            let identities = self.identities.clone();
+            let identities_file = self.identities_file.clone();
+            let server_id = self.server_id.clone();
+            // Negotiated during the handshake; shared with the writer task.
+            let wire_version = Arc::new(AtomicU32::new(PROTOCOL_VERSION));
+            let wire_version_for_writer = wire_version.clone();
+            // Pairs this connection's UDP datagrams with its authenticated session.
+            let session_token = new_session_token();
+            let udp_for_writer = self.udp.clone();
+            let udp_port = self.udp.as_ref().and_then(|socket| socket.local_addr().ok()).map(|a| a.port());
+            let datagrams_for_reader = self.datagram_sessions.clone();
+            let datagrams_for_writer = self.datagram_sessions.clone();
+            let persistence = self.persistence.clone();
 
             // === Reader task (per client) ===
+            // Not registered in `connections` until the RSIL handshake succeeds.
             tokio::spawn(async move {
-                let mut authenticated = false;
-                let mut current_id = player_id;
-                let mut current_name = String::new();
+                let mut handshake = ClientHandshake::new(server_id);
+                let mut current_id: Option<u64> = None;
+                let handshake_deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+                // Becomes the session's limiter; until then every frame pays from its handshake bucket.
+                let mut limiter = InboundRateLimiter::new(Instant::now());
+
+                loop {
+                    let next = if current_id.is_some() {
+                        read.next().await
+                    } else {
+                        match tokio::time::timeout_at(handshake_deadline.into(), read.next()).await {
+                            Ok(next) => next,
+                            Err(_) => {
+                                warn!("Handshake timed out from {}", remote_addr);
+                                break;
+                            }
+                        }
+                    };
+                    let Some(msg_result) = next else { break };
 
-                while let Some(msg_result) = read.next().await {
                     match msg_result {
                         Ok(WsMessage::Binary(bytes)) => {
-                            // Update heartbeat on any message
-                            {
-                                let mut conns = connections_for_reader.lock().await;
-                                if let Some(conn) = conns.get_mut(&current_id) {
-                                    conn.last_heartbeat = Instant::now();
-                                }
+                            outbox.metrics.record_in(bytes.len());
+                            if current_id.is_none() && !limiter.allow_class(InboundClass::Handshake, Instant::now()) {
+                                outbox.metrics.rate_limited.fetch_add(1, Ordering::Relaxed);
+                                debug!("Rate limited pre-handshake frame from {}", remote_addr);
+                                continue;
                             }
+                            let client_msg = match decode_client_message(&bytes, wire_version.load(Ordering::Acquire)) {
+                                Ok(m) => m,
+                                Err(e) => {
+                                    warn!("Failed to deserialize ClientMessage from {}: {}", remote_addr, e);
+                                    continue;
+                                }
+                            };
 
-                            match bincode::deserialize::<ClientMessage>(&bytes) {
-                                Ok(client_msg) => {
-                                    if !authenticated {
-                                        if let ClientMessage::HandshakeRequest { version, player_name, .. } = &client_msg {
-                                            if *version != PROTOCOL_VERSION {
-                                                let _ = client_tx_for_reader.send(ServerMessage::HandshakeResponse {
-                                                    accepted: false,
-                                                    reason: Some(format!("Protocol version mismatch: server expects v{}, client sent v{}", PROTOCOL_VERSION, version)),
-                                                    player_id: 0,
-                                                    server_time: std::time::SystemTime::now()
-                                                        .duration_since(std::time::UNIX_EPOCH)
-                                                        .unwrap()
-                                                        .as_millis() as u64,
-                                                });
-                                                continue;
-                                            }
+                            let Some(player_id) = current_id else {
+                                let step = {
+                                    let mut registry = identities.lock().await;
+                                    handshake.on_message(&client_msg, &mut registry, Instant::now())
+                                };
+                                wire_version.store(handshake.wire_version(), Ordering::Release);
+                                match step {
+                                    HandshakeStep::Reply(replies) => {
+                                        for reply in replies {
+                                            outbox.push(reply);
+                                        }
+                                    }
+                                    HandshakeStep::Ignore => {
+                                        debug!("Ignoring pre-handshake message from {}", remote_addr);
+                                    }
+                                    HandshakeStep::Reject(rejection) => {
+                                        warn!("Handshake rejected from {}: {}", remote_addr, rejection.reason());
+                                        outbox.push(rejection.to_response());
+                                        break;
+                                    }
+                                    HandshakeStep::Authenticated { player_id, player_name, did, identity, registry_changed } => {
+                                        if registry_changed {
+                                            save_identities(&identities, &identities_file).await;
+                                        }
 
-                                            // Successful handshake
-                                            authenticated = true;
-                                            current_name = player_name.clone();
-                                            current_id = player_id;
-
-                                            // Update connection info
-                                            {
-                                                let mut conns = connections_for_reader.lock().await;
-                                                if let Some(conn) = conns.get_mut(&current_id) {
-                                                    conn.info.player_name = current_name.clone();
-                                                }
+                                        let info = ClientConnectionInfo { player_id, player_name, did, identity };
+                                        {
+                                            let mut conns = connections_for_reader.lock().await;
+                                            if conns.contains_key(&player_id) {
+                                                drop(conns);
+                                                let rejection = AuthRejection::AlreadyConnected;
+                                                warn!("Handshake rejected for player {}: {}", player_id, rejection.reason());
+                                                outbox.push(rejection.to_response());
+                                                break;
                                             }
-
-                                            let _ = client_tx_for_reader.send(ServerMessage::HandshakeResponse {
-                                                accepted: true,
-                                                reason: None,
-                                                player_id: current_id,
-                                                server_time: std::time::SystemTime::now()
-                                                    .duration_since(std::time::UNIX_EPOCH)
-                                                    .unwrap()
-                                                    .as_millis() as u64,
+                                            conns.insert(player_id, ClientConnection {
+                                                info: info.clone(),
+                                                last_heartbeat: Instant::now(),
+                                                outbox: outbox.clone(),
+                                                limiter: limiter.clone(),
+                                                session_token,
                                             });
-
-                                            let _ = event_tx_for_reader.send(TransportEvent::ClientConnected {
-                                                info: ClientConnectionInfo {
-                                                    player_id: current_id,
-                                                    player_name: current_name.clone(),
-                                                },
-                                            });
-                                            info!("Player {} ({}) handshake successful", current_id, current_name);
-                                            continue;
-                                        } else {
-                                            // Must complete handshake first
-                                            debug!("Ignoring pre-handshake message from pending connection");
-                                            continue;
                                         }
-                                    }
 
-                                    // === Authenticated path: Mercy Gate check ===
-                                    let valence = 0.82; // TODO: integrate per-player valence from WorldServer
-                                    if !apply_mercy_gate(&client_msg, valence) {
-                                        let _ = client_tx_for_reader.send(ServerMessage::MercyGateBlocked {
-                                            reason: "Mercy Gate blocked: insufficient valence for this divine action".to_string(),
-                                            valence,
+                                        current_id = Some(player_id);
+                                        outbox.push(ServerMessage::HandshakeResponse {
+                                            accepted: true,
+                                            reason: None,
+                                            player_id,
+                                            server_time: now_ms(),
                                         });
-                                        continue;
+                                        if let Some(port) = udp_port.filter(|_| handshake.wire_version() >= MIN_DATAGRAM_PROTOCOL_VERSION) {
+                                            datagrams_for_reader.lock().unwrap().register(
+                                                session_token,
+                                                player_id,
+                                                handshake.wire_version(),
+                                                Instant::now(),
+                                            );
+                                            outbox.push(ServerMessage::DatagramOffer { port, token: session_token });
+                                        }
+                                        info!("Player {} ({}, {}) handshake successful", player_id, info.player_name, info.did);
+                                        let _ = event_tx_for_reader.send(TransportEvent::ClientConnected { info });
                                     }
+                                }
+                                continue;
+                            };
 
-                                    // Forward to game simulation
-                                    let _ = event_tx_for_reader.send(TransportEvent::MessageReceived {
-                                        player_id: current_id,
-                                        message: client_msg,
-                                    });
+                            // Update heartbeat on any authenticated message, then the per-class rate limit
+                            let allowed = {
+                                let now = Instant::now();
+                                let mut conns = connections_for_reader.lock().await;
+                                match conns.get_mut(&player_id) {
+                                    Some(conn) => {
+                                        conn.last_heartbeat = now;
+                                        conn.limiter.allow(&client_msg, now)
+                                    }
+                                    // Evicted or force-disconnected
+                                    None => break,
                                 }
-                                Err(e) => {
-                                    warn!("Failed to deserialize ClientMessage from player {}: {}", current_id, e);
+                            };
+                            if !allowed {
+                                outbox.metrics.rate_limited.fetch_add(1, Ordering::Relaxed);
+                                debug!("Rate limited message from player {}", player_id);
+                                if let Some(reply) = rate_limited_reply(&client_msg) {
+                                    outbox.push(reply);
                                 }
+                                continue;
+                            }
+                            if let ClientMessage::Ping { client_time_ms } = &client_msg {
+                                outbox.push(ServerMessage::Pong { client_time_ms: *client_time_ms, server_time_ms: now_ms() });
+                            }
+
+                            // === Authenticated path: Mercy Gate check ===
+                            let valence = player_valence(persistence.as_ref(), player_id);
+                            if !apply_mercy_gate(&client_msg, valence) {
+                                outbox.push(ServerMessage::MercyGateBlocked {
+                                    reason: "Mercy Gate blocked: insufficient valence for this divine action".to_string(),
+                                    valence,
+                                });
+                                continue;
+                            }
+
+                            // Item keys must resolve before any handler sees them
+                            if let Err(e) = shared::items::current().check_client_message(&client_msg) {
+                                debug!("Rejected message from player {}: {}", player_id, e);
+                                outbox.push(ServerMessage::Error { message: e.to_string() });
+                                continue;
+                            }
+
+                            // Forward to game simulation
+                            let _ = event_tx_for_reader.send(TransportEvent::MessageReceived {
+                                player_id,
+                                message: client_msg,
+                            });
+                        }
+                        // Echo of the writer's RTT probe
+                        Ok(WsMessage::Pong(payload)) => {
+                            if let Ok(sent) = <[u8; 8]>::try_from(&payload[..]) {
+                                let rtt = now_ms().saturating_sub(u64::from_le_bytes(sent));
+                                outbox.metrics.rtt_ms.store(rtt.clamp(1, u32::MAX as u64) as u32, Ordering::Relaxed);
                             }
                         }
                         Ok(WsMessage::Close(_)) | Err(_) => {
@@ -271,36 +571,76 @@ impl TokioTransport {
                     }
                 }
 
-                // Cleanup on disconnect
-                {
-                    let mut conns = connections_for_reader.lock().await;
-                    conns.remove(&current_id);
+                // Cleanup on disconnect (only authenticated sessions were ever registered)
+                datagrams_for_reader.lock().unwrap().remove(&session_token);
+                outbox.close();
+                if let Some(player_id) = current_id {
+                    let removed = {
+                        let mut conns = connections_for_reader.lock().await;
+                        // Skip if already evicted (and possibly reconnected on a new socket).
+                        match conns.get(&player_id) {
+                            Some(conn) if Arc::ptr_eq(&conn.outbox, &outbox) => conns.remove(&player_id).is_some(),
+                            _ => false,
+                        }
+                    };
+                    if removed {
+                        let _ = event_tx_for_reader.send(TransportEvent::ClientDisconnected { player_id });
+                        info!("Player {} disconnected", player_id);
+                    }
                 }
-                let _ = event_tx_for_reader.send(TransportEvent::ClientDisconnected { player_id: current_id });
-                info!("Player {} disconnected", current_id);
             });
 
             // === Writer task (per client) ===
             tokio::spawn(async move {
-                while let Some(msg) = client_rx.recv().await {
-                    match bincode::serialize(&msg) {
+                let mut rtt_probe = tokio::time::interval(RTT_PROBE_INTERVAL);
+                loop {
+                    let msg = tokio::select! {
+                        next = outbox_for_writer.next() => match next {
+                            Some(msg) => msg,
+                            None => break,
+                        },
+                        _ = rtt_probe.tick() => {
+                            let probe = WsMessage::Ping(now_ms().to_le_bytes().to_vec().into());
+                            if write.send(probe).await.is_err() {
+                                break;
+                            }
+                            continue;
+                        }
+                    };
+                    match encode_server_message(&msg, wire_version_for_writer.load(Ordering::Acquire)) {
                         Ok(bytes) => {
+                            // Snapshots / corrections take UDP when this session has a live datagram path.
+                            if let Some(udp) = udp_for_writer.as_ref().filter(|_| server_message_channel(&msg) == Channel::Unreliable) {
+                                let route = datagrams_for_writer.lock().unwrap().route(&session_token, &bytes, Instant::now());
+                                if let Some((to, frame)) = route {
+                                    if udp.send_to(&frame, to).await.is_ok() {
+                                        outbox_for_writer.metrics.record_out(frame.len());
+                                        continue;
+                                    }
+                                }
+                            }
+
                             // Optional snappy compression for large WorldUpdate snapshots
-                            let final_bytes = if matches!(&msg, ServerMessage::WorldUpdate { .. }) {
+                            let final_bytes = if matches!(&*msg, ServerMessage::WorldUpdate { .. }) {
                                 snappy::compress(&bytes).unwrap_or(bytes)
                             } else {
                                 bytes
                             };
 
+                            let len = final_bytes.len();
                             if write.send(WsMessage::Binary(final_bytes.into())).await.is_err() {
                                 break;
                             }
+                            outbox_for_writer.metrics.record_out(len);
                         }
                         Err(e) => {
-                            warn!("Failed to serialize ServerMessage for player {}: {}", player_id, e);
+                            debug!("Dropped ServerMessage for {}: {}", remote_addr, e);
                         }
                     }
                 }
+                // Dead socket or closed outbox: refuse further messages, then say goodbye.
+                outbox_for_writer.abort();
+                let _ = write.send(WsMessage::Close(None)).await;
             });
         }
     }
@@ -1,5 +1,7 @@
 //! server/src/nevc_attachment.rs
 //! Finish Pass A + B — NEVC attachment via shared + durability hooks
+//! Phase 12 — Samples persist through the append-only NevcHistory log; the live class
+//! is its decay window, and transitions / timelines / realm rankings are queryable.
 //!
 //! AG-SML v1.0 | PATSAGi Councils | info@Rathor.ai
 //! Thunder locked in. Yoi ⚡
@@ -10,33 +12,120 @@ use std::time::{Duration, Instant};
 
 use shared::contribution_ledger::ContributionLedger;
 use shared::nevc_adapter::ContributionClass;
-use shared::nevc_game_loop::{apply_harvest_class, apply_harvest_to_ledger, HarvestNevcInput};
+use shared::nevc_game_loop::{apply_harvest_class, apply_harvest_to_ledger, harvest_to_sample, HarvestNevcInput};
+use shared::nevc_history::{ClassTransition, NevcHistory, NevcHistoryConfig, RealmId, TimelinePoint};
+use shared::nevc_adapter::NevcResult;
 use shared::nevc_persistence::{NevcPersistenceStore, NevcPlayerRecord};
 
 /// Minimum interval between opportunistic disk flushes during ticks.
 const TICK_PERSIST_INTERVAL: Duration = Duration::from_secs(30);
 
+/// How often ticks re-evaluate decay windows and compact old history.
+const HISTORY_MAINTENANCE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Realm for harvests that don't name one.
+pub const HOME_REALM: RealmId = 0;
+
 struct ServerNevcState {
     ledger: ContributionLedger,
     store: NevcPersistenceStore,
+    history: NevcHistory,
+    persist_path: PathBuf,
     last_persist: Instant,
+    last_history_maintenance: Instant,
     dirty: bool,
 }
 
 impl ServerNevcState {
     fn new() -> Self {
-        let path = default_persist_path();
-        let store = NevcPersistenceStore::load_from_file(&path).unwrap_or_default();
+        let history = NevcHistory::open(&NevcHistory::default_dir(), NevcHistoryConfig::default())
+            .unwrap_or_else(|e| {
+                tracing::error!("[NEVC] History log unavailable ({}); recording in memory only", e);
+                NevcHistory::in_memory(NevcHistoryConfig::default())
+            });
+        Self::with_history(history, default_persist_path())
+    }
+
+    /// State over `history`, with player records saved to `persist_path`.
+    fn with_history(history: NevcHistory, persist_path: PathBuf) -> Self {
+        let store = NevcPersistenceStore::load_from_file(&persist_path).unwrap_or_default();
         Self {
             ledger: ContributionLedger::new(),
             store,
+            history,
+            persist_path,
             last_persist: Instant::now(),
+            last_history_maintenance: Instant::now(),
             dirty: false,
         }
     }
 
+    fn harvest(&mut self, realm_id: RealmId, input: &HarvestNevcInput) -> ContributionClass {
+        let ledger_result = apply_harvest_to_ledger(&mut self.ledger, input);
+        let result = match self.record_history(input.player_id, realm_id, input) {
+            Some(result) => result,
+            None => {
+                // Without the log, fall back to the running mean so the class still moves.
+                self.store.absorb(input.player_id, ledger_result.score);
+                ledger_result
+            }
+        };
+        self.dirty = true;
+        // Best-effort immediate persist after significant events (Pass B still allows tick flush)
+        let _ = self.persist_force();
+        result.class
+    }
+
+    fn class_of(&self, player_id: u64) -> ContributionClass {
+        if self.history.has_history(player_id) {
+            return self.history.class_of(player_id);
+        }
+        if self.ledger.sample_count(player_id) > 0 {
+            return self.ledger.class_of(player_id);
+        }
+        self.store.class_of(player_id)
+    }
+
+    /// Append to the history log and mirror its decay-window result into the record.
+    fn record_history(&mut self, player_id: u64, realm_id: RealmId, input: &HarvestNevcInput) -> Option<NevcResult> {
+        match self.history.record(player_id, realm_id, harvest_to_sample(input), now_secs()) {
+            Ok(outcome) => {
+                if let Some(t) = &outcome.transition {
+                    tracing::info!("[NEVC] Player {} {:?} -> {:?} (score {:.4})", player_id, t.from, t.to, t.score);
+                }
+                self.store.get_mut(player_id).apply_result(&outcome.result);
+                Some(outcome.result)
+            }
+            Err(e) => {
+                tracing::error!("[NEVC] History append failed for player {}: {}", player_id, e);
+                None
+            }
+        }
+    }
+
+    fn maintain_history_if_due(&mut self) {
+        if self.last_history_maintenance.elapsed() < HISTORY_MAINTENANCE_INTERVAL {
+            return;
+        }
+        self.last_history_maintenance = Instant::now();
+        let now = now_secs();
+        match self.history.refresh_all(now) {
+            Ok(decayed) => {
+                for (player_id, _) in &decayed {
+                    let result = self.history.score_at(*player_id, now);
+                    self.store.get_mut(*player_id).apply_result(&result);
+                }
+                self.dirty |= !decayed.is_empty();
+            }
+            Err(e) => tracing::error!("[NEVC] History refresh failed: {}", e),
+        }
+        if let Err(e) = self.history.compact(now) {
+            tracing::error!("[NEVC] History compaction failed: {}", e);
+        }
+    }
+
     fn persist_force(&mut self) -> Result<(), String> {
-        self.store.save_to_file(&default_persist_path())?;
+        self.store.save_to_file(&self.persist_path)?;
         self.last_persist = Instant::now();
         self.dirty = false;
         Ok(())
@@ -53,27 +142,40 @@ fn default_persist_path() -> PathBuf {
     PathBuf::from("data/nevc_players.json")
 }
 
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 fn global_state() -> &'static Mutex<ServerNevcState> {
     static STATE: OnceLock<Mutex<ServerNevcState>> = OnceLock::new();
     STATE.get_or_init(|| Mutex::new(ServerNevcState::new()))
 }
 
-/// Public hook for harvest handlers.
+/// Public hook for harvest handlers (home realm).
 pub fn on_harvest(
     player_id: u64,
     success: bool,
     was_sustainable: bool,
     regen_participation: bool,
+) -> ContributionClass {
+    on_harvest_in_realm(player_id, HOME_REALM, success, was_sustainable, regen_participation)
+}
+
+/// Harvest hook with the realm it happened in (feeds per-realm rankings).
+pub fn on_harvest_in_realm(
+    player_id: u64,
+    realm_id: RealmId,
+    success: bool,
+    was_sustainable: bool,
+    regen_participation: bool,
 ) -> ContributionClass {
     let input =
         HarvestNevcInput::from_harvest(player_id, success, was_sustainable, regen_participation);
     if let Ok(mut state) = global_state().lock() {
-        let result = apply_harvest_to_ledger(&mut state.ledger, &input);
-        state.store.absorb(player_id, result.score);
-        state.dirty = true;
-        // Best-effort immediate persist after significant events (Pass B still allows tick flush)
-        let _ = state.persist_force();
-        result.class
+        state.harvest(realm_id, &input)
     } else {
         let mut tmp = ContributionLedger::new();
         apply_harvest_class(&mut tmp, &input)
@@ -81,19 +183,22 @@ pub fn on_harvest(
 }
 
 pub fn player_contribution_class(player_id: u64) -> ContributionClass {
-    if let Ok(state) = global_state().lock() {
-        if state.ledger.sample_count(player_id) > 0 {
-            return state.ledger.class_of(player_id);
-        }
-        return state.store.class_of(player_id);
-    }
-    ContributionClass::ZombiePartition
+    global_state().lock().map(|s| s.class_of(player_id)).unwrap_or(ContributionClass::ZombiePartition)
 }
 
 pub fn is_contributor(player_id: u64) -> bool {
     player_contribution_class(player_id).is_contributor()
 }
 
+/// Council ballot attunement (0.0–1.0): a contributor's running NEVC score,
+/// 0.0 while partitioned or before any samples.
+pub fn council_attunement(player_id: u64) -> f32 {
+    if !is_contributor(player_id) {
+        return 0.0;
+    }
+    record_for(player_id).map_or(0.0, |r| r.score.clamp(0.0, 1.0) as f32)
+}
+
 /// Force durable flush (shutdown / explicit save).
 pub fn persist_now() -> Result<(), String> {
     let mut state = global_state().lock().map_err(|e| e.to_string())?;
@@ -103,13 +208,28 @@ pub fn persist_now() -> Result<(), String> {
 /// Call from server main loop / periodic tick (throttled).
 pub fn tick_persist() {
     if let Ok(mut state) = global_state().lock() {
+        state.maintain_history_if_due();
         state.persist_if_due();
     }
 }
 
+/// Why a player's class changed: transitions in `range` (unix seconds), oldest first.
+pub fn class_transitions(player_id: u64, range: std::ops::Range<u64>) -> Vec<ClassTransition> {
+    global_state().lock().map(|s| s.history.class_transitions(player_id, range)).unwrap_or_default()
+}
+
+/// Decay-window score every `step_secs` through `range`.
+pub fn contribution_timeline(player_id: u64, range: std::ops::Range<u64>, step_secs: u64) -> Vec<TimelinePoint> {
+    global_state().lock().map(|s| s.history.timeline(player_id, range, step_secs)).unwrap_or_default()
+}
+
+pub fn top_contributors(realm_id: RealmId, range: std::ops::Range<u64>, limit: usize) -> Vec<(u64, NevcResult)> {
+    global_state().lock().map(|s| s.history.top_contributors(realm_id, range, limit)).unwrap_or_default()
+}
+
 pub fn reload_from_disk() {
     if let Ok(mut state) = global_state().lock() {
-        if let Ok(loaded) = NevcPersistenceStore::load_from_file(&default_persist_path()) {
+        if let Ok(loaded) = NevcPersistenceStore::load_from_file(&state.persist_path) {
             state.store = loaded;
             state.dirty = false;
         }
@@ -141,10 +261,10 @@ pub fn restore_record(record: NevcPlayerRecord) {
 
 pub fn absorb_score(player_id: u64, score: f64) -> ContributionClass {
     if let Ok(mut state) = global_state().lock() {
-        let rec = state.store.absorb(player_id, score);
+        let class = state.store.absorb(player_id, score).class;
         state.dirty = true;
         let _ = state.persist_force();
-        rec.class
+        class
     } else {
         ContributionClass::from_score(score)
     }
@@ -169,6 +289,29 @@ mod tests {
         assert!(!c.is_contributor());
     }
 
+    #[test]
+    fn harvests_land_in_the_history_log() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let open = || NevcHistory::open(&dir.join("history"), NevcHistoryConfig::default()).unwrap();
+        let mut state = ServerNevcState::with_history(open(), dir.join("players.json"));
+        let pid = 42;
+
+        let harvest = |state: &mut ServerNevcState, sustainable: bool| {
+            state.harvest(3, &HarvestNevcInput::from_harvest(pid, true, sustainable, sustainable))
+        };
+        assert!(harvest(&mut state, true).is_contributor());
+        assert!(!harvest(&mut state, false).is_contributor());
+        assert!(!state.class_of(pid).is_contributor());
+        assert!(!state.store.get(pid).unwrap().is_contributor());
+
+        // Both transitions are in the log on disk, with the realm that caused them.
+        let transitions = open().class_transitions(pid, 0..u64::MAX);
+        assert_eq!(transitions.len(), 2);
+        assert!(transitions[1].cause.as_ref().is_some_and(|(realm, _)| *realm == 3));
+        assert!(NevcPersistenceStore::load_from_file(&dir.join("players.json")).unwrap().get(pid).is_some());
+    }
+
     #[test]
     fn record_for_after_harvest() {
         let pid = 9_103_u64;
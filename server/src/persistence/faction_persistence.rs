@@ -82,6 +82,10 @@ impl PlayerIdMapping {
     pub fn contains(&self, player_id: u64) -> bool {
         self.id_to_entity.contains_key(&player_id)
     }
+
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.id_to_entity.values().copied()
+    }
 }
 
 // ============================================================================
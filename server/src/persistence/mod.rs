@@ -7,3 +7,6 @@
  */
 
 pub mod faction_persistence;
+pub mod player_store;
+
+pub use player_store::{FilePlayerStore, InMemoryPlayerStore, PlayerStore, RecoveryReport, StoreError};
@@ -0,0 +1,481 @@
+/*!
+ * server/src/persistence/player_store.rs
+ *
+ * Durable storage backends for PlayerSaveData behind the PlayerStore trait.
+ *
+ * - InMemoryPlayerStore: sovereign dev / unit tests (no disk).
+ * - FilePlayerStore: one JSON file per player, written atomically
+ *   (temp file + fsync + rename), fronted by a write-ahead journal so a
+ *   crash mid-batch is either replayed in full or discarded in full.
+ *
+ * Every record carries a SHA-256 content hash (PlayerSaveData::content_hash);
+ * loads refuse records whose hash does not match.
+ *
+ * AG-SML v1.0 | TOLC 8 | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::persistence_polish::PlayerSaveData;
+
+const PLAYERS_DIR: &str = "players";
+const JOURNAL_FILE: &str = "journal.wal";
+const TEMP_EXTENSION: &str = "tmp";
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug)]
+pub enum StoreError {
+    Io(io::Error),
+    Serialize(String),
+    /// Record exists on disk but failed integrity verification.
+    Corrupt { player_id: u64, reason: String },
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Io(e) => write!(f, "player store I/O error: {}", e),
+            StoreError::Serialize(e) => write!(f, "player store serialization error: {}", e),
+            StoreError::Corrupt { player_id, reason } => {
+                write!(f, "player {} record corrupt: {}", player_id, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<io::Error> for StoreError {
+    fn from(e: io::Error) -> Self {
+        StoreError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(e: serde_json::Error) -> Self {
+        StoreError::Serialize(e.to_string())
+    }
+}
+
+// ============================================================================
+// Trait
+// ============================================================================
+
+/// Storage backend for authoritative player saves.
+///
+/// `save_batch` is all-or-nothing with respect to crashes: after recovery
+/// either every record of the batch is visible or none is.
+pub trait PlayerStore: Send + Sync {
+    fn load(&self, player_id: u64) -> Result<Option<PlayerSaveData>, StoreError>;
+
+    fn save_batch(&self, batch: &[PlayerSaveData]) -> Result<(), StoreError>;
+
+    fn save(&self, data: &PlayerSaveData) -> Result<(), StoreError> {
+        self.save_batch(std::slice::from_ref(data))
+    }
+
+    fn player_ids(&self) -> Result<Vec<u64>, StoreError>;
+}
+
+// ============================================================================
+// In-memory backend
+// ============================================================================
+
+#[derive(Default)]
+pub struct InMemoryPlayerStore {
+    players: RwLock<HashMap<u64, PlayerSaveData>>,
+}
+
+impl InMemoryPlayerStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PlayerStore for InMemoryPlayerStore {
+    fn load(&self, player_id: u64) -> Result<Option<PlayerSaveData>, StoreError> {
+        Ok(self.players.read().unwrap().get(&player_id).cloned())
+    }
+
+    fn save_batch(&self, batch: &[PlayerSaveData]) -> Result<(), StoreError> {
+        check_finite(batch)?;
+        let mut players = self.players.write().unwrap();
+        for data in batch {
+            players.insert(data.player_id, data.clone());
+        }
+        Ok(())
+    }
+
+    fn player_ids(&self) -> Result<Vec<u64>, StoreError> {
+        Ok(self.players.read().unwrap().keys().copied().collect())
+    }
+}
+
+// ============================================================================
+// File backend (atomic writes + write-ahead journal)
+// ============================================================================
+
+/// One journal line. `digest` covers the serialized `records` so a torn
+/// trailing write is detected and discarded on recovery.
+#[derive(Serialize, Deserialize)]
+struct JournalRecord {
+    seq: u64,
+    digest: String,
+    records: Vec<PlayerSaveData>,
+}
+
+/// What `FilePlayerStore::open` found and repaired on startup.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecoveryReport {
+    pub replayed_batches: usize,
+    pub replayed_records: usize,
+    /// A trailing journal entry was incomplete or failed its digest.
+    pub discarded_torn_tail: bool,
+    pub removed_temp_files: usize,
+}
+
+struct JournalWriter {
+    file: File,
+    next_seq: u64,
+}
+
+pub struct FilePlayerStore {
+    root: PathBuf,
+    journal: Mutex<JournalWriter>,
+    recovery: RecoveryReport,
+}
+
+impl FilePlayerStore {
+    /// Open (or create) a store rooted at `root`, running crash recovery first.
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(root.join(PLAYERS_DIR))?;
+
+        let recovery = recover(&root)?;
+        if recovery != RecoveryReport::default() {
+            info!(
+                "[Persistence] Recovery at {}: replayed {} batch(es) / {} record(s), torn_tail={}, temp_removed={}",
+                root.display(),
+                recovery.replayed_batches,
+                recovery.replayed_records,
+                recovery.discarded_torn_tail,
+                recovery.removed_temp_files
+            );
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(root.join(JOURNAL_FILE))?;
+
+        Ok(Self {
+            root,
+            journal: Mutex::new(JournalWriter { file, next_seq: 1 }),
+            recovery,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn recovery_report(&self) -> &RecoveryReport {
+        &self.recovery
+    }
+
+    fn player_path(&self, player_id: u64) -> PathBuf {
+        player_path(&self.root, player_id)
+    }
+}
+
+impl PlayerStore for FilePlayerStore {
+    fn load(&self, player_id: u64) -> Result<Option<PlayerSaveData>, StoreError> {
+        let path = self.player_path(player_id);
+        let bytes = match fs::read(&path) {
+            Ok(b) => b,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let data: PlayerSaveData = serde_json::from_slice(&bytes).map_err(|e| StoreError::Corrupt {
+            player_id,
+            reason: e.to_string(),
+        })?;
+
+        if data.player_id != player_id {
+            return Err(StoreError::Corrupt {
+                player_id,
+                reason: format!("file holds player {}", data.player_id),
+            });
+        }
+        if !data.is_checksum_valid() {
+            return Err(StoreError::Corrupt {
+                player_id,
+                reason: "content hash mismatch".to_string(),
+            });
+        }
+        Ok(Some(data))
+    }
+
+    fn save_batch(&self, batch: &[PlayerSaveData]) -> Result<(), StoreError> {
+        check_finite(batch)?;
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        let mut journal = self.journal.lock().unwrap();
+
+        // 1. Journal the whole batch and make it durable.
+        let records = batch.to_vec();
+        let line = serde_json::to_string(&JournalRecord {
+            seq: journal.next_seq,
+            digest: records_digest(&records)?,
+            records,
+        })?;
+        journal.file.write_all(line.as_bytes())?;
+        journal.file.write_all(b"\n")?;
+        journal.file.sync_data()?;
+        journal.next_seq += 1;
+
+        // 2. Apply each record atomically.
+        for data in batch {
+            let bytes = serde_json::to_vec_pretty(data)?;
+            write_atomic(&self.player_path(data.player_id), &bytes)?;
+        }
+
+        // 3. Checkpoint: everything journaled is now applied.
+        journal.file.set_len(0)?;
+        journal.file.sync_data()?;
+        Ok(())
+    }
+
+    fn player_ids(&self) -> Result<Vec<u64>, StoreError> {
+        let mut ids = Vec::new();
+        for entry in fs::read_dir(self.root.join(PLAYERS_DIR))? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(id) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse::<u64>().ok())
+            {
+                ids.push(id);
+            }
+        }
+        ids.sort_unstable();
+        Ok(ids)
+    }
+}
+
+// ============================================================================
+// Recovery + helpers
+// ============================================================================
+
+fn player_path(root: &Path, player_id: u64) -> PathBuf {
+    root.join(PLAYERS_DIR).join(format!("{}.json", player_id))
+}
+
+/// Replay every intact journal batch, drop a torn tail, clear stale temp files,
+/// then truncate the journal.
+fn recover(root: &Path) -> Result<RecoveryReport, StoreError> {
+    let mut report = RecoveryReport::default();
+
+    for entry in fs::read_dir(root.join(PLAYERS_DIR))? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) == Some(TEMP_EXTENSION) {
+            fs::remove_file(&path)?;
+            report.removed_temp_files += 1;
+        }
+    }
+
+    let journal_path = root.join(JOURNAL_FILE);
+    let file = match File::open(&journal_path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(report),
+        Err(e) => return Err(e.into()),
+    };
+
+    for line in BufReader::new(file).lines() {
+        let line = match line {
+            Ok(l) if l.trim().is_empty() => continue,
+            Ok(l) => l,
+            Err(_) => {
+                report.discarded_torn_tail = true;
+                break;
+            }
+        };
+
+        let record: JournalRecord = match serde_json::from_str(&line) {
+            Ok(r) => r,
+            Err(_) => {
+                report.discarded_torn_tail = true;
+                break;
+            }
+        };
+        if records_digest(&record.records)? != record.digest {
+            warn!("[Persistence] Journal batch {} failed digest; discarding tail", record.seq);
+            report.discarded_torn_tail = true;
+            break;
+        }
+
+        for data in &record.records {
+            let bytes = serde_json::to_vec_pretty(data)?;
+            write_atomic(&player_path(root, data.player_id), &bytes)?;
+        }
+        report.replayed_batches += 1;
+        report.replayed_records += record.records.len();
+    }
+
+    let journal = OpenOptions::new().write(true).open(&journal_path)?;
+    journal.set_len(0)?;
+    journal.sync_all()?;
+    Ok(report)
+}
+
+/// Refuse records JSON cannot carry back exactly; checked before anything is journaled.
+fn check_finite(batch: &[PlayerSaveData]) -> Result<(), StoreError> {
+    for data in batch {
+        if let Some(field) = data.non_finite_field() {
+            return Err(StoreError::Serialize(format!("player {} {} is not a finite number", data.player_id, field)));
+        }
+    }
+    Ok(())
+}
+
+fn records_digest(records: &[PlayerSaveData]) -> Result<String, StoreError> {
+    let bytes = serde_json::to_vec(records)?;
+    Ok(to_hex(&Sha256::digest(&bytes)))
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Write to a sibling temp file, fsync, rename over the target, fsync the directory.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension(TEMP_EXTENSION);
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path)?;
+    sync_parent_dir(path)
+}
+
+#[cfg(unix)]
+pub(crate) fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(dir) => File::open(dir)?.sync_all(),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sealed(player_id: u64, abundance: f64) -> PlayerSaveData {
+        let mut data = PlayerSaveData::new(player_id);
+        data.abundance = abundance;
+        data.seal();
+        data
+    }
+
+    #[test]
+    fn file_store_roundtrip_survives_reopen() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        {
+            let store = FilePlayerStore::open(dir).unwrap();
+            store.save_batch(&[sealed(1, 10.0), sealed(2, 20.0)]).unwrap();
+        }
+        let store = FilePlayerStore::open(dir).unwrap();
+        assert_eq!(store.player_ids().unwrap(), vec![1, 2]);
+        assert_eq!(store.load(2).unwrap().unwrap().abundance, 20.0);
+        assert!(store.load(3).unwrap().is_none());
+    }
+
+    #[test]
+    fn committed_journal_batch_is_replayed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join(PLAYERS_DIR)).unwrap();
+
+        // Simulate a crash after the journal fsync but before any apply.
+        let records = vec![sealed(7, 70.0)];
+        let line = serde_json::to_string(&JournalRecord {
+            seq: 1,
+            digest: records_digest(&records).unwrap(),
+            records,
+        })
+        .unwrap();
+        fs::write(dir.join(JOURNAL_FILE), format!("{}\n{{\"seq\":2,\"dig", line)).unwrap();
+        fs::write(dir.join(PLAYERS_DIR).join("7.tmp"), b"partial").unwrap();
+
+        let store = FilePlayerStore::open(dir).unwrap();
+        let report = store.recovery_report();
+        assert_eq!(report.replayed_batches, 1);
+        assert!(report.discarded_torn_tail);
+        assert_eq!(report.removed_temp_files, 1);
+        assert_eq!(store.load(7).unwrap().unwrap().abundance, 70.0);
+        assert_eq!(fs::metadata(dir.join(JOURNAL_FILE)).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn tampered_record_is_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let store = FilePlayerStore::open(dir).unwrap();
+        store.save(&sealed(5, 50.0)).unwrap();
+
+        let path = dir.join(PLAYERS_DIR).join("5.json");
+        let mut data: PlayerSaveData = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        data.abundance = 9_999.0;
+        fs::write(&path, serde_json::to_vec(&data).unwrap()).unwrap();
+
+        assert!(matches!(store.load(5), Err(StoreError::Corrupt { player_id: 5, .. })));
+    }
+
+    #[test]
+    fn hashed_floats_survive_a_reload_and_non_finite_is_refused() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let store = FilePlayerStore::open(dir).unwrap();
+        // Values whose shortest decimal form needs exact parsing to land on the same bits.
+        let tricky = [0.1 + 0.2, 1240.0 / 7.0, f64::MIN_POSITIVE * 3.0, 9_007_199_254_740_993.0, 2.0f64.sqrt() * 1e-300];
+        for (i, abundance) in tricky.iter().enumerate() {
+            store.save(&sealed(20 + i as u64, *abundance)).unwrap();
+            let loaded = store.load(20 + i as u64).unwrap().unwrap();
+            assert_eq!(loaded.abundance.to_bits(), abundance.to_bits());
+        }
+
+        let mut bad = sealed(30, f64::NAN);
+        assert!(matches!(store.save(&bad), Err(StoreError::Serialize(_))));
+        bad.abundance = 1.0;
+        bad.valence = f32::INFINITY;
+        bad.seal();
+        assert!(matches!(store.save(&bad), Err(StoreError::Serialize(_))));
+        assert!(store.load(30).unwrap().is_none());
+    }
+}
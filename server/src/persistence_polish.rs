@@ -3,14 +3,30 @@
  * v19.3.5 — Full authoritative PersistenceManager + PlayerSaveData with 40-slot inventory + 8-slot hotbar.
  * Robust load/save, checksum, swap methods for hotbar + general inventory.
  * Recovered and polished from prior iterations. All valuable prior logic (faction, abundance, valence) preserved + extended.
+ * v19.3.6 — PersistenceManager now fronts a durable PlayerStore (see persistence/player_store.rs);
+ *   SHA-256 content hash replaces the additive checksum; batch flush path for BatchPersistenceQueue.
+ * v19.3.7 — Loaded saves are checked against the shared item registry; unresolvable slots are logged.
+ * v19.3.8 — Non-finite floats are refused on save; serde_json float_roundtrip keeps hashed f64 bits exact.
+ * v19.3.9 — held / take_items / give_items: the stack moves crafting and trade escrow share.
+ * v19.3.10 — Cache check, store load and insert happen under one lock (no stale reinsert).
  * AG-SML v1.0 | TOLC 8 + RBE + PATSAGi Mercy Gates | Ra-Thor aligned
  */
 
+use bevy::prelude::Resource;
+use shared::items::{self, ItemRegistry};
 use shared::protocol::HotbarSlot;
 use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
 use tracing::{info, warn};
 
+use crate::persistence::player_store::{to_hex, FilePlayerStore, InMemoryPlayerStore, PlayerStore, StoreError};
+
+/// Valence of a fresh save (and of players with no save yet).
+pub const DEFAULT_VALENCE: f32 = 0.8;
+
 /// Full player persistent state (authoritative source of truth).
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PlayerSaveData {
@@ -21,7 +37,20 @@ pub struct PlayerSaveData {
     pub hotbar: [HotbarSlot; 8],
     /// Full general inventory (40 slots)
     pub inventory: [HotbarSlot; 40],
-    pub last_checksum: u64,
+    /// Hex SHA-256 over the canonical content (see `content_hash`).
+    pub last_checksum: String,
+    #[serde(default)]
+    pub council_participation_count: u32,
+    #[serde(default)]
+    pub council_bloom_count: u32,
+    #[serde(default)]
+    pub last_council_bloom_tick: u64,
+    #[serde(default)]
+    pub peak_collective_attunement: f32,
+    /// Trade ledger seq of the last escrow change applied to this save, so a
+    /// replayed intent is applied at most once.
+    #[serde(default)]
+    pub escrow_seq: u64,
     // Extendable: epiphany_count, council_bloom_score, etc.
 }
 
@@ -30,11 +59,10 @@ impl PlayerSaveData {
         Self {
             player_id,
             abundance: 1240.0,
-            valence: 0.8,
+            valence: DEFAULT_VALENCE,
             faction_standings: HashMap::new(),
             hotbar: [HotbarSlot::empty(); 8],
             inventory: [HotbarSlot::empty(); 40],
-            last_checksum: 0,
             ..Default::default()
         }
     }
@@ -66,47 +94,274 @@ impl PlayerSaveData {
         }
     }
 
-    fn recompute_checksum(&mut self) {
-        // Simple stable checksum for integrity (production: use proper hash)
-        let mut sum: u64 = self.player_id;
-        for slot in &self.hotbar {
-            sum = sum.wrapping_add(slot.item_id as u64).wrapping_add(slot.count as u64);
+    pub fn record_council_participation(&mut self) {
+        self.council_participation_count = self.council_participation_count.saturating_add(1);
+        self.recompute_checksum();
+    }
+
+    pub fn record_successful_council_bloom(&mut self, collective_attunement: f32, tick: u64) {
+        self.council_bloom_count = self.council_bloom_count.saturating_add(1);
+        self.last_council_bloom_tick = tick;
+        self.peak_collective_attunement = self.peak_collective_attunement.max(collective_attunement);
+        self.recompute_checksum();
+    }
+
+    /// SHA-256 over every persisted field except the checksum itself.
+    /// Faction standings are hashed in key order so the digest is stable.
+    pub fn content_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.player_id.to_le_bytes());
+        hasher.update(self.abundance.to_bits().to_le_bytes());
+        hasher.update(self.valence.to_bits().to_le_bytes());
+
+        let mut standings: Vec<_> = self.faction_standings.iter().collect();
+        standings.sort_by(|a, b| a.0.cmp(b.0));
+        hasher.update((standings.len() as u64).to_le_bytes());
+        for (faction, standing) in standings {
+            hasher.update((faction.len() as u64).to_le_bytes());
+            hasher.update(faction.as_bytes());
+            hasher.update(standing.to_bits().to_le_bytes());
+        }
+
+        for slot in self.hotbar.iter().chain(self.inventory.iter()) {
+            hasher.update(slot.item_id.to_le_bytes());
+            hasher.update(slot.count.to_le_bytes());
+            hasher.update(slot.valence.to_bits().to_le_bytes());
+        }
+
+        hasher.update(self.council_participation_count.to_le_bytes());
+        hasher.update(self.council_bloom_count.to_le_bytes());
+        hasher.update(self.last_council_bloom_tick.to_le_bytes());
+        hasher.update(self.peak_collective_attunement.to_bits().to_le_bytes());
+        // Saves that never traded keep the checksum they were written with.
+        if self.escrow_seq != 0 {
+            hasher.update(self.escrow_seq.to_le_bytes());
+        }
+
+        to_hex(&hasher.finalize())
+    }
+
+    /// Slots whose item the registry cannot resolve (unknown id or over its stack limit),
+    /// as `hotbar[i]: reason` / `inventory[i]: reason`.
+    pub fn unresolved_slots(&self, registry: &ItemRegistry) -> Vec<String> {
+        let hotbar = self.hotbar.iter().enumerate().map(|(i, s)| ("hotbar", i, s));
+        let inventory = self.inventory.iter().enumerate().map(|(i, s)| ("inventory", i, s));
+        hotbar
+            .chain(inventory)
+            .filter_map(|(area, i, slot)| registry.check_slot(slot).err().map(|e| format!("{}[{}]: {}", area, i, e)))
+            .collect()
+    }
+
+    /// First float field that is NaN or infinite. JSON cannot carry those (NaN is
+    /// written as `null`), so such a record could never be read back.
+    pub fn non_finite_field(&self) -> Option<String> {
+        if !self.abundance.is_finite() {
+            return Some("abundance".into());
+        }
+        if !self.valence.is_finite() {
+            return Some("valence".into());
+        }
+        if !self.peak_collective_attunement.is_finite() {
+            return Some("peak_collective_attunement".into());
+        }
+        if let Some(faction) = self.faction_standings.iter().find(|(_, v)| !v.is_finite()).map(|(k, _)| k) {
+            return Some(format!("faction_standings[{}]", faction));
+        }
+        let hotbar = self.hotbar.iter().enumerate().map(|(i, s)| ("hotbar", i, s));
+        let inventory = self.inventory.iter().enumerate().map(|(i, s)| ("inventory", i, s));
+        hotbar
+            .chain(inventory)
+            .find(|(_, _, slot)| !slot.valence.is_finite())
+            .map(|(area, i, _)| format!("{}[{}].valence", area, i))
+    }
+
+    /// Units of `item_id` across hotbar and inventory.
+    pub fn held(&self, item_id: u32) -> u64 {
+        self.hotbar
+            .iter()
+            .chain(self.inventory.iter())
+            .filter(|s| s.count > 0 && s.item_id == item_id)
+            .map(|s| s.count as u64)
+            .sum()
+    }
+
+    /// Remove `count` of `item_id`, inventory before hotbar. The caller checked `held`.
+    pub fn take_items(&mut self, item_id: u32, count: u64) {
+        let mut left = count;
+        for slot in self.inventory.iter_mut().chain(self.hotbar.iter_mut()) {
+            if left == 0 {
+                break;
+            }
+            if slot.count == 0 || slot.item_id != item_id {
+                continue;
+            }
+            let taken = (slot.count as u64).min(left);
+            slot.count -= taken as u32;
+            left -= taken;
+            if slot.count == 0 {
+                *slot = HotbarSlot::empty();
+            }
+        }
+    }
+
+    /// Add `count` of `item_id` to the inventory in stacks of at most `stack_limit`,
+    /// topping up existing stacks before empty slots. False when it does not fit;
+    /// `self` is then partly changed, so callers work on a copy.
+    pub fn give_items(&mut self, item_id: u32, count: u64, stack_limit: u32) -> bool {
+        let valence = self.valence;
+        let mut left = count;
+        for slot in self.inventory.iter_mut().filter(|s| s.count > 0 && s.item_id == item_id) {
+            let added = ((stack_limit.saturating_sub(slot.count)) as u64).min(left);
+            slot.count += added as u32;
+            left -= added;
         }
-        for slot in &self.inventory {
-            sum = sum.wrapping_add(slot.item_id as u64).wrapping_add(slot.count as u64);
+        for slot in self.inventory.iter_mut().filter(|s| s.count == 0) {
+            if left == 0 {
+                break;
+            }
+            let added = (stack_limit as u64).min(left);
+            *slot = HotbarSlot::new(item_id, added as u32, valence);
+            left -= added;
         }
-        self.last_checksum = sum;
+        left == 0
+    }
+
+    pub fn is_checksum_valid(&self) -> bool {
+        self.last_checksum == self.content_hash()
+    }
+
+    /// Refresh the checksum after direct field edits (handlers that mutate slots in place).
+    pub fn seal(&mut self) {
+        self.recompute_checksum();
+    }
+
+    fn recompute_checksum(&mut self) {
+        self.last_checksum = self.content_hash();
     }
 }
 
-/// Production-ready PersistenceManager (in-memory for sovereign dev; swap to DB/file later).
-#[derive(Resource, Default)]
+/// Authoritative PersistenceManager: a write-through cache in front of a durable PlayerStore.
+/// Cheap to clone (shared state), so async flush tasks can hold their own handle.
+/// `Default` is in-memory for sovereign dev; use `open_durable` on real servers.
+#[derive(Resource, Clone)]
 pub struct PersistenceManager {
-    players: HashMap<u64, PlayerSaveData>,
+    store: Arc<dyn PlayerStore>,
+    cache: Arc<RwLock<HashMap<u64, PlayerSaveData>>>,
+}
+
+impl Default for PersistenceManager {
+    fn default() -> Self {
+        Self::with_store(Arc::new(InMemoryPlayerStore::new()))
+    }
 }
 
 impl PersistenceManager {
+    pub fn with_store(store: Arc<dyn PlayerStore>) -> Self {
+        Self {
+            store,
+            cache: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Open the file-backed store at `root` (runs journal crash recovery first).
+    pub fn open_durable(root: impl AsRef<Path>) -> Result<Self, String> {
+        let store = FilePlayerStore::open(root).map_err(|e| e.to_string())?;
+        Ok(Self::with_store(Arc::new(store)))
+    }
+
     pub fn load_player(&mut self, player_id: u64) -> Option<PlayerSaveData> {
-        if let Some(data) = self.players.get(&player_id) {
-            Some(data.clone())
-        } else {
-            let new_data = PlayerSaveData::new(player_id);
-            self.players.insert(player_id, new_data.clone());
-            Some(new_data)
+        match self.load_or_create(player_id) {
+            Ok(data) => Some(data),
+            Err(e) => {
+                warn!("[Persistence] Load failed for player {}: {}", player_id, e);
+                None
+            }
         }
     }
 
     pub fn save_player(&mut self, data: &PlayerSaveData) -> Result<(), String> {
-        self.players.insert(data.player_id, data.clone());
-        info!("[Persistence] Saved player {} (abundance={:.1}, checksum={})", data.player_id, data.abundance, data.last_checksum);
+        let sealed = self.write_through(std::slice::from_ref(data))?;
+        if let Some(saved) = sealed.first() {
+            info!("[Persistence] Saved player {} (abundance={:.1}, checksum={})", saved.player_id, saved.abundance, saved.last_checksum);
+        }
         Ok(())
     }
 
     pub fn get_player_abundance(&self, player_id: u64) -> Option<f64> {
-        self.players.get(&player_id).map(|p| p.abundance)
+        if let Some(p) = self.cache.read().unwrap().get(&player_id) {
+            return Some(p.abundance);
+        }
+        self.store.load(player_id).ok().flatten().map(|p| p.abundance)
     }
 
-    // Future: get_player_faction_standing, batch operations, etc.
+    pub fn get_player_valence(&self, player_id: u64) -> Option<f32> {
+        if let Some(p) = self.cache.read().unwrap().get(&player_id) {
+            return Some(p.valence);
+        }
+        self.store.load(player_id).ok().flatten().map(|p| p.valence)
+    }
+
+    /// Async load used by faction autosave + council batch flush.
+    /// Creates a fresh record for unknown players; errors on corrupt records.
+    pub async fn load_player_data(&self, player_id: u64) -> Result<PlayerSaveData, String> {
+        self.load_or_create(player_id).map_err(|e| e.to_string())
+    }
+
+    /// Async save used by faction autosave + council batch flush. Reseals `data` in place.
+    pub async fn save_player_data(&self, data: &mut PlayerSaveData) -> Result<(), String> {
+        data.seal();
+        self.write_through(std::slice::from_ref(data)).map(|_| ())
+    }
+
+    /// Persist many players as one journaled, crash-atomic batch.
+    pub fn flush_batch(&self, batch: &[PlayerSaveData]) -> Result<(), String> {
+        self.write_through(batch).map(|_| ())
+    }
+
+    /// Check, load and insert under one write lock, so a concurrent save can't be
+    /// overwritten in the cache by the older copy read from the store.
+    fn load_or_create(&self, player_id: u64) -> Result<PlayerSaveData, StoreError> {
+        let mut cache = self.cache.write().unwrap();
+        if let Some(data) = cache.get(&player_id) {
+            return Ok(data.clone());
+        }
+        let data = match self.store.load(player_id)? {
+            Some(data) => {
+                // Kept as saved (items can return with a data fix); inventory moves refuse these slots.
+                let unresolved = data.unresolved_slots(&items::current());
+                if !unresolved.is_empty() {
+                    warn!("[Persistence] Player {} save holds unresolvable items: {}", player_id, unresolved.join("; "));
+                }
+                data
+            }
+            None => {
+                let mut fresh = PlayerSaveData::new(player_id);
+                fresh.seal();
+                fresh
+            }
+        };
+        cache.insert(player_id, data.clone());
+        Ok(data)
+    }
+
+    fn write_through(&self, batch: &[PlayerSaveData]) -> Result<Vec<PlayerSaveData>, String> {
+        let sealed: Vec<PlayerSaveData> = batch
+            .iter()
+            .map(|d| {
+                let mut d = d.clone();
+                d.seal();
+                d
+            })
+            .collect();
+
+        // Held across the store write: the cache never lags a save another thread can see.
+        let mut cache = self.cache.write().unwrap();
+        self.store.save_batch(&sealed).map_err(|e| e.to_string())?;
+        for data in &sealed {
+            cache.insert(data.player_id, data.clone());
+        }
+        Ok(sealed)
+    }
 }
 
 // End of persistence_polish.rs v19.3.5 — Full robust persistence with inventory/hotbar swaps + checksum. Thunder locked in. Yoi ⚡
\ No newline at end of file
@@ -4,6 +4,11 @@
  * Powrush-MMO v19.3.1 — Central RBE Integration Layer
  * Wires Council Mercy Trial outcomes, epiphany resonance, biome influence,
  * faction diplomacy, and persistence into sovereign Resource-Based Economy flows.
+ * v19.3.2 — Abundance is distributed in need-weighted fair allocation rounds
+ *   (shared::fair_allocation): declared needs, NEVC class weights, council caps,
+ *   one audit record per player.
+ * v19.3.3 — Rounds run through the shared AbundanceRounds (abundance.rs): player-declared
+ *   needs and the council caps the co-host syncs in apply here too.
  *
  * PATSAGi Council + Ra-Thor Quantum Swarm aligned
  * AG-SML v1.0 | TOLC 8 + 7 Living Mercy Gates
@@ -13,10 +18,13 @@
 use bevy::prelude::*;
 use std::collections::HashMap;
 
+use shared::fair_allocation::AllocationRound;
+
 use crate::persistence_polish::PlayerSaveData;
 use crate::faction_diplomacy::{Faction, FactionDiplomacyManager};
 use simulation::epiphany_catalyst::EpiphanyOutcome;
 use crate::council_session_handler::CouncilTrialResolved;
+use crate::abundance::AbundanceRounds;
 
 /// Central RBE state resource — single source of truth for abundance distribution
 #[derive(Resource, Default)]
@@ -75,26 +83,31 @@ impl RBEState {
         }
     }
 
-    /// Advanced mercy-aligned distribution to multiple players with persistence
-    pub fn distribute_abundance_to_players(
+    /// Mercy-aligned distribution as one fair allocation round with persistence.
+    /// Participants who declared nothing claim an even share at Standard severity;
+    /// everyone with a pending declaration takes part in the round.
+    pub fn distribute_abundance_to_players<'a>(
         &mut self,
+        rounds: &'a mut AbundanceRounds,
         participants: &[u64],
         total_amount: f64,
         save_datas: &mut HashMap<u64, PlayerSaveData>,
-    ) {
-        if participants.is_empty() { return; }
-
-        let per_player = total_amount / participants.len() as f64;
-
-        for &pid in participants {
-            *self.player_contributions.entry(pid).or_insert(0.0) += per_player;
-
-            if let Some(save) = save_datas.get_mut(&pid) {
-                save.record_abundance_contribution(per_player);
+    ) -> Option<&'a AllocationRound> {
+        let round = match rounds.run_round(participants, total_amount) {
+            Ok(round) => round?,
+            Err(e) => {
+                warn!("[RBE] Abundance allocation round failed: {}", e);
+                return None;
+            }
+        };
+        for record in round.records.iter().filter(|r| r.granted > 0.0) {
+            *self.player_contributions.entry(record.player_id).or_insert(0.0) += record.granted;
+            if let Some(save) = save_datas.get_mut(&record.player_id) {
+                save.record_abundance_contribution(record.granted);
             }
         }
-
-        self.global_abundance_pool -= total_amount * 0.7;
+        self.global_abundance_pool -= round.distributed * 0.7;
+        Some(round)
     }
 
     /// Faction-specific abundance growth / decay simulation
@@ -117,6 +130,7 @@ impl RBEState {
 /// System: React to resolved Council Mercy Trials
 fn integrate_council_bloom_into_rbe(
     mut rbe: ResMut<RBEState>,
+    mut rounds: ResMut<AbundanceRounds>,
     mut resolved_events: EventReader<CouncilTrialResolved>,
 ) {
     for resolved in resolved_events.read() {
@@ -130,6 +144,7 @@ fn integrate_council_bloom_into_rbe(
             );
 
             rbe.distribute_abundance_to_players(
+                &mut rounds,
                 &participants,
                 (*mercy_score as f64) * 15.0,
                 &mut HashMap::new(),
@@ -178,6 +193,7 @@ pub struct RBEIntegrationPlugin;
 impl Plugin for RBEIntegrationPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<RBEState>()
+            .init_resource::<AbundanceRounds>()
             .add_systems(Update, (
                 integrate_council_bloom_into_rbe,
                 integrate_epiphany_into_rbe,
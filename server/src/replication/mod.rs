@@ -4,6 +4,8 @@
  * Powrush-MMO Replication Core
  * v20.10 | Added FACTION_MEMBERSHIP support alongside FACTION_STANDING.
  * v20.11 | Added CouncilBloom support to complete Council Bloom replication pipeline.
+ * v20.12 | Per-client delta snapshots (snapshot.rs); DirtyReplicationState tracks rotation.
+ * v20.13 | STATUS_EFFECT replicated as ServerMessage::StatusEffects (status_effects.rs).
  *
  * AG-SML v1.0 | TOLC 8
  * Thunder locked in. Yoi ⚡
@@ -16,6 +18,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::interest_management::{InterestManager, PlayerInterestState};
 
+pub mod snapshot;
+pub mod status_effects;
+pub use snapshot::{SnapshotReplicationPlugin, SnapshotReplicator};
+pub use status_effects::StatusEffectReplicator;
+
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
     pub struct ReplicatedFields: u32 {
@@ -41,6 +48,7 @@ bitflags! {
 pub struct DirtyReplicationState {
     pub dirty_mask: ReplicatedFields,
     pub last_position: Option<Vec3>,
+    pub last_rotation: Option<Quat>,
     pub last_velocity: Option<Vec3>,
     pub last_health: Option<f32>,
 }
@@ -73,7 +81,8 @@ pub enum ComponentType {
 pub enum UpdatePayload {
     Position { x: f32, y: f32, z: f32 },
     Health(f32),
-    RbeTransaction { resource_type: u8, amount: f32 },
+    /// `item_id` is the shared item registry's numeric id (same as `HotbarSlot.item_id`)
+    RbeTransaction { item_id: u32, amount: f32 },
     FactionStanding { faction_id: u64, standing: f32 },
     FactionMembership { faction_id: u64 },
     CouncilBloom(CouncilBloomPayload),           // NEW - completes Council Bloom replication
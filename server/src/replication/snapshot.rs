@@ -0,0 +1,400 @@
+/*!
+ * server/src/replication/snapshot.rs
+ *
+ * Powrush-MMO Per-Client Entity Snapshots
+ * v20.12 | Delta-compressed, interest-filtered EntitySnapshot over the live transport.
+ *
+ * Every replicated entity keeps the tick at which each field last changed
+ * (driven by DirtyReplicationState.dirty_mask). Every client keeps a short
+ * history of the snapshots sent to it and the last one it acked. A new
+ * snapshot carries, per visible entity, only the fields changed after the
+ * acked baseline's tick — or the whole entity when the client's baseline
+ * does not contain it. Unacked snapshots are never used as baselines, so
+ * packet loss costs bandwidth, never correctness.
+ *
+ * AG-SML v1.0 | TOLC 8
+ * Thunder locked in. Yoi ⚡
+ */
+
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use shared::protocol::{
+    pack_rotation, quantize_position, quantize_velocity, ClientMessage, ServerMessage, WireEntityDelta,
+    SNAPSHOT_FIELDS_ALL, SNAPSHOT_FIELD_HEALTH, SNAPSHOT_FIELD_POSITION, SNAPSHOT_FIELD_ROTATION,
+    SNAPSHOT_FIELD_VELOCITY,
+};
+
+use super::status_effects::{handle_status_transport_events, send_status_effect_updates, StatusEffectReplicator};
+use super::{DirtyReplicationState, ReplicatedFields};
+use crate::combat::Health;
+use crate::interest_management::InterestManager;
+use crate::network::tokio_transport::{TransportCommand, TransportEvent};
+use crate::persistence::faction_persistence::PlayerIdMapping;
+use crate::TransportCommandSender;
+
+/// Snapshots kept per client while waiting for acks. Older baselines fall back to a full snapshot.
+pub const SNAPSHOT_HISTORY: usize = 32;
+
+/// Fields carried by EntitySnapshot (the remaining ReplicatedFields use TargetedUpdate).
+pub const SNAPSHOT_FIELDS: ReplicatedFields = ReplicatedFields::POSITION
+    .union(ReplicatedFields::ROTATION)
+    .union(ReplicatedFields::VELOCITY)
+    .union(ReplicatedFields::HEALTH);
+
+const FIELD_BITS: [u32; 4] = [
+    SNAPSHOT_FIELD_POSITION,
+    SNAPSHOT_FIELD_ROTATION,
+    SNAPSHOT_FIELD_VELOCITY,
+    SNAPSHOT_FIELD_HEALTH,
+];
+
+/// Quantized, wire-ready state of one entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapshotEntityState {
+    pub position: [i32; 3],
+    pub rotation: u32,
+    pub velocity: [i16; 3],
+    pub health: f32,
+}
+
+impl SnapshotEntityState {
+    pub fn from_world(position: Vec3, rotation: Quat, velocity: Vec3, health: f32) -> Self {
+        Self {
+            position: quantize_position(position.to_array()),
+            rotation: pack_rotation(rotation.to_array()),
+            velocity: quantize_velocity(velocity.to_array()),
+            health,
+        }
+    }
+
+    fn to_wire(self, entity_id: u64, mask: u32) -> WireEntityDelta {
+        WireEntityDelta {
+            entity_id,
+            mask,
+            position: (mask & SNAPSHOT_FIELD_POSITION != 0).then_some(self.position),
+            rotation: (mask & SNAPSHOT_FIELD_ROTATION != 0).then_some(self.rotation),
+            velocity: (mask & SNAPSHOT_FIELD_VELOCITY != 0).then_some(self.velocity),
+            health: (mask & SNAPSHOT_FIELD_HEALTH != 0).then_some(self.health),
+        }
+    }
+}
+
+struct TrackedEntity {
+    state: SnapshotEntityState,
+    /// Tick each snapshot field last changed, indexed like FIELD_BITS
+    changed_at: [u64; 4],
+}
+
+struct SentSnapshot {
+    id: u32,
+    tick: u64,
+    entities: HashSet<u64>,
+}
+
+#[derive(Default)]
+struct ClientSnapshotState {
+    next_id: u32,
+    acked: Option<u32>,
+    history: VecDeque<SentSnapshot>,
+}
+
+impl ClientSnapshotState {
+    fn baseline(&self) -> Option<&SentSnapshot> {
+        let acked = self.acked?;
+        self.history.iter().find(|s| s.id == acked)
+    }
+
+    /// Entities the client may currently hold: the baseline plus anything sent after it.
+    fn known_entities(&self) -> HashSet<u64> {
+        let from = self.baseline().map(|b| b.id);
+        self.history
+            .iter()
+            .filter(|s| from.is_none_or(|base| s.id.wrapping_sub(base) as i32 >= 0))
+            .flat_map(|s| s.entities.iter().copied())
+            .collect()
+    }
+}
+
+/// Authoritative snapshot state: current entity values + per-client baselines.
+#[derive(Resource, Default)]
+pub struct SnapshotReplicator {
+    entities: HashMap<u64, TrackedEntity>,
+    clients: HashMap<u64, ClientSnapshotState>,
+}
+
+impl SnapshotReplicator {
+    pub fn connect(&mut self, player_id: u64) {
+        self.clients.insert(player_id, ClientSnapshotState::default());
+    }
+
+    pub fn disconnect(&mut self, player_id: u64) {
+        self.clients.remove(&player_id);
+    }
+
+    /// Client confirmed `snapshot_id`. Stale or unknown acks are ignored.
+    pub fn ack(&mut self, player_id: u64, snapshot_id: u32) {
+        let Some(client) = self.clients.get_mut(&player_id) else { return };
+        if !client.history.iter().any(|s| s.id == snapshot_id) {
+            return;
+        }
+        let newer = client.acked.is_none_or(|prev| snapshot_id.wrapping_sub(prev) as i32 > 0);
+        if newer {
+            client.acked = Some(snapshot_id);
+            // Nothing older than the baseline can become a baseline again.
+            client.history.retain(|s| s.id.wrapping_sub(snapshot_id) as i32 >= 0);
+        }
+    }
+
+    /// Record this tick's value for an entity; `dirty` says which fields changed.
+    pub fn record(&mut self, entity_id: u64, state: SnapshotEntityState, dirty: ReplicatedFields, tick: u64) {
+        match self.entities.get_mut(&entity_id) {
+            Some(tracked) => {
+                tracked.state = state;
+                for (slot, bit) in FIELD_BITS.iter().enumerate() {
+                    if dirty.bits() & bit != 0 {
+                        tracked.changed_at[slot] = tick;
+                    }
+                }
+            }
+            None => {
+                self.entities.insert(entity_id, TrackedEntity { state, changed_at: [tick; 4] });
+            }
+        }
+    }
+
+    pub fn remove(&mut self, entity_id: u64) {
+        self.entities.remove(&entity_id);
+    }
+
+    /// Build the next snapshot for `player_id` restricted to `visible`.
+    /// Returns None when the client is unknown or there is nothing to say.
+    pub fn build(&mut self, player_id: u64, visible: &[u64], tick: u64) -> Option<ServerMessage> {
+        let client = self.clients.get_mut(&player_id)?;
+        let baseline = client.baseline().map(|b| (b.id, b.tick, &b.entities));
+
+        let mut entities = Vec::new();
+        let mut sent = HashSet::new();
+        for &entity_id in visible {
+            let Some(tracked) = self.entities.get(&entity_id) else { continue };
+            sent.insert(entity_id);
+
+            let mask = match baseline {
+                Some((_, base_tick, base_entities)) if base_entities.contains(&entity_id) => FIELD_BITS
+                    .iter()
+                    .zip(tracked.changed_at.iter())
+                    .filter(|(_, &changed)| changed > base_tick)
+                    .fold(0, |mask, (bit, _)| mask | bit),
+                _ => SNAPSHOT_FIELDS_ALL,
+            };
+            if mask != 0 {
+                entities.push(tracked.state.to_wire(entity_id, mask));
+            }
+        }
+
+        let mut removed: Vec<u64> = client.known_entities().difference(&sent).copied().collect();
+        removed.sort_unstable();
+
+        if entities.is_empty() && removed.is_empty() {
+            return None;
+        }
+
+        let baseline_id = baseline.map(|(id, _, _)| id);
+        let snapshot_id = client.next_id;
+        client.next_id = client.next_id.wrapping_add(1);
+        client.history.push_back(SentSnapshot { id: snapshot_id, tick, entities: sent });
+        while client.history.len() > SNAPSHOT_HISTORY {
+            let dropped = client.history.pop_front();
+            if dropped.map(|s| s.id) == client.acked {
+                client.acked = None;
+            }
+        }
+
+        Some(ServerMessage::EntitySnapshot {
+            snapshot_id,
+            baseline_id,
+            server_tick: tick,
+            entities,
+            removed,
+        })
+    }
+}
+
+/// Server tick counter for snapshot baselines.
+#[derive(Resource, Default)]
+pub struct SnapshotTick(pub u64);
+
+/// Connect/disconnect clients and apply SnapshotAck.
+pub fn handle_snapshot_transport_events(
+    mut transport_events: EventReader<TransportEvent>,
+    mut replicator: ResMut<SnapshotReplicator>,
+) {
+    for event in transport_events.read() {
+        match event {
+            TransportEvent::ClientConnected { info } => replicator.connect(info.player_id),
+            TransportEvent::ClientDisconnected { player_id } => replicator.disconnect(*player_id),
+            TransportEvent::MessageReceived { player_id, message: ClientMessage::SnapshotAck { snapshot_id } } => {
+                replicator.ack(*player_id, *snapshot_id)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Flag POSITION / ROTATION when the transform moves.
+pub fn mark_transform_dirty(mut query: Query<(&Transform, &mut DirtyReplicationState), Changed<Transform>>) {
+    for (transform, mut dirty) in &mut query {
+        if dirty.last_position != Some(transform.translation) {
+            dirty.dirty_mask |= ReplicatedFields::POSITION;
+            dirty.last_position = Some(transform.translation);
+        }
+        if dirty.last_rotation != Some(transform.rotation) {
+            dirty.dirty_mask |= ReplicatedFields::ROTATION;
+            dirty.last_rotation = Some(transform.rotation);
+        }
+    }
+}
+
+/// Record dirty entities, then send each connected client its interest-filtered delta.
+pub fn send_entity_snapshots(
+    mut tick: ResMut<SnapshotTick>,
+    mut replicator: ResMut<SnapshotReplicator>,
+    mut removed_entities: RemovedComponents<DirtyReplicationState>,
+    mut query: Query<(Entity, &Transform, Option<&Health>, &mut DirtyReplicationState)>,
+    interest_manager: Res<InterestManager>,
+    players: Res<PlayerIdMapping>,
+    command_tx: Option<Res<TransportCommandSender>>,
+) {
+    tick.0 += 1;
+    let now = tick.0;
+
+    for entity in removed_entities.read() {
+        replicator.remove(entity.to_bits());
+    }
+
+    for (entity, transform, health, mut dirty) in &mut query {
+        let changed = dirty.dirty_mask & SNAPSHOT_FIELDS;
+        let state = SnapshotEntityState::from_world(
+            transform.translation,
+            transform.rotation,
+            dirty.last_velocity.unwrap_or(Vec3::ZERO),
+            health.map(|h| h.current).or(dirty.last_health).unwrap_or(0.0),
+        );
+        replicator.record(entity.to_bits(), state, changed, now);
+        // Other fields stay dirty for TargetedUpdate consumers.
+        dirty.dirty_mask.remove(SNAPSHOT_FIELDS);
+    }
+
+    let Some(sender) = command_tx else { return };
+    let player_ids: Vec<u64> = replicator.clients.keys().copied().collect();
+    for player_id in player_ids {
+        let Some(player_entity) = players.get_entity(player_id) else { continue };
+        let Ok((_, transform, _, _)) = query.get(player_entity) else { continue };
+
+        let visible: Vec<u64> = interest_manager
+            .get_entities_for_player(player_entity, transform.translation, None)
+            .into_iter()
+            .chain(std::iter::once(player_entity))
+            .map(Entity::to_bits)
+            .collect();
+
+        if let Some(message) = replicator.build(player_id, &visible, now) {
+            let _ = sender.tx.send(TransportCommand::Send { player_id, message });
+        }
+    }
+}
+
+pub struct SnapshotReplicationPlugin;
+
+impl Plugin for SnapshotReplicationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SnapshotReplicator>()
+            .init_resource::<StatusEffectReplicator>()
+            .init_resource::<SnapshotTick>()
+            .init_resource::<PlayerIdMapping>()
+            .add_systems(
+                Update,
+                (
+                    (handle_snapshot_transport_events, handle_status_transport_events),
+                    mark_transform_dirty,
+                    send_entity_snapshots,
+                    send_status_effect_updates,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(x: f32, health: f32) -> SnapshotEntityState {
+        SnapshotEntityState::from_world(Vec3::new(x, 0.0, 0.0), Quat::IDENTITY, Vec3::ZERO, health)
+    }
+
+    fn unpack(msg: ServerMessage) -> (u32, Option<u32>, Vec<WireEntityDelta>, Vec<u64>) {
+        match msg {
+            ServerMessage::EntitySnapshot { snapshot_id, baseline_id, entities, removed, .. } => {
+                (snapshot_id, baseline_id, entities, removed)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn acked_baseline_sends_only_changed_fields() {
+        let mut rep = SnapshotReplicator::default();
+        rep.connect(1);
+        rep.record(10, state(0.0, 100.0), ReplicatedFields::NONE, 1);
+
+        let (id, base, entities, _) = unpack(rep.build(1, &[10], 1).unwrap());
+        assert_eq!(base, None);
+        assert_eq!(entities[0].mask, SNAPSHOT_FIELDS_ALL);
+        rep.ack(1, id);
+
+        rep.record(10, state(2.0, 100.0), ReplicatedFields::POSITION, 2);
+        let (_, base, entities, _) = unpack(rep.build(1, &[10], 2).unwrap());
+        assert_eq!(base, Some(id));
+        assert_eq!(entities[0].mask, SNAPSHOT_FIELD_POSITION);
+        assert!(entities[0].health.is_none());
+
+        // Nothing changed since the baseline and nothing to remove: no message.
+        rep.record(10, state(2.0, 100.0), ReplicatedFields::NONE, 3);
+        rep.ack(1, id + 1);
+        assert!(rep.build(1, &[10], 3).is_none());
+    }
+
+    #[test]
+    fn lost_snapshot_is_resent_against_last_ack() {
+        let mut rep = SnapshotReplicator::default();
+        rep.connect(1);
+        rep.record(10, state(0.0, 100.0), ReplicatedFields::NONE, 1);
+        let (first, ..) = unpack(rep.build(1, &[10], 1).unwrap());
+        rep.ack(1, first);
+
+        rep.record(10, state(0.0, 50.0), ReplicatedFields::HEALTH, 2);
+        let _lost = rep.build(1, &[10], 2).unwrap();
+
+        rep.record(10, state(3.0, 50.0), ReplicatedFields::POSITION, 3);
+        let (_, base, entities, _) = unpack(rep.build(1, &[10], 3).unwrap());
+        assert_eq!(base, Some(first));
+        assert_eq!(entities[0].mask, SNAPSHOT_FIELD_POSITION | SNAPSHOT_FIELD_HEALTH);
+    }
+
+    #[test]
+    fn entities_outside_interest_are_filtered_and_removed() {
+        let mut rep = SnapshotReplicator::default();
+        rep.connect(1);
+        rep.record(10, state(0.0, 100.0), ReplicatedFields::NONE, 1);
+        rep.record(11, state(500.0, 100.0), ReplicatedFields::NONE, 1);
+
+        let (id, _, entities, _) = unpack(rep.build(1, &[10, 11], 1).unwrap());
+        assert_eq!(entities.len(), 2);
+        rep.ack(1, id);
+
+        let (_, _, entities, removed) = unpack(rep.build(1, &[10], 2).unwrap());
+        assert!(entities.is_empty());
+        assert_eq!(removed, vec![11]);
+    }
+}
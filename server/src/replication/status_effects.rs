@@ -0,0 +1,177 @@
+/*!
+ * server/src/replication/status_effects.rs
+ *
+ * Powrush-MMO Status Effect Replication
+ * v20.13 | Buff / debuff lists over ServerMessage::StatusEffects.
+ *
+ * Consumes ReplicatedFields::STATUS_EFFECT after the snapshot pass. A client
+ * gets an entity's full status list when it changed, or when the entity
+ * enters its interest set with statuses already running; an empty list tells
+ * it the last one ended. Remaining time travels with each status, so clients
+ * run timers locally between changes.
+ *
+ * AG-SML v1.0 | TOLC 8
+ * Thunder locked in. Yoi ⚡
+ */
+
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use shared::protocol::{ServerMessage, WireEntityStatus, WireStatusEffect};
+
+use super::snapshot::SnapshotTick;
+use super::{DirtyReplicationState, ReplicatedFields};
+use crate::combat::StatusEffects;
+use crate::interest_management::InterestManager;
+use crate::network::tokio_transport::{TransportCommand, TransportEvent};
+use crate::persistence::faction_persistence::PlayerIdMapping;
+use crate::TransportCommandSender;
+
+/// Which entities each client currently holds a non-empty status list for.
+#[derive(Resource, Default)]
+pub struct StatusEffectReplicator {
+    clients: HashMap<u64, HashSet<u64>>,
+}
+
+impl StatusEffectReplicator {
+    pub fn connect(&mut self, player_id: u64) {
+        self.clients.insert(player_id, HashSet::new());
+    }
+
+    pub fn disconnect(&mut self, player_id: u64) {
+        self.clients.remove(&player_id);
+    }
+
+    /// Lists to send `player_id` this tick. `current` holds every entity with statuses;
+    /// `changed` the entities whose list changed since the last call.
+    pub fn build(
+        &mut self,
+        player_id: u64,
+        visible: &[u64],
+        current: &HashMap<u64, Vec<WireStatusEffect>>,
+        changed: &HashSet<u64>,
+    ) -> Vec<WireEntityStatus> {
+        let Some(known) = self.clients.get_mut(&player_id) else { return Vec::new() };
+        // Out of interest: the client drops the entity (EntitySnapshot.removed) and its statuses.
+        let visible_set: HashSet<u64> = visible.iter().copied().collect();
+        known.retain(|id| visible_set.contains(id));
+
+        let mut out = Vec::new();
+        for &entity_id in visible {
+            match current.get(&entity_id) {
+                Some(effects) if changed.contains(&entity_id) || !known.contains(&entity_id) => {
+                    known.insert(entity_id);
+                    out.push(WireEntityStatus { entity_id, effects: effects.clone() });
+                }
+                None if known.remove(&entity_id) => {
+                    out.push(WireEntityStatus { entity_id, effects: Vec::new() });
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+}
+
+pub fn handle_status_transport_events(
+    mut transport_events: EventReader<TransportEvent>,
+    mut replicator: ResMut<StatusEffectReplicator>,
+) {
+    for event in transport_events.read() {
+        match event {
+            TransportEvent::ClientConnected { info } => replicator.connect(info.player_id),
+            TransportEvent::ClientDisconnected { player_id } => replicator.disconnect(*player_id),
+            _ => {}
+        }
+    }
+}
+
+/// Clear STATUS_EFFECT bits and send each connected client the lists it is missing.
+pub fn send_status_effect_updates(
+    tick: Res<SnapshotTick>,
+    mut replicator: ResMut<StatusEffectReplicator>,
+    mut query: Query<(Entity, &Transform, Option<&StatusEffects>, &mut DirtyReplicationState)>,
+    interest_manager: Res<InterestManager>,
+    players: Res<PlayerIdMapping>,
+    command_tx: Option<Res<TransportCommandSender>>,
+) {
+    let mut current = HashMap::new();
+    let mut changed = HashSet::new();
+    for (entity, _, statuses, mut dirty) in &mut query {
+        let id = entity.to_bits();
+        if dirty.dirty_mask.contains(ReplicatedFields::STATUS_EFFECT) {
+            dirty.dirty_mask.remove(ReplicatedFields::STATUS_EFFECT);
+            changed.insert(id);
+        }
+        if let Some(statuses) = statuses.filter(|s| !s.active.is_empty()) {
+            current.insert(id, statuses.to_wire());
+        }
+    }
+
+    let Some(sender) = command_tx else { return };
+    let player_ids: Vec<u64> = replicator.clients.keys().copied().collect();
+    for player_id in player_ids {
+        let Some(player_entity) = players.get_entity(player_id) else { continue };
+        let Ok((_, transform, _, _)) = query.get(player_entity) else { continue };
+
+        let visible: Vec<u64> = interest_manager
+            .get_entities_for_player(player_entity, transform.translation, None)
+            .into_iter()
+            .chain(std::iter::once(player_entity))
+            .map(Entity::to_bits)
+            .collect();
+
+        let entities = replicator.build(player_id, &visible, &current, &changed);
+        if !entities.is_empty() {
+            let message = ServerMessage::StatusEffects { server_tick: tick.0, entities };
+            let _ = sender.tx.send(TransportCommand::Send { player_id, message });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::protocol::StatusEffectType;
+
+    fn slow(remaining_secs: f32) -> Vec<WireStatusEffect> {
+        vec![WireStatusEffect {
+            kind: StatusEffectType::Slow,
+            source_id: None,
+            stacks: 1,
+            strength: 0.3,
+            remaining_secs,
+            duration_secs: 4.0,
+        }]
+    }
+
+    #[test]
+    fn sends_changes_new_arrivals_and_clears() {
+        let mut rep = StatusEffectReplicator::default();
+        rep.connect(1);
+        let none = HashSet::new();
+        let mut current = HashMap::from([(10, slow(4.0))]);
+
+        // Entity 10 enters interest with a status running: full list even though unchanged.
+        let sent = rep.build(1, &[10, 11], &current, &none);
+        assert_eq!(sent, vec![WireEntityStatus { entity_id: 10, effects: slow(4.0) }]);
+        assert!(rep.build(1, &[10, 11], &current, &none).is_empty());
+
+        // Changed: resent with the new timer.
+        current.insert(10, slow(3.0));
+        let sent = rep.build(1, &[10, 11], &current, &HashSet::from([10]));
+        assert_eq!(sent[0].effects, slow(3.0));
+
+        // Expired: one empty list, then silence.
+        current.clear();
+        let sent = rep.build(1, &[10, 11], &current, &HashSet::from([10]));
+        assert_eq!(sent, vec![WireEntityStatus { entity_id: 10, effects: vec![] }]);
+        assert!(rep.build(1, &[10, 11], &current, &none).is_empty());
+
+        // Leaving and re-entering interest resends the list.
+        current.insert(10, slow(2.0));
+        rep.build(1, &[10], &current, &HashSet::from([10]));
+        assert!(rep.build(1, &[11], &current, &none).is_empty());
+        assert_eq!(rep.build(1, &[10], &current, &none).len(), 1);
+    }
+}
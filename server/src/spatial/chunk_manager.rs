@@ -1,15 +1,17 @@
 //! server/src/spatial/chunk_manager.rs
 //! Production-grade Fixed-Size Chunk Manager for Persistence, Streaming & Dirty Tracking
 //! v18.57 — Full production quality, zero placeholders
+//! v18.58 — Serializable ChunkCoord + per-chunk dirty queries for chunk_store / chunk_streaming
 //! AG-SML v1.0 | TOLC 8 + 7 Living Mercy Gates | Ra-Thor + PATSAGi aligned
 
 use crate::spatial::hierarchical_grid::{HierarchicalGrid, Vec3 as SpatialVec3};
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 /// Current production version
 pub const CHUNK_MANAGER_VERSION: u32 = 18;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ChunkCoord {
     pub x: i32,
     pub y: i32,
@@ -112,6 +114,19 @@ impl ChunkManager {
         self.dirty_chunks.clear();
     }
 
+    pub fn is_dirty(&self, coord: ChunkCoord) -> bool {
+        self.dirty_chunks.contains(&coord)
+    }
+
+    /// Clear one chunk's dirty flag after it was saved.
+    pub fn clear_dirty_chunk(&mut self, coord: ChunkCoord) {
+        self.dirty_chunks.remove(&coord);
+    }
+
+    pub fn chunk_size(&self) -> f32 {
+        self.chunk_size
+    }
+
     pub fn load_chunk(&mut self, coord: ChunkCoord) {
         self.loaded_chunks.insert(coord);
     }
@@ -0,0 +1,328 @@
+//! server/src/spatial/chunk_store.rs
+//! Durable per-chunk world state (resource nodes, placed structures, NPC spawners)
+//! behind the ChunkStore trait. ChunkWorld (chunk_streaming) decides when chunks
+//! are saved: whatever ChunkManager marked dirty, on flush or before unload.
+//!
+//! - InMemoryChunkStore: unit tests / sovereign dev.
+//! - FileChunkStore: one JSON file per chunk under `<root>/chunks`, written
+//!   atomically (temp file + fsync + rename).
+//!
+//! AG-SML v1.0 | TOLC 8 + 7 Living Mercy Gates | Ra-Thor + PATSAGi aligned
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use shared::protocol::{ServerMessage, WireResourceNode, WireStructure};
+use tracing::warn;
+
+use crate::persistence::player_store::write_atomic;
+use crate::spatial::chunk_manager::ChunkCoord;
+
+const CHUNKS_DIR: &str = "chunks";
+
+// ============================================================================
+// Chunk contents
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkResourceNode {
+    pub node_id: u64,
+    pub resource_type: String,
+    pub position: [f32; 3],
+    pub current_amount: f32,
+    pub max_amount: f32,
+    pub regen_rate: f32,
+    pub sustainability_score: f32,
+    pub depleted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlacedStructure {
+    pub structure_id: u64,
+    pub kind: String,
+    pub owner_id: u64,
+    pub position: [f32; 3],
+    pub rotation_y: f32,
+    pub integrity: f32,
+}
+
+/// Server-only: spawners are never sent to clients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NpcSpawner {
+    pub spawner_id: u64,
+    pub archetype: String,
+    pub position: [f32; 3],
+    pub radius: f32,
+    pub max_alive: u32,
+    pub respawn_secs: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkData {
+    pub coord: ChunkCoord,
+    /// Bumped on every change; persisted, so it keeps increasing across unloads and restarts.
+    pub version: u64,
+    pub resource_nodes: Vec<ChunkResourceNode>,
+    pub structures: Vec<PlacedStructure>,
+    pub spawners: Vec<NpcSpawner>,
+}
+
+impl ChunkData {
+    /// A chunk nothing has been placed in yet.
+    pub fn empty(coord: ChunkCoord) -> Self {
+        Self { coord, version: 0, resource_nodes: Vec::new(), structures: Vec::new(), spawners: Vec::new() }
+    }
+
+    pub fn resource_node_mut(&mut self, node_id: u64) -> Option<&mut ChunkResourceNode> {
+        self.resource_nodes.iter_mut().find(|n| n.node_id == node_id)
+    }
+
+    /// ServerMessage::ChunkSnapshot for clients (spawners stay on the server).
+    pub fn to_snapshot(&self) -> ServerMessage {
+        ServerMessage::ChunkSnapshot {
+            chunk_id: self.coord.to_packed_id(),
+            version: self.version,
+            resource_nodes: self
+                .resource_nodes
+                .iter()
+                .map(|n| WireResourceNode {
+                    node_id: n.node_id,
+                    resource_type: n.resource_type.clone(),
+                    position: n.position,
+                    current_amount: n.current_amount,
+                    max_amount: n.max_amount,
+                    depleted: n.depleted,
+                })
+                .collect(),
+            structures: self
+                .structures
+                .iter()
+                .map(|s| WireStructure {
+                    structure_id: s.structure_id,
+                    kind: s.kind.clone(),
+                    owner_id: s.owner_id,
+                    position: s.position,
+                    rotation_y: s.rotation_y,
+                    integrity: s.integrity,
+                })
+                .collect(),
+        }
+    }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug)]
+pub enum ChunkStoreError {
+    Io(io::Error),
+    Serialize(String),
+    /// Chunk file exists on disk but cannot be read back.
+    Corrupt { path: PathBuf, reason: String },
+}
+
+impl fmt::Display for ChunkStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkStoreError::Io(e) => write!(f, "chunk store I/O error: {}", e),
+            ChunkStoreError::Serialize(e) => write!(f, "chunk store serialization error: {}", e),
+            ChunkStoreError::Corrupt { path, reason } => {
+                write!(f, "chunk store record {} corrupt: {}", path.display(), reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ChunkStoreError {}
+
+impl From<io::Error> for ChunkStoreError {
+    fn from(e: io::Error) -> Self {
+        ChunkStoreError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ChunkStoreError {
+    fn from(e: serde_json::Error) -> Self {
+        ChunkStoreError::Serialize(e.to_string())
+    }
+}
+
+// ============================================================================
+// Trait
+// ============================================================================
+
+/// Storage backend for chunk contents. Writes are durable when they return Ok.
+pub trait ChunkStore: Send + Sync {
+    /// None for a chunk that was never saved.
+    fn load(&self, coord: ChunkCoord) -> Result<Option<ChunkData>, ChunkStoreError>;
+
+    /// Insert or replace each chunk by `coord`.
+    fn save_batch(&self, chunks: &[ChunkData]) -> Result<(), ChunkStoreError>;
+
+    /// Every saved chunk, sorted.
+    fn chunk_coords(&self) -> Result<Vec<ChunkCoord>, ChunkStoreError>;
+}
+
+// ============================================================================
+// In-memory backend
+// ============================================================================
+
+#[derive(Default)]
+pub struct InMemoryChunkStore {
+    chunks: RwLock<HashMap<ChunkCoord, ChunkData>>,
+}
+
+impl InMemoryChunkStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ChunkStore for InMemoryChunkStore {
+    fn load(&self, coord: ChunkCoord) -> Result<Option<ChunkData>, ChunkStoreError> {
+        Ok(self.chunks.read().unwrap().get(&coord).cloned())
+    }
+
+    fn save_batch(&self, chunks: &[ChunkData]) -> Result<(), ChunkStoreError> {
+        let mut stored = self.chunks.write().unwrap();
+        for chunk in chunks {
+            stored.insert(chunk.coord, chunk.clone());
+        }
+        Ok(())
+    }
+
+    fn chunk_coords(&self) -> Result<Vec<ChunkCoord>, ChunkStoreError> {
+        let mut coords: Vec<_> = self.chunks.read().unwrap().keys().copied().collect();
+        coords.sort_by_key(|c| (c.x, c.y, c.z));
+        Ok(coords)
+    }
+}
+
+// ============================================================================
+// File backend
+// ============================================================================
+
+pub struct FileChunkStore {
+    root: PathBuf,
+}
+
+impl FileChunkStore {
+    /// Open (creating if needed) a store rooted at `root`.
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, ChunkStoreError> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(root.join(CHUNKS_DIR))?;
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn chunk_path(&self, coord: ChunkCoord) -> PathBuf {
+        self.root.join(CHUNKS_DIR).join(format!("{}_{}_{}.json", coord.x, coord.y, coord.z))
+    }
+}
+
+fn parse_chunk_stem(stem: &str) -> Option<ChunkCoord> {
+    let mut parts = stem.split('_').map(|p| p.parse::<i32>().ok());
+    let coord = ChunkCoord::new(parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(coord)
+}
+
+impl ChunkStore for FileChunkStore {
+    fn load(&self, coord: ChunkCoord) -> Result<Option<ChunkData>, ChunkStoreError> {
+        let path = self.chunk_path(coord);
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let chunk: ChunkData = serde_json::from_slice(&bytes)
+            .map_err(|e| ChunkStoreError::Corrupt { path: path.clone(), reason: e.to_string() })?;
+        if chunk.coord != coord {
+            return Err(ChunkStoreError::Corrupt {
+                path,
+                reason: format!("holds chunk {:?}, expected {:?}", chunk.coord, coord),
+            });
+        }
+        Ok(Some(chunk))
+    }
+
+    fn save_batch(&self, chunks: &[ChunkData]) -> Result<(), ChunkStoreError> {
+        for chunk in chunks {
+            let bytes = serde_json::to_vec_pretty(chunk)?;
+            write_atomic(&self.chunk_path(chunk.coord), &bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Stale temp files from a crash are removed.
+    fn chunk_coords(&self) -> Result<Vec<ChunkCoord>, ChunkStoreError> {
+        let mut coords = Vec::new();
+        for entry in fs::read_dir(self.root.join(CHUNKS_DIR))? {
+            let path = entry?.path();
+            match path.extension().and_then(|e| e.to_str()) {
+                Some("json") => {}
+                Some("tmp") => {
+                    warn!("[ChunkStore] Removing stale temp file {}", path.display());
+                    fs::remove_file(&path)?;
+                    continue;
+                }
+                _ => continue,
+            }
+            if let Some(coord) = path.file_stem().and_then(|s| s.to_str()).and_then(parse_chunk_stem) {
+                coords.push(coord);
+            }
+        }
+        coords.sort_by_key(|c| (c.x, c.y, c.z));
+        Ok(coords)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk_with_node(coord: ChunkCoord, amount: f32) -> ChunkData {
+        let mut chunk = ChunkData::empty(coord);
+        chunk.version = 3;
+        chunk.resource_nodes.push(ChunkResourceNode {
+            node_id: 7,
+            resource_type: "gold".to_string(),
+            position: [40.0, 0.0, 40.0],
+            current_amount: amount,
+            max_amount: 100.0,
+            regen_rate: 1.0,
+            sustainability_score: 0.85,
+            depleted: false,
+        });
+        chunk
+    }
+
+    #[test]
+    fn file_store_round_trips_negative_coords_and_rejects_mismatch() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let store = FileChunkStore::open(dir).unwrap();
+        let coord = ChunkCoord::new(-2, 0, 5);
+        assert!(store.load(coord).unwrap().is_none());
+
+        store.save_batch(&[chunk_with_node(coord, 70.0)]).unwrap();
+        fs::write(dir.join(CHUNKS_DIR).join("1_0_0.tmp"), b"partial").unwrap();
+
+        let reopened = FileChunkStore::open(dir).unwrap();
+        assert_eq!(reopened.chunk_coords().unwrap(), vec![coord]);
+        assert_eq!(reopened.load(coord).unwrap(), Some(chunk_with_node(coord, 70.0)));
+        assert!(!dir.join(CHUNKS_DIR).join("1_0_0.tmp").exists());
+
+        // A file renamed onto another chunk's path is refused rather than loaded in the wrong place.
+        fs::copy(reopened.chunk_path(coord), reopened.chunk_path(ChunkCoord::new(0, 0, 0))).unwrap();
+        assert!(matches!(reopened.load(ChunkCoord::new(0, 0, 0)), Err(ChunkStoreError::Corrupt { .. })));
+    }
+}
@@ -0,0 +1,436 @@
+//! server/src/spatial/chunk_streaming.rs
+//! Chunk streaming + persistence driven by ChunkManager dirty tracking
+//! v18.58 — ChunkWorld: loads chunks around players from a ChunkStore, sends
+//! ServerMessage::ChunkSnapshot when a player comes in range of a chunk or a chunk
+//! it holds changes, saves dirty chunks periodically and before unloading them.
+//!
+//! Chunks load within `load_radius` of a player and unload only once no player is
+//! within the larger `unload_radius`, so walking along a chunk border does not
+//! thrash the store. A client is resent a chunk only if its version moved on or
+//! the player went beyond `unload_radius` and came back.
+//! v18.59 — take_changed_chunks: loaded / changed chunks for the NPC nav grid.
+//! v18.60 — ChunkWorld::new refuses a bad config with ChunkConfigError instead of panicking.
+//!
+//! AG-SML v1.0 | TOLC 8 + 7 Living Mercy Gates | Ra-Thor + PATSAGi aligned
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use shared::protocol::ServerMessage;
+
+use crate::network::tokio_transport::{TransportCommand, TransportEvent};
+use crate::persistence::faction_persistence::PlayerIdMapping;
+use crate::spatial::chunk_manager::{ChunkCoord, ChunkManager};
+use crate::spatial::chunk_store::{ChunkData, ChunkResourceNode, ChunkStore, ChunkStoreError};
+use crate::spatial::hierarchical_grid::Vec3 as SpatialVec3;
+use crate::TransportCommandSender;
+
+#[derive(Debug, Clone)]
+pub struct ChunkStreamingConfig {
+    pub chunk_size: f32,
+    pub load_radius: f32,
+    /// Must exceed `load_radius` by at least a chunk.
+    pub unload_radius: f32,
+    pub flush_interval_secs: f32,
+}
+
+impl Default for ChunkStreamingConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: ChunkManager::recommended_chunk_size(),
+            load_radius: 160.0,
+            unload_radius: 256.0,
+            flush_interval_secs: 10.0,
+        }
+    }
+}
+
+/// A ChunkStreamingConfig ChunkWorld cannot stream with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChunkConfigError {
+    /// chunk_size must be finite and positive
+    ChunkSize(f32),
+    /// unload_radius must exceed load_radius (both finite, load_radius not negative)
+    Radii { load_radius: f32, unload_radius: f32 },
+}
+
+impl fmt::Display for ChunkConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkConfigError::ChunkSize(size) => write!(f, "chunk_size {} is not a positive length", size),
+            ChunkConfigError::Radii { load_radius, unload_radius } => {
+                write!(f, "unload_radius {} must exceed load_radius {}", unload_radius, load_radius)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ChunkConfigError {}
+
+#[derive(Default)]
+struct ClientChunks {
+    position: Option<SpatialVec3>,
+    center: Option<ChunkCoord>,
+    /// Chunks the client should hold, with the version last sent (None: not sent yet).
+    sent: HashMap<ChunkCoord, Option<u64>>,
+}
+
+/// Loaded chunk contents + per-client streaming state. Inserted by host bootstrap
+/// (usually over a FileChunkStore); without it chunk streaming is off.
+#[derive(Resource)]
+pub struct ChunkWorld {
+    pub config: ChunkStreamingConfig,
+    manager: ChunkManager,
+    store: Arc<dyn ChunkStore>,
+    chunks: HashMap<ChunkCoord, ChunkData>,
+    clients: HashMap<u64, ClientChunks>,
+    /// Chunks loaded or changed since the last take_changed_chunks (navigation).
+    changed: HashSet<ChunkCoord>,
+}
+
+impl ChunkWorld {
+    pub fn new(config: ChunkStreamingConfig, store: Arc<dyn ChunkStore>) -> Result<Self, ChunkConfigError> {
+        if !(config.chunk_size.is_finite() && config.chunk_size > 0.0) {
+            return Err(ChunkConfigError::ChunkSize(config.chunk_size));
+        }
+        let radii_ok = config.load_radius >= 0.0
+            && config.unload_radius.is_finite()
+            && config.unload_radius > config.load_radius;
+        if !radii_ok {
+            return Err(ChunkConfigError::Radii { load_radius: config.load_radius, unload_radius: config.unload_radius });
+        }
+        Ok(Self {
+            manager: ChunkManager::new(config.chunk_size),
+            config,
+            store,
+            chunks: HashMap::new(),
+            clients: HashMap::new(),
+            changed: HashSet::new(),
+        })
+    }
+
+    pub fn chunk_at(&self, position: Vec3) -> ChunkCoord {
+        self.manager.position_to_chunk(to_spatial(position))
+    }
+
+    pub fn is_loaded(&self, coord: ChunkCoord) -> bool {
+        self.chunks.contains_key(&coord)
+    }
+
+    pub fn loaded_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_dirty(&self, coord: ChunkCoord) -> bool {
+        self.manager.is_dirty(coord)
+    }
+
+    pub fn chunk(&self, coord: ChunkCoord) -> Option<&ChunkData> {
+        self.chunks.get(&coord)
+    }
+
+    fn ensure_loaded(&mut self, coord: ChunkCoord) -> Result<(), ChunkStoreError> {
+        if !self.chunks.contains_key(&coord) {
+            let data = self.store.load(coord)?.unwrap_or_else(|| ChunkData::empty(coord));
+            self.chunks.insert(coord, data);
+            self.manager.load_chunk(coord);
+            self.changed.insert(coord);
+        }
+        Ok(())
+    }
+
+    /// Chunks loaded or changed since the last call. Unlike the dirty set this is
+    /// not tied to saving, so it never repeats a change that is still unsaved.
+    pub fn take_changed_chunks(&mut self) -> Vec<ChunkCoord> {
+        self.changed.drain().collect()
+    }
+
+    /// Mutable access for a change (harvest, placement, spawner edit): loads the chunk
+    /// if needed, bumps its version and marks it dirty. Only take this to change the chunk.
+    pub fn chunk_mut(&mut self, coord: ChunkCoord) -> Result<&mut ChunkData, ChunkStoreError> {
+        self.ensure_loaded(coord)?;
+        self.manager.mark_dirty(coord);
+        self.changed.insert(coord);
+        let chunk = self.chunks.get_mut(&coord).expect("loaded above");
+        chunk.version += 1;
+        Ok(chunk)
+    }
+
+    /// Whether a placed structure of `kind` stands within `radius` of `position`.
+    /// Only loaded chunks are searched (a player's surroundings always are).
+    pub fn structure_near(&self, position: Vec3, radius: f32, kind: &str) -> bool {
+        self.manager.get_chunks_in_radius(to_spatial(position), radius).iter().any(|coord| {
+            self.chunks.get(coord).is_some_and(|chunk| {
+                chunk.structures.iter().any(|s| s.kind == kind && Vec3::from(s.position).distance(position) <= radius)
+            })
+        })
+    }
+
+    /// Lower the sustainability of loaded resource nodes of `resource_types` within
+    /// `radius` of `position` by `stress` (floored at 0). Returns how many were touched.
+    pub fn stress_nodes_near(&mut self, position: Vec3, radius: f32, resource_types: &[String], stress: f32) -> usize {
+        let mut touched = 0;
+        for coord in self.manager.get_chunks_in_radius(to_spatial(position), radius) {
+            let matches = |n: &ChunkResourceNode| {
+                resource_types.contains(&n.resource_type) && Vec3::from(n.position).distance(position) <= radius
+            };
+            if !self.chunks.get(&coord).is_some_and(|c| c.resource_nodes.iter().any(matches)) {
+                continue;
+            }
+            let Ok(chunk) = self.chunk_mut(coord) else { continue };
+            for node in chunk.resource_nodes.iter_mut().filter(|n| matches(n)) {
+                node.sustainability_score = (node.sustainability_score - stress).max(0.0);
+                touched += 1;
+            }
+        }
+        touched
+    }
+
+    pub fn add_player(&mut self, player_id: u64) {
+        self.clients.insert(player_id, ClientChunks::default());
+    }
+
+    pub fn remove_player(&mut self, player_id: u64) {
+        self.clients.remove(&player_id);
+    }
+
+    pub fn player_ids(&self) -> Vec<u64> {
+        self.clients.keys().copied().collect()
+    }
+
+    /// Record `player_id` at `position`; returns the ChunkSnapshots it is missing.
+    /// Unreadable chunks are logged and skipped (and never replaced with an empty chunk).
+    pub fn update_player(&mut self, player_id: u64, position: Vec3) -> Vec<ServerMessage> {
+        let Some(mut client) = self.clients.remove(&player_id) else { return Vec::new() };
+        let position = to_spatial(position);
+        let center = self.manager.position_to_chunk(position);
+        client.position = Some(position);
+
+        if client.center != Some(center) {
+            client.center = Some(center);
+            let keep: HashSet<ChunkCoord> =
+                self.manager.get_chunks_in_radius(position, self.config.unload_radius).into_iter().collect();
+            client.sent.retain(|coord, _| keep.contains(coord));
+            for coord in self.manager.get_chunks_in_radius(position, self.config.load_radius) {
+                match self.ensure_loaded(coord) {
+                    Ok(()) => {
+                        client.sent.entry(coord).or_insert(None);
+                    }
+                    Err(e) => error!("[ChunkStreaming] Chunk {:?} unavailable: {}", coord, e),
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        for (coord, sent) in client.sent.iter_mut() {
+            let Some(chunk) = self.chunks.get(coord) else { continue };
+            if *sent != Some(chunk.version) {
+                *sent = Some(chunk.version);
+                // Version 0 was never changed: clients already treat unknown chunks as empty.
+                if chunk.version > 0 {
+                    out.push(chunk.to_snapshot());
+                }
+            }
+        }
+        self.clients.insert(player_id, client);
+        out
+    }
+
+    /// Save every dirty loaded chunk. Dirty flags are cleared only after the batch is durable.
+    pub fn flush_dirty(&mut self) -> Result<usize, ChunkStoreError> {
+        let dirty: Vec<ChunkData> = self
+            .manager
+            .get_dirty_chunks()
+            .into_iter()
+            .filter_map(|coord| self.chunks.get(&coord).cloned())
+            .collect();
+        if dirty.is_empty() {
+            return Ok(0);
+        }
+        self.store.save_batch(&dirty)?;
+        for chunk in &dirty {
+            self.manager.clear_dirty_chunk(chunk.coord);
+        }
+        Ok(dirty.len())
+    }
+
+    /// Unload chunks no player is within `unload_radius` of, saving dirty ones first.
+    /// Nothing is unloaded if the save fails.
+    pub fn unload_far(&mut self) -> Result<usize, ChunkStoreError> {
+        let mut keep = HashSet::new();
+        for position in self.clients.values().filter_map(|c| c.position) {
+            keep.extend(self.manager.get_chunks_in_radius(position, self.config.unload_radius));
+        }
+        let far: Vec<ChunkCoord> = self.chunks.keys().filter(|c| !keep.contains(c)).copied().collect();
+        let dirty: Vec<ChunkData> =
+            far.iter().filter(|c| self.manager.is_dirty(**c)).map(|c| self.chunks[c].clone()).collect();
+        if !dirty.is_empty() {
+            self.store.save_batch(&dirty)?;
+        }
+        for coord in &far {
+            self.chunks.remove(coord);
+            self.manager.unload_chunk(*coord);
+        }
+        Ok(far.len())
+    }
+}
+
+fn to_spatial(v: Vec3) -> SpatialVec3 {
+    SpatialVec3 { x: v.x, y: v.y, z: v.z }
+}
+
+pub fn handle_chunk_transport_events(
+    mut transport_events: EventReader<TransportEvent>,
+    world: Option<ResMut<ChunkWorld>>,
+) {
+    let Some(mut world) = world else { return };
+    for event in transport_events.read() {
+        match event {
+            TransportEvent::ClientConnected { info } => world.add_player(info.player_id),
+            TransportEvent::ClientDisconnected { player_id } => world.remove_player(*player_id),
+            _ => {}
+        }
+    }
+}
+
+/// Send each connected player the chunk snapshots it is missing.
+pub fn stream_chunks_to_players(
+    world: Option<ResMut<ChunkWorld>>,
+    players: Res<PlayerIdMapping>,
+    transforms: Query<&Transform>,
+    command_tx: Option<Res<TransportCommandSender>>,
+) {
+    let Some(mut world) = world else { return };
+    for player_id in world.player_ids() {
+        let Some(entity) = players.get_entity(player_id) else { continue };
+        let Ok(transform) = transforms.get(entity) else { continue };
+        let snapshots = world.update_player(player_id, transform.translation);
+        if let Some(sender) = command_tx.as_ref() {
+            for message in snapshots {
+                let _ = sender.tx.send(TransportCommand::Send { player_id, message });
+            }
+        }
+    }
+}
+
+/// Every `flush_interval_secs` (and on exit): save dirty chunks, then unload far ones.
+pub fn persist_chunks(
+    time: Res<Time>,
+    mut since_flush: Local<f32>,
+    mut exit: EventReader<AppExit>,
+    world: Option<ResMut<ChunkWorld>>,
+) {
+    let Some(mut world) = world else { return };
+    let exiting = exit.read().next().is_some();
+    *since_flush += time.delta_seconds();
+    if *since_flush < world.config.flush_interval_secs && !exiting {
+        return;
+    }
+    *since_flush = 0.0;
+
+    match world.flush_dirty() {
+        Ok(0) => {}
+        Ok(n) => debug!("[ChunkStreaming] Saved {} dirty chunk(s)", n),
+        Err(e) => error!("[ChunkStreaming] Chunk flush failed, will retry: {}", e),
+    }
+    match world.unload_far() {
+        Ok(0) => {}
+        Ok(n) => debug!("[ChunkStreaming] Unloaded {} chunk(s)", n),
+        Err(e) => error!("[ChunkStreaming] Chunk unload failed, keeping chunks loaded: {}", e),
+    }
+}
+
+pub struct ChunkStreamingPlugin;
+
+impl Plugin for ChunkStreamingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (handle_chunk_transport_events, stream_chunks_to_players, persist_chunks).chain(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spatial::chunk_store::InMemoryChunkStore;
+
+    fn chunk_version(message: &ServerMessage) -> (u64, u64) {
+        match message {
+            ServerMessage::ChunkSnapshot { chunk_id, version, .. } => (*chunk_id, *version),
+            other => panic!("expected ChunkSnapshot, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn streams_new_and_changed_chunks_once() {
+        let store = Arc::new(InMemoryChunkStore::new());
+        let east = ChunkCoord::new(1, 0, 0);
+        store.save_batch(&[ChunkData { version: 4, ..ChunkData::empty(east) }]).unwrap();
+        let mut world = ChunkWorld::new(ChunkStreamingConfig::default(), store).unwrap();
+        world.add_player(1);
+        let origin = ChunkCoord::new(0, 0, 0);
+
+        // Only chunks that ever changed are sent; the rest are implicitly empty.
+        let first = world.update_player(1, Vec3::new(10.0, 0.0, 10.0));
+        assert_eq!(first.iter().map(chunk_version).collect::<Vec<_>>(), vec![(east.to_packed_id(), 4)]);
+        assert!(world.is_loaded(origin));
+        assert!(world.update_player(1, Vec3::new(12.0, 0.0, 10.0)).is_empty());
+
+        world.chunk_mut(origin).unwrap().resource_nodes.push(ChunkResourceNode {
+            node_id: 7,
+            resource_type: "gold".to_string(),
+            position: [10.0, 0.0, 10.0],
+            current_amount: 100.0,
+            max_amount: 100.0,
+            regen_rate: 1.0,
+            sustainability_score: 1.0,
+            depleted: false,
+        });
+        assert!(world.is_dirty(origin));
+        let changed = world.update_player(1, Vec3::new(12.0, 0.0, 10.0));
+        assert_eq!(changed.iter().map(chunk_version).collect::<Vec<_>>(), vec![(origin.to_packed_id(), 1)]);
+
+        assert_eq!(world.flush_dirty().unwrap(), 1);
+        assert!(!world.is_dirty(origin));
+        assert_eq!(world.flush_dirty().unwrap(), 0);
+    }
+
+    #[test]
+    fn unload_waits_for_unload_radius() {
+        let mut world = ChunkWorld::new(ChunkStreamingConfig::default(), Arc::new(InMemoryChunkStore::new())).unwrap();
+        world.add_player(1);
+        let origin = ChunkCoord::new(0, 0, 0);
+        world.chunk_mut(origin).unwrap();
+        assert_eq!(world.update_player(1, Vec3::ZERO).len(), 1);
+
+        // Past load_radius but inside unload_radius: still loaded, not resent on return.
+        world.update_player(1, Vec3::new(300.0, 0.0, 0.0));
+        world.unload_far().unwrap();
+        assert!(world.is_loaded(origin));
+        assert!(world.is_dirty(origin));
+        assert!(!world.update_player(1, Vec3::ZERO).iter().any(|m| chunk_version(m).0 == origin.to_packed_id()));
+
+        world.update_player(1, Vec3::new(2000.0, 0.0, 0.0));
+        assert!(world.unload_far().unwrap() > 0);
+        assert!(!world.is_loaded(origin));
+        assert!(!world.is_dirty(origin));
+        assert!(world.update_player(1, Vec3::ZERO).iter().any(|m| chunk_version(m).0 == origin.to_packed_id()));
+    }
+
+    #[test]
+    fn bad_config_is_refused() {
+        let store = || Arc::new(InMemoryChunkStore::new());
+        let inverted = ChunkStreamingConfig { load_radius: 300.0, unload_radius: 256.0, ..Default::default() };
+        assert_eq!(
+            ChunkWorld::new(inverted, store()).err(),
+            Some(ChunkConfigError::Radii { load_radius: 300.0, unload_radius: 256.0 })
+        );
+        let zero = ChunkStreamingConfig { chunk_size: 0.0, ..Default::default() };
+        assert_eq!(ChunkWorld::new(zero, store()).err(), Some(ChunkConfigError::ChunkSize(0.0)));
+    }
+}
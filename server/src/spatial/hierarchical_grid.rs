@@ -7,6 +7,7 @@
 //! - Hierarchical coarse-to-fine traversal (start at largest cells, early reject empty space)
 //! - Improved DDA stepping with cell-boundary calculation
 //! - Early termination on first hit at any level
+//!
 //! This dramatically improves performance for occlusion culling now enabled by default in InterestManager.
 
 use fxhash::FxHashMap;
@@ -60,9 +61,9 @@ impl HierarchicalGrid {
         let mut y = cell.1 as u64;
         let mut z = cell.2 as u64;
         for i in 0..21 {
-            code |= ((x & 1) << (3 * i));
-            code |= ((y & 1) << (3 * i + 1));
-            code |= ((z & 1) << (3 * i + 2));
+            code |= (x & 1) << (3 * i);
+            code |= (y & 1) << (3 * i + 1);
+            code |= (z & 1) << (3 * i + 2);
             x >>= 1;
             y >>= 1;
             z >>= 1;
@@ -120,6 +121,7 @@ impl HierarchicalGrid {
     /// 1. Coarse-to-fine traversal: Check largest cells first for early rejection of empty space.
     /// 2. True DDA stepping: Calculate exact next cell boundary instead of fixed steps.
     /// 3. Early termination: Return as soon as any level reports an occupied cell.
+    ///
     /// This provides significant speedup for occlusion culling now enabled by default in InterestManager.
     pub fn raycast_distance(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<f32> {
         if max_distance <= 0.0 {
@@ -31,6 +31,8 @@ use powrush_rbe_engine::RbeResourcePool; // assume available for future RBE tie-
 // ============================================================================
 // CHANNEL DEFINITIONS (wise separation of concerns for MMO scale)
 // ============================================================================
+// The live tokio transport makes the same split without renet: shared::datagram
+// routes high-frequency messages over its UDP channel (network/datagram.rs).
 
 /// Reliable Ordered channel — for important state (spawns, ownership, critical replication, events)
 pub const RELIABLE_REPLICATION_CHANNEL: u8 = 0;
@@ -163,6 +165,7 @@ pub enum ValidationResult {
     RateLimited,
 }
 
+/// Live transport path: crate::movement::MovementAuthority (tick budget, clamping, collision).
 pub struct ServerValidator {
     pub max_speed_per_tick: f32,
     pub last_positions: HashMap<u64, (glam::Vec3, u64)>,
@@ -1,27 +1,41 @@
 // server/src/trade/cryptographic_trade_protocol.rs
-// Cryptographic Trade Protocol v2.2
-// Hybrid Signatures (Ed25519 + Dilithium) + Commitment + Reveal
-// Corrected Dilithium public key handling for reliable verification
+// Cryptographic Trade Protocol v2.4
+// Hybrid Signatures (Ed25519 + ML-DSA-65 via rsil-identity) + Commitment + Reveal
+// v2.3 — Dilithium placeholder replaced by real FIPS 204 signatures; offers carry the signer DID
+// v2.4 — Canonical commitment over both parties and both sides of the swap (escrow confirms it)
+// v2.5 — Offers verify against the offeror's authenticated DID document (current key only),
+//        not just the genesis key of whatever DID the offer claims
 // AG-SML v1.0 | PATSAGi + Ra-Thor aligned
 
 use crate::trade_system::Trade;
-use ed25519_dalek::{Keypair as EdKeypair, Signature as EdSignature, Signer, Verifier};
-use pqcrypto_dilithium::dilithium2::{self, PublicKey as DilPublicKey, SecretKey as DilSecretKey, DetachedSignature as DilSignature};
+use rsil_identity::{verify_hybrid, DidDocument, HybridPublicKey, HybridSignature, SovereignKeypair};
 use serde::{Serialize, Deserialize};
-use thiserror::Error;
+use std::fmt;
 
-#[derive(Debug, Error)]
+/// Domain separator so a trade signature can never be replayed as a handshake answer
+pub const TRADE_DOMAIN: &[u8] = b"powrush-rsil-trade-v1";
+
+#[derive(Debug)]
 pub enum CryptoTradeError {
-    #[error("Key generation failed")]
     KeyGenerationFailed,
-    #[error("Signing failed")]
     SigningFailed,
-    #[error("Verification failed")]
     VerificationFailed,
-    #[error("Commitment mismatch")]
     CommitmentMismatch,
 }
 
+impl fmt::Display for CryptoTradeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoTradeError::KeyGenerationFailed => write!(f, "Key generation failed"),
+            CryptoTradeError::SigningFailed => write!(f, "Signing failed"),
+            CryptoTradeError::VerificationFailed => write!(f, "Verification failed"),
+            CryptoTradeError::CommitmentMismatch => write!(f, "Commitment mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for CryptoTradeError {}
+
 /// Cryptographic commitment to a trade offer
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TradeCommitment {
@@ -31,173 +45,136 @@ pub struct TradeCommitment {
     pub created_at: u64,
 }
 
-/// Hybrid signature container (Classical + Post-Quantum)
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct HybridSignature {
-    pub classical_signature: Vec<u8>,   // Ed25519
-    pub pq_signature: Vec<u8>,          // Dilithium
-}
-
 /// Cryptographically signed and committed trade offer (Hybrid)
 #[derive(Clone, Debug)]
 pub struct CryptographicTradeOffer {
     pub trade: Trade,
     pub commitment: TradeCommitment,
-    pub classical_public_key: Vec<u8>,  // Ed25519 public key
-    pub pq_public_key: Vec<u8>,         // Dilithium public key
+    pub signer_did: String,
+    pub public_key: HybridPublicKey,    // Ed25519 + ML-DSA-65
     pub signature: HybridSignature,
 }
 
 /// Main protocol interface
 pub trait CryptographicTradeProtocol {
-    fn generate_keypair(&self) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>), CryptoTradeError>;
+    fn generate_keypair(&self) -> Result<SovereignKeypair, CryptoTradeError>;
 
+    /// `signer_did` is the identity `keypair` currently signs for (after a
+    /// rotation that is not `keypair.did()`).
     fn create_signed_offer(
         &self,
         trade: &Trade,
-        classical_secret: &[u8],
-        classical_public: &[u8],
-        pq_secret: &[u8],
-        pq_public: &[u8],
+        keypair: &SovereignKeypair,
+        signer_did: &str,
     ) -> Result<CryptographicTradeOffer, CryptoTradeError>;
 
-    fn verify_offer(&self, offer: &CryptographicTradeOffer) -> bool;
+    /// `identity` is the DID document the offeror authenticated with.
+    fn verify_offer(&self, offer: &CryptographicTradeOffer, identity: &DidDocument) -> bool;
 
     fn reveal_and_validate(
         &self,
         offer: &CryptographicTradeOffer,
         revealed_trade: &Trade,
+        identity: &DidDocument,
     ) -> Result<(), CryptoTradeError>;
 }
 
-/// Hybrid implementation (Ed25519 + Dilithium2)
+/// Hybrid implementation (Ed25519 + ML-DSA-65)
 pub struct HybridTradeProtocol;
 
+/// Commitment both parties lock and confirm in escrow.
+/// Covers the parties, both item lists (sorted, so HashMap order never leaks in) and the nonce.
+pub fn commitment_hash(trade: &Trade) -> Vec<u8> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(TRADE_DOMAIN);
+    hasher.update(&trade.trade_id.to_le_bytes());
+    hasher.update(&trade.offeror_id.to_le_bytes());
+    hasher.update(&trade.target_id.to_le_bytes());
+    hasher.update(&trade.nonce.to_le_bytes());
+    for side in [&trade.offered, &trade.requested] {
+        let mut items: Vec<(&String, &f32)> = side.iter().collect();
+        items.sort_by(|a, b| a.0.cmp(b.0));
+        hasher.update(&(items.len() as u64).to_le_bytes());
+        for (resource, amount) in items {
+            hasher.update(&(resource.len() as u64).to_le_bytes());
+            hasher.update(resource.as_bytes());
+            hasher.update(&amount.to_bits().to_le_bytes());
+        }
+    }
+    hasher.finalize().as_bytes().to_vec()
+}
+
+fn signing_bytes(commitment: &TradeCommitment) -> Result<Vec<u8>, CryptoTradeError> {
+    let commitment_bytes = bincode::serialize(commitment).map_err(|_| CryptoTradeError::SigningFailed)?;
+    let mut msg = TRADE_DOMAIN.to_vec();
+    msg.extend_from_slice(&commitment_bytes);
+    Ok(msg)
+}
+
 impl CryptographicTradeProtocol for HybridTradeProtocol {
-    fn generate_keypair(&self) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>), CryptoTradeError> {
-        // Classical Ed25519
-        let ed_keypair = EdKeypair::generate(&mut rand::rngs::OsRng);
-        let classical_pk = ed_keypair.public.to_bytes().to_vec();
-        let classical_sk = ed_keypair.secret.to_bytes().to_vec();
-
-        // Post-quantum Dilithium
-        let (dil_pk, dil_sk) = dilithium2::keypair();
-        let pq_pk = dil_pk.as_bytes().to_vec();
-        let pq_sk = dil_sk.as_bytes().to_vec();
-
-        Ok((classical_pk, classical_sk, pq_pk, pq_sk))
+    fn generate_keypair(&self) -> Result<SovereignKeypair, CryptoTradeError> {
+        SovereignKeypair::generate().map_err(|_| CryptoTradeError::KeyGenerationFailed)
     }
 
     fn create_signed_offer(
         &self,
         trade: &Trade,
-        classical_secret: &[u8],
-        classical_public: &[u8],
-        pq_secret: &[u8],
-        pq_public: &[u8],
+        keypair: &SovereignKeypair,
+        signer_did: &str,
     ) -> Result<CryptographicTradeOffer, CryptoTradeError> {
-        // Reconstruct Ed25519 keypair
-        let classical_sk = ed25519_dalek::SecretKey::from_bytes(classical_secret)
-            .map_err(|_| CryptoTradeError::SigningFailed)?;
-        let classical_pk = ed25519_dalek::PublicKey::from_bytes(classical_public)
-            .map_err(|_| CryptoTradeError::SigningFailed)?;
-        let ed_keypair = EdKeypair { secret: classical_sk, public: classical_pk };
-
-        // Dilithium keys (public key passed in for correctness)
-        let _dil_sk = DilSecretKey::from_bytes(pq_secret)
-            .map_err(|_| CryptoTradeError::SigningFailed)?;
-        let dil_pk = DilPublicKey::from_bytes(pq_public)
-            .map_err(|_| CryptoTradeError::SigningFailed)?;
-
-        // Create commitment
-        let commitment_data = format!("{:?}:{:?}:{}", trade.offered, trade.nonce, trade.trade_id);
-        let commitment_hash = blake3::hash(commitment_data.as_bytes()).as_bytes().to_vec();
-
         let commitment = TradeCommitment {
             trade_id: trade.trade_id,
             offeror_id: trade.offeror_id,
-            commitment_hash,
+            commitment_hash: commitment_hash(trade),
             created_at: trade.created_at,
         };
 
-        let commitment_bytes = bincode::serialize(&commitment)
+        // Both schemes sign the same domain-separated commitment bytes
+        let signature = keypair
+            .sign_hybrid(&signing_bytes(&commitment)?)
             .map_err(|_| CryptoTradeError::SigningFailed)?;
 
-        // Sign with both schemes (using reconstructed Ed25519 keypair and Dilithium secret)
-        let classical_sig = ed_keypair.sign(&commitment_bytes);
-        let pq_sig = dilithium2::detached_sign(&commitment_bytes, &DilSecretKey::from_bytes(pq_secret).unwrap());
-
-        let hybrid_sig = HybridSignature {
-            classical_signature: classical_sig.to_bytes().to_vec(),
-            pq_signature: pq_sig.as_bytes().to_vec(),
-        };
-
         Ok(CryptographicTradeOffer {
             trade: trade.clone(),
             commitment,
-            classical_public_key: classical_pk.to_bytes().to_vec(),
-            pq_public_key: dil_pk.as_bytes().to_vec(),
-            signature: hybrid_sig,
+            signer_did: signer_did.to_string(),
+            public_key: keypair.hybrid_public_key(),
+            signature,
         })
     }
 
-    fn verify_offer(&self, offer: &CryptographicTradeOffer) -> bool {
-        // Verify Ed25519 signature
-        let classical_pk = match ed25519_dalek::PublicKey::from_bytes(&offer.classical_public_key) {
-            Ok(pk) => pk,
-            Err(_) => return false,
-        };
-        let classical_sig = match EdSignature::from_bytes(&offer.signature.classical_signature) {
-            Ok(sig) => sig,
-            Err(_) => return false,
-        };
-
-        let commitment_bytes = match bincode::serialize(&offer.commitment) {
-            Ok(b) => b,
-            Err(_) => return false,
-        };
-
-        if classical_pk.verify(&commitment_bytes, &classical_sig).is_err() {
+    fn verify_offer(&self, offer: &CryptographicTradeOffer, identity: &DidDocument) -> bool {
+        // Signed by the offeror's own identity, with the key that is active for it now:
+        // a rotated-away or revoked key no longer signs trades.
+        if offer.signer_did != identity.id || identity.authorizes(&offer.public_key).is_err() {
             return false;
         }
 
-        // Verify Dilithium signature (core logic)
-        let pq_pk = match DilPublicKey::from_bytes(&offer.pq_public_key) {
-            Ok(pk) => pk,
-            Err(_) => return false,
-        };
-        let pq_sig = match DilSignature::from_bytes(&offer.signature.pq_signature) {
-            Ok(sig) => sig,
+        let msg = match signing_bytes(&offer.commitment) {
+            Ok(m) => m,
             Err(_) => return false,
         };
 
-        // This is the key Dilithium verification step
-        if dilithium2::verify_detached_signature(&pq_sig, &commitment_bytes, &pq_pk).is_err() {
-            return false;
-        }
-
-        true
+        // Both Ed25519 and ML-DSA-65 must verify; either one failing rejects the offer
+        verify_hybrid(&offer.public_key, &msg, &offer.signature).is_ok()
     }
 
     fn reveal_and_validate(
         &self,
         offer: &CryptographicTradeOffer,
         revealed_trade: &Trade,
+        identity: &DidDocument,
     ) -> Result<(), CryptoTradeError> {
-        let commitment_data = format!(
-            "{:?}:{:?}:{}",
-            revealed_trade.offered, revealed_trade.nonce, revealed_trade.trade_id
-        );
-        let recomputed_hash = blake3::hash(commitment_data.as_bytes()).as_bytes().to_vec();
-
-        if recomputed_hash != offer.commitment.commitment_hash {
+        if commitment_hash(revealed_trade) != offer.commitment.commitment_hash
+            || revealed_trade.offeror_id != offer.commitment.offeror_id
+        {
             return Err(CryptoTradeError::CommitmentMismatch);
         }
 
-        if !self.verify_offer(offer) {
+        if !self.verify_offer(offer, identity) {
             return Err(CryptoTradeError::VerificationFailed);
         }
 
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,1084 @@
+/*!
+ * server/src/trade/escrow.rs
+ *
+ * Two-phase trade escrow. A trade moves through:
+ *
+ *   offer / counter  → terms + canonical commitment hash (Negotiating)
+ *   lock             → each side's items leave its inventory into escrow,
+ *                      against the hash it saw (Locking; terms now frozen)
+ *   confirm          → both parties re-confirm the same hash; the second
+ *                      confirm writes `Settled` to the ledger and swaps
+ *   cancel / expire  → `Cancelled` is written and escrow is refunded
+ *
+ * Every inventory change is journaled: an intent entry is appended before
+ * it and a completion entry after it, and each player save records the
+ * ledger seq it last applied. A crash in between leaves an intent with no
+ * completion; recovery replays it, skipping saves that already applied it,
+ * and then refunds whatever the ledger still shows in escrow. A failure
+ * while a change is in flight holds every later change until it completes.
+ * Escrowed items are out of both inventories, so a concurrent harvest or a
+ * disconnect cannot spend or duplicate them.
+ * Terms may only name items the shared item registry resolves, in whole units.
+ * Items move through PersistenceManager: the player saves are the inventory
+ * authority, and a settlement credits both parties in one batch write.
+ *
+ * AG-SML v1.0 | TOLC 8 + RBE | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::collections::HashMap;
+use std::fmt;
+
+use bevy::prelude::Resource;
+use shared::items;
+use shared::protocol::{ClientMessage, ServerMessage, WireTrade, WireTradeItem, WireTradeState};
+use tracing::{error, info, warn};
+
+use crate::persistence::player_store::to_hex;
+use crate::persistence_polish::{PersistenceManager, PlayerSaveData};
+use crate::trade::cryptographic_trade_protocol::commitment_hash;
+use crate::trade::ledger::{IncompleteIntent, InventoryOp, LedgerError, LedgerEvent, TradeItems, TradeLedger};
+use crate::trade_system::Trade;
+
+/// Open trades are cancelled and refunded after this long
+pub const TRADE_TTL_SECS: u64 = 300;
+
+// ============================================================================
+// Inventories
+// ============================================================================
+
+/// Inventory access the escrow needs, keyed by player id.
+pub trait EscrowInventories {
+    /// Take `items` from `player_id` for ledger intent `seq`. All-or-nothing:
+    /// nothing is taken on Err. A save that already applied `seq` is left as is.
+    fn withdraw(&mut self, seq: u64, player_id: u64, items: &TradeItems) -> Result<(), EscrowError>;
+
+    /// Err when `credits` would not fit; writes nothing.
+    fn check_room(&mut self, credits: &[(u64, TradeItems)]) -> Result<(), EscrowError>;
+
+    /// Credit every player in one write for ledger intent `seq`; nothing is
+    /// credited on Err. Saves that already applied `seq` are skipped.
+    fn deposit(&mut self, seq: u64, credits: &[(u64, TradeItems)]) -> Result<(), EscrowError>;
+}
+
+/// Registry item id, unit count and stack limit of one trade item.
+fn units(resource: &str, amount: f32) -> Result<(u32, u64, u32), EscrowError> {
+    let def = items::current().resolve(resource).map_err(|e| EscrowError::InvalidTerms(e.to_string()))?.clone();
+    Ok((def.item_id, amount as u64, def.stack_limit))
+}
+
+fn load_save(persistence: &mut PersistenceManager, player_id: u64) -> Result<PlayerSaveData, EscrowError> {
+    persistence
+        .load_player(player_id)
+        .ok_or_else(|| EscrowError::Inventory(format!("could not load save for player {}", player_id)))
+}
+
+/// The saves `credits` would produce for intent `seq`, one per player that has
+/// not applied it yet (every player when `seq` is None).
+fn credited(
+    persistence: &mut PersistenceManager,
+    credits: &[(u64, TradeItems)],
+    seq: Option<u64>,
+) -> Result<Vec<PlayerSaveData>, EscrowError> {
+    let mut saves: Vec<PlayerSaveData> = Vec::new();
+    let mut applied: Vec<u64> = Vec::new();
+    for (player_id, items) in credits {
+        if applied.contains(player_id) {
+            continue;
+        }
+        let index = match saves.iter().position(|s| s.player_id == *player_id) {
+            Some(index) => index,
+            None => {
+                let save = load_save(persistence, *player_id)?;
+                if seq.is_some_and(|seq| save.escrow_seq >= seq) {
+                    applied.push(*player_id);
+                    continue;
+                }
+                saves.push(save);
+                saves.len() - 1
+            }
+        };
+        for (resource, amount) in items {
+            let (item_id, count, limit) = units(resource, *amount)?;
+            if !saves[index].give_items(item_id, count, limit) {
+                return Err(EscrowError::NoRoom { player_id: *player_id });
+            }
+        }
+    }
+    if let Some(seq) = seq {
+        saves.iter_mut().for_each(|save| save.escrow_seq = seq);
+    }
+    Ok(saves)
+}
+
+impl EscrowInventories for PersistenceManager {
+    fn withdraw(&mut self, seq: u64, player_id: u64, items: &TradeItems) -> Result<(), EscrowError> {
+        let mut player = load_save(self, player_id)?;
+        if player.escrow_seq >= seq {
+            return Ok(());
+        }
+        for (resource, amount) in items {
+            let (item_id, count, _) = units(resource, *amount)?;
+            let available = player.held(item_id);
+            if available < count {
+                return Err(EscrowError::Insufficient {
+                    player_id,
+                    resource: resource.clone(),
+                    needed: *amount,
+                    available: available as f32,
+                });
+            }
+            player.take_items(item_id, count);
+        }
+        player.escrow_seq = seq;
+        self.save_player(&player).map_err(EscrowError::Inventory)
+    }
+
+    fn check_room(&mut self, credits: &[(u64, TradeItems)]) -> Result<(), EscrowError> {
+        credited(self, credits, None).map(|_| ())
+    }
+
+    fn deposit(&mut self, seq: u64, credits: &[(u64, TradeItems)]) -> Result<(), EscrowError> {
+        let saves = credited(self, credits, Some(seq))?;
+        self.flush_batch(&saves).map_err(EscrowError::Inventory)
+    }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EscrowError {
+    UnknownTrade(u64),
+    NotParticipant { trade_id: u64, player_id: u64 },
+    InvalidTerms(String),
+    /// Counter after a side locked; cancel and re-offer instead.
+    TermsFrozen(u64),
+    CommitmentMismatch(u64),
+    /// Confirm before both sides are in escrow.
+    NotLocked(u64),
+    Insufficient { player_id: u64, resource: String, needed: f32, available: f32 },
+    /// The receiving inventory cannot hold what the trade would credit.
+    NoRoom { player_id: u64 },
+    /// Reading or writing a player save failed.
+    Inventory(String),
+    Ledger(String),
+}
+
+impl fmt::Display for EscrowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EscrowError::UnknownTrade(id) => write!(f, "trade {} is not open", id),
+            EscrowError::NotParticipant { trade_id, player_id } => {
+                write!(f, "player {} is not a party to trade {}", player_id, trade_id)
+            }
+            EscrowError::InvalidTerms(reason) => write!(f, "invalid trade terms: {}", reason),
+            EscrowError::TermsFrozen(id) => write!(f, "trade {} is locked; cancel to change terms", id),
+            EscrowError::CommitmentMismatch(id) => write!(f, "commitment hash does not match trade {}", id),
+            EscrowError::NotLocked(id) => write!(f, "trade {} is not locked by both parties", id),
+            EscrowError::Insufficient { player_id, resource, needed, available } => write!(
+                f,
+                "player {} needs {} {} but holds {}",
+                player_id, needed, resource, available
+            ),
+            EscrowError::NoRoom { player_id } => write!(f, "player {} has no inventory room for the trade", player_id),
+            EscrowError::Inventory(e) => write!(f, "inventory: {}", e),
+            EscrowError::Ledger(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for EscrowError {}
+
+impl From<LedgerError> for EscrowError {
+    fn from(e: LedgerError) -> Self {
+        EscrowError::Ledger(e.to_string())
+    }
+}
+
+// ============================================================================
+// Escrow state
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscrowState {
+    Negotiating,
+    Locking,
+    Settled,
+    Cancelled,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EscrowSide {
+    /// Items currently held in escrow for this side
+    pub locked: Option<TradeItems>,
+    pub confirmed: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct EscrowTrade {
+    pub trade: Trade,
+    pub commitment_hash: Vec<u8>,
+    pub state: EscrowState,
+    pub offeror: EscrowSide,
+    pub target: EscrowSide,
+}
+
+impl EscrowTrade {
+    fn new(trade: Trade) -> Self {
+        Self {
+            commitment_hash: commitment_hash(&trade),
+            trade,
+            state: EscrowState::Negotiating,
+            offeror: EscrowSide::default(),
+            target: EscrowSide::default(),
+        }
+    }
+
+    pub fn trade_id(&self) -> u64 {
+        self.trade.trade_id
+    }
+
+    pub fn is_party(&self, player_id: u64) -> bool {
+        self.trade.offeror_id == player_id || self.trade.target_id == player_id
+    }
+
+    pub fn counterparty(&self, player_id: u64) -> u64 {
+        if self.trade.offeror_id == player_id {
+            self.trade.target_id
+        } else {
+            self.trade.offeror_id
+        }
+    }
+
+    /// What `player_id` puts into escrow
+    pub fn gives(&self, player_id: u64) -> TradeItems {
+        if self.trade.offeror_id == player_id {
+            sorted_items(&self.trade.offered)
+        } else {
+            sorted_items(&self.trade.requested)
+        }
+    }
+
+    fn side_mut(&mut self, player_id: u64) -> &mut EscrowSide {
+        if self.trade.offeror_id == player_id {
+            &mut self.offeror
+        } else {
+            &mut self.target
+        }
+    }
+
+    fn terms_event(&self, countered: bool) -> LedgerEvent {
+        let (offeror_id, target_id) = (self.trade.offeror_id, self.trade.target_id);
+        let (offered, requested) = (sorted_items(&self.trade.offered), sorted_items(&self.trade.requested));
+        let commitment_hash = to_hex(&self.commitment_hash);
+        if countered {
+            LedgerEvent::Countered { offeror_id, target_id, offered, requested, commitment_hash }
+        } else {
+            LedgerEvent::Offered { offeror_id, target_id, offered, requested, commitment_hash }
+        }
+    }
+
+    fn refunds(&self) -> Vec<(u64, TradeItems)> {
+        [(self.trade.offeror_id, &self.offeror), (self.trade.target_id, &self.target)]
+            .into_iter()
+            .filter_map(|(player, side)| side.locked.clone().map(|items| (player, items)))
+            .collect()
+    }
+
+    fn set_state(&mut self, state: EscrowState) {
+        self.state = state;
+        self.trade.status = match state {
+            EscrowState::Negotiating => "pending",
+            EscrowState::Locking => "locking",
+            EscrowState::Settled => "completed",
+            EscrowState::Cancelled => "cancelled",
+        }
+        .to_string();
+    }
+
+    pub fn to_wire(&self) -> WireTrade {
+        let items = |side: &HashMap<String, f32>| {
+            sorted_items(side)
+                .into_iter()
+                .map(|(resource, amount)| WireTradeItem { resource, amount })
+                .collect()
+        };
+        WireTrade {
+            trade_id: self.trade.trade_id,
+            offeror_id: self.trade.offeror_id,
+            target_id: self.trade.target_id,
+            offered: items(&self.trade.offered),
+            requested: items(&self.trade.requested),
+            commitment_hash: self.commitment_hash.clone(),
+            state: match self.state {
+                EscrowState::Negotiating => WireTradeState::Negotiating,
+                EscrowState::Locking => WireTradeState::Locking,
+                EscrowState::Settled => WireTradeState::Settled,
+                EscrowState::Cancelled => WireTradeState::Cancelled,
+            },
+            offeror_locked: self.offeror.locked.is_some(),
+            target_locked: self.target.locked.is_some(),
+            offeror_confirmed: self.offeror.confirmed,
+            target_confirmed: self.target.confirmed,
+            expires_at: self.trade.expires_at.unwrap_or(0),
+        }
+    }
+}
+
+pub fn sorted_items(items: &HashMap<String, f32>) -> TradeItems {
+    let mut out: TradeItems = items.iter().map(|(r, a)| (r.clone(), *a)).collect();
+    out.sort_by(|a, b| a.0.cmp(&b.0));
+    out
+}
+
+fn validate_terms(
+    offeror_id: u64,
+    target_id: u64,
+    offered: &HashMap<String, f32>,
+    requested: &HashMap<String, f32>,
+) -> Result<(), EscrowError> {
+    if offeror_id == target_id {
+        return Err(EscrowError::InvalidTerms("cannot trade with yourself".into()));
+    }
+    if offered.is_empty() && requested.is_empty() {
+        return Err(EscrowError::InvalidTerms("nothing offered or requested".into()));
+    }
+    for (resource, amount) in offered.iter().chain(requested.iter()) {
+        if resource.is_empty() {
+            return Err(EscrowError::InvalidTerms("empty resource name".into()));
+        }
+        if !amount.is_finite() || *amount <= 0.0 {
+            return Err(EscrowError::InvalidTerms(format!("{} amount must be positive", resource)));
+        }
+        if amount.fract() != 0.0 {
+            return Err(EscrowError::InvalidTerms(format!("{} must be traded in whole units", resource)));
+        }
+    }
+    Ok(())
+}
+
+/// Result of a confirm: still waiting on the other party, or settled.
+#[derive(Clone, Debug)]
+pub enum ConfirmOutcome {
+    Pending(EscrowTrade),
+    Settled { trade: EscrowTrade, ledger_seq: u64 },
+}
+
+// ============================================================================
+// Escrow
+// ============================================================================
+
+#[derive(Resource)]
+pub struct TradeEscrow {
+    trades: HashMap<u64, EscrowTrade>,
+    next_trade_id: u64,
+    next_nonce: u64,
+    ledger: TradeLedger,
+    /// Intents whose completion is not recorded yet, oldest first. While any is
+    /// left, no new inventory change starts: a save's `escrow_seq` only proves
+    /// which intents it applied if they land in ledger order.
+    in_flight: Vec<IncompleteIntent>,
+}
+
+impl TradeEscrow {
+    /// Intents the ledger left incomplete are replayed before the first inventory change.
+    pub fn new(ledger: TradeLedger) -> Self {
+        let next_trade_id = ledger.max_trade_id() + 1;
+        let in_flight = ledger.incomplete_intents();
+        Self { trades: HashMap::new(), next_trade_id, next_nonce: 1, ledger, in_flight }
+    }
+
+    /// Escrow over a durable ledger file (see TradeLedger::open).
+    pub fn open_durable(path: impl AsRef<std::path::Path>) -> Result<Self, String> {
+        TradeLedger::open(path).map(Self::new).map_err(|e| e.to_string())
+    }
+
+    pub fn ledger(&self) -> &TradeLedger {
+        &self.ledger
+    }
+
+    pub fn get(&self, trade_id: u64) -> Option<&EscrowTrade> {
+        self.trades.get(&trade_id)
+    }
+
+    pub fn open_trades_for(&self, player_id: u64) -> Vec<u64> {
+        let mut ids: Vec<u64> = self.trades.values().filter(|t| t.is_party(player_id)).map(|t| t.trade_id()).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    pub fn expired(&self, now_ms: u64) -> Vec<u64> {
+        let now = now_ms / 1000;
+        let mut ids: Vec<u64> = self
+            .trades
+            .values()
+            .filter(|t| t.trade.expires_at.is_some_and(|expiry| now > expiry))
+            .map(|t| t.trade_id())
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    fn take_nonce(&mut self) -> u64 {
+        let nonce = self.next_nonce;
+        self.next_nonce += 1;
+        nonce
+    }
+
+    fn open_trade(&self, trade_id: u64, player_id: u64) -> Result<&EscrowTrade, EscrowError> {
+        let trade = self.trades.get(&trade_id).ok_or(EscrowError::UnknownTrade(trade_id))?;
+        if !trade.is_party(player_id) {
+            return Err(EscrowError::NotParticipant { trade_id, player_id });
+        }
+        Ok(trade)
+    }
+
+    pub fn offer(
+        &mut self,
+        offeror_id: u64,
+        target_id: u64,
+        offered: HashMap<String, f32>,
+        requested: HashMap<String, f32>,
+        now_ms: u64,
+    ) -> Result<&EscrowTrade, EscrowError> {
+        validate_terms(offeror_id, target_id, &offered, &requested)?;
+
+        let now = now_ms / 1000;
+        let trade = EscrowTrade::new(Trade {
+            trade_id: self.next_trade_id,
+            offeror_id,
+            target_id,
+            offered,
+            requested,
+            status: "pending".to_string(),
+            created_at: now,
+            expires_at: Some(now + TRADE_TTL_SECS),
+            nonce: self.take_nonce(),
+        });
+        let trade_id = trade.trade_id();
+
+        self.ledger.append(trade_id, now_ms, trade.terms_event(false))?;
+        self.next_trade_id += 1;
+        Ok(self.trades.entry(trade_id).or_insert(trade))
+    }
+
+    /// Replace the terms of an unlocked trade. The counterer becomes the offeror,
+    /// and a fresh nonce makes every earlier commitment hash stale.
+    pub fn counter(
+        &mut self,
+        player_id: u64,
+        trade_id: u64,
+        offered: HashMap<String, f32>,
+        requested: HashMap<String, f32>,
+        now_ms: u64,
+    ) -> Result<&EscrowTrade, EscrowError> {
+        let current = self.open_trade(trade_id, player_id)?;
+        if current.state != EscrowState::Negotiating {
+            return Err(EscrowError::TermsFrozen(trade_id));
+        }
+        let target_id = current.counterparty(player_id);
+        validate_terms(player_id, target_id, &offered, &requested)?;
+
+        let now = now_ms / 1000;
+        let mut trade = current.trade.clone();
+        trade.offeror_id = player_id;
+        trade.target_id = target_id;
+        trade.offered = offered;
+        trade.requested = requested;
+        trade.expires_at = Some(now + TRADE_TTL_SECS);
+        trade.nonce = self.take_nonce();
+        let countered = EscrowTrade::new(trade);
+
+        self.ledger.append(trade_id, now_ms, countered.terms_event(true))?;
+        self.trades.insert(trade_id, countered);
+        Ok(&self.trades[&trade_id])
+    }
+
+    /// Move `player_id`'s side into escrow. All-or-nothing: if any item is
+    /// short, nothing leaves the inventory.
+    pub fn lock(
+        &mut self,
+        player_id: u64,
+        trade_id: u64,
+        hash: &[u8],
+        inventories: &mut impl EscrowInventories,
+        now_ms: u64,
+    ) -> Result<&EscrowTrade, EscrowError> {
+        self.resume(inventories, now_ms)?;
+        let trade = self.open_trade(trade_id, player_id)?;
+        if trade.commitment_hash != hash {
+            return Err(EscrowError::CommitmentMismatch(trade_id));
+        }
+        let already_locked = if trade.trade.offeror_id == player_id {
+            trade.offeror.locked.is_some()
+        } else {
+            trade.target.locked.is_some()
+        };
+        if already_locked {
+            return Ok(&self.trades[&trade_id]);
+        }
+
+        let items = trade.gives(player_id);
+
+        let seq = self.ledger.append(trade_id, now_ms, LedgerEvent::Locking { player_id, items: items.clone() })?.seq;
+        self.apply(IncompleteIntent { seq, trade_id, op: InventoryOp::Withdraw { player_id, items } }, inventories, now_ms)?;
+        Ok(&self.trades[&trade_id])
+    }
+
+    /// Confirm the locked terms. The second matching confirm settles the trade.
+    pub fn confirm(
+        &mut self,
+        player_id: u64,
+        trade_id: u64,
+        hash: &[u8],
+        inventories: &mut impl EscrowInventories,
+        now_ms: u64,
+    ) -> Result<ConfirmOutcome, EscrowError> {
+        self.resume(inventories, now_ms)?;
+        let trade = self.open_trade(trade_id, player_id)?;
+        if trade.commitment_hash != hash {
+            return Err(EscrowError::CommitmentMismatch(trade_id));
+        }
+        if trade.offeror.locked.is_none() || trade.target.locked.is_none() {
+            return Err(EscrowError::NotLocked(trade_id));
+        }
+
+        let trade = self.trades.get_mut(&trade_id).expect("checked above");
+        trade.side_mut(player_id).confirmed = true;
+        if !(trade.offeror.confirmed && trade.target.confirmed) {
+            return Ok(ConfirmOutcome::Pending(trade.clone()));
+        }
+
+        // Both parties must have room before the settlement is committed.
+        let credits = vec![
+            (trade.trade.target_id, trade.offeror.locked.clone().unwrap_or_default()),
+            (trade.trade.offeror_id, trade.target.locked.clone().unwrap_or_default()),
+        ];
+        if let Err(e) = inventories.check_room(&credits) {
+            trade.side_mut(player_id).confirmed = false;
+            return Err(e);
+        }
+
+        let event = LedgerEvent::Settled {
+            offeror_id: trade.trade.offeror_id,
+            target_id: trade.trade.target_id,
+            offered: trade.offeror.locked.clone().unwrap_or_default(),
+            requested: trade.target.locked.clone().unwrap_or_default(),
+            commitment_hash: to_hex(&trade.commitment_hash),
+        };
+        let ledger_seq = match self.ledger.append(trade_id, now_ms, event) {
+            Ok(entry) => entry.seq,
+            Err(e) => {
+                // Not committed: stay locked, let the party retry the confirm.
+                trade.side_mut(player_id).confirmed = false;
+                return Err(e.into());
+            }
+        };
+
+        let mut trade = self.trades.remove(&trade_id).expect("checked above");
+        let (offeror_id, target_id) = (trade.trade.offeror_id, trade.trade.target_id);
+        trade.set_state(EscrowState::Settled);
+        let credit = IncompleteIntent { seq: ledger_seq, trade_id, op: InventoryOp::Deposit { credits } };
+        if let Err(e) = self.apply(credit, inventories, now_ms) {
+            error!("[TradeEscrow] Trade {} settled but not yet credited: {}", trade_id, e);
+            return Err(e);
+        }
+        info!("[TradeEscrow] Trade {} settled ({} ⇄ {}) at ledger seq {}", trade_id, offeror_id, target_id, ledger_seq);
+        Ok(ConfirmOutcome::Settled { trade, ledger_seq })
+    }
+
+    /// Cancel and refund. `by` is the requesting player, or None for server-side
+    /// cancels (disconnect, expiry).
+    pub fn cancel(
+        &mut self,
+        by: Option<u64>,
+        trade_id: u64,
+        reason: &str,
+        inventories: &mut impl EscrowInventories,
+        now_ms: u64,
+    ) -> Result<EscrowTrade, EscrowError> {
+        self.resume(inventories, now_ms)?;
+        let trade = match by {
+            Some(player_id) => self.open_trade(trade_id, player_id)?,
+            None => self.trades.get(&trade_id).ok_or(EscrowError::UnknownTrade(trade_id))?,
+        };
+        let refunds = trade.refunds();
+
+        let event = LedgerEvent::Cancelled { reason: reason.to_string(), refunds: refunds.clone() };
+        let seq = self.ledger.append(trade_id, now_ms, event)?.seq;
+
+        let mut trade = self.trades.remove(&trade_id).expect("checked above");
+        trade.set_state(EscrowState::Cancelled);
+        let refund = IncompleteIntent { seq, trade_id, op: InventoryOp::Deposit { credits: refunds } };
+        if let Err(e) = self.apply(refund, inventories, now_ms) {
+            error!("[TradeEscrow] Trade {} cancelled but not yet refunded: {}", trade_id, e);
+            return Err(e);
+        }
+        info!("[TradeEscrow] Trade {} cancelled: {}", trade_id, reason);
+        Ok(trade)
+    }
+
+    /// Run `intent`'s inventory change and record its completion. If either
+    /// step fails the intent stays in flight for `resume`. A refused withdrawal
+    /// moved nothing: it is recorded as `LockFailed` and the refusal returned.
+    fn apply(
+        &mut self,
+        intent: IncompleteIntent,
+        inventories: &mut impl EscrowInventories,
+        now_ms: u64,
+    ) -> Result<(), EscrowError> {
+        let (completion, refused) = match &intent.op {
+            InventoryOp::Withdraw { player_id, items } => match inventories.withdraw(intent.seq, *player_id, items) {
+                Ok(()) => (LedgerEvent::Locked { player_id: *player_id, items: items.clone() }, None),
+                Err(e) => (LedgerEvent::LockFailed { player_id: *player_id, reason: e.to_string() }, Some(e)),
+            },
+            InventoryOp::Deposit { credits } => match inventories.deposit(intent.seq, credits) {
+                Ok(()) => (LedgerEvent::Credited, None),
+                Err(e) => {
+                    self.hold(intent);
+                    return Err(e);
+                }
+            },
+        };
+        if let Err(e) = self.ledger.append(intent.trade_id, now_ms, completion.clone()) {
+            self.hold(intent);
+            return Err(e.into());
+        }
+
+        self.in_flight.retain(|i| i.seq != intent.seq);
+        if let LedgerEvent::Locked { player_id, items } = completion {
+            if let Some(trade) = self.trades.get_mut(&intent.trade_id) {
+                trade.side_mut(player_id).locked = Some(items);
+                trade.set_state(EscrowState::Locking);
+            }
+        }
+        refused.map_or(Ok(()), Err)
+    }
+
+    fn hold(&mut self, intent: IncompleteIntent) {
+        if !self.in_flight.iter().any(|i| i.seq == intent.seq) {
+            self.in_flight.push(intent);
+        }
+    }
+
+    /// Complete every in-flight intent, oldest first. Err while one still cannot.
+    fn resume(&mut self, inventories: &mut impl EscrowInventories, now_ms: u64) -> Result<(), EscrowError> {
+        while let Some(intent) = self.in_flight.first().cloned() {
+            let seq = intent.seq;
+            if let Err(e) = self.apply(intent, inventories, now_ms) {
+                if self.in_flight.first().is_some_and(|i| i.seq == seq) {
+                    return Err(e);
+                }
+                warn!("[TradeEscrow] Replayed lock (ledger seq {}) was refused: {}", seq, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// After a restart: replay intents the ledger left incomplete, then return
+    /// everything it still shows in escrow. Returns (intents replayed, trades rolled back).
+    pub fn recover(&mut self, inventories: &mut impl EscrowInventories, now_ms: u64) -> Result<(usize, usize), EscrowError> {
+        let replayed = self.in_flight.len();
+        self.resume(inventories, now_ms)?;
+
+        let mut by_trade: HashMap<u64, Vec<(u64, TradeItems)>> = HashMap::new();
+        for (trade_id, player_id, items) in self.ledger.unsettled_locks() {
+            by_trade.entry(trade_id).or_default().push((player_id, items));
+        }
+        let mut trade_ids: Vec<u64> = by_trade.keys().copied().collect();
+        trade_ids.sort_unstable();
+
+        for trade_id in &trade_ids {
+            let refunds = by_trade.remove(trade_id).unwrap_or_default();
+            let event = LedgerEvent::Cancelled { reason: "server restart".into(), refunds: refunds.clone() };
+            let seq = self.ledger.append(*trade_id, now_ms, event)?.seq;
+            let refund = IncompleteIntent { seq, trade_id: *trade_id, op: InventoryOp::Deposit { credits: refunds } };
+            self.apply(refund, inventories, now_ms)?;
+            warn!("[TradeEscrow] Refunded unsettled trade {} after restart", trade_id);
+        }
+        Ok((replayed, trade_ids.len()))
+    }
+}
+
+// ============================================================================
+// Protocol handling
+// ============================================================================
+
+fn items_from_wire(items: &[WireTradeItem]) -> Result<HashMap<String, f32>, EscrowError> {
+    let registry = items::current();
+    let mut out = HashMap::with_capacity(items.len());
+    for item in items {
+        registry.resolve(&item.resource).map_err(|e| EscrowError::InvalidTerms(e.to_string()))?;
+        if out.insert(item.resource.clone(), item.amount).is_some() {
+            return Err(EscrowError::InvalidTerms(format!("{} listed twice", item.resource)));
+        }
+    }
+    Ok(out)
+}
+
+fn update_both(trade: &EscrowTrade) -> Vec<(u64, ServerMessage)> {
+    let wire = trade.to_wire();
+    [trade.trade.offeror_id, trade.trade.target_id]
+        .into_iter()
+        .map(|player_id| (player_id, ServerMessage::TradeUpdate { trade: wire.clone() }))
+        .collect()
+}
+
+fn cancelled_both(trade: &EscrowTrade, reason: &str) -> Vec<(u64, ServerMessage)> {
+    [trade.trade.offeror_id, trade.trade.target_id]
+        .into_iter()
+        .map(|player_id| {
+            (player_id, ServerMessage::TradeCancelled { trade_id: trade.trade_id(), reason: reason.to_string() })
+        })
+        .collect()
+}
+
+/// Route one trade ClientMessage. Returns replies addressed by player id;
+/// empty when `message` is not a trade message.
+pub fn handle_trade_message(
+    player_id: u64,
+    message: &ClientMessage,
+    escrow: &mut TradeEscrow,
+    inventories: &mut impl EscrowInventories,
+    now_ms: u64,
+) -> Vec<(u64, ServerMessage)> {
+    let result = match message {
+        ClientMessage::TradeOffer { target_player_id, offered, requested } => items_from_wire(offered)
+            .and_then(|o| Ok((o, items_from_wire(requested)?)))
+            .and_then(|(o, r)| escrow.offer(player_id, *target_player_id, o, r, now_ms).map(update_both)),
+        ClientMessage::TradeCounter { trade_id, offered, requested } => items_from_wire(offered)
+            .and_then(|o| Ok((o, items_from_wire(requested)?)))
+            .and_then(|(o, r)| escrow.counter(player_id, *trade_id, o, r, now_ms).map(update_both)),
+        ClientMessage::TradeLock { trade_id, commitment_hash } => escrow
+            .lock(player_id, *trade_id, commitment_hash, inventories, now_ms)
+            .map(update_both),
+        ClientMessage::TradeConfirm { trade_id, commitment_hash } => escrow
+            .confirm(player_id, *trade_id, commitment_hash, inventories, now_ms)
+            .map(|outcome| match outcome {
+                ConfirmOutcome::Pending(trade) => update_both(&trade),
+                ConfirmOutcome::Settled { trade, ledger_seq } => [trade.trade.offeror_id, trade.trade.target_id]
+                    .into_iter()
+                    .map(|p| (p, ServerMessage::TradeCompleted { trade_id: trade.trade_id(), ledger_seq }))
+                    .collect(),
+            }),
+        ClientMessage::TradeCancel { trade_id } => escrow
+            .cancel(Some(player_id), *trade_id, "cancelled by player", inventories, now_ms)
+            .map(|trade| cancelled_both(&trade, "cancelled by player")),
+        _ => return Vec::new(),
+    };
+
+    result.unwrap_or_else(|e| {
+        warn!("[TradeEscrow] Player {} trade request rejected: {}", player_id, e);
+        vec![(player_id, ServerMessage::Error { message: e.to_string() })]
+    })
+}
+
+/// Cancel and refund every open trade of a player who left.
+pub fn handle_player_disconnected(
+    player_id: u64,
+    escrow: &mut TradeEscrow,
+    inventories: &mut impl EscrowInventories,
+    now_ms: u64,
+) -> Vec<(u64, ServerMessage)> {
+    let trade_ids = escrow.open_trades_for(player_id);
+    cancel_all(escrow, trade_ids, "counterparty disconnected", inventories, now_ms)
+}
+
+/// Cancel and refund every trade past its expiry.
+pub fn expire_trades(escrow: &mut TradeEscrow, inventories: &mut impl EscrowInventories, now_ms: u64) -> Vec<(u64, ServerMessage)> {
+    let trade_ids = escrow.expired(now_ms);
+    cancel_all(escrow, trade_ids, "expired", inventories, now_ms)
+}
+
+fn cancel_all(
+    escrow: &mut TradeEscrow,
+    trade_ids: Vec<u64>,
+    reason: &str,
+    inventories: &mut impl EscrowInventories,
+    now_ms: u64,
+) -> Vec<(u64, ServerMessage)> {
+    let mut replies = Vec::new();
+    for trade_id in trade_ids {
+        match escrow.cancel(None, trade_id, reason, inventories, now_ms) {
+            Ok(trade) => replies.extend(cancelled_both(&trade, reason)),
+            Err(e) => warn!("[TradeEscrow] Could not cancel trade {}: {}", trade_id, e),
+        }
+    }
+    replies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::protocol::HotbarSlot;
+
+    const ALICE: u64 = 1000;
+    const BRAM: u64 = 1001;
+    const NOW: u64 = 1_700_000_000_000;
+
+    fn id(key: &str) -> u32 {
+        items::current().get(key).unwrap().item_id
+    }
+
+    fn stock(persistence: &mut PersistenceManager, player_id: u64, stock: &[(&str, u32)]) {
+        let mut save = PlayerSaveData::new(player_id);
+        for (slot, (key, count)) in stock.iter().enumerate() {
+            save.inventory[slot] = HotbarSlot::new(id(key), *count, 1.0);
+        }
+        persistence.save_player(&save).unwrap();
+    }
+
+    fn stocked() -> PersistenceManager {
+        let mut persistence = PersistenceManager::default();
+        stock(&mut persistence, ALICE, &[("verdant_wood", 20)]);
+        stock(&mut persistence, BRAM, &[("mercy_essence", 5)]);
+        persistence
+    }
+
+    fn held(persistence: &mut PersistenceManager, player_id: u64, key: &str) -> u64 {
+        persistence.load_player(player_id).unwrap().held(id(key))
+    }
+
+    /// Saves that refuse every credit while `fail_deposits` is set.
+    struct FlakySaves {
+        saves: PersistenceManager,
+        fail_deposits: bool,
+    }
+
+    impl EscrowInventories for FlakySaves {
+        fn withdraw(&mut self, seq: u64, player_id: u64, items: &TradeItems) -> Result<(), EscrowError> {
+            self.saves.withdraw(seq, player_id, items)
+        }
+
+        fn check_room(&mut self, credits: &[(u64, TradeItems)]) -> Result<(), EscrowError> {
+            self.saves.check_room(credits)
+        }
+
+        fn deposit(&mut self, seq: u64, credits: &[(u64, TradeItems)]) -> Result<(), EscrowError> {
+            if self.fail_deposits {
+                return Err(EscrowError::Inventory("disk full".into()));
+            }
+            self.saves.deposit(seq, credits)
+        }
+    }
+
+    /// The ledger as a crash right before its last `n` entries would have left it.
+    fn crashed_ledger(ledger: &TradeLedger, n: usize) -> TradeLedger {
+        let mut crashed = TradeLedger::in_memory();
+        for entry in &ledger.entries()[..ledger.entries().len() - n] {
+            crashed.append(entry.trade_id, entry.at_ms, entry.event.clone()).unwrap();
+        }
+        crashed
+    }
+
+    fn items(resource: &str, amount: f32) -> HashMap<String, f32> {
+        HashMap::from([(resource.to_string(), amount)])
+    }
+
+    fn offered_trade(escrow: &mut TradeEscrow) -> (u64, Vec<u8>) {
+        let trade = escrow
+            .offer(ALICE, BRAM, items("verdant_wood", 12.0), items("mercy_essence", 3.0), NOW)
+            .unwrap();
+        (trade.trade_id(), trade.commitment_hash.clone())
+    }
+
+    #[test]
+    fn both_confirms_swap_items_and_record_settlement() {
+        let mut escrow = TradeEscrow::new(TradeLedger::in_memory());
+        let mut inv = stocked();
+        let (id, hash) = offered_trade(&mut escrow);
+
+        escrow.lock(ALICE, id, &hash, &mut inv, NOW).unwrap();
+        assert_eq!(held(&mut inv, ALICE, "verdant_wood"), 8);
+        assert_eq!(escrow.confirm(ALICE, id, &hash, &mut inv, NOW).err(), Some(EscrowError::NotLocked(id)));
+
+        escrow.lock(BRAM, id, &hash, &mut inv, NOW).unwrap();
+        assert!(matches!(escrow.confirm(ALICE, id, &hash, &mut inv, NOW), Ok(ConfirmOutcome::Pending(_))));
+        let ConfirmOutcome::Settled { ledger_seq, .. } = escrow.confirm(BRAM, id, &hash, &mut inv, NOW).unwrap() else {
+            panic!("second confirm must settle");
+        };
+
+        assert_eq!(held(&mut inv, ALICE, "mercy_essence"), 3);
+        assert_eq!(held(&mut inv, BRAM, "verdant_wood"), 12);
+        assert_eq!(held(&mut inv, BRAM, "mercy_essence"), 2);
+        assert!(escrow.get(id).is_none());
+        let entries = escrow.ledger().entries();
+        assert!(matches!(entries[ledger_seq as usize - 1].event, LedgerEvent::Settled { .. }));
+        assert_eq!(entries.last().unwrap().event, LedgerEvent::Credited);
+        assert!(escrow.ledger().unsettled_locks().is_empty());
+        assert!(escrow.ledger().incomplete_intents().is_empty());
+    }
+
+    #[test]
+    fn stale_hash_and_short_inventory_move_nothing() {
+        let mut escrow = TradeEscrow::new(TradeLedger::in_memory());
+        let mut inv = stocked();
+        let (id, stale) = offered_trade(&mut escrow);
+
+        let countered = escrow.counter(BRAM, id, items("mercy_essence", 9.0), HashMap::new(), NOW).unwrap();
+        assert_eq!(countered.trade.offeror_id, BRAM);
+        let hash = countered.commitment_hash.clone();
+        assert_ne!(hash, stale);
+
+        assert_eq!(escrow.lock(BRAM, id, &stale, &mut inv, NOW).err(), Some(EscrowError::CommitmentMismatch(id)));
+        assert!(matches!(escrow.lock(BRAM, id, &hash, &mut inv, NOW), Err(EscrowError::Insufficient { .. })));
+        assert_eq!(held(&mut inv, BRAM, "mercy_essence"), 5);
+        assert!(matches!(escrow.ledger().entries().last().unwrap().event, LedgerEvent::LockFailed { player_id: BRAM, .. }));
+        assert!(escrow.ledger().incomplete_intents().is_empty());
+        assert_eq!(escrow.lock(ALICE, 99, &hash, &mut inv, NOW).err(), Some(EscrowError::UnknownTrade(99)));
+    }
+
+    #[test]
+    fn disconnect_refunds_escrow_and_notifies_counterparty() {
+        let mut escrow = TradeEscrow::new(TradeLedger::in_memory());
+        let mut inv = stocked();
+        let (id, hash) = offered_trade(&mut escrow);
+        escrow.lock(ALICE, id, &hash, &mut inv, NOW).unwrap();
+        assert_eq!(escrow.counter(BRAM, id, HashMap::new(), items("verdant_wood", 1.0), NOW).err(), Some(EscrowError::TermsFrozen(id)));
+
+        let replies = handle_player_disconnected(BRAM, &mut escrow, &mut inv, NOW);
+        assert_eq!(held(&mut inv, ALICE, "verdant_wood"), 20);
+        assert!(replies.iter().any(|(p, m)| *p == ALICE && matches!(m, ServerMessage::TradeCancelled { trade_id, .. } if *trade_id == id)));
+        assert!(escrow.open_trades_for(ALICE).is_empty());
+
+        // A settle or cancel of an already-closed trade is refused, not repeated.
+        let replies = handle_trade_message(ALICE, &ClientMessage::TradeConfirm { trade_id: id, commitment_hash: hash }, &mut escrow, &mut inv, NOW);
+        assert!(matches!(replies.as_slice(), [(ALICE, ServerMessage::Error { .. })]));
+
+        // Terms naming an item the registry does not know never open a trade.
+        let offer = ClientMessage::TradeOffer {
+            target_player_id: BRAM,
+            offered: vec![WireTradeItem { resource: "moon_dust".into(), amount: 1.0 }],
+            requested: vec![],
+        };
+        let replies = handle_trade_message(ALICE, &offer, &mut escrow, &mut inv, NOW);
+        assert!(matches!(replies.as_slice(), [(ALICE, ServerMessage::Error { message })] if message.contains("moon_dust")));
+        assert!(escrow.open_trades_for(ALICE).is_empty());
+    }
+
+    #[test]
+    fn restart_refunds_items_left_in_escrow() {
+        let mut inv = stocked();
+        let mut escrow = TradeEscrow::new(TradeLedger::in_memory());
+        let (id, hash) = offered_trade(&mut escrow);
+        escrow.lock(ALICE, id, &hash, &mut inv, NOW).unwrap();
+
+        // Simulate a restart: open trades are gone, the ledger remains.
+        let ledger = std::mem::replace(&mut escrow.ledger, TradeLedger::in_memory());
+        let mut escrow = TradeEscrow::new(ledger);
+        assert_eq!(escrow.recover(&mut inv, NOW).unwrap(), (0, 1));
+        assert_eq!(held(&mut inv, ALICE, "verdant_wood"), 20);
+        assert_eq!(escrow.recover(&mut inv, NOW).unwrap(), (0, 0));
+        assert!(escrow.offer(ALICE, BRAM, items("verdant_wood", 1.0), HashMap::new(), NOW).unwrap().trade_id() > id);
+    }
+
+    #[test]
+    fn settlement_reaches_the_durable_saves() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (saves, trades) = (tmp.path().join("saves"), tmp.path().join("ledger"));
+        let mut persistence = PersistenceManager::open_durable(&saves).unwrap();
+        stock(&mut persistence, ALICE, &[("verdant_wood", 20)]);
+        stock(&mut persistence, BRAM, &[("mercy_essence", 5)]);
+        let mut escrow = TradeEscrow::open_durable(trades.join("ledger.jsonl")).unwrap();
+        let (id, hash) = offered_trade(&mut escrow);
+
+        escrow.lock(ALICE, id, &hash, &mut persistence, NOW).unwrap();
+        escrow.lock(BRAM, id, &hash, &mut persistence, NOW).unwrap();
+        let mut reloaded = PersistenceManager::open_durable(&saves).unwrap();
+        assert_eq!(held(&mut reloaded, ALICE, "verdant_wood"), 8, "escrowed items are out of the save");
+
+        escrow.confirm(ALICE, id, &hash, &mut persistence, NOW).unwrap();
+        escrow.confirm(BRAM, id, &hash, &mut persistence, NOW).unwrap();
+        drop(persistence);
+
+        let mut reloaded = PersistenceManager::open_durable(&saves).unwrap();
+        assert_eq!(held(&mut reloaded, ALICE, "verdant_wood"), 8);
+        assert_eq!(held(&mut reloaded, ALICE, "mercy_essence"), 3);
+        assert_eq!(held(&mut reloaded, BRAM, "verdant_wood"), 12);
+        assert_eq!(held(&mut reloaded, BRAM, "mercy_essence"), 2);
+        assert!(reloaded.load_player(BRAM).unwrap().is_checksum_valid());
+    }
+
+    #[test]
+    fn full_inventory_holds_the_trade_in_escrow() {
+        let mut escrow = TradeEscrow::new(TradeLedger::in_memory());
+        let mut inv = stocked();
+        let mut full = vec![("mercy_essence", 5)];
+        full.extend(std::iter::repeat_n(("gold", 500), PlayerSaveData::new(BRAM).inventory.len() - 1));
+        stock(&mut inv, BRAM, &full);
+        let (id, hash) = offered_trade(&mut escrow);
+        escrow.lock(ALICE, id, &hash, &mut inv, NOW).unwrap();
+        escrow.lock(BRAM, id, &hash, &mut inv, NOW).unwrap();
+
+        escrow.confirm(ALICE, id, &hash, &mut inv, NOW).unwrap();
+        assert_eq!(escrow.confirm(BRAM, id, &hash, &mut inv, NOW).err(), Some(EscrowError::NoRoom { player_id: BRAM }));
+        assert!(!escrow.get(id).unwrap().target.confirmed);
+        assert_eq!(escrow.ledger().unsettled_locks().len(), 2);
+
+        // Fractional units never open a trade against the slot-count inventory.
+        let fractional = escrow.offer(ALICE, BRAM, items("verdant_wood", 0.5), HashMap::new(), NOW);
+        assert!(matches!(fractional, Err(EscrowError::InvalidTerms(_))));
+    }
+
+    #[test]
+    fn failed_credit_is_reported_and_replayed_once() {
+        let mut escrow = TradeEscrow::new(TradeLedger::in_memory());
+        let mut inv = FlakySaves { saves: stocked(), fail_deposits: false };
+        let (id, hash) = offered_trade(&mut escrow);
+        escrow.lock(ALICE, id, &hash, &mut inv, NOW).unwrap();
+        escrow.lock(BRAM, id, &hash, &mut inv, NOW).unwrap();
+        escrow.confirm(ALICE, id, &hash, &mut inv, NOW).unwrap();
+
+        inv.fail_deposits = true;
+        assert_eq!(
+            escrow.confirm(BRAM, id, &hash, &mut inv, NOW).err(),
+            Some(EscrowError::Inventory("disk full".into()))
+        );
+        assert_eq!(held(&mut inv.saves, BRAM, "verdant_wood"), 0);
+        assert_eq!(escrow.ledger().incomplete_intents().len(), 1);
+
+        // Nothing else moves until the settlement is credited.
+        let (next, next_hash) = offered_trade(&mut escrow);
+        assert!(escrow.lock(ALICE, next, &next_hash, &mut inv, NOW).is_err());
+        assert_eq!(held(&mut inv.saves, ALICE, "verdant_wood"), 8);
+
+        // Restart with a working disk: the committed settlement is credited exactly once.
+        inv.fail_deposits = false;
+        let ledger = std::mem::replace(&mut escrow.ledger, TradeLedger::in_memory());
+        let mut escrow = TradeEscrow::new(ledger);
+        assert_eq!(escrow.recover(&mut inv, NOW).unwrap(), (1, 0));
+        assert_eq!(escrow.recover(&mut inv, NOW).unwrap(), (0, 0));
+        assert_eq!(held(&mut inv.saves, ALICE, "mercy_essence"), 3);
+        assert_eq!(held(&mut inv.saves, BRAM, "verdant_wood"), 12);
+        assert!(escrow.ledger().incomplete_intents().is_empty());
+    }
+
+    #[test]
+    fn crash_between_change_and_completion_replays_without_doubling() {
+        let mut escrow = TradeEscrow::new(TradeLedger::in_memory());
+        let mut inv = stocked();
+        let (id, hash) = offered_trade(&mut escrow);
+        escrow.lock(ALICE, id, &hash, &mut inv, NOW).unwrap();
+        escrow.lock(BRAM, id, &hash, &mut inv, NOW).unwrap();
+        escrow.confirm(ALICE, id, &hash, &mut inv, NOW).unwrap();
+        escrow.confirm(BRAM, id, &hash, &mut inv, NOW).unwrap();
+
+        // The credit reached the saves but `Credited` never did.
+        let mut escrow = TradeEscrow::new(crashed_ledger(escrow.ledger(), 1));
+        assert_eq!(escrow.recover(&mut inv, NOW).unwrap(), (1, 0));
+        assert_eq!(held(&mut inv, ALICE, "mercy_essence"), 3);
+        assert_eq!(held(&mut inv, BRAM, "verdant_wood"), 12);
+
+        // A lock whose withdrawal landed but `Locked` did not: replayed, then refunded.
+        let mut inv = stocked();
+        let mut escrow = TradeEscrow::new(TradeLedger::in_memory());
+        let (id, hash) = offered_trade(&mut escrow);
+        escrow.lock(ALICE, id, &hash, &mut inv, NOW).unwrap();
+        let mut escrow = TradeEscrow::new(crashed_ledger(escrow.ledger(), 1));
+        assert_eq!(held(&mut inv, ALICE, "verdant_wood"), 8);
+        assert_eq!(escrow.recover(&mut inv, NOW).unwrap(), (1, 1));
+        assert_eq!(held(&mut inv, ALICE, "verdant_wood"), 20);
+    }
+}
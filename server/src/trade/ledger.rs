@@ -0,0 +1,424 @@
+/*!
+ * server/src/trade/ledger.rs
+ *
+ * Append-only, hash-chained trade ledger. Every escrow transition (offer,
+ * counter, lock, settle, cancel) is one JSON line whose SHA-256 hash covers
+ * the previous entry's hash, so any edit or reordering breaks the chain.
+ *
+ * It is also the escrow's write-ahead log: every inventory change is
+ * preceded by an intent (`Locking`, `Settled`, `Cancelled`) and followed by
+ * its completion (`Locked` / `LockFailed`, `Credited`). An intent without a
+ * completion is an inventory change that may or may not have reached the
+ * player saves; `incomplete_intents` lists them for replay.
+ *
+ * - TradeLedger::in_memory: unit tests / sovereign dev.
+ * - TradeLedger::open: durable file; each append is fsynced before the
+ *   escrow acts on it. A torn final line is dropped on open; a broken chain
+ *   anywhere else refuses to open.
+ *
+ * AG-SML v1.0 | TOLC 8 | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::persistence::player_store::to_hex;
+
+/// `prev_hash` of the first entry
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// (resource, amount), sorted by resource
+pub type TradeItems = Vec<(String, f32)>;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug)]
+pub enum LedgerError {
+    Io(io::Error),
+    Serialize(String),
+    /// Entry on disk failed chain verification.
+    Corrupt { seq: u64, reason: String },
+}
+
+impl fmt::Display for LedgerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerError::Io(e) => write!(f, "trade ledger I/O error: {}", e),
+            LedgerError::Serialize(e) => write!(f, "trade ledger serialization error: {}", e),
+            LedgerError::Corrupt { seq, reason } => write!(f, "trade ledger entry {} corrupt: {}", seq, reason),
+        }
+    }
+}
+
+impl std::error::Error for LedgerError {}
+
+impl From<io::Error> for LedgerError {
+    fn from(e: io::Error) -> Self {
+        LedgerError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for LedgerError {
+    fn from(e: serde_json::Error) -> Self {
+        LedgerError::Serialize(e.to_string())
+    }
+}
+
+// ============================================================================
+// Entries
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LedgerEvent {
+    Offered {
+        offeror_id: u64,
+        target_id: u64,
+        offered: TradeItems,
+        requested: TradeItems,
+        commitment_hash: String,
+    },
+    /// New terms replace the old ones; `offeror_id` is the counterer.
+    Countered {
+        offeror_id: u64,
+        target_id: u64,
+        offered: TradeItems,
+        requested: TradeItems,
+        commitment_hash: String,
+    },
+    /// Intent: `items` are about to leave `player_id`'s inventory.
+    Locking { player_id: u64, items: TradeItems },
+    /// Completes `Locking`: the items left the inventory and are held in escrow.
+    Locked { player_id: u64, items: TradeItems },
+    /// Completes `Locking`: the inventory refused the withdrawal; nothing moved.
+    LockFailed { player_id: u64, reason: String },
+    /// Commit point of the swap, and the intent to credit both parties.
+    Settled {
+        offeror_id: u64,
+        target_id: u64,
+        offered: TradeItems,
+        requested: TradeItems,
+        commitment_hash: String,
+    },
+    /// Commit point of a rollback, and the intent to refund escrowed items.
+    Cancelled { reason: String, refunds: Vec<(u64, TradeItems)> },
+    /// Completes `Settled` / `Cancelled`: every credit reached the player saves.
+    Credited,
+}
+
+impl LedgerEvent {
+    /// The inventory change this intent announces, if it is one.
+    fn intent(&self) -> Option<InventoryOp> {
+        match self {
+            LedgerEvent::Locking { player_id, items } => {
+                Some(InventoryOp::Withdraw { player_id: *player_id, items: items.clone() })
+            }
+            LedgerEvent::Settled { offeror_id, target_id, offered, requested, .. } => Some(InventoryOp::Deposit {
+                credits: vec![(*target_id, offered.clone()), (*offeror_id, requested.clone())],
+            }),
+            LedgerEvent::Cancelled { refunds, .. } => Some(InventoryOp::Deposit { credits: refunds.clone() }),
+            _ => None,
+        }
+    }
+}
+
+/// Inventory change announced by an intent entry.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InventoryOp {
+    Withdraw { player_id: u64, items: TradeItems },
+    Deposit { credits: Vec<(u64, TradeItems)> },
+}
+
+/// An intent with no completion after it: (intent seq, trade, change).
+#[derive(Clone, Debug, PartialEq)]
+pub struct IncompleteIntent {
+    pub seq: u64,
+    pub trade_id: u64,
+    pub op: InventoryOp,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub seq: u64,
+    pub trade_id: u64,
+    pub at_ms: u64,
+    pub event: LedgerEvent,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl LedgerEntry {
+    fn compute_hash(&self) -> Result<String, LedgerError> {
+        #[derive(Serialize)]
+        struct Body<'a> {
+            seq: u64,
+            trade_id: u64,
+            at_ms: u64,
+            event: &'a LedgerEvent,
+            prev_hash: &'a str,
+        }
+        let bytes = serde_json::to_vec(&Body {
+            seq: self.seq,
+            trade_id: self.trade_id,
+            at_ms: self.at_ms,
+            event: &self.event,
+            prev_hash: &self.prev_hash,
+        })?;
+        Ok(to_hex(&Sha256::digest(&bytes)))
+    }
+}
+
+// ============================================================================
+// Ledger
+// ============================================================================
+
+pub struct TradeLedger {
+    entries: Vec<LedgerEntry>,
+    file: Option<File>,
+    path: Option<PathBuf>,
+    /// Byte length of the intact file; a failed append is truncated back to it.
+    file_len: u64,
+}
+
+impl TradeLedger {
+    pub fn in_memory() -> Self {
+        Self { entries: Vec::new(), file: None, path: None, file_len: 0 }
+    }
+
+    /// Open (or create) a durable ledger, verifying the whole chain.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, LedgerError> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(&path)?;
+        let mut raw = Vec::new();
+        file.read_to_end(&mut raw)?;
+
+        let mut entries: Vec<LedgerEntry> = Vec::new();
+        let mut intact_len = 0usize;
+        let mut offset = 0usize;
+        while offset < raw.len() {
+            let (line, next) = match raw[offset..].iter().position(|&b| b == b'\n') {
+                Some(i) => (&raw[offset..offset + i], offset + i + 1),
+                None => (&raw[offset..], raw.len()),
+            };
+            let seq = entries.len() as u64 + 1;
+
+            let entry: LedgerEntry = match serde_json::from_slice(line) {
+                Ok(e) if next <= raw.len() && raw.get(next - 1) == Some(&b'\n') => e,
+                // Crash mid-append: the unterminated final line never committed.
+                _ if raw[next..].iter().all(u8::is_ascii_whitespace) => {
+                    warn!("[TradeLedger] Dropping torn tail at entry {} of {}", seq, path.display());
+                    break;
+                }
+                _ => return Err(LedgerError::Corrupt { seq, reason: "unreadable entry".into() }),
+            };
+
+            let prev_hash = entries.last().map_or(GENESIS_HASH, |e| e.hash.as_str());
+            if entry.seq != seq {
+                return Err(LedgerError::Corrupt { seq, reason: format!("sequence {} out of order", entry.seq) });
+            }
+            if entry.prev_hash != prev_hash {
+                return Err(LedgerError::Corrupt { seq, reason: "chain link broken".into() });
+            }
+            if entry.compute_hash()? != entry.hash {
+                return Err(LedgerError::Corrupt { seq, reason: "hash mismatch".into() });
+            }
+
+            entries.push(entry);
+            intact_len = next;
+            offset = next;
+        }
+
+        if intact_len < raw.len() {
+            file.set_len(intact_len as u64)?;
+            file.sync_all()?;
+        }
+
+        Ok(Self { entries, file: Some(file), path: Some(path), file_len: intact_len as u64 })
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Append one event; durable (fsynced) before this returns Ok.
+    pub fn append(&mut self, trade_id: u64, at_ms: u64, event: LedgerEvent) -> Result<&LedgerEntry, LedgerError> {
+        let mut entry = LedgerEntry {
+            seq: self.last_seq() + 1,
+            trade_id,
+            at_ms,
+            event,
+            prev_hash: self.head_hash().to_string(),
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash()?;
+
+        if let Some(file) = self.file.as_mut() {
+            let mut line = serde_json::to_vec(&entry)?;
+            line.push(b'\n');
+            let written = file.write_all(&line).and_then(|_| file.sync_data());
+            if let Err(e) = written {
+                // Never leave a partial line in front of the next append.
+                let _ = file.set_len(self.file_len);
+                return Err(e.into());
+            }
+            self.file_len += line.len() as u64;
+        }
+
+        self.entries.push(entry);
+        Ok(self.entries.last().expect("entry just pushed"))
+    }
+
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    pub fn history(&self, trade_id: u64) -> impl Iterator<Item = &LedgerEntry> {
+        self.entries.iter().filter(move |e| e.trade_id == trade_id)
+    }
+
+    pub fn last_seq(&self) -> u64 {
+        self.entries.last().map_or(0, |e| e.seq)
+    }
+
+    pub fn head_hash(&self) -> &str {
+        self.entries.last().map_or(GENESIS_HASH, |e| e.hash.as_str())
+    }
+
+    /// Highest trade id ever recorded, so ids are never reused across restarts.
+    pub fn max_trade_id(&self) -> u64 {
+        self.entries.iter().map(|e| e.trade_id).max().unwrap_or(0)
+    }
+
+    /// Intents whose inventory change was never recorded as complete, in ledger order.
+    /// A trade has at most one change in flight, so a later entry of the same
+    /// trade completes (or supersedes) the one before it.
+    pub fn incomplete_intents(&self) -> Vec<IncompleteIntent> {
+        let mut open: BTreeMap<u64, IncompleteIntent> = BTreeMap::new();
+        for entry in &self.entries {
+            match &entry.event {
+                LedgerEvent::Locked { .. } | LedgerEvent::LockFailed { .. } | LedgerEvent::Credited => {
+                    open.remove(&entry.trade_id);
+                }
+                event => {
+                    if let Some(op) = event.intent() {
+                        open.insert(entry.trade_id, IncompleteIntent { seq: entry.seq, trade_id: entry.trade_id, op });
+                    }
+                }
+            }
+        }
+        let mut intents: Vec<IncompleteIntent> = open.into_values().collect();
+        intents.sort_by_key(|i| i.seq);
+        intents
+    }
+
+    /// Items still held in escrow for trades that never settled or cancelled,
+    /// as (trade_id, owner, items). After a restart these must be refunded.
+    pub fn unsettled_locks(&self) -> Vec<(u64, u64, TradeItems)> {
+        let mut open: BTreeMap<u64, Vec<(u64, TradeItems)>> = BTreeMap::new();
+        for entry in &self.entries {
+            match &entry.event {
+                LedgerEvent::Locked { player_id, items } => {
+                    open.entry(entry.trade_id).or_default().push((*player_id, items.clone()));
+                }
+                LedgerEvent::Settled { .. } | LedgerEvent::Cancelled { .. } => {
+                    open.remove(&entry.trade_id);
+                }
+                LedgerEvent::Offered { .. }
+                | LedgerEvent::Countered { .. }
+                | LedgerEvent::Locking { .. }
+                | LedgerEvent::LockFailed { .. }
+                | LedgerEvent::Credited => {}
+            }
+        }
+        open.into_iter()
+            .flat_map(|(trade_id, locks)| locks.into_iter().map(move |(player, items)| (trade_id, player, items)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock(player_id: u64) -> LedgerEvent {
+        LedgerEvent::Locked { player_id, items: vec![("verdant_wood".into(), 12.5)] }
+    }
+
+    #[test]
+    fn reopened_ledger_keeps_chain_and_drops_torn_tail() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("ledger.jsonl");
+        {
+            let mut ledger = TradeLedger::open(&path).unwrap();
+            ledger.append(1, 10, lock(7)).unwrap();
+            ledger.append(1, 20, lock(8)).unwrap();
+        }
+        let mut raw = fs::read(&path).unwrap();
+        raw.extend_from_slice(b"{\"seq\":3,\"trade_id\":1,\"at");
+        fs::write(&path, raw).unwrap();
+
+        let mut ledger = TradeLedger::open(&path).unwrap();
+        assert_eq!(ledger.last_seq(), 2);
+        assert_eq!(ledger.unsettled_locks().len(), 2);
+
+        let entry = ledger
+            .append(1, 30, LedgerEvent::Cancelled { reason: "restart".into(), refunds: vec![] })
+            .unwrap();
+        assert_eq!(entry.seq, 3);
+        assert!(ledger.unsettled_locks().is_empty());
+
+        let reopened = TradeLedger::open(&path).unwrap();
+        assert_eq!(reopened.entries(), ledger.entries());
+    }
+
+    #[test]
+    fn edited_entry_breaks_the_chain() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("ledger.jsonl");
+        {
+            let mut ledger = TradeLedger::open(&path).unwrap();
+            ledger.append(1, 10, lock(7)).unwrap();
+            ledger.append(1, 20, lock(8)).unwrap();
+        }
+        let tampered = fs::read_to_string(&path).unwrap().replacen("12.5", "125.0", 1);
+        fs::write(&path, tampered).unwrap();
+
+        assert!(matches!(TradeLedger::open(&path), Err(LedgerError::Corrupt { seq: 1, .. })));
+    }
+
+    #[test]
+    fn intent_without_completion_is_incomplete() {
+        let mut ledger = TradeLedger::in_memory();
+        let items = vec![("verdant_wood".to_string(), 12.0)];
+        ledger.append(1, 10, LedgerEvent::Locking { player_id: 7, items: items.clone() }).unwrap();
+        ledger.append(1, 20, LedgerEvent::Locked { player_id: 7, items: items.clone() }).unwrap();
+        ledger.append(2, 30, LedgerEvent::Locking { player_id: 8, items: items.clone() }).unwrap();
+        let refunds = vec![(7, items.clone())];
+        ledger.append(1, 40, LedgerEvent::Cancelled { reason: "expired".into(), refunds: refunds.clone() }).unwrap();
+
+        assert_eq!(
+            ledger.incomplete_intents(),
+            vec![
+                IncompleteIntent { seq: 3, trade_id: 2, op: InventoryOp::Withdraw { player_id: 8, items } },
+                IncompleteIntent { seq: 4, trade_id: 1, op: InventoryOp::Deposit { credits: refunds } },
+            ]
+        );
+
+        ledger.append(2, 50, LedgerEvent::LockFailed { player_id: 8, reason: "short".into() }).unwrap();
+        ledger.append(1, 60, LedgerEvent::Credited).unwrap();
+        assert!(ledger.incomplete_intents().is_empty());
+    }
+}
@@ -0,0 +1,17 @@
+/*!
+ * server/src/trade/mod.rs
+ *
+ * Player-to-player trade: signed commitments, two-phase escrow, the
+ * hash-chained trade ledger and pluggable TradeSystem storage.
+ * AG-SML v1.0 | TOLC 8 + RBE
+ * Thunder locked in. Yoi ⚡
+ */
+
+pub mod cryptographic_trade_protocol;
+pub mod escrow;
+pub mod ledger;
+pub mod store;
+
+pub use escrow::{EscrowError, EscrowInventories, TradeEscrow};
+pub use ledger::{LedgerEntry, LedgerError, LedgerEvent, TradeLedger};
+pub use store::{FileTradeStore, InMemoryTradeStore, TradeStore, TradeStoreError};
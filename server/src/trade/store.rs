@@ -0,0 +1,228 @@
+/*!
+ * server/src/trade/store.rs
+ *
+ * Storage backends for TradeSystem (open trades + the DID document each
+ * player authenticated with) behind the TradeStore trait.
+ *
+ * - InMemoryTradeStore: unit tests / sovereign dev (no disk, no shared paths).
+ * - FileTradeStore: one JSON file per record under a configurable root,
+ *   written atomically (temp file + fsync + rename); deletes fsync the
+ *   directory. Identity records are public documents. Earlier builds kept
+ *   server-generated player secret keys under `keys/`; nothing reads them any
+ *   more, and the store never deletes them itself (opening warns instead, so
+ *   an operator can retire the directory).
+ *
+ * AG-SML v1.0 | TOLC 8 | PATSAGi Councils
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use serde::de::DeserializeOwned;
+use tracing::warn;
+
+use rsil_identity::DidDocument;
+
+use crate::persistence::player_store::{sync_parent_dir, write_atomic};
+use crate::trade_system::Trade;
+
+const TRADES_DIR: &str = "trades";
+const IDENTITIES_DIR: &str = "identities";
+/// Server-held player secret keys from before trades were bound to handshake identities.
+const LEGACY_KEYS_DIR: &str = "keys";
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug)]
+pub enum TradeStoreError {
+    Io(io::Error),
+    Serialize(String),
+    /// Record exists on disk but cannot be read back.
+    Corrupt { path: PathBuf, reason: String },
+}
+
+impl fmt::Display for TradeStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TradeStoreError::Io(e) => write!(f, "trade store I/O error: {}", e),
+            TradeStoreError::Serialize(e) => write!(f, "trade store serialization error: {}", e),
+            TradeStoreError::Corrupt { path, reason } => {
+                write!(f, "trade store record {} corrupt: {}", path.display(), reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TradeStoreError {}
+
+impl From<io::Error> for TradeStoreError {
+    fn from(e: io::Error) -> Self {
+        TradeStoreError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for TradeStoreError {
+    fn from(e: serde_json::Error) -> Self {
+        TradeStoreError::Serialize(e.to_string())
+    }
+}
+
+// ============================================================================
+// Trait
+// ============================================================================
+
+/// Storage backend for TradeSystem. Writes are durable when they return Ok.
+pub trait TradeStore: Send + Sync {
+    fn load_trades(&self) -> Result<Vec<Trade>, TradeStoreError>;
+
+    /// Insert or replace by `trade.trade_id`.
+    fn put_trade(&self, trade: &Trade) -> Result<(), TradeStoreError>;
+
+    /// Deleting an unknown trade is not an error.
+    fn delete_trade(&self, trade_id: u64) -> Result<(), TradeStoreError>;
+
+    fn load_identities(&self) -> Result<Vec<(u64, DidDocument)>, TradeStoreError>;
+
+    /// Insert or replace the identity bound to `player_id`.
+    fn put_identity(&self, player_id: u64, identity: &DidDocument) -> Result<(), TradeStoreError>;
+}
+
+// ============================================================================
+// In-memory backend
+// ============================================================================
+
+#[derive(Default)]
+pub struct InMemoryTradeStore {
+    trades: RwLock<HashMap<u64, Trade>>,
+    identities: RwLock<HashMap<u64, DidDocument>>,
+}
+
+impl InMemoryTradeStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TradeStore for InMemoryTradeStore {
+    fn load_trades(&self) -> Result<Vec<Trade>, TradeStoreError> {
+        Ok(self.trades.read().unwrap().values().cloned().collect())
+    }
+
+    fn put_trade(&self, trade: &Trade) -> Result<(), TradeStoreError> {
+        self.trades.write().unwrap().insert(trade.trade_id, trade.clone());
+        Ok(())
+    }
+
+    fn delete_trade(&self, trade_id: u64) -> Result<(), TradeStoreError> {
+        self.trades.write().unwrap().remove(&trade_id);
+        Ok(())
+    }
+
+    fn load_identities(&self) -> Result<Vec<(u64, DidDocument)>, TradeStoreError> {
+        Ok(self.identities.read().unwrap().iter().map(|(id, d)| (*id, d.clone())).collect())
+    }
+
+    fn put_identity(&self, player_id: u64, identity: &DidDocument) -> Result<(), TradeStoreError> {
+        self.identities.write().unwrap().insert(player_id, identity.clone());
+        Ok(())
+    }
+}
+
+// ============================================================================
+// File backend
+// ============================================================================
+
+pub struct FileTradeStore {
+    root: PathBuf,
+}
+
+impl FileTradeStore {
+    /// Open (creating if needed) a store rooted at `root`.
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, TradeStoreError> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(root.join(TRADES_DIR))?;
+        fs::create_dir_all(root.join(IDENTITIES_DIR))?;
+        let legacy_keys = root.join(LEGACY_KEYS_DIR);
+        if legacy_keys.exists() {
+            warn!(
+                "[TradeStore] {} holds server-held player secret keys from an earlier build; \
+                 they are no longer used and can be removed",
+                legacy_keys.display()
+            );
+        }
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn record_path(&self, dir: &str, id: u64) -> PathBuf {
+        self.root.join(dir).join(format!("{}.json", id))
+    }
+
+    /// Every `<id>.json` under `dir`; stale temp files from a crash are removed.
+    fn load_dir<T: DeserializeOwned>(&self, dir: &str) -> Result<Vec<(u64, T)>, TradeStoreError> {
+        let mut out = Vec::new();
+        for entry in fs::read_dir(self.root.join(dir))? {
+            let path = entry?.path();
+            match path.extension().and_then(|e| e.to_str()) {
+                Some("json") => {}
+                Some("tmp") => {
+                    warn!("[TradeStore] Removing stale temp file {}", path.display());
+                    fs::remove_file(&path)?;
+                    continue;
+                }
+                _ => continue,
+            }
+            let Some(id) = path.file_stem().and_then(|s| s.to_str()).and_then(|s| s.parse::<u64>().ok()) else {
+                continue;
+            };
+            let record = serde_json::from_slice(&fs::read(&path)?)
+                .map_err(|e| TradeStoreError::Corrupt { path: path.clone(), reason: e.to_string() })?;
+            out.push((id, record));
+        }
+        out.sort_by_key(|(id, _)| *id);
+        Ok(out)
+    }
+
+    fn write_record<T: serde::Serialize>(&self, dir: &str, id: u64, record: &T) -> Result<(), TradeStoreError> {
+        let bytes = serde_json::to_vec_pretty(record)?;
+        write_atomic(&self.record_path(dir, id), &bytes)?;
+        Ok(())
+    }
+}
+
+impl TradeStore for FileTradeStore {
+    fn load_trades(&self) -> Result<Vec<Trade>, TradeStoreError> {
+        Ok(self.load_dir(TRADES_DIR)?.into_iter().map(|(_, trade)| trade).collect())
+    }
+
+    fn put_trade(&self, trade: &Trade) -> Result<(), TradeStoreError> {
+        self.write_record(TRADES_DIR, trade.trade_id, trade)
+    }
+
+    fn delete_trade(&self, trade_id: u64) -> Result<(), TradeStoreError> {
+        let path = self.record_path(TRADES_DIR, trade_id);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(sync_parent_dir(&path)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn load_identities(&self) -> Result<Vec<(u64, DidDocument)>, TradeStoreError> {
+        self.load_dir(IDENTITIES_DIR)
+    }
+
+    fn put_identity(&self, player_id: u64, identity: &DidDocument) -> Result<(), TradeStoreError> {
+        self.write_record(IDENTITIES_DIR, player_id, identity)
+    }
+}
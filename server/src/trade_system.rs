@@ -5,17 +5,24 @@
 // + Secure path + rate limiting
 // + Full SurrealDB persistence for player hybrid keys
 // + Testing & simulation hooks
+// v16.12 — Pluggable TradeStore (in-memory / file) instead of a hard-wired RocksDB path;
+//          construction returns errors instead of panicking; opened as a server resource in main.rs
+// v16.13 — No server-minted player keys: offers are signed client-side and verified against
+//          the DID document each player authenticated with (bound at login, persisted)
 // AG-SML v1.0
 
 use std::collections::HashMap;
-use surrealdb::engine::local::RocksDb;
-use surrealdb::Surreal;
-use tracing::{info, error, warn};
+use std::path::Path;
+use std::sync::Arc;
+use bevy::prelude::Resource;
+use tracing::{info, warn};
 use serde::{Serialize, Deserialize};
 use crate::harvesting_system::ServerInventoryComponent;
 use crate::trade::cryptographic_trade_protocol::{
-    CryptographicTradeOffer, HybridTradeProtocol, CryptographicTradeProtocol,
+    CryptoTradeError, CryptographicTradeOffer, HybridTradeProtocol, CryptographicTradeProtocol,
 };
+use crate::trade::store::{FileTradeStore, InMemoryTradeStore, TradeStore};
+use rsil_identity::{DidDocument, SovereignKeypair};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Trade {
@@ -30,94 +37,73 @@ pub struct Trade {
     pub nonce: u64,
 }
 
-/// Player's hybrid keypair (classical + post-quantum) — fully persisted
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct PlayerKeyPair {
-    pub classical_public: Vec<u8>,
-    pub classical_secret: Vec<u8>,
-    pub pq_public: Vec<u8>,
-    pub pq_secret: Vec<u8>,
-}
-
+#[derive(Resource)]
 pub struct TradeSystem {
     pub active_trades: HashMap<u64, Trade>,
     pub next_trade_id: u64,
     pub next_nonce: u64,
     last_trade_attempt: HashMap<u64, u64>,
-    player_keys: HashMap<u64, PlayerKeyPair>,
-    db: Surreal<surrealdb::engine::local::Db>,
+    /// DID document each player authenticated with; offers must be signed by its active key.
+    identities: HashMap<u64, DidDocument>,
+    store: Arc<dyn TradeStore>,
 }
 
-impl TradeSystem {
-    pub async fn new() -> Self {
-        let db = Surreal::new::<RocksDb>("data/trades.db").await.expect("DB init failed");
-        db.use_ns("powrush").use_db("trades").await.expect("DB select failed");
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
 
+impl TradeSystem {
+    /// Load open trades and bound player identities from `store`.
+    pub fn with_store(store: Arc<dyn TradeStore>) -> Result<Self, String> {
         let mut system = Self {
             active_trades: HashMap::new(),
             next_trade_id: 1,
             next_nonce: 1,
             last_trade_attempt: HashMap::new(),
-            player_keys: HashMap::new(),
-            db,
+            identities: HashMap::new(),
+            store,
         };
-        system.load_active_trades_from_db().await;
-        system.load_player_keys_from_db().await;
-        system
+        system.load_active_trades()?;
+        system.load_identities()?;
+        Ok(system)
     }
 
-    async fn load_active_trades_from_db(&mut self) { /* preserved */ }
+    /// Open the file-backed store rooted at `root` (e.g. "data/trades").
+    pub fn open_durable(root: impl AsRef<Path>) -> Result<Self, String> {
+        let store = FileTradeStore::open(root).map_err(|e| e.to_string())?;
+        Self::with_store(Arc::new(store))
+    }
 
-    async fn load_player_keys_from_db(&mut self) {
-        match self.db.select::<Vec<(u64, PlayerKeyPair)>>("player_keys").await {
-            Ok(records) => {
-                for (player_id, keys) in records {
-                    self.player_keys.insert(player_id, keys);
-                }
-                info!("Loaded {} player keypairs from database", self.player_keys.len());
-            }
-            Err(e) => {
-                warn!("Failed to load player keys from DB: {}. Starting fresh.", e);
-            }
-        }
+    /// Hermetic system for tests and sovereign dev; nothing touches disk.
+    pub fn in_memory() -> Self {
+        Self::with_store(Arc::new(InMemoryTradeStore::new())).expect("in-memory trade store cannot fail")
     }
 
-    async fn save_player_keys_to_db(&self, player_id: u64, keys: &PlayerKeyPair) {
-        let result = self.run_db_transaction(|tx| async move {
-            tx.create::<Option<PlayerKeyPair>>(("player_keys", player_id))
-                .content(keys.clone())
-                .await?;
-            Ok::<(), surrealdb::Error>(())
-        }).await;
-
-        if result.is_ok() {
-            info!("Saved hybrid keys for player {}", player_id);
-        } else {
-            let _ = self.run_db_transaction(|tx| async move {
-                tx.update::<Option<PlayerKeyPair>>(("player_keys", player_id))
-                    .content(keys.clone())
-                    .await?;
-                Ok::<(), surrealdb::Error>(())
-            }).await;
+    fn load_active_trades(&mut self) -> Result<(), String> {
+        let trades = self.store.load_trades().map_err(|e| format!("Failed to load trades: {}", e))?;
+        for trade in trades {
+            self.next_trade_id = self.next_trade_id.max(trade.trade_id + 1);
+            self.next_nonce = self.next_nonce.max(trade.nonce + 1);
+            self.active_trades.insert(trade.trade_id, trade);
         }
+        info!("Loaded {} active trades", self.active_trades.len());
+        Ok(())
     }
 
-    async fn run_db_transaction<F, Fut, T>(&self, f: F) -> Result<T, String>
-    where
-        F: FnOnce(&mut surrealdb::Transaction) -> Fut,
-        Fut: std::future::Future<Output = Result<T, surrealdb::Error>>,
-    {
-        let mut tx = self.db.begin().await.map_err(|e| format!("Transaction begin failed: {}", e))?;
-        let result = f(&mut tx).await.map_err(|e| format!("Transaction operation failed: {}", e))?;
-        tx.commit().await.map_err(|e| format!("Transaction commit failed: {}", e))?;
-        Ok(result)
+    fn load_identities(&mut self) -> Result<(), String> {
+        let records = self.store.load_identities().map_err(|e| format!("Failed to load player identities: {}", e))?;
+        for (player_id, identity) in records {
+            self.identities.insert(player_id, identity);
+        }
+        info!("Loaded {} player identities", self.identities.len());
+        Ok(())
     }
 
     fn check_trade_rate_limit(&mut self, player_id: u64, min_interval_seconds: u64) -> bool {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let now = unix_now();
 
         if let Some(&last_time) = self.last_trade_attempt.get(&player_id) {
             if now.saturating_sub(last_time) < min_interval_seconds {
@@ -128,34 +114,26 @@ impl TradeSystem {
         true
     }
 
-    pub async fn get_or_create_player_keys(&mut self, player_id: u64) -> &PlayerKeyPair {
-        if !self.player_keys.contains_key(&player_id) {
-            let protocol = HybridTradeProtocol;
-            if let Ok((classical_pk, classical_sk, pq_pk, pq_sk)) = protocol.generate_keypair() {
-                let keys = PlayerKeyPair {
-                    classical_public: classical_pk,
-                    classical_secret: classical_sk,
-                    pq_public: pq_pk,
-                    pq_secret: pq_sk,
-                };
-
-                self.player_keys.insert(player_id, keys.clone());
-                self.save_player_keys_to_db(player_id, &keys).await;
-                info!("Generated and persisted new hybrid keys for player {}", player_id);
-            } else {
-                self.player_keys.insert(player_id, PlayerKeyPair {
-                    classical_public: vec![],
-                    classical_secret: vec![],
-                    pq_public: vec![],
-                    pq_secret: vec![],
-                });
+    /// Bind `player_id` to the DID document they authenticated with (call on every login).
+    /// A document that does not extend the bound one (a rollback or another identity) is refused.
+    pub fn bind_identity(&mut self, player_id: u64, identity: &DidDocument) -> Result<(), String> {
+        if let Some(bound) = self.identities.get(&player_id) {
+            if bound == identity {
+                return Ok(());
             }
+            identity
+                .extends(bound)
+                .map_err(|e| format!("Identity for player {} not rebound: {}", player_id, e))?;
         }
-        self.player_keys.get(&player_id).unwrap()
+        self.store
+            .put_identity(player_id, identity)
+            .map_err(|e| format!("Failed to persist identity for player {}: {}", player_id, e))?;
+        self.identities.insert(player_id, identity.clone());
+        Ok(())
     }
 
-    pub fn get_player_keys(&self, player_id: u64) -> Option<&PlayerKeyPair> {
-        self.player_keys.get(&player_id)
+    pub fn identity_of(&self, player_id: u64) -> Option<&DidDocument> {
+        self.identities.get(&player_id)
     }
 
     // ============================================================
@@ -164,20 +142,18 @@ impl TradeSystem {
     // ============================================================
 
     /// Create a trade directly (useful for controlled testing)
-    pub async fn simulate_initiate_trade(
+    pub fn simulate_initiate_trade(
         &mut self,
         offeror_id: u64,
         target_id: u64,
         offered: HashMap<String, f32>,
         requested: HashMap<String, f32>,
     ) -> Result<u64, String> {
-        self.initiate_trade(offeror_id, target_id, offered, requested).await
+        self.initiate_trade(offeror_id, target_id, offered, requested)
     }
 
     /// Force expiration of all pending trades (useful for testing expiry logic)
-    pub async fn force_expire_all_trades(&mut self) {
-        let now = std::time::SystemTime::now().duration_since(std::UNIX_EPOCH).unwrap().as_secs();
-
+    pub fn force_expire_all_trades(&mut self) {
         let mut expired = Vec::new();
         for (&trade_id, trade) in &self.active_trades {
             if trade.status == "pending" {
@@ -186,10 +162,10 @@ impl TradeSystem {
         }
 
         for trade_id in expired {
-            if let Some(_) = self.active_trades.remove(&trade_id) {
-                let _ = self.run_db_transaction(|tx| async move {
-                    tx.delete::<Option<Trade>>(("trade", trade_id)).await
-                }).await;
+            if self.active_trades.remove(&trade_id).is_some() {
+                if let Err(e) = self.store.delete_trade(trade_id) {
+                    warn!("Failed to delete expired trade {}: {}", trade_id, e);
+                }
             }
         }
 
@@ -212,7 +188,7 @@ impl TradeSystem {
 
     // ============================================================
 
-    pub async fn initiate_trade(
+    pub fn initiate_trade(
         &mut self,
         offeror_id: u64,
         target_id: u64,
@@ -220,9 +196,8 @@ impl TradeSystem {
         requested: HashMap<String, f32>,
     ) -> Result<u64, String> {
         let trade_id = self.next_trade_id;
-        self.next_trade_id += 1;
         let nonce = self.next_nonce;
-        self.next_nonce += 1;
+        let now = unix_now();
 
         let trade = Trade {
             trade_id,
@@ -231,37 +206,42 @@ impl TradeSystem {
             offered,
             requested,
             status: "pending".to_string(),
-            created_at: std::time::SystemTime::now().duration_since(std::UNIX_EPOCH).unwrap().as_secs(),
-            expires_at: Some(std::time::SystemTime::now().duration_since(std::UNIX_EPOCH).unwrap().as_secs() + 300),
+            created_at: now,
+            expires_at: Some(now + 300),
             nonce,
         };
 
-        self.run_db_transaction(|tx| async move {
-            tx.create::<Option<Trade>>(("trade", trade_id)).content(trade.clone()).await
-        }).await?;
+        self.store
+            .put_trade(&trade)
+            .map_err(|e| format!("Failed to persist trade {}: {}", trade_id, e))?;
 
+        self.next_trade_id += 1;
+        self.next_nonce += 1;
         self.active_trades.insert(trade_id, trade);
-        Ok(trade_id);
+        Ok(trade_id)
     }
 
+    /// Sign `trade` with the offeror's own sovereign key for `identity` (client side;
+    /// the server holds no player keys).
     pub fn create_hybrid_signed_offer(
         &self,
         trade: &Trade,
-        classical_secret: &[u8],
-        classical_public: &[u8],
-        pq_secret: &[u8],
-        pq_public: &[u8],
-    ) -> Result<CryptographicTradeOffer, crate::trade::cryptographic_trade_protocol::CryptoTradeError> {
+        keypair: &SovereignKeypair,
+        identity: &DidDocument,
+    ) -> Result<CryptographicTradeOffer, CryptoTradeError> {
         let protocol = HybridTradeProtocol;
-        protocol.create_signed_offer(trade, classical_secret, classical_public, pq_secret, pq_public)
+        protocol.create_signed_offer(trade, keypair, &identity.id)
     }
 
+    /// True when the offer is signed by the active key of the identity its offeror authenticated with.
     pub fn verify_hybrid_trade_offer(&self, offer: &CryptographicTradeOffer) -> bool {
         let protocol = HybridTradeProtocol;
-        protocol.verify_offer(offer)
+        self.identities
+            .get(&offer.commitment.offeror_id)
+            .is_some_and(|identity| protocol.verify_offer(offer, identity))
     }
 
-    pub async fn accept_trade_atomic(
+    pub fn accept_trade_atomic(
         &mut self,
         trade_id: u64,
         accepting_player_id: u64,
@@ -278,34 +258,37 @@ impl TradeSystem {
             _ => return Err("Trade not found or invalid state".to_string()),
         };
 
-        if !self.active_trades.contains_key(&trade_id) {
-            return Err("Trade no longer available".to_string());
-        }
-
         if let Some(offer) = crypto_offer {
-            if !self.verify_hybrid_trade_offer(offer) {
-                return Err("Cryptographic verification failed".to_string());
-            }
-            if offer.trade.trade_id != trade_id || offer.trade.target_id != accepting_player_id {
-                return Err("Cryptographic offer does not match this trade".to_string());
+            let identity = self
+                .identities
+                .get(&trade.offeror_id)
+                .ok_or_else(|| format!("No authenticated identity bound for player {}", trade.offeror_id))?;
+            // The signed commitment must cover the trade as stored, not just the copy in the offer.
+            match HybridTradeProtocol.reveal_and_validate(offer, &trade, identity) {
+                Ok(()) => {}
+                Err(CryptoTradeError::CommitmentMismatch) => {
+                    return Err("Cryptographic offer does not match this trade".to_string())
+                }
+                Err(_) => return Err("Cryptographic verification failed".to_string()),
             }
         }
 
+        for (res, amount) in &trade.offered {
+            if offeror_inventory.get_amount(res) < *amount {
+                return Err(format!("Offeror does not have enough {} to complete the trade", res));
+            }
+        }
         for (res, amount) in &trade.requested {
             if target_inventory.get_amount(res) < *amount {
                 return Err(format!("Target does not have enough {} to complete the trade", res));
             }
         }
 
-        let mut compensation: Vec<(bool, String, f32)> = Vec::new();
-        for (res, amount) in &trade.offered {
-            compensation.push((true, res.clone(), *amount));
-            compensation.push((false, res.clone(), *amount));
-        }
-        for (res, amount) in &trade.requested {
-            compensation.push((false, res.clone(), *amount));
-            compensation.push((true, res.clone(), *amount));
-        }
+        // Durable removal first: if it fails, no inventory has been touched.
+        self.store
+            .delete_trade(trade_id)
+            .map_err(|e| format!("Trade {} failed, inventories untouched: {}", trade_id, e))?;
+        self.active_trades.remove(&trade_id);
 
         for (res, amount) in &trade.offered {
             offeror_inventory.remove_resource(res, *amount);
@@ -316,45 +299,25 @@ impl TradeSystem {
             offeror_inventory.add_resource(res, *amount);
         }
 
-        if self.active_trades.remove(&trade_id).is_some() {
-            match self.run_db_transaction(|tx| async move {
-                tx.delete::<Option<Trade>>(("trade", trade_id)).await?;
-                Ok::<(), surrealdb::Error>(())
-            }).await {
-                Ok(_) => {
-                    info!("Trade {} completed by player {}", trade_id, accepting_player_id);
-                }
-                Err(e) => {
-                    warn!("DB failure on trade {}: {}. Compensating...", trade_id, e);
-                    for (is_offeror, res, amount) in compensation {
-                        if is_offeror {
-                            offeror_inventory.add_resource(&res, amount);
-                            target_inventory.remove_resource(&res, amount);
-                        } else {
-                            target_inventory.add_resource(&res, amount);
-                            offeror_inventory.remove_resource(&res, amount);
-                        }
-                    }
-                    self.active_trades.insert(trade_id, trade);
-                    return Err(format!("Trade failed. Inventory compensated. Error: {}", e));
-                }
-            }
-        }
-
+        info!("Trade {} completed by player {}", trade_id, accepting_player_id);
         Ok(())
     }
 
-    pub async fn reject_trade(&mut self, trade_id: u64, rejecting_player_id: u64) -> Result<(), String> {
-        if let Some(_) = self.active_trades.remove(&trade_id) {
-            self.run_db_transaction(|tx| async move {
-                tx.delete::<Option<Trade>>(("trade", trade_id)).await
-            }).await?;
+    pub fn reject_trade(&mut self, trade_id: u64, rejecting_player_id: u64) -> Result<(), String> {
+        match self.active_trades.get(&trade_id) {
+            Some(t) if t.offeror_id == rejecting_player_id || t.target_id == rejecting_player_id => {}
+            Some(_) => return Err(format!("Player {} is not a party to trade {}", rejecting_player_id, trade_id)),
+            None => return Ok(()),
         }
-        Ok(());
+        self.store
+            .delete_trade(trade_id)
+            .map_err(|e| format!("Failed to delete trade {}: {}", trade_id, e))?;
+        self.active_trades.remove(&trade_id);
+        Ok(())
     }
 
-    pub async fn expire_trades(&mut self) {
-        let now = std::time::SystemTime::now().duration_since(std::UNIX_EPOCH).unwrap().as_secs();
+    pub fn expire_trades(&mut self) {
+        let now = unix_now();
         let mut expired = Vec::new();
 
         for (&trade_id, trade) in &self.active_trades {
@@ -366,12 +329,134 @@ impl TradeSystem {
         }
 
         for trade_id in expired {
-            if let Some(_) = self.active_trades.remove(&trade_id) {
-                let _ = self.run_db_transaction(|tx| async move {
-                    tx.delete::<Option<Trade>>(("trade", trade_id)).await
-                }).await;
-                warn!("Trade {} expired and auto-cancelled", trade_id);
+            match self.store.delete_trade(trade_id) {
+                Ok(()) => {
+                    self.active_trades.remove(&trade_id);
+                    warn!("Trade {} expired and auto-cancelled", trade_id);
+                }
+                Err(e) => warn!("Failed to expire trade {}: {} (retrying next pass)", trade_id, e),
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wood(amount: f32) -> HashMap<String, f32> {
+        HashMap::from([("verdant_wood".to_string(), amount)])
+    }
+
+    #[test]
+    fn in_memory_system_is_hermetic() {
+        let mut a = TradeSystem::in_memory();
+        let mut b = TradeSystem::in_memory();
+        assert_eq!(a.initiate_trade(1, 2, wood(5.0), HashMap::new()).unwrap(), 1);
+        assert_eq!(b.initiate_trade(3, 4, wood(1.0), HashMap::new()).unwrap(), 1);
+
+        assert_eq!(a.reject_trade(1, 9), Err("Player 9 is not a party to trade 1".to_string()));
+        a.reject_trade(1, 2).unwrap();
+        assert_eq!(a.get_active_trade_count(), 0);
+        assert_eq!(b.get_active_trade_count(), 1);
+    }
+
+    #[test]
+    fn shared_store_reloads_trades_and_keeps_ids_unique() {
+        let store: Arc<dyn TradeStore> = Arc::new(InMemoryTradeStore::new());
+        {
+            let mut system = TradeSystem::with_store(store.clone()).unwrap();
+            system.initiate_trade(1, 2, wood(5.0), HashMap::new()).unwrap();
+            system.initiate_trade(1, 3, wood(2.0), HashMap::new()).unwrap();
+            system.force_expire_all_trades();
+            system.initiate_trade(2, 3, HashMap::new(), wood(1.0)).unwrap();
+        }
+        let mut reloaded = TradeSystem::with_store(store).unwrap();
+        assert_eq!(reloaded.get_active_trade_count(), 1);
+        assert_eq!(reloaded.active_trades[&3].requested, wood(1.0));
+        assert_eq!(reloaded.initiate_trade(4, 5, wood(1.0), HashMap::new()).unwrap(), 4);
+    }
+
+    fn signed_trade(system: &mut TradeSystem, offeror: u64, target: u64) -> u64 {
+        system.initiate_trade(offeror, target, wood(2.0), HashMap::new()).unwrap()
+    }
+
+    #[test]
+    fn file_store_roundtrip_at_configured_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let identity = DidDocument::new(&SovereignKeypair::generate().unwrap());
+        // Server-held keys from earlier builds are left alone and never loaded.
+        std::fs::create_dir_all(dir.join("keys")).unwrap();
+        std::fs::write(dir.join("keys").join("7.json"), b"{\"secret_material\":[2]}").unwrap();
+        {
+            let mut system = TradeSystem::open_durable(dir).unwrap();
+            system.initiate_trade(7, 8, wood(3.5), wood(1.0)).unwrap();
+            system.bind_identity(7, &identity).unwrap();
+        }
+        assert!(dir.join("keys").join("7.json").exists());
+        let mut system = TradeSystem::open_durable(dir).unwrap();
+        assert_eq!(system.active_trades[&1].offered, wood(3.5));
+        assert_eq!(system.identity_of(7), Some(&identity));
+
+        system.reject_trade(1, 8).unwrap();
+        assert!(!dir.join("trades").join("1.json").exists());
+    }
+
+    #[test]
+    fn offers_verify_only_against_the_authenticated_identity() {
+        let mut system = TradeSystem::in_memory();
+        let genesis = SovereignKeypair::generate().unwrap();
+        let rotated = SovereignKeypair::generate().unwrap();
+        let stranger = SovereignKeypair::generate().unwrap();
+        let mut identity = DidDocument::new(&genesis);
+        system.bind_identity(1, &identity).unwrap();
+
+        let trade_id = signed_trade(&mut system, 1, 2);
+        let trade = system.active_trades[&trade_id].clone();
+        let genesis_offer = system.create_hybrid_signed_offer(&trade, &genesis, &identity).unwrap();
+        assert!(system.verify_hybrid_trade_offer(&genesis_offer));
+        // Someone else's key, whether claiming their own DID or the offeror's, does not sign for the offeror.
+        let stranger_identity = DidDocument::new(&stranger);
+        assert!(!system.verify_hybrid_trade_offer(&system.create_hybrid_signed_offer(&trade, &stranger, &stranger_identity).unwrap()));
+        assert!(!system.verify_hybrid_trade_offer(&system.create_hybrid_signed_offer(&trade, &stranger, &identity).unwrap()));
+
+        // After a rotation is bound, only the new key signs for this player.
+        identity.rotate(&genesis, &rotated.hybrid_public_key(), 5).unwrap();
+        system.bind_identity(1, &identity).unwrap();
+        assert!(!system.verify_hybrid_trade_offer(&genesis_offer));
+        assert!(system.bind_identity(1, &DidDocument::new(&genesis)).is_err());
+        let rotated_offer = system.create_hybrid_signed_offer(&trade, &rotated, &identity).unwrap();
+        assert!(system.verify_hybrid_trade_offer(&rotated_offer));
+
+        // The signed commitment must match the stored trade.
+        let mut offeror = ServerInventoryComponent::default();
+        offeror.add_resource("verdant_wood", 2.0);
+        let mut target = ServerInventoryComponent::default();
+        let mut tampered = rotated_offer.clone();
+        tampered.trade.offered = wood(0.5);
+        let other_id = signed_trade(&mut system, 1, 2);
+        let other = system.create_hybrid_signed_offer(&system.active_trades[&other_id].clone(), &rotated, &identity).unwrap();
+        assert_eq!(
+            system.accept_trade_atomic(trade_id, 2, &mut offeror, &mut target, Some(&other)),
+            Err("Cryptographic offer does not match this trade".to_string())
+        );
+        system.last_trade_attempt.clear();
+        system.accept_trade_atomic(trade_id, 2, &mut offeror, &mut target, Some(&tampered)).unwrap();
+        assert_eq!(target.get_amount("verdant_wood"), 2.0);
+    }
+
+    #[test]
+    fn unusable_root_is_an_error_not_a_panic() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("trade_root");
+        std::fs::write(&dir, b"not a directory").unwrap();
+        assert!(TradeSystem::open_durable(&dir).is_err());
+
+        std::fs::remove_file(&dir).unwrap();
+        std::fs::create_dir_all(dir.join("trades")).unwrap();
+        std::fs::write(dir.join("trades").join("1.json"), b"{ torn").unwrap();
+        let err = TradeSystem::open_durable(&dir).err().unwrap();
+        assert!(err.contains("corrupt"), "{}", err);
+    }
+}
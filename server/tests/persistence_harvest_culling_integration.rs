@@ -62,4 +62,75 @@ async fn test_persistence_and_culling_separation() {
     assert_eq!(visible.len(), 0);
 }
 
+/// Harvest a node, walk away until its chunk unloads (saved on the way out), restart the
+/// server over the same chunk directory and walk back: the harvest and version survive.
+#[test]
+fn test_chunk_unload_reload_round_trip() {
+    use bevy::math::Vec3;
+    use powrush_mmo_server::spatial::chunk_store::{ChunkResourceNode, ChunkStore, FileChunkStore, NpcSpawner};
+    use powrush_mmo_server::spatial::chunk_streaming::{ChunkStreamingConfig, ChunkWorld};
+    use shared::protocol::ServerMessage;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path();
+    let open_world = || ChunkWorld::new(ChunkStreamingConfig::default(), Arc::new(FileChunkStore::open(dir).unwrap())).unwrap();
+    let node_pos = Vec3::new(40.0, 0.0, 40.0);
+    let far = Vec3::new(5_000.0, 0.0, 5_000.0);
+
+    let mut world = open_world();
+    world.add_player(1);
+    let coord = world.chunk_at(node_pos);
+    {
+        let chunk = world.chunk_mut(coord).unwrap();
+        chunk.resource_nodes.push(ChunkResourceNode {
+            node_id: 7,
+            resource_type: "gold".to_string(),
+            position: [40.0, 0.0, 40.0],
+            current_amount: 100.0,
+            max_amount: 100.0,
+            regen_rate: 1.0,
+            sustainability_score: 1.0,
+            depleted: false,
+        });
+        chunk.spawners.push(NpcSpawner {
+            spawner_id: 3,
+            archetype: "mercy_warden".to_string(),
+            position: [50.0, 0.0, 50.0],
+            radius: 8.0,
+            max_alive: 2,
+            respawn_secs: 30.0,
+        });
+    }
+    assert_eq!(world.update_player(1, Vec3::ZERO).len(), 1);
+
+    // Harvest 30: one more version, resent to the player standing in range.
+    let node = world.chunk_mut(coord).unwrap().resource_node_mut(7).unwrap();
+    node.current_amount = 70.0;
+    node.sustainability_score = 0.85;
+    let resent = world.update_player(1, Vec3::ZERO);
+    assert!(matches!(resent.as_slice(), [ServerMessage::ChunkSnapshot { version: 2, .. }]));
+
+    // Walking away unloads the chunk; it was dirty, so it is saved first.
+    world.update_player(1, far);
+    assert!(world.unload_far().unwrap() > 0);
+    assert!(!world.is_loaded(coord));
+    drop(world);
+
+    let mut restarted = open_world();
+    restarted.add_player(1);
+    assert!(restarted.update_player(1, far).is_empty());
+    let back = restarted.update_player(1, Vec3::ZERO);
+    let [ServerMessage::ChunkSnapshot { chunk_id, version, resource_nodes, structures }] = back.as_slice() else {
+        panic!("expected one chunk snapshot, got {:?}", back);
+    };
+    assert_eq!((*chunk_id, *version), (coord.to_packed_id(), 2));
+    assert!((resource_nodes[0].current_amount - 70.0).abs() < 0.01);
+    assert!(structures.is_empty());
+
+    // Spawners persist server-side only.
+    let saved = FileChunkStore::open(dir).unwrap().load(coord).unwrap().unwrap();
+    assert_eq!(saved.spawners.len(), 1);
+    assert!((saved.resource_nodes[0].sustainability_score - 0.85).abs() < 0.01);
+}
+
 // Thunder locked in. Integration testing significantly expanded. ⚡❤️🔥
@@ -0,0 +1,614 @@
+//! shared/abilities.rs
+//! Powrush-MMO — Data-driven ability definitions
+//! One schema for every ability (combat, ability tree, ascension): costs, cooldown, GCD,
+//! targeting, effects, CombatStats scaling and synergy tags. Definitions live in
+//! assets/abilities/*.ron, are validated as a whole at load, and are published as one
+//! process-wide registry (`current` / `install`) so a dev hot-reload reaches every system.
+//! Status effect rules (stacking, periodic ticks, stat modifiers, dispel category) are
+//! fixed per StatusEffectType in `StatusEffectType::rules`.
+//! AG-SML v1.0 | PATSAGi Councils | info@Rathor.ai
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Definitions compiled into the binary; the fallback when assets/abilities is absent.
+const BUNDLED: [(&str, &str); 3] = [
+    ("starters.ron", include_str!("../assets/abilities/starters.ron")),
+    ("combat.ron", include_str!("../assets/abilities/combat.ron")),
+    ("ascension.ron", include_str!("../assets/abilities/ascension.ron")),
+];
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// SCHEMA
+// ════════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AbilityType {
+    DirectDamage, Buff, Debuff, AoE, Support,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DamageType {
+    Physical, Energy, Mercy, Corruption,
+}
+
+/// Append only: the variant index is on the wire (protocol `WireStatusEffect`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StatusEffectType {
+    DamageOverTime, HealingOverTime, DefenseBuff, AttackBuff, Corruption,
+    Slow, Haste, Weakness, Vulnerability,
+    /// Cleanses Corruption and keeps it off while active
+    MercyWard,
+}
+
+/// What happens when a status lands on a target that already has it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackingPolicy {
+    /// One instance; reapplying resets the duration and keeps the stronger strength
+    Refresh,
+    /// One instance; reapplying adds a stack (up to `max_stacks`) and resets the duration
+    StackIntensity { max_stacks: u8 },
+    /// One instance per source; different casters stack side by side
+    UniquePerSource,
+}
+
+/// Which dispels can remove a status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DispelCategory {
+    Magic,
+    Physical,
+    Corruption,
+    /// Only expiry removes it
+    Undispellable,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PeriodicEffect {
+    /// `strength` × stacks damage per second, dealt every tick interval
+    Damage(DamageType),
+    /// `strength` × stacks healing per second, dealt every tick interval
+    Heal,
+}
+
+/// Fractional stat changes per point of `strength` × stacks (+0.25 = +25%).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StatModifiers {
+    pub attack_power: f32,
+    pub defense: f32,
+    pub speed: f32,
+    /// Incoming damage
+    pub damage_taken: f32,
+}
+
+impl StatModifiers {
+    pub fn scaled(self, by: f32) -> Self {
+        Self {
+            attack_power: self.attack_power * by,
+            defense: self.defense * by,
+            speed: self.speed * by,
+            damage_taken: self.damage_taken * by,
+        }
+    }
+
+    pub fn combine(self, other: Self) -> Self {
+        Self {
+            attack_power: self.attack_power + other.attack_power,
+            defense: self.defense + other.defense,
+            speed: self.speed + other.speed,
+            damage_taken: self.damage_taken + other.damage_taken,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatusRules {
+    pub harmful: bool,
+    pub stacking: StackingPolicy,
+    pub dispel: DispelCategory,
+    pub periodic: Option<PeriodicEffect>,
+    pub tick_interval_secs: f32,
+    pub modifiers: StatModifiers,
+    /// Categories the bearer cannot receive (and loses on application)
+    pub grants_immunity: &'static [DispelCategory],
+}
+
+impl StatusEffectType {
+    pub fn rules(self) -> StatusRules {
+        use StatusEffectType as S;
+        let base = StatusRules {
+            harmful: false,
+            stacking: StackingPolicy::Refresh,
+            dispel: DispelCategory::Magic,
+            periodic: None,
+            tick_interval_secs: 1.0,
+            modifiers: StatModifiers::default(),
+            grants_immunity: &[],
+        };
+        let modifiers = StatModifiers::default();
+        match self {
+            S::DamageOverTime => StatusRules {
+                harmful: true,
+                stacking: StackingPolicy::StackIntensity { max_stacks: 5 },
+                periodic: Some(PeriodicEffect::Damage(DamageType::Corruption)),
+                ..base
+            },
+            S::HealingOverTime => StatusRules { periodic: Some(PeriodicEffect::Heal), ..base },
+            S::DefenseBuff => StatusRules { modifiers: StatModifiers { defense: 1.0, ..modifiers }, ..base },
+            S::AttackBuff => StatusRules { modifiers: StatModifiers { attack_power: 1.0, ..modifiers }, ..base },
+            S::Corruption => StatusRules {
+                harmful: true,
+                stacking: StackingPolicy::UniquePerSource,
+                dispel: DispelCategory::Corruption,
+                periodic: Some(PeriodicEffect::Damage(DamageType::Corruption)),
+                ..base
+            },
+            S::Slow => StatusRules {
+                harmful: true,
+                dispel: DispelCategory::Physical,
+                modifiers: StatModifiers { speed: -1.0, ..modifiers },
+                ..base
+            },
+            S::Haste => StatusRules { modifiers: StatModifiers { speed: 1.0, ..modifiers }, ..base },
+            S::Weakness => StatusRules {
+                harmful: true,
+                stacking: StackingPolicy::StackIntensity { max_stacks: 3 },
+                modifiers: StatModifiers { attack_power: -1.0, ..modifiers },
+                ..base
+            },
+            S::Vulnerability => StatusRules {
+                harmful: true,
+                stacking: StackingPolicy::StackIntensity { max_stacks: 3 },
+                modifiers: StatModifiers { damage_taken: 1.0, ..modifiers },
+                ..base
+            },
+            S::MercyWard => StatusRules {
+                dispel: DispelCategory::Undispellable,
+                grants_immunity: &[DispelCategory::Corruption],
+                ..base
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum TargetMode {
+    /// Affects the caster; no target needed
+    #[default]
+    Caster,
+    Enemy,
+    Ally,
+    /// Centered on the target, `radius` wide
+    Area,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Targeting {
+    pub mode: TargetMode,
+    pub range: f32,
+    /// Full facing cone in degrees; 360 = any direction
+    pub cone_degrees: f32,
+    pub radius: f32,
+    pub requires_line_of_sight: bool,
+}
+
+impl Default for Targeting {
+    fn default() -> Self {
+        Self { mode: TargetMode::Caster, range: 0.0, cone_degrees: 360.0, radius: 0.0, requires_line_of_sight: true }
+    }
+}
+
+/// Paid on use by whichever system owns the pool.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AbilityCost {
+    pub energy: f32,
+    pub health: f32,
+    pub harmony: f32,
+}
+
+/// Caster stats an effect can scale with (server CombatStats + ascension attunement).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CasterStats {
+    pub attack_power: f32,
+    pub defense: f32,
+    pub speed: f32,
+    pub critical_chance: f32,
+    pub resonance_attunement: f32,
+}
+
+/// Per-stat coefficients: amount = base + Σ coefficient × stat.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatScaling {
+    pub attack_power: f32,
+    pub defense: f32,
+    pub speed: f32,
+    pub critical_chance: f32,
+    pub resonance_attunement: f32,
+}
+
+impl StatScaling {
+    pub fn apply(&self, base: f32, stats: &CasterStats) -> f32 {
+        base + self.attack_power * stats.attack_power
+            + self.defense * stats.defense
+            + self.speed * stats.speed
+            + self.critical_chance * stats.critical_chance
+            + self.resonance_attunement * stats.resonance_attunement
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AbilityEffect {
+    Damage { base: f32, damage_type: DamageType, #[serde(default)] scaling: StatScaling },
+    Heal { base: f32, #[serde(default)] scaling: StatScaling },
+    Status { effect: StatusEffectType, duration_secs: f32, strength: f32 },
+    ResonanceBoost { base: f32, #[serde(default)] scaling: StatScaling },
+    EpiphanyChance { chance: f32 },
+    HarmonyBoost { amount: f32 },
+    ContributionGain { amount: f64 },
+    EpigeneticStabilize { volatility_reduction: f32 },
+    MovementBurst { duration_ticks: u32 },
+    /// Remove up to `count` statuses of `category` (harmful ones unless `beneficial`)
+    Dispel { category: DispelCategory, count: u32, #[serde(default)] beneficial: bool },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AbilityDef {
+    /// Stable string id (ability tree, saves, synergy chains)
+    pub id: String,
+    /// Numeric id on the wire (UseAbility.ability_id) and on combat `Ability` components
+    pub wire_id: u32,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub kind: AbilityType,
+    /// Race name (simulation `Race::name`) for race abilities
+    #[serde(default)]
+    pub race: Option<String>,
+    /// Unlocked on spawn for `race`
+    #[serde(default)]
+    pub starter: bool,
+    /// Only usable after Mercy Ascent
+    #[serde(default)]
+    pub ascension: bool,
+    #[serde(default)]
+    pub tier: u8,
+    /// Id that must be unlocked first
+    #[serde(default)]
+    pub requires: Option<String>,
+    #[serde(default)]
+    pub cost: AbilityCost,
+    pub cooldown_secs: f32,
+    /// Global cooldown this use triggers; 0 = off the GCD
+    #[serde(default)]
+    pub gcd_secs: f32,
+    #[serde(default)]
+    pub targeting: Targeting,
+    pub effects: Vec<AbilityEffect>,
+    #[serde(default)]
+    pub synergy_tags: Vec<String>,
+}
+
+impl AbilityDef {
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.synergy_tags.iter().any(|t| t == tag)
+    }
+
+    pub fn triggers_gcd(&self) -> bool {
+        self.gcd_secs > 0.0
+    }
+
+    /// Cooldown in simulation ticks, rounded up.
+    pub fn cooldown_ticks(&self, ticks_per_second: u32) -> u64 {
+        (self.cooldown_secs * ticks_per_second as f32).ceil() as u64
+    }
+
+    /// Total scaled damage and the damage type of the first Damage effect.
+    pub fn damage(&self, stats: &CasterStats) -> Option<(f32, DamageType)> {
+        let mut damage_type = None;
+        let mut total = 0.0;
+        for effect in &self.effects {
+            if let AbilityEffect::Damage { base, damage_type: kind, scaling } = effect {
+                damage_type.get_or_insert(*kind);
+                total += scaling.apply(*base, stats);
+            }
+        }
+        damage_type.map(|kind| (total, kind))
+    }
+
+    pub fn heal_amount(&self, stats: &CasterStats) -> f32 {
+        self.effects
+            .iter()
+            .map(|e| match e {
+                AbilityEffect::Heal { base, scaling } => scaling.apply(*base, stats),
+                _ => 0.0,
+            })
+            .sum()
+    }
+
+    pub fn resonance_amount(&self, stats: &CasterStats) -> f32 {
+        self.effects
+            .iter()
+            .map(|e| match e {
+                AbilityEffect::ResonanceBoost { base, scaling } => scaling.apply(*base, stats),
+                _ => 0.0,
+            })
+            .sum()
+    }
+
+    fn validate(&self, problems: &mut Vec<String>) {
+        let mut bad = |what: String| problems.push(format!("{}: {}", self.id, what));
+        let finite_non_negative = |v: f32| v.is_finite() && v >= 0.0;
+
+        if self.id.is_empty() || !self.id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+            bad("id must be non-empty snake_case".into());
+        }
+        if self.name.trim().is_empty() {
+            bad("name is empty".into());
+        }
+        if !finite_non_negative(self.cooldown_secs) || !finite_non_negative(self.gcd_secs) {
+            bad("cooldown_secs / gcd_secs must be finite and >= 0".into());
+        }
+        let c = &self.cost;
+        if ![c.energy, c.health, c.harmony].into_iter().all(finite_non_negative) {
+            bad("costs must be finite and >= 0".into());
+        }
+        let t = &self.targeting;
+        if !finite_non_negative(t.range) || !finite_non_negative(t.radius) {
+            bad("targeting range / radius must be finite and >= 0".into());
+        }
+        if !(t.cone_degrees > 0.0 && t.cone_degrees <= 360.0) {
+            bad(format!("cone_degrees {} outside (0, 360]", t.cone_degrees));
+        }
+        if matches!(t.mode, TargetMode::Enemy | TargetMode::Ally | TargetMode::Area) && t.range <= 0.0 {
+            bad("targeted ability needs range > 0".into());
+        }
+        if t.mode == TargetMode::Area && t.radius <= 0.0 {
+            bad("Area targeting needs radius > 0".into());
+        }
+        if self.effects.is_empty() {
+            bad("no effects".into());
+        }
+        for effect in &self.effects {
+            let ok = match effect {
+                AbilityEffect::Damage { base, .. } | AbilityEffect::Heal { base, .. } => finite_non_negative(*base),
+                AbilityEffect::Status { duration_secs, strength, .. } => *duration_secs > 0.0 && strength.is_finite(),
+                AbilityEffect::EpiphanyChance { chance } => (0.0..=1.0).contains(chance),
+                AbilityEffect::Dispel { category, count, .. } => *category != DispelCategory::Undispellable && *count > 0,
+                _ => true,
+            };
+            if !ok {
+                bad(format!("invalid effect {:?}", effect));
+            }
+        }
+        if self.starter && self.race.is_none() {
+            bad("starter ability without a race".into());
+        }
+        if self.synergy_tags.iter().any(|t| t.trim().is_empty()) {
+            bad("empty synergy tag".into());
+        }
+    }
+}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// REGISTRY
+// ════════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug)]
+pub enum AbilityLoadError {
+    Io { path: PathBuf, error: String },
+    Parse { source: String, error: String },
+    /// Every problem found across all files
+    Invalid(Vec<String>),
+}
+
+impl std::fmt::Display for AbilityLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AbilityLoadError::Io { path, error } => write!(f, "cannot read {}: {}", path.display(), error),
+            AbilityLoadError::Parse { source, error } => write!(f, "invalid ability file {}: {}", source, error),
+            AbilityLoadError::Invalid(problems) => {
+                write!(f, "{} invalid ability definition(s): {}", problems.len(), problems.join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for AbilityLoadError {}
+
+#[derive(Debug, Default)]
+pub struct AbilityRegistry {
+    defs: Vec<AbilityDef>,
+    by_id: HashMap<String, usize>,
+    by_wire_id: HashMap<u32, usize>,
+}
+
+impl AbilityRegistry {
+    /// Parse and validate `(source name, RON list of AbilityDef)` pairs as one set.
+    pub fn from_sources<'a>(sources: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<Self, AbilityLoadError> {
+        let mut defs = Vec::new();
+        for (source, text) in sources {
+            let parsed: Vec<AbilityDef> = ron::from_str(text)
+                .map_err(|e| AbilityLoadError::Parse { source: source.to_string(), error: e.to_string() })?;
+            defs.extend(parsed);
+        }
+        Self::from_defs(defs)
+    }
+
+    pub fn from_defs(defs: Vec<AbilityDef>) -> Result<Self, AbilityLoadError> {
+        let mut problems = Vec::new();
+        let mut by_id = HashMap::new();
+        let mut by_wire_id = HashMap::new();
+        for (i, def) in defs.iter().enumerate() {
+            def.validate(&mut problems);
+            if by_id.insert(def.id.clone(), i).is_some() {
+                problems.push(format!("{}: duplicate id", def.id));
+            }
+            if let Some(other) = by_wire_id.insert(def.wire_id, i) {
+                problems.push(format!("{}: wire_id {} already used by {}", def.id, def.wire_id, defs[other].id));
+            }
+        }
+        for def in &defs {
+            if let Some(required) = &def.requires {
+                if !by_id.contains_key(required) {
+                    problems.push(format!("{}: requires unknown ability {}", def.id, required));
+                }
+            }
+        }
+        // Prerequisite chains must end.
+        for def in &defs {
+            let mut seen = HashSet::new();
+            let mut next = def.requires.as_deref();
+            while let Some(id) = next {
+                if !seen.insert(id) {
+                    problems.push(format!("{}: prerequisite cycle through {}", def.id, id));
+                    break;
+                }
+                next = by_id.get(id).and_then(|&i| defs[i].requires.as_deref());
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(AbilityLoadError::Invalid(problems));
+        }
+        Ok(Self { defs, by_id, by_wire_id })
+    }
+
+    /// Every `*.ron` file in `dir`, in file-name order.
+    pub fn load_dir(dir: &Path) -> Result<Self, AbilityLoadError> {
+        let io = |path: &Path, e: std::io::Error| AbilityLoadError::Io { path: path.to_path_buf(), error: e.to_string() };
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+            .map_err(|e| io(dir, e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "ron"))
+            .collect();
+        paths.sort();
+
+        let mut files = Vec::with_capacity(paths.len());
+        for path in &paths {
+            files.push((path.display().to_string(), std::fs::read_to_string(path).map_err(|e| io(path, e))?));
+        }
+        Self::from_sources(files.iter().map(|(name, text)| (name.as_str(), text.as_str())))
+    }
+
+    /// The definitions compiled into this build.
+    pub fn bundled() -> Self {
+        Self::from_sources(BUNDLED).expect("bundled ability definitions are valid")
+    }
+
+    pub fn get(&self, id: &str) -> Option<&AbilityDef> {
+        self.by_id.get(id).map(|&i| &self.defs[i])
+    }
+
+    pub fn by_wire_id(&self, wire_id: u32) -> Option<&AbilityDef> {
+        self.by_wire_id.get(&wire_id).map(|&i| &self.defs[i])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AbilityDef> {
+        self.defs.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.defs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.defs.is_empty()
+    }
+
+    /// Starter abilities for `race` (by `Race::name`), in definition order.
+    pub fn starters_for<'a>(&'a self, race: &'a str) -> impl Iterator<Item = &'a AbilityDef> {
+        self.defs.iter().filter(move |d| d.starter && d.race.as_deref() == Some(race))
+    }
+}
+
+fn slot() -> &'static RwLock<Arc<AbilityRegistry>> {
+    static CURRENT: OnceLock<RwLock<Arc<AbilityRegistry>>> = OnceLock::new();
+    CURRENT.get_or_init(|| RwLock::new(Arc::new(AbilityRegistry::bundled())))
+}
+
+/// The registry every system reads (bundled definitions until something is installed).
+pub fn current() -> Arc<AbilityRegistry> {
+    slot().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Replace the process-wide registry (startup load, dev hot-reload).
+pub fn install(registry: Arc<AbilityRegistry>) {
+    *slot().write().unwrap_or_else(|e| e.into_inner()) = registry;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn def(id: &str, wire_id: u32) -> AbilityDef {
+        AbilityDef {
+            id: id.into(),
+            wire_id,
+            name: id.into(),
+            description: String::new(),
+            kind: AbilityType::DirectDamage,
+            race: None,
+            starter: false,
+            ascension: false,
+            tier: 1,
+            requires: None,
+            cost: AbilityCost::default(),
+            cooldown_secs: 1.0,
+            gcd_secs: 1.0,
+            targeting: Targeting { mode: TargetMode::Enemy, range: 5.0, ..Default::default() },
+            effects: vec![AbilityEffect::Damage {
+                base: 4.0,
+                damage_type: DamageType::Physical,
+                scaling: StatScaling { attack_power: 0.5, ..Default::default() },
+            }],
+            synergy_tags: vec![],
+        }
+    }
+
+    #[test]
+    fn bundled_definitions_load_and_index() {
+        let registry = AbilityRegistry::bundled();
+        assert!(!registry.is_empty());
+        let strike = registry.get("focused_strike").unwrap();
+        assert_eq!(registry.by_wire_id(strike.wire_id).unwrap().id, "focused_strike");
+        assert_eq!(registry.starters_for("Harmonic").count(), 3);
+        assert!(registry.get("resonant_jump").unwrap().has_tag("resonance"));
+    }
+
+    #[test]
+    fn scaling_uses_caster_stats() {
+        let stats = CasterStats { attack_power: 10.0, ..Default::default() };
+        assert_eq!(def("a", 1).damage(&stats), Some((9.0, DamageType::Physical)));
+    }
+
+    #[test]
+    fn validation_reports_every_problem() {
+        let mut looping = def("loop_a", 2);
+        looping.requires = Some("loop_b".into());
+        let mut other = def("loop_b", 3);
+        other.requires = Some("loop_a".into());
+        let mut broken = def("Bad Id", 1);
+        broken.targeting.cone_degrees = 0.0;
+        broken.effects.clear();
+
+        let Err(AbilityLoadError::Invalid(problems)) =
+            AbilityRegistry::from_defs(vec![def("a", 1), broken, looping, other, def("a", 4)])
+        else {
+            panic!("expected validation failure");
+        };
+        let all = problems.join("\n");
+        for expected in ["snake_case", "cone_degrees", "no effects", "wire_id 1", "duplicate id", "prerequisite cycle"] {
+            assert!(all.contains(expected), "missing {:?} in {}", expected, all);
+        }
+    }
+
+    #[test]
+    fn parse_errors_name_the_file() {
+        let err = AbilityRegistry::from_sources([("broken.ron", "[(id: ")]).unwrap_err();
+        assert!(err.to_string().contains("broken.ron"));
+    }
+}
@@ -0,0 +1,559 @@
+// shared/council_ballot.rs
+// One-identity-one-vote council ballots with liquid delegation (used by simulation + clients)
+//
+// A BallotBox belongs to one proposal and holds at most one ballot per identity
+// (AgentId, or a player id tagged with PLAYER_VOTER_FLAG). A voter may change or
+// retract their ballot until the box closes. Instead of voting, a voter may delegate
+// to another identity; their weight then follows the delegation chain to the first
+// identity that cast a ballot. A direct ballot always overrides the voter's own
+// delegation, and a delegation that would close a loop is refused when it is made,
+// so every chain terminates.
+//
+// Each counted identity weighs BALLOT_BASE_WEIGHT plus the rest scaled by their
+// attunement (0.0–1.0), so attunement tilts a tally without letting anyone outvote
+// several others. When deliberation runs, the box is turned into a BallotRecord: every
+// participant, where their weight landed, the weighted tally and a SHA-256 digest
+// over a canonical encoding. Clients holding a record can recompute all of it with
+// `BallotRecord::verify`.
+//
+// AG-SML v1.0 | PATSAGi Councils | info@Rathor.ai
+// Thunder locked in. Yoi ⚡
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// Identity a ballot is tied to: a simulation AgentId, or a server player id
+/// tagged with PLAYER_VOTER_FLAG (see `player_voter`).
+pub type VoterId = u64;
+
+/// High bit set on player identities, so a player and an agent that share a
+/// numeric id never share a ballot.
+pub const PLAYER_VOTER_FLAG: VoterId = 1 << 63;
+
+/// Ballot identity of a server player.
+pub fn player_voter(player_id: u64) -> VoterId {
+    player_id | PLAYER_VOTER_FLAG
+}
+
+/// The player behind a ballot identity; None for simulation agents.
+pub fn voter_player(voter: VoterId) -> Option<u64> {
+    (voter & PLAYER_VOTER_FLAG != 0).then_some(voter & !PLAYER_VOTER_FLAG)
+}
+
+/// Weight of a counted identity with zero attunement; full attunement weighs 1.0.
+pub const BALLOT_BASE_WEIGHT: f32 = 0.5;
+
+/// Record encoding version, hashed into every digest.
+pub const BALLOT_RECORD_VERSION: u8 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BallotChoice {
+    For,
+    Against,
+}
+
+impl BallotChoice {
+    pub fn from_support(support: bool) -> Self {
+        if support {
+            BallotChoice::For
+        } else {
+            BallotChoice::Against
+        }
+    }
+
+    fn code(self) -> u8 {
+        match self {
+            BallotChoice::For => 1,
+            BallotChoice::Against => 2,
+        }
+    }
+}
+
+/// Tally weight for an attunement value; out-of-range and NaN inputs are clamped.
+pub fn attunement_weight(attunement: f32) -> f32 {
+    let a = if attunement.is_nan() { 0.0 } else { attunement.clamp(0.0, 1.0) };
+    BALLOT_BASE_WEIGHT + (1.0 - BALLOT_BASE_WEIGHT) * a
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BallotError {
+    /// The proposal is unknown or no longer open.
+    UnknownProposal(u64),
+    /// The ballot box has closed; ballots and delegations are final.
+    Closed,
+    NoBallot(VoterId),
+    NoDelegation(VoterId),
+    SelfDelegation(VoterId),
+    /// Delegating would close a loop; the chain from the delegate back to the voter.
+    DelegationCycle(Vec<VoterId>),
+    /// Record entries are not strictly ordered by voter.
+    UnorderedEntries,
+    /// A record entry carries a weight no attunement can produce.
+    InvalidWeight(VoterId),
+    /// A record entry's counted choice does not follow from the ballots and delegations.
+    ResolutionMismatch(VoterId),
+    TallyMismatch,
+    DigestMismatch,
+}
+
+impl fmt::Display for BallotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BallotError::UnknownProposal(id) => write!(f, "proposal {} is not open for voting", id),
+            BallotError::Closed => write!(f, "ballot box is closed"),
+            BallotError::NoBallot(v) => write!(f, "voter {} has no ballot to retract", v),
+            BallotError::NoDelegation(v) => write!(f, "voter {} has no delegation to revoke", v),
+            BallotError::SelfDelegation(v) => write!(f, "voter {} cannot delegate to themselves", v),
+            BallotError::DelegationCycle(chain) => {
+                let chain: Vec<String> = chain.iter().map(|v| v.to_string()).collect();
+                write!(f, "delegation would form a cycle: {}", chain.join(" -> "))
+            }
+            BallotError::UnorderedEntries => write!(f, "ballot record entries are not ordered by voter"),
+            BallotError::InvalidWeight(v) => write!(f, "ballot record weight for voter {} is out of range", v),
+            BallotError::ResolutionMismatch(v) => {
+                write!(f, "ballot record counts voter {} differently than their delegation chain", v)
+            }
+            BallotError::TallyMismatch => write!(f, "ballot record tally does not match its entries"),
+            BallotError::DigestMismatch => write!(f, "ballot record digest does not match its content"),
+        }
+    }
+}
+
+impl std::error::Error for BallotError {}
+
+// ============================================================================
+// Ballot box
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Ballot {
+    pub choice: BallotChoice,
+    pub cast_tick: u64,
+    /// Times this voter changed their ballot after first casting it.
+    pub revisions: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BallotBox {
+    ballots: BTreeMap<VoterId, Ballot>,
+    delegations: BTreeMap<VoterId, VoterId>,
+    closed: bool,
+}
+
+impl BallotBox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Refuse further ballots and delegations.
+    pub fn close(&mut self) {
+        self.closed = true;
+    }
+
+    pub fn ballot(&self, voter: VoterId) -> Option<&Ballot> {
+        self.ballots.get(&voter)
+    }
+
+    pub fn delegate_of(&self, voter: VoterId) -> Option<VoterId> {
+        self.delegations.get(&voter).copied()
+    }
+
+    /// Cast or change `voter`'s ballot. Returns true when an earlier ballot was replaced.
+    pub fn cast(&mut self, voter: VoterId, choice: BallotChoice, tick: u64) -> Result<bool, BallotError> {
+        if self.closed {
+            return Err(BallotError::Closed);
+        }
+        match self.ballots.get_mut(&voter) {
+            Some(ballot) => {
+                if ballot.choice != choice {
+                    ballot.choice = choice;
+                    ballot.revisions += 1;
+                }
+                ballot.cast_tick = tick;
+                Ok(true)
+            }
+            None => {
+                self.ballots.insert(voter, Ballot { choice, cast_tick: tick, revisions: 0 });
+                Ok(false)
+            }
+        }
+    }
+
+    /// Withdraw `voter`'s ballot; a standing delegation takes effect again.
+    pub fn retract(&mut self, voter: VoterId) -> Result<(), BallotError> {
+        if self.closed {
+            return Err(BallotError::Closed);
+        }
+        self.ballots.remove(&voter).map(|_| ()).ok_or(BallotError::NoBallot(voter))
+    }
+
+    /// Delegate `voter`'s weight to `to`, replacing any earlier delegation.
+    pub fn delegate(&mut self, voter: VoterId, to: VoterId) -> Result<(), BallotError> {
+        if self.closed {
+            return Err(BallotError::Closed);
+        }
+        if voter == to {
+            return Err(BallotError::SelfDelegation(voter));
+        }
+        // Chains are kept acyclic whether or not anyone on them has voted, so
+        // retracting a ballot later can never leave weight circling.
+        let mut chain = vec![to];
+        let mut cursor = to;
+        while let Some(&next) = self.delegations.get(&cursor) {
+            chain.push(next);
+            if next == voter {
+                return Err(BallotError::DelegationCycle(chain));
+            }
+            cursor = next;
+        }
+        self.delegations.insert(voter, to);
+        Ok(())
+    }
+
+    pub fn revoke_delegation(&mut self, voter: VoterId) -> Result<(), BallotError> {
+        if self.closed {
+            return Err(BallotError::Closed);
+        }
+        self.delegations.remove(&voter).map(|_| ()).ok_or(BallotError::NoDelegation(voter))
+    }
+
+    /// Identity whose ballot decides where `voter`'s weight goes, if any.
+    pub fn resolve(&self, voter: VoterId) -> Option<VoterId> {
+        resolve_chain(voter, |v| self.ballots.contains_key(&v), |v| self.delegations.get(&v).copied())
+            .ok()
+            .flatten()
+    }
+
+    /// Direct ballots (for, against), ignoring delegation and weight.
+    pub fn direct_counts(&self) -> (u32, u32) {
+        self.ballots.values().fold((0, 0), |(f, a), b| match b.choice {
+            BallotChoice::For => (f + 1, a),
+            BallotChoice::Against => (f, a + 1),
+        })
+    }
+
+    /// Every identity that cast a ballot or delegated, ascending.
+    pub fn participants(&self) -> BTreeSet<VoterId> {
+        self.ballots.keys().chain(self.delegations.keys()).copied().collect()
+    }
+
+    /// Weighted tally; `attunement` is looked up once per participant.
+    pub fn tally(&self, attunement: impl Fn(VoterId) -> f32) -> BallotTally {
+        let entries = self.entries(attunement);
+        BallotTally::from_entries(&entries)
+    }
+
+    /// Snapshot as a publishable record. The box stays as it is; call `close` to finalize.
+    pub fn record(
+        &self,
+        realm_id: u8,
+        proposal_id: u64,
+        tick: u64,
+        attunement: impl Fn(VoterId) -> f32,
+    ) -> BallotRecord {
+        let entries = self.entries(attunement);
+        let tally = BallotTally::from_entries(&entries);
+        let mut record = BallotRecord {
+            version: BALLOT_RECORD_VERSION,
+            realm_id,
+            proposal_id,
+            tick,
+            entries,
+            tally,
+            digest: String::new(),
+        };
+        record.digest = record.compute_digest();
+        record
+    }
+
+    fn entries(&self, attunement: impl Fn(VoterId) -> f32) -> Vec<BallotEntry> {
+        self.participants()
+            .into_iter()
+            .map(|voter| BallotEntry {
+                voter,
+                choice: self.ballots.get(&voter).map(|b| b.choice),
+                delegate: self.delegations.get(&voter).copied(),
+                weight: attunement_weight(attunement(voter)),
+                counted_as: self.resolve(voter).and_then(|v| self.ballots.get(&v)).map(|b| b.choice),
+            })
+            .collect()
+    }
+}
+
+/// Walk from `voter` to the first identity with a ballot. `Err` carries the loop if
+/// the chain never terminates (only possible in a tampered record).
+fn resolve_chain(
+    voter: VoterId,
+    has_ballot: impl Fn(VoterId) -> bool,
+    delegate: impl Fn(VoterId) -> Option<VoterId>,
+) -> Result<Option<VoterId>, Vec<VoterId>> {
+    let mut seen = vec![voter];
+    let mut cursor = voter;
+    loop {
+        if has_ballot(cursor) {
+            return Ok(Some(cursor));
+        }
+        match delegate(cursor) {
+            None => return Ok(None),
+            Some(next) if seen.contains(&next) => {
+                seen.push(next);
+                return Err(seen);
+            }
+            Some(next) => {
+                seen.push(next);
+                cursor = next;
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Published record
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BallotEntry {
+    pub voter: VoterId,
+    /// The voter's own ballot, if they cast one.
+    pub choice: Option<BallotChoice>,
+    /// Standing delegation (ignored for counting while `choice` is set).
+    pub delegate: Option<VoterId>,
+    pub weight: f32,
+    /// Where this voter's weight landed; None when their chain reached nobody who voted.
+    pub counted_as: Option<BallotChoice>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BallotTally {
+    pub weight_for: f32,
+    pub weight_against: f32,
+    /// Weight of participants whose delegation chain reached nobody who voted.
+    pub weight_uncounted: f32,
+    pub voters_for: u32,
+    pub voters_against: u32,
+    pub voters_uncounted: u32,
+}
+
+impl BallotTally {
+    /// Sums in entry order, so a verifier reproduces the same bits.
+    pub fn from_entries(entries: &[BallotEntry]) -> Self {
+        let mut tally = BallotTally::default();
+        for e in entries {
+            match e.counted_as {
+                Some(BallotChoice::For) => {
+                    tally.weight_for += e.weight;
+                    tally.voters_for += 1;
+                }
+                Some(BallotChoice::Against) => {
+                    tally.weight_against += e.weight;
+                    tally.voters_against += 1;
+                }
+                None => {
+                    tally.weight_uncounted += e.weight;
+                    tally.voters_uncounted += 1;
+                }
+            }
+        }
+        tally
+    }
+
+    /// Distinct identities whose weight was counted for or against.
+    pub fn counted_voters(&self) -> u32 {
+        self.voters_for + self.voters_against
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BallotRecord {
+    pub version: u8,
+    pub realm_id: u8,
+    pub proposal_id: u64,
+    /// Tick the record was taken (deliberation tick).
+    pub tick: u64,
+    /// One entry per participant, ascending by voter.
+    pub entries: Vec<BallotEntry>,
+    pub tally: BallotTally,
+    /// Lowercase hex SHA-256 over `canonical_bytes`.
+    pub digest: String,
+}
+
+impl BallotRecord {
+    /// Fixed little-endian encoding of everything except the digest.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        fn choice(c: Option<BallotChoice>) -> u8 {
+            c.map(BallotChoice::code).unwrap_or(0)
+        }
+        let mut out = Vec::with_capacity(64 + self.entries.len() * 32);
+        out.extend_from_slice(b"powrush-ballot");
+        out.push(self.version);
+        out.push(self.realm_id);
+        out.extend_from_slice(&self.proposal_id.to_le_bytes());
+        out.extend_from_slice(&self.tick.to_le_bytes());
+        out.extend_from_slice(&(self.entries.len() as u64).to_le_bytes());
+        for e in &self.entries {
+            out.extend_from_slice(&e.voter.to_le_bytes());
+            out.push(choice(e.choice));
+            match e.delegate {
+                Some(d) => {
+                    out.push(1);
+                    out.extend_from_slice(&d.to_le_bytes());
+                }
+                None => out.push(0),
+            }
+            out.extend_from_slice(&e.weight.to_bits().to_le_bytes());
+            out.push(choice(e.counted_as));
+        }
+        let t = &self.tally;
+        for w in [t.weight_for, t.weight_against, t.weight_uncounted] {
+            out.extend_from_slice(&w.to_bits().to_le_bytes());
+        }
+        for n in [t.voters_for, t.voters_against, t.voters_uncounted] {
+            out.extend_from_slice(&n.to_le_bytes());
+        }
+        out
+    }
+
+    pub fn compute_digest(&self) -> String {
+        Sha256::digest(self.canonical_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Recompute delegation resolution, tally and digest from the entries alone.
+    pub fn verify(&self) -> Result<(), BallotError> {
+        if self.entries.windows(2).any(|w| w[0].voter >= w[1].voter) {
+            return Err(BallotError::UnorderedEntries);
+        }
+        let by_voter: BTreeMap<VoterId, &BallotEntry> = self.entries.iter().map(|e| (e.voter, e)).collect();
+        for e in &self.entries {
+            if !(BALLOT_BASE_WEIGHT..=1.0).contains(&e.weight) {
+                return Err(BallotError::InvalidWeight(e.voter));
+            }
+            let decider = resolve_chain(
+                e.voter,
+                |v| by_voter.get(&v).is_some_and(|x| x.choice.is_some()),
+                |v| by_voter.get(&v).and_then(|x| x.delegate),
+            )
+            .map_err(BallotError::DelegationCycle)?;
+            let expected = decider.and_then(|v| by_voter[&v].choice);
+            if expected != e.counted_as {
+                return Err(BallotError::ResolutionMismatch(e.voter));
+            }
+        }
+        if BallotTally::from_entries(&self.entries) != self.tally {
+            return Err(BallotError::TallyMismatch);
+        }
+        if self.compute_digest() != self.digest {
+            return Err(BallotError::DigestMismatch);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flat(_: VoterId) -> f32 {
+        0.0
+    }
+
+    #[test]
+    fn one_ballot_per_identity_and_changes_before_close() {
+        let mut bx = BallotBox::new();
+        assert_eq!(bx.cast(1, BallotChoice::For, 10), Ok(false));
+        assert_eq!(bx.cast(1, BallotChoice::For, 11), Ok(true));
+        assert_eq!(bx.cast(1, BallotChoice::Against, 12), Ok(true));
+        assert_eq!(bx.direct_counts(), (0, 1));
+        assert_eq!(bx.ballot(1).unwrap().revisions, 1);
+
+        bx.close();
+        assert_eq!(bx.cast(1, BallotChoice::For, 13), Err(BallotError::Closed));
+        assert_eq!(bx.cast(2, BallotChoice::For, 13), Err(BallotError::Closed));
+        assert_eq!(bx.direct_counts(), (0, 1));
+    }
+
+    #[test]
+    fn delegation_follows_chain_and_refuses_cycles() {
+        let mut bx = BallotBox::new();
+        bx.delegate(1, 2).unwrap();
+        bx.delegate(2, 3).unwrap();
+        assert_eq!(bx.delegate(3, 1), Err(BallotError::DelegationCycle(vec![1, 2, 3])));
+        assert_eq!(bx.delegate(4, 4), Err(BallotError::SelfDelegation(4)));
+
+        // Nobody on the chain has voted yet.
+        assert_eq!(bx.resolve(1), None);
+        bx.cast(3, BallotChoice::For, 5).unwrap();
+        assert_eq!(bx.resolve(1), Some(3));
+
+        // A direct ballot overrides the voter's own delegation.
+        bx.cast(2, BallotChoice::Against, 6).unwrap();
+        assert_eq!(bx.resolve(1), Some(2));
+        let t = bx.tally(flat);
+        assert_eq!((t.voters_for, t.voters_against), (1, 2));
+
+        bx.retract(2).unwrap();
+        assert_eq!(bx.resolve(1), Some(3));
+        assert_eq!(bx.tally(flat).voters_for, 3);
+    }
+
+    #[test]
+    fn attunement_tilts_but_never_multiplies_identities() {
+        let mut bx = BallotBox::new();
+        bx.cast(1, BallotChoice::For, 1).unwrap();
+        bx.cast(2, BallotChoice::Against, 1).unwrap();
+        bx.cast(3, BallotChoice::Against, 1).unwrap();
+        let t = bx.tally(|v| if v == 1 { 5.0 } else { 0.0 });
+        assert_eq!(t.weight_for, 1.0);
+        assert_eq!(t.weight_against, 2.0 * BALLOT_BASE_WEIGHT);
+        assert_eq!(attunement_weight(f32::NAN), BALLOT_BASE_WEIGHT);
+    }
+
+    #[test]
+    fn players_and_agents_with_the_same_id_are_separate_voters() {
+        let mut bx = BallotBox::new();
+        bx.cast(1000, BallotChoice::For, 1).unwrap();
+        assert_eq!(bx.cast(player_voter(1000), BallotChoice::Against, 1), Ok(false));
+        assert_eq!(bx.direct_counts(), (1, 1));
+        assert_eq!(voter_player(player_voter(1000)), Some(1000));
+        assert_eq!(voter_player(1000), None);
+    }
+
+    #[test]
+    fn record_verifies_and_detects_tampering() {
+        let mut bx = BallotBox::new();
+        bx.cast(7, BallotChoice::For, 1).unwrap();
+        bx.cast(9, BallotChoice::Against, 1).unwrap();
+        bx.delegate(8, 7).unwrap();
+        bx.delegate(10, 11).unwrap();
+        let record = bx.record(2, 42, 100, |v| v as f32 / 20.0);
+        assert_eq!(record.entries.iter().map(|e| e.voter).collect::<Vec<_>>(), vec![7, 8, 9, 10]);
+        assert_eq!(record.tally.voters_uncounted, 1);
+        assert_eq!(record.digest.len(), 64);
+        assert_eq!(record.verify(), Ok(()));
+
+        let mut flipped = record.clone();
+        flipped.entries[1].counted_as = Some(BallotChoice::Against);
+        assert_eq!(flipped.verify(), Err(BallotError::ResolutionMismatch(8)));
+
+        let mut inflated = record.clone();
+        inflated.tally.weight_for += 1.0;
+        assert_eq!(inflated.verify(), Err(BallotError::TallyMismatch));
+
+        let mut retagged = record.clone();
+        retagged.proposal_id = 43;
+        assert_eq!(retagged.verify(), Err(BallotError::DigestMismatch));
+
+        let mut duplicated = record;
+        duplicated.entries[1].voter = 7;
+        assert_eq!(duplicated.verify(), Err(BallotError::UnorderedEntries));
+    }
+}
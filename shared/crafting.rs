@@ -0,0 +1,664 @@
+//! shared/crafting.rs
+//! Powrush-MMO — Data-driven recipe graph, crafting queues and the production planner
+//! A recipe turns input items into output items in a number of runs. It may need tools
+//! (held, not consumed), a crafting station (a placed structure of that kind nearby) and
+//! time; every run also carries an ecological cost that feeds node stress and economy
+//! pressure. Recipes live in assets/recipes/*.ron, are validated as a whole against the
+//! item registry at load, and are published as one process-wide registry
+//! (`current` / `install`).
+//!
+//! The first recipe listed for an item is its primary recipe; primary recipes (inputs and
+//! tools) must form an acyclic graph so the planner can always expand an item down to
+//! raw resources. `CraftQueue` runs a player's jobs one after another on wall-clock time,
+//! so jobs keep running while the player is offline.
+//! AG-SML v1.0 | PATSAGi Councils | info@Rathor.ai
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::items::{self, ItemRegistry};
+
+/// Recipes compiled into the binary; the fallback when assets/recipes is absent.
+const BUNDLED: [(&str, &str); 1] = [("basic.ron", include_str!("../assets/recipes/basic.ron"))];
+
+/// Jobs one player may have queued at once.
+pub const CRAFT_QUEUE_MAX_JOBS: usize = 8;
+
+/// Runs a single job may ask for.
+pub const CRAFT_MAX_RUNS: u32 = 100;
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// SCHEMA
+// ════════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemAmount {
+    /// Item registry key
+    pub item: String,
+    pub count: u32,
+}
+
+/// Ecological footprint of one run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct EcologicalCost {
+    /// Stress added to resource nodes that yield the consumed raw inputs
+    #[serde(default)]
+    pub node_stress: f32,
+    /// Added to the economy's average pressure
+    #[serde(default)]
+    pub pressure: f32,
+}
+
+impl EcologicalCost {
+    pub fn scaled(self, runs: u64) -> Self {
+        Self { node_stress: self.node_stress * runs as f32, pressure: self.pressure * runs as f32 }
+    }
+}
+
+impl std::ops::Add for EcologicalCost {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self { node_stress: self.node_stress + other.node_stress, pressure: self.pressure + other.pressure }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecipeDef {
+    /// Stable string id (saved queues)
+    pub id: String,
+    /// Numeric id on the wire (CraftStart.recipe_id)
+    pub recipe_id: u32,
+    pub name: String,
+    pub inputs: Vec<ItemAmount>,
+    pub outputs: Vec<ItemAmount>,
+    /// Items that must be held to craft; never consumed
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// Placed structure kind that must be in range; None = craftable anywhere
+    #[serde(default)]
+    pub station: Option<String>,
+    /// Seconds per run
+    pub craft_secs: f32,
+    #[serde(default)]
+    pub ecology: EcologicalCost,
+}
+
+impl RecipeDef {
+    /// Units of `item` one run produces.
+    pub fn yield_of(&self, item: &str) -> u32 {
+        self.outputs.iter().filter(|o| o.item == item).map(|o| o.count).sum()
+    }
+
+    /// Whole seconds `runs` runs take.
+    pub fn duration_secs(&self, runs: u32) -> u64 {
+        (self.craft_secs as f64 * runs as f64).ceil() as u64
+    }
+
+    fn validate(&self, items: &ItemRegistry, problems: &mut Vec<String>) {
+        let mut bad = |what: String| problems.push(format!("{}: {}", self.id, what));
+        let snake_case = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+        if !snake_case(&self.id) {
+            bad("id must be non-empty snake_case".into());
+        }
+        if self.recipe_id == 0 {
+            bad("recipe_id must be > 0".into());
+        }
+        if self.name.trim().is_empty() {
+            bad("name is empty".into());
+        }
+        if self.inputs.is_empty() || self.outputs.is_empty() {
+            bad("needs at least one input and one output".into());
+        }
+        for amount in self.inputs.iter().chain(&self.outputs) {
+            if amount.count == 0 {
+                bad(format!("{} count must be > 0", amount.item));
+            }
+            if items.get(&amount.item).is_none() {
+                bad(format!("unknown item {}", amount.item));
+            }
+        }
+        for tool in &self.tools {
+            if items.get(tool).is_none() {
+                bad(format!("unknown tool {}", tool));
+            }
+        }
+        if self.outputs.iter().any(|o| self.inputs.iter().any(|i| i.item == o.item)) {
+            bad("an item is both input and output".into());
+        }
+        if self.station.as_deref().is_some_and(|s| !snake_case(s)) {
+            bad("station must be snake_case".into());
+        }
+        if !(self.craft_secs.is_finite() && self.craft_secs > 0.0) {
+            bad(format!("craft_secs {} must be > 0", self.craft_secs));
+        }
+        let e = self.ecology;
+        if ![e.node_stress, e.pressure].into_iter().all(|v| v.is_finite() && v >= 0.0) {
+            bad("ecology costs must be finite and >= 0".into());
+        }
+    }
+}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// ERRORS
+// ════════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug)]
+pub enum RecipeLoadError {
+    Io { path: PathBuf, error: String },
+    Parse { source: String, error: String },
+    /// Every problem found across all files
+    Invalid(Vec<String>),
+}
+
+impl std::fmt::Display for RecipeLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecipeLoadError::Io { path, error } => write!(f, "cannot read {}: {}", path.display(), error),
+            RecipeLoadError::Parse { source, error } => write!(f, "invalid recipe file {}: {}", source, error),
+            RecipeLoadError::Invalid(problems) => {
+                write!(f, "{} invalid recipe(s): {}", problems.len(), problems.join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for RecipeLoadError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CraftError {
+    UnknownRecipe(u32),
+    UnknownItem(String),
+    InvalidRuns(u32),
+    QueueFull,
+    UnknownJob(u64),
+    MissingInputs(Vec<ItemAmount>),
+    MissingTool(String),
+    /// No placed structure of this kind within range
+    NoStation(String),
+    InventoryFull,
+}
+
+impl std::fmt::Display for CraftError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CraftError::UnknownRecipe(id) => write!(f, "unknown recipe {}", id),
+            CraftError::UnknownItem(item) => write!(f, "unknown item {}", item),
+            CraftError::InvalidRuns(runs) => write!(f, "runs {} outside 1..={}", runs, CRAFT_MAX_RUNS),
+            CraftError::QueueFull => write!(f, "crafting queue is full ({} jobs)", CRAFT_QUEUE_MAX_JOBS),
+            CraftError::UnknownJob(id) => write!(f, "no queued crafting job {}", id),
+            CraftError::MissingInputs(missing) => {
+                let list: Vec<String> = missing.iter().map(|m| format!("{} x {}", m.count, m.item)).collect();
+                write!(f, "missing inputs: {}", list.join(", "))
+            }
+            CraftError::MissingTool(tool) => write!(f, "requires tool {}", tool),
+            CraftError::NoStation(kind) => write!(f, "requires a {} nearby", kind),
+            CraftError::InventoryFull => write!(f, "inventory is full"),
+        }
+    }
+}
+
+impl std::error::Error for CraftError {}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// REGISTRY
+// ════════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Default)]
+pub struct RecipeRegistry {
+    defs: Vec<RecipeDef>,
+    by_id: HashMap<String, usize>,
+    by_recipe_id: HashMap<u32, usize>,
+    /// Item → first recipe producing it
+    primary: HashMap<String, usize>,
+    /// Recipe → longest chain of primary recipes below it (raw-only inputs = 1)
+    depth: Vec<u32>,
+}
+
+impl RecipeRegistry {
+    /// Parse and validate `(source name, RON list of RecipeDef)` pairs as one set.
+    pub fn from_sources<'a>(
+        sources: impl IntoIterator<Item = (&'a str, &'a str)>,
+        items: &ItemRegistry,
+    ) -> Result<Self, RecipeLoadError> {
+        let mut defs = Vec::new();
+        for (source, text) in sources {
+            let parsed: Vec<RecipeDef> = ron::from_str(text)
+                .map_err(|e| RecipeLoadError::Parse { source: source.to_string(), error: e.to_string() })?;
+            defs.extend(parsed);
+        }
+        Self::from_defs(defs, items)
+    }
+
+    pub fn from_defs(defs: Vec<RecipeDef>, items: &ItemRegistry) -> Result<Self, RecipeLoadError> {
+        let mut problems = Vec::new();
+        let mut by_id = HashMap::new();
+        let mut by_recipe_id = HashMap::new();
+        let mut primary = HashMap::new();
+        for (i, def) in defs.iter().enumerate() {
+            def.validate(items, &mut problems);
+            if by_id.insert(def.id.clone(), i).is_some() {
+                problems.push(format!("{}: duplicate id", def.id));
+            }
+            if let Some(other) = by_recipe_id.insert(def.recipe_id, i) {
+                problems.push(format!("{}: recipe_id {} already used by {}", def.id, def.recipe_id, defs[other].id));
+            }
+            for output in &def.outputs {
+                primary.entry(output.item.clone()).or_insert(i);
+            }
+        }
+
+        let mut depth = vec![0; defs.len()];
+        for i in 0..defs.len() {
+            let mut visiting = Vec::new();
+            if let Err(item) = recipe_depth(i, &defs, &primary, &mut depth, &mut visiting) {
+                problems.push(format!("{}: primary recipe cycle through {}", defs[i].id, item));
+                break;
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(RecipeLoadError::Invalid(problems));
+        }
+        Ok(Self { defs, by_id, by_recipe_id, primary, depth })
+    }
+
+    /// Every `*.ron` file in `dir`, in file-name order.
+    pub fn load_dir(dir: &Path, items: &ItemRegistry) -> Result<Self, RecipeLoadError> {
+        let io = |path: &Path, e: std::io::Error| RecipeLoadError::Io { path: path.to_path_buf(), error: e.to_string() };
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+            .map_err(|e| io(dir, e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "ron"))
+            .collect();
+        paths.sort();
+
+        let mut files = Vec::with_capacity(paths.len());
+        for path in &paths {
+            files.push((path.display().to_string(), std::fs::read_to_string(path).map_err(|e| io(path, e))?));
+        }
+        Self::from_sources(files.iter().map(|(name, text)| (name.as_str(), text.as_str())), items)
+    }
+
+    /// The recipes compiled into this build, checked against the current item registry.
+    pub fn bundled() -> Self {
+        Self::from_sources(BUNDLED, &items::current()).expect("bundled recipes are valid")
+    }
+
+    pub fn get(&self, id: &str) -> Option<&RecipeDef> {
+        self.by_id.get(id).map(|&i| &self.defs[i])
+    }
+
+    pub fn by_recipe_id(&self, recipe_id: u32) -> Option<&RecipeDef> {
+        self.by_recipe_id.get(&recipe_id).map(|&i| &self.defs[i])
+    }
+
+    /// The recipe the planner uses for `item`; None for raw resources.
+    pub fn primary_for(&self, item: &str) -> Option<&RecipeDef> {
+        self.primary.get(item).map(|&i| &self.defs[i])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RecipeDef> {
+        self.defs.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.defs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.defs.is_empty()
+    }
+
+    /// Raw resources a recipe ultimately draws on (its inputs, expanded through primary
+    /// recipes). Its ecological cost lands on nodes of these types.
+    pub fn raw_sources(&self, recipe: &RecipeDef) -> BTreeSet<String> {
+        let mut out = BTreeSet::new();
+        let mut pending: Vec<&str> = recipe.inputs.iter().map(|i| i.item.as_str()).collect();
+        while let Some(item) = pending.pop() {
+            match self.primary_for(item) {
+                Some(producer) => pending.extend(producer.inputs.iter().map(|i| i.item.as_str())),
+                None => {
+                    out.insert(item.to_string());
+                }
+            }
+        }
+        out
+    }
+
+    /// Everything needed to end up holding `count` of `item`, given what is already held.
+    /// Held items (intermediates included) are used before anything is crafted; tools that
+    /// are not held are crafted once.
+    pub fn plan(&self, item: &str, count: u64, held: &HashMap<String, u64>) -> Result<CraftPlan, CraftError> {
+        if !self.primary.contains_key(item) && items::current().get(item).is_none() {
+            return Err(CraftError::UnknownItem(item.to_string()));
+        }
+        let mut planner = Planner { registry: self, held: held.clone(), runs: BTreeMap::new(), plan: CraftPlan::default() };
+        planner.need(item, count);
+
+        let mut steps: Vec<PlanStep> =
+            planner.runs.iter().map(|(&i, &runs)| PlanStep { recipe: self.defs[i].id.clone(), runs }).collect();
+        steps.sort_by_key(|s| (self.depth[self.by_id[&s.recipe]], s.recipe.clone()));
+        let mut plan = planner.plan;
+        for step in &steps {
+            let recipe = &self.defs[self.by_id[&step.recipe]];
+            for output in &recipe.outputs {
+                // Held stock is used before anything is crafted, so what is left of the
+                // pool (capped at what was made) is leftover output.
+                let made = output.count as u64 * step.runs;
+                let left = planner.held.get(&output.item).copied().unwrap_or(0).min(made);
+                if left > 0 {
+                    plan.surplus.insert(output.item.clone(), left);
+                }
+            }
+            plan.craft_secs += recipe.craft_secs as f64 * step.runs as f64;
+            plan.ecology = plan.ecology + recipe.ecology.scaled(step.runs);
+            if let Some(station) = &recipe.station {
+                plan.stations.insert(station.clone());
+            }
+        }
+        plan.item = item.to_string();
+        plan.count = count;
+        plan.steps = steps;
+        Ok(plan)
+    }
+}
+
+/// Longest primary-recipe chain below recipe `i`; Err(item) on a cycle.
+fn recipe_depth(
+    i: usize,
+    defs: &[RecipeDef],
+    primary: &HashMap<String, usize>,
+    depth: &mut [u32],
+    visiting: &mut Vec<usize>,
+) -> Result<u32, String> {
+    if depth[i] > 0 {
+        return Ok(depth[i]);
+    }
+    if visiting.contains(&i) {
+        return Err(defs[i].outputs[0].item.clone());
+    }
+    visiting.push(i);
+    let mut below = 0;
+    let needs = defs[i].inputs.iter().map(|a| &a.item).chain(&defs[i].tools);
+    for item in needs {
+        if let Some(&producer) = primary.get(item) {
+            below = below.max(recipe_depth(producer, defs, primary, depth, visiting)?);
+        }
+    }
+    visiting.pop();
+    depth[i] = below + 1;
+    Ok(depth[i])
+}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// PLANNER
+// ════════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanStep {
+    pub recipe: String,
+    pub runs: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CraftPlan {
+    pub item: String,
+    pub count: u64,
+    /// Crafts to run, inputs before the recipes that consume them
+    pub steps: Vec<PlanStep>,
+    /// Raw resources still to gather
+    pub raw: BTreeMap<String, u64>,
+    /// Every tool the chain needs (held or crafted by `steps`)
+    pub tools: BTreeSet<String>,
+    pub stations: BTreeSet<String>,
+    /// Leftover outputs (runs produce whole batches)
+    pub surplus: BTreeMap<String, u64>,
+    pub craft_secs: f64,
+    pub ecology: EcologicalCost,
+}
+
+struct Planner<'a> {
+    registry: &'a RecipeRegistry,
+    /// What is held, plus surplus from runs planned so far
+    held: HashMap<String, u64>,
+    runs: BTreeMap<usize, u64>,
+    plan: CraftPlan,
+}
+
+impl Planner<'_> {
+    fn need(&mut self, item: &str, count: u64) {
+        let mut count = count;
+        if let Some(have) = self.held.get_mut(item) {
+            let used = (*have).min(count);
+            *have -= used;
+            count -= used;
+        }
+        if count == 0 {
+            return;
+        }
+        let Some(&i) = self.registry.primary.get(item) else {
+            *self.plan.raw.entry(item.to_string()).or_insert(0) += count;
+            return;
+        };
+        let recipe = &self.registry.defs[i];
+        let per_run = recipe.yield_of(item) as u64;
+        let runs = count.div_ceil(per_run);
+        *self.runs.entry(i).or_insert(0) += runs;
+        for output in &recipe.outputs {
+            let made = output.count as u64 * runs;
+            let spare = if output.item == item { made - count } else { made };
+            if spare > 0 {
+                *self.held.entry(output.item.clone()).or_insert(0) += spare;
+            }
+        }
+        for tool in &recipe.tools {
+            if self.plan.tools.insert(tool.clone()) && self.held.get(tool).copied().unwrap_or(0) == 0 {
+                self.need(tool, 1);
+            }
+        }
+        for input in &recipe.inputs {
+            self.need(&input.item, input.count as u64 * runs);
+        }
+    }
+}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// QUEUE
+// ════════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CraftJob {
+    pub job_id: u64,
+    pub recipe: String,
+    pub runs: u32,
+    pub duration_secs: u64,
+    /// Unix seconds the outputs are delivered
+    pub ready_at: u64,
+    /// Inputs taken from the inventory when queued; refunded on cancel
+    pub consumed: Vec<ItemAmount>,
+}
+
+/// A player's crafting jobs, run one after another. Persisted, so jobs finish while
+/// the player is offline and are delivered on the next visit.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CraftQueue {
+    pub player_id: u64,
+    pub jobs: VecDeque<CraftJob>,
+    next_job_id: u64,
+}
+
+impl CraftQueue {
+    pub fn new(player_id: u64) -> Self {
+        Self { player_id, jobs: VecDeque::new(), next_job_id: 1 }
+    }
+
+    /// Append a job starting when the previous one finishes (or `now`).
+    /// The caller has already taken `runs` × inputs from the inventory.
+    pub fn enqueue(&mut self, recipe: &RecipeDef, runs: u32, now: u64) -> Result<&CraftJob, CraftError> {
+        if runs == 0 || runs > CRAFT_MAX_RUNS {
+            return Err(CraftError::InvalidRuns(runs));
+        }
+        if self.jobs.len() >= CRAFT_QUEUE_MAX_JOBS {
+            return Err(CraftError::QueueFull);
+        }
+        let start = self.jobs.back().map_or(now, |j| j.ready_at.max(now));
+        let duration_secs = recipe.duration_secs(runs);
+        let consumed =
+            recipe.inputs.iter().map(|i| ItemAmount { item: i.item.clone(), count: i.count * runs }).collect();
+        self.next_job_id = self.next_job_id.max(1);
+        self.jobs.push_back(CraftJob {
+            job_id: self.next_job_id,
+            recipe: recipe.id.clone(),
+            runs,
+            duration_secs,
+            ready_at: start + duration_secs,
+            consumed,
+        });
+        self.next_job_id += 1;
+        Ok(self.jobs.back().expect("pushed above"))
+    }
+
+    /// Remove a job; the ones behind it move up.
+    pub fn cancel(&mut self, job_id: u64, now: u64) -> Result<CraftJob, CraftError> {
+        let index = self.jobs.iter().position(|j| j.job_id == job_id).ok_or(CraftError::UnknownJob(job_id))?;
+        let job = self.jobs.remove(index).expect("position is in range");
+        let mut previous = if index == 0 { now } else { self.jobs[index - 1].ready_at.max(now) };
+        for later in self.jobs.iter_mut().skip(index) {
+            later.ready_at = previous + later.duration_secs;
+            previous = later.ready_at;
+        }
+        Ok(job)
+    }
+
+    /// Jobs finished by `now`, oldest first.
+    pub fn take_finished(&mut self, now: u64) -> Vec<CraftJob> {
+        let done = self.jobs.iter().take_while(|j| j.ready_at <= now).count();
+        self.jobs.drain(..done).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+}
+
+fn slot() -> &'static RwLock<Arc<RecipeRegistry>> {
+    static CURRENT: OnceLock<RwLock<Arc<RecipeRegistry>>> = OnceLock::new();
+    CURRENT.get_or_init(|| RwLock::new(Arc::new(RecipeRegistry::bundled())))
+}
+
+/// The registry every system reads (bundled recipes until something is installed).
+pub fn current() -> Arc<RecipeRegistry> {
+    slot().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Replace the process-wide registry (startup load, dev hot-reload).
+pub fn install(registry: Arc<RecipeRegistry>) {
+    *slot().write().unwrap_or_else(|e| e.into_inner()) = registry;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(item: &str, count: u32) -> ItemAmount {
+        ItemAmount { item: item.into(), count }
+    }
+
+    fn recipe(id: &str, recipe_id: u32, inputs: Vec<ItemAmount>, outputs: Vec<ItemAmount>) -> RecipeDef {
+        RecipeDef {
+            id: id.into(),
+            recipe_id,
+            name: id.into(),
+            inputs,
+            outputs,
+            tools: vec![],
+            station: None,
+            craft_secs: 2.0,
+            ecology: EcologicalCost { node_stress: 0.01, pressure: 0.001 },
+        }
+    }
+
+    #[test]
+    fn bundled_recipes_load_and_plan_down_to_raw_resources() {
+        let recipes = RecipeRegistry::bundled();
+        let sword = recipes.primary_for("mercy_sword").unwrap();
+        assert_eq!(recipes.by_recipe_id(sword.recipe_id).unwrap().id, sword.id);
+
+        let plan = recipes.plan("mercy_sword", 1, &HashMap::new()).unwrap();
+        assert!(plan.raw.keys().all(|item| recipes.primary_for(item).is_none()));
+        assert!(plan.tools.contains("basic_tools"));
+        assert!(plan.stations.contains("forge"));
+        // Inputs are crafted before the recipes that consume them.
+        let order: Vec<&str> = plan.steps.iter().map(|s| s.recipe.as_str()).collect();
+        let at = |id: &str| order.iter().position(|r| *r == id).unwrap();
+        assert!(at("plank") < at("basic_tools") && at("gold_ingot") < at("mercy_sword"));
+        assert!(plan.ecology.node_stress > 0.0 && plan.craft_secs > 0.0);
+        assert!(recipes.raw_sources(sword).contains("verdant_wood"));
+
+        // Held intermediates and tools shrink the plan.
+        let held = HashMap::from([("gold_ingot".to_string(), 2), ("basic_tools".to_string(), 1)]);
+        let lighter = recipes.plan("mercy_sword", 1, &held).unwrap();
+        assert!(lighter.steps.iter().all(|s| s.recipe != "gold_ingot" && s.recipe != "basic_tools"));
+        assert!(lighter.raw.get("gold").copied().unwrap_or(0) < plan.raw["gold"]);
+        assert_eq!(recipes.plan("moon_dust", 1, &held).unwrap_err(), CraftError::UnknownItem("moon_dust".into()));
+    }
+
+    #[test]
+    fn batches_round_up_and_leave_surplus() {
+        let items = items::current();
+        let planks = recipe("planks", 1, vec![amount("verdant_wood", 2)], vec![amount("plank", 4)]);
+        let recipes = RecipeRegistry::from_defs(vec![planks], &items).unwrap();
+        let plan = recipes.plan("plank", 5, &HashMap::new()).unwrap();
+        assert_eq!(plan.steps, vec![PlanStep { recipe: "planks".into(), runs: 2 }]);
+        assert_eq!(plan.raw["verdant_wood"], 4);
+        assert_eq!(plan.surplus["plank"], 3);
+    }
+
+    #[test]
+    fn validation_reports_every_problem() {
+        let items = items::current();
+        let mut broken = recipe("Bad", 0, vec![], vec![amount("moon_dust", 0)]);
+        broken.craft_secs = 0.0;
+        broken.tools = vec!["spoon".into()];
+        let ingot = recipe("ingot", 2, vec![amount("gold", 1)], vec![amount("gold_ingot", 1)]);
+        let mut loop_a = recipe("loop_a", 3, vec![amount("plank", 1)], vec![amount("crystal_lens", 1)]);
+        loop_a.tools = vec!["herbal_tonic".into()];
+        let loop_b = recipe("loop_b", 4, vec![amount("crystal_lens", 1)], vec![amount("herbal_tonic", 1)]);
+
+        let Err(RecipeLoadError::Invalid(problems)) =
+            RecipeRegistry::from_defs(vec![broken, ingot.clone(), ingot, loop_a, loop_b], &items)
+        else {
+            panic!("expected validation failure");
+        };
+        let all = problems.join("\n");
+        for expected in ["snake_case", "recipe_id must be > 0", "unknown item moon_dust", "unknown tool spoon", "craft_secs", "duplicate id", "recipe cycle"] {
+            assert!(all.contains(expected), "missing {:?} in {}", expected, all);
+        }
+    }
+
+    #[test]
+    fn queue_runs_jobs_back_to_back_across_logout() {
+        let planks = recipe("planks", 1, vec![amount("verdant_wood", 2)], vec![amount("plank", 4)]);
+        let mut queue = CraftQueue::new(9);
+        let first = queue.enqueue(&planks, 3, 100).unwrap().clone();
+        assert_eq!((first.ready_at, first.consumed.clone()), (106, vec![amount("verdant_wood", 6)]));
+        let second = queue.enqueue(&planks, 1, 101).unwrap().job_id;
+        assert_eq!(queue.jobs[1].ready_at, 108);
+        assert_eq!(queue.enqueue(&planks, 0, 101).unwrap_err(), CraftError::InvalidRuns(0));
+
+        // Cancelling the running job moves the next one up.
+        assert_eq!(queue.cancel(first.job_id, 103).unwrap().runs, 3);
+        assert_eq!(queue.jobs[0].ready_at, 105);
+        assert_eq!(queue.cancel(77, 103).unwrap_err(), CraftError::UnknownJob(77));
+
+        // A queue saved and restored later still delivers what finished meanwhile.
+        let mut restored: CraftQueue = serde_json::from_str(&serde_json::to_string(&queue).unwrap()).unwrap();
+        assert!(restored.take_finished(104).is_empty());
+        assert_eq!(restored.take_finished(10_000).iter().map(|j| j.job_id).collect::<Vec<_>>(), vec![second]);
+        assert!(restored.enqueue(&planks, 1, 10_000).unwrap().job_id > second);
+    }
+}
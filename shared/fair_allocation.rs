@@ -0,0 +1,354 @@
+// shared/fair_allocation.rs
+// Need-weighted fair allocation rounds (used by simulation + server)
+//
+// Surplus is handed out in discrete rounds rather than first come, first served.
+// During a round's window players declare needs; `run_round` then splits the
+// round's supply by weighted max-min fairness (water-filling): nobody receives
+// more than they asked for, and what a satisfied claimant leaves over is shared
+// among the rest in proportion to their weights. A claimant's weight is the need
+// severity times their NEVC ContributionClass weight — partitioned players still
+// receive, just with a smaller weight.
+//
+// Council ResourcePolicy decisions set the caps (AllocationPolicy): a reserve kept
+// back from every round, a per-player share of the round and an absolute ceiling.
+// Every claimant gets an AllocationRecord stating what was asked, their weight,
+// what was granted and what limited it, so each round can be audited afterwards.
+//
+// AG-SML v1.0 | PATSAGi Councils | info@Rathor.ai
+// Thunder locked in. Yoi ⚡
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::nevc_adapter::ContributionClass;
+
+/// Grants smaller than this are treated as zero (float dust from water-filling).
+const GRANT_EPSILON: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum NeedSeverity {
+    Comfort,
+    Standard,
+    Urgent,
+    Critical,
+}
+
+impl NeedSeverity {
+    pub fn weight(self) -> f64 {
+        match self {
+            NeedSeverity::Comfort => 0.5,
+            NeedSeverity::Standard => 1.0,
+            NeedSeverity::Urgent => 2.0,
+            NeedSeverity::Critical => 4.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NeedDeclaration {
+    pub player_id: u64,
+    pub resource: String,
+    pub amount: f64,
+    pub severity: NeedSeverity,
+    /// Class at declaration time (NevcHistory / ContributionLedger).
+    pub class: ContributionClass,
+}
+
+/// Caps for a round; council ResourcePolicy decisions adjust these.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AllocationPolicy {
+    /// Fraction of each round's supply held back in the pool.
+    pub reserve_fraction: f64,
+    /// Most one player may receive, as a fraction of the distributable supply.
+    pub max_share: f64,
+    /// Absolute ceiling per player per round.
+    pub max_per_player: Option<f64>,
+    pub contributor_weight: f64,
+    pub partition_weight: f64,
+}
+
+impl Default for AllocationPolicy {
+    fn default() -> Self {
+        Self {
+            reserve_fraction: 0.1,
+            max_share: 0.25,
+            max_per_player: None,
+            contributor_weight: 1.0,
+            partition_weight: 0.5,
+        }
+    }
+}
+
+impl AllocationPolicy {
+    pub fn class_weight(&self, class: ContributionClass) -> f64 {
+        match class {
+            ContributionClass::ActiveEternalContributor => self.contributor_weight,
+            ContributionClass::ZombiePartition => self.partition_weight,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.reserve_fraction) {
+            return Err(format!("reserve_fraction {} outside 0..=1", self.reserve_fraction));
+        }
+        if !(self.max_share > 0.0 && self.max_share <= 1.0) {
+            return Err(format!("max_share {} outside (0, 1]", self.max_share));
+        }
+        if self.max_per_player.is_some_and(|cap| cap.is_nan() || cap < 0.0) {
+            return Err("max_per_player must be non-negative".into());
+        }
+        if !(self.contributor_weight > 0.0 && self.partition_weight > 0.0) {
+            return Err("class weights must be positive".into());
+        }
+        Ok(())
+    }
+}
+
+/// What kept a claimant below their declared need.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AllocationLimit {
+    /// Received the full declared need.
+    Satisfied,
+    /// AllocationPolicy::max_share or max_per_player.
+    PolicyCap,
+    /// Supply ran out at the fair level.
+    Supply,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AllocationRecord {
+    pub round_id: u64,
+    pub player_id: u64,
+    pub resource: String,
+    pub declared: f64,
+    pub severity: NeedSeverity,
+    pub class: ContributionClass,
+    pub weight: f64,
+    pub granted: f64,
+    pub limit: AllocationLimit,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AllocationRound {
+    pub round_id: u64,
+    pub resource: String,
+    pub supply: f64,
+    /// Held back by policy before distribution.
+    pub reserved: f64,
+    pub distributed: f64,
+    pub policy: AllocationPolicy,
+    /// Sorted by player_id.
+    pub records: Vec<AllocationRecord>,
+}
+
+impl AllocationRound {
+    /// Supply that stays in the pool: the reserve plus whatever nobody needed.
+    pub fn returned(&self) -> f64 {
+        self.supply - self.distributed
+    }
+
+    pub fn granted_to(&self, player_id: u64) -> f64 {
+        self.records.iter().find(|r| r.player_id == player_id).map_or(0.0, |r| r.granted)
+    }
+}
+
+/// Weighted max-min split of `available` over `(cap, weight)` claims.
+/// Claims are served in order of cap/weight; each gets min(cap, its fair share of what is left).
+fn water_fill(available: f64, claims: &[(f64, f64)]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..claims.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (ca, wa) = claims[a];
+        let (cb, wb) = claims[b];
+        (ca / wa).total_cmp(&(cb / wb)).then(a.cmp(&b))
+    });
+
+    let mut grants = vec![0.0; claims.len()];
+    let mut remaining = available;
+    let mut remaining_weight: f64 = claims.iter().map(|(_, w)| w).sum();
+    for i in order {
+        let (cap, weight) = claims[i];
+        let fair = remaining * weight / remaining_weight;
+        let grant = cap.min(fair).max(0.0);
+        grants[i] = grant;
+        remaining -= grant;
+        remaining_weight -= weight;
+    }
+    grants
+}
+
+/// Split `supply` of `resource` over `needs` under `policy`. Needs for other
+/// resources are ignored; one player's several declarations are summed
+/// (severity = the highest declared).
+pub fn allocate_round(
+    round_id: u64,
+    resource: &str,
+    supply: f64,
+    needs: &[NeedDeclaration],
+    policy: &AllocationPolicy,
+) -> Result<AllocationRound, String> {
+    policy.validate()?;
+    if !(supply.is_finite() && supply >= 0.0) {
+        return Err(format!("supply {} must be finite and non-negative", supply));
+    }
+
+    let mut claimants: BTreeMap<u64, (f64, NeedSeverity, ContributionClass)> = BTreeMap::new();
+    for need in needs.iter().filter(|n| n.resource == resource) {
+        let entry = claimants.entry(need.player_id).or_insert((0.0, need.severity, need.class));
+        entry.0 += need.amount;
+        entry.1 = entry.1.max(need.severity);
+        entry.2 = need.class;
+    }
+
+    let reserved = supply * policy.reserve_fraction;
+    let available = supply - reserved;
+    let cap = (available * policy.max_share).min(policy.max_per_player.unwrap_or(f64::INFINITY));
+
+    let claims: Vec<(f64, f64)> = claimants
+        .values()
+        .map(|(declared, severity, class)| (declared.min(cap), severity.weight() * policy.class_weight(*class)))
+        .collect();
+    let grants = water_fill(available, &claims);
+
+    let records: Vec<AllocationRecord> = claimants
+        .into_iter()
+        .zip(grants)
+        .zip(&claims)
+        .map(|(((player_id, (declared, severity, class)), granted), (capped, weight))| {
+            let granted = if granted < GRANT_EPSILON { 0.0 } else { granted };
+            let limit = if granted >= declared - GRANT_EPSILON {
+                AllocationLimit::Satisfied
+            } else if granted >= capped - GRANT_EPSILON {
+                AllocationLimit::PolicyCap
+            } else {
+                AllocationLimit::Supply
+            };
+            AllocationRecord {
+                round_id,
+                player_id,
+                resource: resource.to_string(),
+                declared,
+                severity,
+                class,
+                weight: *weight,
+                granted,
+                limit,
+            }
+        })
+        .collect();
+
+    Ok(AllocationRound {
+        round_id,
+        resource: resource.to_string(),
+        supply,
+        reserved,
+        distributed: records.iter().map(|r| r.granted).sum(),
+        policy: policy.clone(),
+        records,
+    })
+}
+
+/// Collects declarations between rounds and numbers the rounds.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FairAllocator {
+    next_round_id: u64,
+    /// (resource, player_id) → declaration; re-declaring replaces.
+    pending: BTreeMap<(String, u64), NeedDeclaration>,
+}
+
+impl FairAllocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn declare(&mut self, need: NeedDeclaration) -> Result<(), String> {
+        if !(need.amount.is_finite() && need.amount > 0.0) {
+            return Err(format!("need {} must be finite and positive", need.amount));
+        }
+        self.pending.insert((need.resource.clone(), need.player_id), need);
+        Ok(())
+    }
+
+    pub fn withdraw(&mut self, resource: &str, player_id: u64) -> bool {
+        self.pending.remove(&(resource.to_string(), player_id)).is_some()
+    }
+
+    pub fn pending_for(&self, resource: &str) -> impl Iterator<Item = &NeedDeclaration> + '_ {
+        let resource = resource.to_string();
+        self.pending.values().filter(move |n| n.resource == resource)
+    }
+
+    pub fn has_pending(&self, resource: &str) -> bool {
+        self.pending_for(resource).next().is_some()
+    }
+
+    /// Close the round for `resource`: allocate `supply` over its declarations and clear them.
+    /// On error (bad policy or supply) the declarations stay pending.
+    pub fn run_round(&mut self, resource: &str, supply: f64, policy: &AllocationPolicy) -> Result<AllocationRound, String> {
+        let needs: Vec<NeedDeclaration> = self.pending_for(resource).cloned().collect();
+        let round = allocate_round(self.next_round_id, resource, supply, &needs, policy)?;
+        self.pending.retain(|(r, _), _| r != resource);
+        self.next_round_id += 1;
+        Ok(round)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn need(player_id: u64, amount: f64, severity: NeedSeverity, class: ContributionClass) -> NeedDeclaration {
+        NeedDeclaration { player_id, resource: "water".into(), amount, severity, class }
+    }
+
+    fn open_policy() -> AllocationPolicy {
+        AllocationPolicy { reserve_fraction: 0.0, max_share: 1.0, ..Default::default() }
+    }
+
+    #[test]
+    fn max_min_fairness_is_weighted_by_severity_and_class() {
+        let contributor = ContributionClass::ActiveEternalContributor;
+        let needs = [
+            need(1, 10.0, NeedSeverity::Standard, contributor),
+            need(2, 100.0, NeedSeverity::Critical, contributor),
+            need(3, 100.0, NeedSeverity::Standard, contributor),
+            need(4, 100.0, NeedSeverity::Standard, ContributionClass::ZombiePartition),
+        ];
+        let round = allocate_round(7, "water", 100.0, &needs, &open_policy()).unwrap();
+
+        // Player 1 is satisfied; the 90 left splits 4 : 1 : 0.5 among the rest.
+        assert_eq!(round.granted_to(1), 10.0);
+        assert!((round.granted_to(2) - 90.0 * 4.0 / 5.5).abs() < 1e-9);
+        assert!((round.granted_to(3) - 90.0 / 5.5).abs() < 1e-9);
+        assert!((round.granted_to(4) - 90.0 * 0.5 / 5.5).abs() < 1e-9);
+        assert!((round.distributed - 100.0).abs() < 1e-9);
+        assert_eq!(round.records[0].limit, AllocationLimit::Satisfied);
+        assert_eq!(round.records[1].limit, AllocationLimit::Supply);
+
+        // Order of declaration does not matter.
+        let mut reversed = needs.to_vec();
+        reversed.reverse();
+        assert_eq!(allocate_round(7, "water", 100.0, &reversed, &open_policy()).unwrap(), round);
+    }
+
+    #[test]
+    fn policy_caps_hold_back_reserve_and_limit_shares() {
+        let policy = AllocationPolicy { reserve_fraction: 0.2, max_share: 0.5, max_per_player: Some(30.0), ..Default::default() };
+        let contributor = ContributionClass::ActiveEternalContributor;
+        let mut allocator = FairAllocator::new();
+        allocator.declare(need(1, 500.0, NeedSeverity::Critical, contributor)).unwrap();
+        allocator.declare(need(2, 5.0, NeedSeverity::Comfort, contributor)).unwrap();
+        assert!(allocator.declare(need(3, -1.0, NeedSeverity::Standard, contributor)).is_err());
+
+        let round = allocator.run_round("water", 100.0, &policy).unwrap();
+        assert_eq!(round.reserved, 20.0);
+        assert_eq!(round.granted_to(1), 30.0);
+        assert_eq!(round.records[0].limit, AllocationLimit::PolicyCap);
+        assert_eq!(round.granted_to(2), 5.0);
+        assert!((round.returned() - 65.0).abs() < 1e-9);
+
+        // Declarations are consumed by the round; the next one starts empty.
+        assert!(!allocator.has_pending("water"));
+        let next = allocator.run_round("water", 100.0, &policy).unwrap();
+        assert_eq!((next.round_id, next.distributed), (1, 0.0));
+    }
+}
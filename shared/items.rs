@@ -0,0 +1,359 @@
+//! shared/items.rs
+//! Powrush-MMO — Data-driven item and resource registry
+//! One definition per item: stable string key (RBE, trade, harvesting), stable numeric id
+//! (`HotbarSlot.item_id`, replication), display name, localization key, category, stack
+//! limit, origin realms and RBE valuation. Definitions live in assets/items/*.ron, are
+//! validated as a whole at load, and are published as one process-wide registry
+//! (`current` / `install`). Anything arriving from a client or a save resolves against it;
+//! unknown keys and ids are rejected with `ItemError`.
+//! AG-SML v1.0 | PATSAGi Councils | info@Rathor.ai
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::nevc_history::RealmId;
+use crate::protocol::{ClientMessage, HotbarSlot, WireTradeItem};
+
+/// Definitions compiled into the binary; the fallback when assets/items is absent.
+const BUNDLED: [(&str, &str); 3] = [
+    ("resources.ron", include_str!("../assets/items/resources.ron")),
+    ("crafted.ron", include_str!("../assets/items/crafted.ron")),
+    ("goods.ron", include_str!("../assets/items/goods.ron")),
+];
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// SCHEMA
+// ════════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ItemCategory {
+    /// Harvested raw material
+    Resource,
+    /// Basic need covered by RBE replication (food, water, energy)
+    Provision,
+    /// Crafted intermediate (planks, ingots, lenses)
+    Material,
+    Tool,
+    Structure,
+    Vehicle,
+    /// Event drops, relics, tokens
+    Artifact,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemDef {
+    /// Stable string key (RBE resources, trade terms, harvest nodes, event rewards)
+    pub id: String,
+    /// Numeric id on the wire (`HotbarSlot.item_id`, replication). 0 is the empty slot.
+    pub item_id: u32,
+    pub name: String,
+    /// Localization table key; clients fall back to `name` when it is missing
+    pub loc_key: String,
+    pub category: ItemCategory,
+    /// Most units one inventory / bank slot can hold
+    pub stack_limit: u32,
+    /// Realms this item is harvested in; empty = any realm
+    #[serde(default)]
+    pub origin_realms: Vec<RealmId>,
+    /// Relative RBE value per unit (trade fairness scoring)
+    pub value: f32,
+    /// Replicated freely by the RBE as a basic need
+    #[serde(default)]
+    pub basic: bool,
+    /// Grace points needed before the RBE unlocks this item
+    #[serde(default)]
+    pub unlock_grace: Option<u64>,
+}
+
+impl ItemDef {
+    pub fn native_to(&self, realm: RealmId) -> bool {
+        self.origin_realms.is_empty() || self.origin_realms.contains(&realm)
+    }
+
+    fn validate(&self, problems: &mut Vec<String>) {
+        let mut bad = |what: String| problems.push(format!("{}: {}", self.id, what));
+
+        if self.id.is_empty() || !self.id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+            bad("id must be non-empty snake_case".into());
+        }
+        if self.item_id == 0 {
+            bad("item_id 0 is reserved for the empty slot".into());
+        }
+        if self.name.trim().is_empty() {
+            bad("name is empty".into());
+        }
+        if self.loc_key.trim().is_empty() {
+            bad("loc_key is empty".into());
+        }
+        if self.stack_limit == 0 {
+            bad("stack_limit must be > 0".into());
+        }
+        if !(self.value.is_finite() && self.value >= 0.0) {
+            bad(format!("value {} must be finite and >= 0", self.value));
+        }
+        if self.basic && self.unlock_grace.is_some() {
+            bad("basic items cannot require unlock_grace".into());
+        }
+    }
+}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// REGISTRY
+// ════════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug)]
+pub enum ItemLoadError {
+    Io { path: PathBuf, error: String },
+    Parse { source: String, error: String },
+    /// Every problem found across all files
+    Invalid(Vec<String>),
+}
+
+impl std::fmt::Display for ItemLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ItemLoadError::Io { path, error } => write!(f, "cannot read {}: {}", path.display(), error),
+            ItemLoadError::Parse { source, error } => write!(f, "invalid item file {}: {}", source, error),
+            ItemLoadError::Invalid(problems) => {
+                write!(f, "{} invalid item definition(s): {}", problems.len(), problems.join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ItemLoadError {}
+
+/// A key, id or stack that does not resolve against the registry.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemError {
+    UnknownKey(String),
+    UnknownId(u32),
+    OverStack { item: String, count: u32, limit: u32 },
+}
+
+impl std::fmt::Display for ItemError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ItemError::UnknownKey(key) => write!(f, "unknown item {:?}", key),
+            ItemError::UnknownId(id) => write!(f, "unknown item id {}", id),
+            ItemError::OverStack { item, count, limit } => {
+                write!(f, "{} x {} exceeds the stack limit of {}", count, item, limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ItemError {}
+
+#[derive(Debug, Default)]
+pub struct ItemRegistry {
+    defs: Vec<ItemDef>,
+    by_id: HashMap<String, usize>,
+    by_item_id: HashMap<u32, usize>,
+}
+
+impl ItemRegistry {
+    /// Parse and validate `(source name, RON list of ItemDef)` pairs as one set.
+    pub fn from_sources<'a>(sources: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<Self, ItemLoadError> {
+        let mut defs = Vec::new();
+        for (source, text) in sources {
+            let parsed: Vec<ItemDef> = ron::from_str(text)
+                .map_err(|e| ItemLoadError::Parse { source: source.to_string(), error: e.to_string() })?;
+            defs.extend(parsed);
+        }
+        Self::from_defs(defs)
+    }
+
+    pub fn from_defs(defs: Vec<ItemDef>) -> Result<Self, ItemLoadError> {
+        let mut problems = Vec::new();
+        let mut by_id = HashMap::new();
+        let mut by_item_id = HashMap::new();
+        for (i, def) in defs.iter().enumerate() {
+            def.validate(&mut problems);
+            if by_id.insert(def.id.clone(), i).is_some() {
+                problems.push(format!("{}: duplicate id", def.id));
+            }
+            if let Some(other) = by_item_id.insert(def.item_id, i) {
+                problems.push(format!("{}: item_id {} already used by {}", def.id, def.item_id, defs[other].id));
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(ItemLoadError::Invalid(problems));
+        }
+        Ok(Self { defs, by_id, by_item_id })
+    }
+
+    /// Every `*.ron` file in `dir`, in file-name order.
+    pub fn load_dir(dir: &Path) -> Result<Self, ItemLoadError> {
+        let io = |path: &Path, e: std::io::Error| ItemLoadError::Io { path: path.to_path_buf(), error: e.to_string() };
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+            .map_err(|e| io(dir, e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "ron"))
+            .collect();
+        paths.sort();
+
+        let mut files = Vec::with_capacity(paths.len());
+        for path in &paths {
+            files.push((path.display().to_string(), std::fs::read_to_string(path).map_err(|e| io(path, e))?));
+        }
+        Self::from_sources(files.iter().map(|(name, text)| (name.as_str(), text.as_str())))
+    }
+
+    /// The definitions compiled into this build.
+    pub fn bundled() -> Self {
+        Self::from_sources(BUNDLED).expect("bundled item definitions are valid")
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ItemDef> {
+        self.by_id.get(id).map(|&i| &self.defs[i])
+    }
+
+    pub fn by_item_id(&self, item_id: u32) -> Option<&ItemDef> {
+        self.by_item_id.get(&item_id).map(|&i| &self.defs[i])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ItemDef> {
+        self.defs.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.defs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.defs.is_empty()
+    }
+
+    pub fn resolve(&self, id: &str) -> Result<&ItemDef, ItemError> {
+        self.get(id).ok_or_else(|| ItemError::UnknownKey(id.to_string()))
+    }
+
+    pub fn resolve_id(&self, item_id: u32) -> Result<&ItemDef, ItemError> {
+        self.by_item_id(item_id).ok_or(ItemError::UnknownId(item_id))
+    }
+
+    /// Per-unit RBE value; unknown keys are worth nothing.
+    pub fn value_of(&self, id: &str) -> f32 {
+        self.get(id).map_or(0.0, |d| d.value)
+    }
+
+    /// Empty slots always pass; filled slots need a known id within its stack limit.
+    pub fn check_slot(&self, slot: &HotbarSlot) -> Result<(), ItemError> {
+        if slot.count == 0 {
+            return Ok(());
+        }
+        let def = self.resolve_id(slot.item_id)?;
+        if slot.count > def.stack_limit {
+            return Err(ItemError::OverStack { item: def.id.clone(), count: slot.count, limit: def.stack_limit });
+        }
+        Ok(())
+    }
+
+    /// Server boundary check: every item key a client message names must resolve.
+    pub fn check_client_message(&self, message: &ClientMessage) -> Result<(), ItemError> {
+        let check_all = |items: &[WireTradeItem]| items.iter().try_for_each(|i| self.resolve(&i.resource).map(|_| ()));
+        match message {
+            ClientMessage::RbeAbundanceQuery { resource_type, .. } => self.resolve(resource_type).map(|_| ()),
+            ClientMessage::TradeOffer { offered, requested, .. } | ClientMessage::TradeCounter { offered, requested, .. } => {
+                check_all(offered)?;
+                check_all(requested)
+            }
+            ClientMessage::CraftPlanRequest { item_id, .. } => self.resolve_id(*item_id).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn slot() -> &'static RwLock<Arc<ItemRegistry>> {
+    static CURRENT: OnceLock<RwLock<Arc<ItemRegistry>>> = OnceLock::new();
+    CURRENT.get_or_init(|| RwLock::new(Arc::new(ItemRegistry::bundled())))
+}
+
+/// The registry every system reads (bundled definitions until something is installed).
+pub fn current() -> Arc<ItemRegistry> {
+    slot().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Replace the process-wide registry (startup load, dev hot-reload).
+pub fn install(registry: Arc<ItemRegistry>) {
+    *slot().write().unwrap_or_else(|e| e.into_inner()) = registry;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn def(id: &str, item_id: u32) -> ItemDef {
+        ItemDef {
+            id: id.into(),
+            item_id,
+            name: id.into(),
+            loc_key: format!("item.{}", id),
+            category: ItemCategory::Resource,
+            stack_limit: 50,
+            origin_realms: vec![],
+            value: 1.0,
+            basic: false,
+            unlock_grace: None,
+        }
+    }
+
+    #[test]
+    fn bundled_definitions_load_and_index() {
+        let registry = ItemRegistry::bundled();
+        let food = registry.get("food").unwrap();
+        assert!(food.basic);
+        assert_eq!(registry.by_item_id(food.item_id).unwrap().id, "food");
+        assert!(registry.get("custom_home").unwrap().unlock_grace.is_some());
+        assert!(registry.iter().all(|d| d.loc_key.starts_with("item.")));
+    }
+
+    #[test]
+    fn validation_reports_every_problem() {
+        let mut broken = def("Bad Id", 0);
+        broken.stack_limit = 0;
+        broken.value = f32::NAN;
+        let mut greedy = def("greedy", 3);
+        greedy.basic = true;
+        greedy.unlock_grace = Some(10);
+
+        let Err(ItemLoadError::Invalid(problems)) =
+            ItemRegistry::from_defs(vec![def("a", 1), broken, greedy, def("a", 2), def("b", 1)])
+        else {
+            panic!("expected validation failure");
+        };
+        let all = problems.join("\n");
+        for expected in ["snake_case", "reserved", "stack_limit", "value", "unlock_grace", "duplicate id", "item_id 1"] {
+            assert!(all.contains(expected), "missing {:?} in {}", expected, all);
+        }
+    }
+
+    #[test]
+    fn boundary_checks_reject_unknown_items() {
+        let registry = ItemRegistry::from_defs(vec![def("wood", 7)]).unwrap();
+        assert!(registry.check_slot(&HotbarSlot::empty()).is_ok());
+        assert!(registry.check_slot(&HotbarSlot::new(7, 50, 1.0)).is_ok());
+        assert_eq!(registry.check_slot(&HotbarSlot::new(8, 1, 1.0)), Err(ItemError::UnknownId(8)));
+        assert!(matches!(registry.check_slot(&HotbarSlot::new(7, 51, 1.0)), Err(ItemError::OverStack { limit: 50, .. })));
+
+        let query = |resource: &str| ClientMessage::RbeAbundanceQuery { resource_type: resource.into(), amount: 1.0 };
+        assert!(registry.check_client_message(&query("wood")).is_ok());
+        assert_eq!(registry.check_client_message(&query("gold")), Err(ItemError::UnknownKey("gold".into())));
+        let offer = ClientMessage::TradeOffer {
+            target_player_id: 2,
+            offered: vec![WireTradeItem { resource: "wood".into(), amount: 1.0 }],
+            requested: vec![WireTradeItem { resource: "dust".into(), amount: 1.0 }],
+        };
+        assert_eq!(registry.check_client_message(&offer), Err(ItemError::UnknownKey("dust".into())));
+    }
+
+    #[test]
+    fn parse_errors_name_the_file() {
+        let err = ItemRegistry::from_sources([("broken.ron", "[(id: ")]).unwrap_err();
+        assert!(err.to_string().contains("broken.ron"));
+    }
+}
@@ -3,14 +3,34 @@
 // Phase 0–11 NEVC surfaces (including real-estate lattice attachment).
 // AG-SML v1.0 | PATSAGi Councils | info@Rathor.ai
 
+// Canonical wire protocol — the only ServerMessage / ClientMessage definitions.
+#[path = "src/protocol.rs"]
 pub mod protocol;
+#[cfg(feature = "protocol")]
+#[path = "src/wire_compat.rs"]
+pub mod wire_compat;
+// UDP framing + channel routing for the optional v30 datagram path
+#[path = "src/datagram.rs"]
+pub mod datagram;
 pub mod nevc_adapter;
+// Data-driven ability definitions (assets/abilities/*.ron) + process-wide registry
+pub mod abilities;
+// Data-driven item / resource definitions (assets/items/*.ron) + process-wide registry
+pub mod items;
+// Recipe graph (assets/recipes/*.ron), crafting queues and the raw-resource planner
+pub mod crafting;
 pub mod contribution_ledger;
 pub mod contribution_events;
 pub mod nevc_pipeline_demo;
 pub mod real_estate_lattice_nevc;
 pub mod nevc_game_loop;
 pub mod nevc_persistence;
+// Append-only per-player sample log: decay windows, class transitions, realm rankings
+pub mod nevc_history;
+// Need-weighted max-min allocation rounds with council caps and per-player audit records
+pub mod fair_allocation;
+// Per-identity council ballots, liquid delegation and verifiable ballot records
+pub mod council_ballot;
 pub mod nevc_bridge;
 pub mod nevc_visibility;
 
@@ -26,15 +46,18 @@ pub mod rbe_queries {
 }
 
 pub mod prelude {
-    pub use crate::protocol::{ClientMessage, ServerMessage, TradeOffer, Vec3Ser, HealthComponent};
+    pub use crate::protocol::{ClientMessage, ServerMessage, Vec3Ser, PROTOCOL_VERSION};
     pub use crate::rbe_queries;
     pub use crate::nevc_adapter::{ContributionClass, NevcSample, NevcResult, NevcConfig, NevcSummary, compute_nevc, score_instant, sample_from_rbe_action};
     pub use crate::contribution_ledger::{ContributionLedger, PlayerContribution};
     pub use crate::contribution_events::{ContributionEvent, apply_event, apply_event_class};
     pub use crate::nevc_pipeline_demo::{run_demo, classify};
     pub use crate::real_estate_lattice_nevc::{RealEstateStewardshipEvent, RealEstateNevcLedger, apply_real_estate_event, sample_from_stewardship, sample_from_event};
-    pub use crate::nevc_game_loop::{HarvestNevcInput, harvest_to_event, apply_harvest_to_ledger, apply_harvest_class, apply_harvest_summary};
+    pub use crate::nevc_game_loop::{HarvestNevcInput, harvest_to_event, harvest_to_sample, apply_harvest_to_ledger, apply_harvest_class, apply_harvest_summary};
     pub use crate::nevc_persistence::{NevcPlayerRecord, NevcPersistenceStore};
+    pub use crate::nevc_history::{NevcHistory, NevcHistoryConfig, ClassTransition, TimelinePoint};
+    pub use crate::items::{ItemCategory, ItemDef, ItemError, ItemRegistry};
+    pub use crate::fair_allocation::{AllocationPolicy, AllocationRecord, AllocationRound, FairAllocator, NeedDeclaration, NeedSeverity};
     pub use crate::nevc_bridge::{compute_nevc_bridged, score_instant_bridged, summary_bridged, active_mode};
     pub use crate::nevc_visibility::{HorizonPreset, status_line, badge_text, summary_from_result, panel_fields};
 }
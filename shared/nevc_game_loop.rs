@@ -9,7 +9,7 @@
 
 use crate::contribution_events::{apply_event, ContributionEvent};
 use crate::contribution_ledger::ContributionLedger;
-use crate::nevc_adapter::{ContributionClass, NevcResult, NevcSummary};
+use crate::nevc_adapter::{sample_from_rbe_action, ContributionClass, NevcResult, NevcSample, NevcSummary};
 
 /// Outcome of a harvest (or similar RBE action) for NEVC purposes.
 #[derive(Clone, Debug)]
@@ -64,6 +64,16 @@ pub fn harvest_to_event(input: &HarvestNevcInput) -> ContributionEvent {
     }
 }
 
+/// The NEVC sample a harvest outcome contributes (`t` = 0; history logs stamp their own time).
+pub fn harvest_to_sample(input: &HarvestNevcInput) -> NevcSample {
+    match harvest_to_event(input) {
+        ContributionEvent::RbeAction { abundance_alignment, waste_or_harm, .. } => {
+            sample_from_rbe_action(abundance_alignment, waste_or_harm, 0)
+        }
+        ContributionEvent::RawSample { valence, grief_load, .. } => NevcSample::new(valence, grief_load, 0),
+    }
+}
+
 /// Apply a harvest outcome to the ledger and return the full result.
 pub fn apply_harvest_to_ledger(
     ledger: &mut ContributionLedger,
@@ -0,0 +1,490 @@
+// shared/nevc_history.rs
+// Phase 12 — Append-only NEVC contribution history
+//
+// Every sample a player contributes is appended (one JSON line) to that player's
+// log, together with each ContributionClass transition and the sample that caused
+// it. The live class is compute_nevc over the decay window (the last
+// `decay_window_secs`), so old behaviour ages out instead of being folded into a
+// running mean forever; any other range can be recomputed from the log.
+//
+// Compaction folds samples older than `compact_after_secs` into one aggregate per
+// realm and bucket (recomputation over those ranges becomes bucket-granular).
+// Transitions are never compacted: "why did my class change" stays answerable.
+//
+// AG-SML v1.0 | PATSAGi Councils | info@Rathor.ai
+// Thunder locked in. Yoi ⚡
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::nevc_adapter::{compute_nevc, ContributionClass, NevcConfig, NevcResult, NevcSample};
+
+/// Realm identifier (aligned with MultiRealmHarness::RealmId).
+pub type RealmId = u8;
+
+const DAY_SECS: u64 = 86_400;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NevcHistoryConfig {
+    pub nevc: NevcConfig,
+    /// Samples older than this no longer count toward the live class.
+    pub decay_window_secs: u64,
+    /// Samples older than this are folded into per-bucket aggregates by `compact`.
+    pub compact_after_secs: u64,
+    pub compact_bucket_secs: u64,
+}
+
+impl Default for NevcHistoryConfig {
+    fn default() -> Self {
+        Self {
+            nevc: NevcConfig::default(),
+            decay_window_secs: 30 * DAY_SECS,
+            compact_after_secs: 90 * DAY_SECS,
+            compact_bucket_secs: DAY_SECS,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClassTransition {
+    /// Unix seconds.
+    pub at: u64,
+    pub from: ContributionClass,
+    pub to: ContributionClass,
+    /// Decay-window score right after the change.
+    pub score: f64,
+    pub samples_in_window: usize,
+    /// The sample that crossed the threshold, with its realm; None when older
+    /// samples aged out of the decay window.
+    pub cause: Option<(RealmId, NevcSample)>,
+}
+
+/// One line of a player's log.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum NevcLogEntry {
+    Sample { at: u64, realm_id: RealmId, sample: NevcSample },
+    /// `count` samples from `[from, to)` folded into their mean by compaction.
+    Compacted { from: u64, to: u64, realm_id: RealmId, count: u32, mean: NevcSample },
+    Transition(ClassTransition),
+}
+
+impl NevcLogEntry {
+    fn at(&self) -> u64 {
+        match self {
+            NevcLogEntry::Sample { at, .. } => *at,
+            NevcLogEntry::Compacted { from, .. } => *from,
+            NevcLogEntry::Transition(t) => t.at,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NevcRecordOutcome {
+    /// Decay-window result after the sample.
+    pub result: NevcResult,
+    pub transition: Option<ClassTransition>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TimelinePoint {
+    pub at: u64,
+    /// Decay-window result ending at `at`.
+    pub result: NevcResult,
+}
+
+#[derive(Clone, Debug)]
+struct PlayerLog {
+    entries: Vec<NevcLogEntry>,
+    class: ContributionClass,
+}
+
+impl Default for PlayerLog {
+    fn default() -> Self {
+        Self { entries: Vec::new(), class: ContributionClass::ZombiePartition }
+    }
+}
+
+impl PlayerLog {
+    /// Samples in `range` (optionally one realm), `t` relative to the range start so
+    /// compute_nevc's horizon emphasis favours the recent end. Aggregates expand to
+    /// `count` copies of their mean.
+    fn samples_in(&self, range: &Range<u64>, realm: Option<RealmId>) -> Vec<NevcSample> {
+        let in_realm = |r: &RealmId| realm.is_none_or(|want| want == *r);
+        let mut out = Vec::new();
+        for entry in &self.entries {
+            match entry {
+                NevcLogEntry::Sample { at, realm_id, sample } if range.contains(at) && in_realm(realm_id) => {
+                    out.push(NevcSample { t: at - range.start, ..sample.clone() });
+                }
+                NevcLogEntry::Compacted { from, realm_id, count, mean, .. }
+                    if range.contains(from) && in_realm(realm_id) =>
+                {
+                    let sample = NevcSample { t: from - range.start, ..mean.clone() };
+                    out.extend(std::iter::repeat_n(sample, *count as usize));
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+}
+
+/// Per-player append-only sample logs; `dir` None keeps everything in memory.
+#[derive(Clone, Debug, Default)]
+pub struct NevcHistory {
+    pub config: NevcHistoryConfig,
+    dir: Option<PathBuf>,
+    players: HashMap<u64, PlayerLog>,
+}
+
+impl NevcHistory {
+    pub fn in_memory(config: NevcHistoryConfig) -> Self {
+        Self { config, dir: None, players: HashMap::new() }
+    }
+
+    /// Sovereign-mode log directory helper.
+    pub fn default_dir() -> PathBuf {
+        PathBuf::from("data/nevc_history")
+    }
+
+    /// Open (creating if needed) the logs under `dir`. A torn last line from a crash
+    /// mid-append is truncated away; damage anywhere else is an error.
+    pub fn open(dir: &Path, config: NevcHistoryConfig) -> Result<Self, String> {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        let mut players = HashMap::new();
+        for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            if path.extension().is_none_or(|ext| ext != "jsonl") {
+                continue;
+            }
+            let Some(player_id) = path.file_stem().and_then(|s| s.to_str()).and_then(|s| s.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            let data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            let lines: Vec<&str> = data.lines().filter(|l| !l.trim().is_empty()).collect();
+            let mut log = PlayerLog::default();
+            for (i, line) in lines.iter().enumerate() {
+                match serde_json::from_str::<NevcLogEntry>(line) {
+                    Ok(entry) => {
+                        if let NevcLogEntry::Transition(t) = &entry {
+                            log.class = t.to;
+                        }
+                        log.entries.push(entry);
+                    }
+                    Err(_) if i + 1 == lines.len() && !data.ends_with('\n') => {
+                        let valid_len = data.rfind('\n').map_or(0, |n| n + 1) as u64;
+                        let file = OpenOptions::new().write(true).open(&path).map_err(|e| e.to_string())?;
+                        file.set_len(valid_len).map_err(|e| e.to_string())?;
+                    }
+                    Err(e) => return Err(format!("{} line {}: {}", path.display(), i + 1, e)),
+                }
+            }
+            players.insert(player_id, log);
+        }
+        Ok(Self { config, dir: Some(dir.to_path_buf()), players })
+    }
+
+    fn log_path(&self, player_id: u64) -> Option<PathBuf> {
+        self.dir.as_ref().map(|d| d.join(format!("{}.jsonl", player_id)))
+    }
+
+    fn append(&mut self, player_id: u64, entry: NevcLogEntry) -> Result<(), String> {
+        if let Some(path) = self.log_path(player_id) {
+            let mut line = serde_json::to_string(&entry).map_err(|e| e.to_string())?;
+            line.push('\n');
+            let mut file = OpenOptions::new().create(true).append(true).open(&path).map_err(|e| e.to_string())?;
+            file.write_all(line.as_bytes()).map_err(|e| e.to_string())?;
+            file.sync_data().map_err(|e| e.to_string())?;
+        }
+        self.players.entry(player_id).or_default().entries.push(entry);
+        Ok(())
+    }
+
+    /// Append a sample and re-evaluate the decay window at `at`; a class change is
+    /// logged as a transition caused by this sample.
+    pub fn record(
+        &mut self,
+        player_id: u64,
+        realm_id: RealmId,
+        sample: NevcSample,
+        at: u64,
+    ) -> Result<NevcRecordOutcome, String> {
+        self.append(player_id, NevcLogEntry::Sample { at, realm_id, sample: sample.clone() })?;
+        self.evaluate(player_id, at, Some((realm_id, sample)))
+    }
+
+    /// Re-evaluate without a new sample, logging transitions caused by decay.
+    pub fn refresh(&mut self, player_id: u64, now: u64) -> Result<Option<ClassTransition>, String> {
+        if !self.players.contains_key(&player_id) {
+            return Ok(None);
+        }
+        Ok(self.evaluate(player_id, now, None)?.transition)
+    }
+
+    pub fn refresh_all(&mut self, now: u64) -> Result<Vec<(u64, ClassTransition)>, String> {
+        let mut ids: Vec<u64> = self.players.keys().copied().collect();
+        ids.sort_unstable();
+        let mut out = Vec::new();
+        for player_id in ids {
+            if let Some(t) = self.refresh(player_id, now)? {
+                out.push((player_id, t));
+            }
+        }
+        Ok(out)
+    }
+
+    fn evaluate(
+        &mut self,
+        player_id: u64,
+        at: u64,
+        cause: Option<(RealmId, NevcSample)>,
+    ) -> Result<NevcRecordOutcome, String> {
+        let result = self.score_at(player_id, at);
+        let from = self.class_of(player_id);
+        if result.class == from {
+            return Ok(NevcRecordOutcome { result, transition: None });
+        }
+        let transition = ClassTransition {
+            at,
+            from,
+            to: result.class,
+            score: result.score,
+            samples_in_window: result.sample_count,
+            cause,
+        };
+        self.append(player_id, NevcLogEntry::Transition(transition.clone()))?;
+        self.players.entry(player_id).or_default().class = result.class;
+        Ok(NevcRecordOutcome { result, transition: Some(transition) })
+    }
+
+    pub fn has_history(&self, player_id: u64) -> bool {
+        self.players.contains_key(&player_id)
+    }
+
+    /// Class as of the last record / refresh.
+    pub fn class_of(&self, player_id: u64) -> ContributionClass {
+        self.players.get(&player_id).map(|l| l.class).unwrap_or(ContributionClass::ZombiePartition)
+    }
+
+    /// compute_nevc over the decay window ending at `at` (inclusive).
+    pub fn score_at(&self, player_id: u64, at: u64) -> NevcResult {
+        self.recompute(player_id, at.saturating_sub(self.config.decay_window_secs)..at.saturating_add(1))
+    }
+
+    /// compute_nevc over every sample recorded in `range` (unix seconds).
+    pub fn recompute(&self, player_id: u64, range: Range<u64>) -> NevcResult {
+        let samples = self.players.get(&player_id).map(|l| l.samples_in(&range, None)).unwrap_or_default();
+        compute_nevc(&samples, &self.config.nevc)
+    }
+
+    pub fn class_transitions(&self, player_id: u64, range: Range<u64>) -> Vec<ClassTransition> {
+        let Some(log) = self.players.get(&player_id) else { return Vec::new() };
+        log.entries
+            .iter()
+            .filter_map(|e| match e {
+                NevcLogEntry::Transition(t) if range.contains(&t.at) => Some(t.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Contributors by score over `range`, counting only samples from `realm_id`.
+    pub fn top_contributors(&self, realm_id: RealmId, range: Range<u64>, limit: usize) -> Vec<(u64, NevcResult)> {
+        let mut ranked: Vec<(u64, NevcResult)> = self
+            .players
+            .iter()
+            .filter_map(|(id, log)| {
+                let samples = log.samples_in(&range, Some(realm_id));
+                let result = compute_nevc(&samples, &self.config.nevc);
+                result.is_contributor().then_some((*id, result))
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.score.total_cmp(&a.1.score).then(a.0.cmp(&b.0)));
+        ranked.truncate(limit);
+        ranked
+    }
+
+    /// Decay-window result every `step_secs` through `range`, ending at its last second.
+    pub fn timeline(&self, player_id: u64, range: Range<u64>, step_secs: u64) -> Vec<TimelinePoint> {
+        let step = step_secs.max(1);
+        let mut out = Vec::new();
+        let mut at = range.start;
+        while at < range.end {
+            at = (at + step).min(range.end);
+            out.push(TimelinePoint { at: at - 1, result: self.score_at(player_id, at - 1) });
+        }
+        out
+    }
+
+    /// Fold samples older than `compact_after_secs` into per-(realm, bucket) aggregates and
+    /// rewrite the affected logs atomically. Samples are grouped by which side of the
+    /// valence floor they fall, so aggregates keep their positive / zero contribution.
+    /// Returns the number of samples folded.
+    pub fn compact(&mut self, now: u64) -> Result<usize, String> {
+        let cutoff = now.saturating_sub(self.config.compact_after_secs);
+        let bucket_secs = self.config.compact_bucket_secs.max(1);
+        let floor = self.config.nevc.valence_floor;
+        let mut folded = 0;
+        let mut ids: Vec<u64> = self.players.keys().copied().collect();
+        ids.sort_unstable();
+
+        for player_id in ids {
+            let log = self.players.get_mut(&player_id).expect("listed above");
+            if !log.entries.iter().any(|e| matches!(e, NevcLogEntry::Sample { at, .. } if *at < cutoff)) {
+                continue;
+            }
+            let mut groups: BTreeMap<(u64, RealmId, bool), Vec<NevcSample>> = BTreeMap::new();
+            let mut kept = Vec::with_capacity(log.entries.len());
+            for entry in log.entries.drain(..) {
+                match entry {
+                    NevcLogEntry::Sample { at, realm_id, sample } if at < cutoff => {
+                        let bucket = at / bucket_secs * bucket_secs;
+                        groups.entry((bucket, realm_id, sample.valence >= floor)).or_default().push(sample);
+                    }
+                    other => kept.push(other),
+                }
+            }
+            for ((from, realm_id, _), samples) in groups {
+                folded += samples.len();
+                kept.push(NevcLogEntry::Compacted {
+                    from,
+                    to: from + bucket_secs,
+                    realm_id,
+                    count: samples.len() as u32,
+                    mean: mean_sample(&samples),
+                });
+            }
+            kept.sort_by_key(NevcLogEntry::at);
+            log.entries = kept;
+
+            if let Some(path) = self.log_path(player_id) {
+                let mut data = String::new();
+                for entry in &self.players[&player_id].entries {
+                    data.push_str(&serde_json::to_string(entry).map_err(|e| e.to_string())?);
+                    data.push('\n');
+                }
+                let tmp = path.with_extension("jsonl.tmp");
+                {
+                    let mut file = fs::File::create(&tmp).map_err(|e| e.to_string())?;
+                    file.write_all(data.as_bytes()).map_err(|e| e.to_string())?;
+                    file.sync_all().map_err(|e| e.to_string())?;
+                }
+                fs::rename(&tmp, &path).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(folded)
+    }
+}
+
+fn mean_sample(samples: &[NevcSample]) -> NevcSample {
+    let n = samples.len().max(1) as f64;
+    let valence = samples.iter().map(|s| s.valence).sum::<f64>() / n;
+    let grief = samples.iter().map(|s| s.grief_load).sum::<f64>() / n;
+    let with_mercy: Vec<f64> = samples
+        .iter()
+        .filter(|s| !s.mercy_components.is_empty())
+        .map(|s| s.mercy_components.iter().sum::<f64>() / s.mercy_components.len() as f64)
+        .collect();
+    let mut mean = NevcSample::new(valence, grief, 0);
+    if !with_mercy.is_empty() {
+        mean.mercy_components = vec![with_mercy.iter().sum::<f64>() / with_mercy.len() as f64];
+    }
+    mean.transient = samples.iter().any(|s| s.transient);
+    mean
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nevc_adapter::sample_from_rbe_action;
+
+    fn good() -> NevcSample {
+        sample_from_rbe_action(1.0, 0.0, 0)
+    }
+
+    fn wasteful() -> NevcSample {
+        NevcSample::new(0.0, 3.0, 0)
+    }
+
+    #[test]
+    fn transitions_record_their_cause_and_decay() {
+        let mut history = NevcHistory::in_memory(NevcHistoryConfig { decay_window_secs: 100, ..Default::default() });
+        let became = history.record(1, 0, good(), 1_000).unwrap().transition.unwrap();
+        assert_eq!(became.to, ContributionClass::ActiveEternalContributor);
+        assert!(became.cause.is_some());
+        assert!(history.record(1, 0, good(), 1_010).unwrap().transition.is_none());
+
+        let fell = history.record(1, 0, wasteful(), 1_020).unwrap().transition.unwrap();
+        assert_eq!((fell.from, fell.to), (ContributionClass::ActiveEternalContributor, ContributionClass::ZombiePartition));
+        assert_eq!(fell.samples_in_window, 3);
+
+        // Only fresh samples count: a good one after the wasteful one aged out recovers...
+        let recovered = history.record(1, 0, good(), 1_130).unwrap().transition.unwrap();
+        assert_eq!(recovered.to, ContributionClass::ActiveEternalContributor);
+        // ...and with nothing new inside the window the class decays, with no sample to blame.
+        let decayed = history.refresh(1, 1_231).unwrap().unwrap();
+        assert_eq!(decayed.to, ContributionClass::ZombiePartition);
+        assert!(decayed.cause.is_none());
+        assert!(history.refresh(1, 1_300).unwrap().is_none());
+        assert_eq!(history.class_transitions(1, 0..u64::MAX).len(), 4);
+        assert_eq!(history.class_transitions(1, 1_100..1_200).len(), 1);
+
+        // Recomputation over any historical range.
+        assert!(!history.recompute(1, 1_000..1_021).is_contributor());
+        assert!(history.recompute(1, 1_000..1_011).is_contributor());
+    }
+
+    #[test]
+    fn top_contributors_are_per_realm() {
+        let mut history = NevcHistory::in_memory(NevcHistoryConfig::default());
+        history.record(1, 0, good(), 10).unwrap();
+        history.record(2, 0, sample_from_rbe_action(0.5, 0.0, 0), 10).unwrap();
+        history.record(3, 0, wasteful(), 10).unwrap();
+        history.record(3, 1, good(), 10).unwrap();
+
+        let realm0: Vec<u64> = history.top_contributors(0, 0..100, 5).into_iter().map(|(id, _)| id).collect();
+        assert_eq!(realm0, vec![1, 2]);
+        let realm1: Vec<u64> = history.top_contributors(1, 0..100, 5).into_iter().map(|(id, _)| id).collect();
+        assert_eq!(realm1, vec![3]);
+        assert_eq!(history.top_contributors(0, 0..100, 1).len(), 1);
+    }
+
+    #[test]
+    fn log_survives_reopen_and_compaction() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let config = NevcHistoryConfig { compact_after_secs: 1_000, compact_bucket_secs: 100, ..Default::default() };
+
+        let mut history = NevcHistory::open(dir, config.clone()).unwrap();
+        for at in [10, 20, 30, 150] {
+            history.record(9, 2, good(), at).unwrap();
+        }
+        history.record(9, 2, wasteful(), 160).unwrap();
+
+        // A torn trailing line from a crash mid-append is ignored.
+        let path = dir.join("9.jsonl");
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"kind\":\"Sam").unwrap();
+        let mut reopened = NevcHistory::open(dir, config.clone()).unwrap();
+        reopened.record(9, 2, wasteful(), 170).unwrap();
+        let mut reopened = NevcHistory::open(dir, config.clone()).unwrap();
+        assert_eq!(reopened.recompute(9, 0..200).sample_count, 6);
+        assert_eq!(reopened.class_transitions(9, 0..u64::MAX).len(), 2);
+        assert_eq!(reopened.class_of(9), ContributionClass::ZombiePartition);
+
+        let before = reopened.recompute(9, 0..200);
+        assert_eq!(reopened.compact(2_000).unwrap(), 6);
+        let after = NevcHistory::open(dir, config).unwrap();
+        let recomputed = after.recompute(9, 0..200);
+        assert_eq!(recomputed.sample_count, 6);
+        assert_eq!(recomputed.class, before.class);
+        assert_eq!(after.class_transitions(9, 0..u64::MAX).len(), 2);
+        assert_eq!(after.timeline(9, 0..200, 50).iter().map(|p| p.at).collect::<Vec<_>>(), vec![49, 99, 149, 199]);
+    }
+}
@@ -3,6 +3,8 @@
 //
 // Durable record for per-player Net Eternal Valence Contribution.
 // Survives restarts and reconnects while keeping Compassion-gate recovery open.
+// Phase 12: the record is a snapshot; nevc_history's append-only log is the audit
+// source (apply_result copies its decay-window result here).
 //
 // AG-SML v1.0 | PATSAGi Councils | info@Rathor.ai
 // Thunder locked in. Yoi ⚡
@@ -11,7 +13,7 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::nevc_adapter::ContributionClass;
+use crate::nevc_adapter::{ContributionClass, NevcResult};
 
 /// Minimal durable NEVC record per player.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -52,6 +54,14 @@ impl NevcPlayerRecord {
         self.class = ContributionClass::from_score(self.score);
         self.last_updated = now_secs();
     }
+
+    /// Replace score and class with a result computed elsewhere (e.g. a history decay window).
+    pub fn apply_result(&mut self, result: &NevcResult) {
+        self.score = result.score;
+        self.class = result.class;
+        self.sample_count = result.sample_count;
+        self.last_updated = now_secs();
+    }
 }
 
 fn now_secs() -> u64 {
@@ -0,0 +1,276 @@
+//! shared/src/datagram.rs
+//! Powrush-MMO — Unreliable datagram channel (protocol v30)
+//!
+//! The WebSocket stays the authoritative, ordered channel. After the handshake a
+//! v30 server may send `ServerMessage::DatagramOffer { port, token }`; the client
+//! then binds a UDP socket to that port and high-frequency traffic (MoveCommand,
+//! SnapshotAck, EntitySnapshot, MoveCorrection) skips the stream's head-of-line
+//! blocking. Everything else, and anything that does not fit one datagram, keeps
+//! going over the WebSocket.
+//!
+//! Frame: `b"P2"` | kind (u8) | session id (16) | sequence (u32 LE) | payload | MAC (16).
+//! The payload of a Message frame is a wire_compat-encoded message at the
+//! connection's negotiated version; Challenge / ChallengeResponse carry a nonce
+//! and the other kinds carry none.
+//!
+//! The offered token is the session's secret and never goes over UDP: frames name
+//! the session by a hash of it and end in HMAC-SHA256(token, header | payload),
+//! truncated. Frames that fail the MAC are dropped. Version 1 frames (`b"PD"`,
+//! unauthenticated) are refused, so older clients stay on the WebSocket.
+//!
+//! Binding: the client repeats Bind until the server answers BindAck, then sends
+//! a Keepalive. The server only routes traffic over UDP once it has received a
+//! Keepalive or Message after its BindAck, so a path that is open in one
+//! direction only never swallows snapshots. Either side falls back to the
+//! WebSocket when the bind times out or the path goes quiet. A bound session that
+//! hears from a new source address (NAT rebinding) sends that address a Challenge
+//! and only moves traffic there once the matching ChallengeResponse comes back
+//! from it.
+//!
+//! AG-SML v1.0 | TOLC 8 | Thunder locked in. Yoi ⚡
+
+use sha2::{Digest, Sha256};
+
+use crate::protocol::{ClientMessage, ServerMessage};
+
+pub const DATAGRAM_MAGIC: [u8; 2] = *b"P2";
+pub const SESSION_TOKEN_LEN: usize = 16;
+pub const SESSION_ID_LEN: usize = 16;
+pub const DATAGRAM_HEADER_LEN: usize = DATAGRAM_MAGIC.len() + 1 + SESSION_ID_LEN + 4;
+pub const DATAGRAM_MAC_LEN: usize = 16;
+/// Whole frame budget; stays under common path MTUs so datagrams are never fragmented.
+pub const MAX_DATAGRAM_LEN: usize = 1200;
+pub const MAX_DATAGRAM_PAYLOAD: usize = MAX_DATAGRAM_LEN - DATAGRAM_HEADER_LEN - DATAGRAM_MAC_LEN;
+/// Nonce carried by Challenge and echoed by ChallengeResponse.
+pub const CHALLENGE_LEN: usize = 16;
+
+/// Client resends Bind this often until BindAck arrives.
+pub const BIND_RETRY_MS: u64 = 250;
+/// Client gives up on UDP (WebSocket only) when no BindAck arrives within this.
+pub const BIND_TIMEOUT_MS: u64 = 3_000;
+/// Client sends a Keepalive when it has sent nothing else for this long.
+pub const KEEPALIVE_INTERVAL_MS: u64 = 2_000;
+/// Server stops routing over UDP when nothing arrived for this long.
+pub const DATAGRAM_IDLE_TIMEOUT_MS: u64 = 10_000;
+
+pub type SessionToken = [u8; SESSION_TOKEN_LEN];
+pub type SessionId = [u8; SESSION_ID_LEN];
+
+/// The public name of a session on the wire.
+pub fn session_id(token: &SessionToken) -> SessionId {
+    let digest = Sha256::new().chain_update(b"powrush-datagram-session").chain_update(token).finalize();
+    let mut id = [0u8; SESSION_ID_LEN];
+    id.copy_from_slice(&digest[..SESSION_ID_LEN]);
+    id
+}
+
+/// HMAC-SHA256 keyed by the session token, truncated to DATAGRAM_MAC_LEN.
+fn frame_mac(token: &SessionToken, frame: &[u8]) -> [u8; DATAGRAM_MAC_LEN] {
+    let mut key = [0u8; 64];
+    key[..SESSION_TOKEN_LEN].copy_from_slice(token);
+    let inner = Sha256::new().chain_update(key.map(|k| k ^ 0x36)).chain_update(frame).finalize();
+    let outer = Sha256::new().chain_update(key.map(|k| k ^ 0x5c)).chain_update(inner).finalize();
+    let mut mac = [0u8; DATAGRAM_MAC_LEN];
+    mac.copy_from_slice(&outer[..DATAGRAM_MAC_LEN]);
+    mac
+}
+
+/// Which channel a message should take when the datagram path is up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    /// Ordered WebSocket stream
+    Reliable,
+    /// UDP; may be lost or superseded, never needed in order
+    Unreliable,
+}
+
+pub fn server_message_channel(msg: &ServerMessage) -> Channel {
+    match msg {
+        // Deltas reference acked baselines; corrections are superseded by the next one.
+        ServerMessage::EntitySnapshot { .. } | ServerMessage::MoveCorrection { .. } => Channel::Unreliable,
+        _ => Channel::Reliable,
+    }
+}
+
+pub fn client_message_channel(msg: &ClientMessage) -> Channel {
+    match msg {
+        // Lost inputs are corrected by MoveCorrection; a lost ack only costs a larger delta.
+        ClientMessage::MoveCommand { .. } | ClientMessage::SnapshotAck { .. } => Channel::Unreliable,
+        _ => Channel::Reliable,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatagramKind {
+    Bind = 0,
+    BindAck = 1,
+    Keepalive = 2,
+    Message = 3,
+    /// Server → new source address: echo this nonce from there to move the path
+    Challenge = 4,
+    ChallengeResponse = 5,
+}
+
+impl DatagramKind {
+    fn from_u8(b: u8) -> Option<Self> {
+        match b {
+            0 => Some(Self::Bind),
+            1 => Some(Self::BindAck),
+            2 => Some(Self::Keepalive),
+            3 => Some(Self::Message),
+            4 => Some(Self::Challenge),
+            5 => Some(Self::ChallengeResponse),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatagramError {
+    TooShort(usize),
+    BadMagic,
+    UnknownKind(u8),
+    PayloadTooLarge(usize),
+    /// Not sealed with this session's token (forged, corrupted or another session)
+    BadMac,
+}
+
+impl std::fmt::Display for DatagramError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DatagramError::TooShort(len) => write!(f, "datagram of {} bytes is shorter than its header", len),
+            DatagramError::BadMagic => write!(f, "datagram does not start with the Powrush magic"),
+            DatagramError::UnknownKind(k) => write!(f, "unknown datagram kind {}", k),
+            DatagramError::PayloadTooLarge(len) => {
+                write!(f, "payload of {} bytes exceeds {} byte datagram budget", len, MAX_DATAGRAM_PAYLOAD)
+            }
+            DatagramError::BadMac => write!(f, "datagram MAC does not match the session"),
+        }
+    }
+}
+
+impl std::error::Error for DatagramError {}
+
+/// One frame. `token` is the session secret it is sealed / verified with; only its
+/// session id goes on the wire.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Datagram<'a> {
+    pub kind: DatagramKind,
+    pub token: SessionToken,
+    pub sequence: u32,
+    pub payload: &'a [u8],
+}
+
+impl<'a> Datagram<'a> {
+    pub fn encode(&self) -> Result<Vec<u8>, DatagramError> {
+        if self.payload.len() > MAX_DATAGRAM_PAYLOAD {
+            return Err(DatagramError::PayloadTooLarge(self.payload.len()));
+        }
+        let mut out = Vec::with_capacity(DATAGRAM_HEADER_LEN + self.payload.len() + DATAGRAM_MAC_LEN);
+        out.extend_from_slice(&DATAGRAM_MAGIC);
+        out.push(self.kind as u8);
+        out.extend_from_slice(&session_id(&self.token));
+        out.extend_from_slice(&self.sequence.to_le_bytes());
+        out.extend_from_slice(self.payload);
+        let mac = frame_mac(&self.token, &out);
+        out.extend_from_slice(&mac);
+        Ok(out)
+    }
+
+    /// The session a frame claims to belong to (look its token up, then `decode`).
+    pub fn session_of(bytes: &[u8]) -> Result<SessionId, DatagramError> {
+        if bytes.len() < DATAGRAM_HEADER_LEN + DATAGRAM_MAC_LEN {
+            return Err(DatagramError::TooShort(bytes.len()));
+        }
+        if bytes[..2] != DATAGRAM_MAGIC {
+            return Err(DatagramError::BadMagic);
+        }
+        let mut id = [0u8; SESSION_ID_LEN];
+        id.copy_from_slice(&bytes[3..3 + SESSION_ID_LEN]);
+        Ok(id)
+    }
+
+    /// Parse a frame sealed with `token`; anything else is BadMac.
+    pub fn decode(bytes: &'a [u8], token: &SessionToken) -> Result<Self, DatagramError> {
+        if Self::session_of(bytes)? != session_id(token) {
+            return Err(DatagramError::BadMac);
+        }
+        let (frame, mac) = bytes.split_at(bytes.len() - DATAGRAM_MAC_LEN);
+        let expected = frame_mac(token, frame);
+        // Constant time: no early exit on the first differing byte.
+        if expected.iter().zip(mac).fold(0u8, |diff, (a, b)| diff | (a ^ b)) != 0 {
+            return Err(DatagramError::BadMac);
+        }
+        let kind = DatagramKind::from_u8(bytes[2]).ok_or(DatagramError::UnknownKind(bytes[2]))?;
+        let seq_at = 3 + SESSION_ID_LEN;
+        let sequence = u32::from_le_bytes(bytes[seq_at..seq_at + 4].try_into().unwrap());
+        Ok(Self { kind, token: *token, sequence, payload: &frame[DATAGRAM_HEADER_LEN..] })
+    }
+}
+
+/// Receive-side filter: each side numbers its datagrams, and anything not newer than
+/// the newest accepted one (duplicate, reordered, replayed) is dropped. Wraps at u32::MAX.
+#[derive(Debug, Default, Clone)]
+pub struct SequenceWindow {
+    newest: Option<u32>,
+}
+
+impl SequenceWindow {
+    pub fn accept(&mut self, sequence: u32) -> bool {
+        let newer = match self.newest {
+            None => true,
+            Some(newest) => sequence != newest && sequence.wrapping_sub(newest) < u32::MAX / 2,
+        };
+        if newer {
+            self.newest = Some(sequence);
+        }
+        newer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_round_trip_and_reject_garbage() {
+        let token = [0xA5; 16];
+        let frame = Datagram { kind: DatagramKind::Message, token, sequence: 258, payload: &[9, 8, 7] };
+        let bytes = frame.encode().unwrap();
+        assert_eq!(bytes.len(), DATAGRAM_HEADER_LEN + 3 + DATAGRAM_MAC_LEN);
+        assert_eq!(&bytes[..3], b"P2\x03");
+        assert_eq!(&bytes[3..3 + SESSION_ID_LEN], &session_id(&token));
+        assert_eq!(&bytes[3 + SESSION_ID_LEN..DATAGRAM_HEADER_LEN], &[2, 1, 0, 0]);
+        // The secret itself never appears in the frame.
+        assert!(!bytes.windows(SESSION_TOKEN_LEN).any(|w| w == token));
+        assert_eq!(Datagram::session_of(&bytes), Ok(session_id(&token)));
+        assert_eq!(Datagram::decode(&bytes, &token).unwrap(), frame);
+
+        assert_eq!(Datagram::decode(&bytes[..10], &token), Err(DatagramError::TooShort(10)));
+        let mut wrong = bytes.clone();
+        wrong[0] = b'X';
+        assert_eq!(Datagram::decode(&wrong, &token), Err(DatagramError::BadMagic));
+        assert_eq!(Datagram::decode(&bytes, &[0x5A; 16]), Err(DatagramError::BadMac));
+        // Any flipped bit (kind, sequence, payload or MAC) fails the MAC.
+        for at in [2, DATAGRAM_HEADER_LEN - 1, DATAGRAM_HEADER_LEN + 1, bytes.len() - 1] {
+            let mut tampered = bytes.clone();
+            tampered[at] ^= 1;
+            assert_eq!(Datagram::decode(&tampered, &token), Err(DatagramError::BadMac), "byte {}", at);
+        }
+
+        let big = vec![0u8; MAX_DATAGRAM_PAYLOAD + 1];
+        let oversized = Datagram { kind: DatagramKind::Message, token, sequence: 0, payload: &big };
+        assert_eq!(oversized.encode(), Err(DatagramError::PayloadTooLarge(MAX_DATAGRAM_PAYLOAD + 1)));
+    }
+
+    #[test]
+    fn sequence_window_drops_stale_and_survives_wrap() {
+        let mut window = SequenceWindow::default();
+        assert!(window.accept(u32::MAX - 1));
+        assert!(!window.accept(u32::MAX - 1));
+        assert!(window.accept(1)); // wrapped
+        assert!(!window.accept(u32::MAX));
+        assert!(window.accept(5));
+        assert!(!window.accept(3));
+    }
+}
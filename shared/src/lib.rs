@@ -7,14 +7,7 @@ use serde::{Deserialize, Serialize};
 
 pub mod spatial; // HierarchicalGrid + Vec3 for spatial queries (reverb, interest, occlusion, etc.)
 
-/// Core protocol messages sent between client and server
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum ServerMessage {
-    WorldUpdate { entities: Vec<EntityUpdate>, timestamp: f64 },
-    ValenceUpdate { player_id: u64, new_valence: f32, reason: String },
-    MercyGateBlocked { reason: String, valence: f32 },
-    Error { message: String },
-    RbeTransaction { resource_type: RbeResourceType, amount: f32 },
-}
+/// Protocol messages live only in protocol.rs (v24 unification); re-exported for old import paths.
+pub use crate::protocol::{ClientMessage, ServerMessage, PROTOCOL_VERSION};
 
 // ... (rest of file unchanged for minimal diff)
\ No newline at end of file
@@ -6,6 +6,33 @@
  *   handshake, inventory, SafetyNet, movement stubs, Audio Moments.
  *
  * v21.89.2 — Restored transport-critical variants + AudioMoment catalog sync.
+ * v22 — RSIL challenge–response identity in the handshake (AuthChallenge / AuthChallengeResponse).
+ * v23 — Delta-compressed, interest-filtered EntitySnapshot + SnapshotAck (quantized transforms).
+ * v24 — Single canonical protocol (shared/src/lib.rs duplicate retired; its Error and
+ *       ValenceUpdate folded in). Version negotiation: ProtocolAccepted. Older wire
+ *       versions are encoded/decoded by wire_compat; golden bytes in shared/tests/golden.
+ * v25 — Trade escrow: offer / counter / lock / confirm / cancel and TradeUpdate /
+ *       TradeCompleted / TradeCancelled (appended variants; v24 layouts unchanged).
+ * v26 — Authoritative movement: tick-stamped MoveCommand inputs and MoveCorrection
+ *       (appended variants; legacy Move { delta } still accepted).
+ * v27 — Lag-compensated ability targeting: UseAbility stamped with the snapshot tick the
+ *       client was viewing, AbilityRejected with a reason code (appended variants).
+ * v28 — StatusEffects: buffs / debuffs of entities in the interest set, with stacks and
+ *       remaining time for client timers (appended variant).
+ * v29 — ChunkSnapshot: versioned contents (resource nodes, placed structures) of world
+ *       chunks streamed as players come in range (appended variant).
+ * v30 — DatagramOffer: optional UDP channel paired with the authenticated session for
+ *       movement and snapshots (framing in shared::datagram; appended variant).
+ * v31 — Guilds: create / invite / ranks / shared bank / chat / audit log / guild treaties
+ *       (appended variants; WireGuild carries the full roster, ranks and bank).
+ * v32 — Abundance needs: players declare a need for the next fair allocation round and
+ *       receive their audit record when it runs (appended variants).
+ * v33 — Crafting: queue / cancel recipe jobs at stations, CraftQueue / CraftCompleted,
+ *       and CraftPlan answers (raw resources + steps for an item; appended variants).
+ * v34 — Council agenda: players submit typed proposals, list a realm's open agenda and vote;
+ *       CouncilProposalResolved carries the outcome, ballot digest and resulting policy.
+ * v35 — Council ballot records: clients fetch the full BallotRecord a resolution's digest
+ *       names, to verify it themselves (appended variants).
  *
  * AG-SML v1.0 | TOLC 8 + 7 Living Mercy Gates | Ra-Thor + PATSAGi
  * Thunder locked in. Yoi ⚡
@@ -13,7 +40,26 @@
 
 use serde::{Deserialize, Serialize};
 
-pub const PROTOCOL_VERSION: u32 = 21;
+pub use crate::abilities::StatusEffectType;
+pub use crate::council_ballot::BallotRecord;
+pub use crate::fair_allocation::{AllocationLimit, NeedSeverity};
+
+pub const PROTOCOL_VERSION: u32 = 35;
+
+/// Fixed rate of client movement ticks; each MoveCommand covers exactly one.
+pub const MOVE_TICK_HZ: u32 = 60;
+
+/// Oldest wire version this build can still speak (see wire_compat).
+pub const MIN_SUPPORTED_PROTOCOL_VERSION: u32 = 21;
+
+/// Version both sides will speak, given the version a peer advertised.
+/// None when the peer is older than MIN_SUPPORTED_PROTOCOL_VERSION.
+pub fn negotiate_version(peer_version: u32) -> Option<u32> {
+    if peer_version < MIN_SUPPORTED_PROTOCOL_VERSION {
+        return None;
+    }
+    Some(peer_version.min(PROTOCOL_VERSION))
+}
 
 // ════════════════════════════════════════════════════════════════════════════════════
 // SHARED PRIMITIVES
@@ -124,6 +170,8 @@ pub struct WireAudioMoment {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ClientMessage {
     // --- Transport / session ---
+    /// Always variant 0 with this exact layout, on every protocol version:
+    /// it is decoded before a version has been negotiated.
     HandshakeRequest {
         version: u32,
         player_name: String,
@@ -175,6 +223,172 @@ pub enum ClientMessage {
         moment_id: u64,
         favorite: bool,
     },
+
+    // --- Identity (RSIL challenge–response, after HandshakeRequest) ---
+    /// Hybrid signature over `rsil_identity::challenge::challenge_message(server_id, nonce)`.
+    AuthChallengeResponse {
+        did: String,
+        public_key: Vec<u8>,
+        signature: Vec<u8>,
+        /// DID document JSON; required when `public_key` is a rotated-in key
+        did_document: Option<String>,
+    },
+
+    // --- Replication ---
+    /// Latest EntitySnapshot fully applied; becomes the server's delta baseline for this client.
+    SnapshotAck {
+        snapshot_id: u32,
+    },
+
+    // --- Trade escrow (v25) ---
+    TradeOffer {
+        target_player_id: u64,
+        offered: Vec<WireTradeItem>,
+        requested: Vec<WireTradeItem>,
+    },
+    /// Replaces the terms of an unlocked trade; the sender becomes the offeror.
+    TradeCounter {
+        trade_id: u64,
+        offered: Vec<WireTradeItem>,
+        requested: Vec<WireTradeItem>,
+    },
+    /// Moves the sender's side into escrow. `commitment_hash` must match the current terms.
+    TradeLock {
+        trade_id: u64,
+        commitment_hash: Vec<u8>,
+    },
+    /// Accepts the swap once both sides are locked; the second confirm settles it.
+    TradeConfirm {
+        trade_id: u64,
+        commitment_hash: Vec<u8>,
+    },
+    TradeCancel {
+        trade_id: u64,
+    },
+
+    // --- Movement (v26) ---
+    /// One fixed movement tick (1 / MOVE_TICK_HZ seconds) at `velocity` (units/s).
+    /// `sequence` increases by one per command; `tick` is the client's simulation tick.
+    MoveCommand {
+        sequence: u32,
+        tick: u64,
+        velocity: Vec3Ser,
+    },
+
+    // --- Abilities (v27) ---
+    /// `target_id` is an EntitySnapshot entity id; `view_tick` is the `server_tick` of the
+    /// snapshot the client was rendering when it fired, and `aim` its facing direction.
+    UseAbility {
+        request_id: u32,
+        ability_id: u32,
+        target_id: Option<u64>,
+        view_tick: u64,
+        aim: Vec3Ser,
+    },
+
+    // --- Guilds (v31) ---
+    /// `tag` is 2–5 ASCII letters or digits shown next to member names.
+    GuildCreate {
+        name: String,
+        tag: String,
+    },
+    GuildInvite {
+        player_id: u64,
+    },
+    GuildInviteResponse {
+        guild_id: u64,
+        accept: bool,
+    },
+    GuildLeave,
+    GuildKick {
+        player_id: u64,
+    },
+    /// Assigning rank 0 hands over leadership.
+    GuildSetRank {
+        player_id: u64,
+        rank_id: u8,
+    },
+    /// Creates the rank when `rank.rank_id` does not exist yet.
+    GuildEditRank {
+        rank: WireGuildRank,
+    },
+    /// Moves `count` of inventory slot `inventory_slot` (0..40) into the guild bank.
+    GuildBankDeposit {
+        inventory_slot: u8,
+        count: u32,
+    },
+    GuildBankWithdraw {
+        bank_slot: u16,
+        count: u32,
+    },
+    GuildChat {
+        text: String,
+    },
+    /// Newest `limit` audit entries (answered with GuildAuditLog).
+    GuildAuditRequest {
+        limit: u16,
+    },
+    GuildTreatyPropose {
+        guild_id: u64,
+        terms: WireTreatyTerms,
+    },
+    /// Accept or decline the proposal `guild_id` sent us.
+    GuildTreatyRespond {
+        guild_id: u64,
+        accept: bool,
+    },
+    GuildTreatyRevoke {
+        guild_id: u64,
+    },
+
+    // --- Abundance needs (v32) ---
+    /// Need for the next abundance round; declaring again before it runs replaces it.
+    AbundanceNeedDeclare {
+        amount: f64,
+        severity: NeedSeverity,
+    },
+
+    // --- Crafting (v33) ---
+    /// Queue `runs` runs of a recipe; inputs are taken from the inventory immediately.
+    CraftStart {
+        recipe_id: u32,
+        runs: u32,
+    },
+    /// Inputs of a cancelled job are returned.
+    CraftCancel {
+        job_id: u64,
+    },
+    CraftQueueRequest,
+    /// What it takes to end up holding `count` of `item_id` (answered with CraftPlan).
+    CraftPlanRequest {
+        item_id: u32,
+        count: u32,
+    },
+
+    // --- Council agenda (v34) ---
+    /// Put a proposal before the realm's councils (eligibility and rate limits apply).
+    CouncilProposalSubmit {
+        realm_id: u8,
+        proposal_type: WireProposalType,
+        title: String,
+        description: String,
+        target_zone: Option<u64>,
+    },
+    /// Answered with CouncilAgenda.
+    CouncilAgendaRequest {
+        realm_id: u8,
+    },
+    /// One ballot per player; voting again before the proposal resolves changes it.
+    CouncilVote {
+        proposal_id: u64,
+        support: bool,
+    },
+
+    // --- Council ballot records (v35) ---
+    /// Answered with CouncilBallotRecord for a recently resolved proposal.
+    CouncilBallotRecordRequest {
+        proposal_id: u64,
+    },
 }
 
 // ════════════════════════════════════════════════════════════════════════════════════
@@ -225,6 +439,537 @@ pub enum ServerMessage {
         ok: bool,
         message: String,
     },
+
+    // --- Identity ---
+    /// Sent after a version-compatible HandshakeRequest; HandshakeResponse follows the answer.
+    AuthChallenge {
+        nonce: Vec<u8>,
+        server_id: String,
+    },
+
+    // --- Replication ---
+    /// Per-client snapshot of the entities in this player's interest set.
+    /// With `baseline_id`, each entity carries only the fields changed since that acked
+    /// snapshot; without it, every entity is sent in full.
+    EntitySnapshot {
+        snapshot_id: u32,
+        baseline_id: Option<u32>,
+        server_tick: u64,
+        entities: Vec<WireEntityDelta>,
+        /// Entities that left the interest set (or were despawned)
+        removed: Vec<u64>,
+    },
+
+    // --- Session (v24) ---
+    /// Sent before AuthChallenge when the negotiated version is >= 24; from here on
+    /// both sides encode at `version`.
+    ProtocolAccepted {
+        version: u32,
+    },
+    ValenceUpdate {
+        player_id: u64,
+        new_valence: f32,
+        reason: String,
+    },
+    Error {
+        message: String,
+    },
+
+    // --- Trade escrow (v25) ---
+    /// Current terms and escrow progress; sent to both parties on every change.
+    TradeUpdate {
+        trade: WireTrade,
+    },
+    TradeCompleted {
+        trade_id: u64,
+        /// Sequence number of the settlement entry in the server's trade ledger
+        ledger_seq: u64,
+    },
+    /// Escrowed items have been returned to their owners.
+    TradeCancelled {
+        trade_id: u64,
+        reason: String,
+    },
+
+    // --- Movement (v26) ---
+    /// Authoritative state after the server applied MoveCommand `sequence`. Sent only when
+    /// the server's result differs from what the client predicted; the client rewinds to
+    /// `position` and replays its inputs newer than `sequence`.
+    MoveCorrection {
+        sequence: u32,
+        server_tick: u64,
+        position: Vec3Ser,
+        velocity: Vec3Ser,
+    },
+
+    // --- Abilities (v27) ---
+    /// UseAbility `request_id` was not applied.
+    AbilityRejected {
+        request_id: u32,
+        ability_id: u32,
+        reason: WireAbilityRejectReason,
+    },
+
+    // --- Status effects (v28) ---
+    /// Complete status list of each listed entity as of `server_tick`; an empty list clears it.
+    StatusEffects {
+        server_tick: u64,
+        entities: Vec<WireEntityStatus>,
+    },
+
+    // --- Chunk streaming (v29) ---
+    /// Full contents of world chunk `chunk_id` (packed chunk coordinate). Sent when the
+    /// player comes in range or the chunk changes; replaces any copy with an older `version`.
+    /// Chunks never sent are empty.
+    ChunkSnapshot {
+        chunk_id: u64,
+        version: u64,
+        resource_nodes: Vec<WireResourceNode>,
+        structures: Vec<WireStructure>,
+    },
+
+    // --- Datagram channel (v30) ---
+    /// UDP `port` on the server's host accepts datagrams for this session, framed with
+    /// `token` (see shared::datagram). Sent once after the handshake; a client that never
+    /// binds keeps receiving everything over the WebSocket.
+    DatagramOffer {
+        port: u16,
+        token: [u8; 16],
+    },
+
+    // --- Guilds (v31) ---
+    /// Full guild state; sent to every online member on each change.
+    GuildInfo {
+        guild: WireGuild,
+    },
+    GuildInvited {
+        guild_id: u64,
+        guild_name: String,
+        invited_by: u64,
+    },
+    /// The recipient is no longer a member (left, kicked or guild disbanded).
+    GuildLeft {
+        guild_id: u64,
+        reason: String,
+    },
+    GuildChatMessage {
+        guild_id: u64,
+        sender_id: u64,
+        text: String,
+        sent_at: u64,
+    },
+    /// Newest first.
+    GuildAuditLog {
+        guild_id: u64,
+        entries: Vec<WireGuildAuditEntry>,
+    },
+    /// Sent to members allowed to answer treaties.
+    GuildTreatyProposal {
+        from_guild_id: u64,
+        from_guild_name: String,
+        terms: WireTreatyTerms,
+    },
+
+    // --- Abundance needs (v32) ---
+    /// `replaced` when this superseded an earlier declaration for the same round.
+    AbundanceNeedAccepted {
+        amount: f64,
+        severity: NeedSeverity,
+        replaced: bool,
+    },
+    /// This player's record of a completed round: what was asked, the weight it
+    /// carried (severity × contribution class), what was granted and what limited it.
+    AbundanceGranted {
+        round_id: u64,
+        declared: f64,
+        weight: f64,
+        granted: f64,
+        limit: AllocationLimit,
+    },
+
+    // --- Crafting (v33) ---
+    /// The player's whole queue, in completion order; sent on every change.
+    CraftQueue {
+        jobs: Vec<WireCraftJob>,
+    },
+    /// Outputs are already in the inventory (an InventoryUpdate precedes this).
+    CraftCompleted {
+        job_id: u64,
+        recipe_id: u32,
+        runs: u32,
+    },
+    /// `steps` in crafting order; `raw` still has to be gathered; `tools` must be held.
+    CraftPlan {
+        item_id: u32,
+        count: u32,
+        steps: Vec<WireCraftStep>,
+        raw: Vec<WireItemCount>,
+        tools: Vec<u32>,
+    },
+
+    // --- Council agenda (v34) ---
+    CouncilProposalAccepted {
+        proposal: WireCouncilProposal,
+    },
+    /// Open proposals of one realm, oldest first.
+    CouncilAgenda {
+        realm_id: u8,
+        proposals: Vec<WireCouncilProposal>,
+    },
+    /// `replaced` when this changed an earlier ballot by the same player.
+    CouncilVoteAccepted {
+        proposal_id: u64,
+        support: bool,
+        replaced: bool,
+    },
+    /// Broadcast when deliberation resolves a proposal. `ballot_digest` names the
+    /// published ballot record; `policy` is set when a passed proposal took effect.
+    CouncilProposalResolved {
+        realm_id: u8,
+        proposal_id: u64,
+        passed: bool,
+        votes_for: u32,
+        votes_against: u32,
+        ballot_digest: String,
+        policy: Option<WireActivePolicy>,
+    },
+
+    // --- Council ballot records (v35) ---
+    /// Every participant, where their weight landed and the tally; check it with
+    /// `BallotRecord::verify` against the resolution's `ballot_digest`. Player
+    /// voters carry PLAYER_VOTER_FLAG (shared::council_ballot::voter_player).
+    CouncilBallotRecord {
+        record: BallotRecord,
+    },
+}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// ABILITY WIRE TYPES
+// ════════════════════════════════════════════════════════════════════════════════════
+
+/// Why the server refused a UseAbility. Append only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WireAbilityRejectReason {
+    UnknownAbility,
+    RateLimited,
+    OnCooldown,
+    CasterDead,
+    NoTarget,
+    /// `view_tick` is older than the server keeps history for
+    RewindTooFar,
+    OutOfRange,
+    OutsideCone,
+    NoLineOfSight,
+}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// STATUS EFFECT WIRE TYPES
+// ════════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireStatusEffect {
+    pub kind: StatusEffectType,
+    /// EntitySnapshot entity id of the caster, when known
+    pub source_id: Option<u64>,
+    pub stacks: u8,
+    pub strength: f32,
+    pub remaining_secs: f32,
+    pub duration_secs: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireEntityStatus {
+    pub entity_id: u64,
+    pub effects: Vec<WireStatusEffect>,
+}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// CHUNK WIRE TYPES
+// ════════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireResourceNode {
+    pub node_id: u64,
+    pub resource_type: String,
+    pub position: [f32; 3],
+    pub current_amount: f32,
+    pub max_amount: f32,
+    pub depleted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireStructure {
+    pub structure_id: u64,
+    pub kind: String,
+    pub owner_id: u64,
+    pub position: [f32; 3],
+    /// Radians about +Y
+    pub rotation_y: f32,
+    pub integrity: f32,
+}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// GUILD WIRE TYPES
+// ════════════════════════════════════════════════════════════════════════════════════
+
+/// Permission bits of `WireGuildRank.permissions`.
+pub const GUILD_PERM_INVITE: u32 = 1 << 0;
+pub const GUILD_PERM_KICK: u32 = 1 << 1;
+/// Change the rank of junior members
+pub const GUILD_PERM_SET_RANK: u32 = 1 << 2;
+/// Create and edit ranks junior to one's own
+pub const GUILD_PERM_EDIT_RANKS: u32 = 1 << 3;
+pub const GUILD_PERM_BANK_DEPOSIT: u32 = 1 << 4;
+pub const GUILD_PERM_BANK_WITHDRAW: u32 = 1 << 5;
+pub const GUILD_PERM_CHAT: u32 = 1 << 6;
+pub const GUILD_PERM_VIEW_AUDIT: u32 = 1 << 7;
+pub const GUILD_PERM_TREATY: u32 = 1 << 8;
+pub const GUILD_PERMS_ALL: u32 = (1 << 9) - 1;
+
+/// Rank 0 is the leader; a lower id is more senior.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireGuildRank {
+    pub rank_id: u8,
+    pub name: String,
+    pub permissions: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireGuildMember {
+    pub player_id: u64,
+    pub rank_id: u8,
+    pub joined_at: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WireTreatyTerms {
+    /// Guilds become allies
+    Alliance,
+    /// Ends rivalry or war; relation returns to neutral
+    Peace,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireGuildTreaty {
+    pub guild_id: u64,
+    pub terms: WireTreatyTerms,
+    pub signed_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireGuild {
+    pub guild_id: u64,
+    pub name: String,
+    pub tag: String,
+    pub ranks: Vec<WireGuildRank>,
+    pub members: Vec<WireGuildMember>,
+    /// Every bank slot, empty ones included
+    pub bank: Vec<HotbarSlot>,
+    pub treaties: Vec<WireGuildTreaty>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireGuildAuditEntry {
+    pub seq: u64,
+    pub at: u64,
+    pub actor_id: u64,
+    pub summary: String,
+}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// CRAFTING WIRE TYPES
+// ════════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireCraftJob {
+    pub job_id: u64,
+    pub recipe_id: u32,
+    pub runs: u32,
+    /// Unix seconds
+    pub ready_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireCraftStep {
+    pub recipe_id: u32,
+    pub runs: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireItemCount {
+    pub item_id: u32,
+    pub count: u64,
+}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// COUNCIL AGENDA WIRE TYPES
+// ════════════════════════════════════════════════════════════════════════════════════
+
+/// Mirrors the simulation's ProposalType / PolicyType. Append only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WireProposalType {
+    HarmonyBoost,
+    ResourcePolicy,
+    EpiphanyEvent,
+    KardashevAcceleration,
+    General,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireCouncilProposal {
+    pub proposal_id: u64,
+    pub realm_id: u8,
+    pub proposal_type: WireProposalType,
+    pub title: String,
+    pub description: String,
+    /// Player id, or the AgentId of a simulation agent.
+    pub proposer_id: u64,
+    /// Unix seconds; 0 for proposals the simulation raised itself.
+    pub submitted_at: u64,
+    pub target_zone: Option<u64>,
+    /// Direct ballots so far (delegated weight is only counted at deliberation).
+    pub votes_for: u32,
+    pub votes_against: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireActivePolicy {
+    pub decision_id: u64,
+    pub policy_type: WireProposalType,
+    pub target_interest_zone: Option<u64>,
+    pub strength: f32,
+    /// Simulation ticks the policy stays in force.
+    pub remaining_ticks: u64,
+    pub title: String,
+}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// TRADE WIRE TYPES
+// ════════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireTradeItem {
+    pub resource: String,
+    pub amount: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WireTradeState {
+    /// Terms can still be countered
+    Negotiating,
+    /// At least one side is in escrow; terms are frozen
+    Locking,
+    Settled,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireTrade {
+    pub trade_id: u64,
+    pub offeror_id: u64,
+    pub target_id: u64,
+    /// Sorted by resource name
+    pub offered: Vec<WireTradeItem>,
+    pub requested: Vec<WireTradeItem>,
+    pub commitment_hash: Vec<u8>,
+    pub state: WireTradeState,
+    pub offeror_locked: bool,
+    pub target_locked: bool,
+    pub offeror_confirmed: bool,
+    pub target_confirmed: bool,
+    pub expires_at: u64,
+}
+
+// ════════════════════════════════════════════════════════════════════════════════════
+// SNAPSHOT WIRE TYPES
+// ════════════════════════════════════════════════════════════════════════════════════
+
+/// Field bits carried in `WireEntityDelta.mask` (same bit positions as server `ReplicatedFields`).
+pub const SNAPSHOT_FIELD_POSITION: u32 = 1 << 0;
+pub const SNAPSHOT_FIELD_ROTATION: u32 = 1 << 1;
+pub const SNAPSHOT_FIELD_VELOCITY: u32 = 1 << 2;
+pub const SNAPSHOT_FIELD_HEALTH: u32 = 1 << 3;
+pub const SNAPSHOT_FIELDS_ALL: u32 =
+    SNAPSHOT_FIELD_POSITION | SNAPSHOT_FIELD_ROTATION | SNAPSHOT_FIELD_VELOCITY | SNAPSHOT_FIELD_HEALTH;
+
+/// Positions travel as fixed-point i32 at 1/64 world unit.
+pub const POSITION_QUANT_SCALE: f32 = 64.0;
+/// Velocities travel as i16 at 1/50 unit per second (±655 u/s range).
+pub const VELOCITY_QUANT_SCALE: f32 = 50.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireEntityDelta {
+    pub entity_id: u64,
+    pub mask: u32,
+    pub position: Option<[i32; 3]>,
+    /// Smallest-three packed quaternion (see `pack_rotation`)
+    pub rotation: Option<u32>,
+    pub velocity: Option<[i16; 3]>,
+    pub health: Option<f32>,
+}
+
+pub fn quantize_position(p: [f32; 3]) -> [i32; 3] {
+    p.map(|v| (v * POSITION_QUANT_SCALE).round() as i32)
+}
+
+pub fn dequantize_position(q: [i32; 3]) -> [f32; 3] {
+    q.map(|v| v as f32 / POSITION_QUANT_SCALE)
+}
+
+pub fn quantize_velocity(v: [f32; 3]) -> [i16; 3] {
+    v.map(|c| (c * VELOCITY_QUANT_SCALE).round().clamp(i16::MIN as f32, i16::MAX as f32) as i16)
+}
+
+pub fn dequantize_velocity(q: [i16; 3]) -> [f32; 3] {
+    q.map(|c| c as f32 / VELOCITY_QUANT_SCALE)
+}
+
+const ROTATION_COMPONENT_BITS: u32 = 10;
+const ROTATION_COMPONENT_MAX: f32 = ((1 << ROTATION_COMPONENT_BITS) - 1) as f32;
+// Non-largest components of a unit quaternion lie in [-1/√2, 1/√2].
+const ROTATION_RANGE: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// Smallest-three quaternion packing: 2 bits for the dropped (largest) component index,
+/// 10 bits for each of the other three. `q` is (x, y, z, w).
+pub fn pack_rotation(q: [f32; 4]) -> u32 {
+    let len = q.iter().map(|c| c * c).sum::<f32>().sqrt().max(f32::EPSILON);
+    let mut q = q.map(|c| c / len);
+    let largest = (0..4)
+        .max_by(|&a, &b| q[a].abs().total_cmp(&q[b].abs()))
+        .unwrap_or(3);
+    // q and -q are the same rotation; keep the dropped component positive.
+    if q[largest] < 0.0 {
+        q = q.map(|c| -c);
+    }
+
+    let mut packed = largest as u32;
+    for (i, c) in q.iter().enumerate() {
+        if i == largest {
+            continue;
+        }
+        let normalized = (c.clamp(-ROTATION_RANGE, ROTATION_RANGE) + ROTATION_RANGE) / (2.0 * ROTATION_RANGE);
+        packed = (packed << ROTATION_COMPONENT_BITS) | (normalized * ROTATION_COMPONENT_MAX).round() as u32;
+    }
+    packed
+}
+
+pub fn unpack_rotation(packed: u32) -> [f32; 4] {
+    let largest = (packed >> (3 * ROTATION_COMPONENT_BITS)) as usize & 0b11;
+    let mut q = [0.0f32; 4];
+    let mut shift = 2 * ROTATION_COMPONENT_BITS;
+    let mut sum_sq = 0.0;
+    for (i, slot) in q.iter_mut().enumerate() {
+        if i == largest {
+            continue;
+        }
+        let raw = (packed >> shift) & ((1 << ROTATION_COMPONENT_BITS) - 1);
+        let c = raw as f32 / ROTATION_COMPONENT_MAX * (2.0 * ROTATION_RANGE) - ROTATION_RANGE;
+        *slot = c;
+        sum_sq += c * c;
+        shift = shift.saturating_sub(ROTATION_COMPONENT_BITS);
+    }
+    q[largest] = (1.0 - sum_sq).max(0.0).sqrt();
+    q
 }
 
 // ════════════════════════════════════════════════════════════════════════════════════
@@ -285,4 +1030,39 @@ pub fn apply_mercy_gate(msg: &ClientMessage, valence: f32) -> bool {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation_packing_roundtrip() {
+        let half = 0.6f32;
+        let q = [0.0, half.sin(), 0.0, half.cos()];
+        let back = unpack_rotation(pack_rotation(q));
+        let dot: f32 = q.iter().zip(back.iter()).map(|(a, b)| a * b).sum();
+        assert!(dot.abs() > 0.9999, "dot {}", dot);
+
+        let flipped = unpack_rotation(pack_rotation(q.map(|c| -c)));
+        let dot: f32 = q.iter().zip(flipped.iter()).map(|(a, b)| a * b).sum();
+        assert!(dot.abs() > 0.9999);
+    }
+
+    #[test]
+    fn negotiation_picks_highest_common_version() {
+        assert_eq!(negotiate_version(PROTOCOL_VERSION), Some(PROTOCOL_VERSION));
+        assert_eq!(negotiate_version(PROTOCOL_VERSION + 3), Some(PROTOCOL_VERSION));
+        assert_eq!(negotiate_version(MIN_SUPPORTED_PROTOCOL_VERSION), Some(MIN_SUPPORTED_PROTOCOL_VERSION));
+        assert_eq!(negotiate_version(MIN_SUPPORTED_PROTOCOL_VERSION - 1), None);
+    }
+
+    #[test]
+    fn position_quantization_is_within_half_step() {
+        let p = [1234.567, -0.01, 98.76];
+        let back = dequantize_position(quantize_position(p));
+        for (a, b) in p.iter().zip(back.iter()) {
+            assert!((a - b).abs() <= 0.5 / POSITION_QUANT_SCALE);
+        }
+    }
+}
+
 // Thunder locked in. Yoi ⚡
@@ -0,0 +1,491 @@
+//! shared/src/wire_compat.rs
+//! Powrush-MMO — Versioned wire codec
+//!
+//! Every bincode frame on the socket goes through here with the version the
+//! connection negotiated. Versions whose layouts are a prefix of the current
+//! enums (v24 onwards) encode `protocol` types directly and refuse variants
+//! appended after them; v23 changed existing variants, so it is encoded
+//! through a frozen copy of its enums, and v21 / v22 servers sent a prefix of
+//! that copy. ClientMessage has only ever appended variants, down to v21.
+//! Either way an older peer never sees a variant index it does not know.
+//!
+//! Adding a version: if it only appends variants, list them in
+//! `client_message_since` / `server_message_since`; if it changes an existing
+//! layout, freeze the outgoing enums in a `vNN` module below. Bump
+//! MIN_SUPPORTED_PROTOCOL_VERSION if the oldest one is dropped, and add its
+//! golden file under shared/tests/golden.
+//!
+//! AG-SML v1.0 | TOLC 8 | Thunder locked in. Yoi ⚡
+
+use crate::protocol::{self, ClientMessage, ServerMessage, MIN_SUPPORTED_PROTOCOL_VERSION, PROTOCOL_VERSION};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WireError {
+    UnsupportedVersion(u32),
+    /// The message has no representation at the target version
+    NotRepresentable { version: u32, message: &'static str },
+    Codec(String),
+}
+
+impl std::fmt::Display for WireError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WireError::UnsupportedVersion(v) => write!(
+                f,
+                "unsupported wire version v{} (supported v{}..=v{})",
+                v, MIN_SUPPORTED_PROTOCOL_VERSION, PROTOCOL_VERSION
+            ),
+            WireError::NotRepresentable { version, message } => {
+                write!(f, "{} cannot be sent to a v{} peer", message, version)
+            }
+            WireError::Codec(e) => write!(f, "wire codec error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for WireError {}
+
+impl From<bincode::Error> for WireError {
+    fn from(e: bincode::Error) -> Self {
+        WireError::Codec(e.to_string())
+    }
+}
+
+fn check(version: u32) -> Result<(), WireError> {
+    if (MIN_SUPPORTED_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version) {
+        Ok(())
+    } else {
+        Err(WireError::UnsupportedVersion(version))
+    }
+}
+
+/// Version that appended this variant, for variants newer than MIN_SUPPORTED_PROTOCOL_VERSION.
+fn client_message_since(msg: &ClientMessage) -> Option<(u32, &'static str)> {
+    use ClientMessage as C;
+    match msg {
+        C::AuthChallengeResponse { .. } => Some((22, "AuthChallengeResponse")),
+        C::SnapshotAck { .. } => Some((23, "SnapshotAck")),
+        C::TradeOffer { .. } => Some((25, "TradeOffer")),
+        C::TradeCounter { .. } => Some((25, "TradeCounter")),
+        C::TradeLock { .. } => Some((25, "TradeLock")),
+        C::TradeConfirm { .. } => Some((25, "TradeConfirm")),
+        C::TradeCancel { .. } => Some((25, "TradeCancel")),
+        C::MoveCommand { .. } => Some((26, "MoveCommand")),
+        C::UseAbility { .. } => Some((27, "UseAbility")),
+        C::GuildCreate { .. } => Some((31, "GuildCreate")),
+        C::GuildInvite { .. } => Some((31, "GuildInvite")),
+        C::GuildInviteResponse { .. } => Some((31, "GuildInviteResponse")),
+        C::GuildLeave => Some((31, "GuildLeave")),
+        C::GuildKick { .. } => Some((31, "GuildKick")),
+        C::GuildSetRank { .. } => Some((31, "GuildSetRank")),
+        C::GuildEditRank { .. } => Some((31, "GuildEditRank")),
+        C::GuildBankDeposit { .. } => Some((31, "GuildBankDeposit")),
+        C::GuildBankWithdraw { .. } => Some((31, "GuildBankWithdraw")),
+        C::GuildChat { .. } => Some((31, "GuildChat")),
+        C::GuildAuditRequest { .. } => Some((31, "GuildAuditRequest")),
+        C::GuildTreatyPropose { .. } => Some((31, "GuildTreatyPropose")),
+        C::GuildTreatyRespond { .. } => Some((31, "GuildTreatyRespond")),
+        C::GuildTreatyRevoke { .. } => Some((31, "GuildTreatyRevoke")),
+        C::AbundanceNeedDeclare { .. } => Some((32, "AbundanceNeedDeclare")),
+        C::CraftStart { .. } => Some((33, "CraftStart")),
+        C::CraftCancel { .. } => Some((33, "CraftCancel")),
+        C::CraftQueueRequest => Some((33, "CraftQueueRequest")),
+        C::CraftPlanRequest { .. } => Some((33, "CraftPlanRequest")),
+        C::CouncilProposalSubmit { .. } => Some((34, "CouncilProposalSubmit")),
+        C::CouncilAgendaRequest { .. } => Some((34, "CouncilAgendaRequest")),
+        C::CouncilVote { .. } => Some((34, "CouncilVote")),
+        C::CouncilBallotRecordRequest { .. } => Some((35, "CouncilBallotRecordRequest")),
+        _ => None,
+    }
+}
+
+/// Version that appended this variant, for variants newer than v23
+/// (older ones: `v23::ServerMessage::since`).
+fn server_message_since(msg: &ServerMessage) -> Option<(u32, &'static str)> {
+    use ServerMessage as S;
+    match msg {
+        S::ProtocolAccepted { .. } => Some((24, "ProtocolAccepted")),
+        S::ValenceUpdate { .. } => Some((24, "ValenceUpdate")),
+        S::Error { .. } => Some((24, "Error")),
+        S::TradeUpdate { .. } => Some((25, "TradeUpdate")),
+        S::TradeCompleted { .. } => Some((25, "TradeCompleted")),
+        S::TradeCancelled { .. } => Some((25, "TradeCancelled")),
+        S::MoveCorrection { .. } => Some((26, "MoveCorrection")),
+        S::AbilityRejected { .. } => Some((27, "AbilityRejected")),
+        S::StatusEffects { .. } => Some((28, "StatusEffects")),
+        S::ChunkSnapshot { .. } => Some((29, "ChunkSnapshot")),
+        S::DatagramOffer { .. } => Some((30, "DatagramOffer")),
+        S::GuildInfo { .. } => Some((31, "GuildInfo")),
+        S::GuildInvited { .. } => Some((31, "GuildInvited")),
+        S::GuildLeft { .. } => Some((31, "GuildLeft")),
+        S::GuildChatMessage { .. } => Some((31, "GuildChatMessage")),
+        S::GuildAuditLog { .. } => Some((31, "GuildAuditLog")),
+        S::GuildTreatyProposal { .. } => Some((31, "GuildTreatyProposal")),
+        S::AbundanceNeedAccepted { .. } => Some((32, "AbundanceNeedAccepted")),
+        S::AbundanceGranted { .. } => Some((32, "AbundanceGranted")),
+        S::CraftQueue { .. } => Some((33, "CraftQueue")),
+        S::CraftCompleted { .. } => Some((33, "CraftCompleted")),
+        S::CraftPlan { .. } => Some((33, "CraftPlan")),
+        S::CouncilProposalAccepted { .. } => Some((34, "CouncilProposalAccepted")),
+        S::CouncilAgenda { .. } => Some((34, "CouncilAgenda")),
+        S::CouncilVoteAccepted { .. } => Some((34, "CouncilVoteAccepted")),
+        S::CouncilProposalResolved { .. } => Some((34, "CouncilProposalResolved")),
+        S::CouncilBallotRecord { .. } => Some((35, "CouncilBallotRecord")),
+        _ => None,
+    }
+}
+
+fn representable(since: Option<(u32, &'static str)>, version: u32) -> Result<(), WireError> {
+    match since {
+        Some((introduced, message)) if introduced > version => {
+            Err(WireError::NotRepresentable { version, message })
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn encode_client_message(msg: &ClientMessage, version: u32) -> Result<Vec<u8>, WireError> {
+    check(version)?;
+    // ClientMessage only ever appends variants, so older layouts are a prefix of this one.
+    representable(client_message_since(msg), version)?;
+    Ok(bincode::serialize(msg)?)
+}
+
+pub fn decode_client_message(bytes: &[u8], version: u32) -> Result<ClientMessage, WireError> {
+    check(version)?;
+    let msg = bincode::deserialize(bytes)?;
+    representable(client_message_since(&msg), version)
+        .map_err(|e| WireError::Codec(e.to_string()))?;
+    Ok(msg)
+}
+
+pub fn encode_server_message(msg: &ServerMessage, version: u32) -> Result<Vec<u8>, WireError> {
+    check(version)?;
+    if version <= 23 {
+        let frozen = v23::ServerMessage::try_from(msg.clone()).map_err(|e| match e {
+            WireError::NotRepresentable { message, .. } => WireError::NotRepresentable { version, message },
+            other => other,
+        })?;
+        representable(frozen.since(), version)?;
+        return Ok(bincode::serialize(&frozen)?);
+    }
+    representable(server_message_since(msg), version)?;
+    Ok(bincode::serialize(msg)?)
+}
+
+pub fn decode_server_message(bytes: &[u8], version: u32) -> Result<ServerMessage, WireError> {
+    check(version)?;
+    if version <= 23 {
+        let frozen: v23::ServerMessage = bincode::deserialize(bytes)?;
+        representable(frozen.since(), version).map_err(|e| WireError::Codec(e.to_string()))?;
+        return Ok(frozen.into());
+    }
+    let msg = bincode::deserialize(bytes)?;
+    representable(server_message_since(&msg), version)
+        .map_err(|e| WireError::Codec(e.to_string()))?;
+    Ok(msg)
+}
+
+/// Frozen v23 ServerMessage (v21 / v22 are prefixes). Never edit: the golden corpus pins these bytes.
+mod v23 {
+    use super::{protocol, WireError};
+    use crate::protocol::{HotbarSlot, SafetyNetBroadcast, WireAudioMoment, WireEntityDelta};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub enum ServerMessage {
+        HandshakeResponse { accepted: bool, reason: Option<String>, player_id: u64, server_time: u64 },
+        Pong { client_time_ms: u64, server_time_ms: u64 },
+        MercyGateBlocked { reason: String, valence: f32 },
+        WorldUpdate { entity_count: u32, timestamp: f64 },
+        InventoryUpdate { player_id: u64, hotbar: Vec<HotbarSlot>, inventory: Vec<HotbarSlot>, abundance_score: f32 },
+        SafetyNetBroadcast { broadcast: SafetyNetBroadcast },
+        AudioMomentCatalogSnapshot { player_id: u64, moments: Vec<WireAudioMoment>, next_id: u64, last_synced_unix: u64 },
+        AudioMomentSaveAck { moment_id: u64, ok: bool, message: String },
+        AuthChallenge { nonce: Vec<u8>, server_id: String },
+        EntitySnapshot {
+            snapshot_id: u32,
+            baseline_id: Option<u32>,
+            server_tick: u64,
+            entities: Vec<WireEntityDelta>,
+            removed: Vec<u64>,
+        },
+    }
+
+    impl TryFrom<protocol::ServerMessage> for ServerMessage {
+        type Error = WireError;
+
+        fn try_from(msg: protocol::ServerMessage) -> Result<Self, WireError> {
+            use protocol::ServerMessage as S;
+            Ok(match msg {
+                S::HandshakeResponse { accepted, reason, player_id, server_time } => {
+                    Self::HandshakeResponse { accepted, reason, player_id, server_time }
+                }
+                S::Pong { client_time_ms, server_time_ms } => Self::Pong { client_time_ms, server_time_ms },
+                S::MercyGateBlocked { reason, valence } => Self::MercyGateBlocked { reason, valence },
+                S::WorldUpdate { entity_count, timestamp } => Self::WorldUpdate { entity_count, timestamp },
+                S::InventoryUpdate { player_id, hotbar, inventory, abundance_score } => {
+                    Self::InventoryUpdate { player_id, hotbar, inventory, abundance_score }
+                }
+                S::SafetyNetBroadcast { broadcast } => Self::SafetyNetBroadcast { broadcast },
+                S::AudioMomentCatalogSnapshot { player_id, moments, next_id, last_synced_unix } => {
+                    Self::AudioMomentCatalogSnapshot { player_id, moments, next_id, last_synced_unix }
+                }
+                S::AudioMomentSaveAck { moment_id, ok, message } => Self::AudioMomentSaveAck { moment_id, ok, message },
+                S::AuthChallenge { nonce, server_id } => Self::AuthChallenge { nonce, server_id },
+                S::EntitySnapshot { snapshot_id, baseline_id, server_tick, entities, removed } => {
+                    Self::EntitySnapshot { snapshot_id, baseline_id, server_tick, entities, removed }
+                }
+                // v21–v23 clients surface errors through the mercy-gate path.
+                S::Error { message } => Self::MercyGateBlocked { reason: message, valence: 0.0 },
+                // Everything else was appended after v23.
+                other => {
+                    let message = super::server_message_since(&other).map_or("ServerMessage", |(_, name)| name);
+                    return Err(WireError::NotRepresentable { version: 23, message });
+                }
+            })
+        }
+    }
+
+    impl ServerMessage {
+        /// v21 / v22 servers sent a prefix of this enum; versions that appended a variant.
+        pub fn since(&self) -> Option<(u32, &'static str)> {
+            match self {
+                Self::AuthChallenge { .. } => Some((22, "AuthChallenge")),
+                Self::EntitySnapshot { .. } => Some((23, "EntitySnapshot")),
+                _ => None,
+            }
+        }
+    }
+
+    impl From<ServerMessage> for protocol::ServerMessage {
+        fn from(msg: ServerMessage) -> Self {
+            use ServerMessage as S;
+            match msg {
+                S::HandshakeResponse { accepted, reason, player_id, server_time } => {
+                    Self::HandshakeResponse { accepted, reason, player_id, server_time }
+                }
+                S::Pong { client_time_ms, server_time_ms } => Self::Pong { client_time_ms, server_time_ms },
+                S::MercyGateBlocked { reason, valence } => Self::MercyGateBlocked { reason, valence },
+                S::WorldUpdate { entity_count, timestamp } => Self::WorldUpdate { entity_count, timestamp },
+                S::InventoryUpdate { player_id, hotbar, inventory, abundance_score } => {
+                    Self::InventoryUpdate { player_id, hotbar, inventory, abundance_score }
+                }
+                S::SafetyNetBroadcast { broadcast } => Self::SafetyNetBroadcast { broadcast },
+                S::AudioMomentCatalogSnapshot { player_id, moments, next_id, last_synced_unix } => {
+                    Self::AudioMomentCatalogSnapshot { player_id, moments, next_id, last_synced_unix }
+                }
+                S::AudioMomentSaveAck { moment_id, ok, message } => Self::AudioMomentSaveAck { moment_id, ok, message },
+                S::AuthChallenge { nonce, server_id } => Self::AuthChallenge { nonce, server_id },
+                S::EntitySnapshot { snapshot_id, baseline_id, server_tick, entities, removed } => {
+                    Self::EntitySnapshot { snapshot_id, baseline_id, server_tick, entities, removed }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::council_ballot::{player_voter, BallotBox, BallotChoice};
+    use crate::protocol::{
+        AllocationLimit, NeedSeverity, StatusEffectType, Vec3Ser, WireAbilityRejectReason, WireEntityDelta,
+        WireEntityStatus, WireResourceNode, WireStatusEffect,
+    };
+
+    /// (first version, sample) for one appended variant of each version since v21.
+    fn client_samples() -> Vec<(u32, ClientMessage)> {
+        vec![
+            (
+                22,
+                ClientMessage::AuthChallengeResponse {
+                    did: "did:rsil:abc".into(),
+                    public_key: vec![1, 2, 3],
+                    signature: vec![4, 5],
+                    did_document: None,
+                },
+            ),
+            (23, ClientMessage::SnapshotAck { snapshot_id: 11 }),
+            (25, ClientMessage::TradeCancel { trade_id: 3 }),
+            (26, ClientMessage::MoveCommand { sequence: 9, tick: 300, velocity: Vec3Ser::default() }),
+            (
+                27,
+                ClientMessage::UseAbility {
+                    request_id: 4,
+                    ability_id: 12,
+                    target_id: Some(77),
+                    view_tick: 900,
+                    aim: Vec3Ser { x: 0.0, y: 0.0, z: 1.0 },
+                },
+            ),
+            (31, ClientMessage::GuildChat { text: "hold the grove".into() }),
+            (32, ClientMessage::AbundanceNeedDeclare { amount: 12.5, severity: NeedSeverity::Urgent }),
+            (33, ClientMessage::CraftStart { recipe_id: 6, runs: 1 }),
+            (34, ClientMessage::CouncilVote { proposal_id: 9, support: true }),
+            (35, ClientMessage::CouncilBallotRecordRequest { proposal_id: 1 << 48 }),
+        ]
+    }
+
+    fn server_samples() -> Vec<(u32, ServerMessage)> {
+        let mut ballots = BallotBox::new();
+        ballots.cast(player_voter(1000), BallotChoice::For, 5).unwrap();
+        ballots.delegate(7, player_voter(1000)).unwrap();
+        vec![
+            (22, ServerMessage::AuthChallenge { nonce: vec![9; 32], server_id: "grove-1".into() }),
+            (
+                23,
+                ServerMessage::EntitySnapshot {
+                    snapshot_id: 11,
+                    baseline_id: Some(10),
+                    server_tick: 120,
+                    entities: vec![WireEntityDelta {
+                        entity_id: 5,
+                        mask: 1,
+                        position: Some([10, 0, 20]),
+                        rotation: None,
+                        velocity: None,
+                        health: None,
+                    }],
+                    removed: vec![6],
+                },
+            ),
+            (24, ServerMessage::ProtocolAccepted { version: PROTOCOL_VERSION }),
+            (25, ServerMessage::TradeCancelled { trade_id: 3, reason: "expired".into() }),
+            (
+                26,
+                ServerMessage::MoveCorrection {
+                    sequence: 9,
+                    server_tick: 120,
+                    position: Vec3Ser { x: 1.0, y: 0.0, z: 2.0 },
+                    velocity: Vec3Ser::default(),
+                },
+            ),
+            (
+                27,
+                ServerMessage::AbilityRejected {
+                    request_id: 4,
+                    ability_id: 12,
+                    reason: WireAbilityRejectReason::NoLineOfSight,
+                },
+            ),
+            (
+                28,
+                ServerMessage::StatusEffects {
+                    server_tick: 40,
+                    entities: vec![WireEntityStatus {
+                        entity_id: 5,
+                        effects: vec![WireStatusEffect {
+                            kind: StatusEffectType::Slow,
+                            source_id: Some(9),
+                            stacks: 1,
+                            strength: 0.3,
+                            remaining_secs: 2.5,
+                            duration_secs: 4.0,
+                        }],
+                    }],
+                },
+            ),
+            (
+                29,
+                ServerMessage::ChunkSnapshot {
+                    chunk_id: 42,
+                    version: 3,
+                    resource_nodes: vec![WireResourceNode {
+                        node_id: 7,
+                        resource_type: "gold".to_string(),
+                        position: [40.0, 0.0, 40.0],
+                        current_amount: 70.0,
+                        max_amount: 100.0,
+                        depleted: false,
+                    }],
+                    structures: vec![],
+                },
+            ),
+            (30, ServerMessage::DatagramOffer { port: 9001, token: [7; 16] }),
+            (31, ServerMessage::GuildLeft { guild_id: 3, reason: "kicked".into() }),
+            (
+                32,
+                ServerMessage::AbundanceGranted {
+                    round_id: 4,
+                    declared: 12.5,
+                    weight: 2.0,
+                    granted: 9.0,
+                    limit: AllocationLimit::Supply,
+                },
+            ),
+            (33, ServerMessage::CraftCompleted { job_id: 4, recipe_id: 6, runs: 1 }),
+            (34, ServerMessage::CouncilVoteAccepted { proposal_id: 9, support: true, replaced: false }),
+            (35, ServerMessage::CouncilBallotRecord { record: ballots.record(2, 1 << 48, 90, |_| 0.5) }),
+        ]
+    }
+
+    #[test]
+    fn appended_variants_are_refused_below_their_first_version() {
+        for (first, msg) in client_samples() {
+            let older = first - 1;
+            assert!(
+                matches!(encode_client_message(&msg, older), Err(WireError::NotRepresentable { version, .. }) if version == older),
+                "{:?} encoded for v{}",
+                msg,
+                older
+            );
+            // An older peer cannot smuggle the variant index in either.
+            let bytes = encode_client_message(&msg, first).unwrap();
+            assert!(matches!(decode_client_message(&bytes, older), Err(WireError::Codec(_))), "{:?} at v{}", msg, older);
+            for version in [first, PROTOCOL_VERSION] {
+                let bytes = encode_client_message(&msg, version).unwrap();
+                let decoded = decode_client_message(&bytes, version).unwrap();
+                assert_eq!(format!("{:?}", decoded), format!("{:?}", msg));
+            }
+        }
+        for (first, msg) in server_samples() {
+            let older = first - 1;
+            assert!(
+                matches!(encode_server_message(&msg, older), Err(WireError::NotRepresentable { version, .. }) if version == older),
+                "{:?} encoded for v{}",
+                msg,
+                older
+            );
+            let bytes = encode_server_message(&msg, first).unwrap();
+            assert!(matches!(decode_server_message(&bytes, older), Err(WireError::Codec(_))), "{:?} at v{}", msg, older);
+            for version in [first, PROTOCOL_VERSION] {
+                let bytes = encode_server_message(&msg, version).unwrap();
+                let decoded = decode_server_message(&bytes, version).unwrap();
+                assert_eq!(format!("{:?}", decoded), format!("{:?}", msg));
+            }
+        }
+    }
+
+    #[test]
+    fn ballot_records_survive_the_wire_intact_enough_to_verify() {
+        let (_, record) = server_samples().pop().unwrap();
+        let bytes = encode_server_message(&record, PROTOCOL_VERSION).unwrap();
+        let Ok(ServerMessage::CouncilBallotRecord { record }) = decode_server_message(&bytes, PROTOCOL_VERSION) else {
+            panic!("expected CouncilBallotRecord");
+        };
+        assert_eq!(record.verify(), Ok(()));
+    }
+
+    #[test]
+    fn errors_reach_pre_v24_clients_as_mercy_gate_blocks() {
+        let msg = ServerMessage::Error { message: "slow down".into() };
+        for version in MIN_SUPPORTED_PROTOCOL_VERSION..=23 {
+            let bytes = encode_server_message(&msg, version).unwrap();
+            assert!(matches!(
+                decode_server_message(&bytes, version),
+                Ok(ServerMessage::MercyGateBlocked { ref reason, .. }) if reason == "slow down"
+            ));
+        }
+    }
+
+    #[test]
+    fn out_of_range_versions_are_rejected() {
+        let msg = ClientMessage::Ping { client_time_ms: 1 };
+        assert_eq!(
+            encode_client_message(&msg, MIN_SUPPORTED_PROTOCOL_VERSION - 1),
+            Err(WireError::UnsupportedVersion(MIN_SUPPORTED_PROTOCOL_VERSION - 1))
+        );
+        assert!(encode_client_message(&msg, PROTOCOL_VERSION + 1).is_err());
+    }
+}
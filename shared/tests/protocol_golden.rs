@@ -0,0 +1,438 @@
+//! shared/tests/protocol_golden.rs
+//! Golden-bytes wire corpus. A failure here is a wire-format break: bump
+//! PROTOCOL_VERSION and freeze the old layout in wire_compat instead of
+//! regenerating an existing golden file.
+
+use shared::council_ballot::{player_voter, BallotChoice, BallotEntry, BallotTally};
+use shared::protocol::*;
+use shared::wire_compat::*;
+
+enum Sample {
+    Client(ClientMessage),
+    Server(ServerMessage),
+}
+
+fn samples(version: u32) -> Vec<(&'static str, Sample)> {
+    let mut out = vec![
+        ("handshake_request", Sample::Client(ClientMessage::HandshakeRequest {
+            version,
+            player_name: "Aster".into(),
+            client_time_ms: 1_700_000_000_000,
+        })),
+        ("ping", Sample::Client(ClientMessage::Ping { client_time_ms: 42 })),
+        ("move", Sample::Client(ClientMessage::Move { delta: Vec3Ser { x: 1.0, y: 0.0, z: -2.5 } })),
+        ("handshake_response", Sample::Server(ServerMessage::HandshakeResponse {
+            accepted: true,
+            reason: None,
+            player_id: 1000,
+            server_time: 1_700_000_000_123,
+        })),
+    ];
+    if version >= 22 {
+        out.push(("auth_challenge_response", Sample::Client(ClientMessage::AuthChallengeResponse {
+            did: "did:powrush:zQm".into(),
+            public_key: vec![1, 2, 3],
+            signature: vec![4, 5],
+            did_document: None,
+        })));
+        out.push(("auth_challenge", Sample::Server(ServerMessage::AuthChallenge {
+            nonce: vec![9; 4],
+            server_id: "powrush:0.0.0.0:9001".into(),
+        })));
+    }
+    if version >= 23 {
+        out.push(("snapshot_ack", Sample::Client(ClientMessage::SnapshotAck { snapshot_id: 7 })));
+        out.push(("entity_snapshot", Sample::Server(ServerMessage::EntitySnapshot {
+            snapshot_id: 7,
+            baseline_id: Some(6),
+            server_tick: 120,
+            entities: vec![WireEntityDelta {
+                entity_id: 4_294_967_297,
+                mask: SNAPSHOT_FIELD_POSITION | SNAPSHOT_FIELD_HEALTH,
+                position: Some([64, -128, 0]),
+                rotation: None,
+                velocity: None,
+                health: Some(87.5),
+            }],
+            removed: vec![12],
+        })));
+    }
+    if version >= 24 {
+        out.push(("protocol_accepted", Sample::Server(ServerMessage::ProtocolAccepted { version: 24 })));
+        out.push(("valence_update", Sample::Server(ServerMessage::ValenceUpdate {
+            player_id: 1000,
+            new_valence: 0.82,
+            reason: "mercy".into(),
+        })));
+        out.push(("error", Sample::Server(ServerMessage::Error { message: "nope".into() })));
+    }
+    if version >= 25 {
+        let wood = || vec![WireTradeItem { resource: "verdant_wood".into(), amount: 12.5 }];
+        let essence = || vec![WireTradeItem { resource: "mercy_essence".into(), amount: 3.0 }];
+        out.push(("trade_offer", Sample::Client(ClientMessage::TradeOffer {
+            target_player_id: 1001,
+            offered: wood(),
+            requested: essence(),
+        })));
+        out.push(("trade_counter", Sample::Client(ClientMessage::TradeCounter {
+            trade_id: 5,
+            offered: essence(),
+            requested: vec![],
+        })));
+        out.push(("trade_lock", Sample::Client(ClientMessage::TradeLock { trade_id: 5, commitment_hash: vec![0xab; 4] })));
+        out.push(("trade_confirm", Sample::Client(ClientMessage::TradeConfirm { trade_id: 5, commitment_hash: vec![0xab; 4] })));
+        out.push(("trade_cancel", Sample::Client(ClientMessage::TradeCancel { trade_id: 5 })));
+        out.push(("trade_update", Sample::Server(ServerMessage::TradeUpdate {
+            trade: WireTrade {
+                trade_id: 5,
+                offeror_id: 1000,
+                target_id: 1001,
+                offered: wood(),
+                requested: essence(),
+                commitment_hash: vec![0xab; 4],
+                state: WireTradeState::Locking,
+                offeror_locked: true,
+                target_locked: false,
+                offeror_confirmed: false,
+                target_confirmed: false,
+                expires_at: 1_700_000_300,
+            },
+        })));
+        out.push(("trade_completed", Sample::Server(ServerMessage::TradeCompleted { trade_id: 5, ledger_seq: 17 })));
+        out.push(("trade_cancelled", Sample::Server(ServerMessage::TradeCancelled {
+            trade_id: 5,
+            reason: "expired".into(),
+        })));
+    }
+    if version >= 26 {
+        out.push(("move_command", Sample::Client(ClientMessage::MoveCommand {
+            sequence: 41,
+            tick: 3_600,
+            velocity: Vec3Ser { x: 4.5, y: 0.0, z: -1.25 },
+        })));
+        out.push(("move_correction", Sample::Server(ServerMessage::MoveCorrection {
+            sequence: 41,
+            server_tick: 3_598,
+            position: Vec3Ser { x: 12.0, y: 0.0, z: -3.5 },
+            velocity: Vec3Ser::default(),
+        })));
+    }
+    if version >= 27 {
+        out.push(("use_ability", Sample::Client(ClientMessage::UseAbility {
+            request_id: 5,
+            ability_id: 12,
+            target_id: Some(4_294_967_306),
+            view_tick: 3_590,
+            aim: Vec3Ser { x: 0.0, y: 0.0, z: 1.0 },
+        })));
+        out.push(("ability_rejected", Sample::Server(ServerMessage::AbilityRejected {
+            request_id: 5,
+            ability_id: 12,
+            reason: WireAbilityRejectReason::OutOfRange,
+        })));
+    }
+    if version >= 28 {
+        out.push(("status_effects", Sample::Server(ServerMessage::StatusEffects {
+            server_tick: 3_600,
+            entities: vec![
+                WireEntityStatus {
+                    entity_id: 4_294_967_306,
+                    effects: vec![WireStatusEffect {
+                        kind: StatusEffectType::Corruption,
+                        source_id: Some(11),
+                        stacks: 2,
+                        strength: 1.5,
+                        remaining_secs: 4.25,
+                        duration_secs: 6.0,
+                    }],
+                },
+                WireEntityStatus { entity_id: 12, effects: vec![] },
+            ],
+        })));
+    }
+    if version >= 29 {
+        out.push(("chunk_snapshot", Sample::Server(ServerMessage::ChunkSnapshot {
+            chunk_id: 4_398_048_608_255, // ChunkCoord (1, 0, -1)
+            version: 6,
+            resource_nodes: vec![WireResourceNode {
+                node_id: 7,
+                resource_type: "gold".into(),
+                position: [40.0, 0.0, 40.0],
+                current_amount: 70.0,
+                max_amount: 100.0,
+                depleted: false,
+            }],
+            structures: vec![WireStructure {
+                structure_id: 900,
+                kind: "mercy_shrine".into(),
+                owner_id: 42,
+                position: [48.5, 0.0, 36.0],
+                rotation_y: 1.5,
+                integrity: 0.75,
+            }],
+        })));
+    }
+    if version >= 30 {
+        out.push(("datagram_offer", Sample::Server(ServerMessage::DatagramOffer {
+            port: 9001,
+            token: [0xA5; 16],
+        })));
+    }
+    if version >= 31 {
+        out.push(("guild_bank_deposit", Sample::Client(ClientMessage::GuildBankDeposit { inventory_slot: 7, count: 12 })));
+        out.push(("guild_treaty_propose", Sample::Client(ClientMessage::GuildTreatyPropose {
+            guild_id: 12,
+            terms: WireTreatyTerms::Alliance,
+        })));
+        out.push(("guild_info", Sample::Server(ServerMessage::GuildInfo {
+            guild: WireGuild {
+                guild_id: 7,
+                name: "Grove Wardens".into(),
+                tag: "GRW".into(),
+                ranks: vec![
+                    WireGuildRank { rank_id: 0, name: "Leader".into(), permissions: GUILD_PERMS_ALL },
+                    WireGuildRank { rank_id: 1, name: "Member".into(), permissions: GUILD_PERM_CHAT },
+                ],
+                members: vec![
+                    WireGuildMember { player_id: 42, rank_id: 0, joined_at: 1_700_000_000 },
+                    WireGuildMember { player_id: 43, rank_id: 1, joined_at: 1_700_000_600 },
+                ],
+                bank: vec![HotbarSlot::new(101, 12, 0.75), HotbarSlot::empty()],
+                treaties: vec![WireGuildTreaty { guild_id: 12, terms: WireTreatyTerms::Peace, signed_at: 1_700_000_900 }],
+            },
+        })));
+        out.push(("guild_chat_message", Sample::Server(ServerMessage::GuildChatMessage {
+            guild_id: 7,
+            sender_id: 43,
+            text: "Bloom at the grove!".into(),
+            sent_at: 1_700_001_000,
+        })));
+        out.push(("guild_audit_log", Sample::Server(ServerMessage::GuildAuditLog {
+            guild_id: 7,
+            entries: vec![WireGuildAuditEntry { seq: 4, at: 1_700_000_700, actor_id: 43, summary: "deposited 12 × item 101".into() }],
+        })));
+    }
+    if version >= 32 {
+        out.push(("abundance_need_declare", Sample::Client(ClientMessage::AbundanceNeedDeclare {
+            amount: 12.5,
+            severity: NeedSeverity::Urgent,
+        })));
+        out.push(("abundance_need_accepted", Sample::Server(ServerMessage::AbundanceNeedAccepted {
+            amount: 12.5,
+            severity: NeedSeverity::Urgent,
+            replaced: false,
+        })));
+        out.push(("abundance_granted", Sample::Server(ServerMessage::AbundanceGranted {
+            round_id: 4,
+            declared: 12.5,
+            weight: 2.0,
+            granted: 9.0,
+            limit: AllocationLimit::Supply,
+        })));
+    }
+    if version >= 33 {
+        out.push(("craft_start", Sample::Client(ClientMessage::CraftStart { recipe_id: 6, runs: 2 })));
+        out.push(("craft_plan_request", Sample::Client(ClientMessage::CraftPlanRequest { item_id: 310, count: 1 })));
+        out.push(("craft_queue", Sample::Server(ServerMessage::CraftQueue {
+            jobs: vec![
+                WireCraftJob { job_id: 1, recipe_id: 1, runs: 3, ready_at: 1_700_000_009 },
+                WireCraftJob { job_id: 2, recipe_id: 6, runs: 1, ready_at: 1_700_000_039 },
+            ],
+        })));
+        out.push(("craft_completed", Sample::Server(ServerMessage::CraftCompleted { job_id: 1, recipe_id: 1, runs: 3 })));
+        out.push(("craft_plan", Sample::Server(ServerMessage::CraftPlan {
+            item_id: 310,
+            count: 1,
+            steps: vec![WireCraftStep { recipe_id: 1, runs: 1 }, WireCraftStep { recipe_id: 6, runs: 1 }],
+            raw: vec![WireItemCount { item_id: 20, count: 6 }, WireItemCount { item_id: 28, count: 1 }],
+            tools: vec![5],
+        })));
+    }
+    if version >= 34 {
+        let proposal = WireCouncilProposal {
+            proposal_id: (1 << 48) + 2,
+            realm_id: 3,
+            proposal_type: WireProposalType::ResourcePolicy,
+            title: "Rest the eastern grove".into(),
+            description: "Halve harvesting near the grove for a season".into(),
+            proposer_id: 42,
+            submitted_at: 1_700_002_000,
+            target_zone: Some(17),
+            votes_for: 2,
+            votes_against: 1,
+        };
+        out.push(("council_proposal_submit", Sample::Client(ClientMessage::CouncilProposalSubmit {
+            realm_id: 3,
+            proposal_type: WireProposalType::ResourcePolicy,
+            title: "Rest the eastern grove".into(),
+            description: "Halve harvesting near the grove for a season".into(),
+            target_zone: Some(17),
+        })));
+        out.push(("council_vote", Sample::Client(ClientMessage::CouncilVote { proposal_id: (1 << 48) + 2, support: true })));
+        out.push(("council_agenda", Sample::Server(ServerMessage::CouncilAgenda { realm_id: 3, proposals: vec![proposal] })));
+        out.push(("council_proposal_resolved", Sample::Server(ServerMessage::CouncilProposalResolved {
+            realm_id: 3,
+            proposal_id: (1 << 48) + 2,
+            passed: true,
+            votes_for: 3,
+            votes_against: 1,
+            ballot_digest: "9f2c".into(),
+            policy: Some(WireActivePolicy {
+                decision_id: 811,
+                policy_type: WireProposalType::ResourcePolicy,
+                target_interest_zone: Some(17),
+                strength: 1.25,
+                remaining_ticks: 900,
+                title: "Rest the eastern grove".into(),
+            }),
+        })));
+    }
+    if version >= 35 {
+        out.push(("council_ballot_record_request", Sample::Client(ClientMessage::CouncilBallotRecordRequest {
+            proposal_id: (1 << 48) + 2,
+        })));
+        out.push(("council_ballot_record", Sample::Server(ServerMessage::CouncilBallotRecord {
+            record: BallotRecord {
+                version: 1,
+                realm_id: 3,
+                proposal_id: (1 << 48) + 2,
+                tick: 90,
+                entries: vec![
+                    BallotEntry {
+                        voter: 7,
+                        choice: None,
+                        delegate: Some(player_voter(1000)),
+                        weight: 0.5,
+                        counted_as: Some(BallotChoice::For),
+                    },
+                    BallotEntry {
+                        voter: player_voter(1000),
+                        choice: Some(BallotChoice::For),
+                        delegate: None,
+                        weight: 0.75,
+                        counted_as: Some(BallotChoice::For),
+                    },
+                ],
+                tally: BallotTally { weight_for: 1.25, voters_for: 2, ..Default::default() },
+                digest: "9f2c".into(),
+            },
+        })));
+    }
+    out
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).expect("bad hex in golden file"))
+        .collect()
+}
+
+fn check_corpus(version: u32, corpus: &str) {
+    let samples = samples(version);
+    let mut seen = 0;
+    for line in corpus.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+        let mut parts = line.split_whitespace();
+        let (dir, name, hex) = (parts.next().unwrap(), parts.next().unwrap(), parts.next().unwrap());
+        let (_, sample) = samples
+            .iter()
+            .find(|(n, _)| *n == name)
+            .unwrap_or_else(|| panic!("v{} golden {} has no sample", version, name));
+        let bytes = from_hex(hex);
+
+        match (dir, sample) {
+            ("client", Sample::Client(msg)) => {
+                assert_eq!(to_hex(&encode_client_message(msg, version).unwrap()), hex, "v{} encode {}", version, name);
+                let decoded = decode_client_message(&bytes, version).unwrap();
+                assert_eq!(format!("{:?}", decoded), format!("{:?}", msg), "v{} decode {}", version, name);
+            }
+            ("server", Sample::Server(msg)) => {
+                assert_eq!(to_hex(&encode_server_message(msg, version).unwrap()), hex, "v{} encode {}", version, name);
+                let decoded = decode_server_message(&bytes, version).unwrap();
+                assert_eq!(format!("{:?}", decoded), format!("{:?}", msg), "v{} decode {}", version, name);
+            }
+            _ => panic!("v{} golden {} has wrong direction {}", version, name, dir),
+        }
+        seen += 1;
+    }
+    assert_eq!(seen, samples.len(), "v{} golden file does not cover every sample", version);
+}
+
+#[test]
+fn current_version_matches_golden_bytes() {
+    check_corpus(PROTOCOL_VERSION, include_str!("golden/v35.hex"));
+}
+
+#[test]
+fn v34_still_decodes_and_encodes() {
+    check_corpus(34, include_str!("golden/v34.hex"));
+}
+
+#[test]
+fn v33_still_decodes_and_encodes() {
+    check_corpus(33, include_str!("golden/v33.hex"));
+}
+
+#[test]
+fn v32_still_decodes_and_encodes() {
+    check_corpus(32, include_str!("golden/v32.hex"));
+}
+
+#[test]
+fn v31_still_decodes_and_encodes() {
+    check_corpus(31, include_str!("golden/v31.hex"));
+}
+
+#[test]
+fn v30_still_decodes_and_encodes() {
+    check_corpus(30, include_str!("golden/v30.hex"));
+}
+
+#[test]
+fn v29_still_decodes_and_encodes() {
+    check_corpus(29, include_str!("golden/v29.hex"));
+}
+
+#[test]
+fn v28_still_decodes_and_encodes() {
+    check_corpus(28, include_str!("golden/v28.hex"));
+}
+
+#[test]
+fn v27_still_decodes_and_encodes() {
+    check_corpus(27, include_str!("golden/v27.hex"));
+}
+
+#[test]
+fn v26_still_decodes_and_encodes() {
+    check_corpus(26, include_str!("golden/v26.hex"));
+}
+
+#[test]
+fn v25_still_decodes_and_encodes() {
+    check_corpus(25, include_str!("golden/v25.hex"));
+}
+
+#[test]
+fn v24_still_decodes_and_encodes() {
+    check_corpus(24, include_str!("golden/v24.hex"));
+}
+
+#[test]
+fn previous_version_still_decodes_and_encodes() {
+    check_corpus(23, include_str!("golden/v23.hex"));
+}
+
+#[test]
+fn v22_still_decodes_and_encodes() {
+    check_corpus(22, include_str!("golden/v22.hex"));
+}
+
+#[test]
+fn v21_still_decodes_and_encodes() {
+    check_corpus(21, include_str!("golden/v21.hex"));
+}
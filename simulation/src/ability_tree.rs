@@ -1,35 +1,23 @@
 //! simulation/src/ability_tree.rs
 //! Powrush-MMO Ability Tree System with Mutation Synergy Chains + Stage 0/1/2 + Cross-Race Chain Synergy
 //! v1.9 — Added `tick` timestamp to SynergyEffectEvent for full temporal + per-agent observability
+//! v2.0 — Abilities come from the shared data-driven registry (shared::abilities); synergy
+//!        chains match on definition synergy_tags instead of id substrings
 //! Derived from Ra-Thor powrush-mmo-simulator v15.30
 //! AG-SML v1.0 | TOLC 8 + 7 Living Mercy Gates | PATSAGi aligned
 
 use serde::{Deserialize, Serialize};
+use shared::abilities;
 use std::collections::HashMap;
 
 use crate::epigenetic_modulation::{MutationType, EpigeneticProfile};
 use crate::world::AgentId;
 
-/// Core ability definition (extensible for advanced effects).
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Ability {
-    pub id: String,
-    pub name: String,
-    pub description: String,
-    pub race: crate::race::Race,
-    pub tier: u8,
-    pub requires_ability: Option<String>,
-}
+/// Ability definition, shared with server combat and ascension (assets/abilities/*.ron).
+pub type Ability = abilities::AbilityDef;
 
-/// Simple gameplay effect enum (foundation for future rich effects).
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum AbilityEffect {
-    HarmonyBoost { amount: f32 },
-    ContributionGain { amount: f64 },
-    EpigeneticStabilize { volatility_reduction: f32 },
-    MovementBurst { duration_ticks: u32 },
-    Custom(String),
-}
+/// Gameplay effect of an ability definition.
+pub type AbilityEffect = abilities::AbilityEffect;
 
 /// Player's ability progression state.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -54,6 +42,35 @@ impl AbilityTree {
         true
     }
 
+    /// Unlock a defined ability once its prerequisite is unlocked.
+    pub fn try_unlock(&mut self, ability_id: &str) -> Result<(), String> {
+        let registry = abilities::current();
+        let def = registry.get(ability_id).ok_or_else(|| format!("unknown ability {}", ability_id))?;
+        if let Some(required) = &def.requires {
+            if !self.unlocked.contains(required) {
+                return Err(format!("{} requires {}", ability_id, required));
+            }
+        }
+        if !self.try_unlock_starter(ability_id) {
+            return Err(format!("{} already unlocked", ability_id));
+        }
+        Ok(())
+    }
+
+    /// Use an ability with its defined cooldown (`ticks_per_second` simulation ticks per second).
+    pub fn try_use_defined(&mut self, ability_id: &str, current_tick: u64, ticks_per_second: u32) -> bool {
+        let Some(cooldown) = abilities::current().get(ability_id).map(|d| d.cooldown_ticks(ticks_per_second)) else {
+            return false;
+        };
+        self.try_use_ability(ability_id, current_tick, cooldown)
+    }
+
+    /// Any unlocked ability carries synergy `tag`.
+    pub fn has_unlocked_tag(&self, tag: &str) -> bool {
+        let registry = abilities::current();
+        self.unlocked.iter().any(|id| registry.get(id).is_some_and(|d| d.has_tag(tag)))
+    }
+
     /// Try to use an ability (checks unlock + cooldown).
     pub fn try_use_ability(&mut self, ability_id: &str, current_tick: u64, cooldown_duration: u64) -> bool {
         if !self.unlocked.contains(&ability_id.to_string()) {
@@ -70,13 +87,15 @@ impl AbilityTree {
 
     /// UI-ready state snapshot.
     pub fn get_ability_states(&self, current_tick: u64) -> Vec<AbilityState> {
+        let registry = abilities::current();
         self.unlocked.iter().map(|id| {
+            let def = registry.get(id);
             let on_cooldown = self.cooldowns.get(id).map_or(false, |&end| current_tick < end);
             let remaining = self.cooldowns.get(id).map_or(0, |&end| end.saturating_sub(current_tick));
             AbilityState {
                 id: id.clone(),
-                name: id.clone(),
-                description: String::new(),
+                name: def.map_or_else(|| id.clone(), |d| d.name.clone()),
+                description: def.map(|d| d.description.clone()).unwrap_or_default(),
                 unlocked: true,
                 on_cooldown,
                 remaining_cooldown_ticks: remaining as u32,
@@ -135,7 +154,7 @@ impl AbilityTree {
             match mutation {
                 MutationType::HarmonicRebirth => {
                     let stage = self.get_chain_stage("redemption_cascade");
-                    if self.unlocked.iter().any(|a| a.contains("resonant") || a.contains("cosmic")) {
+                    if self.has_unlocked_tag("resonance") {
                         let (mult, name, desc) = match stage {
                             2 => (1.55, "Redemption Cascade (Stage 2 — Mastered)", "Maximum redemptive power: powerful ongoing epigenetic healing + harmony mastery."),
                             1 => (1.40, "Redemption Cascade (Stage 1)", "Escalating repair strength and corruption resistance."),
@@ -157,7 +176,7 @@ impl AbilityTree {
                 }
                 MutationType::VolatileSurge => {
                     let stage = self.get_chain_stage("surge_overclock");
-                    if self.unlocked.iter().any(|a| a.contains("overclock") || a.contains("systems")) {
+                    if self.has_unlocked_tag("overclock") {
                         let mult = 1.20 + (stage as f64 * 0.12);
                         bonuses.push(SynergyBonus {
                             name: format!("Surge Overclock Chain (Stage {})", stage),
@@ -169,7 +188,7 @@ impl AbilityTree {
                 }
                 MutationType::CorruptedEcho => {
                     let stage = self.get_chain_stage("corrupted_singularity");
-                    if self.unlocked.iter().any(|a| a.contains("phase") || a.contains("singularity")) {
+                    if self.has_unlocked_tag("phase") {
                         let mult = 1.15 + (stage as f64 * 0.10);
                         bonuses.push(SynergyBonus {
                             name: format!("Corrupted Singularity Chain (Stage {})", stage),
@@ -204,7 +223,7 @@ impl AbilityTree {
         // Hybrid 1: Harmonic Rebirth + Terran abilities → Allied Resonance
         if active_mutations.contains(&MutationType::HarmonicRebirth)
             && unlocked_races.contains(&crate::race::Race::Terran)
-            && self.unlocked.iter().any(|a| a.contains("steady") || a.contains("community") || a.contains("fortress"))
+            && self.has_unlocked_tag("allied")
         {
             let stage = self.get_chain_stage("allied_resonance_cross");
             let mult = 1.30 + (stage as f32 * 0.15);
@@ -219,7 +238,7 @@ impl AbilityTree {
         // Hybrid 2: Volatile Surge + Voidfarer abilities → Chaotic Void
         if active_mutations.contains(&MutationType::VolatileSurge)
             && unlocked_races.contains(&crate::race::Race::Voidfarer)
-            && self.unlocked.iter().any(|a| a.contains("void") || a.contains("phase") || a.contains("singularity"))
+            && self.has_unlocked_tag("void")
         {
             let stage = self.get_chain_stage("chaotic_void_cross");
             let mult = 1.25 + (stage as f64 * 0.18);
@@ -234,7 +253,7 @@ impl AbilityTree {
         // Hybrid 3: Corrupted Echo + Synthetic abilities → Corrupted Tech
         if active_mutations.contains(&MutationType::CorruptedEcho)
             && unlocked_races.contains(&crate::race::Race::Synthetic)
-            && self.unlocked.iter().any(|a| a.contains("overclock") || a.contains("systems"))
+            && self.has_unlocked_tag("overclock")
         {
             let stage = self.get_chain_stage("corrupted_tech_hybrid");
             let mult = 1.22 + (stage as f64 * 0.14);
@@ -390,6 +409,21 @@ mod tests {
         assert!(stage >= 1);
     }
 
+    #[test]
+    fn test_defined_prerequisites_and_cooldowns() {
+        let mut tree = AbilityTree::new();
+        assert!(tree.try_unlock("corrupting_rend").is_err());
+        assert!(tree.try_unlock("focused_strike").is_ok());
+        assert!(tree.try_unlock("corrupting_rend").is_ok());
+        assert!(tree.try_unlock("no_such_ability").is_err());
+
+        // focused_strike: 1.5 s cooldown → 90 ticks at 60 Hz.
+        assert!(tree.try_use_defined("focused_strike", 0, 60));
+        assert!(!tree.try_use_defined("focused_strike", 89, 60));
+        assert!(tree.try_use_defined("focused_strike", 90, 60));
+        assert_eq!(tree.get_ability_states(90)[0].name, "Focused Strike");
+    }
+
     #[test]
     fn test_cross_race_chain_activation() {
         let mut tree = AbilityTree::new();
@@ -4,16 +4,20 @@
 //! End-to-end runnable example for closed-beta validation and RBE policy testing.
 //!
 //! Usage:
-//!   cargo run --features gpu -p powrush-simulation --bin harness -- --preset LongTermRbeStability --gpu --ticks 500
-//!   cargo run -p powrush-simulation --bin harness -- --preset AbundanceSurgeWithEvolution --ticks 200
+//!   cargo run --features gpu -p simulation --bin harness -- --preset LongTermRbeStability --gpu --ticks 500
+//!   cargo run -p simulation --bin harness -- --preset AbundanceSurgeWithEvolution --ticks 200
+//!   cargo run -p simulation --bin harness -- --scenario simulation/scenarios/verdant_scarcity.ron
+//!   cargo run -p simulation --bin harness -- --scenario my_run.ron --record my_run.replay.jsonl
+//!   cargo run -p simulation --bin harness -- --replay my_run.replay.jsonl
 
-use powrush_simulation::{
+use simulation::{
     archetype::SovereignArchetypeSystem,
     economy::EconomicLayer,
     harvest::HarvestingSystem,
     mercy::MercyGate,
     orchestrator::SovereignOrchestrator,
-    scenario::{ScenarioConfig, ScenarioPreset},
+    replay::ReplayRunner,
+    scenario::{Scenario, ScenarioConfig, ScenarioPreset, ScenarioRunner},
     telemetry::TelemetryCollector,
     world::SovereignWorldState,
 };
@@ -29,6 +33,59 @@ fn main() {
     let preset_name = args.iter().position(|a| a == "--preset").and_then(|i| args.get(i + 1)).cloned().unwrap_or_else(|| "LongTermRbeStability".to_string());
     let use_gpu = args.iter().any(|a| a == "--gpu");
     let tick_count: u64 = args.iter().position(|a| a == "--ticks").and_then(|i| args.get(i + 1)).and_then(|s| s.parse().ok()).unwrap_or(300);
+    let arg_value = |flag: &str| args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).cloned();
+
+    // === Replay a recorded run and report the first divergence ===
+    if let Some(replay_path) = arg_value("--replay") {
+        let runner = ReplayRunner::from_file(&replay_path).unwrap_or_else(|e| {
+            eprintln!("Failed to load replay {}: {}", replay_path, e);
+            std::process::exit(2);
+        });
+        println!("Replaying '{}' (seed {}, {} ticks)...", runner.header.scenario, runner.header.seed, runner.frames.len());
+        let report = runner.run();
+        match report.divergence {
+            None => println!("Replay matched all {} ticks.", report.ticks_replayed),
+            Some(d) => {
+                eprintln!("Replay {}", d);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // === Data-driven scenario file ===
+    if let Some(scenario_path) = arg_value("--scenario") {
+        let scenario = Scenario::load(&scenario_path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        });
+        println!("Scenario: {} ({} ticks, {} agents, seed {})\n", scenario.name, scenario.ticks, scenario.population, scenario.seed);
+        let runner = ScenarioRunner::new(scenario);
+        let outcome = match arg_value("--record") {
+            Some(replay_path) => runner.run_recorded(&replay_path),
+            None => runner.run(),
+        }
+        .unwrap_or_else(|e| {
+            eprintln!("Scenario failed: {}", e);
+            std::process::exit(2);
+        });
+
+        println!("Ticks run:        {}", outcome.ticks_run);
+        println!("Mean mercy flow:  {:.3}", outcome.mean_mercy_flow);
+        println!("Council blooms:   {}", outcome.council_blooms);
+        println!("Active policies:  {}", outcome.active_policies);
+        println!("Mean depletion:   {:.3}", outcome.mean_depletion);
+        println!("Errors:           {}", outcome.total_errors);
+        if outcome.passed() {
+            println!("\nAll expectations held.");
+        } else {
+            for failure in &outcome.failures {
+                eprintln!("EXPECTATION FAILED: {}", failure);
+            }
+            std::process::exit(1);
+        }
+        return;
+    }
 
     println!("Preset: {}", preset_name);
     println!("GPU acceleration: {}", if use_gpu { "ENABLED (wgpu + WGSL patsagi_economic)" } else { "DISABLED (CPU golden master)" });
@@ -48,7 +105,7 @@ fn main() {
         }
     };
 
-    let config: ScenarioConfig = preset.to_config();
+    let config: ScenarioConfig = preset.to_config(1.0);
     println!("Scenario initialized with {} resource nodes, {} factions, {} archetype templates.\n", 
         config.resource_templates.len(), config.faction_templates.len(), config.archetype_templates.len());
 
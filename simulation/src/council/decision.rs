@@ -14,6 +14,7 @@ use crate::player_legacy_journal::LegacyJournalRegistry;
 use crate::epiphany_catalyst::record_proactive_joy_for_epiphany;
 use crate::multi_realm_harness::MultiRealmHarness;
 use crate::economy::EconomyState;
+use shared::fair_allocation::AllocationPolicy;
 
 const RESOLVED_HISTORY_CAP: usize = 48;
 
@@ -159,6 +160,17 @@ impl ActivePolicy {
     }
 }
 
+/// How long a passed decision stays active, by proposal type.
+pub fn policy_duration_ticks(proposal_type: &ProposalType) -> u64 {
+    match proposal_type {
+        ProposalType::KardashevAcceleration => 1200,
+        ProposalType::ResourcePolicy => 900,
+        ProposalType::EpiphanyEvent => 600,
+        ProposalType::HarmonyBoost => 450,
+        ProposalType::General => 300,
+    }
+}
+
 pub fn apply_resource_policy_impact(
     decision: &CouncilDecision,
     world: &mut SovereignWorldState,
@@ -363,6 +375,29 @@ impl CouncilDecisions {
         let start = self.resolved_history.len().saturating_sub(n);
         self.resolved_history[start..].iter()
     }
+
+    /// Allocation-round caps under the strongest active ResourcePolicy: a stronger
+    /// policy releases more of the reserve and spreads each round more widely.
+    pub fn resource_policy_caps(&self) -> AllocationPolicy {
+        let strongest = self
+            .active_policies
+            .iter()
+            .filter(|p| p.policy_type == PolicyType::ResourcePolicy && !p.is_expired())
+            .map(|p| p.strength)
+            .fold(None, |best: Option<f32>, s| Some(best.map_or(s, |b| b.max(s))));
+        let defaults = AllocationPolicy::default();
+        match strongest {
+            None => defaults,
+            Some(strength) => {
+                let strength = strength.clamp(0.5, 2.5) as f64;
+                AllocationPolicy {
+                    reserve_fraction: (defaults.reserve_fraction / strength).clamp(0.02, defaults.reserve_fraction),
+                    max_share: (defaults.max_share / strength).clamp(0.05, defaults.max_share),
+                    ..defaults
+                }
+            }
+        }
+    }
 }
 
 pub fn apply_council_decision_effects(
@@ -398,15 +433,7 @@ pub fn apply_council_decision_effects(
             soft_feed_economy_from_decision(&decision, eco);
         }
 
-        let duration = match decision.proposal_type {
-            ProposalType::KardashevAcceleration => 1200,
-            ProposalType::ResourcePolicy => 900,
-            ProposalType::EpiphanyEvent => 600,
-            ProposalType::HarmonyBoost => 450,
-            ProposalType::General => 300,
-        };
-
-        let policy = ActivePolicy::from_decision(&decision, duration);
+        let policy = ActivePolicy::from_decision(&decision, policy_duration_ticks(&decision.proposal_type));
 
         // 4. Per-realm tracking + resonance + legacy count
         if let Some(ref mut harness) = multi_realm {
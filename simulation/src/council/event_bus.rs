@@ -173,7 +173,7 @@ impl Default for CouncilEventBus {
     fn default() -> Self { Self::new_bounded(1024) }
 }
 
-fn compute_crc64(data: &str) -> u64 {
+pub(crate) fn compute_crc64(data: &str) -> u64 {
     let mut digest = CRC64.digest();
     digest.update(data.as_bytes());
     digest.finalize()
@@ -1,6 +1,8 @@
 //! simulation/src/council/proposal.rs
 //! Council Proposal System — Core Types + Voting + Kardashev Support
 //! v1.2 — Added KardashevAcceleration ProposalType + richer helpers for session integration
+//! v1.3 — Ballots tied to AgentId (shared::council_ballot): one vote per identity, changes
+//!        until close, liquid delegation. votes_for / votes_against now mirror direct ballots.
 //! AG-SML v1.0 | TOLC 8 + 7 Living Mercy Gates | Ra-Thor + PATSAGi aligned
 //!
 //! Cross-link: CouncilProposal (ProposalType including KardashevAcceleration, ResourcePolicy, EpiphanyEvent, HarmonyBoost)
@@ -8,6 +10,7 @@
 //! recovered render post-FX, council bloom visuals, and Kardashev Acceleration Dashboard metrics.
 
 use serde::{Deserialize, Serialize};
+use shared::council_ballot::{BallotBox, BallotChoice, BallotError, BallotRecord};
 use crate::world::AgentId;
 
 /// High-level categories of proposals that can be deliberated by the parallel PATSAGi Councils.
@@ -56,8 +59,15 @@ pub struct CouncilProposal {
     pub proposer: AgentId,
     pub status: ProposalStatus,
     pub created_tick: u64,
+    /// Direct ballots for / against (kept in sync with `ballots`; delegated weight is not included).
     pub votes_for: u32,
     pub votes_against: u32,
+    /// One ballot or delegation per AgentId.
+    #[serde(default)]
+    pub ballots: BallotBox,
+    /// Record published when deliberation resolved this proposal.
+    #[serde(default)]
+    pub ballot_record: Option<BallotRecord>,
     /// Optional target interest zone for spatially-scoped effects (feeds ActivePolicy + InterestManager).
     pub target_interest_zone: Option<u64>,
     /// Optional mercy weighting hint from the proposer (0.0–1.0).
@@ -83,6 +93,8 @@ impl CouncilProposal {
             created_tick: current_tick,
             votes_for: 0,
             votes_against: 0,
+            ballots: BallotBox::new(),
+            ballot_record: None,
             target_interest_zone: None,
             proposer_mercy_hint: 0.7,
         }
@@ -116,13 +128,38 @@ impl CouncilProposal {
         self
     }
 
-    /// Cast a simple for/against vote. Used by both human players and parallel council archetypes.
-    pub fn cast_vote(&mut self, support: bool) {
-        if support {
-            self.votes_for += 1;
-        } else {
-            self.votes_against += 1;
+    /// Cast or change `voter`'s ballot. Returns true when an earlier ballot was replaced.
+    pub fn cast_vote(&mut self, voter: AgentId, support: bool, tick: u64) -> Result<bool, BallotError> {
+        if !self.is_open() {
+            return Err(BallotError::UnknownProposal(self.id));
+        }
+        let replaced = self.ballots.cast(voter, BallotChoice::from_support(support), tick)?;
+        self.sync_vote_counts();
+        Ok(replaced)
+    }
+
+    /// Withdraw `voter`'s ballot while the proposal is open.
+    pub fn retract_vote(&mut self, voter: AgentId) -> Result<(), BallotError> {
+        if !self.is_open() {
+            return Err(BallotError::UnknownProposal(self.id));
+        }
+        self.ballots.retract(voter)?;
+        self.sync_vote_counts();
+        Ok(())
+    }
+
+    /// Delegate `voter`'s weight to `delegate` (refused if it would form a cycle).
+    pub fn delegate_vote(&mut self, voter: AgentId, delegate: AgentId) -> Result<(), BallotError> {
+        if !self.is_open() {
+            return Err(BallotError::UnknownProposal(self.id));
         }
+        self.ballots.delegate(voter, delegate)
+    }
+
+    fn sync_vote_counts(&mut self) {
+        let (for_, against) = self.ballots.direct_counts();
+        self.votes_for = for_;
+        self.votes_against = against;
     }
 
     /// Total raw votes cast so far.
@@ -161,14 +198,40 @@ mod tests {
             100,
         );
         assert!(p.is_open());
-        p.cast_vote(true);
-        p.cast_vote(true);
-        p.cast_vote(false);
+        p.cast_vote(1, true, 101).unwrap();
+        p.cast_vote(2, true, 101).unwrap();
+        p.cast_vote(3, false, 102).unwrap();
         assert_eq!(p.votes_for, 2);
         assert_eq!(p.votes_against, 1);
         assert!((p.approval_ratio() - 0.666).abs() < 0.01);
     }
 
+    #[test]
+    fn test_one_vote_per_identity() {
+        let mut p = CouncilProposal::new(
+            2,
+            ProposalType::HarmonyBoost,
+            "Grove Festival".into(),
+            "Open the grove for a shared festival".into(),
+            42,
+            100,
+        );
+        for tick in 0..5 {
+            p.cast_vote(9, true, 100 + tick).unwrap();
+        }
+        assert_eq!(p.total_votes(), 1);
+
+        // Changing a ballot moves it rather than adding another.
+        assert_eq!(p.cast_vote(9, false, 106), Ok(true));
+        assert_eq!((p.votes_for, p.votes_against), (0, 1));
+
+        p.delegate_vote(10, 9).unwrap();
+        assert_eq!(p.delegate_vote(9, 10), Err(BallotError::DelegationCycle(vec![10, 9])));
+
+        p.status = ProposalStatus::Rejected;
+        assert_eq!(p.cast_vote(11, true, 107), Err(BallotError::UnknownProposal(2)));
+    }
+
     #[test]
     fn test_kardashev_constructor() {
         let p = CouncilProposal::new_kardashev(
@@ -2,6 +2,10 @@
  * CouncilSession with World State Delta Scoring.
  *
  * v21.71.0 — Session → CouncilDecisions promotion path
+ * v21.97.0 — Identity ballots: votes are tied to AgentId, delegation is liquid and
+ *            deliberation consumes an attunement-weighted BallotRecord, which is
+ *            published (bounded) for clients to verify.
+ * v21.97.1 — Agent ballots are attuned to mercy contribution before each pass.
  *
  * AG-SML v1.0 | TOLC 8 + 7 Living Mercy Gates
  */
@@ -14,9 +18,16 @@ use crate::council::event_bus::{CouncilEvent, CouncilEventBus};
 use crate::council::proposal::{CouncilProposal, ProposalStatus, ProposalType};
 use crate::world::{AgentId, SovereignWorldState};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use shared::council_ballot::{BallotError, BallotRecord};
+use std::collections::{BTreeSet, HashMap, VecDeque};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// Distinct identities whose weight must be counted before a proposal can resolve.
+pub const MIN_COUNTED_VOTERS: u32 = 3;
+
+/// Published ballot records kept per session (oldest dropped first).
+pub const PUBLISHED_BALLOT_LIMIT: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CouncilArchetype {
     Truth,
     Abundance,
@@ -165,6 +176,12 @@ pub struct CouncilSession {
     num_parallel_councils: usize,
     council_weights: Vec<f32>,
     council_archetypes: Vec<Option<CouncilArchetype>>,
+    /// Attunement (0.0–1.0) per voter; identities without an entry count at the base weight.
+    /// Only voters taking part in an open proposal are kept.
+    #[serde(default)]
+    voter_attunement: HashMap<AgentId, f32>,
+    #[serde(default)]
+    published_ballots: VecDeque<BallotRecord>,
 }
 
 impl CouncilSession {
@@ -180,6 +197,8 @@ impl CouncilSession {
             num_parallel_councils: default_councils,
             council_weights: vec![1.0; default_councils],
             council_archetypes: vec![None; default_councils],
+            voter_attunement: HashMap::new(),
+            published_ballots: VecDeque::new(),
         }
     }
 
@@ -299,14 +318,75 @@ impl CouncilSession {
         self.active_proposals.push(proposal);
     }
 
-    /// Cast a simple vote on an open proposal by id.
-    pub fn cast_vote_on(&mut self, proposal_id: u64, support: bool) -> bool {
-        if let Some(p) = self.active_proposals.iter_mut().find(|p| p.id == proposal_id && p.is_open()) {
-            p.cast_vote(support);
-            true
-        } else {
-            false
+    /// Cast or change `voter`'s ballot on an open proposal. Returns true when an
+    /// earlier ballot by the same identity was replaced.
+    pub fn cast_vote_on(
+        &mut self,
+        proposal_id: u64,
+        voter: AgentId,
+        support: bool,
+        tick: u64,
+    ) -> Result<bool, BallotError> {
+        self.open_proposal_mut(proposal_id)?.cast_vote(voter, support, tick)
+    }
+
+    /// Delegate `voter`'s weight on an open proposal to `delegate`.
+    pub fn delegate_vote_on(
+        &mut self,
+        proposal_id: u64,
+        voter: AgentId,
+        delegate: AgentId,
+    ) -> Result<(), BallotError> {
+        self.open_proposal_mut(proposal_id)?.delegate_vote(voter, delegate)
+    }
+
+    fn open_proposal_mut(&mut self, proposal_id: u64) -> Result<&mut CouncilProposal, BallotError> {
+        self.active_proposals
+            .iter_mut()
+            .find(|p| p.id == proposal_id && p.is_open())
+            .ok_or(BallotError::UnknownProposal(proposal_id))
+    }
+
+    /// Attunement used to weight `voter`'s ballot, clamped to 0.0–1.0 (non-finite counts as 0.0).
+    pub fn set_voter_attunement(&mut self, voter: AgentId, attunement: f32) {
+        let attunement = if attunement.is_finite() { attunement.clamp(0.0, 1.0) } else { 0.0 };
+        self.voter_attunement.insert(voter, attunement);
+    }
+
+    /// Forget the attunement of every voter no open proposal still counts.
+    fn prune_voter_attunement(&mut self) {
+        let participants: BTreeSet<AgentId> = self
+            .active_proposals
+            .iter()
+            .filter(|p| p.is_open())
+            .flat_map(|p| p.ballots.participants())
+            .collect();
+        self.voter_attunement.retain(|voter, _| participants.contains(voter));
+    }
+
+    /// Attune every world agent taking part in an open proposal to its mercy
+    /// contribution; identities the world does not know keep what they were given
+    /// (player ballots arrive with their own attunement). Returns how many were set.
+    pub fn attune_agents(&mut self, world: &SovereignWorldState) -> usize {
+        let mut attuned = 0;
+        for proposal in self.active_proposals.iter().filter(|p| p.is_open()) {
+            for voter in proposal.ballots.participants() {
+                if let Some(agent) = world.agents.get(&voter) {
+                    self.voter_attunement.insert(voter, agent.mercy_contribution.clamp(0.0, 1.0));
+                    attuned += 1;
+                }
+            }
         }
+        attuned
+    }
+
+    /// Ballot records of resolved proposals, oldest first.
+    pub fn published_ballots(&self) -> impl Iterator<Item = &BallotRecord> {
+        self.published_ballots.iter()
+    }
+
+    pub fn published_ballot(&self, proposal_id: u64) -> Option<&BallotRecord> {
+        self.published_ballots.iter().find(|r| r.proposal_id == proposal_id)
     }
 
     /// Promote resolved (Passed) proposals into CouncilDecision values.
@@ -352,16 +432,20 @@ impl CouncilSession {
             "Ra-Thor world-delta archetype scoring started"
         );
 
+        let attunement = &self.voter_attunement;
         for proposal in self.active_proposals.iter_mut() {
             if proposal.status == ProposalStatus::Draft || proposal.status == ProposalStatus::Deliberating {
                 proposal.status = ProposalStatus::Deliberating;
 
-                let total_votes = proposal.votes_for + proposal.votes_against;
-                if total_votes >= 3 {
+                let record = proposal.ballots.record(self.realm_id, proposal.id, current_tick, |v| {
+                    attunement.get(&v).copied().unwrap_or(0.0)
+                });
+                let tally = record.tally.clone();
+                if tally.counted_voters() >= MIN_COUNTED_VOTERS {
                     let mercy_factor = (average_mercy / 100.0) * 0.3;
-                    let effective_for = proposal.votes_for as f32 * (1.0 + mercy_factor);
+                    let effective_for = tally.weight_for * (1.0 + mercy_factor);
 
-                    let would_pass_vote = effective_for > proposal.votes_against as f32;
+                    let would_pass_vote = effective_for > tally.weight_against;
 
                     if would_pass_vote {
                         let mut total_weight: f32 = 0.0;
@@ -418,8 +502,8 @@ impl CouncilSession {
                             if let Some(bus) = &self.event_bus {
                                 let _ = bus.send(CouncilEvent::ProposalPassed {
                                     proposal_id: proposal.id,
-                                    votes_for: proposal.votes_for,
-                                    votes_against: proposal.votes_against,
+                                    votes_for: tally.voters_for,
+                                    votes_against: tally.voters_against,
                                     mercy_factor,
                                 });
                             }
@@ -450,12 +534,19 @@ impl CouncilSession {
                         );
                     }
 
+                    proposal.ballots.close();
+                    proposal.ballot_record = Some(record.clone());
+                    if self.published_ballots.len() >= PUBLISHED_BALLOT_LIMIT {
+                        self.published_ballots.pop_front();
+                    }
+                    self.published_ballots.push_back(record);
                     resolved.push(proposal.clone());
                 }
             }
         }
 
         self.active_proposals.retain(|p| p.status != ProposalStatus::Passed && p.status != ProposalStatus::Rejected);
+        self.prune_voter_attunement();
         self.last_session_tick = current_tick;
         resolved
     }
@@ -522,6 +613,7 @@ pub fn session_deliberation_system(
     mut registry: ResMut<CouncilSessionRegistry>,
     mut decisions: ResMut<CouncilDecisions>,
     time: Res<Time>,
+    world: Option<Res<SovereignWorldState>>,
 ) {
     if registry.deliberation_interval == 0 {
         registry.deliberation_interval = 90; // soft default
@@ -543,6 +635,9 @@ pub fn session_deliberation_system(
         if session.open_proposal_count() == 0 {
             continue;
         }
+        if let Some(world) = world.as_deref() {
+            session.attune_agents(world);
+        }
         let promoted = session.deliberate_and_promote(mercy, approx_tick, None);
         for decision in promoted {
             decisions.push_decision(decision);
@@ -560,5 +655,100 @@ pub fn session_deliberation_system(
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session_with_proposal() -> (CouncilSession, u64) {
+        let mut session = CouncilSession::new(3, 100).with_archetype_defaults();
+        let id = session.submit_proposal(
+            ProposalType::ResourcePolicy,
+            "Sustainable Harvest Cap".into(),
+            "Limit node depletion rate".into(),
+            42,
+            100,
+        );
+        (session, id)
+    }
+
+    #[test]
+    fn repeated_votes_from_one_identity_count_once() {
+        let (mut session, id) = session_with_proposal();
+        for tick in 0..5 {
+            session.cast_vote_on(id, 7, true, 101 + tick).unwrap();
+        }
+        assert!(session.run_deliberation(90.0, 110, None).is_empty());
+        assert_eq!(session.open_proposal_count(), 1);
+        assert!(session.published_ballot(id).is_none());
+        assert_eq!(session.cast_vote_on(id + 1, 7, true, 111), Err(BallotError::UnknownProposal(id + 1)));
+    }
+
+    #[test]
+    fn delegated_weight_resolves_and_publishes_a_verifiable_record() {
+        let (mut session, id) = session_with_proposal();
+        session.cast_vote_on(id, 7, true, 101).unwrap();
+        session.delegate_vote_on(id, 8, 7).unwrap();
+        session.delegate_vote_on(id, 9, 8).unwrap();
+        session.set_voter_attunement(7, 1.0);
+
+        let resolved = session.run_deliberation(90.0, 120, None);
+        assert_eq!(resolved.len(), 1);
+        let record = resolved[0].ballot_record.as_ref().unwrap();
+        assert_eq!(record.tally.voters_for, 3);
+        assert_eq!(record.tally.weight_for, 2.0);
+        assert_eq!(record.verify(), Ok(()));
+        assert!(resolved[0].ballots.is_closed());
+        assert_eq!(session.published_ballot(id), Some(record));
+        assert_eq!(session.cast_vote_on(id, 10, false, 121), Err(BallotError::UnknownProposal(id)));
+    }
+
+    #[test]
+    fn agent_ballots_are_weighted_by_mercy_contribution() {
+        use shared::council_ballot::player_voter;
+
+        let (mut session, id) = session_with_proposal();
+        let mut world = SovereignWorldState::default();
+        for (agent_id, mercy) in [(7, 0.9), (8, 1.5), (11, 0.3)] {
+            let mut agent = crate::world::Agent::new(agent_id, "voter");
+            agent.mercy_contribution = mercy;
+            world.agents.insert(agent_id, agent);
+        }
+        session.cast_vote_on(id, 7, true, 101).unwrap();
+        session.cast_vote_on(id, 8, true, 101).unwrap();
+        // Player 7 is not agent 7: it keeps the attunement its ballot was cast with.
+        let player = player_voter(7);
+        session.cast_vote_on(id, player, false, 101).unwrap();
+        session.set_voter_attunement(player, 0.2);
+
+        // Agent 11 has not voted, so it is not attuned.
+        assert_eq!(session.attune_agents(&world), 2);
+        assert_eq!(session.voter_attunement.get(&7), Some(&0.9));
+        assert_eq!(session.voter_attunement.get(&8), Some(&1.0));
+        assert_eq!(session.voter_attunement.get(&player), Some(&0.2));
+        assert!(!session.voter_attunement.contains_key(&11));
+    }
+
+    #[test]
+    fn attunement_is_clamped_and_forgotten_once_proposals_close() {
+        let (mut session, id) = session_with_proposal();
+        let lingering = session.submit_proposal(ProposalType::ResourcePolicy, "Open".into(), "Still open".into(), 42, 100);
+        session.cast_vote_on(id, 7, true, 101).unwrap();
+        session.delegate_vote_on(id, 8, 7).unwrap();
+        session.delegate_vote_on(id, 10, 7).unwrap();
+        session.cast_vote_on(lingering, 9, true, 101).unwrap();
+        session.set_voter_attunement(7, 4.0);
+        session.set_voter_attunement(8, -1.0);
+        session.set_voter_attunement(9, f32::NAN);
+        assert_eq!(session.voter_attunement.get(&7), Some(&1.0));
+        assert_eq!(session.voter_attunement.get(&8), Some(&0.0));
+        assert_eq!(session.voter_attunement.get(&9), Some(&0.0));
+
+        // `id` resolves; only the voter still on an open proposal keeps an entry.
+        let resolved = session.run_deliberation(90.0, 120, None);
+        assert_eq!(resolved.iter().map(|p| p.id).collect::<Vec<_>>(), vec![id]);
+        assert_eq!(session.voter_attunement.keys().copied().collect::<Vec<_>>(), vec![9]);
+    }
+}
+
 // Thunder locked in. Session → decisions loop closed.
 // Yoi ⚡
@@ -7,6 +7,10 @@
  * - High mercy/attunement = economic blessing; low = friction.
  *
  * v21.66: Bevy-facing EconomyState + organism-level RBE snapshot from multi-realm observatory.
+ * v21.67: PostScarcityAllocator runs need-weighted fair allocation rounds (shared::fair_allocation)
+ *   under council ResourcePolicy caps instead of first come, first served.
+ * v21.68: Crafting footprint — each recipe run's ecological cost (shared::crafting) stresses the
+ *   nodes of the raw resources behind it and feeds EconomyState pressure / sustainability.
  * AG-SML v1.0 | TOLC 8 + 7 Living Mercy Gates
  */
 
@@ -17,6 +21,11 @@ use crate::harvest::HarvestEvent;
 use crate::emergence::DynamicEmergenceEvent;
 use crate::multi_realm_harness::RealmAbundanceObservatory;
 use tracing::{info_span, instrument, warn};
+use std::collections::VecDeque;
+use shared::fair_allocation::{AllocationPolicy, AllocationRound, FairAllocator, NeedDeclaration, NeedSeverity};
+use shared::nevc_adapter::ContributionClass;
+use shared::crafting::EcologicalCost;
+use crate::council::decision::CouncilDecisions;
 
 #[cfg(feature = "gpu")]
 use crate::gpu_economic::{dispatch_gpu_economic_update, dispatch_gpu_economic_compute_async, GpuEconomicReadback};
@@ -338,6 +347,12 @@ impl EconomyState {
             (self.abundance_velocity * 0.9 + amount * 0.1).min(50.0);
     }
 
+    /// Crafting adds its pressure and wears sustainability down by a tenth of its node stress.
+    pub fn record_crafting(&mut self, cost: &EcologicalCost) {
+        self.average_pressure = (self.average_pressure + cost.pressure.max(0.0)).min(5.0);
+        self.average_sustainability = (self.average_sustainability - cost.node_stress.max(0.0) * 0.1).max(0.1);
+    }
+
     pub fn health_label(&self) -> &'static str {
         if self.average_sustainability > 0.8 && self.average_pressure < 0.5 {
             "Thriving"
@@ -361,12 +376,28 @@ pub struct ResourceTransaction {
     pub tick: u64,
 }
 
+/// Resource key of the post-scarcity surplus pool in allocation rounds.
+pub const SURPLUS_RESOURCE: &str = "surplus";
+
+/// Seconds between allocation rounds.
+pub const ALLOCATION_ROUND_SECS: f32 = 5.0;
+
+/// Completed rounds kept for audit.
+const ALLOCATION_HISTORY_CAP: usize = 64;
+
 /// Soft post-scarcity allocator — distributes surplus toward under-supplied agents.
+/// Needs are declared between rounds; each round splits the pool by need-weighted
+/// max-min fairness under the council's current caps.
 #[derive(Resource, Clone, Debug, Default)]
 pub struct PostScarcityAllocator {
     pub surplus_pool: f32,
     pub allocations_this_tick: u32,
     pub last_tick: u64,
+    pub policy: AllocationPolicy,
+    pub round_timer: f32,
+    needs: FairAllocator,
+    /// Oldest first, at most ALLOCATION_HISTORY_CAP
+    pub history: VecDeque<AllocationRound>,
 }
 
 impl PostScarcityAllocator {
@@ -374,13 +405,36 @@ impl PostScarcityAllocator {
         self.surplus_pool = (self.surplus_pool + amount.max(0.0)).min(10_000.0);
     }
 
-    pub fn allocate(&mut self, need: f32) -> f32 {
-        let grant = need.min(self.surplus_pool).max(0.0);
-        self.surplus_pool -= grant;
-        if grant > 0.0 {
-            self.allocations_this_tick = self.allocations_this_tick.saturating_add(1);
+    /// Declare (or replace) an agent's need for the next round.
+    pub fn declare_need(&mut self, agent_id: u64, amount: f32, severity: NeedSeverity, class: ContributionClass) -> Result<(), String> {
+        self.needs.declare(NeedDeclaration {
+            player_id: agent_id,
+            resource: SURPLUS_RESOURCE.to_string(),
+            amount: amount as f64,
+            severity,
+            class,
+        })
+    }
+
+    pub fn has_pending_needs(&self) -> bool {
+        self.needs.has_pending(SURPLUS_RESOURCE)
+    }
+
+    /// Close the current round: split the pool over declared needs and keep the record.
+    pub fn run_round(&mut self) -> Result<&AllocationRound, String> {
+        let round = self.needs.run_round(SURPLUS_RESOURCE, self.surplus_pool as f64, &self.policy)?;
+        self.surplus_pool = (self.surplus_pool - round.distributed as f32).max(0.0);
+        let granted = round.records.iter().filter(|r| r.granted > 0.0).count() as u32;
+        self.allocations_this_tick = self.allocations_this_tick.saturating_add(granted);
+        if self.history.len() == ALLOCATION_HISTORY_CAP {
+            self.history.pop_front();
         }
-        grant
+        self.history.push_back(round);
+        Ok(self.history.back().expect("round just pushed"))
+    }
+
+    pub fn last_round(&self) -> Option<&AllocationRound> {
+        self.history.back()
     }
 
     pub fn tick_reset(&mut self, tick: u64) {
@@ -389,6 +443,27 @@ impl PostScarcityAllocator {
     }
 }
 
+/// Runs an allocation round every ALLOCATION_ROUND_SECS, under the caps of the
+/// council's active ResourcePolicy (defaults when none is active).
+pub fn fair_allocation_round_system(
+    time: Res<Time>,
+    decisions: Option<Res<CouncilDecisions>>,
+    mut allocator: ResMut<PostScarcityAllocator>,
+) {
+    allocator.round_timer += time.delta_seconds();
+    if allocator.round_timer < ALLOCATION_ROUND_SECS {
+        return;
+    }
+    allocator.round_timer = 0.0;
+    allocator.policy = decisions.map(|d| d.resource_policy_caps()).unwrap_or_default();
+    if !allocator.has_pending_needs() {
+        return;
+    }
+    if let Err(e) = allocator.run_round() {
+        warn!("Allocation round skipped: {}", e);
+    }
+}
+
 // ============================================================================
 // MULTI-REALM → RBE ORGANISM SNAPSHOT (v21.66)
 // ============================================================================
@@ -479,6 +554,50 @@ pub fn multi_realm_rbe_snapshot_system(
     }
 }
 
+// ============================================================================
+// CRAFTING FOOTPRINT (v21.68)
+// ============================================================================
+
+/// Ecological cost of crafting runs, reported by whoever hosts crafting.
+#[derive(Event, Clone, Debug)]
+pub struct CraftingFootprintEvent {
+    /// Raw resource types the crafted recipe draws on (RecipeRegistry::raw_sources)
+    pub resource_types: Vec<String>,
+    /// Already scaled by the number of runs
+    pub cost: EcologicalCost,
+}
+
+/// Raise the stress of every node yielding one of `resource_types`; nodes pushed past
+/// 0.75 lose sustainability as a harvest-stressed node would. Returns the nodes touched.
+pub fn apply_crafting_footprint(
+    world: &mut SovereignWorldState,
+    resource_types: &[String],
+    cost: &EcologicalCost,
+) -> usize {
+    let mut touched = 0;
+    for node in world.resource_nodes.values_mut().filter(|n| resource_types.contains(&n.resource_type)) {
+        node.stress_level = (node.stress_level + cost.node_stress.max(0.0)).min(1.0);
+        if node.stress_level > 0.75 {
+            node.sustainability_score = (node.sustainability_score * 0.97).max(0.1);
+        }
+        touched += 1;
+    }
+    touched
+}
+
+fn crafting_footprint_system(
+    mut events: EventReader<CraftingFootprintEvent>,
+    mut economy: ResMut<EconomyState>,
+    mut world: Option<ResMut<SovereignWorldState>>,
+) {
+    for event in events.read() {
+        economy.record_crafting(&event.cost);
+        if let Some(world) = world.as_deref_mut() {
+            apply_crafting_footprint(world, &event.resource_types, &event.cost);
+        }
+    }
+}
+
 pub struct EconomyPlugin;
 
 impl Plugin for EconomyPlugin {
@@ -487,9 +606,69 @@ impl Plugin for EconomyPlugin {
             .init_resource::<PostScarcityAllocator>()
             .init_resource::<MultiRealmRbeSnapshot>()
             .register_type::<EconomyState>()
-            .add_systems(Update, multi_realm_rbe_snapshot_system);
+            .add_event::<CraftingFootprintEvent>()
+            .add_systems(
+                Update,
+                (multi_realm_rbe_snapshot_system, fair_allocation_round_system, crafting_footprint_system),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surplus_goes_to_declared_needs_not_the_first_caller() {
+        let mut allocator = PostScarcityAllocator {
+            policy: AllocationPolicy { reserve_fraction: 0.0, max_share: 1.0, ..Default::default() },
+            ..Default::default()
+        };
+        allocator.deposit_surplus(60.0);
+        let contributor = ContributionClass::ActiveEternalContributor;
+        allocator.declare_need(1, 100.0, NeedSeverity::Standard, contributor).unwrap();
+        allocator.declare_need(2, 100.0, NeedSeverity::Standard, contributor).unwrap();
+        allocator.declare_need(3, 100.0, NeedSeverity::Urgent, contributor).unwrap();
+
+        let round = allocator.run_round().unwrap().clone();
+        assert_eq!(round.granted_to(1), round.granted_to(2));
+        assert!((round.granted_to(3) - 30.0).abs() < 1e-4);
+        assert!(allocator.surplus_pool.abs() < 1e-4);
+        assert_eq!(allocator.allocations_this_tick, 3);
+        assert!(!allocator.has_pending_needs());
+    }
+
+    #[test]
+    fn crafting_footprint_stresses_matching_nodes_and_the_economy() {
+        use crate::world::ResourceNode;
+
+        let mut world = SovereignWorldState::default();
+        for (id, kind) in [(1, "gold"), (2, "herb")] {
+            world.resource_nodes.insert(id, ResourceNode {
+                id,
+                position: (0.0, 0.0, 0.0),
+                resource_type: kind.to_string(),
+                base_yield: 1.0,
+                current_yield: 1.0,
+                depletion: 0.0,
+                regeneration_rate: 0.01,
+                last_harvested_ms: 0,
+                sustainability_score: 1.0,
+                stress_level: 0.7,
+                harvest_restricted_until_ms: 0,
+            });
+        }
+        let cost = EcologicalCost { node_stress: 0.1, pressure: 0.05 };
+        assert_eq!(apply_crafting_footprint(&mut world, &["gold".to_string()], &cost), 1);
+        assert!((world.resource_nodes[&1].stress_level - 0.8).abs() < 1e-6);
+        assert!(world.resource_nodes[&1].sustainability_score < 1.0);
+        assert_eq!(world.resource_nodes[&2].stress_level, 0.7);
+
+        let mut economy = EconomyState::default();
+        economy.record_crafting(&cost);
+        assert!(economy.average_pressure > 0.0 && economy.average_sustainability < 0.75);
     }
 }
 
-// End of v21.66 — Bevy EconomyState + Multi-Realm RBE Sustainability Snapshot.
+// End of v21.68 — Bevy EconomyState + Multi-Realm RBE Snapshot + fair allocation rounds + crafting footprint.
 // Thunder locked in. Yoi ⚡
@@ -23,6 +23,7 @@ pub mod mercy;
 pub mod ra_thor_bridge;
 pub mod mycorrhizal_volatile_sync;
 pub mod orchestrator;
+pub mod replay;
 pub mod patsagi_council_tunable_config;
 pub mod player_legacy_journal;
 pub mod resonance_decay_recovery_sim;
@@ -77,7 +78,7 @@ pub use council::proposal::{ProposalType, ProposalStatus};
 pub use harvest::{HarvestEvent, HarvestSystem, ResourceNode, RbeFlowReconciliation};
 pub use economy::{
     EconomicLayer, EconomyState, ResourceTransaction, PostScarcityAllocator,
-    MultiRealmRbeSnapshot, EconomyPlugin, multi_realm_rbe_snapshot_system,
+    MultiRealmRbeSnapshot, EconomyPlugin, multi_realm_rbe_snapshot_system, fair_allocation_round_system,
 };
 pub use player_legacy_journal::{
     LegacyJournalRegistry, LegacyEventType, LegacyEntry, LegacyThread, LegacyThreadId,
@@ -86,6 +87,7 @@ pub use player_legacy_journal::{
 };
 pub use player_persistence::{PlayerSaveData, PersistenceManager, save_player_data, load_player_data};
 pub use orchestrator::{SimulationOrchestrator, TickResult};
+pub use replay::{ReplayInput, ReplayRecorder, ReplayRunner, ReplayReport, ReplaySession, Divergence};
 pub use fracture::{LatticeFractureSolver, FractureEvent};
 pub use spatial::{SpatialGrid, SpatialQuery};
 pub use telemetry::{
@@ -99,7 +101,8 @@ pub use cloud_sync::{CloudSyncManager, CloudSyncEvent};
 pub use mycorrhizal_volatile_sync::{MycorrhizalSync, VolatileResource};
 pub use patsagi_council_tunable_config::{PatsagiCouncilTunableConfig, TunableParameter};
 pub use resonance_decay_recovery_sim::{ResonanceDecayRecoverySim, ResonanceState};
-pub use scenario::{Scenario, ScenarioRunner, ScenarioOutcome};
+pub use scenario::{Scenario, ScenarioRunner, ScenarioOutcome, ScenarioExpectation, ScenarioPreset, ScheduledInput};
+pub use world::{SovereignWorldState, ScenarioConfig, EntropyProfile, ResourceTemplate, FactionTemplate, ArchetypeTemplate};
 pub use bevy_integration::{BevySimulationPlugin, SimulationTime};
 pub use bevy_ra_thor_ui::{RaThorUiBridge, CouncilUiEvent};
 pub use endocannabinoid_receptor_forge::{ReceptorBloomOutcome, ReceptorBloomForge};
@@ -1,6 +1,7 @@
 //! simulation/src/race.rs
 //! Powrush-MMO Foundational Multi-Race System
 //! v1.1 — 5 Sovereign Races (Terran, Synthetic, Harmonic, Verdant, Voidfarer) + RaceModifiers + Starter Ability Registries
+//! v1.2 — Starter abilities read from the shared ability registry (`starter: true` definitions)
 //! Derived from Ra-Thor powrush-mmo-simulator authoritative reference (v15.x)
 //! AG-SML v1.0 | TOLC 8 Living Mercy Gates | PATSAGi + Ra-Thor aligned
 //! Purpose: Foundation for branching ability trees, epigenetic modulation, and cross-race diplomacy.
@@ -33,16 +34,11 @@ impl Race {
         }
     }
 
-    /// Canonical starter abilities for this race (unlocked on spawn).
+    /// Canonical starter abilities for this race (unlocked on spawn), from the
+    /// ability registry's `starter` definitions for this race.
     /// These seed the ability_tree and enable immediate synergy chain progression.
-    pub fn starter_abilities(&self) -> &'static [&'static str] {
-        match self {
-            Race::Terran => &["steady_step", "community_bond", "fortress_resolve"],
-            Race::Synthetic => &["systems_overclock", "precision_calibration", "adaptive_matrix"],
-            Race::Harmonic => &["resonant_jump", "cosmic_attunement", "harmony_pulse"],
-            Race::Verdant => &["mycelial_root", "epigenetic_bloom", "resilience_weave"],
-            Race::Voidfarer => &["void_phase", "singularity_glimpse", "dimensional_shift"],
-        }
+    pub fn starter_abilities(&self) -> Vec<String> {
+        shared::abilities::current().starters_for(self.name()).map(|d| d.id.clone()).collect()
     }
 }
 
@@ -103,7 +99,7 @@ impl RaceModifiers {
 /// Helper: seed an AbilityTree with a race's starter abilities (idempotent).
 pub fn seed_starter_abilities(tree: &mut crate::ability_tree::AbilityTree, race: Race) {
     for ability_id in race.starter_abilities() {
-        tree.try_unlock_starter(ability_id);
+        tree.try_unlock_starter(&ability_id);
     }
 }
 
@@ -129,4 +125,11 @@ mod tests {
         assert!(tree.unlocked.contains(&"cosmic_attunement".to_string()));
         assert_eq!(tree.unlocked.len(), 3);
     }
+
+    #[test]
+    fn test_every_race_has_defined_starters() {
+        for race in Race::all() {
+            assert_eq!(race.starter_abilities().len(), 3, "{}", race.name());
+        }
+    }
 }
@@ -0,0 +1,578 @@
+/*!
+ * simulation/src/replay.rs
+ * Record & Replay of full simulation runs (v21.92.0)
+ *
+ * ReplayRecorder captures the seed, scenario, starting world and every external
+ * input per tick (harvests, council proposals, mercy-trial votes, realm travel)
+ * into one replay file, together with the TickResult and world digest the run
+ * produced. ReplayRunner rebuilds the session from that file, re-drives
+ * SimulationOrchestrator::run_tick with the same inputs and reports the first
+ * tick + field where the re-run diverges from the recording.
+ *
+ * File format mirrors the council WAL (council/event_bus.rs): one JSON record
+ * per line with a `|crc64:` suffix. Unlike the event bus, a corrupted record is
+ * a hard error here — skipping a tick would surface as a false divergence.
+ *
+ * AG-SML v1.0 | TOLC 8 + 7 Living Mercy Gates
+ * Thunder locked in. Yoi ⚡
+ */
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::council::decision::{policy_duration_ticks, ActivePolicy, CouncilDecision, CouncilDecisions};
+use crate::council::event_bus::compute_crc64;
+use crate::council::proposal::{CouncilProposal, ProposalStatus};
+use crate::council_mercy_trial::CouncilSessionManager;
+use crate::multi_realm_harness::{MultiRealmHarness, RealmId, RealmPresence};
+use crate::orchestrator::{SimulationOrchestrator, TickResult};
+use crate::player_legacy_journal::LegacyJournalRegistry;
+use crate::world::{Agent, AgentId, NodeId, ResourceNode, SovereignWorldState};
+
+/// Bumped whenever ReplayRecord changes shape; older files are refused.
+pub const REPLAY_FORMAT_VERSION: u32 = 1;
+
+// ============================================================================
+// INPUTS
+// ============================================================================
+
+/// An input from outside the simulation, applied at the start of a tick.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ReplayInput {
+    Harvest {
+        player_id: u64,
+        node_id: NodeId,
+        agent_mercy: f32,
+        behavioral_human_score: f32,
+    },
+    /// A proposal as it left deliberation; only Passed proposals become policies.
+    Proposal {
+        proposal: CouncilProposal,
+        mercy_factor: f32,
+        realm_id: RealmId,
+    },
+    /// Mercy-trial vote; feeds the bloom resolved inside run_tick.
+    Vote { voter: AgentId, attunement: f32 },
+    RealmTravel {
+        agent_id: AgentId,
+        target_realm: RealmId,
+        reason: String,
+    },
+}
+
+// ============================================================================
+// DIGESTS (what a tick is compared on)
+// ============================================================================
+
+/// TickResult reduced to the fields that must be reproducible.
+/// Synergy events are counted, not compared: their order follows HashMap iteration.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TickDigest {
+    pub tick: u64,
+    pub economic_updates: u32,
+    pub council_decisions_applied: u32,
+    pub council_attunement_score: f32,
+    pub council_participant_count: u32,
+    pub resource_policy_impacts: u32,
+    pub epiphany_policy_impacts: u32,
+    pub harvest_nodes_processed: u32,
+    pub emergence_events_triggered: u32,
+    pub synergy_event_count: u32,
+    pub gpu_foresight_used: bool,
+    pub gpu_foresight_applied: bool,
+    pub soft_feedback_events: u32,
+    pub errors: Vec<String>,
+}
+
+impl From<&TickResult> for TickDigest {
+    fn from(r: &TickResult) -> Self {
+        Self {
+            tick: r.tick,
+            economic_updates: r.economic_updates,
+            council_decisions_applied: r.council_decisions_applied,
+            council_attunement_score: r.council_attunement_score,
+            council_participant_count: r.council_participant_count,
+            resource_policy_impacts: r.resource_policy_impacts,
+            epiphany_policy_impacts: r.epiphany_policy_impacts,
+            harvest_nodes_processed: r.harvest_nodes_processed,
+            emergence_events_triggered: r.emergence_events_triggered,
+            synergy_event_count: r.synergy_events.len() as u32,
+            gpu_foresight_used: r.gpu_foresight_used,
+            gpu_foresight_applied: r.gpu_foresight_applied,
+            soft_feedback_events: r.soft_feedback_events,
+            errors: r.errors.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AgentDigest {
+    pub position: Option<[f32; 3]>,
+    pub mercy_contribution: f32,
+    pub rbe_efficiency: f32,
+    pub active_mutations: u32,
+}
+
+/// Ordered snapshot of the world state touched by ticks and inputs.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WorldDigest {
+    pub sim_time: u64,
+    pub agents: BTreeMap<AgentId, AgentDigest>,
+    pub resource_nodes: BTreeMap<NodeId, ResourceNode>,
+    pub realm_presence: BTreeMap<AgentId, RealmId>,
+    /// (decision_id, remaining_ticks) in activation order
+    pub active_policies: Vec<(u64, u64)>,
+}
+
+// ============================================================================
+// SESSION (everything a run needs, owned in one place)
+// ============================================================================
+
+/// Deterministic harness around SimulationOrchestrator: the world, the council
+/// state run_tick reads, and the state external inputs act on.
+pub struct ReplaySession {
+    pub seed: u64,
+    pub scenario: String,
+    pub world: SovereignWorldState,
+    pub orchestrator: SimulationOrchestrator,
+    pub council: CouncilSessionManager,
+    pub decisions: CouncilDecisions,
+    pub realms: MultiRealmHarness,
+    pub presence: BTreeMap<AgentId, RealmPresence>,
+    pub legacy: LegacyJournalRegistry,
+}
+
+impl ReplaySession {
+    pub fn new(seed: u64, scenario: impl Into<String>, world: SovereignWorldState) -> Self {
+        let mut realms = MultiRealmHarness::new();
+        realms.seed_default_realms(0);
+        Self {
+            seed,
+            scenario: scenario.into(),
+            world,
+            orchestrator: SimulationOrchestrator::new(),
+            council: CouncilSessionManager::new(),
+            decisions: CouncilDecisions::default(),
+            realms,
+            presence: BTreeMap::new(),
+            legacy: LegacyJournalRegistry::default(),
+        }
+    }
+
+    pub fn header(&self) -> ReplayHeader {
+        let mut agents: Vec<Agent> = self.world.agents.values().cloned().collect();
+        agents.sort_by_key(|a| a.id);
+        let mut resource_nodes: Vec<ResourceNode> = self.world.resource_nodes.values().cloned().collect();
+        resource_nodes.sort_by_key(|n| n.id);
+        ReplayHeader {
+            format_version: REPLAY_FORMAT_VERSION,
+            seed: self.seed,
+            scenario: self.scenario.clone(),
+            start_sim_time: self.world.sim_time,
+            agents,
+            resource_nodes,
+        }
+    }
+
+    fn from_header(header: &ReplayHeader) -> Self {
+        let world = SovereignWorldState {
+            agents: header.agents.iter().map(|a| (a.id, a.clone())).collect(),
+            resource_nodes: header.resource_nodes.iter().map(|n| (n.id, n.clone())).collect(),
+            sim_time: header.start_sim_time,
+        };
+        Self::new(header.seed, header.scenario.clone(), world)
+    }
+
+    /// Apply this tick's inputs, then run one orchestrator tick.
+    /// Rejected inputs are appended to `TickResult::errors`.
+    pub fn step(&mut self, inputs: &[ReplayInput]) -> TickResult {
+        let tick = self.orchestrator.current_tick + 1;
+        let mut input_errors = Vec::new();
+        for input in inputs {
+            if let Err(e) = self.apply_input(input, tick) {
+                input_errors.push(e);
+            }
+        }
+
+        let mut result = self.orchestrator.run_tick(
+            &mut self.world,
+            None,
+            Some(&mut self.council),
+            None,
+            Some(&self.decisions),
+        );
+        result.errors.extend(input_errors);
+
+        for policy in self.decisions.active_policies.iter_mut() {
+            policy.tick();
+        }
+        self.decisions.clear_expired_policies();
+        result
+    }
+
+    fn apply_input(&mut self, input: &ReplayInput, tick: u64) -> Result<(), String> {
+        match input {
+            ReplayInput::Harvest { player_id, node_id, agent_mercy, behavioral_human_score } => {
+                self.orchestrator
+                    .harvesting_system
+                    .attempt_harvest(
+                        &mut self.world,
+                        *node_id,
+                        *agent_mercy,
+                        *behavioral_human_score,
+                        *player_id,
+                        self.council.active_bloom_field.as_ref(),
+                        &mut self.legacy,
+                        None,
+                    )
+                    .map(|_| ())
+                    .map_err(|e| format!("Harvest of node {} rejected: {}", node_id, e.reason))
+            }
+            ReplayInput::Proposal { proposal, mercy_factor, realm_id } => {
+                if proposal.status != ProposalStatus::Passed {
+                    return Ok(());
+                }
+                let decision = CouncilDecision::from_resolved_proposal(proposal, *mercy_factor, tick, *realm_id);
+                let policy = ActivePolicy::from_decision(&decision, policy_duration_ticks(&decision.proposal_type));
+                self.realms.record_decision_for_realm(&decision, policy.clone());
+                self.decisions.active_policies.push(policy);
+                self.decisions.push_resolved(decision);
+                Ok(())
+            }
+            ReplayInput::Vote { attunement, .. } => {
+                self.council.add_participant_attunement(*attunement);
+                Ok(())
+            }
+            ReplayInput::RealmTravel { agent_id, target_realm, .. } => {
+                let presence = self.presence.entry(*agent_id).or_default();
+                self.realms.ensure_realm_presence(presence, None);
+                if self.realms.travel_to_realm(presence, *target_realm, tick, *agent_id) {
+                    Ok(())
+                } else {
+                    Err(format!("Realm travel to unknown realm {} by agent {}", target_realm, agent_id))
+                }
+            }
+        }
+    }
+
+    pub fn world_digest(&self) -> WorldDigest {
+        WorldDigest {
+            sim_time: self.world.sim_time,
+            agents: self
+                .world
+                .agents
+                .iter()
+                .map(|(&id, a)| {
+                    (id, AgentDigest {
+                        position: a.position.map(|p| [p.x, p.y, p.z]),
+                        mercy_contribution: a.mercy_contribution,
+                        rbe_efficiency: a.rbe_efficiency,
+                        active_mutations: a.active_mutations.len() as u32,
+                    })
+                })
+                .collect(),
+            resource_nodes: self.world.resource_nodes.iter().map(|(&id, n)| (id, n.clone())).collect(),
+            realm_presence: self.presence.iter().map(|(&id, p)| (id, p.current_realm_id)).collect(),
+            active_policies: self
+                .decisions
+                .active_policies
+                .iter()
+                .map(|p| (p.decision_id, p.remaining_ticks))
+                .collect(),
+        }
+    }
+}
+
+// ============================================================================
+// FILE RECORDS
+// ============================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReplayHeader {
+    pub format_version: u32,
+    pub seed: u64,
+    pub scenario: String,
+    pub start_sim_time: u64,
+    pub agents: Vec<Agent>,
+    pub resource_nodes: Vec<ResourceNode>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    pub tick: u64,
+    pub inputs: Vec<ReplayInput>,
+    pub result: TickDigest,
+    pub world: WorldDigest,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ReplayRecord {
+    Header(ReplayHeader),
+    Frame(ReplayFrame),
+}
+
+fn write_record(writer: &mut BufWriter<File>, record: &ReplayRecord) -> io::Result<()> {
+    let json = serde_json::to_string(record).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    writeln!(writer, "{}|crc64:{:016x}", json, compute_crc64(&json))
+}
+
+// ============================================================================
+// RECORDER
+// ============================================================================
+
+pub struct ReplayRecorder {
+    writer: BufWriter<File>,
+    pending: Vec<ReplayInput>,
+}
+
+impl ReplayRecorder {
+    /// Create (or truncate) `path` and write the header for `session`'s current state.
+    pub fn create<P: AsRef<Path>>(path: P, session: &ReplaySession) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        write_record(&mut writer, &ReplayRecord::Header(session.header()))?;
+        writer.flush()?;
+        Ok(Self { writer, pending: Vec::new() })
+    }
+
+    /// Queue an input for the next recorded tick.
+    pub fn push_input(&mut self, input: ReplayInput) {
+        self.pending.push(input);
+    }
+
+    /// Step `session` with the queued inputs and append the frame.
+    pub fn record_tick(&mut self, session: &mut ReplaySession) -> io::Result<TickResult> {
+        let inputs = std::mem::take(&mut self.pending);
+        let result = session.step(&inputs);
+        let frame = ReplayFrame {
+            tick: result.tick,
+            inputs,
+            result: TickDigest::from(&result),
+            world: session.world_digest(),
+        };
+        write_record(&mut self.writer, &ReplayRecord::Frame(frame))?;
+        self.writer.flush()?;
+        Ok(result)
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        self.writer.get_ref().sync_all()
+    }
+}
+
+// ============================================================================
+// RUNNER
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    pub tick: u64,
+    /// Dotted path, e.g. `world.resource_nodes.10.depletion`
+    pub field: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "diverged at tick {} on {}: recorded {}, replayed {}",
+            self.tick, self.field, self.expected, self.actual
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ReplayReport {
+    pub ticks_replayed: u64,
+    pub divergence: Option<Divergence>,
+}
+
+impl ReplayReport {
+    pub fn is_clean(&self) -> bool {
+        self.divergence.is_none()
+    }
+}
+
+pub struct ReplayRunner {
+    pub header: ReplayHeader,
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl ReplayRunner {
+    /// Load a replay file. Any checksum mismatch or unparsable record is an error.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let invalid = |line: usize, msg: String| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("replay line {}: {}", line, msg))
+        };
+
+        let reader = BufReader::new(File::open(path)?);
+        let mut header = None;
+        let mut frames = Vec::new();
+
+        for (line_num, line_result) in reader.lines().enumerate() {
+            let line = line_result?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let pos = line.rfind("|crc64:").ok_or_else(|| invalid(line_num + 1, "missing checksum".into()))?;
+            let (json, checksum) = (&line[..pos], &line[pos + 7..]);
+            let expected = u64::from_str_radix(checksum, 16).map_err(|e| invalid(line_num + 1, e.to_string()))?;
+            if compute_crc64(json) != expected {
+                return Err(invalid(line_num + 1, "CRC64 mismatch".into()));
+            }
+
+            match serde_json::from_str(json).map_err(|e| invalid(line_num + 1, e.to_string()))? {
+                ReplayRecord::Header(h) if header.is_none() => {
+                    if h.format_version != REPLAY_FORMAT_VERSION {
+                        return Err(invalid(line_num + 1, format!("unsupported format v{}", h.format_version)));
+                    }
+                    header = Some(h);
+                }
+                ReplayRecord::Header(_) => return Err(invalid(line_num + 1, "duplicate header".into())),
+                ReplayRecord::Frame(_) if header.is_none() => {
+                    return Err(invalid(line_num + 1, "frame before header".into()))
+                }
+                ReplayRecord::Frame(f) => frames.push(f),
+            }
+        }
+
+        let header = header.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "replay file has no header"))?;
+        Ok(Self { header, frames })
+    }
+
+    /// Rebuild the session from the header and re-drive every recorded tick,
+    /// stopping at the first divergence.
+    pub fn run(&self) -> ReplayReport {
+        let mut session = ReplaySession::from_header(&self.header);
+        let mut report = ReplayReport::default();
+
+        for frame in &self.frames {
+            let result = session.step(&frame.inputs);
+            report.ticks_replayed += 1;
+
+            let divergence = first_difference("result", &to_value(&frame.result), &to_value(&TickDigest::from(&result)))
+                .or_else(|| first_difference("world", &to_value(&frame.world), &to_value(&session.world_digest())));
+            if let Some((field, expected, actual)) = divergence {
+                report.divergence = Some(Divergence { tick: frame.tick, field, expected, actual });
+                break;
+            }
+        }
+        report
+    }
+}
+
+/// Both sides go through the same f32 → JSON conversion, so equal floats compare equal.
+fn to_value<T: Serialize>(v: &T) -> Value {
+    serde_json::to_value(v).unwrap_or(Value::Null)
+}
+
+fn first_difference(path: &str, expected: &Value, actual: &Value) -> Option<(String, String, String)> {
+    match (expected, actual) {
+        (Value::Object(e), Value::Object(a)) => {
+            let keys: std::collections::BTreeSet<&String> = e.keys().chain(a.keys()).collect();
+            keys.into_iter().find_map(|k| {
+                let child = format!("{}.{}", path, k);
+                first_difference(&child, e.get(k).unwrap_or(&Value::Null), a.get(k).unwrap_or(&Value::Null))
+            })
+        }
+        (Value::Array(e), Value::Array(a)) => e
+            .iter()
+            .zip(a.iter())
+            .enumerate()
+            .find_map(|(i, (ev, av))| first_difference(&format!("{}[{}]", path, i), ev, av))
+            .or_else(|| {
+                (e.len() != a.len()).then(|| (format!("{}.len", path), e.len().to_string(), a.len().to_string()))
+            }),
+        _ if expected == actual => None,
+        _ => Some((path.to_string(), expected.to_string(), actual.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::council::proposal::ProposalType;
+
+    fn seeded_session() -> ReplaySession {
+        let mut world = SovereignWorldState::default();
+        world.agents.insert(1, Agent::new(1, "Aster"));
+        world.agents.insert(2, Agent::new(2, "Bram"));
+        world.resource_nodes.insert(10, ResourceNode {
+            id: 10,
+            base_yield: 2.5,
+            current_yield: 2.5,
+            ..Default::default()
+        });
+        ReplaySession::new(1337, "LongTermRbeStability", world)
+    }
+
+    fn record_run(path: &Path) {
+        let mut session = seeded_session();
+        let mut recorder = ReplayRecorder::create(path, &session).expect("create replay");
+        for tick in 1..=6u64 {
+            if tick == 2 {
+                let mut proposal = CouncilProposal::new(7, ProposalType::ResourcePolicy, "Cap".into(), "t".into(), 1, tick);
+                proposal.status = ProposalStatus::Passed;
+                recorder.push_input(ReplayInput::Proposal { proposal, mercy_factor: 0.8, realm_id: 2 });
+            }
+            if tick == 3 {
+                for voter in 1..=3 {
+                    recorder.push_input(ReplayInput::Vote { voter, attunement: 0.9 });
+                }
+                recorder.push_input(ReplayInput::RealmTravel { agent_id: 1, target_realm: 2, reason: "bloom".into() });
+            }
+            recorder.record_tick(&mut session).expect("record tick");
+        }
+        recorder.finish().expect("finish");
+    }
+
+    #[test]
+    fn recorded_run_replays_cleanly() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("clean.jsonl");
+        record_run(&path);
+        let runner = ReplayRunner::from_file(&path).expect("load replay");
+        assert_eq!(runner.header.seed, 1337);
+        assert_eq!(runner.frames.len(), 6);
+
+        let report = runner.run();
+        assert!(report.is_clean(), "{:?}", report.divergence);
+        assert_eq!(report.ticks_replayed, 6);
+    }
+
+    #[test]
+    fn first_divergent_tick_and_field_are_reported() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("diverge.jsonl");
+        record_run(&path);
+        let mut runner = ReplayRunner::from_file(&path).expect("load replay");
+        runner.frames[3].world.sim_time += 1;
+        runner.frames[4].result.council_participant_count += 1;
+
+        let report = runner.run();
+        let divergence = report.divergence.expect("divergence");
+        assert_eq!(divergence.tick, 4);
+        assert_eq!(divergence.field, "world.sim_time");
+        assert_eq!(report.ticks_replayed, 4);
+    }
+
+    #[test]
+    fn corrupted_record_is_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("corrupt.jsonl");
+        record_run(&path);
+        let contents = std::fs::read_to_string(&path).unwrap().replacen("\"tick\":2", "\"tick\":9", 1);
+        std::fs::write(&path, contents).unwrap();
+        assert!(ReplayRunner::from_file(&path).is_err());
+    }
+}
+
+// Thunder locked in. Yoi ⚡
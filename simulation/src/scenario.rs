@@ -1,19 +1,30 @@
 /*!
  * Scenario System — Declarative Configuration & Presets
- * 
+ *
  * Pure config-driven scenario definition for repeatable, deterministic "what-if" experiments.
- * 
+ *
  * Supports time acceleration (1x–10,000x+), population scaling (100–50,000+ agents),
  * entropy/griefing profiles, faction diplomacy seeds, PATSAGi policy variants,
  * and archetype evolution under different abundance/pressure conditions.
- * 
+ *
  * Presets are ready for immediate closed-beta validation and council deliberation.
+ *
+ * v21.92.1: Scenario files (RON). Designers author world templates, population,
+ * scripted inputs at given ticks and expected-outcome assertions without touching
+ * Rust; `harness --scenario path.ron` runs them through the replay session, so any
+ * scenario run can also be recorded and replayed.
  */
 
-use crate::world::{ScenarioConfig, EntropyProfile, ResourceTemplate, FactionTemplate, ArchetypeTemplate};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::multi_realm_harness::RealmId;
+use crate::replay::{ReplayInput, ReplayRecorder, ReplaySession};
+use crate::world::{ScenarioConfig, EntropyProfile, ResourceTemplate, FactionTemplate, ArchetypeTemplate, SovereignWorldState};
 
 /// High-level scenario presets for common RBE validation experiments.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ScenarioPreset {
     /// Long-term RBE stability under normal cooperation (decades in minutes)
     LongTermRbeStability,
@@ -28,38 +39,357 @@ pub enum ScenarioPreset {
 }
 
 impl ScenarioPreset {
-    /// Build a full ScenarioConfig from a preset + overrides.
-    pub fn to_config(&self, global_seed: u64, time_acceleration: f32) -> ScenarioConfig {
+    /// Build a full ScenarioConfig from a preset + overrides. The config is seed-free;
+    /// the seed is applied in SovereignWorldState::new_from_scenario / spawn_population.
+    pub fn to_config(&self, time_acceleration: f32) -> ScenarioConfig {
+        let harvester = || ArchetypeTemplate { id: 1, name: "BalancedHarvester".to_string() };
+        let node = |id, base_yield, regen_rate, count| ResourceTemplate {
+            id,
+            base_yield,
+            regen_rate,
+            count,
+            resource_type: "verdant_essence".to_string(),
+        };
         match self {
             ScenarioPreset::LongTermRbeStability => ScenarioConfig {
                 start_time: 0,
-                resource_templates: vec![ /* realistic starting nodes */ ResourceTemplate { id: 1, base_yield: 2.5, regen_rate: 0.015 } ],
-                faction_templates: vec![ /* balanced factions */ FactionTemplate { faction_id: 1 } ],
-                archetype_templates: vec![ /* initial archetypes from dynamic_archetype_balance_sim.py elevation */ ArchetypeTemplate { id: 1, name: "BalancedHarvester".to_string() } ],
+                resource_templates: vec![node(1, 2.5, 0.015, 24)],
+                faction_templates: vec![FactionTemplate::new(1), FactionTemplate::new(2)],
+                archetype_templates: vec![harvester()],
                 time_acceleration,
-                entropy_profile: EntropyProfile { /* low entropy, high cooperation seed */ },
+                entropy_profile: EntropyProfile { grief_intensity: 0.05, cooperation_seed: 0.8 },
             },
             ScenarioPreset::HighGriefStressTest => ScenarioConfig {
                 start_time: 0,
-                resource_templates: vec![ ResourceTemplate { id: 1, base_yield: 2.5, regen_rate: 0.015 } ],
-                faction_templates: vec![ FactionTemplate { faction_id: 1 } ],
-                archetype_templates: vec![ ArchetypeTemplate { id: 1, name: "BalancedHarvester".to_string() } ],
+                resource_templates: vec![node(1, 2.5, 0.015, 12)],
+                faction_templates: vec![FactionTemplate::new(1), FactionTemplate::new(2)],
+                archetype_templates: vec![harvester()],
+                time_acceleration,
+                entropy_profile: EntropyProfile { grief_intensity: 0.7, cooperation_seed: 0.4 },
+            },
+            ScenarioPreset::ArchetypeEvolutionUnderAbundance => ScenarioConfig {
+                start_time: 0,
+                resource_templates: vec![node(1, 4.0, 0.03, 32)],
+                faction_templates: vec![FactionTemplate::new(1)],
+                archetype_templates: vec![
+                    harvester(),
+                    ArchetypeTemplate { id: 2, name: "RegenWarden".to_string() },
+                    ArchetypeTemplate { id: 3, name: "CouncilWeaver".to_string() },
+                ],
                 time_acceleration,
-                entropy_profile: EntropyProfile { /* high griefing intensity seed */ },
+                entropy_profile: EntropyProfile { grief_intensity: 0.02, cooperation_seed: 0.9 },
             },
-            _ => ScenarioConfig {
+            ScenarioPreset::ServerWarSimulation => ScenarioConfig {
+                start_time: 0,
+                resource_templates: vec![node(1, 2.0, 0.01, 16)],
+                faction_templates: vec![
+                    FactionTemplate::new(1),
+                    FactionTemplate::new(2),
+                    FactionTemplate::new(3),
+                ],
+                archetype_templates: vec![harvester()],
+                time_acceleration,
+                entropy_profile: EntropyProfile { grief_intensity: 0.5, cooperation_seed: 0.5 },
+            },
+            ScenarioPreset::Custom => ScenarioConfig {
                 start_time: 0,
                 resource_templates: vec![],
                 faction_templates: vec![],
                 archetype_templates: vec![],
                 time_acceleration,
-                entropy_profile: EntropyProfile {},
+                entropy_profile: EntropyProfile::default(),
             },
         }
     }
 }
 
-// Note: Full ScenarioConfig builder, YAML/JSON deserialization (serde), and more presets
-// will be expanded in subsequent sequential passes. Current implementation is already
-// production-grade for early deterministic harness runs and closed-beta validation.
-// All presets respect TOLC 8 and preserve mercy-gated dynamics.
+// ============================================================================
+// SCENARIO FILES
+// ============================================================================
+
+/// An external input fired at the start of `tick` (1-based, as in TickResult::tick).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduledInput {
+    pub tick: u64,
+    pub input: ReplayInput,
+}
+
+/// Assertions checked once the scenario has run all its ticks.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ScenarioExpectation {
+    /// Mean TickResult::estimated_mercy_flow over the run
+    MinMeanMercyFlow(f32),
+    /// Total TickResult errors, including rejected inputs
+    MaxErrors(u32),
+    /// Ticks on which a council bloom was applied
+    MinCouncilBlooms(u32),
+    /// Mean resource node depletion at the end of the run
+    MaxMeanDepletion(f32),
+    MinActivePolicies(u32),
+    MinAgentsInRealm { realm: RealmId, count: u32 },
+}
+
+/// A complete, file-authored experiment.
+///
+/// ```ron
+/// (
+///     name: "Verdant scarcity",
+///     seed: 7,
+///     ticks: 120,
+///     population: 40,
+///     config: (
+///         start_time: 0,
+///         time_acceleration: 60.0,
+///         resource_templates: [(id: 1, base_yield: 1.5, regen_rate: 0.01, count: 8)],
+///         archetype_templates: [(id: 1, name: "BalancedHarvester")],
+///         entropy_profile: (grief_intensity: 0.3, cooperation_seed: 0.6),
+///     ),
+///     events: [(tick: 10, input: Vote(voter: 1, attunement: 0.9))],
+///     expect: [MaxErrors(0)],
+/// )
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub seed: u64,
+    pub ticks: u64,
+    #[serde(default)]
+    pub population: u32,
+    pub config: ScenarioConfig,
+    #[serde(default)]
+    pub events: Vec<ScheduledInput>,
+    #[serde(default)]
+    pub expect: Vec<ScenarioExpectation>,
+}
+
+impl Scenario {
+    pub fn from_ron_str(source: &str) -> Result<Self, String> {
+        let scenario: Scenario = ron::from_str(source).map_err(|e| format!("Invalid scenario: {}", e))?;
+        scenario.config.validate()?;
+        if let Some(late) = scenario.events.iter().find(|e| e.tick == 0 || e.tick > scenario.ticks) {
+            return Err(format!("Scripted event at tick {} is outside 1..={}", late.tick, scenario.ticks));
+        }
+        Ok(scenario)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read scenario {}: {}", path.display(), e))?;
+        Self::from_ron_str(&source)
+    }
+
+    pub fn from_preset(preset: ScenarioPreset, seed: u64, ticks: u64, population: u32) -> Self {
+        Self {
+            name: format!("{:?}", preset),
+            seed,
+            ticks,
+            population,
+            config: preset.to_config(1.0),
+            events: Vec::new(),
+            expect: Vec::new(),
+        }
+    }
+
+    pub fn build_session(&self) -> Result<ReplaySession, String> {
+        let mut world = SovereignWorldState::new_from_scenario(&self.config, self.seed)?;
+        world.spawn_population(self.population, &self.config, self.seed);
+        Ok(ReplaySession::new(self.seed, self.name.clone(), world))
+    }
+}
+
+// ============================================================================
+// RUNNER
+// ============================================================================
+
+#[derive(Clone, Debug, Default)]
+pub struct ScenarioOutcome {
+    pub scenario: String,
+    pub ticks_run: u64,
+    pub mean_mercy_flow: f32,
+    pub total_errors: u32,
+    pub council_blooms: u32,
+    pub mean_depletion: f32,
+    pub active_policies: u32,
+    /// One line per expectation that did not hold
+    pub failures: Vec<String>,
+}
+
+impl ScenarioOutcome {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+pub struct ScenarioRunner {
+    pub scenario: Scenario,
+}
+
+impl ScenarioRunner {
+    pub fn new(scenario: Scenario) -> Self {
+        Self { scenario }
+    }
+
+    pub fn run(&self) -> Result<ScenarioOutcome, String> {
+        self.run_inner(None)
+    }
+
+    /// Run and write every tick to a replay file (see replay.rs).
+    pub fn run_recorded<P: AsRef<Path>>(&self, replay_path: P) -> Result<ScenarioOutcome, String> {
+        self.run_inner(Some(replay_path.as_ref()))
+    }
+
+    fn run_inner(&self, replay_path: Option<&Path>) -> Result<ScenarioOutcome, String> {
+        let mut session = self.scenario.build_session()?;
+        let mut recorder = match replay_path {
+            Some(path) => Some(
+                ReplayRecorder::create(path, &session)
+                    .map_err(|e| format!("Failed to create replay {}: {}", path.display(), e))?,
+            ),
+            None => None,
+        };
+
+        let mut outcome = ScenarioOutcome { scenario: self.scenario.name.clone(), ..Default::default() };
+        let mut mercy_sum = 0.0_f32;
+
+        for tick in 1..=self.scenario.ticks {
+            let inputs: Vec<ReplayInput> = self
+                .scenario
+                .events
+                .iter()
+                .filter(|e| e.tick == tick)
+                .map(|e| e.input.clone())
+                .collect();
+
+            let result = match recorder.as_mut() {
+                Some(rec) => {
+                    for input in inputs {
+                        rec.push_input(input);
+                    }
+                    rec.record_tick(&mut session).map_err(|e| format!("Replay write failed: {}", e))?
+                }
+                None => session.step(&inputs),
+            };
+
+            outcome.ticks_run += 1;
+            mercy_sum += result.estimated_mercy_flow();
+            outcome.total_errors += result.errors.len() as u32;
+            outcome.council_blooms += result.council_decisions_applied;
+        }
+
+        if let Some(rec) = recorder {
+            rec.finish().map_err(|e| format!("Replay flush failed: {}", e))?;
+        }
+
+        outcome.mean_mercy_flow = mercy_sum / outcome.ticks_run.max(1) as f32;
+        let nodes = &session.world.resource_nodes;
+        outcome.mean_depletion = if nodes.is_empty() {
+            0.0
+        } else {
+            nodes.values().map(|n| n.depletion).sum::<f32>() / nodes.len() as f32
+        };
+        outcome.active_policies = session.decisions.active_policies.len() as u32;
+
+        for expectation in &self.scenario.expect {
+            if let Some(failure) = check_expectation(expectation, &outcome, &session) {
+                outcome.failures.push(failure);
+            }
+        }
+        Ok(outcome)
+    }
+}
+
+fn check_expectation(
+    expectation: &ScenarioExpectation,
+    outcome: &ScenarioOutcome,
+    session: &ReplaySession,
+) -> Option<String> {
+    match *expectation {
+        ScenarioExpectation::MinMeanMercyFlow(min) if outcome.mean_mercy_flow < min => {
+            Some(format!("mean mercy flow {:.3} < {:.3}", outcome.mean_mercy_flow, min))
+        }
+        ScenarioExpectation::MaxErrors(max) if outcome.total_errors > max => {
+            Some(format!("{} errors > {}", outcome.total_errors, max))
+        }
+        ScenarioExpectation::MinCouncilBlooms(min) if outcome.council_blooms < min => {
+            Some(format!("{} council blooms < {}", outcome.council_blooms, min))
+        }
+        ScenarioExpectation::MaxMeanDepletion(max) if outcome.mean_depletion > max => {
+            Some(format!("mean depletion {:.3} > {:.3}", outcome.mean_depletion, max))
+        }
+        ScenarioExpectation::MinActivePolicies(min) if outcome.active_policies < min => {
+            Some(format!("{} active policies < {}", outcome.active_policies, min))
+        }
+        ScenarioExpectation::MinAgentsInRealm { realm, count } => {
+            let present = session.presence.values().filter(|p| p.current_realm_id == realm).count() as u32;
+            (present < count).then(|| format!("{} agents in realm {} < {}", present, realm, count))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERDANT: &str = include_str!("../scenarios/verdant_scarcity.ron");
+
+    #[test]
+    fn bundled_scenario_parses_and_runs() {
+        let scenario = Scenario::from_ron_str(VERDANT).expect("parse scenario");
+        assert_eq!(scenario.population, 40);
+        let outcome = ScenarioRunner::new(scenario).run().expect("run scenario");
+        assert_eq!(outcome.ticks_run, 120);
+        assert!(outcome.passed(), "{:?}", outcome.failures);
+    }
+
+    #[test]
+    fn failed_expectations_are_reported() {
+        let mut scenario = Scenario::from_ron_str(VERDANT).expect("parse scenario");
+        scenario.expect = vec![ScenarioExpectation::MinAgentsInRealm { realm: 4, count: 1 }];
+        let outcome = ScenarioRunner::new(scenario).run().expect("run scenario");
+        assert_eq!(outcome.failures, vec!["0 agents in realm 4 < 1".to_string()]);
+    }
+
+    #[test]
+    fn faction_templates_split_the_population_and_bias_mercy() {
+        let mut config = ScenarioPreset::LongTermRbeStability.to_config(60.0);
+        config.entropy_profile = EntropyProfile { grief_intensity: 0.0, cooperation_seed: 0.5 };
+        config.faction_templates = vec![
+            FactionTemplate { faction_id: 1, share: 3.0, cooperation_bias: 0.2 },
+            FactionTemplate { faction_id: 2, share: 1.0, cooperation_bias: -0.2 },
+        ];
+        let mut world = SovereignWorldState::new_from_scenario(&config, 9).unwrap();
+        world.spawn_population(40, &config, 9);
+
+        let members = |faction| world.agents.values().filter(|a| a.faction_id == Some(faction)).count();
+        assert_eq!((members(1), members(2)), (30, 10));
+        for agent in world.agents.values() {
+            let expected = if agent.faction_id == Some(1) { 0.7 } else { 0.3 };
+            assert!((agent.mercy_contribution - expected).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn non_finite_and_duplicate_templates_are_rejected() {
+        let mut config = ScenarioPreset::LongTermRbeStability.to_config(60.0);
+        config.resource_templates[0].base_yield = f32::NAN;
+        assert!(config.validate().is_err());
+
+        let mut config = ScenarioPreset::LongTermRbeStability.to_config(60.0);
+        config.faction_templates.push(FactionTemplate::new(1));
+        assert!(config.validate().is_err());
+        config.faction_templates = vec![FactionTemplate { faction_id: 1, share: f32::NAN, cooperation_bias: 0.0 }];
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn events_outside_the_run_are_rejected() {
+        let source = VERDANT.replace("(tick: 90,", "(tick: 500,");
+        assert!(Scenario::from_ron_str(&source).is_err());
+    }
+}
+
+// All presets and scenario files respect TOLC 8 and preserve mercy-gated dynamics.
+// Thunder locked in. Yoi ⚡
@@ -15,6 +15,8 @@
 use bevy::prelude::*;
 use bevy::render::texture::{Image, ImageSampler};
 use bevy_hanabi::prelude::*;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::effects::{frame, modulation, types};
@@ -39,6 +41,9 @@ pub struct Agent {
     pub mercy_contribution: f32,
     pub rbe_efficiency: f32,
     pub archetype_id: Option<ArchetypeId>,
+    /// Faction the agent was spawned into (see FactionTemplate)
+    #[serde(default)]
+    pub faction_id: Option<u32>,
 }
 
 impl Agent {
@@ -54,6 +59,7 @@ impl Agent {
             mercy_contribution: 0.0,
             rbe_efficiency: 0.5,
             archetype_id: None,
+            faction_id: None,
         }
     }
 
@@ -103,12 +109,195 @@ pub type NodeId = u64;
 pub type Vec3 = bevy::math::Vec3;
 pub type ArchetypeId = u64;
 
+// ============================================================================
+// SCENARIO CONFIG (world-shaping part of a scenario; see scenario.rs for files)
+// ============================================================================
+
+/// Side length of the square nodes and agents are scattered over.
+const SCENARIO_WORLD_EXTENT: f32 = 512.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceTemplate {
+    pub id: u64,
+    pub base_yield: f32,
+    pub regen_rate: f32,
+    /// Nodes spawned from this template
+    #[serde(default = "default_node_count")]
+    pub count: u32,
+    #[serde(default = "default_resource_type")]
+    pub resource_type: String,
+}
+
+fn default_node_count() -> u32 {
+    1
+}
+
+fn default_resource_type() -> String {
+    "verdant_essence".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FactionTemplate {
+    pub faction_id: u32,
+    /// Relative share of the spawned population (any positive weight)
+    #[serde(default = "default_faction_share")]
+    pub share: f32,
+    /// Added to each member's starting mercy (-1.0..=1.0)
+    #[serde(default)]
+    pub cooperation_bias: f32,
+}
+
+impl FactionTemplate {
+    pub fn new(faction_id: u32) -> Self {
+        Self { faction_id, share: default_faction_share(), cooperation_bias: 0.0 }
+    }
+}
+
+fn default_faction_share() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchetypeTemplate {
+    pub id: ArchetypeId,
+    pub name: String,
+}
+
+/// Griefing pressure vs. cooperative baseline for spawned agents (both 0.0–1.0).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntropyProfile {
+    pub grief_intensity: f32,
+    pub cooperation_seed: f32,
+}
+
+impl Default for EntropyProfile {
+    fn default() -> Self {
+        Self { grief_intensity: 0.1, cooperation_seed: 0.7 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioConfig {
+    pub start_time: u64,
+    pub resource_templates: Vec<ResourceTemplate>,
+    #[serde(default)]
+    pub faction_templates: Vec<FactionTemplate>,
+    #[serde(default)]
+    pub archetype_templates: Vec<ArchetypeTemplate>,
+    pub time_acceleration: f32,
+    #[serde(default)]
+    pub entropy_profile: EntropyProfile,
+}
+
+impl ScenarioConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.time_acceleration.is_finite() && self.time_acceleration > 0.0) {
+            return Err(format!("time_acceleration must be > 0 (got {})", self.time_acceleration));
+        }
+        for t in &self.resource_templates {
+            if !(t.base_yield.is_finite() && t.base_yield >= 0.0) || !(0.0..=1.0).contains(&t.regen_rate) {
+                return Err(format!("resource template {} needs base_yield >= 0 and regen_rate in 0..=1", t.id));
+            }
+        }
+        for (i, f) in self.faction_templates.iter().enumerate() {
+            if !(f.share.is_finite() && f.share > 0.0) || !(-1.0..=1.0).contains(&f.cooperation_bias) {
+                return Err(format!("faction template {} needs share > 0 and cooperation_bias in -1..=1", f.faction_id));
+            }
+            if self.faction_templates[..i].iter().any(|other| other.faction_id == f.faction_id) {
+                return Err(format!("faction {} is defined twice", f.faction_id));
+            }
+        }
+        let e = &self.entropy_profile;
+        if !(0.0..=1.0).contains(&e.grief_intensity) || !(0.0..=1.0).contains(&e.cooperation_seed) {
+            return Err("entropy_profile values must be in 0..=1".to_string());
+        }
+        Ok(())
+    }
+}
+
 // ============================================================================
 // ACCELERATED RAYCAST LOS HELPERS (wired for Council, Events, Agent Perception)
 // Uses the spatially accelerated HierarchicalGrid::raycast_distance
 // ============================================================================
 
 impl SovereignWorldState {
+    /// Build the starting world for a scenario. Node placement is derived from `seed`,
+    /// so the same config + seed always yields the same world.
+    pub fn new_from_scenario(config: &ScenarioConfig, seed: u64) -> Result<Self, String> {
+        config.validate()?;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let mut world = Self { sim_time: config.start_time, ..Default::default() };
+
+        let mut next_id: NodeId = 1;
+        for template in &config.resource_templates {
+            for _ in 0..template.count {
+                world.resource_nodes.insert(next_id, ResourceNode {
+                    id: next_id,
+                    position: (
+                        rng.gen_range(0.0..SCENARIO_WORLD_EXTENT),
+                        0.0,
+                        rng.gen_range(0.0..SCENARIO_WORLD_EXTENT),
+                    ),
+                    resource_type: template.resource_type.clone(),
+                    base_yield: template.base_yield,
+                    current_yield: template.base_yield,
+                    regeneration_rate: template.regen_rate,
+                    sustainability_score: 1.0,
+                    ..Default::default()
+                });
+                next_id += 1;
+            }
+        }
+        Ok(world)
+    }
+
+    /// Spawn `count` agents round-robin over the archetype templates and split them over
+    /// the faction templates by share (weighted round-robin, so the split is exact up to
+    /// rounding and independent of the seed). Starting mercy is the entropy profile's
+    /// cooperation seed minus a seeded share of grief, plus the faction's cooperation bias.
+    pub fn spawn_population(&mut self, count: u32, config: &ScenarioConfig, seed: u64) {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed ^ 0xA11C_E5ED);
+        let entropy = &config.entropy_profile;
+        let first_id = self.agents.keys().max().copied().unwrap_or(0) + 1;
+        let mut faction_members = vec![0u32; config.faction_templates.len()];
+
+        for i in 0..count as u64 {
+            let id = first_id + i;
+            let archetype = config
+                .archetype_templates
+                .get(i as usize % config.archetype_templates.len().max(1));
+            let name = match archetype {
+                Some(a) => format!("{}-{}", a.name, id),
+                None => format!("Agent-{}", id),
+            };
+            let mut agent = Agent::new(id, name);
+            agent.archetype_id = archetype.map(|a| a.id);
+            agent.position = Some(Vec3::new(
+                rng.gen_range(0.0..SCENARIO_WORLD_EXTENT),
+                0.0,
+                rng.gen_range(0.0..SCENARIO_WORLD_EXTENT),
+            ));
+            // Next member goes to the faction furthest below its share.
+            let faction = config
+                .faction_templates
+                .iter()
+                .zip(&faction_members)
+                .enumerate()
+                .min_by(|(_, (a, na)), (_, (b, nb))| {
+                    ((**na + 1) as f32 / a.share).total_cmp(&((**nb + 1) as f32 / b.share))
+                })
+                .map(|(k, (template, _))| (k, template));
+            if let Some((k, _)) = faction {
+                faction_members[k] += 1;
+            }
+            agent.faction_id = faction.map(|(_, f)| f.faction_id);
+            let bias = faction.map_or(0.0, |(_, f)| f.cooperation_bias);
+            agent.mercy_contribution =
+                (entropy.cooperation_seed - entropy.grief_intensity * rng.gen::<f32>() + bias).clamp(0.0, 1.0);
+            self.agents.insert(id, agent);
+        }
+    }
+
     /// Returns true if there is a clear line of sight between two positions.
     /// Now powered by accelerated hierarchical raycast (coarse-to-fine + early termination).
     pub fn has_line_of_sight(&self, grid: &HierarchicalGrid, from: Vec3, to: Vec3, max_dist: f32) -> bool {
@@ -1,3 +1,8 @@
+// src/crafting.rs — legacy replicon prototype (not part of the client or server build).
+// Crafting is server-authoritative: recipes in assets/recipes (shared::crafting), queued
+// jobs in server/src/crafting, requested with ClientMessage::CraftStart / CraftPlanRequest.
+// This local two-item merge is kept only as a reference for the old prototype.
+
 use bevy::prelude::*;
 use bevy_replicon::prelude::*;
 